@@ -0,0 +1,4 @@
+fn main() {
+    // Re-embed migrations when a new file is added to ./migrations
+    println!("cargo:rerun-if-changed=migrations");
+}
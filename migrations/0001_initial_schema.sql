@@ -0,0 +1,91 @@
+-- Initial schema. Uses IF NOT EXISTS so databases created before versioned
+-- migrations were introduced are adopted without data loss.
+
+CREATE TABLE IF NOT EXISTS symbols (
+    id TEXT PRIMARY KEY,
+    symbol TEXT UNIQUE NOT NULL,
+    name TEXT,
+    exchange TEXT,
+    sector TEXT,
+    industry TEXT,
+    market_cap TEXT, -- Decimal stored as TEXT
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS historical_prices (
+    id TEXT PRIMARY KEY,
+    symbol_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    open TEXT NOT NULL, -- Decimal stored as TEXT
+    high TEXT NOT NULL,
+    low TEXT NOT NULL,
+    close TEXT NOT NULL,
+    adjusted_close TEXT,
+    volume INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (symbol_id) REFERENCES symbols (id)
+);
+
+CREATE TABLE IF NOT EXISTS realtime_quotes (
+    id TEXT PRIMARY KEY,
+    symbol_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    price TEXT NOT NULL, -- Decimal stored as TEXT
+    change TEXT,
+    change_percent TEXT,
+    volume INTEGER,
+    market_time TEXT NOT NULL,
+    trading_session TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (symbol_id) REFERENCES symbols (id)
+);
+
+CREATE TABLE IF NOT EXISTS company_profiles (
+    id TEXT PRIMARY KEY,
+    symbol_id TEXT NOT NULL,
+    symbol TEXT UNIQUE NOT NULL,
+    company_name TEXT,
+    description TEXT,
+    sector TEXT,
+    industry TEXT,
+    employees INTEGER,
+    website TEXT,
+    address TEXT,
+    city TEXT,
+    state TEXT,
+    country TEXT,
+    zip_code TEXT,
+    phone TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (symbol_id) REFERENCES symbols (id)
+);
+
+CREATE TABLE IF NOT EXISTS portfolio_holdings (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    symbol_id TEXT,
+    asset_type TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    purchase_price TEXT NOT NULL,
+    current_price TEXT,
+    current_value TEXT,
+    gain_loss TEXT,
+    gain_loss_percent TEXT,
+    last_updated TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (symbol_id) REFERENCES symbols (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbols_symbol ON symbols (symbol);
+CREATE INDEX IF NOT EXISTS idx_historical_prices_symbol ON historical_prices (symbol);
+CREATE INDEX IF NOT EXISTS idx_historical_prices_timestamp ON historical_prices (timestamp);
+CREATE INDEX IF NOT EXISTS idx_historical_prices_symbol_timestamp ON historical_prices (symbol, timestamp);
+CREATE INDEX IF NOT EXISTS idx_realtime_quotes_symbol ON realtime_quotes (symbol);
+CREATE INDEX IF NOT EXISTS idx_realtime_quotes_market_time ON realtime_quotes (market_time);
+CREATE INDEX IF NOT EXISTS idx_company_profiles_symbol ON company_profiles (symbol);
+CREATE INDEX IF NOT EXISTS idx_portfolio_holdings_symbol ON portfolio_holdings (symbol);
+CREATE INDEX IF NOT EXISTS idx_portfolio_holdings_asset_type ON portfolio_holdings (asset_type);
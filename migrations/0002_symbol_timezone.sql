@@ -0,0 +1,3 @@
+-- Exchange timezone metadata so daily candles can be aligned to the exchange's trading day
+ALTER TABLE symbols ADD COLUMN timezone TEXT;
+ALTER TABLE symbols ADD COLUMN utc_offset_seconds INTEGER;
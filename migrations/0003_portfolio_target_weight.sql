@@ -0,0 +1,2 @@
+-- Target allocation per holding, as a percentage of total portfolio value
+ALTER TABLE portfolio_holdings ADD COLUMN target_weight TEXT;
@@ -0,0 +1,13 @@
+-- Dividend events recorded alongside daily price history
+CREATE TABLE IF NOT EXISTS dividends (
+    id TEXT PRIMARY KEY,
+    symbol_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    ex_date TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (symbol_id) REFERENCES symbols (id),
+    UNIQUE(symbol, ex_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dividends_symbol_ex_date ON dividends (symbol, ex_date);
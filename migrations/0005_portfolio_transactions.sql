@@ -0,0 +1,15 @@
+-- Buy/sell ledger used for tax lot matching
+CREATE TABLE IF NOT EXISTS portfolio_transactions (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('buy', 'sell')),
+    quantity TEXT NOT NULL,
+    price TEXT NOT NULL,
+    fees TEXT NOT NULL DEFAULT '0',
+    trade_date TEXT NOT NULL,
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_transactions_symbol_date
+    ON portfolio_transactions (symbol, trade_date);
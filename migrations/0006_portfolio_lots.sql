@@ -0,0 +1,28 @@
+-- Individual purchase lots behind each holding, and per-portfolio settings
+CREATE TABLE IF NOT EXISTS portfolio_lots (
+    id TEXT PRIMARY KEY,
+    holding_id TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    purchase_price TEXT NOT NULL,
+    acquired_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (holding_id) REFERENCES portfolio_holdings (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_lots_holding ON portfolio_lots (holding_id);
+
+CREATE TABLE IF NOT EXISTS portfolio_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Existing holdings start with a single lot at their average cost
+INSERT INTO portfolio_lots (id, holding_id, quantity, purchase_price, acquired_at, created_at)
+SELECT
+    lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+        substr(lower(hex(randomblob(2))), 2) || '-' ||
+        substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2) || '-' ||
+        lower(hex(randomblob(6))),
+    id, quantity, purchase_price, created_at, created_at
+FROM portfolio_holdings;
@@ -0,0 +1,11 @@
+-- External cash movements into and out of the portfolio
+CREATE TABLE IF NOT EXISTS cash_flows (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('deposit', 'withdrawal')),
+    amount TEXT NOT NULL,
+    flow_date TEXT NOT NULL,
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cash_flows_flow_date ON cash_flows (flow_date);
@@ -0,0 +1,12 @@
+-- Server-side admin sessions so signed session cookies can be revoked before expiry
+CREATE TABLE IF NOT EXISTS admin_sessions (
+    id TEXT PRIMARY KEY,
+    method TEXT NOT NULL CHECK (method IN ('tezos', 'oidc')),
+    subject TEXT NOT NULL,
+    user_agent TEXT,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_sessions_expires_at ON admin_sessions (expires_at);
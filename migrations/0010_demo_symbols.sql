@@ -0,0 +1,7 @@
+-- Symbols seeded from the bundled sample data in DEMO_MODE. Rows are kept after a wipe
+-- (wiped_at set) so the sample data is only loaded on the very first run.
+CREATE TABLE IF NOT EXISTS demo_symbols (
+    symbol TEXT PRIMARY KEY,
+    seeded_at TEXT NOT NULL,
+    wiped_at TEXT
+);
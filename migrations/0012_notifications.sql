@@ -0,0 +1,13 @@
+-- Every email/Telegram notification attempt, kept for debugging missed alerts
+CREATE TABLE IF NOT EXISTS notifications (
+    id TEXT PRIMARY KEY,
+    channel TEXT NOT NULL,
+    source TEXT NOT NULL, -- what triggered it, e.g. "test" or "alert:<id>"
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    error TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_created ON notifications (created_at);
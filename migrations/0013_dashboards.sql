@@ -0,0 +1,13 @@
+-- Saved dashboard layouts; each owner (signed-in admin, or "public" without auth) has their own
+CREATE TABLE IF NOT EXISTS dashboards (
+    id TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    widgets TEXT NOT NULL, -- JSON array of widgets
+    is_default INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (owner, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dashboards_owner ON dashboards (owner, name);
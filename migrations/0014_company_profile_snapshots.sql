@@ -0,0 +1,32 @@
+-- Point-in-time company profiles. A refresh that changes the stored profile adds a
+-- snapshot dated the day the new values were seen, so backtests can ask for the profile
+-- as it was known on a past date.
+CREATE TABLE IF NOT EXISTS company_profile_snapshots (
+    id TEXT PRIMARY KEY,
+    as_of TEXT NOT NULL, -- YYYY-MM-DD
+    symbol_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    company_name TEXT,
+    description TEXT,
+    sector TEXT,
+    industry TEXT,
+    employees INTEGER,
+    website TEXT,
+    address TEXT,
+    city TEXT,
+    state TEXT,
+    country TEXT,
+    zip_code TEXT,
+    phone TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (symbol, as_of)
+);
+
+-- Existing profiles become the first snapshot, dated their last refresh
+INSERT OR IGNORE INTO company_profile_snapshots
+    (id, as_of, symbol_id, symbol, company_name, description, sector, industry, employees,
+     website, address, city, state, country, zip_code, phone, created_at, updated_at)
+SELECT id, substr(updated_at, 1, 10), symbol_id, symbol, company_name, description, sector, industry,
+       employees, website, address, city, state, country, zip_code, phone, created_at, updated_at
+FROM company_profiles;
@@ -0,0 +1,19 @@
+-- Named symbol universes (e.g. index constituents) that screeners, movers and bulk fetches can
+-- be limited to
+CREATE TABLE IF NOT EXISTS universes (
+    name TEXT PRIMARY KEY,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS universe_members (
+    universe TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    weight TEXT, -- index weight in percent, as imported
+    added_at TEXT NOT NULL,
+    PRIMARY KEY (universe, symbol),
+    FOREIGN KEY (universe) REFERENCES universes (name) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_universe_members_symbol ON universe_members (symbol);
@@ -0,0 +1,15 @@
+-- Background jobs for long-running fetches; results are kept for polling via /api/jobs/:id
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    request TEXT NOT NULL, -- JSON job request
+    status TEXT NOT NULL, -- queued, running, succeeded or failed
+    result TEXT, -- JSON, once succeeded
+    error TEXT,
+    created_at TEXT NOT NULL,
+    started_at TEXT,
+    finished_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs (status, created_at);
+CREATE INDEX IF NOT EXISTS idx_jobs_created ON jobs (created_at);
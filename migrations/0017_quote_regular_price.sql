@@ -0,0 +1,2 @@
+-- Quotes taken in the pre- or post-market session also keep the last regular-session price
+ALTER TABLE realtime_quotes ADD COLUMN regular_price TEXT; -- Decimal stored as TEXT
@@ -0,0 +1,9 @@
+-- Exit levels per holding, checked by the background price updater
+ALTER TABLE portfolio_holdings ADD COLUMN stop_loss TEXT; -- Decimal stored as TEXT
+ALTER TABLE portfolio_holdings ADD COLUMN trailing_stop_percent TEXT;
+ALTER TABLE portfolio_holdings ADD COLUMN target_price TEXT;
+-- Highest price seen since the trailing stop was set
+ALTER TABLE portfolio_holdings ADD COLUMN high_water_mark TEXT;
+-- When the stop or target last fired; cleared when the level changes so it fires once
+ALTER TABLE portfolio_holdings ADD COLUMN stop_triggered_at TEXT;
+ALTER TABLE portfolio_holdings ADD COLUMN target_triggered_at TEXT;
@@ -0,0 +1,7 @@
+-- User-facing names mapped to canonical provider tickers, e.g. BMW -> BMW.DE
+CREATE TABLE IF NOT EXISTS symbol_aliases (
+    alias TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
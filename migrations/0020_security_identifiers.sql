@@ -0,0 +1,12 @@
+-- ISIN/CUSIP to ticker mappings found by /api/lookup, kept so each identifier is looked up once
+CREATE TABLE IF NOT EXISTS security_identifiers (
+    identifier TEXT PRIMARY KEY,
+    kind TEXT NOT NULL, -- isin or cusip
+    symbol TEXT NOT NULL,
+    name TEXT,
+    exchange TEXT,
+    source TEXT NOT NULL, -- search or openfigi
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_security_identifiers_symbol ON security_identifiers (symbol);
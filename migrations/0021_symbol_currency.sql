@@ -0,0 +1,2 @@
+-- Trading currency as reported by the provider, e.g. USD, EUR or GBp (pence) for London
+ALTER TABLE symbols ADD COLUMN currency TEXT;
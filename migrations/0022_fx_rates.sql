@@ -0,0 +1,8 @@
+-- Daily closing exchange rates per currency pair (e.g. GBPUSD: USD per GBP), used to convert
+-- candles of foreign symbols into a requested base currency
+CREATE TABLE IF NOT EXISTS fx_rates (
+    pair TEXT NOT NULL,
+    date TEXT NOT NULL,
+    rate TEXT NOT NULL,
+    PRIMARY KEY (pair, date)
+);
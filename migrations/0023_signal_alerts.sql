@@ -0,0 +1,10 @@
+-- Alerts on technical signals such as a golden cross, checked against daily closes
+CREATE TABLE IF NOT EXISTS signal_alerts (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    last_fired_on TEXT, -- date of the daily close the alert last fired on
+    last_fired_at TEXT,
+    created_at TEXT NOT NULL,
+    UNIQUE (symbol, condition)
+);
@@ -0,0 +1,9 @@
+-- Alert throttling: per-alert cooldown and re-arm overrides of ALERT_COOLDOWN_MINUTES and
+-- ALERT_REARM_PERCENT, plus snoozing until a given time
+ALTER TABLE portfolio_holdings ADD COLUMN alert_cooldown_minutes INTEGER;
+ALTER TABLE portfolio_holdings ADD COLUMN alert_rearm_percent TEXT; -- Decimal stored as TEXT
+ALTER TABLE portfolio_holdings ADD COLUMN alerts_muted_until TEXT;
+-- When the stop or target last fired, kept across re-arms for the cooldown
+ALTER TABLE portfolio_holdings ADD COLUMN last_alert_at TEXT;
+ALTER TABLE signal_alerts ADD COLUMN cooldown_minutes INTEGER;
+ALTER TABLE signal_alerts ADD COLUMN muted_until TEXT;
@@ -0,0 +1,8 @@
+-- Cache and provider activity per sampling interval, kept when STATS_HISTORY_PERSIST is set
+CREATE TABLE IF NOT EXISTS stats_samples (
+    sampled_at TEXT PRIMARY KEY,
+    interval_seconds INTEGER NOT NULL,
+    yahoo_calls INTEGER NOT NULL,
+    yahoo_rate_limited INTEGER NOT NULL,
+    caches TEXT NOT NULL -- JSON array of per-cache figures
+);
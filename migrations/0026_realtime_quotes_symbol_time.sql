@@ -0,0 +1,2 @@
+-- Quote history pages through one symbol's captures by market_time
+CREATE INDEX IF NOT EXISTS idx_realtime_quotes_symbol_market_time ON realtime_quotes (symbol, market_time);
@@ -0,0 +1,9 @@
+-- Listing status: symbols the provider keeps returning no data for are marked delisted and
+-- left out of background refreshes until a fetch returns data again
+ALTER TABLE symbols ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+ALTER TABLE symbols ADD COLUMN status_changed_at TEXT;
+-- Consecutive provider fetches without data, and when the first of them happened
+ALTER TABLE symbols ADD COLUMN empty_fetches INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE symbols ADD COLUMN first_empty_fetch_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_symbols_status ON symbols(status);
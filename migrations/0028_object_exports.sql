@@ -0,0 +1,20 @@
+-- Runs of the exporter that copies candles and quotes to S3-compatible object storage.
+-- Each run covers rows stored in (data_since, data_until]; the next one starts from the
+-- data_until of the last run that succeeded.
+CREATE TABLE IF NOT EXISTS object_exports (
+    id TEXT PRIMARY KEY,
+    triggered_by TEXT NOT NULL, -- scheduled or manual
+    status TEXT NOT NULL, -- running, succeeded or failed
+    data_since TEXT, -- NULL for the first run, which exports everything stored
+    data_until TEXT NOT NULL,
+    objects_written INTEGER NOT NULL DEFAULT 0,
+    rows_written INTEGER NOT NULL DEFAULT 0,
+    bytes_written INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at TEXT NOT NULL,
+    finished_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_object_exports_started ON object_exports (started_at);
+CREATE INDEX IF NOT EXISTS idx_historical_prices_created ON historical_prices (created_at);
+CREATE INDEX IF NOT EXISTS idx_realtime_quotes_created ON realtime_quotes (created_at);
@@ -0,0 +1,3 @@
+-- Progress of running bulk jobs, for the jobs dashboard: symbols fetched out of the total
+ALTER TABLE jobs ADD COLUMN progress_done INTEGER;
+ALTER TABLE jobs ADD COLUMN progress_total INTEGER;
@@ -0,0 +1,35 @@
+-- Indicator expressions such as ema(close, 12) - ema(close, 26), saved per owner (signed-in
+-- admin, or "public" without auth)
+CREATE TABLE IF NOT EXISTS custom_indicators (
+    id TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    expression TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (owner, name)
+);
+
+-- Signal alerts can watch a custom indicator cross a threshold. The table is rebuilt so a
+-- symbol can have several of those; built-in conditions leave both columns empty.
+CREATE TABLE signal_alerts_new (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    indicator_id TEXT NOT NULL DEFAULT '',
+    threshold TEXT NOT NULL DEFAULT '', -- Decimal stored as TEXT
+    last_fired_on TEXT,
+    last_fired_at TEXT,
+    created_at TEXT NOT NULL,
+    cooldown_minutes INTEGER,
+    muted_until TEXT,
+    UNIQUE (symbol, condition, indicator_id, threshold)
+);
+
+INSERT INTO signal_alerts_new
+    (id, symbol, condition, last_fired_on, last_fired_at, created_at, cooldown_minutes, muted_until)
+SELECT id, symbol, condition, last_fired_on, last_fired_at, created_at, cooldown_minutes, muted_until
+FROM signal_alerts;
+
+DROP TABLE signal_alerts;
+ALTER TABLE signal_alerts_new RENAME TO signal_alerts;
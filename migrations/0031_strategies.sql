@@ -0,0 +1,24 @@
+-- Named backtest strategies, saved per owner (signed-in admin, or "public" without auth)
+CREATE TABLE IF NOT EXISTS strategies (
+    id TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    shared BOOLEAN NOT NULL DEFAULT 0,
+    version INTEGER NOT NULL DEFAULT 1, -- Latest row in strategy_versions
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (owner, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategies_shared ON strategies(shared);
+
+-- Every definition a strategy has had, as JSON rules
+CREATE TABLE IF NOT EXISTS strategy_versions (
+    strategy_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (strategy_id, version),
+    FOREIGN KEY (strategy_id) REFERENCES strategies(id) ON DELETE CASCADE
+);
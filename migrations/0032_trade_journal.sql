@@ -0,0 +1,20 @@
+-- Real trades logged with their rationale, per owner (signed-in admin, or "public" without
+-- auth). Separate from portfolio_transactions, which drive holdings and tax lots.
+CREATE TABLE IF NOT EXISTS journal_entries (
+    id TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('buy', 'sell')),
+    quantity TEXT NOT NULL, -- Decimal stored as TEXT
+    price TEXT NOT NULL,
+    fees TEXT NOT NULL DEFAULT '0',
+    trade_date TEXT NOT NULL, -- YYYY-MM-DD
+    rationale TEXT,
+    tags TEXT NOT NULL DEFAULT '[]', -- JSON array of lower-case tags
+    screenshot_urls TEXT NOT NULL DEFAULT '[]', -- JSON array of URLs
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_entries_owner_date ON journal_entries(owner, trade_date);
+CREATE INDEX IF NOT EXISTS idx_journal_entries_owner_symbol ON journal_entries(owner, symbol);
@@ -0,0 +1,8 @@
+-- Observations of macro series (Treasury yields in percent, CPI as an index) by the
+-- service's series name, e.g. tbill-13w
+CREATE TABLE IF NOT EXISTS macro_observations (
+    series TEXT NOT NULL,
+    date TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (series, date)
+);
@@ -0,0 +1,18 @@
+-- Ranking models: factor weights as a JSON object, e.g. {"momentum_12m": 0.3, "volatility": 0.2}
+CREATE TABLE IF NOT EXISTS ranking_models (
+    name TEXT PRIMARY KEY,
+    description TEXT,
+    weights TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- The model /api/rankings uses when none is named; it can be edited but not deleted
+INSERT OR IGNORE INTO ranking_models (name, description, weights, created_at, updated_at)
+VALUES (
+    'default',
+    'Momentum tilted towards steady, risk-adjusted performers with some income',
+    '{"momentum_3m":0.2,"momentum_12m":0.3,"volatility":0.15,"max_drawdown":0.05,"sharpe_ratio":0.2,"dividend_yield":0.1}',
+    strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+    strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+);
@@ -0,0 +1,17 @@
+-- Unusual daily returns and volumes found by the background analyzer, one row per symbol,
+-- bar and kind ("return" or "volume")
+CREATE TABLE IF NOT EXISTS anomalies (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL, -- Decimal stored as TEXT
+    z_score TEXT NOT NULL,
+    baseline TEXT NOT NULL,
+    close TEXT NOT NULL,
+    volume INTEGER NOT NULL,
+    detected_at TEXT NOT NULL,
+    UNIQUE (symbol, date, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_date ON anomalies(date);
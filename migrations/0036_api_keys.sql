@@ -0,0 +1,25 @@
+-- API keys of programmatic consumers, with optional request quotas per UTC day and calendar
+-- month. Only the SHA-256 hash of a key is kept; `prefix` is its first characters, to tell
+-- keys apart in listings.
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    daily_quota INTEGER,   -- NULL for unlimited
+    monthly_quota INTEGER, -- NULL for unlimited
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    last_used_at TEXT
+);
+
+-- Requests made with each key per UTC day, and those refused for exceeding a quota
+CREATE TABLE IF NOT EXISTS key_usage (
+    key_id TEXT NOT NULL,
+    date TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    rejected INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, date),
+    FOREIGN KEY (key_id) REFERENCES api_keys (id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- What an API key may do: "read" keys only read, "admin" keys may also write and use admin routes
+ALTER TABLE api_keys ADD COLUMN role TEXT NOT NULL DEFAULT 'read';
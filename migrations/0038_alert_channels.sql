@@ -0,0 +1,4 @@
+-- Notification channels picked per alert as comma-separated names; NULL sends to every
+-- configured channel and an empty string to none (webhooks still fire)
+ALTER TABLE portfolio_holdings ADD COLUMN alert_channels TEXT;
+ALTER TABLE signal_alerts ADD COLUMN channels TEXT;
@@ -0,0 +1,31 @@
+#!/usr/bin/env sh
+# Download the web UI's CSS/JS libraries into static/vendor so the UI works without
+# access to public CDNs. Serve them with WEB_UI_LOCAL_ASSETS=true.
+#
+# Usage: scripts/fetch-web-assets.sh [static-dir]
+set -eu
+
+STATIC_DIR="${1:-static}"
+VENDOR="$STATIC_DIR/vendor"
+CDNJS="https://cdnjs.cloudflare.com/ajax/libs"
+
+fetch() {
+    mkdir -p "$(dirname "$2")"
+    echo "  $2"
+    curl -fsSL "$1" -o "$2"
+}
+
+echo "Fetching web UI assets into $VENDOR"
+fetch "$CDNJS/tailwindcss/2.2.19/tailwind.min.css" "$VENDOR/tailwind.min.css"
+fetch "$CDNJS/Chart.js/3.9.1/chart.min.js" "$VENDOR/chart.min.js"
+fetch "https://unpkg.com/@airgap/beacon-sdk@3.1.2/dist/walletbeacon.min.js" "$VENDOR/walletbeacon.min.js"
+
+# all.min.css loads its fonts from ../webfonts/
+fetch "$CDNJS/font-awesome/6.0.0/css/all.min.css" "$VENDOR/fontawesome/css/all.min.css"
+for font in fa-brands-400 fa-regular-400 fa-solid-900 fa-v4compatibility; do
+    for ext in woff2 ttf; do
+        fetch "$CDNJS/font-awesome/6.0.0/webfonts/$font.$ext" "$VENDOR/fontawesome/webfonts/$font.$ext"
+    done
+done
+
+echo "Done. Start the service with WEB_UI_LOCAL_ASSETS=true to use them."
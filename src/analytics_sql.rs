@@ -0,0 +1,184 @@
+//! Restricted SQL for ad-hoc analysis of the stored data.
+//!
+//! `POST /api/analytics/sql` runs a single `SELECT` (optionally led by `WITH`) on a
+//! connection switched to `query_only`, with a row cap and a time limit enforced through
+//! SQLite's progress handler. Statements are checked here before they reach the database:
+//! anything that is not a lone query is refused, as are tables holding credentials or
+//! records of who did what. The endpoint is off unless `ANALYTICS_SQL_ENABLED` is set.
+
+/// Tables holding session tokens, key hashes, signing secrets, delivery payloads or the
+/// audit and notification history, never readable through the endpoint. New tables with
+/// such contents belong here.
+pub const DENIED_TABLES: &[&str] = &[
+    "admin_sessions",
+    "api_keys",
+    "audit_log",
+    "key_usage",
+    "notifications",
+    "webhook_deliveries",
+    "webhooks",
+];
+
+/// Keywords that only appear in statements that change the database or the connection
+const DENIED_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "drop", "create", "alter", "attach", "detach", "pragma",
+    "vacuum", "reindex", "load_extension",
+];
+
+/// Check a statement against the allow-list and return it without trailing semicolons
+pub fn check_statement(sql: &str) -> Result<&str, String> {
+    let statement = sql.trim().trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    let Tokens { words, literals } = tokens(statement)?;
+
+    match words.first().map(String::as_str) {
+        Some("select") | Some("with") => {}
+        Some(_) => return Err("must be a SELECT or WITH query".to_string()),
+        None => return Err("must not be empty".to_string()),
+    }
+    if let Some(keyword) = words.iter().find(|word| DENIED_KEYWORDS.contains(&word.as_str())) {
+        return Err(format!("must not use {}", keyword.to_uppercase()));
+    }
+    // SQLite takes a string literal where a table name is expected, so literals are checked too
+    if let Some(table) = words
+        .iter()
+        .chain(&literals)
+        .find(|word| DENIED_TABLES.contains(&word.as_str()))
+    {
+        return Err(format!("must not read {}", table));
+    }
+    Ok(statement)
+}
+
+/// Lowercased words of a statement and the contents of its string literals
+struct Tokens {
+    words: Vec<String>,
+    literals: Vec<String>,
+}
+
+/// Split a statement into identifiers and keywords, skipping comments. Quoted identifiers
+/// (`"..."`, `` `...` `` and `[...]`) count as single words, so quoting a table name does not
+/// hide it and comment markers inside quotes are not comments. A semicolon anywhere outside
+/// quotes means more than one statement.
+fn tokens(statement: &str) -> Result<Tokens, String> {
+    let mut words = Vec::new();
+    let mut literals = Vec::new();
+    let mut word = String::new();
+    let mut chars = statement.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            word.extend(c.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        match c {
+            '\'' => literals.push(quoted(&mut chars, '\'')?),
+            '"' | '`' => words.push(quoted(&mut chars, c)?),
+            '[' => words.push(quoted(&mut chars, ']')?),
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => break,
+                    }
+                }
+            }
+            ';' => return Err("must be a single statement".to_string()),
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(Tokens { words, literals })
+}
+
+/// Lowercased contents up to the closing `quote`, where a doubled quote stands for itself
+fn quoted(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, quote: char) -> Result<String, String> {
+    let mut contents = String::new();
+    loop {
+        match chars.next() {
+            Some(c) if c == quote && quote != ']' && chars.peek() == Some(&quote) => {
+                chars.next();
+                contents.push(c);
+            }
+            Some(c) if c == quote => return Ok(contents),
+            Some(c) => contents.extend(c.to_lowercase()),
+            None => return Err("has an unterminated quote".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refused(sql: &str) -> String {
+        check_statement(sql).expect_err(sql)
+    }
+
+    #[test]
+    fn accepts_single_queries() {
+        assert_eq!(check_statement("SELECT symbol FROM symbols;  ").unwrap(), "SELECT symbol FROM symbols");
+        assert!(check_statement("WITH recent AS (SELECT * FROM historical_prices) SELECT COUNT(*) FROM recent").is_ok());
+        // Semicolons and keywords inside literals and comments are only text
+        assert!(check_statement("SELECT 'a;b', 'drop' FROM symbols -- ; delete").is_ok());
+        assert!(check_statement("/* update */ SELECT 1").is_ok());
+        assert!(check_statement("SELECT \"it's\" FROM symbols").is_ok());
+    }
+
+    #[test]
+    fn refuses_anything_but_a_query() {
+        assert_eq!(refused("   "), "must not be empty");
+        assert_eq!(refused("-- just a comment"), "must not be empty");
+        assert_eq!(refused("DELETE FROM symbols"), "must be a SELECT or WITH query");
+        assert_eq!(refused("WITH x AS (SELECT 1) DELETE FROM symbols"), "must not use DELETE");
+        assert_eq!(refused("PRAGMA table_info(symbols)"), "must be a SELECT or WITH query");
+        assert_eq!(refused("ATTACH DATABASE 'other.db' AS other"), "must be a SELECT or WITH query");
+        assert_eq!(refused("SELECT load_extension('evil')"), "must not use LOAD_EXTENSION");
+        assert_eq!(refused("SELECT 'unterminated"), "has an unterminated quote");
+    }
+
+    #[test]
+    fn refuses_multiple_statements() {
+        assert_eq!(refused("SELECT 1; SELECT 2"), "must be a single statement");
+        assert_eq!(refused("SELECT 1; ATTACH DATABASE 'x' AS y"), "must be a single statement");
+        assert_eq!(refused("SELECT 1 /* */; PRAGMA writable_schema = 1"), "must be a single statement");
+    }
+
+    #[test]
+    fn refuses_denied_tables_however_they_are_written() {
+        for sql in [
+            "SELECT * FROM api_keys",
+            "SELECT key_hash FROM API_KEYS",
+            "SELECT * FROM \"audit_log\"",
+            "SELECT * FROM `notifications`",
+            "SELECT * FROM [admin_sessions]",
+            "SELECT * FROM main.webhooks",
+            "SELECT * FROM 'webhook_deliveries'",
+            "SELECT * FROM /* symbols */ key_usage",
+            "WITH k AS (SELECT * FROM api_keys) SELECT * FROM k",
+        ] {
+            assert!(refused(sql).starts_with("must not read"), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn comment_markers_inside_quotes_do_not_hide_the_rest() {
+        assert!(refused("SELECT 1 AS \"--\", * FROM admin_sessions").starts_with("must not read"));
+        assert!(refused("SELECT 1 AS [/*], * FROM api_keys").starts_with("must not read"));
+        assert!(refused("SELECT '--', * FROM audit_log").starts_with("must not read"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Background detection of unusual daily moves in stored symbols.
+//!
+//! Every `ANOMALY_SCAN_INTERVAL` the analyzer reads the stored daily candles of each active,
+//! listed symbol and compares each of its last few bars with the `ANOMALY_LOOKBACK_DAYS` bars
+//! before it: a daily return whose z-score is beyond `ANOMALY_RETURN_Z` either way, or a log
+//! volume at least `ANOMALY_VOLUME_Z` above the mean, is recorded once per symbol, bar and
+//! kind. New anomalies go out as `anomaly.detected` webhooks and, with `ANOMALY_NOTIFY`, to
+//! the notification channels. Nothing is fetched; symbols are as fresh as their stored bars.
+
+use chrono::Utc;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::{AnomalyConfig, NotificationConfig, ANOMALY_RECENT_BARS};
+use crate::models::{Anomaly, AnomalyKind, HistoricalPrice};
+use crate::yahoo_service::YahooFinanceService;
+
+/// Start the periodic anomaly scan on the current runtime
+pub fn spawn(service: Arc<YahooFinanceService>, config: AnomalyConfig, notifications: NotificationConfig) {
+    info!(
+        "🔎 Anomaly scan every {}s over {} daily bars",
+        config.interval.as_secs(),
+        config.lookback_days
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+        loop {
+            interval.tick().await;
+            match service.scan_anomalies(&config, &notifications).await {
+                Ok(0) => {}
+                Ok(found) => info!("🔎 {} new anomalies", found),
+                Err(e) => warn!("Failed to scan for anomalies: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Mean and population standard deviation
+fn mean_stdev(values: &[f64]) -> Option<(f64, f64)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some((mean, variance.sqrt())).filter(|(_, stdev)| *stdev > 0.0)
+}
+
+fn decimal(value: f64, dp: u32) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(dp)
+}
+
+/// Anomalies among the last [`ANOMALY_RECENT_BARS`] of `candles` (oldest first), each bar
+/// measured against the `lookback_days` bars before it
+pub fn detect(symbol: &str, candles: &[HistoricalPrice], config: &AnomalyConfig) -> Vec<Anomaly> {
+    let closes: Vec<f64> = candles.iter().map(|c| c.close.to_f64().unwrap_or_default()).collect();
+    let now = Utc::now();
+    let anomaly = |candle: &HistoricalPrice, kind, value: f64, z_score: f64, baseline: f64| Anomaly {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        date: candle.timestamp.date_naive(),
+        kind,
+        value: decimal(value, if kind == AnomalyKind::Volume { 0 } else { 4 }),
+        z_score: decimal(z_score, 2),
+        baseline: decimal(baseline, if kind == AnomalyKind::Volume { 0 } else { 4 }),
+        close: candle.close,
+        volume: candle.volume,
+        detected_at: now,
+    };
+
+    let mut found = Vec::new();
+    let first = candles.len().saturating_sub(ANOMALY_RECENT_BARS).max(config.lookback_days + 1);
+    for i in first..candles.len() {
+        let window = i - config.lookback_days..i;
+        let candle = &candles[i];
+
+        // Percent returns; the first bar of the window needs the close before it
+        let returns: Vec<f64> = window
+            .clone()
+            .filter(|&j| closes[j - 1] > 0.0)
+            .map(|j| (closes[j] / closes[j - 1] - 1.0) * 100.0)
+            .collect();
+        if closes[i - 1] > 0.0 {
+            let change = (closes[i] / closes[i - 1] - 1.0) * 100.0;
+            if let Some((mean, stdev)) = mean_stdev(&returns) {
+                let z = (change - mean) / stdev;
+                if z.abs() >= config.return_z {
+                    found.push(anomaly(candle, AnomalyKind::Return, change, z, mean));
+                }
+            }
+        }
+
+        // Volume is skewed, so compare logs; days without volume carry no signal
+        let volumes: Vec<i64> = window.map(|j| candles[j].volume).filter(|v| *v > 0).collect();
+        let logs: Vec<f64> = volumes.iter().map(|v| (*v as f64).ln()).collect();
+        if candle.volume > 0 && logs.len() * 2 >= config.lookback_days {
+            if let Some((mean, stdev)) = mean_stdev(&logs) {
+                let z = ((candle.volume as f64).ln() - mean) / stdev;
+                if z >= config.volume_z {
+                    let average = volumes.iter().sum::<i64>() as f64 / volumes.len() as f64;
+                    found.push(anomaly(candle, AnomalyKind::Volume, candle.volume as f64, z, average));
+                }
+            }
+        }
+    }
+    found
+}
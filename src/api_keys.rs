@@ -0,0 +1,205 @@
+//! API keys of programmatic consumers, metered against request quotas.
+//!
+//! A request carrying `X-API-Key` is counted towards its key's usage of the current UTC day in
+//! `key_usage`. Once the key has used its daily or calendar-month quota, further requests fail
+//! with `429 QUOTA_EXCEEDED` and a `Retry-After` until the period starts over. Refused requests
+//! are counted separately and do not use up the quota, and a key's own usage report is never
+//! metered. Unknown and deactivated keys are refused with `401`. A valid `read` key may read the
+//! API without a login when `PUBLIC_READ_API=false`, and an `admin` key may also write and use
+//! admin routes.
+//! Keys are stored only as SHA-256 hashes, since they are long and random enough not to need a
+//! slow hash. Read-only instances check keys but cannot count requests, so quotas are enforced
+//! by the writable instance.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::errors::{ApiError, ErrorCode};
+use crate::handlers::AppState;
+use crate::models::{ApiKey, ApiKeyRole};
+use crate::quota;
+use crate::webhooks;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Start of every key, so leaked keys are easy to recognize
+const KEY_PREFIX: &str = "mk_";
+/// Characters of a key kept in the clear to tell keys apart
+const DISPLAYED_PREFIX_CHARS: usize = 11;
+/// Routes of `/api/keys/:id/usage`, which neither count nor are refused
+const UNMETERED_PREFIX: &str = "/api/keys/";
+
+/// The API key a request was made with, in the request's extensions once it is accepted
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyAuth {
+    pub key_id: Uuid,
+    pub role: ApiKeyRole,
+}
+
+impl ApiKeyAuth {
+    pub fn is_admin(&self) -> bool {
+        self.role == ApiKeyRole::Admin
+    }
+}
+
+/// New random key: the prefix and 32 random bytes hex-encoded
+pub fn generate_key() -> String {
+    format!("{}{}", KEY_PREFIX, webhooks::generate_secret())
+}
+
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// The part of `key` shown in listings
+pub fn display_prefix(key: &str) -> String {
+    key.chars().take(DISPLAYED_PREFIX_CHARS).collect()
+}
+
+/// First day of the calendar month of `date`
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Midnight UTC on the first of the next month, when the monthly quota starts over
+pub fn next_month(now: DateTime<Utc>) -> DateTime<Utc> {
+    let first = month_start(now.date_naive());
+    let next = first.checked_add_months(Months::new(1)).unwrap_or(first);
+    next.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// The quota `key` went over with `today` and `month` requests, and when it starts over. The
+/// monthly one is reported first since it lasts longer.
+fn exceeded_quota(key: &ApiKey, today: u32, month: u32, now: DateTime<Utc>) -> Option<(&'static str, u32, DateTime<Utc>)> {
+    if let Some(quota) = key.monthly_quota.filter(|quota| month > *quota) {
+        return Some(("monthly", quota, next_month(now)));
+    }
+    key.daily_quota
+        .filter(|quota| today > *quota)
+        .map(|quota| ("daily", quota, quota::next_reset(now)))
+}
+
+/// Authenticate and meter requests that carry `X-API-Key`; others pass through untouched
+pub async fn api_key_middleware(State(app_state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let Some(presented) = request.headers().get(API_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let Ok(presented) = presented.to_str() else {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response();
+    };
+
+    let db = &app_state.service.db;
+    let key = match db.get_api_key_by_hash(&hash_key(presented.trim())).await {
+        Ok(Some(key)) if key.active => key,
+        Ok(_) => return ApiError::new(ErrorCode::Unauthorized, "Invalid or inactive API key").into_response(),
+        Err(e) => {
+            error!("Failed to look up API key: {}", e);
+            return ApiError::from(ErrorCode::InternalError).into_response();
+        }
+    };
+
+    // A key can always see its own usage, even over quota
+    let metered = !request.uri().path().starts_with(UNMETERED_PREFIX);
+    if metered && !db.is_read_only() {
+        let now = Utc::now();
+        // Counted before the check so concurrent requests cannot all slip under the quota
+        match db.record_key_request(key.id, now, month_start(now.date_naive())).await {
+            Ok((today, month)) => {
+                if let Some((period, quota, resets_at)) = exceeded_quota(&key, today, month, now) {
+                    if let Err(e) = db.reject_key_request(key.id, now.date_naive()).await {
+                        error!("Failed to record refused request of API key {}: {}", key.id, e);
+                    }
+                    return ApiError {
+                        retry_after: (resets_at - now).to_std().ok(),
+                        ..ApiError::new(
+                            ErrorCode::QuotaExceeded,
+                            format!(
+                                "API key '{}' has used its {} quota of {} requests; it resets at {}",
+                                key.name,
+                                period,
+                                quota,
+                                resets_at.to_rfc3339()
+                            ),
+                        )
+                    }
+                    .into_response();
+                }
+            }
+            // Metering must not take the API down with it
+            Err(e) => error!("Failed to record request of API key {}: {}", key.id, e),
+        }
+    }
+
+    request.extensions_mut().insert(ApiKeyAuth { key_id: key.id, role: key.role });
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 30, 0).unwrap()
+    }
+
+    fn key(daily_quota: Option<u32>, monthly_quota: Option<u32>) -> ApiKey {
+        let now = at(2024, 1, 1, 0);
+        ApiKey {
+            id: Uuid::new_v4(),
+            name: "test".to_string(),
+            prefix: "mk_test".to_string(),
+            key_hash: String::new(),
+            role: ApiKeyRole::Read,
+            daily_quota,
+            monthly_quota,
+            active: true,
+            created_at: now,
+            updated_at: now,
+            last_used_at: None,
+        }
+    }
+
+    #[test]
+    fn month_start_is_the_first_of_the_month() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(month_start(date), NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+    }
+
+    #[test]
+    fn next_month_rolls_over_the_year() {
+        assert_eq!(next_month(at(2024, 1, 31, 23)), Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap());
+        assert_eq!(next_month(at(2024, 12, 15, 8)), Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn quotas_are_exceeded_only_past_their_limit() {
+        let now = at(2024, 3, 10, 12);
+        let key = key(Some(10), Some(100));
+        assert!(exceeded_quota(&key, 10, 100, now).is_none());
+
+        let (period, quota, resets_at) = exceeded_quota(&key, 11, 50, now).unwrap();
+        assert_eq!((period, quota), ("daily", 10));
+        assert_eq!(resets_at, Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn monthly_quota_is_reported_before_the_daily_one() {
+        let now = at(2024, 3, 10, 12);
+        let (period, quota, resets_at) = exceeded_quota(&key(Some(10), Some(100)), 11, 101, now).unwrap();
+        assert_eq!((period, quota), ("monthly", 100));
+        assert_eq!(resets_at, Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn unlimited_keys_are_never_refused() {
+        assert!(exceeded_quota(&key(None, None), u32::MAX, u32::MAX, at(2024, 3, 10, 12)).is_none());
+    }
+}
@@ -0,0 +1,621 @@
+//! Router construction and background tasks, shared by the service binary and by
+//! anything embedding the service.
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{
+        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+        HeaderName, HeaderValue, Method,
+    },
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+use tower::ServiceBuilder;
+use tower_http::{
+    cors::CorsLayer,
+    trace::TraceLayer,
+};
+use tracing::{info, warn};
+
+use crate::anomaly;
+use crate::api_keys;
+use crate::auth_middleware;
+use crate::auth_routes;
+use crate::cache_bus;
+use crate::config::{Config, CorsConfig};
+use crate::database::Database;
+use crate::errors;
+use crate::event_stream;
+use crate::object_export;
+use crate::read_only;
+use crate::handlers::{
+    health_check, get_symbols, delete_symbol, delete_symbols, rename_symbol, search_symbols, suggest_symbols, validate_symbol,
+    get_historical_data, get_klines, udf_config, udf_symbols, udf_search, udf_history, udf_time,
+    fetch_historical_data, verify_historical_data, bulk_fetch_historical,
+    get_real_time_quote, get_quote_history, get_symbol_stats, get_symbol_peers, get_symbol_forecast, get_symbol_features, get_company_profile, get_holders, get_etf_composition, get_symbol_listings, get_symbol_overview,
+    get_price_analysis, get_macro_series, get_database_stats, get_limits, get_stats_history, get_comprehensive_quote,
+    get_extended_quote_data, handler_404, cleanup_cache, get_audit_log, get_latency_report, wipe_demo_data,
+    export_archive, import_archive, list_object_exports, start_object_export, get_object_export,
+    list_webhooks, create_webhook, update_webhook, delete_webhook, get_webhook_deliveries,
+    test_webhook, list_api_keys, create_api_key, update_api_key, delete_api_key, get_api_key_usage,
+    get_notifications, test_notification, list_tools, call_tool, list_dashboards,
+    get_dashboard, create_dashboard, update_dashboard, delete_dashboard,
+    list_strategies, get_strategy, list_strategy_versions, create_strategy, update_strategy,
+    delete_strategy, backtest_strategy, list_journal_entries, export_journal, list_journal_tags,
+    get_journal_entry, create_journal_entry, update_journal_entry, delete_journal_entry,
+    list_aliases, set_alias, delete_alias, resolve_symbol, lookup_identifier,
+    list_custom_indicators, save_custom_indicator, delete_custom_indicator,
+    list_signal_alerts, create_signal_alert, update_signal_alert, delete_signal_alert, mute_signal_alert,
+    unmute_signal_alert, mute_holding_alerts, unmute_holding_alerts,
+    list_universes, get_universe, import_universe, delete_universe, list_anomalies, get_rankings, list_ranking_models, get_ranking_model, save_ranking_model, delete_ranking_model, list_jobs, get_job, submit_job,
+    retry_job, natural_language_query,
+    get_technical_indicators, compare_symbols, get_sectors, get_sector_performance,
+    get_market_movers, get_market_status,
+    get_portfolio, add_portfolio_holding, update_portfolio_holding,
+    delete_portfolio_holding, update_portfolio_prices, project_portfolio, set_portfolio_targets,
+    get_portfolio_rebalance, get_portfolio_income, get_portfolio_exposure, get_portfolio_summary, add_portfolio_transaction,
+    get_portfolio_transactions, delete_portfolio_transaction, get_tax_report,
+    get_portfolio_settings, update_portfolio_settings, get_portfolio_cash, deposit_cash,
+    withdraw_cash, AppState,
+};
+use crate::jobs;
+use crate::fields;
+use crate::http_cache;
+use crate::latency;
+use crate::limits;
+use crate::numeric;
+use crate::portfolio_updater;
+use crate::stats_history;
+#[cfg(feature = "web-ui")]
+use crate::web_ui;
+use crate::yahoo_service::YahooFinanceService;
+
+/// Response headers browser clients may read besides the CORS-safelisted ones
+fn exposed_headers() -> [HeaderName; 4] {
+    [limits::RATE_LIMIT_LIMIT, limits::RATE_LIMIT_REMAINING, limits::RATE_LIMIT_RESET, RETRY_AFTER]
+}
+
+fn cors_layer(cors: &CorsConfig) -> CorsLayer {
+    if cors.allow_all_origins {
+        CorsLayer::new()
+            .allow_origin("*".parse::<HeaderValue>().unwrap())
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, HeaderName::from_static(api_keys::API_KEY_HEADER)])
+            .expose_headers(exposed_headers())
+    } else {
+        let mut cors_builder = CorsLayer::new()
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, HeaderName::from_static(api_keys::API_KEY_HEADER)])
+            .expose_headers(exposed_headers());
+        
+        for origin in &cors.allowed_origins {
+            if let Ok(header_value) = origin.parse::<HeaderValue>() {
+                cors_builder = cors_builder.allow_origin(header_value);
+            } else {
+                warn!("Invalid CORS origin: {}", origin);
+            }
+        }
+        
+        cors_builder
+    }
+}
+
+/// Build the service for `config` on top of an opened database and return its router
+pub fn build_app(config: Config, db: Database) -> anyhow::Result<Router> {
+    let service = Arc::new(YahooFinanceService::new(Arc::new(db), config.clone())?);
+    Ok(build_router(AppState::new(service, config)))
+}
+
+/// The full API on a fresh in-memory database with [`Config::for_testing`], ready to be
+/// driven with `tower::ServiceExt::oneshot`. No background tasks are started.
+///
+/// ```no_run
+/// use axum::{body::Body, http::{Request, StatusCode}};
+/// use tower::ServiceExt;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let app = mango_data_service::build_test_app().await?;
+/// let response = app
+///     .oneshot(Request::builder().uri("/health").body(Body::empty())?)
+///     .await?;
+/// assert_eq!(response.status(), StatusCode::OK);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn build_test_app() -> anyhow::Result<Router> {
+    build_app(Config::for_testing(), Database::in_memory().await?)
+}
+
+/// Build the full router: health, auth, `/api` and, with the `web-ui` feature, the dashboard
+pub fn build_router(app_state: AppState) -> Router {
+    let cors = cors_layer(&app_state.config.cors);
+
+    // API routes; mutating requests require an admin when auth is enabled
+    let api_routes = Router::<AppState>::new()
+        // Symbol management
+        .route("/api/symbols", get(get_symbols).delete(delete_symbols))
+        .route("/api/symbols/:symbol", delete(delete_symbol))
+        .route("/api/symbols/:symbol/rename", post(rename_symbol))
+        .route("/api/symbols/search", get(search_symbols))
+        .route("/api/symbols/suggest", get(suggest_symbols))
+        .route("/api/symbols/:symbol/validate", get(validate_symbol))
+        
+        // Historical data
+        .route("/api/symbols/:symbol/historical", get(get_historical_data))
+        .route("/api/symbols/:symbol/klines", get(get_klines))
+        .route("/api/symbols/:symbol/fetch", post(fetch_historical_data))
+        .route("/api/symbols/:symbol/verify", get(verify_historical_data))
+        .route("/api/bulk/historical", get(bulk_fetch_historical))
+
+        // TradingView UDF datafeed
+        .route("/udf/config", get(udf_config))
+        .route("/udf/symbols", get(udf_symbols))
+        .route("/udf/search", get(udf_search))
+        .route("/udf/history", get(udf_history))
+        .route("/udf/time", get(udf_time))
+        
+        // Real-time quotes
+        .route("/api/symbols/:symbol/quote", get(get_real_time_quote))
+        .route("/api/symbols/:symbol/quotes", get(get_quote_history))
+        .route("/api/symbols/:symbol/stats", get(get_symbol_stats))
+        .route("/api/symbols/:symbol/peers", get(get_symbol_peers))
+        .route("/api/symbols/:symbol/forecast", get(get_symbol_forecast))
+        .route("/api/symbols/:symbol/features", get(get_symbol_features))
+        
+        // Company profiles
+        .route("/api/symbols/:symbol/profile", get(get_company_profile))
+        .route("/api/symbols/:symbol/holders", get(get_holders))
+        .route("/api/symbols/:symbol/etf", get(get_etf_composition))
+        .route("/api/symbols/:symbol/listings", get(get_symbol_listings))
+        
+        // Comprehensive data
+        .route("/api/symbols/:symbol/overview", get(get_symbol_overview))
+        .route("/api/symbols/:symbol/analysis", get(get_price_analysis))
+        .route("/api/symbols/:symbol/comprehensive", get(get_comprehensive_quote))
+        .route("/api/symbols/:symbol/extended", get(get_extended_quote_data))
+        .route("/api/symbols/:symbol/indicators", get(get_technical_indicators))
+        
+        // Comparison and advanced analytics
+        .route("/api/compare", get(compare_symbols))
+        .route("/api/sectors", get(get_sectors))
+        .route("/api/sectors/:sector/performance", get(get_sector_performance))
+        .route("/api/market/movers", get(get_market_movers))
+        .route("/api/market/status", get(get_market_status))
+        .route("/api/macro/:series", get(get_macro_series))
+        
+        // Tool endpoints for LLM agents
+        .route("/api/tools", get(list_tools))
+        .route("/api/tools/:name", post(call_tool))
+        .route("/api/query", post(natural_language_query))
+        
+        // Statistics and monitoring
+        .route("/api/stats", get(get_database_stats))
+        .route("/api/stats/history", get(get_stats_history))
+        .route("/api/limits", get(get_limits))
+        
+        // Portfolio endpoints
+        .route("/api/portfolio", get(get_portfolio))
+        .route("/api/portfolio/holdings", post(add_portfolio_holding))
+        .route("/api/portfolio/holdings/:holding_id", put(update_portfolio_holding))
+        .route("/api/portfolio/holdings/:holding_id", delete(delete_portfolio_holding))
+        .route(
+            "/api/portfolio/holdings/:holding_id/mute",
+            put(mute_holding_alerts).delete(unmute_holding_alerts),
+        )
+        .route("/api/portfolio/update-prices", post(update_portfolio_prices))
+        .route("/api/portfolio/projection", post(project_portfolio))
+        .route("/api/portfolio/targets", put(set_portfolio_targets))
+        .route("/api/portfolio/rebalance", get(get_portfolio_rebalance))
+        .route("/api/portfolio/income", get(get_portfolio_income))
+        .route("/api/portfolio/exposure", get(get_portfolio_exposure))
+        .route("/api/portfolio/summary", get(get_portfolio_summary))
+        .route("/api/portfolio/transactions", get(get_portfolio_transactions).post(add_portfolio_transaction))
+        .route("/api/portfolio/transactions/:transaction_id", delete(delete_portfolio_transaction))
+        .route("/api/portfolio/tax-report", get(get_tax_report))
+        .route("/api/portfolio/settings", get(get_portfolio_settings).put(update_portfolio_settings))
+        .route("/api/portfolio/cash", get(get_portfolio_cash))
+        .route("/api/portfolio/cash/deposit", post(deposit_cash))
+        .route("/api/portfolio/cash/withdraw", post(withdraw_cash))
+        
+        // Saved dashboard layouts
+        .route("/api/dashboards", get(list_dashboards).post(create_dashboard))
+        .route(
+            "/api/dashboards/:dashboard_id",
+            get(get_dashboard).put(update_dashboard).delete(delete_dashboard),
+        )
+
+        // Stored backtest strategies
+        .route("/api/strategies", get(list_strategies).post(create_strategy))
+        .route(
+            "/api/strategies/:strategy_id",
+            get(get_strategy).put(update_strategy).delete(delete_strategy),
+        )
+        .route("/api/strategies/:strategy_id/versions", get(list_strategy_versions))
+        .route("/api/strategies/:strategy_id/backtest", get(backtest_strategy))
+
+        // Trade journal
+        .route("/api/journal", get(list_journal_entries).post(create_journal_entry))
+        .route("/api/journal/export", get(export_journal))
+        .route("/api/journal/tags", get(list_journal_tags))
+        .route(
+            "/api/journal/:entry_id",
+            get(get_journal_entry).put(update_journal_entry).delete(delete_journal_entry),
+        )
+
+        // Background jobs
+        .route("/api/jobs", get(list_jobs).post(submit_job))
+        .route("/api/jobs/:job_id", get(get_job))
+        .route("/api/jobs/:job_id/retry", post(retry_job))
+
+        // Symbol aliases and exchange suffix resolution
+        .route("/api/aliases", get(list_aliases))
+        .route("/api/aliases/:alias", put(set_alias).delete(delete_alias))
+        .route("/api/symbols/:symbol/resolve", get(resolve_symbol))
+        .route("/api/lookup", get(lookup_identifier))
+        // Custom indicator expressions
+        .route("/api/indicators/custom", get(list_custom_indicators))
+        .route(
+            "/api/indicators/custom/:name",
+            put(save_custom_indicator).delete(delete_custom_indicator),
+        )
+        // Alerts on technical signals
+        .route("/api/alerts/signals", get(list_signal_alerts).post(create_signal_alert))
+        .route("/api/alerts/signals/:alert_id", put(update_signal_alert).delete(delete_signal_alert))
+        .route(
+            "/api/alerts/signals/:alert_id/mute",
+            put(mute_signal_alert).delete(unmute_signal_alert),
+        )
+        // Unusual daily moves found by the background analyzer
+        .route("/api/anomalies", get(list_anomalies))
+        // Symbol universes (index constituents)
+        .route("/api/universes", get(list_universes))
+        .route("/api/universes/:name", get(get_universe).delete(delete_universe))
+        .route("/api/universes/:name/import", post(import_universe))
+        // Composite rankings and their factor models
+        .route("/api/rankings", get(get_rankings))
+        .route("/api/rankings/models", get(list_ranking_models))
+        .route(
+            "/api/rankings/models/:name",
+            get(get_ranking_model).put(save_ranking_model).delete(delete_ranking_model),
+        )
+        
+        // Admin endpoints
+        .route("/api/admin/cache/cleanup", post(cleanup_cache))
+        .route("/api/admin/audit", get(get_audit_log))
+        .route("/api/admin/latency", get(get_latency_report))
+        .route("/api/admin/demo", delete(wipe_demo_data))
+        .route("/api/admin/export", get(export_archive))
+        .route("/api/admin/object-exports", get(list_object_exports).post(start_object_export))
+        .route("/api/admin/object-exports/:run_id", get(get_object_export))
+        .route(
+            "/api/admin/import",
+            post(import_archive).layer(DefaultBodyLimit::max(app_state.config.archive.import_max_bytes)),
+        )
+        .route("/api/admin/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/api/admin/webhooks/:webhook_id", put(update_webhook).delete(delete_webhook))
+        .route("/api/admin/webhooks/:webhook_id/deliveries", get(get_webhook_deliveries))
+        .route("/api/admin/webhooks/:webhook_id/test", post(test_webhook))
+        .route("/api/admin/keys", get(list_api_keys).post(create_api_key))
+        .route("/api/admin/keys/:key_id", put(update_api_key).delete(delete_api_key))
+        .route("/api/keys/:key_id/usage", get(get_api_key_usage))
+        .route("/api/admin/notifications", get(get_notifications))
+        .route("/api/admin/notifications/test", post(test_notification));
+
+    // Read-only SQL for ad-hoc analysis (ANALYTICS_SQL_ENABLED)
+    #[cfg(feature = "analytics")]
+    let api_routes = api_routes.route("/api/analytics/sql", post(crate::handlers::analytics_sql));
+
+    let api_routes = api_routes
+        // Inside field selection, which rewrites the envelope with sorted keys
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            http_cache::api_cache_headers_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn(fields::select_fields_middleware))
+        .route_layer(axum::middleware::from_fn(numeric::numeric_mode_middleware))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware::require_api_auth_middleware,
+        ))
+        // Outside auth, which lets a valid key read without a login
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            api_keys::api_key_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            latency::track_route_latency_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn(errors::envelope_errors_middleware))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            limits::rate_limit_headers_middleware,
+        ));
+
+    // Build the application with optimized routes
+    let mut app = Router::<AppState>::new()
+        // Health check
+        .route("/health", get(health_check))
+        
+        // Auth routes (if Tezos auth is enabled)
+        .merge(auth_routes::create_auth_router())
+        
+        // Market data and portfolio API
+        .merge(api_routes);
+        
+    // Add web UI routes if feature is enabled
+    #[cfg(feature = "web-ui")]
+    {
+        use axum::middleware;
+        use tower_http::services::ServeDir;
+        
+        let web_ui_config = &app_state.config.web_ui;
+        if web_ui_config.local_assets {
+            for path in web_ui::missing_vendor_assets(web_ui_config) {
+                tracing::warn!(
+                    "WEB_UI_LOCAL_ASSETS is set but {} is missing; run scripts/fetch-web-assets.sh",
+                    path.display()
+                );
+            }
+        }
+
+        // Serve static files (favicon, vendored CSS/JS, etc.) with proper cache headers
+        let static_files = Router::new()
+            .route("/favicon.svg", get(web_ui::favicon))
+            .route("/favicon.ico", get(web_ui::favicon))
+            .nest_service("/static", ServeDir::new(&web_ui_config.static_dir))
+            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
+        
+        // Create protected routes with auth middleware and cache headers
+        let protected_routes = Router::new()
+            .route("/ui", get(web_ui::dashboard))
+            .route("/ui/search", get(web_ui::search))
+            .route("/ui/analytics", get(web_ui::analytics))
+            .route("/ui/compare", get(web_ui::compare))
+            .route("/ui/symbols/:symbol", get(web_ui::symbol))
+            .route("/ui/backup", get(web_ui::backup))
+            .route("/ui/jobs", get(web_ui::jobs))
+            .route("/ui/journal", get(web_ui::journal))
+            .route("/ui/admin", get(web_ui::admin))
+            .route("/api/backup/download", get(crate::handlers::download_backup))
+            .route("/", get(web_ui::dashboard)) // Root redirects to dashboard
+            .route_layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware::require_auth_middleware,
+            ))
+            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
+        
+        // Public login route with cache headers
+        let login_route = Router::new()
+            .route("/login", get(web_ui::login))
+            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
+        
+        app = app
+            .merge(static_files)
+            .merge(protected_routes)
+            .merge(login_route);
+    }
+        
+    // Add basic API info route when web-ui is disabled
+    #[cfg(not(feature = "web-ui"))]
+    {
+        app = app
+            .route("/", get(|| async { "Mango Data Service API - Use /health for status or /api/* for endpoints" }));
+    }
+        
+    if app_state.config.database.read_only {
+        app = app.layer(axum::middleware::from_fn(read_only::reject_writes_middleware));
+    }
+
+    app
+        // Fallback for 404
+        .fallback(handler_404)
+        
+        // Add middleware
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors)
+        )
+        
+        // Add shared state
+        .with_state(app_state)
+}
+
+/// Spawn the periodic cache cleanup, stats sampling and portfolio price refresh tasks, the
+/// cache bus, the event stream, object storage exports and the job workers on the current
+/// runtime. A read-only instance only gets the tasks that do not write.
+pub fn spawn_background_tasks(service: Arc<YahooFinanceService>, config: &Config) {
+    let read_only = config.database.read_only;
+    if read_only {
+        info!("📖 Read-only instance: job workers, portfolio updates and exports are not started");
+    } else {
+        jobs::spawn_workers(service.clone(), config.jobs.workers);
+    }
+
+    // Start background cache cleanup task
+    let cleanup_service = service.clone();
+    let cleanup_interval = config.cache.cleanup_interval;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(cleanup_interval);
+        loop {
+            interval.tick().await;
+            cleanup_service.cleanup_cache();
+            info!("🧹 Cache cleanup completed");
+        }
+    });
+
+    let mut stats_history = config.stats_history.clone();
+    stats_history.persist &= !read_only;
+    stats_history::spawn(service.clone(), stats_history);
+    cache_bus::spawn(service.clone(), config.cache_bus.clone());
+    event_stream::spawn(service.clone(), config.events.clone());
+    if read_only {
+        return;
+    }
+    object_export::spawn(service.clone(), config.object_export.interval);
+    if config.anomalies.enabled {
+        anomaly::spawn(service.clone(), config.anomalies.clone(), config.notifications.clone());
+    }
+
+    portfolio_updater::spawn(
+        service,
+        config.portfolio_updates.clone(),
+        config.notifications.clone(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn get(uri: &str) -> (StatusCode, serde_json::Value) {
+        get_from(&build_test_app().await.unwrap(), uri).await
+    }
+
+    async fn get_from(app: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn health_check_answers() {
+        let (status, body) = get("/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["status"], "healthy");
+    }
+
+    #[tokio::test]
+    async fn unknown_routes_are_not_found_envelopes() {
+        let (status, body) = get("/api/no-such-route").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn rejected_parameters_carry_their_status() {
+        let (status, body) = get("/api/symbols/AAPL/verify?range=forever").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_REQUEST");
+
+        let (status, body) = get("/api/symbols/AAPL/features?format=xml").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_REQUEST");
+    }
+
+    #[tokio::test]
+    async fn historical_data_is_served_from_the_mock_provider() {
+        let (status, body) = get("/api/symbols/aapl/historical?interval=1d&limit=30").await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let candles = body["data"]["data"].as_array().unwrap();
+        assert_eq!(candles.len(), 30);
+        assert_eq!(body["data"]["count"], 30);
+        assert!(candles.iter().all(|candle| candle["symbol"] == "AAPL"));
+    }
+
+    #[tokio::test]
+    async fn signal_alerts_keep_their_notification_channels() {
+        let app = build_test_app().await.unwrap();
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/api/alerts/signals",
+            serde_json::json!({"symbol": "AAPL", "condition": "golden_cross", "channels": ["sms"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/api/alerts/signals",
+            serde_json::json!({"symbol": "AAPL", "condition": "golden_cross", "channels": ["Telegram"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["data"]["channels"], serde_json::json!(["telegram"]));
+
+        let uri = format!("/api/alerts/signals/{}", body["data"]["id"].as_str().unwrap());
+        let (status, _) = send(&app, "PUT", &uri, serde_json::json!({"channels": ["email", "email"]})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, "PUT", &uri, serde_json::json!({"channels": []})).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = get_from(&app, "/api/alerts/signals").await;
+        assert_eq!(body["data"][0]["channels"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn jwt_token_requests_are_refused_after_repeated_failures() {
+        let credentials = |password: &str| serde_json::json!({"username": "admin", "password": password});
+        let (status, body) = send(&build_test_app().await.unwrap(), "POST", "/auth/jwt/token", credentials("x")).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(body["code"], "FEATURE_DISABLED");
+
+        let mut config = Config::for_testing();
+        config.auth.jwt = Some(crate::config::JwtConfig {
+            keys: crate::jwt::JwtKeys::Hmac(vec![7; 32]),
+            issuer: "pantera-test".to_string(),
+            ttl: std::time::Duration::from_secs(3600),
+            admin_username: "admin".to_string(),
+            admin_password: Some("correct horse".to_string()),
+        });
+        let app = build_app(config, Database::in_memory().await.unwrap()).unwrap();
+
+        let (status, body) = send(&app, "POST", "/auth/jwt/token", credentials("correct horse")).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        for _ in 0..crate::config::MAX_LOGIN_FAILURES {
+            let (status, _) = send(&app, "POST", "/auth/jwt/token", credentials("wrong")).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        // Locked out, even with the right password
+        let (status, body) = send(&app, "POST", "/auth/jwt/token", credentials("correct horse")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "RATE_LIMITED");
+    }
+
+    #[tokio::test]
+    async fn read_only_instances_refuse_writes_as_read_only() {
+        let mut config = Config::for_testing();
+        config.database.read_only = true;
+        let app = build_app(config, Database::in_memory().await.unwrap()).unwrap();
+
+        let (status, body) = send(&app, "POST", "/api/portfolio/holdings", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["code"], "READ_ONLY");
+
+        // Nothing stored to serve, and fetching would store it
+        let (status, body) = get_from(&app, "/api/symbols/AAPL/quote").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", body);
+        assert_eq!(body["code"], "READ_ONLY");
+
+        // Compute-only POSTs still work
+        let (status, _) = send(&app, "POST", "/api/query", serde_json::json!({"query": "RSI for AAPL"})).await;
+        assert_ne!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,192 @@
+//! Portable JSON archive of the database for migrating between backends.
+//!
+//! `GET /api/admin/export` streams every table in [`ARCHIVE_TABLES`] as one JSON document,
+//! each row an object keyed by column name, so the archive does not depend on SQLite's file
+//! format. `POST /api/admin/import` loads such a document back in a single transaction,
+//! either merging with the stored rows or replacing them. Sessions, the audit log, webhooks
+//! (which hold signing secrets), API keys and their usage, notifications, jobs and stats
+//! samples are operational state of one deployment and are left out.
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::database::Database;
+use crate::validation::{FieldErrors, Validate};
+
+pub const ARCHIVE_FORMAT: &str = "mango-data-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Rows read per query while streaming an export
+const EXPORT_PAGE_ROWS: i64 = 1000;
+
+/// Archived tables, parents before the tables whose foreign keys point at them
+pub const ARCHIVE_TABLES: &[&str] = &[
+    "symbols",
+    "historical_prices",
+    "realtime_quotes",
+    "company_profiles",
+    "company_profile_snapshots",
+    "dividends",
+    "security_identifiers",
+    "symbol_aliases",
+    "fx_rates",
+    "macro_observations",
+    "demo_symbols",
+    "universes",
+    "universe_members",
+    "portfolio_settings",
+    "portfolio_holdings",
+    "portfolio_lots",
+    "portfolio_transactions",
+    "cash_flows",
+    "custom_indicators",
+    "signal_alerts",
+    "dashboards",
+    "strategies",
+    "strategy_versions",
+    "journal_entries",
+    "ranking_models",
+    "anomalies",
+];
+
+/// A JSON archive as accepted by the import endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct Archive {
+    pub format: String,
+    pub version: u32,
+    /// Migration version of the exporting database
+    pub schema_version: Option<i64>,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub tables: HashMap<String, Vec<Map<String, Value>>>,
+}
+
+impl Validate for Archive {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.format != ARCHIVE_FORMAT {
+            errors.add("format", format!("must be \"{}\"", ARCHIVE_FORMAT));
+        }
+        if self.version == 0 || self.version > ARCHIVE_VERSION {
+            errors.add("version", format!("must be between 1 and {}", ARCHIVE_VERSION));
+        }
+        let mut unknown: Vec<&str> = self
+            .tables
+            .keys()
+            .map(String::as_str)
+            .filter(|table| !ARCHIVE_TABLES.contains(table))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            errors.add("tables", format!("contains unknown tables: {}", unknown.join(", ")));
+        }
+    }
+}
+
+/// How an import treats rows already in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Keep stored rows; archived rows whose keys already exist are skipped
+    Merge,
+    /// Empty every archived table first
+    Replace,
+}
+
+impl ImportMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "merge" => Some(Self::Merge),
+            "replace" => Some(Self::Replace),
+            _ => None,
+        }
+    }
+}
+
+struct ExportCursor {
+    db: Arc<Database>,
+    /// Document opening, written ahead of the first table
+    header: Option<String>,
+    table: usize,
+    /// Last rowid written from the current table, `None` before its first page
+    after_rowid: Option<i64>,
+    done: bool,
+}
+
+/// The archive as a stream of JSON chunks, one page of rows at a time. A database error ends
+/// the stream early, which aborts the response rather than sending a truncated document.
+pub fn export_stream(
+    db: Arc<Database>,
+    schema_version: Option<i64>,
+    exported_at: DateTime<Utc>,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    let header = serde_json::json!({
+        "format": ARCHIVE_FORMAT,
+        "version": ARCHIVE_VERSION,
+        "schema_version": schema_version,
+        "exported_at": exported_at,
+    })
+    .to_string();
+    // Reopen the object so the tables follow the metadata
+    let header = format!("{},\"tables\":{{", header.trim_end_matches('}'));
+
+    let cursor = ExportCursor {
+        db,
+        header: Some(header),
+        table: 0,
+        after_rowid: None,
+        done: false,
+    };
+
+    futures::stream::unfold(cursor, |mut cursor| async move {
+        if cursor.done {
+            return None;
+        }
+        let mut chunk = cursor.header.take().unwrap_or_default();
+
+        let Some(table) = ARCHIVE_TABLES.get(cursor.table) else {
+            chunk.push_str("}}");
+            cursor.done = true;
+            return Some((Ok(Bytes::from(chunk)), cursor));
+        };
+
+        let page = cursor
+            .db
+            .export_table_page(table, cursor.after_rowid.unwrap_or(i64::MIN), EXPORT_PAGE_ROWS)
+            .await;
+        let rows = match page {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Error exporting table {}: {:?}", table, e);
+                cursor.done = true;
+                return Some((Err(std::io::Error::other(e.to_string())), cursor));
+            }
+        };
+
+        if cursor.after_rowid.is_none() {
+            if cursor.table > 0 {
+                chunk.push(',');
+            }
+            chunk.push_str(&format!("\"{}\":[", table));
+        }
+        let last_page = (rows.len() as i64) < EXPORT_PAGE_ROWS;
+        for (rowid, row) in rows {
+            if cursor.after_rowid.is_some() {
+                chunk.push(',');
+            }
+            chunk.push_str(&Value::Object(row).to_string());
+            cursor.after_rowid = Some(rowid);
+        }
+
+        if last_page {
+            chunk.push(']');
+            cursor.table += 1;
+            cursor.after_rowid = None;
+        }
+        Some((Ok(Bytes::from(chunk)), cursor))
+    })
+}
@@ -0,0 +1,73 @@
+//! Audit trail for logins, admin actions and mutating API requests.
+//!
+//! Recording never fails the request being audited; storage errors are only logged.
+
+use axum::http::HeaderMap;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::auth::AdminAuth;
+use crate::config::AUDIT_SUMMARY_MAX_CHARS;
+use crate::database::Database;
+use crate::handlers::get_client_id;
+use crate::models::AuditEntry;
+
+/// Actor type and identifier for an authenticated caller, falling back to the client IP
+pub fn actor(auth: &AdminAuth, headers: &HeaderMap) -> (&'static str, String) {
+    if let Some(address) = &auth.tezos_admin_address {
+        ("tezos", address.clone())
+    } else if let Some(email) = &auth.oidc_email {
+        ("oidc", email.clone())
+    } else if let Some(subject) = &auth.jwt_subject {
+        ("jwt", subject.clone())
+    } else if auth.is_dev_admin {
+        ("dev", "dev".to_string())
+    } else {
+        ("anonymous", get_client_id(headers))
+    }
+}
+
+pub fn entry(
+    (actor_type, actor): (&str, String),
+    action: &str,
+    method: &str,
+    path: &str,
+    status_code: u16,
+    summary: Option<String>,
+) -> AuditEntry {
+    AuditEntry {
+        id: Uuid::new_v4(),
+        actor_type: actor_type.to_string(),
+        actor,
+        action: action.to_string(),
+        method: method.to_string(),
+        path: path.to_string(),
+        status_code,
+        summary,
+        created_at: Utc::now(),
+    }
+}
+
+pub async fn record(db: &Database, entry: AuditEntry) {
+    // Only the writable instance keeps an audit log
+    if db.is_read_only() {
+        return;
+    }
+    if let Err(e) = db.insert_audit_entry(&entry).await {
+        tracing::error!("Failed to record audit entry for {} {}: {}", entry.method, entry.path, e);
+    }
+}
+
+/// Request body as a single whitespace-collapsed line, truncated
+pub fn summarize_payload(body: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(body);
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return None;
+    }
+    if collapsed.chars().count() <= AUDIT_SUMMARY_MAX_CHARS {
+        return Some(collapsed);
+    }
+    let truncated: String = collapsed.chars().take(AUDIT_SUMMARY_MAX_CHARS).collect();
+    Some(format!("{}…", truncated))
+}
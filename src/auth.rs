@@ -1,23 +1,28 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 // Struct to hold admin status
 #[derive(Clone, Debug)]
 pub struct AdminAuth {
     pub is_dev_admin: bool,
     pub tezos_admin_address: Option<String>,
+    pub jwt_subject: Option<String>,
+    pub oidc_email: Option<String>,
 }
 
 impl AdminAuth {
     pub fn is_admin(&self) -> bool {
-        self.is_dev_admin || self.tezos_admin_address.is_some()
+        self.is_dev_admin || self.tezos_admin_address.is_some() || self.jwt_subject.is_some() || self.oidc_email.is_some()
     }
     
     /// Create a non-admin auth for public routes
-    #[allow(dead_code)]
     pub fn public() -> Self {
         Self {
             is_dev_admin: false,
             tezos_admin_address: None,
+            jwt_subject: None,
+            oidc_email: None,
         }
     }
 }
@@ -26,6 +31,25 @@ impl AdminAuth {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TezosAdminSession {
     pub address: String,
+    pub session_id: Uuid,
 }
 
- 
\ No newline at end of file
+// Session data for an admin signed in through an OIDC provider
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OidcAdminSession {
+    pub provider: String,
+    pub email: String,
+    pub session_id: Uuid,
+}
+
+// Server-side record of an admin session; cookies reference it by id so it can be revoked
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdminSession {
+    pub id: Uuid,
+    pub method: String, // "tezos" or "oidc"
+    pub subject: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
@@ -1,22 +1,24 @@
 use axum::{
     response::{IntoResponse, Json, Response},
-    extract::{State, Extension},
-    http::StatusCode,
+    extract::State,
+    http::{HeaderMap, StatusCode},
 };
 use axum_extra::extract::{CookieJar, cookie::{Cookie, SameSite}};
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 use uuid::Uuid;
-use time;
 
 use crate::{
     errors::AppError,
     handlers::AppState,
-    auth::{TezosAdminSession, AdminAuth},
+    auth::{AdminSession, OidcAdminSession, TezosAdminSession},
+    oidc::PendingLogin,
+    auth_middleware::extract_admin_auth,
+    audit,
+    validation::{OptionalValidJson, ValidJson, Validate},
 };
 
 // --- New Crypto & Encoding Crates ---
-use bs58;
 use blake2::{Blake2b, Digest as CryptoDigest};
 use generic_array::GenericArray;
 use signature::Verifier;
@@ -60,8 +62,6 @@ mod tezos_consts {
 }
 
 use tezos_consts::*;
-#[allow(unused_imports)] // sha2::Digest is used for .finalize() trait method
-use sha2::Digest as Sha2Digest;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -78,42 +78,34 @@ fn sign_session_cookie(session_json: &str, hmac_key: &[u8; 32]) -> String {
     format!("{}.{}", encoded_data, encoded_sig)
 }
 
-/// Verify and decode a signed session cookie.
-/// Returns None if cookie is invalid or tampered with.
-pub fn verify_session_cookie(cookie_value: &str, hmac_key: &[u8; 32]) -> Option<TezosAdminSession> {
+/// Verify and decode a value signed with `sign_session_cookie`.
+/// Returns None if the value is invalid or tampered with.
+fn verify_signed_cookie<T: serde::de::DeserializeOwned>(cookie_value: &str, hmac_key: &[u8; 32]) -> Option<T> {
     // Split on the last dot (data.signature format)
-    match cookie_value.rfind('.') {
-        Some(dot_pos) => {
-            let encoded_data = &cookie_value[..dot_pos];
-            let encoded_sig = &cookie_value[dot_pos + 1..];
-            
-            // Decode signature
-            let expected_sig_bytes = base64::engine::general_purpose::STANDARD.decode(encoded_sig).ok()?;
-            
-            // Verify HMAC
-            let mut mac = HmacSha256::new_from_slice(hmac_key)
-                .expect("HMAC can take key of any size");
-            mac.update(encoded_data.as_bytes());
-            mac.verify_slice(&expected_sig_bytes).ok()?;
-            
-            // Decode session data
-            let session_bytes = base64::engine::general_purpose::STANDARD.decode(encoded_data).ok()?;
-            let session_str = String::from_utf8(session_bytes).ok()?;
-            serde_json::from_str::<TezosAdminSession>(&session_str).ok()
-        }
-        None => {
-            // Legacy format: try to decode as plain base64 (backward compatibility)
-            // This allows existing cookies to still work during transition
-            if let Ok(session_bytes) = base64::engine::general_purpose::STANDARD.decode(cookie_value) {
-                if let Ok(session_str) = String::from_utf8(session_bytes) {
-                    if let Ok(session) = serde_json::from_str::<TezosAdminSession>(&session_str) {
-                        return Some(session);
-                    }
-                }
-            }
-            None
-        }
-    }
+    let dot_pos = cookie_value.rfind('.')?;
+    let encoded_data = &cookie_value[..dot_pos];
+    let encoded_sig = &cookie_value[dot_pos + 1..];
+
+    // Decode signature
+    let expected_sig_bytes = base64::engine::general_purpose::STANDARD.decode(encoded_sig).ok()?;
+
+    // Verify HMAC
+    let mut mac = HmacSha256::new_from_slice(hmac_key)
+        .expect("HMAC can take key of any size");
+    mac.update(encoded_data.as_bytes());
+    mac.verify_slice(&expected_sig_bytes).ok()?;
+
+    // Decode session data
+    let session_bytes = base64::engine::general_purpose::STANDARD.decode(encoded_data).ok()?;
+    let session_str = String::from_utf8(session_bytes).ok()?;
+    serde_json::from_str::<T>(&session_str).ok()
+}
+
+/// Verify and decode a signed Tezos session cookie.
+/// Returns None if cookie is invalid or tampered with. Cookies from before server-side
+/// sessions carry no session id and no longer decode, so those admins sign in again.
+pub fn verify_session_cookie(cookie_value: &str, hmac_key: &[u8; 32]) -> Option<TezosAdminSession> {
+    verify_signed_cookie(cookie_value, hmac_key)
 }
 
 // Helper for Base58Check decoding with prefix validation
@@ -174,12 +166,6 @@ fn b58_encode_with_prefix(payload: &[u8], prefix: &[u8]) -> String {
     bs58::encode(data_to_encode).into_string()
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChallengePayload {
-    challenge: String,
-    packed_bytes_hex: String,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct TezosLoginPayload {
     pkh: String,
@@ -188,6 +174,9 @@ pub struct TezosLoginPayload {
     challenge: String,
 }
 
+// Keys and signatures are checked when the signature is verified
+impl Validate for TezosLoginPayload {}
+
 #[derive(Serialize)]
 pub struct ChallengeResponse {
     challenge: String,
@@ -311,8 +300,9 @@ pub async fn get_tezos_challenge(
 /// Verifies the signed Tezos challenge and logs the user in.
 pub async fn tezos_login(
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     jar: CookieJar,
-    Json(payload): Json<TezosLoginPayload>,
+    ValidJson(payload): ValidJson<TezosLoginPayload>,
 ) -> Result<(CookieJar, Response), AppError>
 {
     tracing::info!("Attempting Tezos login for PKH: {}", payload.pkh);
@@ -341,6 +331,7 @@ pub async fn tezos_login(
         Ok(true) => tracing::info!("Tezos signature VERIFIED for PKH: {}", payload.pkh),
         Ok(false) => {
             tracing::warn!("Tezos signature verification FAILED for PKH: {}", payload.pkh);
+            audit_auth_event(&app_state, ("tezos", payload.pkh.clone()), "login", "/auth/tezos/login", StatusCode::UNAUTHORIZED, Some("invalid signature")).await;
             return Err(AppError::Unauthorized);
         }
         Err(e) => {
@@ -356,43 +347,413 @@ pub async fn tezos_login(
 
     if !app_state.config.auth.admin_tezos_addresses.contains(&payload.pkh) { // Check against pkh (address)
         tracing::warn!("PKH {} is not an admin address.", payload.pkh);
+        audit_auth_event(&app_state, ("tezos", payload.pkh.clone()), "login", "/auth/tezos/login", StatusCode::UNAUTHORIZED, Some("not an admin address")).await;
         return Err(AppError::Unauthorized);
     }
 
-    let session_data = TezosAdminSession { address: payload.pkh.clone() }; // Store PKH in session
+    let admin_session = start_admin_session(&app_state, "tezos", &payload.pkh, &headers).await?;
+    let session_data = TezosAdminSession { address: payload.pkh.clone(), session_id: admin_session.id }; // Store PKH in session
     let session_json = serde_json::to_string(&session_data)
         .map_err(|e| AppError::Internal(format!("Serialize session error: {}", e)))?;
     
     // Sign the session cookie with HMAC-SHA256
     let signed_cookie_value = sign_session_cookie(&session_json, &app_state.config.auth.cookie_hmac_key);
-
-    let cookie_std_duration = std::time::Duration::from_secs(3600 * 24 * 7); // 7 days
-    let cookie_time_duration: time::Duration = cookie_std_duration.try_into()
-        .map_err(|_| AppError::Internal("Failed to convert duration for cookie.".to_string()))?;
-
-    let mut cookie = Cookie::new("tezos_admin_session", signed_cookie_value);
-    cookie.set_path("/");
-    cookie.set_http_only(true);
-    cookie.set_secure(true); // Ensure this is true for production
-    cookie.set_same_site(SameSite::Lax);
-    cookie.set_max_age(cookie_time_duration);
+    let cookie = session_cookie(TEZOS_SESSION_COOKIE, signed_cookie_value, SESSION_TTL)?;
 
     tracing::info!("Setting admin session cookie for PKH: {}", payload.pkh);
     let updated_jar = jar.add(cookie);
     let response_body = (StatusCode::OK, Json("Login successful")).into_response();
+    audit_auth_event(&app_state, ("tezos", payload.pkh.clone()), "login", "/auth/tezos/login", StatusCode::OK, None).await;
     tracing::info!("Tezos login completed successfully for PKH: {}", payload.pkh);
     Ok((updated_jar, response_body))
 }
 
+#[derive(Deserialize)]
+pub struct JwtTokenRequest {
+    pub username: String,
+    pub password: String,
+}
+
+impl Validate for JwtTokenRequest {}
+
+#[derive(Serialize)]
+pub struct JwtTokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+}
+
+/// Issues a bearer token to the configured JWT admin. Clients and usernames with too many
+/// recent failures are refused before the password is checked.
+pub async fn issue_jwt_token(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    ValidJson(payload): ValidJson<JwtTokenRequest>,
+) -> Result<Json<JwtTokenResponse>, AppError> {
+    let jwt_config = app_state.config.auth.jwt.as_ref()
+        .ok_or_else(|| AppError::FeatureDisabled("JWT auth is not enabled".to_string()))?;
+    let admin_password = jwt_config.admin_password.as_ref()
+        .filter(|_| jwt_config.keys.can_sign())
+        .ok_or_else(|| AppError::FeatureDisabled("JWT token issuance is not configured".to_string()))?;
+
+    let attempt_keys = [
+        format!("client:{}", crate::handlers::get_client_id(&headers)),
+        format!("user:{}", payload.username.to_lowercase()),
+    ];
+    if let Some(retry_after) = app_state.service.login_lockout(&attempt_keys).await {
+        tracing::warn!("Refused JWT token request for user {} after repeated failures", payload.username);
+        audit_auth_event(&app_state, ("jwt", payload.username.clone()), "token", "/auth/jwt/token", StatusCode::TOO_MANY_REQUESTS, Some("too many failed attempts")).await;
+        return Err(AppError::TooManyAttempts { retry_after });
+    }
+
+    // Compare MACs of both sides so the check takes the same time regardless of where they differ
+    let username_ok = constant_time_eq(&payload.username, &jwt_config.admin_username, &app_state.config.auth.cookie_hmac_key);
+    let password_ok = constant_time_eq(&payload.password, admin_password, &app_state.config.auth.cookie_hmac_key);
+    if !(username_ok && password_ok) {
+        tracing::warn!("Rejected JWT token request for user: {}", payload.username);
+        app_state.service.record_login_failure(&attempt_keys).await;
+        audit_auth_event(&app_state, ("jwt", payload.username.clone()), "token", "/auth/jwt/token", StatusCode::UNAUTHORIZED, Some("invalid credentials")).await;
+        return Err(AppError::Unauthorized);
+    }
+    app_state.service.clear_login_failures(&attempt_keys).await;
+
+    let now = chrono::Utc::now().timestamp();
+    let claims = crate::jwt::Claims {
+        sub: jwt_config.admin_username.clone(),
+        iss: jwt_config.issuer.clone(),
+        iat: now,
+        exp: now + jwt_config.ttl.as_secs() as i64,
+        role: Some(crate::jwt::ADMIN_ROLE.to_string()),
+    };
+    let access_token = crate::jwt::encode(&claims, &jwt_config.keys)
+        .map_err(|e| AppError::Internal(format!("Failed to sign JWT: {}", e)))?;
+
+    audit_auth_event(&app_state, ("jwt", claims.sub.clone()), "token", "/auth/jwt/token", StatusCode::OK, None).await;
+    tracing::info!("Issued JWT for {}", claims.sub);
+    Ok(Json(JwtTokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: jwt_config.ttl.as_secs(),
+    }))
+}
+
+fn constant_time_eq(a: &str, b: &str, key: &[u8; 32]) -> bool {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(a.as_bytes());
+    let mut expected = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    expected.update(b.as_bytes());
+    mac.verify_slice(&expected.finalize().into_bytes()).is_ok()
+}
+
+/// Returns the claims of a valid `Authorization: Bearer` token when JWT auth is enabled.
+/// Only tokens with the admin role are accepted: another service sharing the issuer and
+/// keys may sign tokens for its own users.
+pub fn verify_bearer_token(app_state: &AppState, headers: &HeaderMap) -> Option<crate::jwt::Claims> {
+    let jwt_config = app_state.config.auth.jwt.as_ref()?;
+    let token = headers
+        .get(axum::http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+
+    match crate::jwt::decode(token.trim(), &jwt_config.keys, &jwt_config.issuer, chrono::Utc::now().timestamp()) {
+        Ok(claims) if claims.is_admin() => Some(claims),
+        Ok(claims) => {
+            tracing::debug!("Rejected bearer token of {} without the admin role", claims.sub);
+            None
+        }
+        Err(e) => {
+            tracing::debug!("Rejected bearer token: {}", e);
+            None
+        }
+    }
+}
+
+const TEZOS_SESSION_COOKIE: &str = "tezos_admin_session";
+const OIDC_SESSION_COOKIE: &str = "oidc_admin_session";
+const OIDC_PENDING_COOKIE: &str = "oidc_pending_login";
+const SESSION_TTL: std::time::Duration = std::time::Duration::from_secs(3600 * 24 * 7); // 7 days
+
+/// Writes a login, token or session event to the audit log.
+async fn audit_auth_event(
+    app_state: &AppState,
+    actor: (&str, String),
+    action: &str,
+    path: &str,
+    status: StatusCode,
+    summary: Option<&str>,
+) {
+    // Every auth route is a POST except the provider redirect back to the OIDC callback
+    let method = if path.ends_with("/callback") { "GET" } else { "POST" };
+    let entry = audit::entry(actor, action, method, path, status.as_u16(), summary.map(str::to_string));
+    audit::record(&app_state.service.db, entry).await;
+}
+
+/// Records a new server-side session that the login cookie will reference.
+async fn start_admin_session(
+    app_state: &AppState,
+    method: &str,
+    subject: &str,
+    headers: &HeaderMap,
+) -> Result<AdminSession, AppError> {
+    let now = chrono::Utc::now();
+    let session = AdminSession {
+        id: Uuid::new_v4(),
+        method: method.to_string(),
+        subject: subject.to_string(),
+        user_agent: headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|ua| ua.chars().take(256).collect()),
+        created_at: now,
+        expires_at: now + chrono::Duration::seconds(SESSION_TTL.as_secs() as i64),
+        revoked_at: None,
+    };
+
+    let db = &app_state.service.db;
+    if let Err(e) = db.delete_expired_admin_sessions(now).await {
+        tracing::warn!("Failed to prune expired admin sessions: {}", e);
+    }
+    db.create_admin_session(&session).await
+        .map_err(|e| AppError::Internal(format!("Failed to store session: {}", e)))?;
+    Ok(session)
+}
+
+/// Whether the server-side session behind a cookie is still active.
+async fn session_is_active(app_state: &AppState, session_id: Uuid) -> bool {
+    match app_state.service.db.get_active_admin_session(session_id, chrono::Utc::now()).await {
+        Ok(session) => session.is_some(),
+        Err(e) => {
+            tracing::error!("Failed to look up admin session {}: {}", session_id, e);
+            false
+        }
+    }
+}
+
+/// Returns the Tezos session if its cookie is valid, the address is still an admin
+/// and the session has not been revoked.
+pub async fn verify_tezos_session(app_state: &AppState, jar: &CookieJar) -> Option<TezosAdminSession> {
+    let cookie = jar.get(TEZOS_SESSION_COOKIE)?;
+    let session = verify_session_cookie(cookie.value(), &app_state.config.auth.cookie_hmac_key)?;
+    if !app_state.config.auth.admin_tezos_addresses.contains(&session.address) {
+        return None;
+    }
+    session_is_active(app_state, session.session_id).await.then_some(session)
+}
+
+/// Returns the OIDC session if its cookie is valid, the email is still an admin and the
+/// session has not been revoked.
+pub async fn verify_oidc_session(app_state: &AppState, jar: &CookieJar) -> Option<OidcAdminSession> {
+    let oidc_config = app_state.config.auth.oidc.as_ref()?;
+    let cookie = jar.get(OIDC_SESSION_COOKIE)?;
+    let session: OidcAdminSession = verify_signed_cookie(cookie.value(), &app_state.config.auth.cookie_hmac_key)?;
+    if !oidc_config.admin_emails.contains(&session.email) {
+        return None;
+    }
+    session_is_active(app_state, session.session_id).await.then_some(session)
+}
+
+/// Session id referenced by whichever session cookie the request carries.
+fn current_session_id(app_state: &AppState, jar: &CookieJar) -> Option<Uuid> {
+    let hmac_key = &app_state.config.auth.cookie_hmac_key;
+    jar.get(TEZOS_SESSION_COOKIE)
+        .and_then(|c| verify_session_cookie(c.value(), hmac_key))
+        .map(|s| s.session_id)
+        .or_else(|| {
+            jar.get(OIDC_SESSION_COOKIE)
+                .and_then(|c| verify_signed_cookie::<OidcAdminSession>(c.value(), hmac_key))
+                .map(|s| s.session_id)
+        })
+}
+
+fn session_cookie(name: &'static str, value: String, max_age: std::time::Duration) -> Result<Cookie<'static>, AppError> {
+    let max_age: time::Duration = max_age.try_into()
+        .map_err(|_| AppError::Internal("Failed to convert duration for cookie.".to_string()))?;
+    let mut cookie = Cookie::new(name, value);
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    // Lax so the cookie survives the top-level redirect back from the provider
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_max_age(max_age);
+    Ok(cookie)
+}
+
+/// Starts an OIDC login by redirecting to the provider's consent screen.
+pub async fn oidc_login(
+    State(app_state): State<AppState>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Response), AppError> {
+    let oidc_config = app_state.config.auth.oidc.as_ref()
+        .ok_or_else(|| AppError::FeatureDisabled("OIDC login is not enabled".to_string()))?;
+
+    let pending = PendingLogin::generate();
+    let url = crate::oidc::authorization_url(oidc_config, &pending).await
+        .map_err(AppError::Internal)?;
+
+    let pending_json = serde_json::to_string(&pending)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize login state: {}", e)))?;
+    let cookie = session_cookie(
+        OIDC_PENDING_COOKIE,
+        sign_session_cookie(&pending_json, &app_state.config.auth.cookie_hmac_key),
+        std::time::Duration::from_secs(600),
+    )?;
+
+    Ok((jar.add(cookie), axum::response::Redirect::to(&url).into_response()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// Completes an OIDC login and issues the admin session cookie.
+pub async fn oidc_callback(
+    State(app_state): State<AppState>,
+    axum::extract::Query(params): axum::extract::Query<OidcCallbackParams>,
+    headers: HeaderMap,
+    jar: CookieJar,
+) -> Result<(CookieJar, Response), AppError> {
+    let oidc_config = app_state.config.auth.oidc.as_ref()
+        .ok_or_else(|| AppError::FeatureDisabled("OIDC login is not enabled".to_string()))?;
+
+    if let Some(error) = params.error {
+        return Err(AppError::ValidationError(format!("Provider returned an error: {}", error)));
+    }
+
+    let pending: PendingLogin = jar.get(OIDC_PENDING_COOKIE)
+        .and_then(|c| verify_signed_cookie(c.value(), &app_state.config.auth.cookie_hmac_key))
+        .ok_or_else(|| AppError::ValidationError("Login session expired. Please try again.".to_string()))?;
+    if params.state.as_deref() != Some(pending.state.as_str()) {
+        return Err(AppError::ValidationError("Login state mismatch".to_string()));
+    }
+    let code = params.code
+        .ok_or_else(|| AppError::ValidationError("Missing authorization code".to_string()))?;
+
+    let email = crate::oidc::verified_email(oidc_config, &pending, &code).await
+        .map_err(|e| {
+            tracing::warn!("OIDC login failed: {}", e);
+            AppError::Unauthorized
+        })?;
+    if !oidc_config.admin_emails.contains(&email) {
+        tracing::warn!("OIDC login rejected for non-admin email: {}", email);
+        audit_auth_event(&app_state, ("oidc", email), "login", "/auth/oidc/callback", StatusCode::UNAUTHORIZED, Some("not an admin email")).await;
+        return Err(AppError::Unauthorized);
+    }
+
+    let admin_session = start_admin_session(&app_state, "oidc", &email, &headers).await?;
+    let session = OidcAdminSession {
+        provider: oidc_config.provider.as_str().to_string(),
+        email,
+        session_id: admin_session.id,
+    };
+    let session_json = serde_json::to_string(&session)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize session: {}", e)))?;
+    let cookie = session_cookie(
+        OIDC_SESSION_COOKIE,
+        sign_session_cookie(&session_json, &app_state.config.auth.cookie_hmac_key),
+        SESSION_TTL,
+    )?;
+
+    audit_auth_event(&app_state, ("oidc", session.email.clone()), "login", "/auth/oidc/callback", StatusCode::SEE_OTHER, None).await;
+    tracing::info!("Setting OIDC admin session cookie for {}", session.email);
+    let mut pending_cookie = Cookie::new(OIDC_PENDING_COOKIE, "");
+    pending_cookie.set_path("/");
+    let updated_jar = jar.remove(pending_cookie).add(cookie);
+    Ok((updated_jar, axum::response::Redirect::to("/ui").into_response()))
+}
+
+#[derive(Serialize)]
+pub struct AdminSessionList {
+    pub current_session_id: Option<Uuid>,
+    pub sessions: Vec<AdminSession>,
+}
+
+/// Lists active admin sessions (admin only).
+pub async fn list_sessions(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+) -> Result<Json<AdminSessionList>, AppError> {
+    if !extract_admin_auth(&app_state, &headers, &jar).await.is_admin() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let sessions = app_state.service.db.get_active_admin_sessions(chrono::Utc::now()).await
+        .map_err(|e| AppError::Internal(format!("Failed to list sessions: {}", e)))?;
+
+    Ok(Json(AdminSessionList {
+        current_session_id: current_session_id(&app_state, &jar),
+        sessions,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RevokeSessionsRequest {
+    /// Session to revoke; defaults to the caller's own session
+    pub session_id: Option<Uuid>,
+    /// Revoke every active session
+    #[serde(default)]
+    pub all: bool,
+}
+
+impl Validate for RevokeSessionsRequest {}
+
+/// Revokes one, the current or all admin sessions (admin only).
+pub async fn revoke_sessions(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    OptionalValidJson(payload): OptionalValidJson<RevokeSessionsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let admin_auth = extract_admin_auth(&app_state, &headers, &jar).await;
+    if !admin_auth.is_admin() {
+        return Err(AppError::Unauthorized);
+    }
+    let request = payload.unwrap_or_default();
+
+    let target = if request.all {
+        None
+    } else {
+        let session_id = request.session_id
+            .or_else(|| current_session_id(&app_state, &jar))
+            .ok_or_else(|| AppError::ValidationError("session_id is required when not signed in with a session cookie".to_string()))?;
+        Some(session_id)
+    };
+
+    let revoked = app_state.service.db.revoke_admin_sessions(target, chrono::Utc::now()).await
+        .map_err(|e| AppError::Internal(format!("Failed to revoke sessions: {}", e)))?;
+
+    let summary = match target {
+        Some(session_id) => format!("revoked session {}", session_id),
+        None => format!("revoked all {} sessions", revoked),
+    };
+    audit_auth_event(&app_state, audit::actor(&admin_auth, &headers), "session.revoke", "/auth/sessions/revoke", StatusCode::OK, Some(&summary)).await;
+    tracing::info!("Revoked {} admin session(s)", revoked);
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
 /// Logs the admin out by clearing the session cookie.
 pub async fn logout(
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
     jar: CookieJar 
 ) -> Result<(CookieJar, Response), AppError> { 
-    tracing::info!("Logging out Tezos admin.");
-    let mut cookie = Cookie::new("tezos_admin_session", "");
+    tracing::info!("Logging out admin.");
+    let admin_auth = extract_admin_auth(&app_state, &headers, &jar).await;
+    audit_auth_event(&app_state, audit::actor(&admin_auth, &headers), "logout", "/auth/logout", StatusCode::SEE_OTHER, None).await;
+    if let Some(session_id) = current_session_id(&app_state, &jar) {
+        if let Err(e) = app_state.service.db.revoke_admin_sessions(Some(session_id), chrono::Utc::now()).await {
+            tracing::error!("Failed to revoke session {} on logout: {}", session_id, e);
+        }
+    }
+    let mut cookie = Cookie::new(TEZOS_SESSION_COOKIE, "");
     cookie.set_path("/");
-    let updated_jar = jar.remove(cookie);
+    let mut oidc_cookie = Cookie::new(OIDC_SESSION_COOKIE, "");
+    oidc_cookie.set_path("/");
+    let updated_jar = jar.remove(cookie).remove(oidc_cookie);
     let response_body = axum::response::Redirect::to("/login").into_response();
     Ok((updated_jar, response_body))
 }
@@ -400,22 +761,30 @@ pub async fn logout(
 /// Public endpoint to check authentication status (for frontend)
 pub async fn auth_status(
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     jar: CookieJar,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let mut status = serde_json::json!({
         "authenticated": false,
         "dev_mode": app_state.config.auth.dev_mode,
+        "jwt_enabled": app_state.config.auth.jwt.is_some(),
+        "oidc_provider": app_state.config.auth.oidc.as_ref().map(|o| o.provider.as_str()),
     });
 
+    if let Some(claims) = verify_bearer_token(&app_state, &headers) {
+        status["authenticated"] = serde_json::Value::Bool(true);
+        status["jwt_subject"] = serde_json::Value::String(claims.sub);
+    }
+
     // Check for valid session cookie
-    if let Some(cookie) = jar.get("tezos_admin_session") {
-        if let Some(session) = verify_session_cookie(cookie.value(), &app_state.config.auth.cookie_hmac_key) {
-            // Verify the address is still in admin list
-            if app_state.config.auth.admin_tezos_addresses.contains(&session.address) {
-                status["authenticated"] = serde_json::Value::Bool(true);
-                status["is_admin_address"] = serde_json::Value::Bool(true);
-            }
-        }
+    if verify_tezos_session(&app_state, &jar).await.is_some() {
+        status["authenticated"] = serde_json::Value::Bool(true);
+        status["is_admin_address"] = serde_json::Value::Bool(true);
+    }
+
+    if let Some(session) = verify_oidc_session(&app_state, &jar).await {
+        status["authenticated"] = serde_json::Value::Bool(true);
+        status["oidc_email"] = serde_json::Value::String(session.email);
     }
 
     // If dev mode is enabled, user is considered authenticated
@@ -441,7 +810,7 @@ pub async fn debug_auth_status(
         "admin_addresses": app_state.config.auth.admin_tezos_addresses.iter().collect::<Vec<_>>()
     });
 
-    if let Some(cookie) = jar.get("tezos_admin_session") {
+    if let Some(cookie) = jar.get(TEZOS_SESSION_COOKIE) {
         debug_info["has_cookie"] = serde_json::Value::Bool(true);
         
         if let Some(session) = verify_session_cookie(cookie.value(), &app_state.config.auth.cookie_hmac_key) {
@@ -450,6 +819,9 @@ pub async fn debug_auth_status(
             debug_info["is_admin_address"] = serde_json::Value::Bool(
                 app_state.config.auth.admin_tezos_addresses.contains(&session.address)
             );
+            debug_info["session_active"] = serde_json::Value::Bool(
+                session_is_active(&app_state, session.session_id).await
+            );
         }
     }
 
@@ -1,14 +1,27 @@
 use axum::{
+    body::Body,
     extract::{Request, State},
+    http::{HeaderMap, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::CookieJar;
 use crate::handlers::AppState;
-use crate::auth_handler::verify_session_cookie;
+use crate::api_keys::ApiKeyAuth;
+use crate::auth_handler::{verify_bearer_token, verify_oidc_session, verify_tezos_session};
 use crate::auth::AdminAuth;
+use crate::audit;
+use crate::config::MAX_AUDITED_BODY_BYTES;
+use crate::errors::AppError;
 
-/// Middleware to check if Tezos auth is enabled and user is authenticated
+/// POST routes that only read data; they follow the rules for GET requests
+const READ_ONLY_POST_PREFIXES: &[&str] = &["/api/tools/", "/api/query"];
+
+/// Mutating routes whose bodies may exceed MAX_AUDITED_BODY_BYTES; they are audited without
+/// a payload summary
+const UNBUFFERED_PATHS: &[&str] = &["/api/admin/import"];
+
+/// Middleware to check if Tezos, JWT or OIDC auth is enabled and user is authenticated
 pub async fn require_auth_middleware(
     State(app_state): State<AppState>,
     jar: CookieJar,
@@ -16,22 +29,22 @@ pub async fn require_auth_middleware(
     next: Next,
 ) -> Response {
     // If auth is not enabled, allow access
-    if !app_state.config.auth.enable_tezos_auth {
+    if !app_state.config.auth.is_enabled() {
         return next.run(request).await;
     }
 
-    // Check for valid session cookie
-    if let Some(cookie) = jar.get("tezos_admin_session") {
-        if let Some(session) = verify_session_cookie(
-            cookie.value(),
-            &app_state.config.auth.cookie_hmac_key,
-        ) {
-            // Verify the address is still in admin list
-            if app_state.config.auth.admin_tezos_addresses.contains(&session.address) {
-                // Valid session, allow access
-                return next.run(request).await;
-            }
-        }
+    // API clients authenticate with a bearer token instead of the session cookie
+    if verify_bearer_token(&app_state, request.headers()).is_some() {
+        return next.run(request).await;
+    }
+
+    // Check for a valid, unrevoked session cookie
+    if verify_tezos_session(&app_state, &jar).await.is_some() {
+        return next.run(request).await;
+    }
+
+    if verify_oidc_session(&app_state, &jar).await.is_some() {
+        return next.run(request).await;
     }
 
     // Check if dev mode is enabled (bypass auth)
@@ -43,30 +56,113 @@ pub async fn require_auth_middleware(
     Redirect::to("/login").into_response()
 }
 
+/// Middleware for /api routes. When auth is enabled, mutating requests and /api/admin
+/// routes need an admin session or bearer token; other reads do too unless
+/// PUBLIC_READ_API is left on or they come with a valid API key. Admin API keys count as
+/// an admin. Every mutating request is written to the audit log.
+pub async fn require_api_auth_middleware(
+    State(app_state): State<AppState>,
+    jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let auth = &app_state.config.auth;
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+        || (request.method() == Method::POST
+            && READ_ONLY_POST_PREFIXES
+                .iter()
+                .any(|prefix| request.uri().path().starts_with(prefix)));
+    let is_admin_route = request.uri().path().starts_with("/api/admin/");
+    // A valid API key stands in for a login when reads need one, and an admin key for an admin
+    let api_key = request.extensions().get::<ApiKeyAuth>().copied();
+    let needs_admin =
+        auth.is_enabled() && (!is_read || is_admin_route || (!auth.public_read_api && api_key.is_none()));
+
+    if is_read && !needs_admin {
+        return next.run(request).await;
+    }
+
+    let admin_auth = extract_admin_auth(&app_state, request.headers(), &jar).await;
+    let admin_key = api_key.filter(|key| key.is_admin() && !admin_auth.is_admin());
+    let actor = match admin_key {
+        Some(key) => ("api_key", key.key_id.to_string()),
+        None => audit::actor(&admin_auth, request.headers()),
+    };
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+
+    if needs_admin && !admin_auth.is_admin() && admin_key.is_none() {
+        tracing::warn!("Rejected unauthenticated {} {}", method, path);
+        if !is_read {
+            let entry = audit::entry(actor, "api", &method, &path, StatusCode::UNAUTHORIZED.as_u16(), None);
+            audit::record(&app_state.service.db, entry).await;
+        }
+        return AppError::Unauthorized.into_response();
+    }
+
+    if is_read {
+        return next.run(request).await;
+    }
+
+    if UNBUFFERED_PATHS.contains(&path.as_str()) {
+        let response = next.run(request).await;
+        let entry = audit::entry(actor, "api", &method, &path, response.status().as_u16(), None);
+        audit::record(&app_state.service.db, entry).await;
+        return response;
+    }
+
+    // Buffer the body so a summary of the payload can go into the audit log
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let summary = audit::summarize_payload(&bytes);
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    let entry = audit::entry(actor, "api", &method, &path, response.status().as_u16(), summary);
+    audit::record(&app_state.service.db, entry).await;
+    response
+}
+
 /// Extract AdminAuth from request (for use in handlers)
-pub fn extract_admin_auth(
+pub async fn extract_admin_auth(
     app_state: &AppState,
+    headers: &HeaderMap,
     jar: &CookieJar,
 ) -> AdminAuth {
     // If auth is not enabled, return public auth
-    if !app_state.config.auth.enable_tezos_auth {
+    if !app_state.config.auth.is_enabled() {
         return AdminAuth::public();
     }
 
-    // Check for valid session cookie
-    if let Some(cookie) = jar.get("tezos_admin_session") {
-        if let Some(session) = verify_session_cookie(
-            cookie.value(),
-            &app_state.config.auth.cookie_hmac_key,
-        ) {
-            // Verify the address is still in admin list
-            if app_state.config.auth.admin_tezos_addresses.contains(&session.address) {
-                return AdminAuth {
-                    is_dev_admin: false,
-                    tezos_admin_address: Some(session.address),
-                };
-            }
-        }
+    if let Some(claims) = verify_bearer_token(app_state, headers) {
+        return AdminAuth {
+            is_dev_admin: false,
+            tezos_admin_address: None,
+            jwt_subject: Some(claims.sub),
+            oidc_email: None,
+        };
+    }
+
+    // Check for a valid, unrevoked session cookie
+    if let Some(session) = verify_tezos_session(app_state, jar).await {
+        return AdminAuth {
+            is_dev_admin: false,
+            tezos_admin_address: Some(session.address),
+            jwt_subject: None,
+            oidc_email: None,
+        };
+    }
+
+    if let Some(session) = verify_oidc_session(app_state, jar).await {
+        return AdminAuth {
+            is_dev_admin: false,
+            tezos_admin_address: None,
+            jwt_subject: None,
+            oidc_email: Some(session.email),
+        };
     }
 
     // Check if dev mode is enabled
@@ -74,6 +170,8 @@ pub fn extract_admin_auth(
         return AdminAuth {
             is_dev_admin: true,
             tezos_admin_address: None,
+            jwt_subject: None,
+            oidc_email: None,
         };
     }
 
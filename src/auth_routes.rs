@@ -9,7 +9,12 @@ pub fn create_auth_router() -> Router<AppState> {
     let mut router = Router::new()
         .route("/auth/tezos/challenge", get(auth_handler::get_tezos_challenge))
         .route("/auth/tezos/login", post(auth_handler::tezos_login))
+        .route("/auth/jwt/token", post(auth_handler::issue_jwt_token))
+        .route("/auth/oidc/login", get(auth_handler::oidc_login))
+        .route("/auth/oidc/callback", get(auth_handler::oidc_callback))
         .route("/auth/logout", post(auth_handler::logout))
+        .route("/auth/sessions", get(auth_handler::list_sessions))
+        .route("/auth/sessions/revoke", post(auth_handler::revoke_sessions))
         .route("/auth/status", get(auth_handler::auth_status));
     
     // Only add debug endpoint in debug builds
@@ -0,0 +1,168 @@
+//! Backtests of stored strategies over daily candles.
+//!
+//! A strategy is long-only and fully invested while in a position: it buys at the close of
+//! the candle where its entry rule fires and sells at the close of the candle where its exit
+//! rule fires. A stop loss or take profit sells during the candle whose low or high reaches
+//! it, at that level or at the open when the price gapped through; when a candle reaches
+//! both, the stop is assumed to have been hit first. A position still open after the last
+//! candle is closed at its close. There are no costs, slippage or dividends, and cash earns
+//! nothing between trades, so the Sharpe and Sortino ratios count flat days as falling short
+//! of the risk-free rate.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::indicator_expr::Expression;
+use crate::models::{BacktestResult, BacktestTrade, CrossDirection, HistoricalPrice, StrategyDefinition, StrategyRule};
+use crate::risk;
+
+/// Decimal places kept on returns and ratios
+const RATIO_DP: u32 = 6;
+
+/// Check every rule and level of a definition, naming the field at fault
+pub fn validate(definition: &StrategyDefinition) -> Result<(), String> {
+    Expression::parse(&definition.entry.expression).map_err(|e| format!("entry: {}", e))?;
+    if let Some(exit) = &definition.exit {
+        Expression::parse(&exit.expression).map_err(|e| format!("exit: {}", e))?;
+    }
+    if let Some(stop_loss) = definition.stop_loss {
+        if stop_loss <= Decimal::ZERO || stop_loss >= Decimal::ONE {
+            return Err("stop_loss must be a fraction between 0 and 1".to_string());
+        }
+    }
+    if let Some(take_profit) = definition.take_profit {
+        if take_profit <= Decimal::ZERO {
+            return Err("take_profit must be a positive fraction".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Candles the rules need before their first signal can fire
+pub fn lookback(definition: &StrategyDefinition) -> usize {
+    [Some(&definition.entry), definition.exit.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|rule| Expression::parse(&rule.expression).ok())
+        .map(|expression| expression.lookback() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether the rule fired at each candle, oldest first
+fn signals(rule: &StrategyRule, candles: &[HistoricalPrice]) -> Result<Vec<bool>, String> {
+    let expression = Expression::parse(&rule.expression)?;
+    let threshold = rule.threshold.to_f64().unwrap_or_default();
+    let values = expression.evaluate(candles);
+
+    let mut fired = vec![false; values.len()];
+    for i in 1..values.len() {
+        if let (Some(previous), Some(latest)) = (values[i - 1], values[i]) {
+            fired[i] = match rule.cross {
+                CrossDirection::Above => previous <= threshold && latest > threshold,
+                CrossDirection::Below => previous >= threshold && latest < threshold,
+            };
+        }
+    }
+    Ok(fired)
+}
+
+fn ratio(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(RATIO_DP).normalize()
+}
+
+/// Run `definition` over `candles`, which must be oldest first, measuring risk-adjusted
+/// returns over `risk_free_rate` (annual, as a fraction)
+pub fn run(
+    symbol: &str,
+    definition: &StrategyDefinition,
+    candles: &[HistoricalPrice],
+    risk_free_rate: f64,
+) -> Result<BacktestResult, String> {
+    let entries = signals(&definition.entry, candles)?;
+    let exits = match &definition.exit {
+        Some(rule) => signals(rule, candles)?,
+        None => vec![false; candles.len()],
+    };
+
+    let mut trades = Vec::new();
+    // Entry candle and price of the open position
+    let mut position: Option<(usize, Decimal)> = None;
+    // Equity before the open position, and its high-water mark
+    let mut equity = 1.0;
+    let mut peak: f64 = 1.0;
+    let mut max_drawdown: f64 = 0.0;
+    let mut candles_held = 0;
+    // Equity marked at each candle's close, or at the exit price on the candle of an exit
+    let mut curve = Vec::with_capacity(candles.len());
+
+    for (i, candle) in candles.iter().enumerate() {
+        let Some((entry_index, entry_price)) = position else {
+            if entries[i] {
+                position = Some((i, candle.close));
+            }
+            curve.push(equity);
+            continue;
+        };
+        candles_held += 1;
+
+        let stop = definition.stop_loss.map(|stop| entry_price * (Decimal::ONE - stop));
+        let target = definition.take_profit.map(|target| entry_price * (Decimal::ONE + target));
+        let exit = match (stop, target) {
+            (Some(stop), _) if candle.low <= stop => Some((candle.open.min(stop), "stop_loss")),
+            (_, Some(target)) if candle.high >= target => Some((candle.open.max(target), "take_profit")),
+            _ if exits[i] => Some((candle.close, "exit_signal")),
+            _ if i + 1 == candles.len() => Some((candle.close, "end_of_data")),
+            _ => None,
+        };
+
+        let price = exit.map_or(candle.close, |(price, _)| price);
+        let trade_return = (price / entry_price).to_f64().unwrap_or(1.0) - 1.0;
+        let marked = equity * (1.0 + trade_return);
+        peak = peak.max(marked);
+        max_drawdown = max_drawdown.max(1.0 - marked / peak);
+        curve.push(marked);
+
+        if let Some((price, reason)) = exit {
+            trades.push(BacktestTrade {
+                entry_date: candles[entry_index].timestamp.date_naive(),
+                entry_price,
+                exit_date: candle.timestamp.date_naive(),
+                exit_price: price,
+                exit_reason: reason.to_string(),
+                return_ratio: ratio(trade_return),
+            });
+            equity = marked;
+            position = None;
+        }
+    }
+
+    let buy_and_hold_return = match (candles.first(), candles.last()) {
+        (Some(first), Some(last)) if first.close > Decimal::ZERO && candles.len() > 1 => {
+            Some(ratio((last.close / first.close).to_f64().unwrap_or(1.0) - 1.0))
+        }
+        _ => None,
+    };
+    let win_rate = (!trades.is_empty()).then(|| {
+        let wins = trades.iter().filter(|trade| trade.return_ratio > Decimal::ZERO).count();
+        ratio(wins as f64 / trades.len() as f64)
+    });
+    let exposure = if candles.is_empty() { 0.0 } else { candles_held as f64 / candles.len() as f64 };
+    let daily_returns = risk::daily_returns(&curve);
+    let daily_risk_free = risk::daily_rate(risk_free_rate);
+
+    Ok(BacktestResult {
+        symbol: symbol.to_string(),
+        from: candles.first().map(|candle| candle.timestamp.date_naive()),
+        to: candles.last().map(|candle| candle.timestamp.date_naive()),
+        candles: candles.len(),
+        trades,
+        total_return: ratio(equity - 1.0),
+        buy_and_hold_return,
+        max_drawdown: ratio(max_drawdown),
+        win_rate,
+        exposure: ratio(exposure),
+        sharpe_ratio: risk::sharpe_ratio(&daily_returns, daily_risk_free).map(ratio),
+        sortino_ratio: risk::sortino_ratio(&daily_returns, daily_risk_free).map(ratio),
+    })
+}
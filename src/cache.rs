@@ -0,0 +1,296 @@
+//! Size-bounded in-memory caches for the market data service.
+//!
+//! Each cache is a moka cache (TinyLFU admission, LRU eviction) limited by an estimate of
+//! the bytes its entries hold rather than by entry count, so a handful of ten-year minute
+//! histories cannot crowd out memory the way a thousand "entries" could. Entries carry
+//! their own time-to-live, and every lookup is counted as a hit or miss for `/api/stats`, as is
+//! every entry evicted to stay within the byte budget.
+
+use moka::notification::RemovalCause;
+use moka::sync::Cache;
+use moka::Expiry;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::models::{
+    CompanyProfile, EtfComposition, Forecast, ForecastPoint, FundHolding, HistoricalPrice, Holder, ListingQuote,
+    RealTimeQuote, SectorWeight, SymbolHolders, SymbolListings, SymbolResolution, SymbolSuggestion,
+};
+
+/// Approximate heap and inline bytes held by a cached value
+pub trait CacheWeight {
+    fn weight(&self) -> usize;
+}
+
+impl<T: CacheWeight> CacheWeight for Vec<T> {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+            + (self.capacity() - self.len()) * size_of::<T>()
+            + self.iter().map(CacheWeight::weight).sum::<usize>()
+    }
+}
+
+impl<T: CacheWeight> CacheWeight for Option<T> {
+    fn weight(&self) -> usize {
+        match self {
+            Some(value) => value.weight(),
+            None => size_of::<Self>(),
+        }
+    }
+}
+
+fn string_bytes(value: &Option<String>) -> usize {
+    value.as_ref().map_or(0, String::capacity)
+}
+
+impl CacheWeight for HistoricalPrice {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.symbol.capacity()
+    }
+}
+
+impl CacheWeight for RealTimeQuote {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.symbol.capacity() + self.trading_session.capacity()
+    }
+}
+
+impl CacheWeight for CompanyProfile {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+            + self.symbol.capacity()
+            + [
+                &self.company_name,
+                &self.description,
+                &self.sector,
+                &self.industry,
+                &self.website,
+                &self.address,
+                &self.city,
+                &self.state,
+                &self.country,
+                &self.zip_code,
+                &self.phone,
+            ]
+            .into_iter()
+            .map(string_bytes)
+            .sum::<usize>()
+    }
+}
+
+impl CacheWeight for SymbolSuggestion {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+            + self.symbol.capacity()
+            + string_bytes(&self.name)
+            + string_bytes(&self.exchange)
+    }
+}
+
+impl CacheWeight for SymbolResolution {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.input.capacity() + self.symbol.capacity() + self.source.capacity()
+    }
+}
+
+impl CacheWeight for Holder {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.name.capacity()
+    }
+}
+
+impl CacheWeight for SymbolHolders {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.symbol.capacity() + self.institutions.weight() + self.funds.weight()
+    }
+}
+
+impl CacheWeight for FundHolding {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + string_bytes(&self.symbol) + self.name.capacity()
+    }
+}
+
+impl CacheWeight for SectorWeight {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.sector.capacity()
+    }
+}
+
+impl CacheWeight for EtfComposition {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.symbol.capacity() + self.holdings.weight() + self.sector_weights.weight()
+    }
+}
+
+impl CacheWeight for Decimal {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
+impl CacheWeight for Forecast {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+            + self.symbol.capacity()
+            + self.method.capacity()
+            + self.parameters.keys().map(|name| name.capacity() + size_of::<f64>()).sum::<usize>()
+            + self.points.capacity() * size_of::<ForecastPoint>()
+    }
+}
+
+impl CacheWeight for ListingQuote {
+    fn weight(&self) -> usize {
+        size_of::<Self>()
+            + self.symbol.capacity()
+            + string_bytes(&self.name)
+            + string_bytes(&self.exchange)
+            + self.currency.capacity()
+    }
+}
+
+impl CacheWeight for SymbolListings {
+    fn weight(&self) -> usize {
+        size_of::<Self>() + self.symbol.capacity() + self.currency.capacity() + self.listings.weight()
+    }
+}
+
+#[derive(Clone)]
+struct Entry<V> {
+    value: V,
+    ttl: Duration,
+}
+
+/// Expires each entry after the TTL it was inserted with
+struct PerEntryTtl;
+
+impl<V> Expiry<String, Entry<V>> for PerEntryTtl {
+    fn expire_after_create(&self, _key: &String, entry: &Entry<V>, _created_at: Instant) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        entry: &Entry<V>,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+}
+
+/// Point-in-time figures for one cache
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub entries: u64,
+    pub weighted_bytes: u64,
+    pub max_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to stay within `max_bytes`, not counting expiry
+    pub evictions: u64,
+    /// Hits over lookups, or 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+/// String-keyed cache bounded by the approximate bytes of its entries
+pub struct WeightedCache<V: Clone + Send + Sync + 'static> {
+    inner: Cache<String, Entry<V>>,
+    max_bytes: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: Arc<AtomicU64>,
+}
+
+impl<V: CacheWeight + Clone + Send + Sync + 'static> WeightedCache<V> {
+    pub fn new(max_bytes: u64) -> Self {
+        let evictions = Arc::new(AtomicU64::new(0));
+        let evicted = evictions.clone();
+        let inner = Cache::builder()
+            .max_capacity(max_bytes)
+            .weigher(|key: &String, entry: &Entry<V>| {
+                let bytes = key.capacity() + size_of::<Entry<V>>() + entry.value.weight();
+                u32::try_from(bytes).unwrap_or(u32::MAX)
+            })
+            .expire_after(PerEntryTtl)
+            .eviction_listener(move |_key, _entry, cause| {
+                if cause == RemovalCause::Size {
+                    evicted.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .build();
+        Self {
+            inner,
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions,
+        }
+    }
+
+    /// Unexpired value for `key`, counted as a hit or miss
+    pub fn get(&self, key: &str) -> Option<V> {
+        match self.inner.get(key) {
+            Some(entry) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.value)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, key: String, value: V, ttl: Duration) {
+        self.inner.insert(key, Entry { value, ttl });
+    }
+
+    /// Whether an unexpired value is cached, without counting a lookup
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.inner.invalidate(key);
+    }
+
+    /// Drop every entry whose key starts with `prefix`
+    pub fn remove_prefix(&self, prefix: &str) {
+        for (key, _) in self.inner.iter() {
+            if key.starts_with(prefix) {
+                self.inner.invalidate(key.as_str());
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        self.inner.invalidate_all();
+    }
+
+    /// Drop expired entries and apply pending evictions now rather than on later writes
+    pub fn run_pending_tasks(&self) {
+        self.inner.run_pending_tasks();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.inner.run_pending_tasks();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        CacheStats {
+            entries: self.inner.entry_count(),
+            weighted_bytes: self.inner.weighted_size(),
+            max_bytes: self.max_bytes,
+            hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use anyhow::Result;
 use std::time::Duration;
 use rand::RngCore;
+use rust_decimal::Decimal;
+
+use crate::jwt::{JwtAlgorithm, JwtKeys};
+use crate::notify::SmtpTls;
+use crate::oidc::OidcProvider;
+use crate::provider::{DataProvider, BROWSER_USER_AGENT};
 
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
@@ -11,12 +17,50 @@ pub struct Config {
     pub cache: CacheConfig,
     pub cors: CorsConfig,
     pub auth: AuthConfig,
+    pub market_data: MarketDataConfig,
+    pub notifications: NotificationConfig,
+    pub web_ui: WebUiConfig,
+    pub jobs: JobConfig,
+    pub portfolio_updates: PortfolioUpdateConfig,
+    pub alerts: AlertConfig,
+    pub stats_history: StatsHistoryConfig,
+    pub cache_bus: CacheBusConfig,
+    pub events: EventStreamConfig,
+    pub object_export: ObjectExportConfig,
+    pub latency: LatencyConfig,
+    pub archive: ArchiveConfig,
+    pub analysis: AnalysisConfig,
+    pub analytics_sql: AnalyticsSqlConfig,
+    pub anomalies: AnomalyConfig,
 }
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
+    pub journal_mode: String,
+    pub synchronous: String,
+    pub busy_timeout: Duration,
+    pub foreign_keys: bool,
+    /// Serve an existing database without writing to it, from READ_ONLY: mutating endpoints
+    /// and background writers are off, and data is served as stored
+    pub read_only: bool,
+}
+
+impl DatabaseConfig {
+    /// A private in-memory database. It lives on a single connection, since each SQLite
+    /// `:memory:` connection is a separate database.
+    pub fn in_memory() -> Self {
+        DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            journal_mode: "MEMORY".to_string(),
+            synchronous: "OFF".to_string(),
+            busy_timeout: Duration::from_millis(5000),
+            foreign_keys: true,
+            read_only: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,9 +72,51 @@ pub struct ServerConfig {
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
     pub api_requests_per_minute: u32,
-    pub api_burst: u32,
     pub yahoo_api_requests_per_minute: u32,
-    pub yahoo_api_burst: u32,
+    /// How long a provider call may queue for a free slot in the Yahoo window before it is
+    /// refused; zero refuses immediately
+    pub yahoo_api_max_wait: Duration,
+    /// Provider fetches that bulk operations (`/api/bulk/*`, batch quotes of the portfolio
+    /// updater and allocation) may run at once across all requests, each holding a database
+    /// connection at most
+    pub bulk_max_concurrency: usize,
+    /// Share of the Yahoo window bulk operations may use; the rest stays free for interactive requests
+    pub bulk_yahoo_requests_per_minute: u32,
+    /// Daily budget of Yahoo calls split across features
+    pub yahoo_quota: QuotaConfig,
+}
+
+/// Daily budget of provider calls, from YAHOO_DAILY_BUDGET and the YAHOO_BUDGET_*_PERCENT
+/// shares. Whatever the shares leave over goes to searches, symbol resolution and listings.
+#[derive(Debug, Clone)]
+pub struct QuotaConfig {
+    /// Provider calls per UTC day; zero disables the budget
+    pub daily_budget: u32,
+    pub quotes_percent: u32,
+    pub historical_percent: u32,
+    /// Company profiles, holders and ETF compositions
+    pub profiles_percent: u32,
+    /// Bulk operations and background jobs
+    pub backfills_percent: u32,
+}
+
+impl QuotaConfig {
+    /// Percent of the budget given to named features
+    pub fn allocated_percent(&self) -> u32 {
+        self.quotes_percent + self.historical_percent + self.profiles_percent + self.backfills_percent
+    }
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            daily_budget: 0,
+            quotes_percent: DEFAULT_QUOTA_QUOTES_PERCENT,
+            historical_percent: DEFAULT_QUOTA_HISTORICAL_PERCENT,
+            profiles_percent: DEFAULT_QUOTA_PROFILES_PERCENT,
+            backfills_percent: DEFAULT_QUOTA_BACKFILLS_PERCENT,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,9 +125,401 @@ pub struct CacheConfig {
     pub ttl_historical: Duration,
     pub ttl_profiles: Duration,
     pub cleanup_interval: Duration,
-    pub max_size_historical: usize,
-    pub max_size_quotes: usize,
-    pub max_size_profiles: usize,
+    /// Byte budgets for the in-memory caches
+    pub max_bytes_historical: u64,
+    pub max_bytes_quotes: u64,
+    pub max_bytes_profiles: u64,
+    pub max_bytes_suggestions: u64,
+    pub max_bytes_holders: u64,
+}
+
+impl CacheConfig {
+    /// How long candles of `interval` are kept: CACHE_TTL_HISTORICAL, shortened for intraday
+    /// bars so a forming candle is refetched while it still changes
+    pub fn historical_ttl(&self, interval: &str) -> Duration {
+        let intraday_cap = match interval {
+            "1m" | "2m" | "5m" => Some(Duration::from_secs(60)),
+            "15m" | "30m" | "90m" => Some(Duration::from_secs(300)),
+            "1h" => Some(Duration::from_secs(1800)),
+            _ => None,
+        };
+        intraday_cap.map_or(self.ttl_historical, |cap| cap.min(self.ttl_historical))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketDataConfig {
+    /// Source of candles, quotes and symbol search, chosen with DATA_PROVIDER
+    pub provider: DataProvider,
+    /// JSON fixtures for the mock provider, from MOCK_PROVIDER_FIXTURES
+    pub mock_fixtures: Option<std::path::PathBuf>,
+    /// Load the bundled sample data on first start, from DEMO_MODE
+    pub demo_mode: bool,
+    /// Yahoo exchange suffixes tried in order for bare tickers, `US` meaning no suffix, from
+    /// SYMBOL_EXCHANGE_PREFERENCE (e.g. "DE,US"). Empty resolves through aliases only.
+    pub exchange_preference: Vec<String>,
+    /// Fall back to OpenFIGI for ISIN/CUSIP lookups the symbol search cannot answer, from
+    /// OPENFIGI_ENABLED. Never used with the mock provider.
+    pub openfigi_enabled: bool,
+    /// Optional key for OpenFIGI's higher rate limits, from OPENFIGI_API_KEY
+    pub openfigi_api_key: Option<String>,
+    /// Key for the FRED API, the source of macro series when set, from FRED_API_KEY
+    pub fred_api_key: Option<String>,
+    /// Time allowed to open a connection to Yahoo, from YAHOO_CONNECT_TIMEOUT_MS
+    pub yahoo_connect_timeout: Duration,
+    /// Longest wait for the next bytes of a Yahoo response, from YAHOO_READ_TIMEOUT_MS
+    pub yahoo_read_timeout: Duration,
+    /// HTTP(S) proxy for every Yahoo request, from YAHOO_PROXY_URL. Without it the standard
+    /// HTTPS_PROXY/HTTP_PROXY/NO_PROXY variables apply.
+    pub yahoo_proxy: Option<String>,
+    /// User-Agent sent to Yahoo, from YAHOO_USER_AGENT; Yahoo only serves browser-like agents
+    pub yahoo_user_agent: String,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct WebUiConfig {
+    /// Directory served at /static, from STATIC_DIR
+    pub static_dir: std::path::PathBuf,
+    /// Load CSS/JS libraries from /static/vendor instead of public CDNs, from
+    /// WEB_UI_LOCAL_ASSETS (for air-gapped deployments)
+    pub local_assets: bool,
+}
+
+impl Default for WebUiConfig {
+    fn default() -> Self {
+        Self {
+            static_dir: "static".into(),
+            local_assets: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobConfig {
+    /// Background job workers running at once, from JOB_WORKERS
+    pub workers: usize,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self { workers: 2 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PortfolioUpdateConfig {
+    /// Time between background holding price refreshes, from PORTFOLIO_UPDATE_INTERVAL (seconds)
+    pub interval: Duration,
+    /// Quotes fetched at once during a refresh, from PORTFOLIO_UPDATE_CONCURRENCY
+    pub max_concurrent: usize,
+}
+
+impl Default for PortfolioUpdateConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            max_concurrent: 4,
+        }
+    }
+}
+
+/// Defaults for how often price and signal alerts may fire; each alert can override them
+#[derive(Debug, Clone)]
+pub struct AlertConfig {
+    /// Least time between two firings of the same alert, from ALERT_COOLDOWN_MINUTES
+    pub cooldown: Duration,
+    /// How far in percent the price must move back past a stop or target that fired before it
+    /// can fire again, from ALERT_REARM_PERCENT
+    pub rearm_percent: Decimal,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            cooldown: Duration::from_secs(60 * 60),
+            rearm_percent: Decimal::ONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatsHistoryConfig {
+    /// Time between samples of cache and provider activity, from STATS_SAMPLE_INTERVAL (seconds)
+    pub sample_interval: Duration,
+    /// Also store samples in the database so history survives restarts, from
+    /// STATS_HISTORY_PERSIST
+    pub persist: bool,
+}
+
+impl Default for StatsHistoryConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_secs(300),
+            persist: false,
+        }
+    }
+}
+
+impl StatsHistoryConfig {
+    /// Samples kept in memory to cover MAX_STATS_HISTORY_HOURS
+    pub fn capacity(&self) -> usize {
+        let seconds = (MAX_STATS_HISTORY_HOURS * 3600) as u64;
+        seconds.div_ceil(self.sample_interval.as_secs().max(1)) as usize
+    }
+}
+
+/// Redis pub/sub channel shared by replicas to invalidate each other's caches
+#[derive(Clone)]
+pub struct CacheBusConfig {
+    /// `redis://[[user]:password@]host[:port]`, from CACHE_BUS_REDIS_URL; unset keeps caches local
+    pub redis_url: Option<String>,
+    /// Channel the instances publish on, from CACHE_BUS_CHANNEL
+    pub channel: String,
+}
+
+impl Default for CacheBusConfig {
+    fn default() -> Self {
+        Self {
+            redis_url: None,
+            channel: "mango-data-service:cache".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for CacheBusConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheBusConfig")
+            .field("redis_url", &self.redis_url.as_ref().map(|_| "<redacted>"))
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+/// NATS server that receives ingested-data events
+#[derive(Clone)]
+pub struct EventStreamConfig {
+    /// `nats://[user:password@ or token@]host[:port]`, from EVENTS_NATS_URL; unset publishes nothing
+    pub nats_url: Option<String>,
+    /// Prefix of the event subjects, from EVENTS_SUBJECT_PREFIX
+    pub subject_prefix: String,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            nats_url: None,
+            subject_prefix: "mango".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for EventStreamConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventStreamConfig")
+            .field("nats_url", &self.nats_url.as_ref().map(|_| "<redacted>"))
+            .field("subject_prefix", &self.subject_prefix)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectExportConfig {
+    /// Bucket the exports are written to; `None` without EXPORT_S3_BUCKET, which disables exports
+    pub storage: Option<S3Config>,
+    /// Time between scheduled exports, from EXPORT_INTERVAL_HOURS; zero only exports on demand
+    pub interval: Duration,
+}
+
+impl Default for ObjectExportConfig {
+    fn default() -> Self {
+        Self {
+            storage: None,
+            interval: Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+/// An S3-compatible bucket (AWS S3, MinIO, Cloudflare R2, Backblaze B2...)
+#[derive(Clone)]
+pub struct S3Config {
+    /// Base URL of the service, from EXPORT_S3_ENDPOINT; AWS S3 in `region` when unset
+    pub endpoint: String,
+    /// Signing region, from EXPORT_S3_REGION
+    pub region: String,
+    pub bucket: String,
+    /// Credentials from EXPORT_S3_ACCESS_KEY_ID and EXPORT_S3_SECRET_ACCESS_KEY
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Key prefix of every exported object, from EXPORT_S3_PREFIX; empty or ending in `/`
+    pub prefix: String,
+    /// Address the bucket in the path rather than the host name, from EXPORT_S3_PATH_STYLE;
+    /// on by default with a custom endpoint, as most self-hosted stores expect
+    pub path_style: bool,
+}
+
+impl std::fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field("prefix", &self.prefix)
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LatencyConfig {
+    /// Market-data provider calls slower than this are logged as warnings, from
+    /// SLOW_UPSTREAM_CALL_MS; zero disables the warnings
+    pub slow_upstream_call: Duration,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            slow_upstream_call: Duration::from_millis(2000),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    /// Largest archive `POST /api/admin/import` accepts, from ARCHIVE_IMPORT_MAX_MB
+    pub import_max_bytes: usize,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            import_max_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    /// Index proxy that beta is measured against, from ANALYSIS_BENCHMARK
+    pub benchmark: String,
+    /// Annual risk-free rate as a fraction (0.04 = 4%) for Sharpe and Sortino ratios, from
+    /// ANALYSIS_RISK_FREE_RATE; the fallback when the T-bill yield is not used or unavailable
+    pub risk_free_rate: f64,
+    /// Take the risk-free rate from the latest 13-week T-bill yield, from
+    /// ANALYSIS_RISK_FREE_FROM_TBILL
+    pub risk_free_from_tbill: bool,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            benchmark: "SPY".to_string(),
+            risk_free_rate: 0.04,
+            risk_free_from_tbill: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalyticsSqlConfig {
+    /// Serve `POST /api/analytics/sql`, from ANALYTICS_SQL_ENABLED
+    pub enabled: bool,
+    /// Most rows a query returns, from ANALYTICS_SQL_MAX_ROWS
+    pub max_rows: usize,
+    /// Queries running longer are interrupted, from ANALYTICS_SQL_TIMEOUT_MS
+    pub timeout: Duration,
+}
+
+impl Default for AnalyticsSqlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_rows: 1000,
+            timeout: Duration::from_millis(5000),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    /// Run the background analyzer, from ANOMALY_DETECTION_ENABLED
+    pub enabled: bool,
+    /// Time between scans of stored symbols, from ANOMALY_SCAN_INTERVAL (seconds)
+    pub interval: Duration,
+    /// Daily bars a move is compared with, from ANOMALY_LOOKBACK_DAYS
+    pub lookback_days: usize,
+    /// Absolute z-score of a daily return that counts as unusual, from ANOMALY_RETURN_Z
+    pub return_z: f64,
+    /// Z-score of a day's log volume that counts as unusual, from ANOMALY_VOLUME_Z
+    pub volume_z: f64,
+    /// Also send new anomalies to the notification channels, from ANOMALY_NOTIFY
+    pub notify: bool,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(60 * 60),
+            lookback_days: 60,
+            return_z: 3.0,
+            volume_z: 3.0,
+            notify: false,
+        }
+    }
+}
+
+/// Alert notification channels. A channel is available only when its settings are present.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationConfig {
+    pub email: Option<SmtpConfig>,
+    pub telegram: Option<TelegramConfig>,
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl std::fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+    /// Bot API base URL, overridable for self-hosted Bot API servers
+    pub api_url: String,
+}
+
+impl std::fmt::Debug for TelegramConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelegramConfig")
+            .field("bot_token", &"<redacted>")
+            .field("chat_id", &self.chat_id)
+            .field("api_url", &self.api_url)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +534,66 @@ pub struct AuthConfig {
     pub admin_tezos_addresses: Vec<String>,
     pub dev_mode: bool,
     pub cookie_hmac_key: [u8; 32],
+    /// Keep GET /api routes open when auth is enabled; mutating routes always need an admin
+    pub public_read_api: bool,
+    /// Bearer-token auth, enabled with ENABLE_JWT_AUTH
+    pub jwt: Option<JwtConfig>,
+    /// Web UI login through an OAuth2/OIDC provider, enabled with ENABLE_OIDC_AUTH
+    pub oidc: Option<OidcConfig>,
+}
+
+impl AuthConfig {
+    /// Whether any admin auth mode is active
+    pub fn is_enabled(&self) -> bool {
+        self.enable_tezos_auth || self.jwt.is_some() || self.oidc.is_some()
+    }
+}
+
+#[derive(Clone)]
+pub struct JwtConfig {
+    pub keys: JwtKeys,
+    pub issuer: String,
+    pub ttl: Duration,
+    pub admin_username: String,
+    /// Token issuance is disabled when unset (verify-only deployments)
+    pub admin_password: Option<String>,
+}
+
+impl std::fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("keys", &self.keys)
+            .field("issuer", &self.issuer)
+            .field("ttl", &self.ttl)
+            .field("admin_username", &self.admin_username)
+            .field("admin_password", &self.admin_password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct OidcConfig {
+    pub provider: OidcProvider,
+    /// Discovery base for the generic provider
+    pub issuer_url: Option<String>,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    /// Lowercased emails allowed to sign in as admin
+    pub admin_emails: Vec<String>,
+}
+
+impl std::fmt::Debug for OidcConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OidcConfig")
+            .field("provider", &self.provider)
+            .field("issuer_url", &self.issuer_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("redirect_url", &self.redirect_url)
+            .field("admin_emails", &self.admin_emails)
+            .finish()
+    }
 }
 
 impl Config {
@@ -85,7 +623,27 @@ impl Config {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(50),
+            // WAL lets readers proceed while the background updater and API handlers write
+            journal_mode: std::env::var("DATABASE_JOURNAL_MODE").unwrap_or_else(|_| "WAL".to_string()),
+            synchronous: std::env::var("DATABASE_SYNCHRONOUS").unwrap_or_else(|_| "NORMAL".to_string()),
+            busy_timeout: Duration::from_millis(
+                std::env::var("DATABASE_BUSY_TIMEOUT_MS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(5000),
+            ),
+            foreign_keys: std::env::var("DATABASE_FOREIGN_KEYS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(true),
+            read_only: std::env::var("READ_ONLY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
         };
+        if database.read_only {
+            tracing::info!("READ_ONLY: serving stored data; writes and background writers are disabled");
+        }
 
         let server = ServerConfig {
             host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
@@ -95,23 +653,54 @@ impl Config {
                 .unwrap_or(3000),
         };
 
+        let yahoo_api_requests_per_minute = std::env::var("YAHOO_API_RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(120); // Increased from 30 to 120 requests/min (2 per second)
         let rate_limiting = RateLimitConfig {
             api_requests_per_minute: std::env::var("API_RATE_LIMIT_PER_MINUTE")
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(100),
-            api_burst: std::env::var("API_RATE_LIMIT_BURST")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(10),
-            yahoo_api_requests_per_minute: std::env::var("YAHOO_API_RATE_LIMIT_PER_MINUTE")
+            yahoo_api_requests_per_minute,
+            yahoo_api_max_wait: Duration::from_millis(
+                std::env::var("YAHOO_RATE_LIMIT_MAX_WAIT_MS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0),
+            ),
+            bulk_max_concurrency: std::env::var("BULK_MAX_CONCURRENCY")
                 .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(120), // Increased from 30 to 120 requests/min (2 per second)
-            yahoo_api_burst: std::env::var("YAHOO_API_RATE_LIMIT_BURST")
+                .and_then(|s| s.parse::<usize>().ok())
+                .map(|n| n.clamp(1, MAX_BULK_CONCURRENCY))
+                .unwrap_or(MAX_BULK_CONCURRENCY),
+            // Half of the Yahoo window unless configured
+            bulk_yahoo_requests_per_minute: std::env::var("BULK_YAHOO_RATE_LIMIT_PER_MINUTE")
                 .ok()
                 .and_then(|s| s.parse().ok())
-                .unwrap_or(5),
+                .unwrap_or((yahoo_api_requests_per_minute / 2).max(1)),
+            yahoo_quota: QuotaConfig {
+                daily_budget: std::env::var("YAHOO_DAILY_BUDGET")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0),
+                quotes_percent: std::env::var("YAHOO_BUDGET_QUOTES_PERCENT")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_QUOTA_QUOTES_PERCENT),
+                historical_percent: std::env::var("YAHOO_BUDGET_HISTORICAL_PERCENT")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_QUOTA_HISTORICAL_PERCENT),
+                profiles_percent: std::env::var("YAHOO_BUDGET_PROFILES_PERCENT")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_QUOTA_PROFILES_PERCENT),
+                backfills_percent: std::env::var("YAHOO_BUDGET_BACKFILLS_PERCENT")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_QUOTA_BACKFILLS_PERCENT),
+            },
         };
 
         let cache = CacheConfig {
@@ -139,18 +728,36 @@ impl Config {
                     .and_then(|s| s.parse().ok())
                     .unwrap_or(3600),
             ),
-            max_size_historical: std::env::var("CACHE_MAX_SIZE_HISTORICAL")
+            max_bytes_historical: std::env::var("CACHE_MAX_MB_HISTORICAL")
                 .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(1000),
-            max_size_quotes: std::env::var("CACHE_MAX_SIZE_QUOTES")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(64)
+                * 1024
+                * 1024,
+            max_bytes_quotes: std::env::var("CACHE_MAX_MB_QUOTES")
                 .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(500),
-            max_size_profiles: std::env::var("CACHE_MAX_SIZE_PROFILES")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(8)
+                * 1024
+                * 1024,
+            max_bytes_profiles: std::env::var("CACHE_MAX_MB_PROFILES")
                 .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(200),
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(16)
+                * 1024
+                * 1024,
+            max_bytes_suggestions: std::env::var("CACHE_MAX_MB_SUGGESTIONS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(4)
+                * 1024
+                * 1024,
+            max_bytes_holders: std::env::var("CACHE_MAX_MB_HOLDERS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(4)
+                * 1024
+                * 1024,
         };
 
         let cors_origins = std::env::var("CORS_ALLOWED_ORIGINS").ok();
@@ -178,6 +785,224 @@ impl Config {
             }
         };
 
+        let market_data = MarketDataConfig {
+            provider: std::env::var("DATA_PROVIDER")
+                .unwrap_or_else(|_| "yahoo".to_string())
+                .parse()
+                .map_err(|e: String| anyhow::anyhow!(e))?,
+            mock_fixtures: std::env::var("MOCK_PROVIDER_FIXTURES")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .map(std::path::PathBuf::from),
+            demo_mode: std::env::var("DEMO_MODE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            exchange_preference: std::env::var("SYMBOL_EXCHANGE_PREFERENCE")
+                .map(|s| {
+                    s.split(',')
+                        .map(|code| code.trim().trim_start_matches('.').to_uppercase())
+                        .filter(|code| !code.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            openfigi_enabled: std::env::var("OPENFIGI_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(true),
+            openfigi_api_key: std::env::var("OPENFIGI_API_KEY")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            fred_api_key: std::env::var("FRED_API_KEY")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            yahoo_connect_timeout: std::env::var("YAHOO_CONNECT_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_YAHOO_CONNECT_TIMEOUT),
+            yahoo_read_timeout: std::env::var("YAHOO_READ_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_YAHOO_READ_TIMEOUT),
+            yahoo_proxy: std::env::var("YAHOO_PROXY_URL")
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty()),
+            yahoo_user_agent: std::env::var("YAHOO_USER_AGENT")
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| BROWSER_USER_AGENT.to_string()),
+        };
+        tracing::info!("Market data provider: {}", market_data.provider.as_str());
+
+        let web_ui = WebUiConfig {
+            static_dir: std::env::var("STATIC_DIR")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| "static".into()),
+            local_assets: std::env::var("WEB_UI_LOCAL_ASSETS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+        };
+
+        let jobs = JobConfig {
+            workers: std::env::var("JOB_WORKERS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(2),
+        };
+
+        let portfolio_updates = PortfolioUpdateConfig {
+            interval: std::env::var("PORTFOLIO_UPDATE_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(PortfolioUpdateConfig::default().interval),
+            max_concurrent: std::env::var("PORTFOLIO_UPDATE_CONCURRENCY")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .map(|n| n.clamp(1, MAX_BULK_CONCURRENCY))
+                .unwrap_or(PortfolioUpdateConfig::default().max_concurrent),
+        };
+
+        let alerts = AlertConfig {
+            cooldown: std::env::var("ALERT_COOLDOWN_MINUTES")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|minutes| *minutes <= MAX_ALERT_COOLDOWN_MINUTES as u64)
+                .map(|minutes| Duration::from_secs(minutes * 60))
+                .unwrap_or(AlertConfig::default().cooldown),
+            rearm_percent: std::env::var("ALERT_REARM_PERCENT")
+                .ok()
+                .and_then(|s| s.parse::<Decimal>().ok())
+                .filter(|percent| *percent >= Decimal::ZERO && *percent < Decimal::ONE_HUNDRED)
+                .unwrap_or(AlertConfig::default().rearm_percent),
+        };
+
+        let stats_history = StatsHistoryConfig {
+            sample_interval: std::env::var("STATS_SAMPLE_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs >= MIN_STATS_SAMPLE_INTERVAL_SECS)
+                .map(Duration::from_secs)
+                .unwrap_or(StatsHistoryConfig::default().sample_interval),
+            persist: std::env::var("STATS_HISTORY_PERSIST")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+        };
+
+        let cache_bus = CacheBusConfig {
+            redis_url: std::env::var("CACHE_BUS_REDIS_URL").ok().filter(|url| !url.trim().is_empty()),
+            channel: std::env::var("CACHE_BUS_CHANNEL")
+                .ok()
+                .filter(|channel| !channel.trim().is_empty())
+                .unwrap_or(CacheBusConfig::default().channel),
+        };
+        if cache_bus.redis_url.is_some() {
+            tracing::info!("Cache bus: Redis channel {}", cache_bus.channel);
+        }
+
+        let events = EventStreamConfig {
+            nats_url: std::env::var("EVENTS_NATS_URL").ok().filter(|url| !url.trim().is_empty()),
+            subject_prefix: std::env::var("EVENTS_SUBJECT_PREFIX")
+                .ok()
+                .map(|prefix| prefix.trim().trim_end_matches('.').to_string())
+                .filter(|prefix| !prefix.is_empty())
+                .unwrap_or(EventStreamConfig::default().subject_prefix),
+        };
+        if events.nats_url.is_some() {
+            tracing::info!("Event stream: NATS subjects {}.*", events.subject_prefix);
+        }
+
+        let latency = LatencyConfig {
+            slow_upstream_call: std::env::var("SLOW_UPSTREAM_CALL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(LatencyConfig::default().slow_upstream_call),
+        };
+
+        let archive = ArchiveConfig {
+            import_max_bytes: std::env::var("ARCHIVE_IMPORT_MAX_MB")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(ArchiveConfig::default().import_max_bytes),
+        };
+
+        let analysis = AnalysisConfig {
+            benchmark: std::env::var("ANALYSIS_BENCHMARK")
+                .map(|s| s.trim().to_uppercase())
+                .unwrap_or_else(|_| AnalysisConfig::default().benchmark),
+            risk_free_rate: std::env::var("ANALYSIS_RISK_FREE_RATE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(AnalysisConfig::default().risk_free_rate),
+            risk_free_from_tbill: std::env::var("ANALYSIS_RISK_FREE_FROM_TBILL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(AnalysisConfig::default().risk_free_from_tbill),
+        };
+
+        let analytics_sql = AnalyticsSqlConfig {
+            enabled: std::env::var("ANALYTICS_SQL_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            max_rows: std::env::var("ANALYTICS_SQL_MAX_ROWS")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .map(|rows| rows.clamp(1, MAX_ANALYTICS_SQL_ROWS))
+                .unwrap_or(AnalyticsSqlConfig::default().max_rows),
+            timeout: std::env::var("ANALYTICS_SQL_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(AnalyticsSqlConfig::default().timeout),
+        };
+
+        let anomalies = AnomalyConfig {
+            enabled: std::env::var("ANOMALY_DETECTION_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(AnomalyConfig::default().enabled),
+            interval: std::env::var("ANOMALY_SCAN_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs >= MIN_ANOMALY_SCAN_INTERVAL_SECS)
+                .map(Duration::from_secs)
+                .unwrap_or(AnomalyConfig::default().interval),
+            lookback_days: std::env::var("ANOMALY_LOOKBACK_DAYS")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .map(|days| days.clamp(MIN_ANOMALY_LOOKBACK_DAYS, MAX_ANOMALY_LOOKBACK_DAYS))
+                .unwrap_or(AnomalyConfig::default().lookback_days),
+            return_z: std::env::var("ANOMALY_RETURN_Z")
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|z| z.is_finite() && *z > 0.0)
+                .unwrap_or(AnomalyConfig::default().return_z),
+            volume_z: std::env::var("ANOMALY_VOLUME_Z")
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|z| z.is_finite() && *z > 0.0)
+                .unwrap_or(AnomalyConfig::default().volume_z),
+            notify: std::env::var("ANOMALY_NOTIFY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(AnomalyConfig::default().notify),
+        };
+
         // Tezos authentication configuration
         let enable_tezos_auth = std::env::var("ENABLE_TEZOS_AUTH")
             .ok()
@@ -198,6 +1023,11 @@ impl Config {
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(false);
+
+        let public_read_api = std::env::var("PUBLIC_READ_API")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true);
         
         // Log Tezos auth configuration for debugging
         tracing::info!("Tezos Auth Configuration:");
@@ -236,6 +1066,9 @@ impl Config {
             admin_tezos_addresses,
             dev_mode,
             cookie_hmac_key,
+            public_read_api,
+            jwt: load_jwt_config()?,
+            oidc: load_oidc_config()?,
         };
 
         Ok(Config {
@@ -245,9 +1078,95 @@ impl Config {
             cache,
             cors,
             auth,
+            market_data,
+            notifications: load_notification_config()?,
+            web_ui,
+            jobs,
+            portfolio_updates,
+            alerts,
+            stats_history,
+            cache_bus,
+            events,
+            object_export: load_object_export_config()?,
+            latency,
+            archive,
+            analysis,
+            analytics_sql,
+            anomalies,
         })
     }
 
+    /// Fixed configuration for tests and embedding: in-memory SQLite, the mock market-data
+    /// provider, default limits and every auth mode off. Nothing is read from the environment.
+    pub fn for_testing() -> Self {
+        Config {
+            database: DatabaseConfig::in_memory(),
+            server: ServerConfig {
+                host: "127.0.0.1".to_string(),
+                port: 0,
+            },
+            rate_limiting: RateLimitConfig {
+                api_requests_per_minute: 100,
+                yahoo_api_requests_per_minute: 120,
+                yahoo_api_max_wait: Duration::ZERO,
+                bulk_max_concurrency: MAX_BULK_CONCURRENCY,
+                bulk_yahoo_requests_per_minute: 60,
+                yahoo_quota: QuotaConfig::default(),
+            },
+            cache: CacheConfig {
+                ttl_quotes: Duration::from_secs(300),
+                ttl_historical: Duration::from_secs(3600),
+                ttl_profiles: Duration::from_secs(86400),
+                cleanup_interval: Duration::from_secs(3600),
+                max_bytes_historical: 64 * 1024 * 1024,
+                max_bytes_quotes: 8 * 1024 * 1024,
+                max_bytes_profiles: 16 * 1024 * 1024,
+                max_bytes_suggestions: 4 * 1024 * 1024,
+                max_bytes_holders: 4 * 1024 * 1024,
+            },
+            cors: CorsConfig {
+                allowed_origins: vec![],
+                allow_all_origins: true,
+            },
+            auth: AuthConfig {
+                enable_tezos_auth: false,
+                admin_tezos_addresses: vec![],
+                dev_mode: false,
+                cookie_hmac_key: generate_random_key(),
+                public_read_api: true,
+                jwt: None,
+                oidc: None,
+            },
+            market_data: MarketDataConfig {
+                provider: DataProvider::Mock,
+                mock_fixtures: None,
+                demo_mode: false,
+                exchange_preference: Vec::new(),
+                openfigi_enabled: false,
+                openfigi_api_key: None,
+                fred_api_key: None,
+                yahoo_connect_timeout: DEFAULT_YAHOO_CONNECT_TIMEOUT,
+                yahoo_read_timeout: DEFAULT_YAHOO_READ_TIMEOUT,
+                yahoo_proxy: None,
+                yahoo_user_agent: BROWSER_USER_AGENT.to_string(),
+            },
+            notifications: NotificationConfig::default(),
+            web_ui: WebUiConfig::default(),
+            jobs: JobConfig::default(),
+            portfolio_updates: PortfolioUpdateConfig::default(),
+            alerts: AlertConfig::default(),
+            stats_history: StatsHistoryConfig::default(),
+            cache_bus: CacheBusConfig::default(),
+            events: EventStreamConfig::default(),
+            object_export: ObjectExportConfig::default(),
+            latency: LatencyConfig::default(),
+            archive: ArchiveConfig::default(),
+            analysis: AnalysisConfig::default(),
+            analytics_sql: AnalyticsSqlConfig::default(),
+            anomalies: AnomalyConfig::default(),
+        }
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> Result<()> {
         if self.rate_limiting.api_requests_per_minute == 0 {
@@ -256,9 +1175,68 @@ impl Config {
         if self.rate_limiting.yahoo_api_requests_per_minute == 0 {
             anyhow::bail!("YAHOO_API_RATE_LIMIT_PER_MINUTE must be greater than 0");
         }
+        if self.rate_limiting.bulk_yahoo_requests_per_minute == 0 {
+            anyhow::bail!("BULK_YAHOO_RATE_LIMIT_PER_MINUTE must be greater than 0");
+        }
+        if self.rate_limiting.yahoo_quota.allocated_percent() > 100 {
+            anyhow::bail!("YAHOO_BUDGET_*_PERCENT shares must not add up to more than 100");
+        }
+        if let Some(url) = &self.cache_bus.redis_url {
+            let scheme = reqwest::Url::parse(url).map(|url| url.scheme().to_string()).unwrap_or_default();
+            if scheme != "redis" {
+                anyhow::bail!("CACHE_BUS_REDIS_URL must be a redis:// URL");
+            }
+        }
+        if let Some(url) = &self.events.nats_url {
+            let scheme = reqwest::Url::parse(url).map(|url| url.scheme().to_string()).unwrap_or_default();
+            if scheme != "nats" {
+                anyhow::bail!("EVENTS_NATS_URL must be a nats:// URL");
+            }
+        }
+        if self.events.subject_prefix.contains([' ', '*', '>']) {
+            anyhow::bail!("EVENTS_SUBJECT_PREFIX must not contain spaces or wildcards");
+        }
+        if let Some(storage) = &self.object_export.storage {
+            let scheme = reqwest::Url::parse(&storage.endpoint).map(|url| url.scheme().to_string()).unwrap_or_default();
+            if scheme != "http" && scheme != "https" {
+                anyhow::bail!("EXPORT_S3_ENDPOINT must be an http:// or https:// URL");
+            }
+            if storage.bucket.contains('/') {
+                anyhow::bail!("EXPORT_S3_BUCKET must be a bucket name, not a path");
+            }
+        }
         if self.database.max_connections == 0 {
             anyhow::bail!("DATABASE_MAX_CONNECTIONS must be greater than 0");
         }
+        if self.jobs.workers == 0 {
+            anyhow::bail!("JOB_WORKERS must be greater than 0");
+        }
+        if crate::validation::validate_symbol(&self.analysis.benchmark).is_err() {
+            anyhow::bail!("ANALYSIS_BENCHMARK must be a valid symbol");
+        }
+        if self
+            .market_data
+            .exchange_preference
+            .iter()
+            .any(|code| !code.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            anyhow::bail!("SYMBOL_EXCHANGE_PREFERENCE must be comma-separated exchange suffixes, e.g. DE,US");
+        }
+        if !RISK_FREE_RATE_RANGE.contains(&self.analysis.risk_free_rate) {
+            anyhow::bail!("ANALYSIS_RISK_FREE_RATE must be an annual fraction between -0.1 and 1, e.g. 0.04");
+        }
+        if !matches!(
+            self.database.journal_mode.to_ascii_lowercase().as_str(),
+            "delete" | "truncate" | "persist" | "memory" | "wal" | "off"
+        ) {
+            anyhow::bail!("DATABASE_JOURNAL_MODE must be one of DELETE, TRUNCATE, PERSIST, MEMORY, WAL, OFF");
+        }
+        if !matches!(
+            self.database.synchronous.to_ascii_lowercase().as_str(),
+            "off" | "normal" | "full" | "extra"
+        ) {
+            anyhow::bail!("DATABASE_SYNCHRONOUS must be one of OFF, NORMAL, FULL, EXTRA");
+        }
         Ok(())
     }
 }
@@ -266,11 +1244,370 @@ impl Config {
 // Constants for validation
 pub const MAX_SYMBOL_LENGTH: usize = 20;
 pub const MAX_SEARCH_QUERY_LENGTH: usize = 100;
+pub const MAX_NL_QUERY_LENGTH: usize = 200;
+pub const MAX_ANALYTICS_SQL_LENGTH: usize = 10_000;
+/// Most names a `?fields=` parameter may list
+pub const MAX_SELECTED_FIELDS: usize = 50;
+/// Upper bound for ANALYTICS_SQL_MAX_ROWS
+pub const MAX_ANALYTICS_SQL_ROWS: usize = 100_000;
 pub const MAX_BULK_SYMBOLS: usize = 20;
+pub const DEFAULT_YAHOO_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_YAHOO_READ_TIMEOUT: Duration = Duration::from_secs(15);
+/// Permits in the service's shared bulk semaphore, the ceiling for any concurrent fetch setting
+pub const MAX_BULK_CONCURRENCY: usize = 10;
+/// Default shares of YAHOO_DAILY_BUDGET in percent; the remaining 10% is for searches
+pub const DEFAULT_QUOTA_QUOTES_PERCENT: u32 = 40;
+pub const DEFAULT_QUOTA_HISTORICAL_PERCENT: u32 = 25;
+pub const DEFAULT_QUOTA_PROFILES_PERCENT: u32 = 10;
+pub const DEFAULT_QUOTA_BACKFILLS_PERCENT: u32 = 15;
 pub const MAX_COMPARE_SYMBOLS: usize = 10;
+pub const DEFAULT_COMPARE_PERIOD: i32 = 100;
 pub const DEFAULT_HISTORICAL_LIMIT: i32 = 100;
 pub const MAX_HISTORICAL_LIMIT: i32 = 1000;
+pub const DEFAULT_KLINES_LIMIT: i32 = 500;
+pub const DEFAULT_QUOTE_HISTORY_LIMIT: i64 = 500;
+pub const MAX_QUOTE_HISTORY_LIMIT: i64 = 5000;
+/// Bucket of `/api/symbols/{symbol}/stats` when none is given
+pub const DEFAULT_STATS_BUCKET: &str = "monthly";
 pub const MIN_TECHNICAL_INDICATOR_PERIODS: usize = 20;
+/// Lookback ranges accepted by the historical data verify endpoint (Yahoo notation)
+pub const VERIFY_RANGES: [&str; 10] = ["5d", "1mo", "3mo", "6mo", "ytd", "1y", "2y", "5y", "10y", "max"];
+pub const DEFAULT_VERIFY_RANGE: &str = "1mo";
+pub const DEFAULT_VERIFY_TOLERANCE: f64 = 0.000001;
+pub const MAX_VERIFY_TOLERANCE: f64 = 0.1;
+pub const DEFAULT_SUGGEST_LIMIT: i32 = 8;
+pub const MAX_SUGGEST_LIMIT: i32 = 20;
+pub const DEFAULT_MOVERS_LIMIT: i32 = 20;
+pub const MAX_MOVERS_LIMIT: i32 = 100;
+pub const DEFAULT_PEER_LIMIT: usize = 10;
+pub const MAX_PEER_LIMIT: usize = 50;
+pub const DEFAULT_PROJECTION_HORIZON_DAYS: u32 = 252;
+pub const MAX_PROJECTION_HORIZON_DAYS: u32 = 1260;
+pub const DEFAULT_PROJECTION_SIMULATIONS: u32 = 1000;
+pub const MAX_PROJECTION_SIMULATIONS: u32 = 10_000;
+pub const DEFAULT_PROJECTION_LOOKBACK_DAYS: u32 = 252;
+pub const MIN_PROJECTION_OBSERVATIONS: usize = 20;
+pub const DEFAULT_FORECAST_HORIZON: u32 = 30;
+pub const MAX_FORECAST_HORIZON: u32 = 252;
+/// Stored daily closes a forecast is fitted on, two trading years
+pub const FORECAST_LOOKBACK_BARS: i32 = 504;
+pub const MIN_FORECAST_OBSERVATIONS: usize = 60;
+pub const DEFAULT_FEATURE_WINDOW: usize = 20;
+pub const MAX_FEATURE_WINDOW: usize = 252;
+/// Custom indicators one feature export may add as columns
+pub const MAX_FEATURE_CUSTOM_INDICATORS: usize = 20;
+pub const MIN_BETA_OBSERVATIONS: usize = 20;
+pub const RISK_FREE_RATE_RANGE: std::ops::RangeInclusive<f64> = -0.1..=1.0;
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+pub const DEFAULT_INCOME_CALENDAR_DAYS: i64 = 90;
+pub const MAX_INCOME_CALENDAR_DAYS: i64 = 365;
+/// Longest per-alert cooldown and snooze, one year
+pub const MAX_ALERT_COOLDOWN_MINUTES: i64 = 366 * 24 * 60;
+/// Sector reported for portfolio exposure without a known sector
+pub const UNCLASSIFIED_SECTOR: &str = "Unclassified";
+/// Provider fetches in a row without data before a stored symbol is marked delisted
+pub const DELISTING_EMPTY_FETCHES: i64 = 5;
+/// Least time between the first and the last of those fetches, so a short outage or a burst
+/// of requests cannot delist a symbol
+pub const DELISTING_MIN_DAYS: i64 = 3;
+/// Exchange listings returned per company, the requested one included
+pub const MAX_LISTINGS: usize = 10;
+/// Stored FX rates older than this many days are refreshed before converting candles
+pub const FX_RATE_MAX_AGE_DAYS: i64 = 3;
+/// Days of a macro series fetched the first time it is requested
+pub const MACRO_HISTORY_DAYS: i64 = 10 * 365;
+/// Macro series are topped up from their source at most this often
+pub const MACRO_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+pub const MIN_JWT_SECRET_LENGTH: usize = 32;
+/// Failed password logins per client and per username before further attempts are refused
+/// until the oldest failure leaves the window
+pub const MAX_LOGIN_FAILURES: usize = 5;
+pub const LOGIN_FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+pub const DEFAULT_STATS_HISTORY_HOURS: i64 = 24;
+pub const MAX_STATS_HISTORY_HOURS: i64 = 7 * 24;
+pub const MIN_STATS_SAMPLE_INTERVAL_SECS: u64 = 10;
+pub const MIN_ANOMALY_SCAN_INTERVAL_SECS: u64 = 60;
+pub const MIN_ANOMALY_LOOKBACK_DAYS: usize = 20;
+pub const MAX_ANOMALY_LOOKBACK_DAYS: usize = 252;
+/// Latest daily bars each anomaly scan checks, so a missed scan or late candle is still caught
+pub const ANOMALY_RECENT_BARS: usize = 5;
+pub const DEFAULT_ANOMALY_LIMIT: i64 = 100;
+pub const MAX_ANOMALY_LIMIT: i64 = 1000;
+pub const DEFAULT_AUDIT_LIMIT: i64 = 50;
+pub const MAX_AUDIT_LIMIT: i64 = 500;
+pub const DEFAULT_WEBHOOK_DELIVERY_LIMIT: i64 = 50;
+pub const MAX_WEBHOOK_DELIVERY_LIMIT: i64 = 500;
+pub const DEFAULT_NOTIFICATION_LIMIT: i64 = 50;
+pub const MAX_NOTIFICATION_LIMIT: i64 = 500;
+pub const MAX_DASHBOARDS_PER_OWNER: i64 = 50;
+pub const MAX_DASHBOARD_WIDGETS: usize = 24;
+pub const MAX_DASHBOARD_NAME_LENGTH: usize = 100;
+pub const MAX_WATCHLIST_SYMBOLS: usize = 25;
+pub const MAX_CUSTOM_INDICATORS_PER_OWNER: i64 = 50;
+pub const MAX_INDICATOR_NAME_LENGTH: usize = 32;
+pub const MAX_EXPRESSION_LENGTH: usize = 200;
+/// Longest window a custom indicator function may take, in daily candles
+pub const MAX_EXPRESSION_PERIOD: usize = 250;
+pub const MAX_STRATEGIES_PER_OWNER: i64 = 50;
+pub const MAX_STRATEGY_NAME_LENGTH: usize = 100;
+pub const MAX_STRATEGY_DESCRIPTION_LENGTH: usize = 1000;
+pub const DEFAULT_JOURNAL_LIMIT: i64 = 50;
+pub const MAX_JOURNAL_LIMIT: i64 = 500;
+pub const MAX_JOURNAL_TAGS: usize = 10;
+pub const MAX_JOURNAL_TAG_LENGTH: usize = 32;
+pub const MAX_JOURNAL_SCREENSHOTS: usize = 10;
+pub const MAX_JOURNAL_RATIONALE_LENGTH: usize = 5000;
+pub const MAX_UNIVERSE_NAME_LENGTH: usize = 50;
+pub const MAX_UNIVERSE_MEMBERS: usize = 5000;
+/// Ranking model used when `/api/rankings` names none; it cannot be deleted
+pub const DEFAULT_RANKING_MODEL: &str = "default";
+pub const MAX_RANKING_MODEL_NAME_LENGTH: usize = 50;
+pub const MAX_RANKING_DESCRIPTION_LENGTH: usize = 1000;
+pub const MAX_RANKING_FACTOR_WEIGHT: f64 = 100.0;
+pub const DEFAULT_RANKING_LIMIT: i32 = 50;
+pub const MAX_RANKING_LIMIT: i32 = 500;
+pub const MAX_API_KEY_NAME_LENGTH: usize = 100;
+/// Days of history `/api/keys/:id/usage` reports by default, and at most
+pub const DEFAULT_KEY_USAGE_DAYS: i64 = 30;
+pub const MAX_KEY_USAGE_DAYS: i64 = 366;
+pub const DEFAULT_JOB_LIMIT: i64 = 50;
+pub const MAX_JOB_LIMIT: i64 = 500;
+pub const DEFAULT_EXPORT_RUN_LIMIT: i64 = 20;
+pub const MAX_EXPORT_RUN_LIMIT: i64 = 200;
+/// Request payloads longer than this are truncated in audit summaries
+pub const AUDIT_SUMMARY_MAX_CHARS: usize = 500;
+/// Largest mutating request body the audit middleware buffers (axum's default body limit)
+pub const MAX_AUDITED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// JWT settings from ENABLE_JWT_AUTH, JWT_ALGORITHM, JWT_SECRET (HS256),
+/// JWT_PRIVATE_KEY_PATH / JWT_PUBLIC_KEY_PATH (RS256), JWT_ISSUER, JWT_TTL_SECONDS,
+/// JWT_ADMIN_USERNAME and JWT_ADMIN_PASSWORD
+fn load_jwt_config() -> Result<Option<JwtConfig>> {
+    let enabled = std::env::var("ENABLE_JWT_AUTH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    let algorithm: JwtAlgorithm = std::env::var("JWT_ALGORITHM")
+        .unwrap_or_else(|_| "HS256".to_string())
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+
+    let keys = match algorithm {
+        JwtAlgorithm::Hs256 => {
+            let secret = std::env::var("JWT_SECRET")
+                .map_err(|_| anyhow::anyhow!("JWT_SECRET is required for HS256"))?;
+            if secret.len() < MIN_JWT_SECRET_LENGTH {
+                anyhow::bail!("JWT_SECRET must be at least {} characters", MIN_JWT_SECRET_LENGTH);
+            }
+            JwtKeys::Hmac(secret.into_bytes())
+        }
+        JwtAlgorithm::Rs256 => {
+            let read_pem = |var: &str| -> Result<Option<String>> {
+                match std::env::var(var) {
+                    Ok(path) => Ok(Some(std::fs::read_to_string(&path).map_err(|e| {
+                        anyhow::anyhow!("Failed to read {} ({}): {}", var, path, e)
+                    })?)),
+                    Err(_) => Ok(None),
+                }
+            };
+            let private_pem = read_pem("JWT_PRIVATE_KEY_PATH")?;
+            let public_pem = read_pem("JWT_PUBLIC_KEY_PATH")?;
+            JwtKeys::rsa_from_pem(private_pem.as_deref(), public_pem.as_deref())
+                .map_err(|e| anyhow::anyhow!(e))?
+        }
+    };
+
+    let jwt = JwtConfig {
+        keys,
+        issuer: std::env::var("JWT_ISSUER").unwrap_or_else(|_| "mango-data-service".to_string()),
+        ttl: Duration::from_secs(
+            std::env::var("JWT_TTL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600),
+        ),
+        admin_username: std::env::var("JWT_ADMIN_USERNAME").unwrap_or_else(|_| "admin".to_string()),
+        admin_password: std::env::var("JWT_ADMIN_PASSWORD").ok().filter(|p| !p.is_empty()),
+    };
+
+    tracing::info!("JWT Auth Configuration:");
+    tracing::info!("  JWT_ALGORITHM: {}", algorithm.as_str());
+    tracing::info!("  JWT_ISSUER: {}", jwt.issuer);
+    tracing::info!("  Token issuance: {}", jwt.admin_password.is_some() && jwt.keys.can_sign());
+
+    Ok(Some(jwt))
+}
+
+/// Object storage exports from EXPORT_S3_BUCKET, EXPORT_S3_ENDPOINT, EXPORT_S3_REGION,
+/// EXPORT_S3_ACCESS_KEY_ID, EXPORT_S3_SECRET_ACCESS_KEY, EXPORT_S3_PREFIX,
+/// EXPORT_S3_PATH_STYLE and EXPORT_INTERVAL_HOURS
+fn load_object_export_config() -> Result<ObjectExportConfig> {
+    let interval = std::env::var("EXPORT_INTERVAL_HOURS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(|hours| Duration::from_secs(hours * 3600))
+        .unwrap_or(ObjectExportConfig::default().interval);
+    let Some(bucket) = std::env::var("EXPORT_S3_BUCKET").ok().filter(|b| !b.trim().is_empty()) else {
+        return Ok(ObjectExportConfig { storage: None, interval });
+    };
+
+    let required = |var: &str| -> Result<String> {
+        std::env::var(var)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} is required when EXPORT_S3_BUCKET is set", var))
+    };
+    let region = std::env::var("EXPORT_S3_REGION")
+        .ok()
+        .filter(|r| !r.trim().is_empty())
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let custom_endpoint = std::env::var("EXPORT_S3_ENDPOINT")
+        .ok()
+        .map(|e| e.trim().trim_end_matches('/').to_string())
+        .filter(|e| !e.is_empty());
+    let path_style = std::env::var("EXPORT_S3_PATH_STYLE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(custom_endpoint.is_some());
+    let prefix = std::env::var("EXPORT_S3_PREFIX")
+        .ok()
+        .map(|p| p.trim().trim_matches('/').to_string())
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("{}/", p))
+        .unwrap_or_default();
+
+    let storage = S3Config {
+        endpoint: custom_endpoint.unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region)),
+        region,
+        bucket: bucket.trim().to_string(),
+        access_key_id: required("EXPORT_S3_ACCESS_KEY_ID")?,
+        secret_access_key: required("EXPORT_S3_SECRET_ACCESS_KEY")?,
+        prefix,
+        path_style,
+    };
+
+    tracing::info!("Object Export Configuration:");
+    tracing::info!("  EXPORT_S3_ENDPOINT: {}", storage.endpoint);
+    tracing::info!("  EXPORT_S3_BUCKET: {}", storage.bucket);
+    tracing::info!("  EXPORT_INTERVAL_HOURS: {}", interval.as_secs() / 3600);
+
+    Ok(ObjectExportConfig {
+        storage: Some(storage),
+        interval,
+    })
+}
+
+fn load_oidc_config() -> Result<Option<OidcConfig>> {
+    let enabled = std::env::var("ENABLE_OIDC_AUTH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    let required = |var: &str| -> Result<String> {
+        std::env::var(var)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} is required when ENABLE_OIDC_AUTH is set", var))
+    };
+
+    let provider: OidcProvider = std::env::var("OIDC_PROVIDER")
+        .unwrap_or_else(|_| "generic".to_string())
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    let issuer_url = match provider {
+        OidcProvider::Generic => Some(required("OIDC_ISSUER_URL")?),
+        _ => None,
+    };
+
+    let admin_emails: Vec<String> = required("OIDC_ADMIN_EMAILS")?
+        .split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let oidc = OidcConfig {
+        provider,
+        issuer_url,
+        client_id: required("OIDC_CLIENT_ID")?,
+        client_secret: required("OIDC_CLIENT_SECRET")?,
+        redirect_url: required("OIDC_REDIRECT_URL")?,
+        admin_emails,
+    };
+
+    tracing::info!("OIDC Auth Configuration:");
+    tracing::info!("  OIDC_PROVIDER: {}", oidc.provider.as_str());
+    tracing::info!("  OIDC_REDIRECT_URL: {}", oidc.redirect_url);
+    tracing::info!("  OIDC_ADMIN_EMAILS count: {}", oidc.admin_emails.len());
+
+    Ok(Some(oidc))
+}
+
+fn load_notification_config() -> Result<NotificationConfig> {
+    let var = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    let email = match var("SMTP_HOST") {
+        Some(host) => {
+            let tls: SmtpTls = var("SMTP_TLS")
+                .unwrap_or_else(|| "starttls".to_string())
+                .parse()
+                .map_err(|e: String| anyhow::anyhow!(e))?;
+            let default_port = match tls {
+                SmtpTls::Implicit => 465,
+                SmtpTls::StartTls => 587,
+                SmtpTls::None => 25,
+            };
+            let port = match var("SMTP_PORT") {
+                Some(p) => p.parse().map_err(|_| anyhow::anyhow!("Invalid SMTP_PORT '{}'", p))?,
+                None => default_port,
+            };
+            let to: Vec<String> = var("SMTP_TO")
+                .ok_or_else(|| anyhow::anyhow!("SMTP_TO is required when SMTP_HOST is set"))?
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            let from = var("SMTP_FROM")
+                .ok_or_else(|| anyhow::anyhow!("SMTP_FROM is required when SMTP_HOST is set"))?;
+
+            tracing::info!("Email notifications via {}:{} ({}) to {} recipient(s)", host, port, tls.as_str(), to.len());
+            Some(SmtpConfig {
+                host,
+                port,
+                tls,
+                username: var("SMTP_USERNAME"),
+                password: var("SMTP_PASSWORD"),
+                from,
+                to,
+            })
+        }
+        None => None,
+    };
+
+    let telegram = match (var("TELEGRAM_BOT_TOKEN"), var("TELEGRAM_CHAT_ID")) {
+        (Some(bot_token), Some(chat_id)) => {
+            tracing::info!("Telegram notifications enabled for chat {}", chat_id);
+            Some(TelegramConfig {
+                bot_token,
+                chat_id,
+                api_url: var("TELEGRAM_API_URL")
+                    .unwrap_or_else(|| "https://api.telegram.org".to_string())
+                    .trim_end_matches('/')
+                    .to_string(),
+            })
+        }
+        (None, None) => None,
+        _ => anyhow::bail!("TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together"),
+    };
+
+    Ok(NotificationConfig { email, telegram })
+}
 
 fn generate_random_key() -> [u8; 32] {
     let mut key = [0u8; 32];
@@ -1,20 +1,51 @@
+use crate::archive::{ImportMode, ARCHIVE_TABLES};
+use crate::auth::AdminSession;
+use crate::config::{DatabaseConfig, DELISTING_EMPTY_FETCHES, DELISTING_MIN_DAYS, TRADING_DAYS_PER_YEAR};
+use crate::db_writer::{DbWriter, WriteOp};
+use crate::indicators::SignalCondition;
 use crate::models::{PortfolioHolding, *};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+#[cfg(feature = "analytics")]
+use futures::TryStreamExt;
 use rust_decimal::Decimal;
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Row, Sqlite};
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow, SqliteSynchronous},
+    Column, FromRow, Pool, Row, Sqlite, SqliteConnection, TypeInfo, ValueRef,
+};
+#[cfg(feature = "analytics")]
+use sqlx::Executor;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
-use tracing::{info, warn};
+#[cfg(feature = "analytics")]
+use std::time::{Duration, Instant};
+use tracing::info;
 use uuid::Uuid;
 
 pub type DbPool = Pool<Sqlite>;
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Cheap to clone; clones share the connection pool and the writer task
+#[derive(Clone)]
 pub struct Database {
     pool: DbPool,
+    /// Owns the high-volume writes (candles, dividends, quotes, holding prices)
+    writer: DbWriter,
+    /// Opened read-only (READ_ONLY), leaving migrations and writes to another instance
+    read_only: bool,
 }
 
 impl Database {
-    pub async fn new(database_url: &str, max_connections: u32) -> Result<Self> {
+    /// Fresh, migrated in-memory database for tests and embedding
+    pub async fn in_memory() -> Result<Self> {
+        Self::new(&DatabaseConfig::in_memory()).await
+    }
+
+    pub async fn new(config: &DatabaseConfig) -> Result<Self> {
+        let database_url = config.url.as_str();
+
         // Handle SQLite-specific setup
         let processed_url = if database_url.starts_with("sqlite:") {
             // Extract the file path from the URL
@@ -25,8 +56,16 @@ impl Database {
                 database_url.strip_prefix("sqlite:").unwrap_or(database_url)
             };
 
-            // If it's not an in-memory database, ensure the directory exists
-            if file_path != ":memory:" && !file_path.is_empty() {
+            // A read-only instance needs the database another instance created and migrated;
+            // its directory may well be mounted read-only
+            if config.read_only {
+                if file_path == ":memory:" || !std::path::Path::new(file_path).exists() {
+                    anyhow::bail!("READ_ONLY needs an existing database file, but {} does not exist", file_path);
+                }
+                info!("Opening existing database file read-only: {}", file_path);
+                database_url.to_string()
+            } else if file_path != ":memory:" && !file_path.is_empty() {
+                // If it's not an in-memory database, ensure the directory exists
                 let db_path = std::path::Path::new(file_path);
                 
                 // Ensure the directory exists and is writable
@@ -76,19 +115,52 @@ impl Database {
             database_url.to_string()
         };
 
-        let pool = SqlitePoolOptions::new()
-            .max_connections(max_connections)
-            .connect(&processed_url)
-            .await?;
+        // Apply pragmas on every pooled connection so concurrent writers wait instead of
+        // failing immediately with "database is locked"
+        let mut connect_options = SqliteConnectOptions::from_str(&processed_url)?
+            .synchronous(SqliteSynchronous::from_str(&config.synchronous)?)
+            .busy_timeout(config.busy_timeout)
+            .foreign_keys(config.foreign_keys);
+        // The journal mode is a property of the file, set by the instance that writes it
+        connect_options = if config.read_only {
+            connect_options.read_only(true)
+        } else {
+            connect_options
+                .create_if_missing(true)
+                .journal_mode(SqliteJournalMode::from_str(&config.journal_mode)?)
+        };
+
+        info!(
+            "SQLite pragmas: journal_mode={}, synchronous={}, busy_timeout={}ms, foreign_keys={}",
+            config.journal_mode,
+            config.synchronous,
+            config.busy_timeout.as_millis(),
+            config.foreign_keys
+        );
+
+        let mut pool_options = SqlitePoolOptions::new().max_connections(config.max_connections);
+        if processed_url.contains(":memory:") {
+            // Closing the last connection would drop the whole in-memory database
+            pool_options = pool_options.min_connections(1).idle_timeout(None).max_lifetime(None);
+        }
+        let pool = pool_options.connect_with(connect_options).await?;
 
-        let db = Database { pool };
-        db.run_migrations().await?;
-        db.create_indexes().await?;
+        let writer = DbWriter::spawn(pool.clone());
+        let db = Database {
+            pool,
+            writer,
+            read_only: config.read_only,
+        };
+        if config.read_only {
+            db.check_schema_current().await?;
+        } else {
+            db.run_migrations().await?;
+        }
 
         // Verify portfolio data persistence by checking if we can read holdings
         if let Ok(holdings) = db.get_all_portfolio_holdings().await {
             info!("✅ Portfolio database initialized - {} holdings found", holdings.len());
-            if holdings.len() > 0 {
+            if !holdings.is_empty() {
                 info!("📊 Portfolio holdings persisted successfully");
             }
         }
@@ -96,153 +168,78 @@ impl Database {
         Ok(db)
     }
 
-    #[allow(dead_code)]
-    pub fn pool(&self) -> &DbPool {
-        &self.pool
+    /// Whether the database was opened read-only; every write fails
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// A read-only instance cannot migrate, so the writable instance must already have
+    /// brought the schema to exactly the version this build expects
+    async fn check_schema_current(&self) -> Result<()> {
+        let latest_known = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        let current = self.schema_version().await?.unwrap_or(0);
+        if current != latest_known {
+            anyhow::bail!(
+                "Database schema version {} does not match this build ({}); READ_ONLY instances \
+                 need a database migrated by a writable instance of the same version",
+                current,
+                latest_known
+            );
+        }
+        info!("Database opened read-only (schema version {})", current);
+        Ok(())
     }
 
     async fn run_migrations(&self) -> Result<()> {
         info!("Running database migrations...");
 
-        // Create symbols table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS symbols (
-                id TEXT PRIMARY KEY,
-                symbol TEXT UNIQUE NOT NULL,
-                name TEXT,
-                exchange TEXT,
-                sector TEXT,
-                industry TEXT,
-                market_cap TEXT, -- Decimal stored as TEXT
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // Create historical_prices table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS historical_prices (
-                id TEXT PRIMARY KEY,
-                symbol_id TEXT NOT NULL,
-                symbol TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                open TEXT NOT NULL, -- Decimal stored as TEXT
-                high TEXT NOT NULL,
-                low TEXT NOT NULL,
-                close TEXT NOT NULL,
-                adjusted_close TEXT,
-                volume INTEGER NOT NULL,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (symbol_id) REFERENCES symbols (id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // Create realtime_quotes table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS realtime_quotes (
-                id TEXT PRIMARY KEY,
-                symbol_id TEXT NOT NULL,
-                symbol TEXT NOT NULL,
-                price TEXT NOT NULL, -- Decimal stored as TEXT
-                change TEXT,
-                change_percent TEXT,
-                volume INTEGER,
-                market_time TEXT NOT NULL,
-                trading_session TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (symbol_id) REFERENCES symbols (id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // Create company_profiles table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS company_profiles (
-                id TEXT PRIMARY KEY,
-                symbol_id TEXT NOT NULL,
-                symbol TEXT UNIQUE NOT NULL,
-                company_name TEXT,
-                description TEXT,
-                sector TEXT,
-                industry TEXT,
-                employees INTEGER,
-                website TEXT,
-                address TEXT,
-                city TEXT,
-                state TEXT,
-                country TEXT,
-                zip_code TEXT,
-                phone TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (symbol_id) REFERENCES symbols (id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        // Refuse to start against a database migrated by a newer build; sqlx would
+        // otherwise only notice once it tries to resolve the unknown version
+        let latest_known = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        if let Some(current) = self.schema_version().await? {
+            if current > latest_known {
+                anyhow::bail!(
+                    "Database schema version {} is newer than this build supports ({})",
+                    current,
+                    latest_known
+                );
+            }
+        }
 
-        // Create portfolio_holdings table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS portfolio_holdings (
-                id TEXT PRIMARY KEY,
-                symbol TEXT NOT NULL,
-                symbol_id TEXT,
-                asset_type TEXT NOT NULL,
-                quantity TEXT NOT NULL,
-                purchase_price TEXT NOT NULL,
-                current_price TEXT,
-                current_value TEXT,
-                gain_loss TEXT,
-                gain_loss_percent TEXT,
-                last_updated TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (symbol_id) REFERENCES symbols (id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        MIGRATOR.run(&self.pool).await?;
 
-        info!("Database migrations completed successfully");
+        info!(
+            "Database migrations completed successfully (schema version {})",
+            self.schema_version().await?.unwrap_or(0)
+        );
         Ok(())
     }
 
-    async fn create_indexes(&self) -> Result<()> {
-        info!("Creating database indexes...");
+    /// Fold the WAL back into the main database file so a raw file copy is complete
+    pub async fn checkpoint(&self) -> Result<()> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 
-        let indexes = vec![
-            "CREATE INDEX IF NOT EXISTS idx_symbols_symbol ON symbols (symbol)",
-            "CREATE INDEX IF NOT EXISTS idx_historical_prices_symbol ON historical_prices (symbol)",
-            "CREATE INDEX IF NOT EXISTS idx_historical_prices_timestamp ON historical_prices (timestamp)",
-            "CREATE INDEX IF NOT EXISTS idx_historical_prices_symbol_timestamp ON historical_prices (symbol, timestamp)",
-            "CREATE INDEX IF NOT EXISTS idx_realtime_quotes_symbol ON realtime_quotes (symbol)",
-            "CREATE INDEX IF NOT EXISTS idx_realtime_quotes_market_time ON realtime_quotes (market_time)",
-            "CREATE INDEX IF NOT EXISTS idx_company_profiles_symbol ON company_profiles (symbol)",
-            "CREATE INDEX IF NOT EXISTS idx_portfolio_holdings_symbol ON portfolio_holdings (symbol)",
-            "CREATE INDEX IF NOT EXISTS idx_portfolio_holdings_asset_type ON portfolio_holdings (asset_type)",
-        ];
+    /// Highest successfully applied migration version, or `None` on a fresh database
+    pub async fn schema_version(&self) -> Result<Option<i64>> {
+        let table_exists: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
-        for index in indexes {
-            sqlx::query(index).execute(&self.pool).await?;
+        if table_exists == 0 {
+            return Ok(None);
         }
 
-        info!("Database indexes created successfully");
-        Ok(())
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(version)
     }
 
     // Symbol operations
@@ -288,97 +285,229 @@ impl Database {
         }
     }
 
-    pub async fn get_all_symbols(&self) -> Result<Vec<Symbol>> {
-        let rows = sqlx::query_as::<_, (String, String, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, String, String)>(
-            "SELECT id, symbol, name, exchange, sector, industry, market_cap, created_at, updated_at FROM symbols ORDER BY symbol"
+    pub async fn update_symbol_metadata(&self, symbol: &str, metadata: &SymbolMetadata) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE symbols SET
+                name = COALESCE(?2, name),
+                exchange = COALESCE(?3, exchange),
+                sector = COALESCE(?4, sector),
+                industry = COALESCE(?5, industry),
+                market_cap = COALESCE(?6, market_cap),
+                timezone = COALESCE(?7, timezone),
+                utc_offset_seconds = COALESCE(?8, utc_offset_seconds),
+                currency = COALESCE(?9, currency),
+                updated_at = ?10
+            WHERE symbol = ?1
+            "#,
         )
-        .fetch_all(&self.pool)
+        .bind(symbol)
+        .bind(&metadata.name)
+        .bind(&metadata.exchange)
+        .bind(&metadata.sector)
+        .bind(&metadata.industry)
+        .bind(metadata.market_cap.as_ref().map(|d| d.to_string()))
+        .bind(&metadata.timezone)
+        .bind(metadata.utc_offset_seconds)
+        .bind(&metadata.currency)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
         .await?;
 
-        let mut symbols = Vec::new();
-        for row in rows {
-            symbols.push(Symbol {
-                id: Uuid::from_str(&row.0)?,
-                symbol: row.1,
-                name: row.2,
-                exchange: row.3,
-                sector: row.4,
-                industry: row.5,
-                market_cap: row.6.as_ref().and_then(|s| Decimal::from_str(s).ok()),
-                created_at: DateTime::parse_from_rfc3339(&row.7)?.with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.8)?.with_timezone(&Utc),
-            });
-        }
+        Ok(())
+    }
 
-        Ok(symbols)
+    /// Count a provider fetch for a stored symbol. Data resets the count and reactivates a
+    /// delisted symbol; the `DELISTING_EMPTY_FETCHES`th fetch in a row without data, at least
+    /// `DELISTING_MIN_DAYS` after the first, marks it delisted. Returns the status it moved to.
+    pub async fn record_symbol_fetch(&self, symbol: &str, had_data: bool) -> Result<Option<SymbolStatus>> {
+        let row: Option<(String, i64, Option<String>)> = sqlx::query_as(
+            "SELECT status, empty_fetches, first_empty_fetch_at FROM symbols WHERE symbol = ?1",
+        )
+        .bind(symbol)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((status, empty_fetches, first_empty_fetch_at)) = row else {
+            return Ok(None);
+        };
+        let status: SymbolStatus = status.parse().map_err(anyhow::Error::msg)?;
+        let now = Utc::now();
+
+        let (new_status, empty_fetches, first_empty_fetch_at) = if had_data {
+            if status == SymbolStatus::Active && empty_fetches == 0 {
+                return Ok(None);
+            }
+            (SymbolStatus::Active, 0, None)
+        } else {
+            let first = first_empty_fetch_at
+                .as_deref()
+                .and_then(|at| parse_timestamp(at).ok())
+                .unwrap_or(now);
+            let empty_fetches = empty_fetches + 1;
+            let delisted = empty_fetches >= DELISTING_EMPTY_FETCHES
+                && now - first >= chrono::Duration::days(DELISTING_MIN_DAYS);
+            let new_status = if delisted { SymbolStatus::Delisted } else { status };
+            (new_status, empty_fetches, Some(first.to_rfc3339()))
+        };
+
+        let changed = new_status != status;
+        sqlx::query(
+            r#"
+            UPDATE symbols SET
+                status = ?2,
+                status_changed_at = CASE WHEN ?3 THEN ?4 ELSE status_changed_at END,
+                empty_fetches = ?5,
+                first_empty_fetch_at = ?6
+            WHERE symbol = ?1
+            "#,
+        )
+        .bind(symbol)
+        .bind(new_status.as_str())
+        .bind(changed)
+        .bind(now.to_rfc3339())
+        .bind(empty_fetches)
+        .bind(first_empty_fetch_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(changed.then_some(new_status))
     }
 
-    pub async fn search_symbols(&self, query: &str, limit: i32) -> Result<Vec<Symbol>> {
-        let search_pattern = format!("%{}%", query.to_uppercase());
-        let rows = sqlx::query_as::<_, (String, String, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, String, String)>(
-            "SELECT id, symbol, name, exchange, sector, industry, market_cap, created_at, updated_at 
-             FROM symbols 
-             WHERE UPPER(symbol) LIKE ?1 OR UPPER(COALESCE(name, '')) LIKE ?1 
-             ORDER BY symbol 
-             LIMIT ?2"
-        )
-        .bind(&search_pattern)
+    pub async fn get_all_symbols(&self, filter: &SymbolFilter) -> Result<Vec<Symbol>> {
+        let mut query = format!("SELECT {SYMBOL_COLUMNS} FROM symbols WHERE 1 = 1");
+        push_symbol_filter(&mut query, filter, 0);
+        query.push_str(" ORDER BY symbol");
+
+        let rows: Vec<SymbolRow> = bind_symbol_filter(sqlx::query_as(&query), filter)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(Symbol::try_from).collect()
+    }
+
+    /// Prefix-ranked suggestions: exact ticker, ticker prefix, name prefix, then name substring
+    pub async fn suggest_symbols(&self, query: &str, limit: i32) -> Result<Vec<SymbolSuggestion>> {
+        let upper = query.to_uppercase();
+        let prefix = format!("{upper}%");
+        let contains = format!("%{upper}%");
+
+        let rows: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT symbol, name, exchange
+            FROM symbols
+            WHERE UPPER(symbol) LIKE ?2 OR UPPER(COALESCE(name, '')) LIKE ?3
+            ORDER BY
+                CASE
+                    WHEN UPPER(symbol) = ?1 THEN 0
+                    WHEN UPPER(symbol) LIKE ?2 THEN 1
+                    WHEN UPPER(COALESCE(name, '')) LIKE ?2 THEN 2
+                    ELSE 3
+                END,
+                LENGTH(symbol),
+                symbol
+            LIMIT ?4
+            "#,
+        )
+        .bind(&upper)
+        .bind(&prefix)
+        .bind(&contains)
         .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut symbols = Vec::new();
-        for row in rows {
-            symbols.push(Symbol {
-                id: Uuid::from_str(&row.0)?,
-                symbol: row.1,
-                name: row.2,
-                exchange: row.3,
-                sector: row.4,
-                industry: row.5,
-                market_cap: row.6.as_ref().and_then(|s| Decimal::from_str(s).ok()),
-                created_at: DateTime::parse_from_rfc3339(&row.7)?.with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.8)?.with_timezone(&Utc),
-            });
-        }
+        Ok(rows
+            .into_iter()
+            .map(|(symbol, name, exchange)| SymbolSuggestion { symbol, name, exchange })
+            .collect())
+    }
 
-        Ok(symbols)
+    pub async fn get_symbol(&self, symbol: &str) -> Result<Option<Symbol>> {
+        let row: Option<SymbolRow> = sqlx::query_as(&format!(
+            "SELECT {SYMBOL_COLUMNS} FROM symbols WHERE symbol = ?1"
+        ))
+        .bind(symbol)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Symbol::try_from).transpose()
     }
 
-    // Historical price operations
-    pub async fn insert_historical_prices(&self, prices: &[HistoricalPrice]) -> Result<usize> {
-        let mut tx = self.pool.begin().await?;
-        let mut inserted = 0;
+    pub async fn search_symbols(
+        &self,
+        query: &str,
+        filter: &SymbolFilter,
+        limit: i32,
+    ) -> Result<Vec<Symbol>> {
+        let search_pattern = format!("%{}%", query.to_uppercase());
+        let mut sql = format!(
+            "SELECT {SYMBOL_COLUMNS}
+             FROM symbols
+             WHERE (UPPER(symbol) LIKE ?1 OR UPPER(COALESCE(name, '')) LIKE ?1)"
+        );
+        let bind_count = push_symbol_filter(&mut sql, filter, 1);
+        sql.push_str(&format!(" ORDER BY symbol LIMIT ?{}", bind_count + 1));
 
-        for price in prices {
-            let result = sqlx::query(
-                r#"
-                INSERT OR IGNORE INTO historical_prices 
-                (id, symbol_id, symbol, timestamp, open, high, low, close, adjusted_close, volume, created_at)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-                "#,
+        let rows: Vec<SymbolRow> =
+            bind_symbol_filter(sqlx::query_as(&sql).bind(&search_pattern), filter)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter().map(Symbol::try_from).collect()
+    }
+
+    pub async fn get_sector_summaries(&self) -> Result<Vec<SectorSummary>> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT sector, COUNT(*), COUNT(DISTINCT industry)
+            FROM symbols
+            WHERE sector IS NOT NULL AND sector != ''
+            GROUP BY sector
+            ORDER BY sector
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(sector, symbol_count, industry_count)| SectorSummary {
+                sector,
+                symbol_count,
+                industry_count,
+            })
+            .collect())
+    }
+
+    /// Symbols joined with their most recent stored real-time quote
+    pub async fn get_symbol_snapshots(&self, filter: &SymbolFilter) -> Result<Vec<SymbolSnapshot>> {
+        let mut query = String::from(
+            r#"
+            SELECT symbols.symbol, symbols.name, symbols.exchange, symbols.sector, symbols.industry,
+                   q.price, q.change_percent, q.volume, q.market_time
+            FROM symbols
+            LEFT JOIN realtime_quotes q ON q.id = (
+                SELECT id FROM realtime_quotes
+                WHERE symbol = symbols.symbol
+                ORDER BY market_time DESC
+                LIMIT 1
             )
-            .bind(price.id.to_string())
-            .bind(price.symbol_id.to_string())
-            .bind(&price.symbol)
-            .bind(price.timestamp.to_rfc3339())
-            .bind(price.open.to_string())
-            .bind(price.high.to_string())
-            .bind(price.low.to_string())
-            .bind(price.close.to_string())
-            .bind(price.adjusted_close.as_ref().map(|d| d.to_string()))
-            .bind(price.volume)
-            .bind(price.created_at.to_rfc3339())
-            .execute(&mut *tx)
+            WHERE 1 = 1
+            "#,
+        );
+        push_symbol_filter(&mut query, filter, 0);
+        query.push_str(" ORDER BY symbols.symbol");
+
+        let rows: Vec<SymbolSnapshotRow> = bind_symbol_filter(sqlx::query_as(&query), filter)
+            .fetch_all(&self.pool)
             .await?;
 
-            if result.rows_affected() > 0 {
-                inserted += 1;
-            }
-        }
+        rows.into_iter().map(SymbolSnapshot::try_from).collect()
+    }
 
-        tx.commit().await?;
-        Ok(inserted)
+    // Historical price operations
+    pub async fn insert_historical_prices(&self, prices: &[HistoricalPrice]) -> Result<usize> {
+        self.writer.write(WriteOp::HistoricalPrices(prices.to_vec())).await
     }
 
     pub async fn get_historical_prices(
@@ -388,9 +517,8 @@ impl Database {
         end_date: Option<DateTime<Utc>>,
         limit: Option<i32>,
     ) -> Result<Vec<HistoricalPrice>> {
-        let mut query = String::from(
-            "SELECT id, symbol_id, symbol, timestamp, open, high, low, close, adjusted_close, volume, created_at 
-             FROM historical_prices WHERE symbol = ?1"
+        let mut query = format!(
+            "SELECT {HISTORICAL_PRICE_COLUMNS} FROM historical_prices WHERE symbol = ?1"
         );
 
         let mut bind_count = 1;
@@ -410,7 +538,7 @@ impl Database {
             query.push_str(&format!(" LIMIT ?{bind_count}"));
         }
 
-        let mut sqlx_query = sqlx::query(&query).bind(symbol);
+        let mut sqlx_query = sqlx::query_as::<_, HistoricalPriceRow>(&query).bind(symbol);
 
         if let Some(start) = start_date {
             sqlx_query = sqlx_query.bind(start.to_rfc3339());
@@ -424,96 +552,281 @@ impl Database {
 
         let rows = sqlx_query.fetch_all(&self.pool).await?;
 
-        let mut prices = Vec::new();
-        for row in rows {
-            prices.push(HistoricalPrice {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol_id: Uuid::from_str(&row.get::<String, _>(1))?,
-                symbol: row.get(2),
-                timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>(3))?
-                    .with_timezone(&Utc),
-                open: Decimal::from_str(&row.get::<String, _>(4))?,
-                high: Decimal::from_str(&row.get::<String, _>(5))?,
-                low: Decimal::from_str(&row.get::<String, _>(6))?,
-                close: Decimal::from_str(&row.get::<String, _>(7))?,
-                adjusted_close: row
-                    .get::<Option<String>, _>(8)
-                    .as_ref()
-                    .and_then(|s| Decimal::from_str(s).ok()),
-                volume: row.get(9),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(10))?
-                    .with_timezone(&Utc),
-            });
-        }
-
-        Ok(prices)
+        rows.into_iter().map(HistoricalPrice::try_from).collect()
     }
 
-    // Real-time quote operations
-    pub async fn insert_realtime_quote(&self, quote: &RealTimeQuote) -> Result<()> {
-        sqlx::query(
+    /// OHLC, volume and daily return aggregates of a symbol's stored candles per calendar
+    /// bucket, newest first. Candles are bucketed by their date on the symbol's exchange.
+    pub async fn get_period_stats(
+        &self,
+        symbol: &str,
+        bucket: StatsBucket,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PeriodStats>> {
+        let period_start = match bucket {
+            StatsBucket::Weekly => "date(local_time, 'weekday 0', '-6 days')",
+            StatsBucket::Monthly => "strftime('%Y-%m-01', local_time)",
+            StatsBucket::Quarterly => {
+                "printf('%s-%02d-01', strftime('%Y', local_time), (CAST(strftime('%m', local_time) AS INTEGER) - 1) / 3 * 3 + 1)"
+            }
+            StatsBucket::Yearly => "strftime('%Y-01-01', local_time)",
+        };
+        let query = format!(
             r#"
-            INSERT INTO realtime_quotes 
-            (id, symbol_id, symbol, price, change, change_percent, volume, market_time, trading_session, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-            "#,
-        )
-        .bind(quote.id.to_string())
-        .bind(quote.symbol_id.to_string())
-        .bind(&quote.symbol)
-        .bind(quote.price.to_string())
-        .bind(quote.change.as_ref().map(|d| d.to_string()))
-        .bind(quote.change_percent.as_ref().map(|d| d.to_string()))
-        .bind(quote.volume)
-        .bind(quote.market_time.to_rfc3339())
-        .bind(&quote.trading_session)
-        .bind(quote.created_at.to_rfc3339())
-        .execute(&self.pool)
+            WITH local AS (
+                SELECT timestamp, open, high, low, close, volume,
+                       datetime(timestamp, printf('%+d seconds', COALESCE(
+                           (SELECT utc_offset_seconds FROM symbols WHERE symbol = ?1), 0))) AS local_time,
+                       CAST(close AS REAL) / LAG(CAST(close AS REAL)) OVER (ORDER BY timestamp) - 1 AS daily_return
+                FROM historical_prices
+                WHERE symbol = ?1 AND (?2 IS NULL OR timestamp >= ?2) AND (?3 IS NULL OR timestamp <= ?3)
+            ),
+            candles AS (
+                SELECT *, {period_start} AS period_start FROM local
+            ),
+            ranked AS (
+                SELECT *,
+                       FIRST_VALUE(open) OVER (PARTITION BY period_start ORDER BY timestamp) AS period_open,
+                       FIRST_VALUE(close) OVER (PARTITION BY period_start ORDER BY timestamp DESC) AS period_close,
+                       FIRST_VALUE(high) OVER (PARTITION BY period_start ORDER BY CAST(high AS REAL) DESC) AS period_high,
+                       FIRST_VALUE(low) OVER (PARTITION BY period_start ORDER BY CAST(low AS REAL)) AS period_low
+                FROM candles
+            ),
+            periods AS (
+                SELECT period_start,
+                       MIN(timestamp) AS first_timestamp,
+                       MAX(timestamp) AS last_timestamp,
+                       MAX(period_open) AS open,
+                       MAX(period_high) AS high,
+                       MAX(period_low) AS low,
+                       MAX(period_close) AS close,
+                       SUM(volume) AS volume,
+                       CAST(AVG(volume) AS INTEGER) AS average_volume,
+                       COUNT(*) AS candles,
+                       COUNT(daily_return) AS returns,
+                       AVG(daily_return) AS mean_return,
+                       AVG(daily_return * daily_return) AS mean_square_return
+                FROM ranked
+                GROUP BY period_start
+            )
+            SELECT *, LAG(close) OVER (ORDER BY period_start) AS previous_close
+            FROM periods
+            ORDER BY period_start DESC
+            "#
+        );
+
+        let rows: Vec<PeriodStatsRow> = sqlx::query_as(&query)
+            .bind(symbol)
+            .bind(from.map(|t| t.to_rfc3339()))
+            .bind(to.map(|t| t.to_rfc3339()))
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(PeriodStats::try_from).collect()
+    }
+
+    /// Close of the latest stored candle starting in `[from, to)`
+    pub async fn get_close_between(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Option<Decimal>> {
+        let close: Option<String> = sqlx::query_scalar(
+            "SELECT close FROM historical_prices WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3 \
+             ORDER BY timestamp DESC LIMIT 1",
+        )
+        .bind(symbol)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(parse_optional_decimal(close))
+    }
+
+    // Dividend operations
+    pub async fn insert_dividends(&self, dividends: &[Dividend]) -> Result<usize> {
+        self.writer.write(WriteOp::Dividends(dividends.to_vec())).await
+    }
+
+    /// Dividends with an ex-date on or after `since`, oldest first
+    pub async fn get_dividends(&self, symbol: &str, since: NaiveDate) -> Result<Vec<Dividend>> {
+        let rows: Vec<DividendRow> = sqlx::query_as(&format!(
+            "SELECT {DIVIDEND_COLUMNS} FROM dividends WHERE symbol = ?1 AND ex_date >= ?2 ORDER BY ex_date ASC"
+        ))
+        .bind(symbol)
+        .bind(since.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Dividend::try_from).collect()
+    }
+
+    // FX rate operations
+    pub async fn upsert_fx_rates(&self, pair: &str, rates: Vec<(NaiveDate, Decimal)>) -> Result<usize> {
+        self.writer
+            .write(WriteOp::FxRates {
+                pair: pair.to_string(),
+                rates,
+            })
+            .await
+    }
+
+    /// Stored daily rates of `pair` between `from` and `to` inclusive, oldest first
+    pub async fn get_fx_rates(&self, pair: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, Decimal)>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT date, rate FROM fx_rates WHERE pair = ?1 AND date >= ?2 AND date <= ?3 ORDER BY date ASC",
+        )
+        .bind(pair)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(date, rate)| Ok((NaiveDate::from_str(&date)?, Decimal::from_str(&rate)?)))
+            .collect()
+    }
+
+    // Macro series operations
+    pub async fn upsert_macro_observations(&self, series: &str, observations: Vec<(NaiveDate, Decimal)>) -> Result<usize> {
+        self.writer
+            .write(WriteOp::MacroObservations {
+                series: series.to_string(),
+                observations,
+            })
+            .await
+    }
+
+    /// Stored observations of `series` between `from` and `to` inclusive, oldest first
+    pub async fn get_macro_observations(&self, series: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, Decimal)>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT date, value FROM macro_observations WHERE series = ?1 AND date >= ?2 AND date <= ?3 ORDER BY date ASC",
+        )
+        .bind(series)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(date, value)| Ok((NaiveDate::from_str(&date)?, Decimal::from_str(&value)?)))
+            .collect()
+    }
+
+    /// Latest stored observation of `series`
+    pub async fn get_latest_macro_observation(&self, series: &str) -> Result<Option<(NaiveDate, Decimal)>> {
+        let row: Option<(String, String)> = sqlx::query_as(
+            "SELECT date, value FROM macro_observations WHERE series = ?1 ORDER BY date DESC LIMIT 1",
+        )
+        .bind(series)
+        .fetch_optional(&self.pool)
         .await?;
 
+        row.map(|(date, value)| Ok((NaiveDate::from_str(&date)?, Decimal::from_str(&value)?)))
+            .transpose()
+    }
+
+    // Real-time quote operations
+    pub async fn insert_realtime_quote(&self, quote: &RealTimeQuote) -> Result<()> {
+        self.writer.write(WriteOp::RealtimeQuote(quote.clone())).await?;
         Ok(())
     }
 
     pub async fn get_latest_quote(&self, symbol: &str) -> Result<Option<RealTimeQuote>> {
-        let row = sqlx::query(
-            "SELECT id, symbol_id, symbol, price, change, change_percent, volume, market_time, trading_session, created_at 
-             FROM realtime_quotes 
-             WHERE symbol = ?1 
-             ORDER BY market_time DESC 
+        let row: Option<RealTimeQuoteRow> = sqlx::query_as(&format!(
+            "SELECT {REALTIME_QUOTE_COLUMNS}
+             FROM realtime_quotes
+             WHERE symbol = ?1
+             ORDER BY market_time DESC
              LIMIT 1"
-        )
+        ))
         .bind(symbol)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(RealTimeQuote {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol_id: Uuid::from_str(&row.get::<String, _>(1))?,
-                symbol: row.get(2),
-                price: Decimal::from_str(&row.get::<String, _>(3))?,
-                change: row
-                    .get::<Option<String>, _>(4)
-                    .as_ref()
-                    .and_then(|s| Decimal::from_str(s).ok()),
-                change_percent: row
-                    .get::<Option<String>, _>(5)
-                    .as_ref()
-                    .and_then(|s| Decimal::from_str(s).ok()),
-                volume: row.get(6),
-                market_time: DateTime::parse_from_rfc3339(&row.get::<String, _>(7))?
-                    .with_timezone(&Utc),
-                trading_session: row.get(8),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(9))?
-                    .with_timezone(&Utc),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(RealTimeQuote::try_from).transpose()
+    }
+
+    /// Captured quotes with `market_time` in `[from, to]`, oldest first, keeping only the
+    /// latest capture of each `market_time`, plus the number of such ticks in the window
+    pub async fn get_quote_history(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<RealTimeQuote>, i64)> {
+        let rows: Vec<RealTimeQuoteRow> = sqlx::query_as(&format!(
+            "SELECT {REALTIME_QUOTE_COLUMNS}
+             FROM (
+                 SELECT *, ROW_NUMBER() OVER (PARTITION BY market_time ORDER BY created_at DESC) AS capture
+                 FROM realtime_quotes
+                 WHERE symbol = ?1 AND market_time >= ?2 AND market_time <= ?3
+             )
+             WHERE capture = 1
+             ORDER BY market_time ASC
+             LIMIT ?4 OFFSET ?5"
+        ))
+        .bind(symbol)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT market_time)
+             FROM realtime_quotes
+             WHERE symbol = ?1 AND market_time >= ?2 AND market_time <= ?3",
+        )
+        .bind(symbol)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let quotes = rows
+            .into_iter()
+            .map(RealTimeQuote::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        Ok((quotes, total))
+    }
+
+    /// Every distinct tick captured in `[from, to]`, oldest first, as in [`Database::get_quote_history`]
+    pub async fn get_captured_quotes(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<RealTimeQuote>> {
+        let rows: Vec<RealTimeQuoteRow> = sqlx::query_as(&format!(
+            "SELECT {REALTIME_QUOTE_COLUMNS}
+             FROM (
+                 SELECT *, ROW_NUMBER() OVER (PARTITION BY market_time ORDER BY created_at DESC) AS capture
+                 FROM realtime_quotes
+                 WHERE symbol = ?1 AND market_time >= ?2 AND market_time <= ?3
+             )
+             WHERE capture = 1
+             ORDER BY market_time ASC"
+        ))
+        .bind(symbol)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(RealTimeQuote::try_from).collect()
     }
 
     // Company profile operations
     pub async fn upsert_company_profile(&self, profile: &CompanyProfile) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query(
             r#"
             INSERT INTO company_profiles 
@@ -553,52 +866,83 @@ impl Database {
         .bind(&profile.phone)
         .bind(profile.created_at.to_rfc3339())
         .bind(profile.updated_at.to_rfc3339())
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        // Keep the merged profile as a point-in-time snapshot when it changed since the last one
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO company_profile_snapshots (id, as_of, {SNAPSHOT_PROFILE_COLUMNS})
+            SELECT ?2, ?3, {SNAPSHOT_PROFILE_COLUMNS} FROM company_profiles p
+            WHERE p.symbol = ?1 AND NOT EXISTS (
+                SELECT 1 FROM company_profile_snapshots s
+                WHERE s.symbol = p.symbol
+                  AND s.as_of = (SELECT MAX(as_of) FROM company_profile_snapshots WHERE symbol = p.symbol)
+                  AND s.company_name IS p.company_name AND s.description IS p.description
+                  AND s.sector IS p.sector AND s.industry IS p.industry
+                  AND s.employees IS p.employees AND s.website IS p.website
+                  AND s.address IS p.address AND s.city IS p.city AND s.state IS p.state
+                  AND s.country IS p.country AND s.zip_code IS p.zip_code AND s.phone IS p.phone
+            )
+            ON CONFLICT(symbol, as_of) DO UPDATE SET
+                company_name = excluded.company_name,
+                description = excluded.description,
+                sector = excluded.sector,
+                industry = excluded.industry,
+                employees = excluded.employees,
+                website = excluded.website,
+                address = excluded.address,
+                city = excluded.city,
+                state = excluded.state,
+                country = excluded.country,
+                zip_code = excluded.zip_code,
+                phone = excluded.phone,
+                updated_at = excluded.updated_at
+            "#
+        ))
+        .bind(&profile.symbol)
+        .bind(Uuid::new_v4().to_string())
+        .bind(profile.updated_at.date_naive().to_string())
+        .execute(&mut *tx)
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
-    pub async fn get_company_profile(&self, symbol: &str) -> Result<Option<CompanyProfile>> {
-        let row = sqlx::query(
-            "SELECT id, symbol_id, symbol, company_name, description, sector, industry, employees, 
-             website, address, city, state, country, zip_code, phone, created_at, updated_at
-             FROM company_profiles 
-             WHERE symbol = ?1",
-        )
+    /// The profile as it was known on `as_of`: the latest snapshot dated on or before it,
+    /// together with the snapshot date
+    pub async fn get_company_profile_as_of(
+        &self,
+        symbol: &str,
+        as_of: NaiveDate,
+    ) -> Result<Option<(NaiveDate, CompanyProfile)>> {
+        let row: Option<CompanyProfileSnapshotRow> = sqlx::query_as(&format!(
+            "SELECT as_of, {COMPANY_PROFILE_COLUMNS} FROM company_profile_snapshots \
+             WHERE symbol = ?1 AND as_of <= ?2 ORDER BY as_of DESC LIMIT 1"
+        ))
         .bind(symbol)
+        .bind(as_of.to_string())
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(CompanyProfile {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol_id: Uuid::from_str(&row.get::<String, _>(1))?,
-                symbol: row.get(2),
-                company_name: row.get(3),
-                description: row.get(4),
-                sector: row.get(5),
-                industry: row.get(6),
-                employees: row.get(7),
-                website: row.get(8),
-                address: row.get(9),
-                city: row.get(10),
-                state: row.get(11),
-                country: row.get(12),
-                zip_code: row.get(13),
-                phone: row.get(14),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(15))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(16))?
-                    .with_timezone(&Utc),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(|row| Ok((NaiveDate::from_str(&row.as_of)?, CompanyProfile::try_from(row.profile)?)))
+            .transpose()
     }
 
-    // Analytics and utility functions
-    pub async fn get_database_stats(&self) -> Result<serde_json::Value> {
+    pub async fn get_company_profile(&self, symbol: &str) -> Result<Option<CompanyProfile>> {
+        let row: Option<CompanyProfileRow> = sqlx::query_as(&format!(
+            "SELECT {COMPANY_PROFILE_COLUMNS} FROM company_profiles WHERE symbol = ?1"
+        ))
+        .bind(symbol)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(CompanyProfile::try_from).transpose()
+    }
+
+    // Analytics and utility functions
+    pub async fn get_database_stats(&self) -> Result<serde_json::Value> {
         let symbols_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM symbols")
             .fetch_one(&self.pool)
             .await?;
@@ -615,7 +959,10 @@ impl Database {
             .fetch_one(&self.pool)
             .await?;
 
+        let schema_version = self.schema_version().await?;
+
         Ok(serde_json::json!({
+            "schema_version": schema_version,
             "symbols_count": symbols_count,
             "historical_records_count": historical_count,
             "realtime_quotes_count": quotes_count,
@@ -628,6 +975,49 @@ impl Database {
         }))
     }
 
+    pub async fn insert_stats_sample(&self, sample: &StatsSample) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO stats_samples
+                (sampled_at, interval_seconds, yahoo_calls, yahoo_rate_limited, caches)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(sample.sampled_at.to_rfc3339())
+        .bind(sample.interval_seconds as i64)
+        .bind(sample.yahoo_calls as i64)
+        .bind(sample.yahoo_rate_limited as i64)
+        .bind(serde_json::to_string(&sample.caches)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stored samples taken at or after `since`, oldest first
+    pub async fn get_stats_samples(&self, since: DateTime<Utc>) -> Result<Vec<StatsSample>> {
+        let rows: Vec<StatsSampleRow> = sqlx::query_as(
+            r#"
+            SELECT sampled_at, interval_seconds, yahoo_calls, yahoo_rate_limited, caches
+            FROM stats_samples WHERE sampled_at >= ?1 ORDER BY sampled_at
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(StatsSample::try_from).collect()
+    }
+
+    pub async fn delete_stats_samples_before(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM stats_samples WHERE sampled_at < ?1")
+            .bind(before.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
     // Portfolio operations
     pub async fn add_portfolio_holding(
         &self,
@@ -642,6 +1032,8 @@ impl Database {
         // Try to get symbol_id if symbol exists
         let symbol_id = self.get_symbol_id(symbol).await.ok().flatten();
 
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query(
             r#"
             INSERT INTO portfolio_holdings 
@@ -657,133 +1049,45 @@ impl Database {
         .bind(purchase_price.to_string())
         .bind(now.to_rfc3339())
         .bind(now.to_rfc3339())
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
+        insert_portfolio_lot(&mut tx, holding_id, quantity, purchase_price, now).await?;
+
+        tx.commit().await?;
         Ok(holding_id)
     }
 
     pub async fn get_all_portfolio_holdings(&self) -> Result<Vec<PortfolioHolding>> {
-        let rows = sqlx::query(
-            "SELECT id, symbol, symbol_id, asset_type, quantity, purchase_price, 
-             current_price, current_value, gain_loss, gain_loss_percent, last_updated, 
-             created_at, updated_at 
-             FROM portfolio_holdings ORDER BY created_at DESC"
-        )
+        let rows: Vec<PortfolioHoldingRow> = sqlx::query_as(&format!(
+            "SELECT {PORTFOLIO_HOLDING_COLUMNS} FROM portfolio_holdings ORDER BY created_at DESC"
+        ))
         .fetch_all(&self.pool)
         .await?;
 
-        let mut holdings = Vec::new();
-        for row in rows {
-            holdings.push(PortfolioHolding {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol: row.get(1),
-                symbol_id: row.get::<Option<String>, _>(2)
-                    .and_then(|s| Uuid::from_str(&s).ok()),
-                asset_type: row.get(3),
-                quantity: Decimal::from_str(&row.get::<String, _>(4))?,
-                purchase_price: Decimal::from_str(&row.get::<String, _>(5))?,
-                current_price: row.get::<Option<String>, _>(6)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                current_value: row.get::<Option<String>, _>(7)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss: row.get::<Option<String>, _>(8)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss_percent: row.get::<Option<String>, _>(9)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                last_updated: row.get::<Option<String>, _>(10)
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(11))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(12))?
-                    .with_timezone(&Utc),
-            });
-        }
-
-        Ok(holdings)
+        rows.into_iter().map(PortfolioHolding::try_from).collect()
     }
 
     pub async fn get_portfolio_holding(&self, holding_id: Uuid) -> Result<Option<PortfolioHolding>> {
-        let row = sqlx::query(
-            "SELECT id, symbol, symbol_id, asset_type, quantity, purchase_price, 
-             current_price, current_value, gain_loss, gain_loss_percent, last_updated, 
-             created_at, updated_at 
-             FROM portfolio_holdings WHERE id = ?1"
-        )
+        let row: Option<PortfolioHoldingRow> = sqlx::query_as(&format!(
+            "SELECT {PORTFOLIO_HOLDING_COLUMNS} FROM portfolio_holdings WHERE id = ?1"
+        ))
         .bind(holding_id.to_string())
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(PortfolioHolding {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol: row.get(1),
-                symbol_id: row.get::<Option<String>, _>(2)
-                    .and_then(|s| Uuid::from_str(&s).ok()),
-                asset_type: row.get(3),
-                quantity: Decimal::from_str(&row.get::<String, _>(4))?,
-                purchase_price: Decimal::from_str(&row.get::<String, _>(5))?,
-                current_price: row.get::<Option<String>, _>(6)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                current_value: row.get::<Option<String>, _>(7)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss: row.get::<Option<String>, _>(8)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss_percent: row.get::<Option<String>, _>(9)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                last_updated: row.get::<Option<String>, _>(10)
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(11))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(12))?
-                    .with_timezone(&Utc),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(PortfolioHolding::try_from).transpose()
     }
 
     pub async fn get_portfolio_holding_by_symbol(&self, symbol: &str) -> Result<Option<PortfolioHolding>> {
-        let row = sqlx::query(
-            "SELECT id, symbol, symbol_id, asset_type, quantity, purchase_price, 
-             current_price, current_value, gain_loss, gain_loss_percent, last_updated, 
-             created_at, updated_at 
-             FROM portfolio_holdings WHERE symbol = ?1 LIMIT 1"
-        )
+        let row: Option<PortfolioHoldingRow> = sqlx::query_as(&format!(
+            "SELECT {PORTFOLIO_HOLDING_COLUMNS} FROM portfolio_holdings WHERE symbol = ?1 LIMIT 1"
+        ))
         .bind(symbol)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(PortfolioHolding {
-                id: Uuid::from_str(&row.get::<String, _>(0))?,
-                symbol: row.get(1),
-                symbol_id: row.get::<Option<String>, _>(2)
-                    .and_then(|s| Uuid::from_str(&s).ok()),
-                asset_type: row.get(3),
-                quantity: Decimal::from_str(&row.get::<String, _>(4))?,
-                purchase_price: Decimal::from_str(&row.get::<String, _>(5))?,
-                current_price: row.get::<Option<String>, _>(6)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                current_value: row.get::<Option<String>, _>(7)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss: row.get::<Option<String>, _>(8)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                gain_loss_percent: row.get::<Option<String>, _>(9)
-                    .and_then(|s| Decimal::from_str(&s).ok()),
-                last_updated: row.get::<Option<String>, _>(10)
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(11))?
-                    .with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>(12))?
-                    .with_timezone(&Utc),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(PortfolioHolding::try_from).transpose()
     }
 
     pub async fn update_portfolio_holding(
@@ -818,21 +1122,31 @@ impl Database {
             updates.join(", ")
         );
 
+        let mut tx = self.pool.begin().await?;
+
         let mut sqlx_query = sqlx::query(&query);
         for value in bind_values.iter() {
             sqlx_query = sqlx_query.bind(value);
         }
 
-        sqlx_query.execute(&self.pool).await?;
+        sqlx_query.execute(&mut *tx).await?;
+
+        // A manual edit replaces the purchase history with a single lot at the new values
+        collapse_portfolio_lots(&mut tx, holding_id).await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Add a purchase to an existing holding. The holding keeps the combined quantity at the
+    /// weighted average price; with `keep_lots` the purchase is also recorded as its own lot,
+    /// otherwise all lots are merged into one.
     pub async fn merge_portfolio_holding(
         &self,
         holding_id: Uuid,
         new_quantity: Decimal,
         new_purchase_price: Decimal,
+        keep_lots: bool,
     ) -> Result<()> {
         // Get existing holding
         let existing = match self.get_portfolio_holding(holding_id).await? {
@@ -850,53 +1164,4027 @@ impl Database {
             new_purchase_price
         };
 
-        // Update the holding with merged values
-        self.update_portfolio_holding(
-            holding_id,
-            Some(combined_quantity),
-            Some(average_purchase_price),
-        ).await?;
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE portfolio_holdings SET quantity = ?1, purchase_price = ?2, updated_at = ?3 WHERE id = ?4",
+        )
+        .bind(combined_quantity.to_string())
+        .bind(average_purchase_price.to_string())
+        .bind(now.to_rfc3339())
+        .bind(holding_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        if keep_lots {
+            insert_portfolio_lot(&mut tx, holding_id, new_quantity, new_purchase_price, now).await?;
+        } else {
+            collapse_portfolio_lots(&mut tx, holding_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Purchase lots of every holding, oldest first
+    pub async fn get_all_portfolio_lots(&self) -> Result<Vec<PortfolioLot>> {
+        let rows: Vec<PortfolioLotRow> = sqlx::query_as(&format!(
+            "SELECT {PORTFOLIO_LOT_COLUMNS} FROM portfolio_lots ORDER BY acquired_at ASC, created_at ASC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(PortfolioLot::try_from).collect()
+    }
+
+    pub async fn get_portfolio_settings(&self) -> Result<PortfolioSettings> {
+        let mut settings = PortfolioSettings::default();
+        if let Some(lot_mode) = self.get_portfolio_setting("lot_mode").await? {
+            settings.lot_mode = lot_mode;
+        }
+        Ok(settings)
+    }
+
+    pub async fn get_portfolio_setting(&self, key: &str) -> Result<Option<String>> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM portfolio_settings WHERE key = ?1")
+                .bind(key)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(value)
+    }
+
+    pub async fn set_portfolio_setting(&self, key: &str, value: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO portfolio_settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Write refreshed holding values in one transaction; returns the holdings updated
+    pub async fn update_portfolio_holding_prices(&self, updates: Vec<HoldingPriceUpdate>) -> Result<usize> {
+        self.write_holding_prices(updates, false).await
+    }
+
+    /// Write the holding values of a background refresh cycle and stamp the cycle's time as
+    /// the portfolio's last refresh, all in one transaction
+    pub async fn record_portfolio_refresh(&self, updates: Vec<HoldingPriceUpdate>) -> Result<usize> {
+        self.write_holding_prices(updates, true).await
+    }
+
+    async fn write_holding_prices(&self, updates: Vec<HoldingPriceUpdate>, refresh_cycle: bool) -> Result<usize> {
+        if updates.is_empty() {
+            return Ok(0);
+        }
+        self.writer
+            .write(WriteOp::HoldingPrices {
+                updates,
+                updated_at: Utc::now(),
+                refresh_cycle,
+            })
+            .await
+    }
+
+    /// Time of the last background refresh that updated holding prices
+    pub async fn get_portfolio_last_refresh(&self) -> Result<Option<DateTime<Utc>>> {
+        let Some(value) = self.get_portfolio_setting(LAST_REFRESH_SETTING).await? else {
+            return Ok(None);
+        };
+        Ok(Some(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc)))
+    }
+
+    // Cash operations
+    pub async fn add_cash_flow(&self, flow: &CashFlow) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO cash_flows (id, kind, amount, flow_date, notes, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(flow.id.to_string())
+        .bind(&flow.kind)
+        .bind(flow.amount.to_string())
+        .bind(flow.flow_date.to_string())
+        .bind(&flow.notes)
+        .bind(flow.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// All cash flows, oldest first
+    pub async fn get_cash_flows(&self) -> Result<Vec<CashFlow>> {
+        let rows: Vec<CashFlowRow> = sqlx::query_as(&format!(
+            "SELECT {CASH_FLOW_COLUMNS} FROM cash_flows ORDER BY flow_date ASC, created_at ASC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(CashFlow::try_from).collect()
+    }
+
+    // Transaction ledger operations
+    pub async fn add_portfolio_transaction(&self, transaction: &PortfolioTransaction) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO portfolio_transactions
+            (id, symbol, side, quantity, price, fees, trade_date, notes, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(transaction.id.to_string())
+        .bind(&transaction.symbol)
+        .bind(&transaction.side)
+        .bind(transaction.quantity.to_string())
+        .bind(transaction.price.to_string())
+        .bind(transaction.fees.to_string())
+        .bind(transaction.trade_date.to_string())
+        .bind(&transaction.notes)
+        .bind(transaction.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
 
-    pub async fn update_portfolio_holding_prices(
+    /// Ledger entries in trade order, optionally for one symbol
+    pub async fn get_portfolio_transactions(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<Vec<PortfolioTransaction>> {
+        let rows: Vec<PortfolioTransactionRow> = sqlx::query_as(&format!(
+            "SELECT {PORTFOLIO_TRANSACTION_COLUMNS} FROM portfolio_transactions \
+             WHERE ?1 IS NULL OR symbol = ?1 ORDER BY trade_date ASC, created_at ASC"
+        ))
+        .bind(symbol)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(PortfolioTransaction::try_from).collect()
+    }
+
+    /// Returns false when no transaction had that id
+    pub async fn delete_portfolio_transaction(&self, transaction_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM portfolio_transactions WHERE id = ?1")
+            .bind(transaction_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set or clear a holding's target allocation (percent of portfolio value)
+    pub async fn set_portfolio_target_weight(
         &self,
         holding_id: Uuid,
-        current_price: Decimal,
-        current_value: Decimal,
-        gain_loss: Decimal,
-        gain_loss_percent: Decimal,
+        target_weight: Option<Decimal>,
     ) -> Result<()> {
-        let now = Utc::now();
+        sqlx::query("UPDATE portfolio_holdings SET target_weight = ?1, updated_at = ?2 WHERE id = ?3")
+            .bind(target_weight.map(|w| w.to_string()))
+            .bind(Utc::now().to_rfc3339())
+            .bind(holding_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set exit levels of a holding from an update request; `Some(0)` clears a level and `None`
+    /// leaves it unchanged. Changing the stop or target re-arms its alert, and a new trailing
+    /// stop starts tracking from the current price. The alert cooldown, re-arm distance and
+    /// channels are stored as given.
+    pub async fn set_holding_exit_levels(&self, holding_id: Uuid, request: &UpdateHoldingRequest) -> Result<()> {
+        let UpdateHoldingRequest {
+            stop_loss,
+            trailing_stop_percent,
+            target_price,
+            alert_cooldown_minutes,
+            alert_rearm_percent,
+            ref alert_channels,
+            ..
+        } = *request;
+        let mut updates = Vec::new();
+        let mut bind_values: Vec<Option<String>> = Vec::new();
+        let non_zero = |value: Decimal| (!value.is_zero()).then(|| value.to_string());
+
+        if let Some(stop) = stop_loss {
+            updates.push("stop_loss = ?");
+            bind_values.push(non_zero(stop));
+        }
+        if let Some(percent) = trailing_stop_percent {
+            updates.push("trailing_stop_percent = ?");
+            bind_values.push(non_zero(percent));
+            updates.push("high_water_mark = current_price");
+        }
+        if stop_loss.is_some() || trailing_stop_percent.is_some() {
+            updates.push("stop_triggered_at = NULL");
+        }
+        if let Some(target) = target_price {
+            updates.push("target_price = ?");
+            bind_values.push(non_zero(target));
+            updates.push("target_triggered_at = NULL");
+        }
+        if let Some(minutes) = alert_cooldown_minutes {
+            updates.push("alert_cooldown_minutes = ?");
+            bind_values.push(Some(minutes.to_string()));
+        }
+        if let Some(percent) = alert_rearm_percent {
+            updates.push("alert_rearm_percent = ?");
+            bind_values.push(Some(percent.to_string()));
+        }
+        if let Some(channels) = alert_channels {
+            updates.push("alert_channels = ?");
+            bind_values.push(Some(join_channels(channels)));
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        updates.push("updated_at = ?");
+        bind_values.push(Some(Utc::now().to_rfc3339()));
+        bind_values.push(Some(holding_id.to_string()));
+
+        let query = format!(
+            "UPDATE portfolio_holdings SET {} WHERE id = ?",
+            updates.join(", ")
+        );
+        let mut sqlx_query = sqlx::query(&query);
+        for value in bind_values {
+            sqlx_query = sqlx_query.bind(value);
+        }
+        sqlx_query.execute(&self.pool).await?;
 
+        Ok(())
+    }
+
+    /// Record the trailing high, which of the stop and target are waiting to re-arm, and when
+    /// an alert last fired
+    pub async fn update_holding_alert_state(&self, holding: &PortfolioHolding) -> Result<()> {
         sqlx::query(
             r#"
-            UPDATE portfolio_holdings 
-            SET current_price = ?1, current_value = ?2, gain_loss = ?3, 
-                gain_loss_percent = ?4, last_updated = ?5, updated_at = ?6
-            WHERE id = ?7
+            UPDATE portfolio_holdings
+            SET high_water_mark = ?1, stop_triggered_at = ?2, target_triggered_at = ?3,
+                last_alert_at = ?4
+            WHERE id = ?5
             "#,
         )
-        .bind(current_price.to_string())
-        .bind(current_value.to_string())
-        .bind(gain_loss.to_string())
-        .bind(gain_loss_percent.to_string())
-        .bind(now.to_rfc3339())
-        .bind(now.to_rfc3339())
-        .bind(holding_id.to_string())
+        .bind(holding.high_water_mark.map(|p| p.to_string()))
+        .bind(holding.stop_triggered_at.map(|t| t.to_rfc3339()))
+        .bind(holding.target_triggered_at.map(|t| t.to_rfc3339()))
+        .bind(holding.last_alert_at.map(|t| t.to_rfc3339()))
+        .bind(holding.id.to_string())
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Snooze a holding's stop and target alerts until `until`, or unmute them with `None`.
+    /// Returns false when no holding has this id.
+    pub async fn set_holding_alerts_muted_until(
+        &self,
+        holding_id: Uuid,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let result = sqlx::query("UPDATE portfolio_holdings SET alerts_muted_until = ?1 WHERE id = ?2")
+            .bind(until.map(|t| t.to_rfc3339()))
+            .bind(holding_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replace every holding's target allocation in one transaction; holdings missing from
+    /// `targets` have their target cleared.
+    pub async fn replace_portfolio_target_weights(
+        &self,
+        targets: &HashMap<String, Decimal>,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE portfolio_holdings SET target_weight = NULL, updated_at = ?1")
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+        for (symbol, weight) in targets {
+            sqlx::query(
+                "UPDATE portfolio_holdings SET target_weight = ?1, updated_at = ?2 WHERE symbol = ?3",
+            )
+            .bind(weight.to_string())
+            .bind(&now)
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn delete_portfolio_holding(&self, holding_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM portfolio_lots WHERE holding_id = ?1")
+            .bind(holding_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
         sqlx::query("DELETE FROM portfolio_holdings WHERE id = ?1")
             .bind(holding_id.to_string())
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Admin session operations
+    pub async fn create_admin_session(&self, session: &AdminSession) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO admin_sessions (id, method, subject, user_agent, created_at, expires_at, revoked_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL)
+            "#,
+        )
+        .bind(session.id.to_string())
+        .bind(&session.method)
+        .bind(&session.subject)
+        .bind(&session.user_agent)
+        .bind(session.created_at.to_rfc3339())
+        .bind(session.expires_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The session if it exists, has not expired and has not been revoked
+    pub async fn get_active_admin_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<Option<AdminSession>> {
+        let row: Option<AdminSessionRow> = sqlx::query_as(&format!(
+            "SELECT {ADMIN_SESSION_COLUMNS} FROM admin_sessions \
+             WHERE id = ?1 AND revoked_at IS NULL AND expires_at > ?2"
+        ))
+        .bind(session_id.to_string())
+        .bind(now.to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(AdminSession::try_from).transpose()
+    }
+
+    /// Active sessions, newest first
+    pub async fn get_active_admin_sessions(&self, now: DateTime<Utc>) -> Result<Vec<AdminSession>> {
+        let rows: Vec<AdminSessionRow> = sqlx::query_as(&format!(
+            "SELECT {ADMIN_SESSION_COLUMNS} FROM admin_sessions \
+             WHERE revoked_at IS NULL AND expires_at > ?1 ORDER BY created_at DESC"
+        ))
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AdminSession::try_from).collect()
+    }
+
+    /// Revoke one session, or every active session when `session_id` is None.
+    /// Returns the number of sessions revoked.
+    pub async fn revoke_admin_sessions(&self, session_id: Option<Uuid>, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE admin_sessions SET revoked_at = ?1 \
+             WHERE revoked_at IS NULL AND (?2 IS NULL OR id = ?2)",
+        )
+        .bind(now.to_rfc3339())
+        .bind(session_id.map(|id| id.to_string()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Audit log operations
+    pub async fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (id, actor_type, actor, action, method, path, status_code, summary, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(entry.id.to_string())
+        .bind(&entry.actor_type)
+        .bind(&entry.actor)
+        .bind(&entry.action)
+        .bind(&entry.method)
+        .bind(&entry.path)
+        .bind(entry.status_code as i64)
+        .bind(&entry.summary)
+        .bind(entry.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
+
+    /// Matching entries newest first, plus the total match count for pagination
+    pub async fn get_audit_entries(
+        &self,
+        filter: &AuditFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<AuditEntry>, i64)> {
+        let where_clause = "WHERE (?1 IS NULL OR actor = ?1) \
+             AND (?2 IS NULL OR actor_type = ?2) \
+             AND (?3 IS NULL OR action = ?3) \
+             AND (?4 IS NULL OR substr(path, 1, length(?4)) = ?4) \
+             AND (?5 IS NULL OR created_at >= ?5) \
+             AND (?6 IS NULL OR created_at < ?6)";
+        let since = filter.since.map(|t| t.to_rfc3339());
+        let until = filter.until.map(|t| t.to_rfc3339());
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_log {where_clause}"))
+            .bind(&filter.actor)
+            .bind(&filter.actor_type)
+            .bind(&filter.action)
+            .bind(&filter.path)
+            .bind(&since)
+            .bind(&until)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows: Vec<AuditEntryRow> = sqlx::query_as(&format!(
+            "SELECT {AUDIT_COLUMNS} FROM audit_log {where_clause} \
+             ORDER BY created_at DESC LIMIT ?7 OFFSET ?8"
+        ))
+        .bind(&filter.actor)
+        .bind(&filter.actor_type)
+        .bind(&filter.action)
+        .bind(&filter.path)
+        .bind(&since)
+        .bind(&until)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows.into_iter().map(AuditEntry::try_from).collect::<Result<Vec<_>>>()?;
+        Ok((entries, total))
+    }
+
+    pub async fn delete_expired_admin_sessions(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM admin_sessions WHERE expires_at <= ?1")
+            .bind(now.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Webhook operations
+    pub async fn create_webhook(&self, webhook: &Webhook) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhooks (id, url, events, secret, description, active, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(webhook.id.to_string())
+        .bind(&webhook.url)
+        .bind(webhook.events.join(","))
+        .bind(&webhook.secret)
+        .bind(&webhook.description)
+        .bind(webhook.active)
+        .bind(webhook.created_at.to_rfc3339())
+        .bind(webhook.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_webhooks(&self) -> Result<Vec<Webhook>> {
+        let rows: Vec<WebhookRow> = sqlx::query_as(&format!(
+            "SELECT {WEBHOOK_COLUMNS} FROM webhooks ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Webhook::try_from).collect()
+    }
+
+    pub async fn get_webhook(&self, webhook_id: Uuid) -> Result<Option<Webhook>> {
+        let row: Option<WebhookRow> = sqlx::query_as(&format!(
+            "SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE id = ?1"
+        ))
+        .bind(webhook_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Webhook::try_from).transpose()
+    }
+
+    /// Active webhooks subscribed to `event`
+    pub async fn get_webhooks_for_event(&self, event: &str) -> Result<Vec<Webhook>> {
+        let rows: Vec<WebhookRow> = sqlx::query_as(&format!(
+            "SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE active = 1 AND ',' || events || ',' LIKE ?1"
+        ))
+        .bind(format!("%,{},%", event))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Webhook::try_from).collect()
+    }
+
+    pub async fn update_webhook(&self, webhook: &Webhook) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE webhooks SET url = ?2, events = ?3, description = ?4, active = ?5, updated_at = ?6 \
+             WHERE id = ?1",
+        )
+        .bind(webhook.id.to_string())
+        .bind(&webhook.url)
+        .bind(webhook.events.join(","))
+        .bind(&webhook.description)
+        .bind(webhook.active)
+        .bind(webhook.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ?1")
+            .bind(webhook_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = ?1")
+            .bind(webhook_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn insert_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries
+            (id, webhook_id, event, payload, attempts, status_code, success, error, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(delivery.id.to_string())
+        .bind(delivery.webhook_id.to_string())
+        .bind(&delivery.event)
+        .bind(&delivery.payload)
+        .bind(delivery.attempts as i64)
+        .bind(delivery.status_code.map(|code| code as i64))
+        .bind(delivery.success)
+        .bind(&delivery.error)
+        .bind(delivery.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recent deliveries for one webhook, newest first
+    pub async fn get_webhook_deliveries(&self, webhook_id: Uuid, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let rows: Vec<WebhookDeliveryRow> = sqlx::query_as(&format!(
+            "SELECT {WEBHOOK_DELIVERY_COLUMNS} FROM webhook_deliveries \
+             WHERE webhook_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+        ))
+        .bind(webhook_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(WebhookDelivery::try_from).collect()
+    }
+
+    // Notification history
+    pub async fn insert_notification(&self, notification: &Notification) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (id, channel, source, subject, body, success, error, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(notification.id.to_string())
+        .bind(&notification.channel)
+        .bind(&notification.source)
+        .bind(&notification.subject)
+        .bind(&notification.body)
+        .bind(notification.success)
+        .bind(&notification.error)
+        .bind(notification.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recent notifications newest first, optionally for one channel or outcome
+    pub async fn get_notifications(
+        &self,
+        channel: Option<&str>,
+        success: Option<bool>,
+        limit: i64,
+    ) -> Result<Vec<Notification>> {
+        let rows: Vec<NotificationRow> = sqlx::query_as(&format!(
+            "SELECT {NOTIFICATION_COLUMNS} FROM notifications \
+             WHERE (?1 IS NULL OR channel = ?1) AND (?2 IS NULL OR success = ?2) \
+             ORDER BY created_at DESC LIMIT ?3"
+        ))
+        .bind(channel)
+        .bind(success)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Notification::try_from).collect()
+    }
+
+    // API key operations
+    pub async fn create_api_key(&self, key: &ApiKey) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys
+            (id, name, prefix, key_hash, role, daily_quota, monthly_quota, active, created_at, updated_at, last_used_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+        )
+        .bind(key.id.to_string())
+        .bind(&key.name)
+        .bind(&key.prefix)
+        .bind(&key.key_hash)
+        .bind(key.role.as_str())
+        .bind(key.daily_quota.map(i64::from))
+        .bind(key.monthly_quota.map(i64::from))
+        .bind(key.active)
+        .bind(key.created_at.to_rfc3339())
+        .bind(key.updated_at.to_rfc3339())
+        .bind(key.last_used_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_api_keys(&self) -> Result<Vec<ApiKey>> {
+        let rows: Vec<ApiKeyRow> = sqlx::query_as(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ApiKey::try_from).collect()
+    }
+
+    pub async fn get_api_key(&self, key_id: Uuid) -> Result<Option<ApiKey>> {
+        let row: Option<ApiKeyRow> = sqlx::query_as(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE id = ?1"
+        ))
+        .bind(key_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(ApiKey::try_from).transpose()
+    }
+
+    pub async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row: Option<ApiKeyRow> = sqlx::query_as(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE key_hash = ?1"
+        ))
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(ApiKey::try_from).transpose()
+    }
+
+    pub async fn update_api_key(&self, key: &ApiKey) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET name = ?2, role = ?3, daily_quota = ?4, monthly_quota = ?5, active = ?6, \
+             updated_at = ?7 WHERE id = ?1",
+        )
+        .bind(key.id.to_string())
+        .bind(&key.name)
+        .bind(key.role.as_str())
+        .bind(key.daily_quota.map(i64::from))
+        .bind(key.monthly_quota.map(i64::from))
+        .bind(key.active)
+        .bind(key.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_api_key(&self, key_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM key_usage WHERE key_id = ?1")
+            .bind(key_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM api_keys WHERE id = ?1")
+            .bind(key_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Count a request made with `key_id` at `now` and return the key's requests on that day
+    /// and since `month_start`, this one included
+    pub async fn record_key_request(
+        &self,
+        key_id: Uuid,
+        now: DateTime<Utc>,
+        month_start: NaiveDate,
+    ) -> Result<(u32, u32)> {
+        let mut tx = self.pool.begin().await?;
+
+        let date = now.date_naive().to_string();
+        sqlx::query(
+            "INSERT INTO key_usage (key_id, date, requests) VALUES (?1, ?2, 1) \
+             ON CONFLICT (key_id, date) DO UPDATE SET requests = requests + 1",
+        )
+        .bind(key_id.to_string())
+        .bind(&date)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE api_keys SET last_used_at = ?2 WHERE id = ?1")
+            .bind(key_id.to_string())
+            .bind(now.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        let (today, month): (i64, i64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(CASE WHEN date = ?2 THEN requests END), 0), COALESCE(SUM(requests), 0) \
+             FROM key_usage WHERE key_id = ?1 AND date >= ?3",
+        )
+        .bind(key_id.to_string())
+        .bind(&date)
+        .bind(month_start.to_string())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok((today.max(0) as u32, month.max(0) as u32))
+    }
+
+    /// Move a request counted by `record_key_request` to the refused ones of its day
+    pub async fn reject_key_request(&self, key_id: Uuid, date: NaiveDate) -> Result<()> {
+        sqlx::query(
+            "UPDATE key_usage SET requests = MAX(requests - 1, 0), rejected = rejected + 1 \
+             WHERE key_id = ?1 AND date = ?2",
+        )
+        .bind(key_id.to_string())
+        .bind(date.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Daily request counts of `key_id` from `since` on, newest first
+    pub async fn get_key_usage(&self, key_id: Uuid, since: NaiveDate) -> Result<Vec<KeyUsageDay>> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            "SELECT date, requests, rejected FROM key_usage WHERE key_id = ?1 AND date >= ?2 ORDER BY date DESC",
+        )
+        .bind(key_id.to_string())
+        .bind(since.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(date, requests, rejected)| {
+                Ok(KeyUsageDay {
+                    date: NaiveDate::from_str(&date)?,
+                    requests: requests.max(0) as u32,
+                    rejected: rejected.max(0) as u32,
+                })
+            })
+            .collect()
+    }
+
+    // Dashboard operations
+    pub async fn create_dashboard(&self, dashboard: &Dashboard) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        if dashboard.is_default {
+            clear_default_dashboard(&mut tx, &dashboard.owner).await?;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO dashboards (id, owner, name, widgets, is_default, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(dashboard.id.to_string())
+        .bind(&dashboard.owner)
+        .bind(&dashboard.name)
+        .bind(serde_json::to_string(&dashboard.widgets)?)
+        .bind(dashboard.is_default)
+        .bind(dashboard.created_at.to_rfc3339())
+        .bind(dashboard.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_dashboards(&self, owner: &str) -> Result<Vec<Dashboard>> {
+        let rows: Vec<DashboardRow> = sqlx::query_as(&format!(
+            "SELECT {DASHBOARD_COLUMNS} FROM dashboards WHERE owner = ?1 ORDER BY name"
+        ))
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Dashboard::try_from).collect()
+    }
+
+    pub async fn get_dashboard(&self, owner: &str, dashboard_id: Uuid) -> Result<Option<Dashboard>> {
+        let row: Option<DashboardRow> = sqlx::query_as(&format!(
+            "SELECT {DASHBOARD_COLUMNS} FROM dashboards WHERE owner = ?1 AND id = ?2"
+        ))
+        .bind(owner)
+        .bind(dashboard_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Dashboard::try_from).transpose()
+    }
+
+    pub async fn count_dashboards(&self, owner: &str) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM dashboards WHERE owner = ?1")
+            .bind(owner)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Whether `owner` has another dashboard called `name`
+    pub async fn dashboard_name_taken(&self, owner: &str, name: &str, except: Option<Uuid>) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM dashboards WHERE owner = ?1 AND name = ?2 AND (?3 IS NULL OR id != ?3)",
+        )
+        .bind(owner)
+        .bind(name)
+        .bind(except.map(|id| id.to_string()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    pub async fn update_dashboard(&self, dashboard: &Dashboard) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        if dashboard.is_default {
+            clear_default_dashboard(&mut tx, &dashboard.owner).await?;
+        }
+        let result = sqlx::query(
+            "UPDATE dashboards SET name = ?3, widgets = ?4, is_default = ?5, updated_at = ?6 \
+             WHERE owner = ?1 AND id = ?2",
+        )
+        .bind(&dashboard.owner)
+        .bind(dashboard.id.to_string())
+        .bind(&dashboard.name)
+        .bind(serde_json::to_string(&dashboard.widgets)?)
+        .bind(dashboard.is_default)
+        .bind(dashboard.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_dashboard(&self, owner: &str, dashboard_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM dashboards WHERE owner = ?1 AND id = ?2")
+            .bind(owner)
+            .bind(dashboard_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Trade journal operations
+    pub async fn insert_journal_entry(&self, entry: &JournalEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO journal_entries
+                (id, owner, symbol, side, quantity, price, fees, trade_date, rationale, tags, screenshot_urls, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            "#,
+        )
+        .bind(entry.id.to_string())
+        .bind(&entry.owner)
+        .bind(&entry.symbol)
+        .bind(&entry.side)
+        .bind(entry.quantity.to_string())
+        .bind(entry.price.to_string())
+        .bind(entry.fees.to_string())
+        .bind(entry.trade_date.to_string())
+        .bind(&entry.rationale)
+        .bind(serde_json::to_string(&entry.tags)?)
+        .bind(serde_json::to_string(&entry.screenshot_urls)?)
+        .bind(entry.created_at.to_rfc3339())
+        .bind(entry.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_journal_entry(&self, owner: &str, entry_id: Uuid) -> Result<Option<JournalEntry>> {
+        let row: Option<JournalEntryRow> = sqlx::query_as(&format!(
+            "SELECT {JOURNAL_ENTRY_COLUMNS} FROM journal_entries WHERE owner = ?1 AND id = ?2"
+        ))
+        .bind(owner)
+        .bind(entry_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(JournalEntry::try_from).transpose()
+    }
+
+    /// The owner's matching entries, newest trade first, plus the total match count for
+    /// pagination. A negative `limit` returns every match.
+    pub async fn get_journal_entries(
+        &self,
+        owner: &str,
+        filter: &JournalFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<JournalEntry>, i64)> {
+        let where_clause = "WHERE owner = ?1 \
+             AND (?2 IS NULL OR symbol = ?2) \
+             AND (?3 IS NULL OR EXISTS (SELECT 1 FROM json_each(journal_entries.tags) WHERE value = ?3)) \
+             AND (?4 IS NULL OR side = ?4) \
+             AND (?5 IS NULL OR trade_date >= ?5) \
+             AND (?6 IS NULL OR trade_date <= ?6)";
+        let from = filter.from.map(|d| d.to_string());
+        let to = filter.to.map(|d| d.to_string());
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM journal_entries {where_clause}"))
+            .bind(owner)
+            .bind(&filter.symbol)
+            .bind(&filter.tag)
+            .bind(&filter.side)
+            .bind(&from)
+            .bind(&to)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows: Vec<JournalEntryRow> = sqlx::query_as(&format!(
+            "SELECT {JOURNAL_ENTRY_COLUMNS} FROM journal_entries {where_clause} \
+             ORDER BY trade_date DESC, created_at DESC LIMIT ?7 OFFSET ?8"
+        ))
+        .bind(owner)
+        .bind(&filter.symbol)
+        .bind(&filter.tag)
+        .bind(&filter.side)
+        .bind(&from)
+        .bind(&to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows.into_iter().map(JournalEntry::try_from).collect::<Result<Vec<_>>>()?;
+        Ok((entries, total))
+    }
+
+    /// Every tag the owner has used, with how many entries carry it, most used first
+    pub async fn get_journal_tags(&self, owner: &str) -> Result<Vec<(String, i64)>> {
+        let tags: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT t.value, COUNT(*) AS entries FROM journal_entries, json_each(journal_entries.tags) t \
+             WHERE owner = ?1 GROUP BY t.value ORDER BY entries DESC, t.value",
+        )
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tags)
+    }
+
+    pub async fn update_journal_entry(&self, entry: &JournalEntry) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE journal_entries SET symbol = ?3, side = ?4, quantity = ?5, price = ?6, fees = ?7, \
+             trade_date = ?8, rationale = ?9, tags = ?10, screenshot_urls = ?11, updated_at = ?12 \
+             WHERE owner = ?1 AND id = ?2",
+        )
+        .bind(&entry.owner)
+        .bind(entry.id.to_string())
+        .bind(&entry.symbol)
+        .bind(&entry.side)
+        .bind(entry.quantity.to_string())
+        .bind(entry.price.to_string())
+        .bind(entry.fees.to_string())
+        .bind(entry.trade_date.to_string())
+        .bind(&entry.rationale)
+        .bind(serde_json::to_string(&entry.tags)?)
+        .bind(serde_json::to_string(&entry.screenshot_urls)?)
+        .bind(entry.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_journal_entry(&self, owner: &str, entry_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM journal_entries WHERE owner = ?1 AND id = ?2")
+            .bind(owner)
+            .bind(entry_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Strategy operations
+    pub async fn create_strategy(&self, strategy: &Strategy) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO strategies (id, owner, name, description, shared, version, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(strategy.id.to_string())
+        .bind(&strategy.owner)
+        .bind(&strategy.name)
+        .bind(&strategy.description)
+        .bind(strategy.shared)
+        .bind(strategy.version)
+        .bind(strategy.created_at.to_rfc3339())
+        .bind(strategy.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        insert_strategy_version(&mut tx, strategy).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The owner's strategies and those shared by anyone, by name
+    pub async fn get_strategies(&self, owner: &str) -> Result<Vec<Strategy>> {
+        let rows: Vec<StrategyRow> = sqlx::query_as(&format!(
+            "SELECT {STRATEGY_COLUMNS} FROM strategies s \
+             JOIN strategy_versions v ON v.strategy_id = s.id AND v.version = s.version \
+             WHERE s.owner = ?1 OR s.shared = 1 ORDER BY s.name, s.owner"
+        ))
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Strategy::try_from).collect()
+    }
+
+    /// A strategy with its latest definition, whoever owns it
+    pub async fn get_strategy(&self, strategy_id: Uuid) -> Result<Option<Strategy>> {
+        let row: Option<StrategyRow> = sqlx::query_as(&format!(
+            "SELECT {STRATEGY_COLUMNS} FROM strategies s \
+             JOIN strategy_versions v ON v.strategy_id = s.id AND v.version = s.version \
+             WHERE s.id = ?1"
+        ))
+        .bind(strategy_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Strategy::try_from).transpose()
+    }
+
+    /// Every version of a strategy, newest first
+    pub async fn get_strategy_versions(&self, strategy_id: Uuid) -> Result<Vec<StrategyVersion>> {
+        let rows: Vec<StrategyVersionRow> = sqlx::query_as(
+            "SELECT version, definition, created_at FROM strategy_versions WHERE strategy_id = ?1 ORDER BY version DESC",
+        )
+        .bind(strategy_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(StrategyVersion::try_from).collect()
+    }
+
+    pub async fn get_strategy_version(&self, strategy_id: Uuid, version: i64) -> Result<Option<StrategyVersion>> {
+        let row: Option<StrategyVersionRow> = sqlx::query_as(
+            "SELECT version, definition, created_at FROM strategy_versions WHERE strategy_id = ?1 AND version = ?2",
+        )
+        .bind(strategy_id.to_string())
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(StrategyVersion::try_from).transpose()
+    }
+
+    pub async fn count_strategies(&self, owner: &str) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM strategies WHERE owner = ?1")
+            .bind(owner)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Whether `owner` has another strategy called `name`
+    pub async fn strategy_name_taken(&self, owner: &str, name: &str, except: Option<Uuid>) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM strategies WHERE owner = ?1 AND name = ?2 AND (?3 IS NULL OR id != ?3)",
+        )
+        .bind(owner)
+        .bind(name)
+        .bind(except.map(|id| id.to_string()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Store changed fields; a `version` above the stored one also stores `definition` as that
+    /// version
+    pub async fn update_strategy(&self, strategy: &Strategy) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let current: Option<i64> = sqlx::query_scalar("SELECT version FROM strategies WHERE owner = ?1 AND id = ?2")
+            .bind(&strategy.owner)
+            .bind(strategy.id.to_string())
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(current) = current else {
+            tx.rollback().await?;
+            return Ok(false);
+        };
+        if strategy.version > current {
+            insert_strategy_version(&mut tx, strategy).await?;
+        }
+        sqlx::query(
+            "UPDATE strategies SET name = ?3, description = ?4, shared = ?5, version = ?6, updated_at = ?7 \
+             WHERE owner = ?1 AND id = ?2",
+        )
+        .bind(&strategy.owner)
+        .bind(strategy.id.to_string())
+        .bind(&strategy.name)
+        .bind(&strategy.description)
+        .bind(strategy.shared)
+        .bind(strategy.version.max(current))
+        .bind(strategy.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn delete_strategy(&self, owner: &str, strategy_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query("DELETE FROM strategies WHERE owner = ?1 AND id = ?2")
+            .bind(owner)
+            .bind(strategy_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() > 0 {
+            sqlx::query("DELETE FROM strategy_versions WHERE strategy_id = ?1")
+                .bind(strategy_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Custom indicator operations
+    pub async fn get_custom_indicators(&self, owner: &str) -> Result<Vec<CustomIndicator>> {
+        let rows: Vec<CustomIndicatorRow> = sqlx::query_as(&format!(
+            "SELECT {CUSTOM_INDICATOR_COLUMNS} FROM custom_indicators WHERE owner = ?1 ORDER BY name"
+        ))
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(CustomIndicator::try_from).collect()
+    }
+
+    pub async fn get_custom_indicator(&self, owner: &str, name: &str) -> Result<Option<CustomIndicator>> {
+        let row: Option<CustomIndicatorRow> = sqlx::query_as(&format!(
+            "SELECT {CUSTOM_INDICATOR_COLUMNS} FROM custom_indicators WHERE owner = ?1 AND name = ?2"
+        ))
+        .bind(owner)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(CustomIndicator::try_from).transpose()
+    }
+
+    /// Indicators watched by signal alerts, of any owner
+    pub async fn get_alerted_custom_indicators(&self) -> Result<Vec<CustomIndicator>> {
+        let rows: Vec<CustomIndicatorRow> = sqlx::query_as(&format!(
+            "SELECT {CUSTOM_INDICATOR_COLUMNS} FROM custom_indicators \
+             WHERE id IN (SELECT indicator_id FROM signal_alerts)"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(CustomIndicator::try_from).collect()
+    }
+
+    pub async fn count_custom_indicators(&self, owner: &str) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM custom_indicators WHERE owner = ?1")
+            .bind(owner)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Save `expression` under `name` for `owner`, replacing the expression of an indicator
+    /// with that name
+    pub async fn upsert_custom_indicator(&self, owner: &str, name: &str, expression: &str) -> Result<CustomIndicator> {
+        let now = Utc::now().to_rfc3339();
+        let row: CustomIndicatorRow = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO custom_indicators (id, owner, name, expression, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+            ON CONFLICT(owner, name) DO UPDATE SET expression = excluded.expression, updated_at = excluded.updated_at
+            RETURNING {CUSTOM_INDICATOR_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(owner)
+        .bind(name)
+        .bind(expression)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        CustomIndicator::try_from(row)
+    }
+
+    /// Delete an indicator with the signal alerts watching it; `None` if `owner` has no
+    /// indicator called `name`, otherwise the number of alerts deleted
+    pub async fn delete_custom_indicator(&self, owner: &str, name: &str) -> Result<Option<u64>> {
+        let mut tx = self.pool.begin().await?;
+
+        let id: Option<String> =
+            sqlx::query_scalar("DELETE FROM custom_indicators WHERE owner = ?1 AND name = ?2 RETURNING id")
+                .bind(owner)
+                .bind(name)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(id) = id else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        let alerts = sqlx::query("DELETE FROM signal_alerts WHERE indicator_id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(Some(alerts))
+    }
+
+    // Universe operations
+    /// Upsert `name` and its constituents, adding unknown symbols to `symbols`. With `replace`
+    /// members missing from `constituents` are dropped. Returns (added, removed) counts.
+    pub async fn import_universe(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        constituents: &[UniverseConstituent],
+        replace: bool,
+    ) -> Result<(usize, usize)> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO universes (name, description, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?3)
+            ON CONFLICT(name) DO UPDATE SET
+                description = COALESCE(?2, description),
+                updated_at = ?3
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let existing: std::collections::HashSet<String> =
+            sqlx::query_scalar("SELECT symbol FROM universe_members WHERE universe = ?1")
+                .bind(name)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+
+        let mut removed = 0;
+        if replace {
+            let incoming: std::collections::HashSet<&str> =
+                constituents.iter().map(|c| c.symbol.as_str()).collect();
+            for symbol in existing.iter().filter(|s| !incoming.contains(s.as_str())) {
+                sqlx::query("DELETE FROM universe_members WHERE universe = ?1 AND symbol = ?2")
+                    .bind(name)
+                    .bind(symbol)
+                    .execute(&mut *tx)
+                    .await?;
+                removed += 1;
+            }
+        }
+
+        let mut added = 0;
+        for constituent in constituents {
+            sqlx::query(
+                r#"
+                INSERT INTO symbols (id, symbol, name, sector, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+                ON CONFLICT(symbol) DO UPDATE SET
+                    name = COALESCE(?3, name),
+                    sector = COALESCE(?4, sector),
+                    updated_at = ?5
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&constituent.symbol)
+            .bind(&constituent.name)
+            .bind(&constituent.sector)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO universe_members (universe, symbol, weight, added_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(universe, symbol) DO UPDATE SET weight = ?3
+                "#,
+            )
+            .bind(name)
+            .bind(&constituent.symbol)
+            .bind(constituent.weight.as_ref().map(|w| w.to_string()))
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+            if !existing.contains(&constituent.symbol) {
+                added += 1;
+            }
+        }
+
+        tx.commit().await?;
+        Ok((added, removed))
+    }
+
+    pub async fn get_universes(&self) -> Result<Vec<Universe>> {
+        let rows: Vec<UniverseRow> = sqlx::query_as(&format!(
+            "SELECT {UNIVERSE_COLUMNS} FROM universes u ORDER BY u.name"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Universe::try_from).collect()
+    }
+
+    pub async fn get_universe(&self, name: &str) -> Result<Option<Universe>> {
+        let row: Option<UniverseRow> = sqlx::query_as(&format!(
+            "SELECT {UNIVERSE_COLUMNS} FROM universes u WHERE u.name = ?1"
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Universe::try_from).transpose()
+    }
+
+    pub async fn get_universe_members(&self, name: &str) -> Result<Vec<UniverseMember>> {
+        let rows: Vec<UniverseMemberRow> = sqlx::query_as(
+            r#"
+            SELECT m.symbol, s.name, s.sector, m.weight, m.added_at
+            FROM universe_members m
+            LEFT JOIN symbols s ON s.symbol = m.symbol
+            WHERE m.universe = ?1
+            ORDER BY m.symbol
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(UniverseMember::try_from).collect()
+    }
+
+    pub async fn get_universe_symbols(&self, name: &str) -> Result<Vec<String>> {
+        let symbols = sqlx::query_scalar(
+            "SELECT symbol FROM universe_members WHERE universe = ?1 ORDER BY symbol",
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(symbols)
+    }
+
+    pub async fn delete_universe(&self, name: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM universe_members WHERE universe = ?1")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM universes WHERE name = ?1")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Ranking model operations
+    pub async fn get_ranking_models(&self) -> Result<Vec<RankingModel>> {
+        let rows: Vec<RankingModelRow> = sqlx::query_as(&format!(
+            "SELECT {RANKING_MODEL_COLUMNS} FROM ranking_models ORDER BY name"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(RankingModel::try_from).collect()
+    }
+
+    pub async fn get_ranking_model(&self, name: &str) -> Result<Option<RankingModel>> {
+        let row: Option<RankingModelRow> = sqlx::query_as(&format!(
+            "SELECT {RANKING_MODEL_COLUMNS} FROM ranking_models WHERE name = ?1"
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(RankingModel::try_from).transpose()
+    }
+
+    /// Save a model under `name`, replacing the description and weights of one with that name
+    pub async fn upsert_ranking_model(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        weights: &BTreeMap<RankingFactor, f64>,
+    ) -> Result<RankingModel> {
+        let now = Utc::now().to_rfc3339();
+        let row: RankingModelRow = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO ranking_models (name, description, weights, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?4)
+            ON CONFLICT(name) DO UPDATE SET
+                description = excluded.description,
+                weights = excluded.weights,
+                updated_at = excluded.updated_at
+            RETURNING {RANKING_MODEL_COLUMNS}
+            "#
+        ))
+        .bind(name)
+        .bind(description)
+        .bind(serde_json::to_string(weights)?)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        RankingModel::try_from(row)
+    }
+
+    pub async fn delete_ranking_model(&self, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM ranking_models WHERE name = ?1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Symbol alias operations
+    pub async fn get_symbol_aliases(&self) -> Result<Vec<SymbolAlias>> {
+        let rows: Vec<SymbolAliasRow> = sqlx::query_as(
+            "SELECT alias, symbol, created_at, updated_at FROM symbol_aliases ORDER BY alias",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(SymbolAlias::try_from).collect()
+    }
+
+    /// Canonical ticker an alias points to
+    pub async fn get_alias_target(&self, alias: &str) -> Result<Option<String>> {
+        let symbol: Option<String> = sqlx::query_scalar("SELECT symbol FROM symbol_aliases WHERE alias = ?1")
+            .bind(alias)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(symbol)
+    }
+
+    pub async fn upsert_symbol_alias(&self, alias: &str, symbol: &str) -> Result<SymbolAlias> {
+        let now = Utc::now().to_rfc3339();
+        let row: SymbolAliasRow = sqlx::query_as(
+            r#"
+            INSERT INTO symbol_aliases (alias, symbol, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?3)
+            ON CONFLICT(alias) DO UPDATE SET symbol = excluded.symbol, updated_at = excluded.updated_at
+            RETURNING alias, symbol, created_at, updated_at
+            "#,
+        )
+        .bind(alias)
+        .bind(symbol)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        SymbolAlias::try_from(row)
+    }
+
+    pub async fn delete_symbol_alias(&self, alias: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM symbol_aliases WHERE alias = ?1")
+            .bind(alias)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Signal alert operations
+    pub async fn get_signal_alerts(&self) -> Result<Vec<SignalAlert>> {
+        let rows: Vec<SignalAlertRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM signal_alerts ORDER BY symbol, condition",
+            SIGNAL_ALERT_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(SignalAlert::try_from).collect()
+    }
+
+    /// Create an alert, or return the existing one for the same symbol, condition and custom
+    /// indicator threshold with its cooldown and channels replaced when given. `indicator` is
+    /// the custom indicator and threshold of the indicator conditions.
+    pub async fn insert_signal_alert(
+        &self,
+        symbol: &str,
+        condition: SignalCondition,
+        indicator: Option<(Uuid, Decimal)>,
+        cooldown_minutes: Option<i64>,
+        channels: Option<&[String]>,
+    ) -> Result<SignalAlert> {
+        let (indicator_id, threshold) = match indicator {
+            Some((id, threshold)) => (id.to_string(), threshold.normalize().to_string()),
+            None => (String::new(), String::new()),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO signal_alerts
+                (id, symbol, condition, indicator_id, threshold, cooldown_minutes, channels, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(symbol, condition, indicator_id, threshold) DO UPDATE
+            SET cooldown_minutes = COALESCE(excluded.cooldown_minutes, cooldown_minutes),
+                channels = COALESCE(excluded.channels, channels)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(symbol)
+        .bind(condition.as_str())
+        .bind(&indicator_id)
+        .bind(&threshold)
+        .bind(cooldown_minutes)
+        .bind(channels.map(join_channels))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        let row: SignalAlertRow = sqlx::query_as(&format!(
+            "SELECT {} FROM signal_alerts WHERE symbol = ?1 AND condition = ?2 AND indicator_id = ?3 AND threshold = ?4",
+            SIGNAL_ALERT_COLUMNS
+        ))
+        .bind(symbol)
+        .bind(condition.as_str())
+        .bind(&indicator_id)
+        .bind(&threshold)
+        .fetch_one(&self.pool)
+        .await?;
+
+        SignalAlert::try_from(row)
+    }
+
+    pub async fn delete_signal_alert(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM signal_alerts WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Snooze an alert until `until`, or unmute it with `None`. Returns false when no alert has
+    /// this id.
+    /// Pick the notification channels of an alert. Returns false when no alert has this id.
+    pub async fn set_signal_alert_channels(&self, id: Uuid, channels: &[String]) -> Result<bool> {
+        let result = sqlx::query("UPDATE signal_alerts SET channels = ?1 WHERE id = ?2")
+            .bind(join_channels(channels))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_signal_alert_muted_until(
+        &self,
+        id: Uuid,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let result = sqlx::query("UPDATE signal_alerts SET muted_until = ?1 WHERE id = ?2")
+            .bind(until.map(|t| t.to_rfc3339()))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that an alert fired on the daily close of `candle_date`
+    pub async fn mark_signal_alert_fired(
+        &self,
+        id: Uuid,
+        candle_date: NaiveDate,
+        fired_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE signal_alerts SET last_fired_on = ?1, last_fired_at = ?2 WHERE id = ?3")
+            .bind(candle_date.to_string())
+            .bind(fired_at.to_rfc3339())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Anomaly operations
+    /// Store anomalies not recorded yet for their symbol, bar and kind; returns the new ones
+    pub async fn insert_anomalies(&self, anomalies: &[Anomaly]) -> Result<Vec<Anomaly>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut inserted = Vec::new();
+        for anomaly in anomalies {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO anomalies (id, symbol, date, kind, value, z_score, baseline, close, volume, detected_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                ON CONFLICT(symbol, date, kind) DO NOTHING
+                "#,
+            )
+            .bind(anomaly.id.to_string())
+            .bind(&anomaly.symbol)
+            .bind(anomaly.date.to_string())
+            .bind(anomaly.kind.as_str())
+            .bind(anomaly.value.to_string())
+            .bind(anomaly.z_score.to_string())
+            .bind(anomaly.baseline.to_string())
+            .bind(anomaly.close.to_string())
+            .bind(anomaly.volume)
+            .bind(anomaly.detected_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() > 0 {
+                inserted.push(anomaly.clone());
+            }
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Anomalies matching `filter`, latest bar first
+    pub async fn get_anomalies(&self, filter: &AnomalyFilter, limit: i64) -> Result<Vec<Anomaly>> {
+        let rows: Vec<AnomalyRow> = sqlx::query_as(&format!(
+            "SELECT {ANOMALY_COLUMNS} FROM anomalies \
+             WHERE (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR symbol = ?2) AND (?3 IS NULL OR kind = ?3) \
+             ORDER BY date DESC, symbol, kind LIMIT ?4"
+        ))
+        .bind(filter.since.map(|d| d.to_string()))
+        .bind(&filter.symbol)
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Anomaly::try_from).collect()
+    }
+
+    // Security identifier operations
+    pub async fn get_security_identifier(&self, identifier: &str) -> Result<Option<SecurityIdentifier>> {
+        let row: Option<SecurityIdentifierRow> = sqlx::query_as(
+            r#"
+            SELECT identifier, kind, symbol, name, exchange, source, created_at
+            FROM security_identifiers WHERE identifier = ?1
+            "#,
+        )
+        .bind(identifier)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(SecurityIdentifier::try_from).transpose()
+    }
+
+    pub async fn upsert_security_identifier(&self, mapping: &SecurityIdentifier) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO security_identifiers (identifier, kind, symbol, name, exchange, source, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(identifier) DO UPDATE SET
+                symbol = excluded.symbol,
+                name = excluded.name,
+                exchange = excluded.exchange,
+                source = excluded.source
+            "#,
+        )
+        .bind(&mapping.identifier)
+        .bind(&mapping.kind)
+        .bind(&mapping.symbol)
+        .bind(&mapping.name)
+        .bind(&mapping.exchange)
+        .bind(&mapping.source)
+        .bind(mapping.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Job operations
+    pub async fn insert_job(&self, job: &Job) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO jobs (id, kind, request, status, result, error, created_at, started_at, finished_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(job.id.to_string())
+        .bind(&job.kind)
+        .bind(serde_json::to_string(&job.request)?)
+        .bind(job.status.as_str())
+        .bind(job.result.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&job.error)
+        .bind(job.created_at.to_rfc3339())
+        .bind(job.started_at.map(|t| t.to_rfc3339()))
+        .bind(job.finished_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_job(&self, job_id: Uuid) -> Result<Option<Job>> {
+        let row: Option<JobRow> = sqlx::query_as(&format!("SELECT {JOB_COLUMNS} FROM jobs WHERE id = ?1"))
+            .bind(job_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(Job::try_from).transpose()
+    }
+
+    /// Most recent jobs first, optionally only those in `status`
+    pub async fn get_jobs(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>> {
+        let rows: Vec<JobRow> = sqlx::query_as(&format!(
+            "SELECT {JOB_COLUMNS} FROM jobs WHERE (?1 IS NULL OR status = ?1) \
+             ORDER BY created_at DESC LIMIT ?2"
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Job::try_from).collect()
+    }
+
+    /// Mark the oldest queued job as running and return it. The single UPDATE keeps two
+    /// workers from claiming the same job.
+    pub async fn claim_next_job(&self) -> Result<Option<Job>> {
+        let row: Option<JobRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE jobs SET status = 'running', started_at = ?1
+            WHERE id = (SELECT id FROM jobs WHERE status = 'queued' ORDER BY created_at LIMIT 1)
+              AND status = 'queued'
+            RETURNING {JOB_COLUMNS}
+            "#
+        ))
+        .bind(Utc::now().to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Job::try_from).transpose()
+    }
+
+    pub async fn finish_job(
+        &self,
+        job_id: Uuid,
+        result: std::result::Result<serde_json::Value, String>,
+    ) -> Result<()> {
+        let (status, result, error) = match result {
+            Ok(value) => (JobStatus::Succeeded, Some(serde_json::to_string(&value)?), None),
+            Err(e) => (JobStatus::Failed, None, Some(e)),
+        };
+        sqlx::query("UPDATE jobs SET status = ?2, result = ?3, error = ?4, finished_at = ?5 WHERE id = ?1")
+            .bind(job_id.to_string())
+            .bind(status.as_str())
+            .bind(result)
+            .bind(error)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_job_progress(&self, job_id: Uuid, progress: JobProgress) -> Result<()> {
+        sqlx::query("UPDATE jobs SET progress_done = ?2, progress_total = ?3 WHERE id = ?1")
+            .bind(job_id.to_string())
+            .bind(progress.done)
+            .bind(progress.total)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue a failed job again, clearing its outcome; `None` unless the job exists and failed
+    pub async fn retry_job(&self, job_id: Uuid) -> Result<Option<Job>> {
+        let row: Option<JobRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE jobs SET status = 'queued', error = NULL, progress_done = NULL, progress_total = NULL,
+                started_at = NULL, finished_at = NULL
+            WHERE id = ?1 AND status = 'failed'
+            RETURNING {JOB_COLUMNS}
+            "#
+        ))
+        .bind(job_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(Job::try_from).transpose()
+    }
+
+    /// Put jobs left running by a previous process back in the queue
+    pub async fn requeue_interrupted_jobs(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE jobs SET status = 'queued', started_at = NULL, progress_done = NULL, progress_total = NULL \
+             WHERE status = 'running'",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Object storage export operations
+    pub async fn insert_export_run(&self, run: &ExportRun) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO object_exports
+            (id, triggered_by, status, data_since, data_until, objects_written, rows_written,
+             bytes_written, error, started_at, finished_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+        )
+        .bind(run.id.to_string())
+        .bind(run.triggered_by.as_str())
+        .bind(run.status.as_str())
+        .bind(run.data_since.map(|t| t.to_rfc3339()))
+        .bind(run.data_until.to_rfc3339())
+        .bind(run.objects_written)
+        .bind(run.rows_written)
+        .bind(run.bytes_written)
+        .bind(&run.error)
+        .bind(run.started_at.to_rfc3339())
+        .bind(run.finished_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store the progress or outcome of a run
+    pub async fn update_export_run(&self, run: &ExportRun) -> Result<()> {
+        sqlx::query(
+            "UPDATE object_exports SET status = ?2, objects_written = ?3, rows_written = ?4, \
+             bytes_written = ?5, error = ?6, finished_at = ?7 WHERE id = ?1",
+        )
+        .bind(run.id.to_string())
+        .bind(run.status.as_str())
+        .bind(run.objects_written)
+        .bind(run.rows_written)
+        .bind(run.bytes_written)
+        .bind(&run.error)
+        .bind(run.finished_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_export_run(&self, run_id: Uuid) -> Result<Option<ExportRun>> {
+        let row: Option<ExportRunRow> =
+            sqlx::query_as(&format!("SELECT {EXPORT_RUN_COLUMNS} FROM object_exports WHERE id = ?1"))
+                .bind(run_id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(ExportRun::try_from).transpose()
+    }
+
+    /// Most recent export runs first
+    pub async fn get_export_runs(&self, limit: i64) -> Result<Vec<ExportRun>> {
+        let rows: Vec<ExportRunRow> = sqlx::query_as(&format!(
+            "SELECT {EXPORT_RUN_COLUMNS} FROM object_exports ORDER BY started_at DESC LIMIT ?1"
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ExportRun::try_from).collect()
+    }
+
+    /// Where the last successful export stopped, i.e. where the next one starts
+    pub async fn last_export_until(&self) -> Result<Option<DateTime<Utc>>> {
+        let until: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(data_until) FROM object_exports WHERE status = 'succeeded'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        until.as_deref().map(parse_timestamp).transpose()
+    }
+
+    /// Mark exports left running by a previous process as failed; the next run covers their rows
+    pub async fn fail_interrupted_exports(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE object_exports SET status = 'failed', error = 'Interrupted by a restart', finished_at = ?1 \
+             WHERE status = 'running'",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Symbols and UTC days of the candles stored in `(since, until]`, i.e. the candle
+    /// partitions an export over that window has to rewrite
+    pub async fn candle_partitions_stored_between(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<(String, NaiveDate)>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT symbol, substr(timestamp, 1, 10) FROM historical_prices \
+             WHERE (?1 IS NULL OR created_at > ?1) AND created_at <= ?2 ORDER BY 1, 2",
+        )
+        .bind(since.map(|t| t.to_rfc3339()))
+        .bind(until.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(symbol, day)| Ok((symbol, NaiveDate::from_str(&day)?)))
+            .collect()
+    }
+
+    /// Symbols and UTC market days of the quotes captured in `(since, until]`
+    pub async fn quote_partitions_stored_between(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<(String, NaiveDate)>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT symbol, substr(market_time, 1, 10) FROM realtime_quotes \
+             WHERE (?1 IS NULL OR created_at > ?1) AND created_at <= ?2 ORDER BY 1, 2",
+        )
+        .bind(since.map(|t| t.to_rfc3339()))
+        .bind(until.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(symbol, day)| Ok((symbol, NaiveDate::from_str(&day)?)))
+            .collect()
+    }
+
+    /// Every stored candle of `symbol` on UTC day `day`, oldest first
+    pub async fn get_candles_on(&self, symbol: &str, day: NaiveDate) -> Result<Vec<HistoricalPrice>> {
+        let rows: Vec<HistoricalPriceRow> = sqlx::query_as(&format!(
+            "SELECT {HISTORICAL_PRICE_COLUMNS} FROM historical_prices \
+             WHERE symbol = ?1 AND substr(timestamp, 1, 10) = ?2 ORDER BY timestamp ASC"
+        ))
+        .bind(symbol)
+        .bind(day.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(HistoricalPrice::try_from).collect()
+    }
+
+    /// Every captured quote of `symbol` with a market time on UTC day `day`, including
+    /// repeated captures of the same tick, oldest first
+    pub async fn get_quotes_on(&self, symbol: &str, day: NaiveDate) -> Result<Vec<RealTimeQuote>> {
+        let rows: Vec<RealTimeQuoteRow> = sqlx::query_as(&format!(
+            "SELECT {REALTIME_QUOTE_COLUMNS} FROM realtime_quotes \
+             WHERE symbol = ?1 AND substr(market_time, 1, 10) = ?2 ORDER BY market_time ASC, created_at ASC"
+        ))
+        .bind(symbol)
+        .bind(day.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(RealTimeQuote::try_from).collect()
+    }
+
+    // Demo data operations
+    /// Whether sample data was ever seeded, including seeds that were wiped since
+    pub async fn demo_data_seeded(&self) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM demo_symbols")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    pub async fn mark_demo_symbol(&self, symbol: &str, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO demo_symbols (symbol, seeded_at) VALUES (?1, ?2)")
+            .bind(symbol)
+            .bind(now.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete prices, quotes, profiles and dividends of seeded demo symbols. Symbol rows
+    /// still referenced by a portfolio holding are kept.
+    pub async fn wipe_demo_data(&self, now: DateTime<Utc>) -> Result<DemoWipeSummary> {
+        let demo = "SELECT symbol FROM demo_symbols WHERE wiped_at IS NULL";
+        let mut tx = self.pool.begin().await?;
+
+        let symbols: Vec<String> = sqlx::query_scalar(demo).fetch_all(&mut *tx).await?;
+
+        let price_rows = sqlx::query(&format!("DELETE FROM historical_prices WHERE symbol IN ({demo})"))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        for table in ["realtime_quotes", "company_profiles", "dividends"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE symbol IN ({demo})"))
+                .execute(&mut *tx)
+                .await?;
+        }
+        let deleted_symbols = sqlx::query(&format!(
+            "DELETE FROM symbols WHERE symbol IN ({demo}) \
+             AND id NOT IN (SELECT symbol_id FROM portfolio_holdings WHERE symbol_id IS NOT NULL)"
+        ))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("UPDATE demo_symbols SET wiped_at = ?1 WHERE wiped_at IS NULL")
+            .bind(now.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(DemoWipeSummary {
+            symbols,
+            deleted_symbols,
+            price_rows,
+        })
+    }
+
+    /// Delete a symbol with its candles, quotes, dividends, profiles and cached identifier
+    /// lookups; `None` if it is not stored. Aliases, alerts, universe members and portfolio
+    /// rows that name it are left alone.
+    pub async fn delete_symbol(&self, symbol: &str) -> Result<Option<SymbolDeletion>> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = |table: &'static str| format!("DELETE FROM {table} WHERE symbol = ?1");
+        let price_rows = sqlx::query(&deleted("historical_prices"))
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let quote_rows = sqlx::query(&deleted("realtime_quotes"))
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let dividend_rows = sqlx::query(&deleted("dividends"))
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let mut profile_rows = 0;
+        for table in ["company_profiles", "company_profile_snapshots"] {
+            profile_rows += sqlx::query(&deleted(table))
+                .bind(symbol)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        for table in ["security_identifiers", "anomalies"] {
+            sqlx::query(&deleted(table))
+                .bind(symbol)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let symbol_rows = sqlx::query(&deleted("symbols"))
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if symbol_rows == 0 {
+            // Nothing is stored without a symbol row, so there was nothing to delete
+            tx.rollback().await?;
+            return Ok(None);
+        }
+        tx.commit().await?;
+
+        Ok(Some(SymbolDeletion {
+            symbol: symbol.to_string(),
+            price_rows,
+            quote_rows,
+            dividend_rows,
+            profile_rows,
+        }))
+    }
+
+    /// Re-key everything stored under `from` to `to` after a ticker change, merging into `to`
+    /// when it is stored already, and alias `from` to `to`; `None` if `from` is not stored
+    pub async fn rename_symbol(&self, from: &str, to: &str) -> Result<Option<SymbolRename>> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now().to_rfc3339();
+
+        let from_id: Option<String> = sqlx::query_scalar("SELECT id FROM symbols WHERE symbol = ?1")
+            .bind(from)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(from_id) = from_id else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        let to_id: Option<String> = sqlx::query_scalar("SELECT id FROM symbols WHERE symbol = ?1")
+            .bind(to)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let merged = to_id.is_some();
+        let target_id = match to_id {
+            Some(id) => id,
+            None => {
+                sqlx::query("UPDATE symbols SET symbol = ?1, updated_at = ?2 WHERE id = ?3")
+                    .bind(to)
+                    .bind(&now)
+                    .bind(&from_id)
+                    .execute(&mut *tx)
+                    .await?;
+                from_id.clone()
+            }
+        };
+
+        // Candles have no unique key, so those `to` already has for a timestamp are dropped
+        let price_rows = sqlx::query(
+            r#"
+            UPDATE historical_prices SET symbol = ?2, symbol_id = ?3
+            WHERE symbol = ?1 AND timestamp NOT IN (SELECT timestamp FROM historical_prices WHERE symbol = ?2)
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(&target_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let quote_rows = sqlx::query("UPDATE realtime_quotes SET symbol = ?2, symbol_id = ?3 WHERE symbol = ?1")
+            .bind(from)
+            .bind(to)
+            .bind(&target_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        // Tables keyed by symbol keep the row `to` already has and drop the one of `from`
+        let mut moved = HashMap::new();
+        for table in [
+            "dividends",
+            "company_profiles",
+            "company_profile_snapshots",
+            "signal_alerts",
+            "universe_members",
+            "security_identifiers",
+            "anomalies",
+        ] {
+            let sets_id = matches!(table, "dividends" | "company_profiles" | "company_profile_snapshots");
+            let update = if sets_id {
+                format!("UPDATE OR IGNORE {table} SET symbol = ?2, symbol_id = ?3 WHERE symbol = ?1")
+            } else {
+                format!("UPDATE OR IGNORE {table} SET symbol = ?2 WHERE symbol = ?1")
+            };
+            let rows = sqlx::query(&update)
+                .bind(from)
+                .bind(to)
+                .bind(&target_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            moved.insert(table, rows);
+        }
+        for table in [
+            "historical_prices",
+            "dividends",
+            "company_profiles",
+            "company_profile_snapshots",
+            "signal_alerts",
+            "universe_members",
+            "anomalies",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE symbol = ?1"))
+                .bind(from)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let holding_rows = rename_portfolio_holding(&mut tx, from, to, &target_id, &now).await?;
+        let transaction_rows = sqlx::query("UPDATE portfolio_transactions SET symbol = ?2 WHERE symbol = ?1")
+            .bind(from)
+            .bind(to)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if merged {
+            sqlx::query("DELETE FROM symbols WHERE id = ?1")
+                .bind(&from_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Requests for the old ticker follow it, including through aliases that pointed to it
+        sqlx::query("DELETE FROM symbol_aliases WHERE alias = ?1")
+            .bind(to)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE symbol_aliases SET symbol = ?2, updated_at = ?3 WHERE symbol = ?1")
+            .bind(from)
+            .bind(to)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO symbol_aliases (alias, symbol, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?3)
+            ON CONFLICT(alias) DO UPDATE SET symbol = excluded.symbol, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(SymbolRename {
+            from: from.to_string(),
+            to: to.to_string(),
+            merged,
+            price_rows,
+            quote_rows,
+            dividend_rows: moved["dividends"],
+            profile_rows: moved["company_profiles"] + moved["company_profile_snapshots"],
+            holding_rows,
+            transaction_rows,
+        }))
+    }
+
+    /// Up to `limit` rows of an archived table with a rowid above `after_rowid`, in rowid
+    /// order, each as a map of column name to JSON value
+    pub async fn export_table_page(
+        &self,
+        table: &str,
+        after_rowid: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, serde_json::Map<String, serde_json::Value>)>> {
+        anyhow::ensure!(ARCHIVE_TABLES.contains(&table), "{} is not an archived table", table);
+
+        let rows = sqlx::query(&format!(
+            "SELECT rowid AS archive_rowid, * FROM {table} WHERE rowid > ?1 ORDER BY rowid LIMIT ?2"
+        ))
+        .bind(after_rowid)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut page = Vec::with_capacity(rows.len());
+        for row in rows {
+            let rowid: i64 = row.try_get(0)?;
+            let mut values = serde_json::Map::new();
+            for column in row.columns().iter().skip(1) {
+                values.insert(column.name().to_string(), column_json(&row, column.ordinal())?);
+            }
+            page.push((rowid, values));
+        }
+        Ok(page)
+    }
+
+    /// Run a checked analytics query on a pooled connection switched to `query_only`,
+    /// returning at most `max_rows` rows and interrupting it once `timeout` has passed. The
+    /// query runs in its own task so a dropped request cannot return the connection to the
+    /// pool before it is made writable again.
+    #[cfg(feature = "analytics")]
+    pub async fn analytics_query(
+        &self,
+        sql: &str,
+        max_rows: usize,
+        timeout: Duration,
+    ) -> Result<AnalyticsSqlResult> {
+        let pool = self.pool.clone();
+        let sql = sql.to_string();
+        tokio::spawn(async move {
+            let mut conn = pool.acquire().await?;
+            sqlx::query("PRAGMA query_only = ON").execute(&mut *conn).await?;
+
+            let result = run_analytics_query(&mut conn, &sql, max_rows, timeout).await;
+
+            let reset = async {
+                conn.lock_handle().await?.remove_progress_handler();
+                sqlx::query("PRAGMA query_only = OFF").execute(&mut *conn).await?;
+                Ok::<_, sqlx::Error>(())
+            };
+            if reset.await.is_err() {
+                conn.close_on_drop();
+            }
+            result
+        })
+        .await?
+    }
+
+    /// Load archived rows in one transaction, parents first. `Replace` empties every archived
+    /// table before inserting; `Merge` skips rows whose keys are already stored. Columns the
+    /// current schema does not have are ignored and reported.
+    pub async fn import_archive(
+        &self,
+        tables: &HashMap<String, Vec<serde_json::Map<String, serde_json::Value>>>,
+        mode: ImportMode,
+    ) -> Result<Vec<ArchiveTableImport>> {
+        let mut tx = self.pool.begin().await?;
+
+        if mode == ImportMode::Replace {
+            for table in ARCHIVE_TABLES.iter().rev() {
+                sqlx::query(&format!("DELETE FROM {table}"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        let verb = match mode {
+            ImportMode::Merge => "INSERT OR IGNORE",
+            ImportMode::Replace => "INSERT",
+        };
+        let mut summary = Vec::new();
+        for table in ARCHIVE_TABLES {
+            let Some(rows) = tables.get(*table) else {
+                continue;
+            };
+            let known: Vec<String> =
+                sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{table}')"))
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+            let mut imported = 0;
+            let mut skipped_columns: Vec<String> = Vec::new();
+            for row in rows {
+                let mut columns = Vec::with_capacity(row.len());
+                let mut values = Vec::with_capacity(row.len());
+                for (column, value) in row {
+                    if known.contains(column) {
+                        columns.push(column.as_str());
+                        values.push(value);
+                    } else if !skipped_columns.contains(column) {
+                        skipped_columns.push(column.clone());
+                    }
+                }
+                if columns.is_empty() {
+                    continue;
+                }
+
+                let placeholders = vec!["?"; columns.len()].join(", ");
+                let sql = format!("{verb} INTO {table} ({}) VALUES ({placeholders})", columns.join(", "));
+                let mut query = sqlx::query(&sql);
+                for value in values {
+                    query = match value {
+                        serde_json::Value::Null => query.bind(None::<String>),
+                        serde_json::Value::Bool(flag) => query.bind(*flag as i64),
+                        serde_json::Value::Number(number) => match number.as_i64() {
+                            Some(integer) => query.bind(integer),
+                            None => query.bind(number.as_f64()),
+                        },
+                        serde_json::Value::String(text) => query.bind(text.as_str()),
+                        other => query.bind(other.to_string()),
+                    };
+                }
+                imported += query.execute(&mut *tx).await?.rows_affected();
+            }
+
+            skipped_columns.sort();
+            summary.push(ArchiveTableImport {
+                table: table.to_string(),
+                rows: rows.len(),
+                imported,
+                skipped_columns,
+            });
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
+
+#[cfg(feature = "analytics")]
+async fn run_analytics_query(
+    conn: &mut SqliteConnection,
+    sql: &str,
+    max_rows: usize,
+    timeout: Duration,
+) -> Result<AnalyticsSqlResult> {
+    let started = Instant::now();
+    let deadline = started + timeout;
+    conn.lock_handle()
+        .await?
+        .set_progress_handler(1000, move || Instant::now() < deadline);
+
+    let columns: Vec<String> = (&mut *conn)
+        .describe(sql)
+        .await?
+        .columns()
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut stream = sqlx::query(sql).fetch(&mut *conn);
+    loop {
+        let row = match stream.try_next().await {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(e) if Instant::now() >= deadline => {
+                return Err(anyhow::Error::new(e).context(format!("query exceeded the {}ms time limit", timeout.as_millis())))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if rows.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        let values = (0..row.len())
+            .map(|index| column_json(&row, index))
+            .collect::<Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+
+    Ok(AnalyticsSqlResult {
+        columns,
+        row_count: rows.len(),
+        rows,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// A column of an untyped row as JSON, following the value's storage class. BLOBs are
+/// base64 encoded.
+fn column_json(row: &SqliteRow, index: usize) -> Result<serde_json::Value> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+    Ok(match raw.type_info().name() {
+        "INTEGER" => row.try_get::<i64, _>(index)?.into(),
+        "REAL" => row.try_get::<f64, _>(index)?.into(),
+        "BLOB" => {
+            use base64::Engine;
+            let bytes: Vec<u8> = row.try_get(index)?;
+            base64::engine::general_purpose::STANDARD.encode(bytes).into()
+        }
+        _ => row.try_get::<String, _>(index)?.into(),
+    })
+}
+
+// Row types. SQLite stores UUIDs, decimals and timestamps as TEXT, so each table is read
+// into a row struct with named columns and then converted into its domain model.
+
+const SYMBOL_COLUMNS: &str = "id, symbol, name, exchange, sector, industry, market_cap, \
+     timezone, utc_offset_seconds, currency, status, status_changed_at, created_at, updated_at";
+
+const HISTORICAL_PRICE_COLUMNS: &str =
+    "id, symbol_id, symbol, timestamp, open, high, low, close, adjusted_close, volume, created_at";
+
+const DIVIDEND_COLUMNS: &str = "id, symbol_id, symbol, ex_date, amount, created_at";
+
+const REALTIME_QUOTE_COLUMNS: &str =
+    "id, symbol_id, symbol, price, change, change_percent, volume, market_time, trading_session, regular_price, created_at";
+
+const COMPANY_PROFILE_COLUMNS: &str =
+    "id, symbol_id, symbol, company_name, description, sector, industry, employees, \
+     website, address, city, state, country, zip_code, phone, created_at, updated_at";
+
+/// Company profile columns copied into snapshots; `id` is the snapshot's own
+const SNAPSHOT_PROFILE_COLUMNS: &str =
+    "symbol_id, symbol, company_name, description, sector, industry, employees, \
+     website, address, city, state, country, zip_code, phone, created_at, updated_at";
+
+const PORTFOLIO_HOLDING_COLUMNS: &str =
+    "id, symbol, symbol_id, asset_type, quantity, purchase_price, current_price, current_value, \
+     gain_loss, gain_loss_percent, target_weight, stop_loss, trailing_stop_percent, target_price, \
+     high_water_mark, stop_triggered_at, target_triggered_at, alert_cooldown_minutes, \
+     alert_rearm_percent, alerts_muted_until, last_alert_at, alert_channels, last_updated, created_at, \
+     updated_at";
+
+const CASH_FLOW_COLUMNS: &str = "id, kind, amount, flow_date, notes, created_at";
+
+const PORTFOLIO_LOT_COLUMNS: &str =
+    "id, holding_id, quantity, purchase_price, acquired_at, created_at";
+
+const SIGNAL_ALERT_COLUMNS: &str = "id, symbol, condition, indicator_id, \
+     (SELECT name FROM custom_indicators c WHERE c.id = signal_alerts.indicator_id) AS indicator, threshold, \
+     cooldown_minutes, channels, muted_until, last_fired_on, last_fired_at, created_at";
+
+/// `portfolio_settings` key holding the time of the last background price refresh
+const LAST_REFRESH_SETTING: &str = "last_refreshed_at";
+
+const AUDIT_COLUMNS: &str =
+    "id, actor_type, actor, action, method, path, status_code, summary, created_at";
+
+const ADMIN_SESSION_COLUMNS: &str =
+    "id, method, subject, user_agent, created_at, expires_at, revoked_at";
+
+const WEBHOOK_COLUMNS: &str =
+    "id, url, events, secret, description, active, created_at, updated_at";
+
+const WEBHOOK_DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event, payload, attempts, status_code, success, error, created_at";
+
+const API_KEY_COLUMNS: &str =
+    "id, name, prefix, key_hash, role, daily_quota, monthly_quota, active, created_at, updated_at, last_used_at";
+
+const NOTIFICATION_COLUMNS: &str =
+    "id, channel, source, subject, body, success, error, created_at";
+
+const DASHBOARD_COLUMNS: &str =
+    "id, owner, name, widgets, is_default, created_at, updated_at";
+
+const CUSTOM_INDICATOR_COLUMNS: &str = "id, owner, name, expression, created_at, updated_at";
+
+const JOURNAL_ENTRY_COLUMNS: &str = "id, owner, symbol, side, quantity, price, fees, trade_date, rationale, tags, \
+     screenshot_urls, created_at, updated_at";
+
+/// Columns of `strategies s` joined with its latest `strategy_versions v`
+const STRATEGY_COLUMNS: &str =
+    "s.id, s.owner, s.name, s.description, s.shared, s.version, v.definition, s.created_at, s.updated_at";
+
+const ANOMALY_COLUMNS: &str = "id, symbol, date, kind, value, z_score, baseline, close, volume, detected_at";
+
+const RANKING_MODEL_COLUMNS: &str = "name, description, weights, created_at, updated_at";
+
+const UNIVERSE_COLUMNS: &str = "u.name, u.description, \
+     (SELECT COUNT(*) FROM universe_members m WHERE m.universe = u.name) AS member_count, \
+     u.created_at, u.updated_at";
+
+const JOB_COLUMNS: &str = "id, kind, request, status, result, error, progress_done, progress_total, \
+     created_at, started_at, finished_at";
+
+const EXPORT_RUN_COLUMNS: &str = "id, triggered_by, status, data_since, data_until, objects_written, \
+     rows_written, bytes_written, error, started_at, finished_at";
+
+const PORTFOLIO_TRANSACTION_COLUMNS: &str =
+    "id, symbol, side, quantity, price, fees, trade_date, notes, created_at";
+
+/// Apply one writer-task operation on `conn`; returns the rows written
+pub(crate) async fn apply_write(conn: &mut SqliteConnection, op: &WriteOp) -> Result<usize> {
+    let mut written = 0;
+    match op {
+        WriteOp::HistoricalPrices(prices) => {
+            for price in prices {
+                let result = sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO historical_prices
+                    (id, symbol_id, symbol, timestamp, open, high, low, close, adjusted_close, volume, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                )
+                .bind(price.id.to_string())
+                .bind(price.symbol_id.to_string())
+                .bind(&price.symbol)
+                .bind(price.timestamp.to_rfc3339())
+                .bind(price.open.to_string())
+                .bind(price.high.to_string())
+                .bind(price.low.to_string())
+                .bind(price.close.to_string())
+                .bind(price.adjusted_close.as_ref().map(|d| d.to_string()))
+                .bind(price.volume)
+                .bind(price.created_at.to_rfc3339())
+                .execute(&mut *conn)
+                .await?;
+
+                written += result.rows_affected() as usize;
+            }
+        }
+        WriteOp::Dividends(dividends) => {
+            for dividend in dividends {
+                let result = sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO dividends (id, symbol_id, symbol, ex_date, amount, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    "#,
+                )
+                .bind(dividend.id.to_string())
+                .bind(dividend.symbol_id.to_string())
+                .bind(&dividend.symbol)
+                .bind(dividend.ex_date.to_string())
+                .bind(dividend.amount.to_string())
+                .bind(dividend.created_at.to_rfc3339())
+                .execute(&mut *conn)
+                .await?;
+
+                written += result.rows_affected() as usize;
+            }
+        }
+        WriteOp::RealtimeQuote(quote) => {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO realtime_quotes
+                (id, symbol_id, symbol, price, change, change_percent, volume, market_time, trading_session, regular_price, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                "#,
+            )
+            .bind(quote.id.to_string())
+            .bind(quote.symbol_id.to_string())
+            .bind(&quote.symbol)
+            .bind(quote.price.to_string())
+            .bind(quote.change.as_ref().map(|d| d.to_string()))
+            .bind(quote.change_percent.as_ref().map(|d| d.to_string()))
+            .bind(quote.volume)
+            .bind(quote.market_time.to_rfc3339())
+            .bind(&quote.trading_session)
+            .bind(quote.regular_price.as_ref().map(|d| d.to_string()))
+            .bind(quote.created_at.to_rfc3339())
+            .execute(&mut *conn)
+            .await?;
+
+            written += result.rows_affected() as usize;
+        }
+        WriteOp::MacroObservations { series, observations } => {
+            for (date, value) in observations {
+                let result = sqlx::query(
+                    r#"
+                    INSERT INTO macro_observations (series, date, value) VALUES (?1, ?2, ?3)
+                    ON CONFLICT(series, date) DO UPDATE SET value = excluded.value
+                    "#,
+                )
+                .bind(series)
+                .bind(date.to_string())
+                .bind(value.to_string())
+                .execute(&mut *conn)
+                .await?;
+
+                written += result.rows_affected() as usize;
+            }
+        }
+        WriteOp::FxRates { pair, rates } => {
+            for (date, rate) in rates {
+                let result = sqlx::query(
+                    r#"
+                    INSERT INTO fx_rates (pair, date, rate) VALUES (?1, ?2, ?3)
+                    ON CONFLICT(pair, date) DO UPDATE SET rate = excluded.rate
+                    "#,
+                )
+                .bind(pair)
+                .bind(date.to_string())
+                .bind(rate.to_string())
+                .execute(&mut *conn)
+                .await?;
+
+                written += result.rows_affected() as usize;
+            }
+        }
+        WriteOp::HoldingPrices {
+            updates,
+            updated_at,
+            refresh_cycle,
+        } => {
+            for update in updates {
+                let result = sqlx::query(
+                    r#"
+                    UPDATE portfolio_holdings
+                    SET current_price = ?1, current_value = ?2, gain_loss = ?3,
+                        gain_loss_percent = ?4, last_updated = ?5, updated_at = ?5
+                    WHERE id = ?6
+                    "#,
+                )
+                .bind(update.current_price.to_string())
+                .bind(update.current_value.to_string())
+                .bind(update.gain_loss.to_string())
+                .bind(update.gain_loss_percent.to_string())
+                .bind(updated_at.to_rfc3339())
+                .bind(update.holding_id.to_string())
+                .execute(&mut *conn)
+                .await?;
+
+                written += result.rows_affected() as usize;
+            }
+
+            if *refresh_cycle {
+                let stamp = updated_at.to_rfc3339();
+                sqlx::query(
+                    r#"
+                    INSERT INTO portfolio_settings (key, value, updated_at) VALUES (?1, ?2, ?2)
+                    ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+                    "#,
+                )
+                .bind(LAST_REFRESH_SETTING)
+                .bind(&stamp)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+    }
+    Ok(written)
+}
+
+async fn clear_default_dashboard(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, owner: &str) -> Result<()> {
+    sqlx::query("UPDATE dashboards SET is_default = 0 WHERE owner = ?1 AND is_default = 1")
+        .bind(owner)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Store a strategy's current definition as its `version`
+async fn insert_strategy_version(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, strategy: &Strategy) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO strategy_versions (strategy_id, version, definition, created_at) VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(strategy.id.to_string())
+    .bind(strategy.version)
+    .bind(serde_json::to_string(&strategy.definition)?)
+    .bind(strategy.updated_at.to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Move the holding of `from` to `to`. When both are held, the quantities are combined at the
+/// weighted average price and the lots of `from` join the holding of `to`.
+async fn rename_portfolio_holding(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    from: &str,
+    to: &str,
+    symbol_id: &str,
+    now: &str,
+) -> Result<u64> {
+    let held = |symbol: &str| {
+        sqlx::query_as::<_, (String, String, String)>(
+            "SELECT id, quantity, purchase_price FROM portfolio_holdings WHERE symbol = ?1 LIMIT 1",
+        )
+        .bind(symbol.to_string())
+    };
+    let existing = held(to).fetch_optional(&mut **tx).await?;
+    let Some((target_id, target_quantity, target_price)) = existing else {
+        let result = sqlx::query(
+            "UPDATE portfolio_holdings SET symbol = ?2, symbol_id = ?3, updated_at = ?4 WHERE symbol = ?1",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(symbol_id)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+        return Ok(result.rows_affected());
+    };
+
+    let mut moved = 0;
+    let mut quantity = Decimal::from_str(&target_quantity)?;
+    let mut cost = quantity * Decimal::from_str(&target_price)?;
+    while let Some((holding_id, held_quantity, held_price)) = held(from).fetch_optional(&mut **tx).await? {
+        let held_quantity = Decimal::from_str(&held_quantity)?;
+        quantity += held_quantity;
+        cost += held_quantity * Decimal::from_str(&held_price)?;
+
+        sqlx::query("UPDATE portfolio_lots SET holding_id = ?1 WHERE holding_id = ?2")
+            .bind(&target_id)
+            .bind(&holding_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query("DELETE FROM portfolio_holdings WHERE id = ?1")
+            .bind(&holding_id)
+            .execute(&mut **tx)
+            .await?;
+        moved += 1;
+    }
+
+    if moved > 0 && quantity > Decimal::ZERO {
+        sqlx::query(
+            "UPDATE portfolio_holdings SET quantity = ?1, purchase_price = ?2, updated_at = ?3 WHERE id = ?4",
+        )
+        .bind(quantity.to_string())
+        .bind((cost / quantity).to_string())
+        .bind(now)
+        .bind(&target_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(moved)
+}
+
+async fn insert_portfolio_lot(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    holding_id: Uuid,
+    quantity: Decimal,
+    purchase_price: Decimal,
+    acquired_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO portfolio_lots (id, holding_id, quantity, purchase_price, acquired_at, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(holding_id.to_string())
+    .bind(quantity.to_string())
+    .bind(purchase_price.to_string())
+    .bind(acquired_at.to_rfc3339())
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Replace a holding's lots with one lot matching its current quantity and average price,
+/// dated at the earliest purchase
+async fn collapse_portfolio_lots(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    holding_id: Uuid,
+) -> Result<()> {
+    let (quantity, purchase_price, created_at): (String, String, String) = sqlx::query_as(
+        "SELECT quantity, purchase_price, created_at FROM portfolio_holdings WHERE id = ?1",
+    )
+    .bind(holding_id.to_string())
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let earliest: Option<String> =
+        sqlx::query_scalar("SELECT MIN(acquired_at) FROM portfolio_lots WHERE holding_id = ?1")
+            .bind(holding_id.to_string())
+            .fetch_one(&mut **tx)
+            .await?;
+
+    sqlx::query("DELETE FROM portfolio_lots WHERE holding_id = ?1")
+        .bind(holding_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+    let acquired_at = parse_timestamp(earliest.as_deref().unwrap_or(&created_at))?;
+    insert_portfolio_lot(
+        tx,
+        holding_id,
+        Decimal::from_str(&quantity)?,
+        Decimal::from_str(&purchase_price)?,
+        acquired_at,
+    )
+    .await
+}
+
+/// Append sector/exchange/universe predicates numbered after `bind_count`; returns the new
+/// bind count. The query must select from `symbols` without an alias.
+fn push_symbol_filter(query: &mut String, filter: &SymbolFilter, mut bind_count: usize) -> usize {
+    if filter.sector.is_some() {
+        bind_count += 1;
+        query.push_str(&format!(" AND UPPER(symbols.sector) = UPPER(?{bind_count})"));
+    }
+    if filter.exchange.is_some() {
+        bind_count += 1;
+        query.push_str(&format!(" AND UPPER(symbols.exchange) = UPPER(?{bind_count})"));
+    }
+    if filter.universe.is_some() {
+        bind_count += 1;
+        query.push_str(&format!(
+            " AND symbols.symbol IN (SELECT symbol FROM universe_members WHERE universe = LOWER(?{bind_count}))"
+        ));
+    }
+    if filter.status.is_some() {
+        bind_count += 1;
+        query.push_str(&format!(" AND symbols.status = ?{bind_count}"));
+    }
+    bind_count
+}
+
+fn bind_symbol_filter<'q, O>(
+    mut query: sqlx::query::QueryAs<'q, Sqlite, O, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &'q SymbolFilter,
+) -> sqlx::query::QueryAs<'q, Sqlite, O, sqlx::sqlite::SqliteArguments<'q>> {
+    if let Some(sector) = &filter.sector {
+        query = query.bind(sector);
+    }
+    if let Some(exchange) = &filter.exchange {
+        query = query.bind(exchange);
+    }
+    if let Some(universe) = &filter.universe {
+        query = query.bind(universe);
+    }
+    if let Some(status) = filter.status {
+        query = query.bind(status.as_str());
+    }
+    query
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
+// Optional columns are parsed leniently: a malformed value reads as NULL rather than
+// failing the whole query
+fn parse_optional_decimal(value: Option<String>) -> Option<Decimal> {
+    value.and_then(|s| Decimal::from_str(&s).ok())
+}
+
+/// Notification channel names as stored: lowercase and comma-separated, empty for none
+fn join_channels(channels: &[String]) -> String {
+    channels.iter().map(|c| c.trim().to_ascii_lowercase()).collect::<Vec<_>>().join(",")
+}
+
+fn split_channels(value: &str) -> Vec<String> {
+    value.split(',').filter(|c| !c.is_empty()).map(str::to_string).collect()
+}
+
+#[derive(FromRow)]
+struct SymbolRow {
+    id: String,
+    symbol: String,
+    name: Option<String>,
+    exchange: Option<String>,
+    sector: Option<String>,
+    industry: Option<String>,
+    market_cap: Option<String>,
+    timezone: Option<String>,
+    utc_offset_seconds: Option<i32>,
+    currency: Option<String>,
+    status: String,
+    status_changed_at: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<SymbolRow> for Symbol {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SymbolRow) -> Result<Self> {
+        Ok(Symbol {
+            id: Uuid::from_str(&row.id)?,
+            symbol: row.symbol,
+            name: row.name,
+            exchange: row.exchange,
+            sector: row.sector,
+            industry: row.industry,
+            market_cap: parse_optional_decimal(row.market_cap),
+            timezone: row.timezone,
+            utc_offset_seconds: row.utc_offset_seconds,
+            currency: row.currency,
+            status: row.status.parse().map_err(anyhow::Error::msg)?,
+            status_changed_at: row.status_changed_at.as_deref().map(parse_timestamp).transpose()?,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct DividendRow {
+    id: String,
+    symbol_id: String,
+    symbol: String,
+    ex_date: String,
+    amount: String,
+    created_at: String,
+}
+
+impl TryFrom<DividendRow> for Dividend {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DividendRow) -> Result<Self> {
+        Ok(Dividend {
+            id: Uuid::from_str(&row.id)?,
+            symbol_id: Uuid::from_str(&row.symbol_id)?,
+            symbol: row.symbol,
+            ex_date: NaiveDate::from_str(&row.ex_date)?,
+            amount: Decimal::from_str(&row.amount)?,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct HistoricalPriceRow {
+    id: String,
+    symbol_id: String,
+    symbol: String,
+    timestamp: String,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    adjusted_close: Option<String>,
+    volume: i64,
+    created_at: String,
+}
+
+impl TryFrom<HistoricalPriceRow> for HistoricalPrice {
+    type Error = anyhow::Error;
+
+    fn try_from(row: HistoricalPriceRow) -> Result<Self> {
+        Ok(HistoricalPrice {
+            id: Uuid::from_str(&row.id)?,
+            symbol_id: Uuid::from_str(&row.symbol_id)?,
+            symbol: row.symbol,
+            timestamp: parse_timestamp(&row.timestamp)?,
+            open: Decimal::from_str(&row.open)?,
+            high: Decimal::from_str(&row.high)?,
+            low: Decimal::from_str(&row.low)?,
+            close: Decimal::from_str(&row.close)?,
+            adjusted_close: parse_optional_decimal(row.adjusted_close),
+            volume: row.volume,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct PeriodStatsRow {
+    period_start: String,
+    first_timestamp: String,
+    last_timestamp: String,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    volume: i64,
+    average_volume: i64,
+    candles: i64,
+    returns: i64,
+    mean_return: Option<f64>,
+    mean_square_return: Option<f64>,
+    previous_close: Option<String>,
+}
+
+impl TryFrom<PeriodStatsRow> for PeriodStats {
+    type Error = anyhow::Error;
+
+    fn try_from(row: PeriodStatsRow) -> Result<Self> {
+        let open = Decimal::from_str(&row.open)?;
+        let close = Decimal::from_str(&row.close)?;
+        let base = parse_optional_decimal(row.previous_close).unwrap_or(open);
+        let return_ratio = (!base.is_zero()).then(|| (close / base - Decimal::ONE).round_dp(4));
+
+        // Sample standard deviation from the mean and mean square of the daily returns
+        let volatility = match (row.mean_return, row.mean_square_return) {
+            (Some(mean), Some(mean_square)) if row.returns >= 2 => {
+                let n = row.returns as f64;
+                let variance = ((mean_square - mean * mean) * n / (n - 1.0)).max(0.0);
+                Decimal::from_f64_retain((variance * TRADING_DAYS_PER_YEAR).sqrt()).map(|v| v.round_dp(4))
+            }
+            _ => None,
+        };
+
+        Ok(PeriodStats {
+            period_start: NaiveDate::from_str(&row.period_start)?,
+            first_timestamp: parse_timestamp(&row.first_timestamp)?,
+            last_timestamp: parse_timestamp(&row.last_timestamp)?,
+            open,
+            high: Decimal::from_str(&row.high)?,
+            low: Decimal::from_str(&row.low)?,
+            close,
+            return_ratio,
+            volatility,
+            volume: row.volume,
+            average_volume: row.average_volume,
+            candles: row.candles,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct RealTimeQuoteRow {
+    id: String,
+    symbol_id: String,
+    symbol: String,
+    price: String,
+    change: Option<String>,
+    change_percent: Option<String>,
+    volume: Option<i64>,
+    market_time: String,
+    trading_session: String,
+    regular_price: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<RealTimeQuoteRow> for RealTimeQuote {
+    type Error = anyhow::Error;
+
+    fn try_from(row: RealTimeQuoteRow) -> Result<Self> {
+        Ok(RealTimeQuote {
+            id: Uuid::from_str(&row.id)?,
+            symbol_id: Uuid::from_str(&row.symbol_id)?,
+            symbol: row.symbol,
+            price: Decimal::from_str(&row.price)?,
+            change: parse_optional_decimal(row.change),
+            change_percent: parse_optional_decimal(row.change_percent),
+            volume: row.volume,
+            market_time: parse_timestamp(&row.market_time)?,
+            trading_session: row.trading_session,
+            regular_price: parse_optional_decimal(row.regular_price),
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct CompanyProfileRow {
+    id: String,
+    symbol_id: String,
+    symbol: String,
+    company_name: Option<String>,
+    description: Option<String>,
+    sector: Option<String>,
+    industry: Option<String>,
+    employees: Option<i32>,
+    website: Option<String>,
+    address: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+    zip_code: Option<String>,
+    phone: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(FromRow)]
+struct CompanyProfileSnapshotRow {
+    as_of: String,
+    #[sqlx(flatten)]
+    profile: CompanyProfileRow,
+}
+
+impl TryFrom<CompanyProfileRow> for CompanyProfile {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CompanyProfileRow) -> Result<Self> {
+        Ok(CompanyProfile {
+            id: Uuid::from_str(&row.id)?,
+            symbol_id: Uuid::from_str(&row.symbol_id)?,
+            symbol: row.symbol,
+            company_name: row.company_name,
+            description: row.description,
+            sector: row.sector,
+            industry: row.industry,
+            employees: row.employees,
+            website: row.website,
+            address: row.address,
+            city: row.city,
+            state: row.state,
+            country: row.country,
+            zip_code: row.zip_code,
+            phone: row.phone,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct PortfolioHoldingRow {
+    id: String,
+    symbol: String,
+    symbol_id: Option<String>,
+    asset_type: String,
+    quantity: String,
+    purchase_price: String,
+    current_price: Option<String>,
+    current_value: Option<String>,
+    gain_loss: Option<String>,
+    gain_loss_percent: Option<String>,
+    target_weight: Option<String>,
+    stop_loss: Option<String>,
+    trailing_stop_percent: Option<String>,
+    target_price: Option<String>,
+    high_water_mark: Option<String>,
+    stop_triggered_at: Option<String>,
+    target_triggered_at: Option<String>,
+    alert_cooldown_minutes: Option<i64>,
+    alert_rearm_percent: Option<String>,
+    alerts_muted_until: Option<String>,
+    last_alert_at: Option<String>,
+    alert_channels: Option<String>,
+    last_updated: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<PortfolioHoldingRow> for PortfolioHolding {
+    type Error = anyhow::Error;
+
+    fn try_from(row: PortfolioHoldingRow) -> Result<Self> {
+        Ok(PortfolioHolding {
+            id: Uuid::from_str(&row.id)?,
+            symbol: row.symbol,
+            symbol_id: row.symbol_id.and_then(|s| Uuid::from_str(&s).ok()),
+            asset_type: row.asset_type,
+            quantity: Decimal::from_str(&row.quantity)?,
+            purchase_price: Decimal::from_str(&row.purchase_price)?,
+            current_price: parse_optional_decimal(row.current_price),
+            current_value: parse_optional_decimal(row.current_value),
+            gain_loss: parse_optional_decimal(row.gain_loss),
+            gain_loss_percent: parse_optional_decimal(row.gain_loss_percent),
+            target_weight: parse_optional_decimal(row.target_weight),
+            stop_loss: parse_optional_decimal(row.stop_loss),
+            trailing_stop_percent: parse_optional_decimal(row.trailing_stop_percent),
+            target_price: parse_optional_decimal(row.target_price),
+            high_water_mark: parse_optional_decimal(row.high_water_mark),
+            stop_triggered_at: row.stop_triggered_at.and_then(|s| parse_timestamp(&s).ok()),
+            target_triggered_at: row.target_triggered_at.and_then(|s| parse_timestamp(&s).ok()),
+            alert_cooldown_minutes: row.alert_cooldown_minutes,
+            alert_rearm_percent: parse_optional_decimal(row.alert_rearm_percent),
+            alerts_muted_until: row.alerts_muted_until.and_then(|s| parse_timestamp(&s).ok()),
+            last_alert_at: row.last_alert_at.and_then(|s| parse_timestamp(&s).ok()),
+            alert_channels: row.alert_channels.as_deref().map(split_channels),
+            last_updated: row.last_updated.and_then(|s| parse_timestamp(&s).ok()),
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct CashFlowRow {
+    id: String,
+    kind: String,
+    amount: String,
+    flow_date: String,
+    notes: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<CashFlowRow> for CashFlow {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CashFlowRow) -> Result<Self> {
+        Ok(CashFlow {
+            id: Uuid::from_str(&row.id)?,
+            kind: row.kind,
+            amount: Decimal::from_str(&row.amount)?,
+            flow_date: NaiveDate::from_str(&row.flow_date)?,
+            notes: row.notes,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct PortfolioLotRow {
+    id: String,
+    holding_id: String,
+    quantity: String,
+    purchase_price: String,
+    acquired_at: String,
+    created_at: String,
+}
+
+impl TryFrom<PortfolioLotRow> for PortfolioLot {
+    type Error = anyhow::Error;
+
+    fn try_from(row: PortfolioLotRow) -> Result<Self> {
+        Ok(PortfolioLot {
+            id: Uuid::from_str(&row.id)?,
+            holding_id: Uuid::from_str(&row.holding_id)?,
+            quantity: Decimal::from_str(&row.quantity)?,
+            purchase_price: Decimal::from_str(&row.purchase_price)?,
+            acquired_at: parse_timestamp(&row.acquired_at)?,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct PortfolioTransactionRow {
+    id: String,
+    symbol: String,
+    side: String,
+    quantity: String,
+    price: String,
+    fees: String,
+    trade_date: String,
+    notes: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<PortfolioTransactionRow> for PortfolioTransaction {
+    type Error = anyhow::Error;
+
+    fn try_from(row: PortfolioTransactionRow) -> Result<Self> {
+        Ok(PortfolioTransaction {
+            id: Uuid::from_str(&row.id)?,
+            symbol: row.symbol,
+            side: row.side,
+            quantity: Decimal::from_str(&row.quantity)?,
+            price: Decimal::from_str(&row.price)?,
+            fees: Decimal::from_str(&row.fees)?,
+            trade_date: NaiveDate::from_str(&row.trade_date)?,
+            notes: row.notes,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct SymbolSnapshotRow {
+    symbol: String,
+    name: Option<String>,
+    exchange: Option<String>,
+    sector: Option<String>,
+    industry: Option<String>,
+    price: Option<String>,
+    change_percent: Option<String>,
+    volume: Option<i64>,
+    market_time: Option<String>,
+}
+
+impl TryFrom<SymbolSnapshotRow> for SymbolSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SymbolSnapshotRow) -> Result<Self> {
+        Ok(SymbolSnapshot {
+            symbol: row.symbol,
+            name: row.name,
+            exchange: row.exchange,
+            sector: row.sector,
+            industry: row.industry,
+            price: parse_optional_decimal(row.price),
+            change_percent: parse_optional_decimal(row.change_percent),
+            volume: row.volume,
+            as_of: row.market_time.map(|t| parse_timestamp(&t)).transpose()?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct AdminSessionRow {
+    id: String,
+    method: String,
+    subject: String,
+    user_agent: Option<String>,
+    created_at: String,
+    expires_at: String,
+    revoked_at: Option<String>,
+}
+
+impl TryFrom<AdminSessionRow> for AdminSession {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AdminSessionRow) -> Result<Self> {
+        Ok(AdminSession {
+            id: Uuid::from_str(&row.id)?,
+            method: row.method,
+            subject: row.subject,
+            user_agent: row.user_agent,
+            created_at: parse_timestamp(&row.created_at)?,
+            expires_at: parse_timestamp(&row.expires_at)?,
+            revoked_at: row.revoked_at.map(|t| parse_timestamp(&t)).transpose()?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct AuditEntryRow {
+    id: String,
+    actor_type: String,
+    actor: String,
+    action: String,
+    method: String,
+    path: String,
+    status_code: i64,
+    summary: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<AuditEntryRow> for AuditEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AuditEntryRow) -> Result<Self> {
+        Ok(AuditEntry {
+            id: Uuid::from_str(&row.id)?,
+            actor_type: row.actor_type,
+            actor: row.actor,
+            action: row.action,
+            method: row.method,
+            path: row.path,
+            status_code: u16::try_from(row.status_code)?,
+            summary: row.summary,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct WebhookRow {
+    id: String,
+    url: String,
+    events: String,
+    secret: String,
+    description: Option<String>,
+    active: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(FromRow)]
+struct ApiKeyRow {
+    id: String,
+    name: String,
+    prefix: String,
+    key_hash: String,
+    role: String,
+    daily_quota: Option<i64>,
+    monthly_quota: Option<i64>,
+    active: bool,
+    created_at: String,
+    updated_at: String,
+    last_used_at: Option<String>,
+}
+
+impl TryFrom<ApiKeyRow> for ApiKey {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ApiKeyRow) -> Result<Self> {
+        Ok(ApiKey {
+            id: Uuid::from_str(&row.id)?,
+            name: row.name,
+            prefix: row.prefix,
+            key_hash: row.key_hash,
+            role: row.role.parse().map_err(anyhow::Error::msg)?,
+            daily_quota: row.daily_quota.map(|quota| quota.clamp(0, u32::MAX as i64) as u32),
+            monthly_quota: row.monthly_quota.map(|quota| quota.clamp(0, u32::MAX as i64) as u32),
+            active: row.active,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+            last_used_at: row.last_used_at.as_deref().map(parse_timestamp).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<WebhookRow> for Webhook {
+    type Error = anyhow::Error;
+
+    fn try_from(row: WebhookRow) -> Result<Self> {
+        Ok(Webhook {
+            id: Uuid::from_str(&row.id)?,
+            url: row.url,
+            events: row
+                .events
+                .split(',')
+                .filter(|e| !e.is_empty())
+                .map(str::to_string)
+                .collect(),
+            secret: row.secret,
+            description: row.description,
+            active: row.active,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct WebhookDeliveryRow {
+    id: String,
+    webhook_id: String,
+    event: String,
+    payload: String,
+    attempts: i64,
+    status_code: Option<i64>,
+    success: bool,
+    error: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<WebhookDeliveryRow> for WebhookDelivery {
+    type Error = anyhow::Error;
+
+    fn try_from(row: WebhookDeliveryRow) -> Result<Self> {
+        Ok(WebhookDelivery {
+            id: Uuid::from_str(&row.id)?,
+            webhook_id: Uuid::from_str(&row.webhook_id)?,
+            event: row.event,
+            payload: row.payload,
+            attempts: u32::try_from(row.attempts)?,
+            status_code: row.status_code.map(u16::try_from).transpose()?,
+            success: row.success,
+            error: row.error,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct DashboardRow {
+    id: String,
+    owner: String,
+    name: String,
+    widgets: String,
+    is_default: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<DashboardRow> for Dashboard {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DashboardRow) -> Result<Self> {
+        Ok(Dashboard {
+            id: Uuid::from_str(&row.id)?,
+            owner: row.owner,
+            name: row.name,
+            widgets: serde_json::from_str(&row.widgets)?,
+            is_default: row.is_default,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct JournalEntryRow {
+    id: String,
+    owner: String,
+    symbol: String,
+    side: String,
+    quantity: String,
+    price: String,
+    fees: String,
+    trade_date: String,
+    rationale: Option<String>,
+    tags: String,
+    screenshot_urls: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<JournalEntryRow> for JournalEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JournalEntryRow) -> Result<Self> {
+        Ok(JournalEntry {
+            id: Uuid::from_str(&row.id)?,
+            owner: row.owner,
+            symbol: row.symbol,
+            side: row.side,
+            quantity: Decimal::from_str(&row.quantity)?,
+            price: Decimal::from_str(&row.price)?,
+            fees: Decimal::from_str(&row.fees)?,
+            trade_date: NaiveDate::from_str(&row.trade_date)?,
+            rationale: row.rationale,
+            tags: serde_json::from_str(&row.tags)?,
+            screenshot_urls: serde_json::from_str(&row.screenshot_urls)?,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct StrategyRow {
+    id: String,
+    owner: String,
+    name: String,
+    description: Option<String>,
+    shared: bool,
+    version: i64,
+    definition: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<StrategyRow> for Strategy {
+    type Error = anyhow::Error;
+
+    fn try_from(row: StrategyRow) -> Result<Self> {
+        Ok(Strategy {
+            id: Uuid::from_str(&row.id)?,
+            owner: row.owner,
+            name: row.name,
+            description: row.description,
+            shared: row.shared,
+            version: row.version,
+            definition: serde_json::from_str(&row.definition)?,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct StrategyVersionRow {
+    version: i64,
+    definition: String,
+    created_at: String,
+}
+
+impl TryFrom<StrategyVersionRow> for StrategyVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(row: StrategyVersionRow) -> Result<Self> {
+        Ok(StrategyVersion {
+            version: row.version,
+            definition: serde_json::from_str(&row.definition)?,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct CustomIndicatorRow {
+    id: String,
+    owner: String,
+    name: String,
+    expression: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<CustomIndicatorRow> for CustomIndicator {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CustomIndicatorRow) -> Result<Self> {
+        Ok(CustomIndicator {
+            id: Uuid::from_str(&row.id)?,
+            owner: row.owner,
+            name: row.name,
+            expression: row.expression,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct JobRow {
+    id: String,
+    kind: String,
+    request: String,
+    status: String,
+    result: Option<String>,
+    error: Option<String>,
+    progress_done: Option<i64>,
+    progress_total: Option<i64>,
+    created_at: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+}
+
+impl TryFrom<JobRow> for Job {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JobRow) -> Result<Self> {
+        Ok(Job {
+            id: Uuid::from_str(&row.id)?,
+            kind: row.kind,
+            request: serde_json::from_str(&row.request)?,
+            status: row.status.parse().map_err(anyhow::Error::msg)?,
+            result: row.result.as_deref().map(serde_json::from_str).transpose()?,
+            error: row.error,
+            progress: row
+                .progress_done
+                .zip(row.progress_total)
+                .map(|(done, total)| JobProgress { done, total }),
+            created_at: parse_timestamp(&row.created_at)?,
+            started_at: row.started_at.as_deref().map(parse_timestamp).transpose()?,
+            finished_at: row.finished_at.as_deref().map(parse_timestamp).transpose()?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct ExportRunRow {
+    id: String,
+    triggered_by: String,
+    status: String,
+    data_since: Option<String>,
+    data_until: String,
+    objects_written: i64,
+    rows_written: i64,
+    bytes_written: i64,
+    error: Option<String>,
+    started_at: String,
+    finished_at: Option<String>,
+}
+
+impl TryFrom<ExportRunRow> for ExportRun {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ExportRunRow) -> Result<Self> {
+        Ok(ExportRun {
+            id: Uuid::from_str(&row.id)?,
+            triggered_by: row.triggered_by.parse().map_err(anyhow::Error::msg)?,
+            status: row.status.parse().map_err(anyhow::Error::msg)?,
+            data_since: row.data_since.as_deref().map(parse_timestamp).transpose()?,
+            data_until: parse_timestamp(&row.data_until)?,
+            objects_written: row.objects_written,
+            rows_written: row.rows_written,
+            bytes_written: row.bytes_written,
+            error: row.error,
+            started_at: parse_timestamp(&row.started_at)?,
+            finished_at: row.finished_at.as_deref().map(parse_timestamp).transpose()?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct AnomalyRow {
+    id: String,
+    symbol: String,
+    date: String,
+    kind: String,
+    value: String,
+    z_score: String,
+    baseline: String,
+    close: String,
+    volume: i64,
+    detected_at: String,
+}
+
+impl TryFrom<AnomalyRow> for Anomaly {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AnomalyRow) -> Result<Self> {
+        Ok(Anomaly {
+            id: Uuid::from_str(&row.id)?,
+            symbol: row.symbol,
+            date: NaiveDate::from_str(&row.date)?,
+            kind: row.kind.parse().map_err(anyhow::Error::msg)?,
+            value: Decimal::from_str(&row.value)?,
+            z_score: Decimal::from_str(&row.z_score)?,
+            baseline: Decimal::from_str(&row.baseline)?,
+            close: Decimal::from_str(&row.close)?,
+            volume: row.volume,
+            detected_at: parse_timestamp(&row.detected_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct RankingModelRow {
+    name: String,
+    description: Option<String>,
+    weights: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<RankingModelRow> for RankingModel {
+    type Error = anyhow::Error;
+
+    fn try_from(row: RankingModelRow) -> Result<Self> {
+        Ok(RankingModel {
+            name: row.name,
+            description: row.description,
+            weights: serde_json::from_str(&row.weights)?,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct UniverseRow {
+    name: String,
+    description: Option<String>,
+    member_count: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<UniverseRow> for Universe {
+    type Error = anyhow::Error;
+
+    fn try_from(row: UniverseRow) -> Result<Self> {
+        Ok(Universe {
+            name: row.name,
+            description: row.description,
+            member_count: row.member_count,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct SymbolAliasRow {
+    alias: String,
+    symbol: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<SymbolAliasRow> for SymbolAlias {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SymbolAliasRow) -> Result<Self> {
+        Ok(SymbolAlias {
+            alias: row.alias,
+            symbol: row.symbol,
+            created_at: parse_timestamp(&row.created_at)?,
+            updated_at: parse_timestamp(&row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct StatsSampleRow {
+    sampled_at: String,
+    interval_seconds: i64,
+    yahoo_calls: i64,
+    yahoo_rate_limited: i64,
+    caches: String,
+}
+
+impl TryFrom<StatsSampleRow> for StatsSample {
+    type Error = anyhow::Error;
+
+    fn try_from(row: StatsSampleRow) -> Result<Self> {
+        Ok(StatsSample {
+            sampled_at: parse_timestamp(&row.sampled_at)?,
+            interval_seconds: row.interval_seconds.max(0) as u64,
+            yahoo_calls: row.yahoo_calls.max(0) as u64,
+            yahoo_rate_limited: row.yahoo_rate_limited.max(0) as u64,
+            caches: serde_json::from_str(&row.caches)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct SignalAlertRow {
+    id: String,
+    symbol: String,
+    condition: String,
+    indicator_id: String,
+    indicator: Option<String>,
+    threshold: String,
+    cooldown_minutes: Option<i64>,
+    channels: Option<String>,
+    muted_until: Option<String>,
+    last_fired_on: Option<String>,
+    last_fired_at: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<SignalAlertRow> for SignalAlert {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SignalAlertRow) -> Result<Self> {
+        Ok(SignalAlert {
+            id: Uuid::parse_str(&row.id)?,
+            symbol: row.symbol,
+            condition: row.condition.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+            // Built-in conditions store both as empty strings to keep them in the unique key
+            indicator_id: Some(row.indicator_id).filter(|id| !id.is_empty()).map(|id| Uuid::parse_str(&id)).transpose()?,
+            indicator: row.indicator,
+            threshold: parse_optional_decimal(Some(row.threshold)),
+            cooldown_minutes: row.cooldown_minutes,
+            channels: row.channels.as_deref().map(split_channels),
+            muted_until: row.muted_until.as_deref().map(parse_timestamp).transpose()?,
+            last_fired_on: row.last_fired_on.as_deref().map(NaiveDate::from_str).transpose()?,
+            last_fired_at: row.last_fired_at.as_deref().map(parse_timestamp).transpose()?,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct SecurityIdentifierRow {
+    identifier: String,
+    kind: String,
+    symbol: String,
+    name: Option<String>,
+    exchange: Option<String>,
+    source: String,
+    created_at: String,
+}
+
+impl TryFrom<SecurityIdentifierRow> for SecurityIdentifier {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SecurityIdentifierRow) -> Result<Self> {
+        Ok(SecurityIdentifier {
+            identifier: row.identifier,
+            kind: row.kind,
+            symbol: row.symbol,
+            name: row.name,
+            exchange: row.exchange,
+            source: row.source,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct UniverseMemberRow {
+    symbol: String,
+    name: Option<String>,
+    sector: Option<String>,
+    weight: Option<String>,
+    added_at: String,
+}
+
+impl TryFrom<UniverseMemberRow> for UniverseMember {
+    type Error = anyhow::Error;
+
+    fn try_from(row: UniverseMemberRow) -> Result<Self> {
+        Ok(UniverseMember {
+            symbol: row.symbol,
+            name: row.name,
+            sector: row.sector,
+            weight: parse_optional_decimal(row.weight),
+            added_at: parse_timestamp(&row.added_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct NotificationRow {
+    id: String,
+    channel: String,
+    source: String,
+    subject: String,
+    body: String,
+    success: bool,
+    error: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<NotificationRow> for Notification {
+    type Error = anyhow::Error;
+
+    fn try_from(row: NotificationRow) -> Result<Self> {
+        Ok(Notification {
+            id: Uuid::from_str(&row.id)?,
+            channel: row.channel,
+            source: row.source,
+            subject: row.subject,
+            body: row.body,
+            success: row.success,
+            error: row.error,
+            created_at: parse_timestamp(&row.created_at)?,
+        })
+    }
 }
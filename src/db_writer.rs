@@ -0,0 +1,136 @@
+//! Single writer task for the high-volume inserts and updates.
+//!
+//! SQLite allows one writer at a time. Bulk fetches, quote refreshes and the portfolio
+//! updater would otherwise contend for the write lock from many pooled connections and sit
+//! out the busy timeout. Instead these writes are sent over a channel to one task that owns
+//! them: it drains whatever has queued up (up to [`MAX_WRITE_BATCH`] operations) and applies
+//! the batch in a single transaction, each operation in its own savepoint so one failure
+//! does not undo the others. Callers wait for their own operation's outcome. Reads keep
+//! using the pool and, in WAL mode, never wait for the writer.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::{Connection, Sqlite, Transaction};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error};
+
+use crate::database::{self, DbPool};
+use crate::models::{Dividend, HistoricalPrice, HoldingPriceUpdate, RealTimeQuote};
+
+/// Most operations applied in one transaction
+const MAX_WRITE_BATCH: usize = 64;
+/// Operations that can wait for the writer before senders are held back
+const WRITE_QUEUE_CAPACITY: usize = 1024;
+
+/// A write owned by the writer task
+pub enum WriteOp {
+    HistoricalPrices(Vec<HistoricalPrice>),
+    Dividends(Vec<Dividend>),
+    RealtimeQuote(RealTimeQuote),
+    /// Daily closes of a currency pair such as `GBPUSD`; a stored day is overwritten
+    FxRates {
+        pair: String,
+        rates: Vec<(NaiveDate, Decimal)>,
+    },
+    /// Observations of a macro series such as `tbill-13w`; a stored day is overwritten
+    MacroObservations {
+        series: String,
+        observations: Vec<(NaiveDate, Decimal)>,
+    },
+    /// Refreshed values of several holdings, written together; a background refresh cycle
+    /// also records `updated_at` as the portfolio's last refresh
+    HoldingPrices {
+        updates: Vec<HoldingPriceUpdate>,
+        updated_at: DateTime<Utc>,
+        refresh_cycle: bool,
+    },
+}
+
+struct WriteRequest {
+    op: WriteOp,
+    /// Rows written, or why the operation failed
+    reply: oneshot::Sender<Result<usize>>,
+}
+
+/// Handle to the writer task; cheap to clone. The task stops once every handle is dropped.
+#[derive(Clone)]
+pub struct DbWriter {
+    sender: mpsc::Sender<WriteRequest>,
+}
+
+impl DbWriter {
+    /// Start the writer task for `pool` on the current runtime
+    pub fn spawn(pool: DbPool) -> Self {
+        let (sender, receiver) = mpsc::channel(WRITE_QUEUE_CAPACITY);
+        tokio::spawn(run(pool, receiver));
+        Self { sender }
+    }
+
+    /// Queue `op` and wait until it is committed
+    pub async fn write(&self, op: WriteOp) -> Result<usize> {
+        let (reply, outcome) = oneshot::channel();
+        self.sender
+            .send(WriteRequest { op, reply })
+            .await
+            .map_err(|_| anyhow!("Database writer has stopped"))?;
+        outcome
+            .await
+            .map_err(|_| anyhow!("Database writer dropped the write"))?
+    }
+}
+
+async fn run(pool: DbPool, mut receiver: mpsc::Receiver<WriteRequest>) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < MAX_WRITE_BATCH {
+            match receiver.try_recv() {
+                Ok(request) => batch.push(request),
+                Err(_) => break,
+            }
+        }
+        write_batch(&pool, batch).await;
+    }
+    debug!("Database writer stopped");
+}
+
+async fn write_batch(pool: &DbPool, batch: Vec<WriteRequest>) {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Database writer could not start a transaction: {}", e);
+            for request in batch {
+                let _ = request.reply.send(Err(anyhow!("Could not start a transaction: {}", e)));
+            }
+            return;
+        }
+    };
+
+    let mut outcomes = Vec::with_capacity(batch.len());
+    for request in &batch {
+        outcomes.push(apply_in_savepoint(&mut tx, &request.op).await);
+    }
+
+    match tx.commit().await {
+        Ok(()) => {
+            debug!("Database writer committed {} operations", batch.len());
+            for (request, outcome) in batch.into_iter().zip(outcomes) {
+                let _ = request.reply.send(outcome);
+            }
+        }
+        Err(e) => {
+            error!("Database writer failed to commit {} operations: {}", batch.len(), e);
+            for request in batch {
+                let _ = request.reply.send(Err(anyhow!("Write batch failed to commit: {}", e)));
+            }
+        }
+    }
+}
+
+/// A failed operation rolls back to its savepoint and leaves the rest of the batch intact
+async fn apply_in_savepoint(tx: &mut Transaction<'_, Sqlite>, op: &WriteOp) -> Result<usize> {
+    let mut savepoint = tx.begin().await?;
+    let rows = database::apply_write(&mut savepoint, op).await?;
+    savepoint.commit().await?;
+    Ok(rows)
+}
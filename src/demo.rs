@@ -0,0 +1,67 @@
+//! Sample data for DEMO_MODE.
+//!
+//! On the first start with DEMO_MODE=true a handful of well-known symbols are loaded from
+//! the bundled `demo/sample_data.json`: a year of daily candles, a latest quote, a company
+//! profile and sector metadata, generated by the mock provider so the dashboard has
+//! something to show without any network access. `DELETE /api/admin/demo` removes it again.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::database::Database;
+use crate::models::SymbolMetadata;
+use crate::provider::MockProvider;
+use crate::yahoo_service::YahooFinanceService;
+
+const SAMPLE_DATA: &str = include_str!("../demo/sample_data.json");
+
+/// Descriptive fields the mock provider does not serve; everything else in the sample
+/// file is read by [`MockProvider`]
+#[derive(Debug, Deserialize)]
+struct SampleSymbol {
+    sector: Option<String>,
+    industry: Option<String>,
+}
+
+/// Seed the sample symbols unless demo data was loaded before. Symbols that already exist
+/// in the database are left alone. Returns the number of symbols seeded.
+pub async fn seed_if_needed(db: Arc<Database>, config: &Config) -> Result<usize> {
+    if db.demo_data_seeded().await? {
+        info!("Demo data already seeded, skipping");
+        return Ok(0);
+    }
+
+    let samples: BTreeMap<String, SampleSymbol> =
+        serde_json::from_str(SAMPLE_DATA).context("Invalid bundled demo data")?;
+    let provider = MockProvider::from_fixture_json(SAMPLE_DATA).context("Invalid bundled demo data")?;
+    let seeder = YahooFinanceService::with_provider(db.clone(), config.clone(), Arc::new(provider));
+
+    let mut seeded = 0;
+    for (symbol, sample) in samples {
+        if db.get_symbol_id(&symbol).await?.is_some() {
+            warn!("Skipping demo data for {}: symbol already has data", symbol);
+            continue;
+        }
+
+        seeder.fetch_historical_data(&symbol, "1d", true).await?;
+        seeder.fetch_company_profile(&symbol, true).await?;
+        seeder.get_latest_quote(&symbol).await?;
+
+        let metadata = SymbolMetadata {
+            sector: sample.sector,
+            industry: sample.industry,
+            ..Default::default()
+        };
+        db.update_symbol_metadata(&symbol, &metadata).await?;
+        db.mark_demo_symbol(&symbol, Utc::now()).await?;
+        seeded += 1;
+    }
+
+    info!("Seeded demo data for {} symbols", seeded);
+    Ok(seeded)
+}
@@ -1,11 +1,240 @@
 use thiserror::Error;
+use axum::extract::Request;
+use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::Duration;
+
+use crate::models::ApiResponse;
+use crate::provider::UpstreamError;
+use crate::read_only::{is_read_only_write, READ_ONLY_MESSAGE};
+
+/// Machine-readable error codes carried in the `code` field of every failed `ApiResponse`.
+/// Clients should branch on these rather than on the human-readable `error` text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// Malformed parameters or body, or a value outside its allowed range
+    InvalidRequest,
+    /// Missing or invalid admin session or bearer token
+    Unauthorized,
+    /// The symbol is unknown locally and upstream
+    SymbolNotFound,
+    /// Any other resource (holding, webhook, dashboard, job...) that does not exist
+    NotFound,
+    /// Not enough stored history for the requested calculation
+    InsufficientData,
+    /// The request body exceeds the configured size limit
+    PayloadTooLarge,
+    /// The per-client API rate limit was hit
+    RateLimited,
+    /// The API key has used up its daily or monthly request quota
+    QuotaExceeded,
+    /// The upstream market-data provider, or the service's own budget of calls to it, refused
+    /// the call with a rate limit
+    UpstreamRateLimited,
+    /// The upstream market-data provider failed or could not be reached
+    UpstreamUnavailable,
+    /// The feature behind this endpoint is turned off in the configuration
+    FeatureDisabled,
+    /// A write, or a fetch that would have to store its result, sent to a read-only instance
+    ReadOnly,
+    /// Unexpected failure; the details are only logged
+    InternalError,
+}
+
+impl ErrorCode {
+    /// Every code, in the order they are documented
+    pub const ALL: [ErrorCode; 13] = [
+        ErrorCode::InvalidRequest,
+        ErrorCode::Unauthorized,
+        ErrorCode::SymbolNotFound,
+        ErrorCode::NotFound,
+        ErrorCode::InsufficientData,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::RateLimited,
+        ErrorCode::QuotaExceeded,
+        ErrorCode::UpstreamRateLimited,
+        ErrorCode::UpstreamUnavailable,
+        ErrorCode::FeatureDisabled,
+        ErrorCode::ReadOnly,
+        ErrorCode::InternalError,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::SymbolNotFound => "SYMBOL_NOT_FOUND",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::InsufficientData => "INSUFFICIENT_DATA",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ErrorCode::UpstreamRateLimited => "UPSTREAM_RATE_LIMITED",
+            ErrorCode::UpstreamUnavailable => "UPSTREAM_UNAVAILABLE",
+            ErrorCode::FeatureDisabled => "FEATURE_DISABLED",
+            ErrorCode::ReadOnly => "READ_ONLY",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Status used when the code is returned as an HTTP error rather than in a 200 envelope
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::SymbolNotFound | ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::InsufficientData => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::RateLimited | ErrorCode::QuotaExceeded | ErrorCode::UpstreamRateLimited => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            ErrorCode::UpstreamUnavailable | ErrorCode::ReadOnly => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::FeatureDisabled => StatusCode::NOT_IMPLEMENTED,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message sent when the handler has nothing more specific to say
+    pub fn default_message(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "Invalid request",
+            ErrorCode::Unauthorized => "Unauthorized",
+            ErrorCode::SymbolNotFound => "Symbol not found",
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::InsufficientData => "Insufficient data available",
+            ErrorCode::PayloadTooLarge => "Request body too large",
+            ErrorCode::RateLimited => "Rate limit exceeded. Please try again later",
+            ErrorCode::QuotaExceeded => "API key quota exceeded",
+            ErrorCode::UpstreamRateLimited => "Upstream data provider is rate limiting requests. Please try again later",
+            ErrorCode::UpstreamUnavailable => "Upstream data provider is unavailable",
+            ErrorCode::FeatureDisabled => "This feature is disabled",
+            ErrorCode::ReadOnly => READ_ONLY_MESSAGE,
+            ErrorCode::InternalError => "Internal server error",
+        }
+    }
+
+    /// Best code for a bare status, used to wrap responses produced outside the handlers
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCode::Unauthorized,
+            StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED => ErrorCode::NotFound,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            StatusCode::NOT_IMPLEMENTED => ErrorCode::FeatureDisabled,
+            StatusCode::SERVICE_UNAVAILABLE | StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => {
+                ErrorCode::UpstreamUnavailable
+            }
+            status if status.is_client_error() => ErrorCode::InvalidRequest,
+            _ => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error half of a handler result: rendered as an `ApiResponse` envelope with the code's status
+#[derive(Debug)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: Cow<'static, str>,
+    /// Sent as `Retry-After` so clients know when to come back
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<Cow<'static, str>>) -> Self {
+        Self { code, message: message.into(), retry_after: None }
+    }
+
+    /// Error for a failed service call: throttling and outages of the upstream provider keep
+    /// their own codes so clients can back off, anything else is an internal error
+    pub fn from_service(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            let upstream = match cause.downcast_ref::<YahooServiceError>() {
+                Some(YahooServiceError::Upstream(upstream)) => Some(upstream),
+                _ => cause.downcast_ref::<UpstreamError>(),
+            };
+            if let Some(upstream) = upstream {
+                return upstream.into();
+            }
+            if let Some(YahooServiceError::QuotaExhausted { retry_after, .. }) = cause.downcast_ref::<YahooServiceError>() {
+                return Self {
+                    retry_after: Some(*retry_after),
+                    ..ErrorCode::UpstreamRateLimited.into()
+                };
+            }
+            if let Some(YahooServiceError::RateLimitExceeded) = cause.downcast_ref::<YahooServiceError>() {
+                return ErrorCode::RateLimited.into();
+            }
+            if let Some(e @ YahooServiceError::ReadOnly) = cause.downcast_ref::<YahooServiceError>() {
+                return Self::new(ErrorCode::ReadOnly, e.to_string());
+            }
+            if cause.downcast_ref::<sqlx::Error>().is_some_and(is_read_only_write) {
+                return ErrorCode::ReadOnly.into();
+            }
+            if let Some(upstream) = cause.downcast_ref::<reqwest::Error>().and_then(UpstreamError::from_request) {
+                return (&upstream).into();
+            }
+        }
+        ErrorCode::InternalError.into()
+    }
+}
+
+impl From<&UpstreamError> for ApiError {
+    fn from(err: &UpstreamError) -> Self {
+        match err {
+            UpstreamError::RateLimited { retry_after } => Self {
+                retry_after: *retry_after,
+                ..ErrorCode::UpstreamRateLimited.into()
+            },
+            UpstreamError::Unavailable(_) => ErrorCode::UpstreamUnavailable.into(),
+        }
+    }
+}
+
+impl From<ErrorCode> for ApiError {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code, code.default_message())
+    }
+}
+
+impl From<ExternalError> for ApiError {
+    fn from(err: ExternalError) -> Self {
+        let code = match err {
+            ExternalError::InvalidRequest => ErrorCode::InvalidRequest,
+            ExternalError::SymbolNotFound => ErrorCode::SymbolNotFound,
+            ExternalError::RateLimitExceeded => ErrorCode::RateLimited,
+            ExternalError::InsufficientData => ErrorCode::InsufficientData,
+            ExternalError::InternalError => ErrorCode::InternalError,
+        };
+        Self::new(code, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response =
+            (self.code.status(), Json(ApiResponse::<()>::failure(self.code, self.message))).into_response();
+        if let Some(retry_after) = self.retry_after {
+            // Whole seconds, rounded up so clients never retry before the window frees
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response.headers_mut().insert(header::RETRY_AFTER, secs.max(1).into());
+        }
+        response
+    }
+}
 
 /// Internal error types - detailed for logging and debugging
 #[derive(Debug, Error)]
-#[allow(dead_code)] // Some variants are reserved for future use
 pub enum InternalError {
     #[error("Database error: {0}")]
     Database(#[from] anyhow::Error),
@@ -24,12 +253,6 @@ pub enum InternalError {
 
     #[error("Insufficient data: {message}")]
     InsufficientData { message: String },
-
-    #[error("Calculation error: {message}")]
-    CalculationError { message: String },
-
-    #[error("Configuration error: {message}")]
-    ConfigurationError { message: String },
 }
 
 /// External error types - safe to return to clients
@@ -51,6 +274,45 @@ pub enum ExternalError {
     InternalError,
 }
 
+/// Longest rejection text carried over into the envelope's `error`
+const MAX_WRAPPED_ERROR_BYTES: usize = 4096;
+
+/// Wrap error responses that were not produced by a handler, such as axum's plain-text
+/// query, path and body rejections or a bare 405, in the `ApiResponse` envelope
+pub async fn envelope_errors_middleware(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    let code = ErrorCode::from_status(status);
+    let (parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, MAX_WRAPPED_ERROR_BYTES)
+        .await
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty() && !status.is_server_error());
+    let message = match text {
+        Some(text) => Cow::Owned(text),
+        None => Cow::Borrowed(code.default_message()),
+    };
+    let mut wrapped = (status, Json(ApiResponse::<()>::failure(code, message))).into_response();
+    // Keep headers such as `Allow` and `Cache-Control`, but not the old body's type and length
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            wrapped.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+    wrapped
+}
+
 impl From<InternalError> for ExternalError {
     fn from(err: InternalError) -> Self {
         match err {
@@ -70,6 +332,9 @@ impl From<YahooServiceError> for InternalError {
             YahooServiceError::RateLimitExceeded => InternalError::RateLimitExceeded {
                 client_id: "unknown".to_string(),
             },
+            YahooServiceError::Upstream(e) => InternalError::YahooApi(e.to_string()),
+            e @ YahooServiceError::QuotaExhausted { .. } => InternalError::YahooApi(e.to_string()),
+            e @ YahooServiceError::ReadOnly => InternalError::Database(e.into()),
         }
     }
 }
@@ -85,6 +350,12 @@ pub enum AppError {
     
     #[error("Validation error: {0}")]
     ValidationError(String),
+
+    #[error("Feature disabled: {0}")]
+    FeatureDisabled(String),
+
+    #[error("Too many attempts")]
+    TooManyAttempts { retry_after: Duration },
     
     #[error("Internal error: {0}")]
     Internal(String),
@@ -92,21 +363,20 @@ pub enum AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
-            AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
+        match self {
+            AppError::Unauthorized => ApiError::from(ErrorCode::Unauthorized),
+            AppError::ValidationError(msg) => ApiError::new(ErrorCode::InvalidRequest, msg),
+            AppError::FeatureDisabled(msg) => ApiError::new(ErrorCode::FeatureDisabled, msg),
+            AppError::TooManyAttempts { retry_after } => ApiError {
+                retry_after: Some(retry_after),
+                ..ApiError::new(ErrorCode::RateLimited, "Too many failed attempts. Please try again later")
+            },
             AppError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
+                ApiError::from(ErrorCode::InternalError)
             }
-        };
-        
-        let body = Json(serde_json::json!({
-            "success": false,
-            "error": error_message,
-        }));
-        
-        (status, body).into_response()
+        }
+        .into_response()
     }
 }
 
@@ -0,0 +1,257 @@
+//! Ingested-data events for downstream consumers.
+//!
+//! With `EVENTS_NATS_URL` set, every stored quote, every batch of newly inserted candles
+//! and every portfolio price refresh is published to a NATS server, so data lakes,
+//! alerting and ML pipelines can follow the data without polling the REST API. Subjects
+//! are `EVENTS_SUBJECT_PREFIX` followed by the event name, e.g. `mango.quote.updated`, and
+//! payloads carry the same `id`/`event`/`created_at`/`data` envelope as webhooks. Delivery
+//! is at most once: events are queued in memory and dropped while the server is unreachable,
+//! and publishing never blocks or fails the caller.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use reqwest::Url;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::EventStreamConfig;
+use crate::yahoo_service::YahooFinanceService;
+
+const DEFAULT_NATS_PORT: u16 = 4222;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause before reconnecting after a failure; events published meanwhile are dropped
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Events waiting to be published; more are dropped while the server is slow or away
+const OUTGOING_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEvent {
+    QuoteUpdated,
+    CandlesInserted,
+    PortfolioUpdated,
+}
+
+impl StreamEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamEvent::QuoteUpdated => "quote.updated",
+            StreamEvent::CandlesInserted => "candle.inserted",
+            StreamEvent::PortfolioUpdated => "portfolio.updated",
+        }
+    }
+}
+
+/// Queue of events for the broker; publishing does nothing until [`spawn`] connects it
+#[derive(Debug, Default)]
+pub struct EventPublisher {
+    outgoing: OnceLock<mpsc::Sender<(StreamEvent, serde_json::Value)>>,
+}
+
+impl EventPublisher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a broker is configured, so callers can skip building payloads otherwise
+    pub fn is_enabled(&self) -> bool {
+        self.outgoing.get().is_some()
+    }
+
+    /// Publish `data` as `event` in the background
+    pub fn publish(&self, event: StreamEvent, data: serde_json::Value) {
+        if let Some(outgoing) = self.outgoing.get() {
+            if outgoing.try_send((event, data)).is_err() {
+                debug!("Event stream backlog full; dropping {}", event.as_str());
+            }
+        }
+    }
+}
+
+/// Connect the service's event publisher to NATS on the current runtime, when configured
+pub fn spawn(service: Arc<YahooFinanceService>, config: EventStreamConfig) {
+    let Some(url) = config.nats_url else {
+        return;
+    };
+    let (sender, receiver) = mpsc::channel(OUTGOING_CAPACITY);
+    if service.events.outgoing.set(sender).is_err() {
+        warn!("Event stream already started");
+        return;
+    }
+    info!("📤 Publishing data events to NATS under {}.*", config.subject_prefix);
+    tokio::spawn(publish_loop(url, config.subject_prefix, receiver));
+}
+
+async fn publish_loop(
+    url: String,
+    subject_prefix: String,
+    mut receiver: mpsc::Receiver<(StreamEvent, serde_json::Value)>,
+) {
+    let mut connection: Option<NatsConnection> = None;
+    let mut retry_at = Instant::now();
+    loop {
+        let Some(connected) = connection.as_mut() else {
+            // Wait for something to publish before (re)connecting
+            let Some((event, data)) = receiver.recv().await else {
+                return;
+            };
+            if Instant::now() < retry_at {
+                continue;
+            }
+            match NatsConnection::connect(&url).await {
+                Ok(mut connected) => {
+                    if let Err(e) = connected.publish(&subject_prefix, event, data).await {
+                        warn!("Failed to publish {} event: {:#}", event.as_str(), e);
+                        retry_at = Instant::now() + RECONNECT_DELAY;
+                        continue;
+                    }
+                    connection = Some(connected);
+                }
+                Err(e) => {
+                    warn!("Cannot reach NATS, dropping events for {}s: {:#}", RECONNECT_DELAY.as_secs(), e);
+                    retry_at = Instant::now() + RECONNECT_DELAY;
+                }
+            }
+            continue;
+        };
+
+        let result = tokio::select! {
+            next = receiver.recv() => match next {
+                Some((event, data)) => connected
+                    .publish(&subject_prefix, event, data)
+                    .await
+                    .with_context(|| format!("Failed to publish {} event", event.as_str())),
+                None => return,
+            },
+            // The server pings idle clients and reports errors on the same connection
+            line = connected.read_line() => match line {
+                Ok(line) => connected.handle_server_line(&line).await,
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = result {
+            warn!("NATS connection lost: {:#}", e);
+            connection = None;
+            retry_at = Instant::now() + RECONNECT_DELAY;
+        }
+    }
+}
+
+/// Minimal NATS client that only publishes
+struct NatsConnection {
+    writer: OwnedWriteHalf,
+    /// Lines from the server, read in their own task so waiting for them can be cancelled
+    lines: mpsc::Receiver<Result<String>>,
+}
+
+impl NatsConnection {
+    async fn connect(url: &str) -> Result<Self> {
+        let url = Url::parse(url).context("Invalid EVENTS_NATS_URL")?;
+        let host = url.host_str().ok_or_else(|| anyhow!("EVENTS_NATS_URL has no host"))?;
+        let port = url.port().unwrap_or(DEFAULT_NATS_PORT);
+        let tcp = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| anyhow!("Timed out connecting to NATS at {}:{}", host, port))?
+            .with_context(|| format!("Failed to connect to NATS at {}:{}", host, port))?;
+        let (read, mut writer) = tcp.into_split();
+        let mut reader = BufReader::new(read);
+
+        let info = tokio::time::timeout(CONNECT_TIMEOUT, read_line(&mut reader))
+            .await
+            .map_err(|_| anyhow!("NATS did not send its greeting"))??;
+        let info: serde_json::Value = info
+            .strip_prefix("INFO ")
+            .and_then(|json| serde_json::from_str(json).ok())
+            .ok_or_else(|| anyhow!("Unexpected NATS greeting: {}", info))?;
+        if info["tls_required"].as_bool() == Some(true) {
+            bail!("NATS server requires TLS, which the event stream does not support");
+        }
+
+        let mut options = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "mango-data-service",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+        });
+        match (url.username(), url.password()) {
+            ("", _) => {}
+            (token, None) => options["auth_token"] = token.into(),
+            (user, Some(password)) => {
+                options["user"] = user.into();
+                options["pass"] = password.into();
+            }
+        }
+        writer.write_all(format!("CONNECT {}\r\nPING\r\n", options).as_bytes()).await?;
+        loop {
+            let line = tokio::time::timeout(CONNECT_TIMEOUT, read_line(&mut reader))
+                .await
+                .map_err(|_| anyhow!("NATS did not answer the connection handshake"))??;
+            match line.as_str() {
+                "PONG" => break,
+                "PING" => writer.write_all(b"PONG\r\n").await?,
+                _ if line.starts_with("-ERR") => bail!("NATS refused the connection: {}", line),
+                _ => {}
+            }
+        }
+
+        let (line_sender, lines) = mpsc::channel(16);
+        tokio::spawn(async move {
+            loop {
+                let line = read_line(&mut reader).await;
+                let failed = line.is_err();
+                if line_sender.send(line).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Ok(Self { writer, lines })
+    }
+
+    async fn publish(&mut self, subject_prefix: &str, event: StreamEvent, data: serde_json::Value) -> Result<()> {
+        let payload = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "event": event.as_str(),
+            "created_at": Utc::now(),
+            "data": data,
+        })
+        .to_string();
+        let subject = format!("{}.{}", subject_prefix, event.as_str());
+        let frame = format!("PUB {} {}\r\n{}\r\n", subject, payload.len(), payload);
+        self.writer.write_all(frame.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Next line from the server
+    async fn read_line(&mut self) -> Result<String> {
+        self.lines
+            .recv()
+            .await
+            .unwrap_or_else(|| Err(anyhow!("NATS closed the connection")))
+    }
+
+    /// Answer server pings and surface protocol errors
+    async fn handle_server_line(&mut self, line: &str) -> Result<()> {
+        match line {
+            "PING" => {
+                self.writer.write_all(b"PONG\r\n").await?;
+                Ok(())
+            }
+            _ if line.starts_with("-ERR") => bail!("NATS error: {}", line),
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn read_line(reader: &mut BufReader<OwnedReadHalf>) -> Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        bail!("NATS closed the connection");
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,205 @@
+//! Engineered features of a symbol's daily candles, for training models outside the service.
+//!
+//! Every row is one daily candle, oldest first, and every feature uses only that candle and
+//! earlier ones except `forward_return_1d`, the next day's return, which is the usual label.
+//! Returns are percentages. Rolling statistics span the request's `window`; the technical
+//! indicators come from [`crate::indicators`] with the periods the indicators endpoint uses,
+//! and each of the owner's custom indicators requested becomes a `custom_<name>` column.
+//! Features are missing until their window is full.
+
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use std::fmt::Write;
+
+use crate::indicator_expr::Expression;
+use crate::indicators::{calculate_bollinger_bands_safe, calculate_ema_safe, calculate_rsi_safe};
+use crate::models::{FeatureMatrix, FeatureRow, HistoricalPrice};
+use crate::parquet::{self, Column, ColumnData};
+
+/// Past daily returns given their own columns
+const RETURN_LAGS: usize = 5;
+const RSI_PERIOD: usize = 14;
+const MACD_SLOW_PERIOD: usize = 26;
+const BOLLINGER_PERIOD: usize = 20;
+
+/// Percent change from `from` to `to`
+fn change(from: f64, to: f64) -> Option<f64> {
+    (from > 0.0).then(|| (to / from - 1.0) * 100.0)
+}
+
+/// Six decimal places, plenty for features and free of float noise in CSV
+fn round(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+fn mean_stdev(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+    (mean, variance.sqrt())
+}
+
+/// Place an indicator series that ends with the latest close into one slot per close
+fn align(series: Vec<f64>, len: usize) -> Vec<Option<f64>> {
+    let mut aligned = vec![None; len.saturating_sub(series.len())];
+    aligned.extend(series.into_iter().map(Some));
+    aligned.truncate(len);
+    aligned
+}
+
+/// Feature matrix of `candles` (daily, oldest first) with rolling statistics over `window`
+/// candles and the named custom indicator expressions
+pub fn compute(
+    symbol: &str,
+    candles: &[HistoricalPrice],
+    window: usize,
+    custom: &[(String, Expression)],
+) -> FeatureMatrix {
+    let n = candles.len();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close.to_f64().unwrap_or_default()).collect();
+    let returns: Vec<Option<f64>> = (0..n)
+        .map(|i| if i == 0 { None } else { change(closes[i - 1], closes[i]) })
+        .collect();
+    let window_of = |i: usize| (i + 1).checked_sub(window).map(|start| start..=i);
+
+    let mut columns: Vec<(String, Vec<Option<f64>>)> = Vec::new();
+    columns.push(("close".to_string(), closes.iter().map(|c| Some(*c)).collect()));
+    columns.push(("return_1d".to_string(), returns.clone()));
+    for lag in 1..=RETURN_LAGS {
+        let lagged = (0..n).map(|i| i.checked_sub(lag).and_then(|j| returns[j])).collect();
+        columns.push((format!("return_lag_{}", lag), lagged));
+    }
+    for days in [5, 20] {
+        let changes = (0..n)
+            .map(|i| i.checked_sub(days).and_then(|j| change(closes[j], closes[i])))
+            .collect();
+        columns.push((format!("return_{}d", days), changes));
+    }
+
+    let (mut mean_returns, mut volatility) = (Vec::with_capacity(n), Vec::with_capacity(n));
+    let (mut sma_ratio, mut zscore, mut volume_ratio) = (Vec::with_capacity(n), Vec::with_capacity(n), Vec::with_capacity(n));
+    for i in 0..n {
+        // Returns need the close before the window too
+        let window_returns: Option<Vec<f64>> =
+            window_of(i).filter(|range| *range.start() > 0).and_then(|range| range.map(|j| returns[j]).collect());
+        let (mean, stdev) = window_returns.as_deref().map(mean_stdev).unzip();
+        mean_returns.push(mean);
+        volatility.push(stdev);
+
+        let stats = window_of(i).map(|range| mean_stdev(&closes[range]));
+        sma_ratio.push(stats.and_then(|(mean, _)| change(mean, closes[i])));
+        zscore.push(stats.and_then(|(mean, stdev)| (stdev > 0.0).then(|| (closes[i] - mean) / stdev)));
+
+        // Today's volume against the window before it
+        let average_volume = i.checked_sub(window).map(|start| {
+            candles[start..i].iter().map(|c| c.volume as f64).sum::<f64>() / window as f64
+        });
+        volume_ratio.push(average_volume.filter(|v| *v > 0.0).map(|v| candles[i].volume as f64 / v));
+    }
+    columns.push((format!("mean_return_{}", window), mean_returns));
+    columns.push((format!("volatility_{}", window), volatility));
+    columns.push((format!("sma_ratio_{}", window), sma_ratio));
+    columns.push((format!("zscore_{}", window), zscore));
+    columns.push((format!("volume_ratio_{}", window), volume_ratio));
+
+    let range = candles
+        .iter()
+        .map(|c| match (c.high.to_f64(), c.low.to_f64(), c.close.to_f64()) {
+            (Some(high), Some(low), Some(close)) if close > 0.0 => Some((high - low) / close * 100.0),
+            _ => None,
+        })
+        .collect();
+    columns.push(("range_percent".to_string(), range));
+
+    columns.push(("rsi_14".to_string(), align(calculate_rsi_safe(&closes, RSI_PERIOD), n)));
+    // EMAs are seeded with the first close, so MACD is left out until the slow one settles
+    let (ema_fast, ema_slow) = (calculate_ema_safe(&closes, 12), calculate_ema_safe(&closes, MACD_SLOW_PERIOD));
+    let macd = (0..n)
+        .map(|i| match (ema_fast.get(i), ema_slow.get(i)) {
+            (Some(fast), Some(slow)) if ema_fast.len() == n && i + 1 >= MACD_SLOW_PERIOD => Some(fast - slow),
+            _ => None,
+        })
+        .collect();
+    columns.push(("macd".to_string(), macd));
+    let (upper, _, lower) = calculate_bollinger_bands_safe(&closes, BOLLINGER_PERIOD, 2.0);
+    let percent_b = align(upper, n)
+        .into_iter()
+        .zip(align(lower, n))
+        .zip(&closes)
+        .map(|((upper, lower), close)| match (upper, lower) {
+            (Some(upper), Some(lower)) if upper > lower => Some((close - lower) / (upper - lower)),
+            _ => None,
+        })
+        .collect();
+    columns.push(("bollinger_percent_b".to_string(), percent_b));
+
+    for (name, expression) in custom {
+        columns.push((format!("custom_{}", name), expression.evaluate(candles)));
+    }
+
+    let forward = (0..n).map(|i| returns.get(i + 1).copied().flatten()).collect();
+    columns.push(("forward_return_1d".to_string(), forward));
+
+    let rows = (0..n)
+        .map(|i| FeatureRow {
+            date: candles[i].timestamp.date_naive(),
+            volume: candles[i].volume,
+            values: columns.iter().map(|(_, values)| values[i].filter(|v| v.is_finite()).map(round)).collect(),
+        })
+        .collect();
+    FeatureMatrix {
+        symbol: symbol.to_string(),
+        window,
+        columns: columns.into_iter().map(|(name, _)| name).collect(),
+        rows,
+    }
+}
+
+/// Keep the rows dated within `from..=to`
+pub fn retain_dates(matrix: &mut FeatureMatrix, from: Option<NaiveDate>, to: Option<NaiveDate>) {
+    matrix
+        .rows
+        .retain(|row| from.is_none_or(|from| row.date >= from) && to.is_none_or(|to| row.date <= to));
+}
+
+/// `date,volume,<features...>` with empty cells for missing values
+pub fn to_csv(matrix: &FeatureMatrix) -> String {
+    let mut csv = String::from("date,volume");
+    for column in &matrix.columns {
+        csv.push(',');
+        csv.push_str(column);
+    }
+    csv.push('\n');
+    for row in &matrix.rows {
+        let _ = write!(csv, "{},{}", row.date, row.volume);
+        for value in &row.values {
+            csv.push(',');
+            if let Some(value) = value {
+                let _ = write!(csv, "{}", value);
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// The matrix as a Parquet file: `date` as DATE, `volume` as INT64 and nullable DOUBLE features
+pub fn to_parquet(matrix: &FeatureMatrix) -> Vec<u8> {
+    let mut columns = vec![
+        Column {
+            name: "date".to_string(),
+            data: ColumnData::Date(matrix.rows.iter().map(|row| row.date).collect()),
+        },
+        Column {
+            name: "volume".to_string(),
+            data: ColumnData::Int64(matrix.rows.iter().map(|row| row.volume).collect()),
+        },
+    ];
+    for (i, name) in matrix.columns.iter().enumerate() {
+        columns.push(Column {
+            name: name.clone(),
+            data: ColumnData::Double(matrix.rows.iter().map(|row| row.values[i]).collect()),
+        });
+    }
+    parquet::write(&columns)
+}
@@ -0,0 +1,146 @@
+//! `?fields=` selection of the columns returned by data endpoints.
+//!
+//! Charting clients usually need a few columns of each row, e.g. `timestamp,close,volume`
+//! of a candle, not its ids and bookkeeping timestamps. Any JSON GET under `/api` accepts
+//! `fields`: the rows of the response (objects in arrays inside `data`, or `data` itself
+//! when it has no such arrays) keep only the named keys, while envelope values such as
+//! `symbol` and `count` are left alone.
+
+use axum::{
+    body::Body,
+    extract::{Query, Request},
+    http::{header, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use tracing::error;
+
+use crate::config::MAX_SELECTED_FIELDS;
+use crate::errors::AppError;
+
+#[derive(Debug, Deserialize)]
+struct FieldsParams {
+    fields: Option<String>,
+}
+
+/// Field names from a `fields` parameter, or a message explaining what is wrong with it
+fn parse_fields(fields: &str) -> Result<Vec<String>, String> {
+    let names: Vec<String> = fields
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.is_empty() {
+        return Err("fields must name at least one field".to_string());
+    }
+    if names.len() > MAX_SELECTED_FIELDS {
+        return Err(format!("fields can name at most {} fields", MAX_SELECTED_FIELDS));
+    }
+    if let Some(name) = names
+        .iter()
+        .find(|name| !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+    {
+        return Err(format!("Invalid field name: {}", name));
+    }
+    Ok(names)
+}
+
+/// Keep only `fields` in the rows of an `ApiResponse` payload. Returns the requested fields
+/// that no row has, and every key the rows had, so typos can be reported.
+fn select(data: &mut Value, fields: &[String]) -> (Vec<String>, BTreeSet<String>) {
+    let mut rows: Vec<&mut Map<String, Value>> = Vec::new();
+    match data {
+        Value::Array(items) => rows.extend(items.iter_mut().filter_map(Value::as_object_mut)),
+        Value::Object(object) => {
+            let has_rows = object.values().any(is_rows);
+            if has_rows {
+                for value in object.values_mut().filter(|value| is_rows(value)) {
+                    if let Value::Array(items) = value {
+                        rows.extend(items.iter_mut().filter_map(Value::as_object_mut));
+                    }
+                }
+            } else {
+                rows.push(object);
+            }
+        }
+        _ => {}
+    }
+
+    let mut available = BTreeSet::new();
+    for row in &mut rows {
+        available.extend(row.keys().cloned());
+        row.retain(|key, _| fields.contains(key));
+    }
+    let unknown = if rows.is_empty() {
+        Vec::new()
+    } else {
+        fields.iter().filter(|field| !available.contains(*field)).cloned().collect()
+    };
+    (unknown, available)
+}
+
+/// An array holding at least one object
+fn is_rows(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if items.iter().any(Value::is_object))
+}
+
+/// Apply `?fields=` to successful JSON responses of GET requests
+pub async fn select_fields_middleware(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let fields = match Query::<FieldsParams>::try_from_uri(request.uri()) {
+        Ok(Query(FieldsParams { fields: Some(fields) })) => fields,
+        _ => return next.run(request).await,
+    };
+    let fields = match parse_fields(&fields) {
+        Ok(fields) => fields,
+        Err(message) => return AppError::ValidationError(message).into_response(),
+    };
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Error buffering response for field selection: {}", e);
+            return AppError::Internal(e.to_string()).into_response();
+        }
+    };
+    let mut payload: Value = match serde_json::from_slice(&bytes) {
+        Ok(payload) => payload,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    // Only successful ApiResponse envelopes have rows to select from
+    let is_envelope = payload.get("success") == Some(&Value::Bool(true));
+    match payload.get_mut("data") {
+        Some(data) if is_envelope => {
+            let (unknown, available) = select(data, &fields);
+            if !unknown.is_empty() {
+                return AppError::ValidationError(format!(
+                    "Unknown fields: {}. Available: {}",
+                    unknown.join(", "),
+                    available.into_iter().collect::<Vec<_>>().join(", ")
+                ))
+                .into_response();
+            }
+        }
+        _ => return Response::from_parts(parts, Body::from(bytes)),
+    }
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(payload.to_string()))
+}
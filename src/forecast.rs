@@ -0,0 +1,208 @@
+//! Statistical baseline forecasts of a symbol's daily closes.
+//!
+//! Both models work on log closes, so intervals are multiplicative and prices stay positive:
+//! damped-trend exponential smoothing (ETS(A,Ad,N), Holt's method with a damped trend) picks
+//! its smoothing and damping parameters by grid search on one-step squared errors, and
+//! ARIMA(1,1,0) fits an AR(1) with drift to daily log returns by least squares. Intervals come
+//! from each model's closed-form forecast variance under normal errors. These are baselines to
+//! judge other forecasts against, not trading signals.
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::str::FromStr;
+
+use crate::market_calendar;
+
+/// Two-sided normal quantiles of the 80% and 95% intervals
+const Z_80: f64 = 1.281_551_565_5;
+const Z_95: f64 = 1.959_963_985;
+
+const ALPHAS: [f64; 20] = [
+    0.05, 0.1, 0.15, 0.2, 0.25, 0.3, 0.35, 0.4, 0.45, 0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95, 1.0,
+];
+const BETAS: [f64; 6] = [0.0, 0.01, 0.02, 0.05, 0.1, 0.2];
+const PHIS: [f64; 6] = [0.8, 0.85, 0.9, 0.95, 0.98, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForecastMethod {
+    /// Exponential smoothing with a damped additive trend
+    Ets,
+    /// AR(1) on daily log returns with a constant
+    Arima,
+}
+
+impl ForecastMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ForecastMethod::Ets => "ets",
+            ForecastMethod::Arima => "arima",
+        }
+    }
+}
+
+impl FromStr for ForecastMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ets" | "holt" => Ok(ForecastMethod::Ets),
+            "arima" => Ok(ForecastMethod::Arima),
+            other => Err(format!("Unsupported forecast method '{}'. Use 'ets' or 'arima'", other)),
+        }
+    }
+}
+
+/// A fitted model's forecast in log space
+#[derive(Debug, Clone)]
+pub struct Fit {
+    /// Fitted parameters by name
+    pub parameters: Vec<(&'static str, f64)>,
+    /// Standard deviation of one-step errors
+    pub sigma: f64,
+    /// Mean of the log close, one per step ahead
+    pub means: Vec<f64>,
+    /// Standard deviation of the log close forecast, one per step ahead
+    pub deviations: Vec<f64>,
+}
+
+/// Price forecast and interval bounds of one step ahead
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub median: f64,
+    pub lower_80: f64,
+    pub upper_80: f64,
+    pub lower_95: f64,
+    pub upper_95: f64,
+}
+
+impl Fit {
+    /// Median price and interval bounds per step ahead
+    pub fn steps(&self) -> Vec<Step> {
+        self.means
+            .iter()
+            .zip(&self.deviations)
+            .map(|(mean, sd)| Step {
+                median: mean.exp(),
+                lower_80: (mean - Z_80 * sd).exp(),
+                upper_80: (mean + Z_80 * sd).exp(),
+                lower_95: (mean - Z_95 * sd).exp(),
+                upper_95: (mean + Z_95 * sd).exp(),
+            })
+            .collect()
+    }
+}
+
+/// Fit `method` to positive `closes` (oldest first) and forecast `horizon` steps. `None` when
+/// there are too few closes to fit.
+pub fn fit(method: ForecastMethod, closes: &[f64], horizon: usize) -> Option<Fit> {
+    let logs: Vec<f64> = closes.iter().filter(|c| **c > 0.0).map(|c| c.ln()).collect();
+    match method {
+        ForecastMethod::Ets => fit_ets(&logs, horizon),
+        ForecastMethod::Arima => fit_arima(&logs, horizon),
+    }
+}
+
+/// One-step squared errors of damped Holt smoothing, with the final level and trend
+fn ets_pass(y: &[f64], alpha: f64, beta: f64, phi: f64) -> (f64, f64, f64) {
+    let (mut level, mut trend) = (y[0], y[1] - y[0]);
+    let mut sse = 0.0;
+    for &value in &y[1..] {
+        let error = value - (level + phi * trend);
+        sse += error * error;
+        level += phi * trend + alpha * error;
+        trend = phi * trend + beta * error;
+    }
+    (sse, level, trend)
+}
+
+fn fit_ets(y: &[f64], horizon: usize) -> Option<Fit> {
+    if y.len() < 3 {
+        return None;
+    }
+    let mut best: Option<(f64, f64, f64, f64, f64, f64)> = None;
+    for &alpha in &ALPHAS {
+        for &beta in BETAS.iter().filter(|beta| **beta <= alpha) {
+            for &phi in &PHIS {
+                let (sse, level, trend) = ets_pass(y, alpha, beta, phi);
+                if best.is_none_or(|(best_sse, ..)| sse < best_sse) {
+                    best = Some((sse, alpha, beta, phi, level, trend));
+                }
+            }
+        }
+    }
+    let (sse, alpha, beta, phi, level, trend) = best?;
+    // Level, trend and the three smoothing parameters
+    let dof = (y.len() - 1).saturating_sub(5).max(1);
+    let sigma = (sse / dof as f64).sqrt();
+
+    let (mut means, mut deviations) = (Vec::with_capacity(horizon), Vec::with_capacity(horizon));
+    let (mut damped, mut variance_factor): (f64, f64) = (0.0, 1.0);
+    let mut power = 1.0;
+    for _ in 0..horizon {
+        power *= phi;
+        damped += power;
+        means.push(level + damped * trend);
+        deviations.push(sigma * variance_factor.sqrt());
+        // Error weight of this step on all later ones
+        variance_factor += (alpha + beta * damped).powi(2);
+    }
+
+    Some(Fit {
+        parameters: vec![("alpha", alpha), ("beta", beta), ("phi", phi)],
+        sigma,
+        means,
+        deviations,
+    })
+}
+
+fn fit_arima(y: &[f64], horizon: usize) -> Option<Fit> {
+    let returns: Vec<f64> = y.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if returns.len() < 3 {
+        return None;
+    }
+    // Least squares of r[t] on r[t-1]
+    let (x, z) = (&returns[..returns.len() - 1], &returns[1..]);
+    let n = x.len() as f64;
+    let (mean_x, mean_z) = (x.iter().sum::<f64>() / n, z.iter().sum::<f64>() / n);
+    let covariance: f64 = x.iter().zip(z).map(|(a, b)| (a - mean_x) * (b - mean_z)).sum();
+    let variance: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+    let ar = if variance > 0.0 { (covariance / variance).clamp(-0.99, 0.99) } else { 0.0 };
+    let constant = mean_z - ar * mean_x;
+    let sse: f64 = x.iter().zip(z).map(|(a, b)| (b - constant - ar * a).powi(2)).sum();
+    let sigma = (sse / (n - 2.0).max(1.0)).sqrt();
+
+    let (mut means, mut deviations) = (Vec::with_capacity(horizon), Vec::with_capacity(horizon));
+    let (mut log_close, mut last_return) = (*y.last()?, *returns.last()?);
+    let (mut cumulative_weight, mut power, mut variance_factor): (f64, f64, f64) = (0.0, 1.0, 0.0);
+    for _ in 0..horizon {
+        last_return = constant + ar * last_return;
+        log_close += last_return;
+        // A shock's effect on the close j steps later is 1 + ar + ... + ar^j
+        cumulative_weight += power;
+        power *= ar;
+        variance_factor += cumulative_weight * cumulative_weight;
+        means.push(log_close);
+        deviations.push(sigma * variance_factor.sqrt());
+    }
+
+    Some(Fit {
+        parameters: vec![("constant", constant), ("ar1", ar)],
+        sigma,
+        means,
+        deviations,
+    })
+}
+
+/// The next `count` trading days of `symbol` after `last`: every day for crypto, otherwise
+/// weekdays that are not holidays on its exchange calendar
+pub fn future_dates(symbol: &str, last: NaiveDate, count: usize) -> Vec<NaiveDate> {
+    let calendar = market_calendar::calendar_for_symbol(symbol);
+    let around_the_clock = symbol.contains('-');
+    last.iter_days()
+        .skip(1)
+        .filter(|date| {
+            around_the_clock
+                || (!matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && calendar.holiday(*date).is_none())
+        })
+        .take(count)
+        .collect()
+}
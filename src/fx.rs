@@ -0,0 +1,90 @@
+//! Currency codes and foreign-exchange pairs.
+//!
+//! Rates come from the market-data provider's currency pair quotes (`GBPUSD=X` on Yahoo
+//! Finance). Some exchanges quote prices in a minor unit, such as London in pence (`GBp`),
+//! so prices are brought to the major unit before a rate is applied.
+
+use rust_decimal::Decimal;
+
+/// Approximate USD value of one unit of each currency the mock provider knows, used to
+/// derive synthetic pair rates
+const MOCK_USD_VALUES: [(&str, f64); 15] = [
+    ("USD", 1.0),
+    ("EUR", 1.08),
+    ("GBP", 1.27),
+    ("CHF", 1.12),
+    ("JPY", 0.0067),
+    ("CAD", 0.73),
+    ("AUD", 0.66),
+    ("HKD", 0.128),
+    ("SEK", 0.095),
+    ("DKK", 0.145),
+    ("NOK", 0.093),
+    ("CNY", 0.138),
+    ("INR", 0.012),
+    ("ZAR", 0.054),
+    ("ILS", 0.27),
+];
+
+/// Three-letter ISO 4217 code, upper-cased
+pub fn normalize_currency_code(code: &str) -> Result<String, String> {
+    let code = code.trim().to_ascii_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid currency code '{}'", code));
+    }
+    Ok(code)
+}
+
+/// Major currency and the number of quoted units per major unit, e.g. `GBp` -> (`GBP`, 100)
+pub fn major_unit(code: &str) -> (String, Decimal) {
+    match code {
+        "GBp" | "GBX" => ("GBP".to_string(), Decimal::ONE_HUNDRED),
+        "ZAc" | "ZAC" => ("ZAR".to_string(), Decimal::ONE_HUNDRED),
+        "ILA" => ("ILS".to_string(), Decimal::ONE_HUNDRED),
+        other => (other.to_ascii_uppercase(), Decimal::ONE),
+    }
+}
+
+/// Provider ticker of the pair quoting `to` per unit of `from`
+pub fn pair_symbol(from: &str, to: &str) -> String {
+    format!("{}{}=X", from, to)
+}
+
+fn pair_currencies(symbol: &str) -> Option<(&str, &str)> {
+    let pair = symbol.strip_suffix("=X")?;
+    (pair.len() == 6 && pair.is_ascii()).then(|| pair.split_at(3))
+}
+
+/// Trading currency implied by an exchange suffix, for listings whose currency the provider
+/// has not reported yet
+pub fn currency_for_symbol(symbol: &str) -> &'static str {
+    match symbol.rsplit_once('.').map(|(_, suffix)| suffix) {
+        Some("L") => "GBp",
+        Some("DE" | "F" | "PA" | "AS" | "BR" | "MI" | "MC" | "VI" | "LS" | "HE" | "IR") => "EUR",
+        Some("SW") => "CHF",
+        Some("ST") => "SEK",
+        Some("CO") => "DKK",
+        Some("OL") => "NOK",
+        Some("TO" | "V") => "CAD",
+        Some("AX") => "AUD",
+        Some("HK") => "HKD",
+        Some("T") => "JPY",
+        Some("SS" | "SZ") => "CNY",
+        Some("NS" | "BO") => "INR",
+        Some("JO") => "ZAc",
+        Some("TA") => "ILA",
+        _ => "USD",
+    }
+}
+
+/// Synthetic rate for a pair ticker in the mock market, `None` for other tickers
+pub fn mock_pair_rate(symbol: &str) -> Option<f64> {
+    let (from, to) = pair_currencies(symbol)?;
+    let usd_value = |code: &str| {
+        MOCK_USD_VALUES
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(code))
+            .map(|(_, v)| *v)
+    };
+    Some(usd_value(from)? / usd_value(to)?)
+}
@@ -0,0 +1,138 @@
+//! `Cache-Control` headers for market-data responses.
+//!
+//! `web_ui::cache_headers_middleware` only covers HTML and static assets. Data routes get
+//! a `max-age` equal to how long the service itself keeps the same data in memory:
+//! `CACHE_TTL_QUOTES` for quotes and other price snapshots, `CACHE_TTL_HISTORICAL` for
+//! candles (shorter for intraday intervals) and `CACHE_TTL_PROFILES` for slow-moving data
+//! such as profiles and holders. Only
+//! successful `GET`s are cacheable; errors, `force_refresh` requests, portfolio and admin
+//! routes are sent with `no-store`. When reads need a login (`PUBLIC_READ_API=false`) the
+//! directive is `private` so shared caches never keep a response.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Query, Request, State},
+    http::{header, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::CacheConfig;
+use crate::errors::{ApiError, ErrorCode};
+use crate::handlers::AppState;
+use crate::udf;
+
+/// Routes whose responses depend on the caller or change with every write
+const NO_STORE_PREFIXES: [&str; 10] = [
+    "/api/portfolio",
+    "/api/admin/",
+    "/api/jobs",
+    "/api/dashboards",
+    "/api/alerts",
+    "/api/indicators/custom",
+    "/api/strategies",
+    "/api/journal",
+    "/api/rankings",
+    "/api/keys",
+];
+
+/// How long a successful response of `route` may be reused, if at all
+pub fn max_age(route: &str, params: &HashMap<String, String>, cache: &CacheConfig) -> Option<Duration> {
+    let interval = || params.get("interval").map(String::as_str).unwrap_or("1d");
+    match route {
+        "/api/symbols/:symbol/quote"
+        | "/api/symbols/:symbol/quotes"
+        | "/api/symbols/:symbol/comprehensive"
+        | "/api/symbols/:symbol/extended"
+        | "/api/symbols/:symbol/overview"
+        | "/api/symbols/:symbol/listings" => Some(cache.ttl_quotes),
+        "/api/symbols/:symbol/historical" | "/api/symbols/:symbol/klines" | "/api/compare" => {
+            Some(cache.historical_ttl(interval()))
+        }
+        "/api/symbols/:symbol/indicators"
+        | "/api/symbols/:symbol/analysis"
+        | "/api/symbols/:symbol/stats"
+        | "/api/symbols/:symbol/peers"
+        | "/api/symbols/:symbol/forecast" => Some(cache.historical_ttl("1d")),
+        "/udf/history" => params
+            .get("resolution")
+            .and_then(|resolution| udf::interval_for_resolution(resolution))
+            .map(|interval| cache.historical_ttl(interval)),
+        "/api/symbols/:symbol/profile"
+        | "/api/symbols/:symbol/holders"
+        | "/api/symbols/:symbol/etf"
+        | "/api/sectors"
+        | "/api/macro/:series" => Some(cache.ttl_profiles),
+        "/api/sectors/:sector/performance" | "/api/market/movers" | "/api/market/status" => {
+            Some(cache.ttl_quotes)
+        }
+        _ => None,
+    }
+}
+
+/// Whether a buffered body is a successful envelope or UDF bar set, judged from its start
+/// since both serialize their status field first
+fn is_success_body(body: &[u8]) -> bool {
+    body.starts_with(br#"{"success":true"#) || body.starts_with(br#"{"s":"ok""#)
+}
+
+/// Add `Cache-Control` to data responses; headers set by a handler are kept
+pub async fn api_cache_headers_middleware(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cacheable_method = matches!(*request.method(), Method::GET | Method::HEAD);
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let params = Query::<HashMap<String, String>>::try_from_uri(request.uri())
+        .map(|Query(params)| params)
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+    if response.headers().contains_key(header::CACHE_CONTROL) {
+        return response;
+    }
+
+    let force_refresh = params.get("force_refresh").is_some_and(|value| value == "true");
+    let succeeded = response.status().is_success();
+    let max_age = if cacheable_method && succeeded && !force_refresh {
+        max_age(&route, &params, &app_state.config.cache)
+    } else {
+        None
+    };
+    let Some(max_age) = max_age else {
+        let mut response = response;
+        if !succeeded || force_refresh || NO_STORE_PREFIXES.iter().any(|prefix| route.starts_with(prefix)) {
+            response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        }
+        return response;
+    };
+
+    // UDF answers failures with a 200 and `"s":"error"`, which must not be reused
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for cache headers: {}", e);
+            return ApiError::from(ErrorCode::InternalError).into_response();
+        }
+    };
+    let auth = &app_state.config.auth;
+    let directive = if !is_success_body(&bytes) {
+        "no-store".to_string()
+    } else if auth.is_enabled() && !auth.public_read_api {
+        format!("private, max-age={}", max_age.as_secs())
+    } else {
+        format!("public, max-age={}", max_age.as_secs())
+    };
+    if let Ok(value) = HeaderValue::from_str(&directive) {
+        parts.headers.insert(header::CACHE_CONTROL, value);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
@@ -0,0 +1,282 @@
+//! ISIN and CUSIP security identifiers.
+//!
+//! Broker exports usually name securities by ISIN rather than ticker. Identifiers are checked
+//! against their check digit before any lookup; a CUSIP is turned into a US ISIN so both can
+//! go through the provider's symbol search. OpenFIGI's mapping API is the fallback for
+//! identifiers the search does not know, and its exchange codes are translated to the
+//! ticker suffixes the market data provider expects.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::provider::SymbolMatch;
+
+const OPENFIGI_MAPPING_URL: &str = "https://api.openfigi.com/v3/mapping";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifierKind {
+    Isin,
+    Cusip,
+}
+
+impl IdentifierKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentifierKind::Isin => "isin",
+            IdentifierKind::Cusip => "cusip",
+        }
+    }
+
+    fn figi_id_type(&self) -> &'static str {
+        match self {
+            IdentifierKind::Isin => "ID_ISIN",
+            IdentifierKind::Cusip => "ID_CUSIP",
+        }
+    }
+}
+
+/// Upper-cased identifier with a valid check digit
+pub fn normalize_identifier(kind: IdentifierKind, value: &str) -> Result<String, String> {
+    let value = value.trim().to_ascii_uppercase();
+    let valid = match kind {
+        IdentifierKind::Isin => is_valid_isin(&value),
+        IdentifierKind::Cusip => is_valid_cusip(&value),
+    };
+    if !valid {
+        return Err(format!("Invalid {} '{}'", kind.as_str().to_uppercase(), value));
+    }
+    Ok(value)
+}
+
+/// Two-letter country code, nine alphanumerics and a Luhn check digit over the letters
+/// expanded to numbers (A = 10 ... Z = 35)
+pub fn is_valid_isin(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() != 12
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..11].iter().all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+        || !bytes[11].is_ascii_digit()
+    {
+        return false;
+    }
+
+    let digits: Vec<u32> = value
+        .chars()
+        .flat_map(|c| {
+            let v = c.to_digit(36).unwrap_or(0);
+            if v >= 10 {
+                vec![v / 10, v % 10]
+            } else {
+                vec![v]
+            }
+        })
+        .collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Eight alphanumerics (or `*`, `@`, `#`) and a check digit
+pub fn is_valid_cusip(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() != 9 || !bytes[8].is_ascii_digit() {
+        return false;
+    }
+    match cusip_check_digit(&value[..8]) {
+        Some(check) => u32::from(bytes[8] - b'0') == check,
+        None => false,
+    }
+}
+
+fn cusip_check_digit(base: &str) -> Option<u32> {
+    let mut sum = 0;
+    for (i, c) in base.chars().enumerate() {
+        let mut v = match c {
+            '0'..='9' | 'A'..='Z' => c.to_digit(36)?,
+            '*' => 36,
+            '@' => 37,
+            '#' => 38,
+            _ => return None,
+        };
+        if i % 2 == 1 {
+            v *= 2;
+        }
+        sum += v / 10 + v % 10;
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+/// US ISIN wrapping a CUSIP, so that both can be found by an ISIN-aware symbol search
+pub fn cusip_to_isin(cusip: &str) -> Option<String> {
+    (0..10)
+        .map(|check| format!("US{}{}", cusip, check))
+        .find(|isin| is_valid_isin(isin))
+}
+
+/// Provider ticker suffix for an OpenFIGI exchange code; `Some("")` for US listings
+fn ticker_suffix(exch_code: &str) -> Option<&'static str> {
+    Some(match exch_code {
+        "US" | "UN" | "UW" | "UQ" | "UA" | "UR" | "UP" => "",
+        "GY" | "GR" | "GF" => ".DE",
+        "LN" => ".L",
+        "FP" => ".PA",
+        "NA" => ".AS",
+        "BB" => ".BR",
+        "IM" => ".MI",
+        "SM" => ".MC",
+        "SW" | "SE" => ".SW",
+        "SS" => ".ST",
+        "DC" => ".CO",
+        "NO" => ".OL",
+        "FH" => ".HE",
+        "ID" => ".IR",
+        "AV" => ".VI",
+        "PL" => ".LS",
+        "CN" | "CT" => ".TO",
+        "AU" | "AT" => ".AX",
+        "HK" => ".HK",
+        "JP" | "JT" => ".T",
+        _ => return None,
+    })
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("mango-data-service/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("reqwest client with static configuration")
+    })
+}
+
+#[derive(Deserialize)]
+struct FigiResult {
+    #[serde(default)]
+    data: Vec<FigiInstrument>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FigiInstrument {
+    ticker: Option<String>,
+    name: Option<String>,
+    exch_code: Option<String>,
+}
+
+/// Listing of an identifier from OpenFIGI, preferring US listings and then the first one on
+/// an exchange with a known ticker suffix
+pub async fn openfigi_lookup(
+    api_key: Option<&str>,
+    kind: IdentifierKind,
+    value: &str,
+) -> Result<Option<SymbolMatch>> {
+    let mut request = http_client()
+        .post(OPENFIGI_MAPPING_URL)
+        .json(&serde_json::json!([{ "idType": kind.figi_id_type(), "idValue": value }]));
+    if let Some(key) = api_key {
+        request = request.header("X-OPENFIGI-APIKEY", key);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("OpenFIGI returned HTTP {}", status);
+    }
+    let mut results: Vec<FigiResult> = response.json().await?;
+    let Some(result) = results.pop() else {
+        return Ok(None);
+    };
+    if let Some(error) = result.error {
+        bail!("OpenFIGI rejected {}: {}", value, error);
+    }
+
+    let mut listings: Vec<(SymbolMatch, bool)> = result
+        .data
+        .into_iter()
+        .filter_map(|instrument| {
+            let exch_code = instrument.exch_code?;
+            let suffix = ticker_suffix(&exch_code)?;
+            // OpenFIGI writes share classes as BRK/B where the provider expects BRK-B
+            let ticker = instrument.ticker?.replace(['/', ' '], "-");
+            Some((
+                SymbolMatch {
+                    symbol: format!("{}{}", ticker, suffix),
+                    name: instrument.name,
+                    exchange: exch_code,
+                },
+                suffix.is_empty(),
+            ))
+        })
+        .collect();
+    // Stable sort keeps OpenFIGI's order within US and non-US listings
+    listings.sort_by_key(|(_, us)| !us);
+
+    Ok(listings.into_iter().next().map(|(listing, _)| listing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isin_check_digits() {
+        for isin in ["US0378331005", "DE000BAY0017", "GB0002634946", "AU0000XVGZA3", "US38259P5089"] {
+            assert!(is_valid_isin(isin), "{isin} should be valid");
+        }
+        for isin in [
+            "US0378331006",  // wrong check digit
+            "US0378331050",  // transposed digits
+            "us0378331005",  // lower-case country
+            "US037833100",   // too short
+            "US037833100X",  // letter as check digit
+            "US03783310-5",  // punctuation
+            "",
+        ] {
+            assert!(!is_valid_isin(isin), "{isin} should be invalid");
+        }
+    }
+
+    #[test]
+    fn cusip_check_digits() {
+        for cusip in ["037833100", "38259P508", "594918104"] {
+            assert!(is_valid_cusip(cusip), "{cusip} should be valid");
+        }
+        for cusip in ["037833101", "38259P509", "03783310", "0378331000", "03783310A", "0378-3100"] {
+            assert!(!is_valid_cusip(cusip), "{cusip} should be invalid");
+        }
+    }
+
+    #[test]
+    fn cusip_wraps_into_a_us_isin() {
+        assert_eq!(cusip_to_isin("037833100").as_deref(), Some("US0378331005"));
+        assert_eq!(cusip_to_isin("38259P508").as_deref(), Some("US38259P5089"));
+    }
+
+    #[test]
+    fn normalize_trims_and_upper_cases() {
+        assert_eq!(normalize_identifier(IdentifierKind::Isin, " us0378331005 ").unwrap(), "US0378331005");
+        assert_eq!(normalize_identifier(IdentifierKind::Cusip, "38259p508").unwrap(), "38259P508");
+        assert_eq!(
+            normalize_identifier(IdentifierKind::Isin, "US0378331006").unwrap_err(),
+            "Invalid ISIN 'US0378331006'"
+        );
+    }
+}
@@ -0,0 +1,636 @@
+//! Custom indicator expressions such as `ema(close, 12) - ema(close, 26)` or
+//! `close / sma(close, 200)`.
+//!
+//! An expression combines the candle fields `open`, `high`, `low`, `close` and `volume`,
+//! numbers, `+ - * /`, parentheses and these functions, where `n` is a whole number of
+//! daily candles:
+//!
+//! | Function | Value |
+//! |----------|-------|
+//! | `sma(x, n)` | simple moving average |
+//! | `ema(x, n)` | exponential moving average, seeded with the first `n`-candle average |
+//! | `rsi(x, n)` | Wilder's relative strength index |
+//! | `std(x, n)` | population standard deviation |
+//! | `highest(x, n)`, `lowest(x, n)` | rolling extremes |
+//! | `lag(x, n)` | the value `n` candles earlier |
+//! | `abs(x)` | absolute value |
+//!
+//! Expressions are evaluated over candles oldest first into one value per candle. A value is
+//! missing until every window it depends on is full, and where a division by zero occurs.
+
+use rust_decimal::prelude::ToPrimitive;
+use std::fmt;
+
+use crate::config::{MAX_EXPRESSION_LENGTH, MAX_EXPRESSION_PERIOD};
+use crate::models::HistoricalPrice;
+
+/// Deepest nesting of parentheses, calls and unary minus accepted
+const MAX_DEPTH: usize = 16;
+
+/// Periods an EMA or RSI is given to settle from its seed before its value is trusted
+const SETTLE_PERIODS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sma,
+    Ema,
+    Rsi,
+    Std,
+    Highest,
+    Lowest,
+    Lag,
+    Abs,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "sma" => Some(Function::Sma),
+            "ema" => Some(Function::Ema),
+            "rsi" => Some(Function::Rsi),
+            "std" => Some(Function::Std),
+            "highest" => Some(Function::Highest),
+            "lowest" => Some(Function::Lowest),
+            "lag" => Some(Function::Lag),
+            "abs" => Some(Function::Abs),
+            _ => None,
+        }
+    }
+
+    fn takes_period(&self) -> bool {
+        *self != Function::Abs
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f64),
+    Field(Field),
+    Neg(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    /// Function applied to a series, with its period (0 for `abs`)
+    Call(Function, Box<Node>, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(Op),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Op(Op::Add) => write!(f, "+"),
+            Token::Op(Op::Sub) => write!(f, "-"),
+            Token::Op(Op::Mul) => write!(f, "*"),
+            Token::Op(Op::Div) => write!(f, "/"),
+            Token::LeftParen => write!(f, "("),
+            Token::RightParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+/// A parsed indicator expression
+#[derive(Debug, Clone)]
+pub struct Expression {
+    root: Node,
+}
+
+impl Expression {
+    /// Parse `source`, with an error message meant for the user who wrote it
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let source = source.trim();
+        if source.is_empty() {
+            return Err("Expression cannot be empty".to_string());
+        }
+        if source.len() > MAX_EXPRESSION_LENGTH {
+            return Err(format!("Expression too long (max {} characters)", MAX_EXPRESSION_LENGTH));
+        }
+
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, position: 0, depth: 0 };
+        let root = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{}' at the end of the expression", token));
+        }
+        Ok(Self { root })
+    }
+
+    /// Daily candles needed for a settled latest value
+    pub fn lookback(&self) -> usize {
+        lookback(&self.root) + 1
+    }
+
+    /// One value per candle; `candles` must be oldest first
+    pub fn evaluate(&self, candles: &[HistoricalPrice]) -> Vec<Option<f64>> {
+        evaluate(&self.root, candles)
+    }
+
+    /// Value at the latest candle
+    pub fn latest(&self, candles: &[HistoricalPrice]) -> Option<f64> {
+        self.evaluate(candles).last().copied().flatten()
+    }
+
+    /// Value at the latest candle when it crossed `threshold` upwards (`above`) or downwards,
+    /// having been on the other side at the candle before. `candles` must be oldest first.
+    pub fn crossed(&self, candles: &[HistoricalPrice], threshold: f64, above: bool) -> Option<f64> {
+        match self.evaluate(candles).as_slice() {
+            [.., Some(previous), Some(latest)] => {
+                let crossed = if above {
+                    *previous <= threshold && *latest > threshold
+                } else {
+                    *previous >= threshold && *latest < threshold
+                };
+                crossed.then_some(*latest)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Op(match c {
+                    '+' => Op::Add,
+                    '-' => Op::Sub,
+                    '*' => Op::Mul,
+                    _ => Op::Div,
+                }));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LeftParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RightParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let value = literal
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}'", literal))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect::<String>().to_lowercase()));
+            }
+            other => return Err(format!("Unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `expression := term (('+' | '-') term)*`,
+/// `term := unary (('*' | '/') unary)*` and `unary := '-' unary | primary`
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if *token == expected => Ok(()),
+            Some(token) => Err(format!("Expected '{}' but found '{}'", expected, token)),
+            None => Err(format!("Expected '{}' but the expression ended", expected)),
+        }
+    }
+
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("Expression nested too deeply (max {} levels)", MAX_DEPTH));
+        }
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expression(&mut self) -> Result<Node, String> {
+        let mut node = self.term()?;
+        while let Some(Token::Op(op @ (Op::Add | Op::Sub))) = self.peek() {
+            let op = *op;
+            self.position += 1;
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    fn term(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+        while let Some(Token::Op(op @ (Op::Mul | Op::Div))) = self.peek() {
+            let op = *op;
+            self.position += 1;
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if let Some(Token::Op(Op::Sub)) = self.peek() {
+            self.position += 1;
+            return self.nested(|parser| Ok(Node::Neg(Box::new(parser.unary()?))));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        match self.next().cloned() {
+            Some(Token::Number(value)) => Ok(Node::Number(value)),
+            Some(Token::LeftParen) => {
+                let node = self.nested(|parser| parser.expression())?;
+                self.expect(Token::RightParen)?;
+                Ok(node)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LeftParen) => {
+                let function = Function::parse(&name).ok_or_else(|| {
+                    format!(
+                        "Unknown function '{}'. Use sma, ema, rsi, std, highest, lowest, lag or abs",
+                        name
+                    )
+                })?;
+                self.position += 1;
+                let series = self.nested(|parser| parser.expression())?;
+                let period = if function.takes_period() {
+                    self.expect(Token::Comma)?;
+                    self.period(&name)?
+                } else {
+                    0
+                };
+                self.expect(Token::RightParen)?;
+                Ok(Node::Call(function, Box::new(series), period))
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "open" => Ok(Node::Field(Field::Open)),
+                "high" => Ok(Node::Field(Field::High)),
+                "low" => Ok(Node::Field(Field::Low)),
+                "close" => Ok(Node::Field(Field::Close)),
+                "volume" => Ok(Node::Field(Field::Volume)),
+                _ => Err(format!(
+                    "Unknown name '{}'. Use open, high, low, close, volume or a function",
+                    name
+                )),
+            },
+            Some(token) => Err(format!("Unexpected '{}'", token)),
+            None => Err("The expression ended too early".to_string()),
+        }
+    }
+
+    fn period(&mut self, function: &str) -> Result<usize, String> {
+        match self.next() {
+            Some(Token::Number(value))
+                if value.fract() == 0.0 && *value >= 1.0 && *value <= MAX_EXPRESSION_PERIOD as f64 =>
+            {
+                Ok(*value as usize)
+            }
+            _ => Err(format!(
+                "The period of {} must be a whole number from 1 to {}",
+                function, MAX_EXPRESSION_PERIOD
+            )),
+        }
+    }
+}
+
+fn lookback(node: &Node) -> usize {
+    match node {
+        Node::Number(_) | Node::Field(_) => 0,
+        Node::Neg(inner) => lookback(inner),
+        Node::Binary(_, left, right) => lookback(left).max(lookback(right)),
+        Node::Call(function, inner, period) => {
+            let own = match function {
+                Function::Sma | Function::Std | Function::Highest | Function::Lowest => period - 1,
+                Function::Ema => period * SETTLE_PERIODS,
+                Function::Rsi => period * SETTLE_PERIODS + 1,
+                Function::Lag => *period,
+                Function::Abs => 0,
+            };
+            lookback(inner) + own
+        }
+    }
+}
+
+fn evaluate(node: &Node, candles: &[HistoricalPrice]) -> Vec<Option<f64>> {
+    match node {
+        Node::Number(value) => vec![Some(*value); candles.len()],
+        Node::Field(field) => candles
+            .iter()
+            .map(|candle| match field {
+                Field::Open => candle.open.to_f64(),
+                Field::High => candle.high.to_f64(),
+                Field::Low => candle.low.to_f64(),
+                Field::Close => candle.close.to_f64(),
+                Field::Volume => Some(candle.volume as f64),
+            })
+            .collect(),
+        Node::Neg(inner) => evaluate(inner, candles).into_iter().map(|v| v.map(|v| -v)).collect(),
+        Node::Binary(op, left, right) => evaluate(left, candles)
+            .into_iter()
+            .zip(evaluate(right, candles))
+            .map(|(a, b)| {
+                let (a, b) = (a?, b?);
+                let value = match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div if b == 0.0 => return None,
+                    Op::Div => a / b,
+                };
+                value.is_finite().then_some(value)
+            })
+            .collect(),
+        Node::Call(function, inner, period) => {
+            let series = evaluate(inner, candles);
+            match function {
+                Function::Sma => rolling(&series, *period, |window| window.iter().sum::<f64>() / window.len() as f64),
+                Function::Std => rolling(&series, *period, |window| {
+                    let mean = window.iter().sum::<f64>() / window.len() as f64;
+                    let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / window.len() as f64;
+                    variance.sqrt()
+                }),
+                Function::Highest => rolling(&series, *period, |window| window.iter().cloned().fold(f64::MIN, f64::max)),
+                Function::Lowest => rolling(&series, *period, |window| window.iter().cloned().fold(f64::MAX, f64::min)),
+                Function::Lag => (0..series.len())
+                    .map(|i| i.checked_sub(*period).and_then(|j| series[j]))
+                    .collect(),
+                Function::Abs => series.into_iter().map(|v| v.map(f64::abs)).collect(),
+                Function::Ema => ema(&series, *period),
+                Function::Rsi => rsi(&series, *period),
+            }
+        }
+    }
+}
+
+/// `f` of each full window of `period` values ending at a candle
+fn rolling(series: &[Option<f64>], period: usize, f: impl Fn(&[f64]) -> f64) -> Vec<Option<f64>> {
+    let mut window = Vec::with_capacity(period);
+    (0..series.len())
+        .map(|i| {
+            let start = (i + 1).checked_sub(period)?;
+            window.clear();
+            for value in &series[start..=i] {
+                window.push((*value)?);
+            }
+            Some(f(&window))
+        })
+        .collect()
+}
+
+/// Restarts from a fresh seed after a missing value
+fn ema(series: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut run: Vec<f64> = Vec::with_capacity(period);
+    let mut current: Option<f64> = None;
+    series
+        .iter()
+        .map(|value| {
+            let Some(value) = *value else {
+                run.clear();
+                current = None;
+                return None;
+            };
+            current = match current {
+                Some(previous) => Some(alpha * value + (1.0 - alpha) * previous),
+                None => {
+                    run.push(value);
+                    (run.len() == period).then(|| run.iter().sum::<f64>() / period as f64)
+                }
+            };
+            current
+        })
+        .collect()
+}
+
+/// Wilder's smoothing of gains and losses; restarts after a missing value
+fn rsi(series: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    let mut previous: Option<f64> = None;
+    let mut seed: Vec<f64> = Vec::with_capacity(period);
+    let mut averages: Option<(f64, f64)> = None;
+    series
+        .iter()
+        .map(|value| {
+            let Some(value) = *value else {
+                previous = None;
+                seed.clear();
+                averages = None;
+                return None;
+            };
+            let change = previous.map(|p| value - p);
+            previous = Some(value);
+            let change = change?;
+            let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+
+            averages = match averages {
+                Some((avg_gain, avg_loss)) => {
+                    let n = period as f64;
+                    Some(((avg_gain * (n - 1.0) + gain) / n, (avg_loss * (n - 1.0) + loss) / n))
+                }
+                None => {
+                    seed.push(change);
+                    (seed.len() == period).then(|| {
+                        let gains: f64 = seed.iter().map(|c| c.max(0.0)).sum();
+                        let losses: f64 = seed.iter().map(|c| (-c).max(0.0)).sum();
+                        (gains / period as f64, losses / period as f64)
+                    })
+                }
+            };
+            let (avg_gain, avg_loss) = averages?;
+            Some(if avg_loss == 0.0 {
+                if avg_gain == 0.0 { 50.0 } else { 100.0 }
+            } else {
+                100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HistoricalPriceBuilder;
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    /// Daily candles closing at each of `closes`, with high one above and low one below
+    fn candles(closes: &[f64]) -> Vec<HistoricalPrice> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::try_from(*close).unwrap();
+                HistoricalPriceBuilder::new("TEST", Uuid::nil())
+                    .timestamp(start + Duration::days(i as i64))
+                    .prices(close, close + Decimal::ONE, close - Decimal::ONE, close)
+                    .volume(1_000 * (i as i64 + 1))
+                    .build()
+            })
+            .collect()
+    }
+
+    fn values(source: &str, closes: &[f64]) -> Vec<Option<f64>> {
+        Expression::parse(source).unwrap().evaluate(&candles(closes))
+    }
+
+    fn value(source: &str) -> f64 {
+        Expression::parse(source).unwrap().latest(&candles(&[1.0])).unwrap()
+    }
+
+    fn error(source: &str) -> String {
+        Expression::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_and_operators_associate_left() {
+        assert_eq!(value("1 + 2 * 3"), 7.0);
+        assert_eq!(value("2 * 3 + 1"), 7.0);
+        assert_eq!(value("10 - 4 - 3"), 3.0);
+        assert_eq!(value("8 / 2 / 2"), 2.0);
+        assert_eq!(value("-2 * 3 + 10"), 4.0);
+        assert_eq!(value("- -2"), 2.0);
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(value("(1 + 2) * 3"), 9.0);
+        assert_eq!(value("10 - (4 - 3)"), 9.0);
+        assert_eq!(value("-(2 + 3)"), -5.0);
+        assert!(error("(1 + 2").contains("Expected ')'"));
+        assert!(error("1 + 2)").contains("Unexpected ')' at the end"));
+    }
+
+    #[test]
+    fn unknown_names_and_functions_are_rejected() {
+        assert!(error("price * 2").starts_with("Unknown name 'price'"));
+        assert!(error("wma(close, 10)").starts_with("Unknown function 'wma'"));
+        assert!(error("close $ 2").starts_with("Unexpected character '$'"));
+        assert!(error("sma(close, 0)").contains("must be a whole number"));
+        assert!(error("sma(close, 2.5)").contains("must be a whole number"));
+        assert!(error("sma(close)").contains("Expected ','"));
+        assert_eq!(error("  "), "Expression cannot be empty");
+    }
+
+    #[test]
+    fn nesting_is_limited_to_max_depth() {
+        let nested = |depth: usize| format!("{}close{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Expression::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(error(&nested(MAX_DEPTH + 1)).starts_with("Expression nested too deeply"));
+        assert!(error(&format!("{}1", "-".repeat(MAX_DEPTH + 1))).starts_with("Expression nested too deeply"));
+        assert!(error(&format!("{}close{}", "abs(".repeat(MAX_DEPTH + 1), ")".repeat(MAX_DEPTH + 1)))
+            .starts_with("Expression nested too deeply"));
+    }
+
+    #[test]
+    fn fields_read_the_candle() {
+        assert_eq!(values("high - low", &[5.0, 6.0]), vec![Some(2.0), Some(2.0)]);
+        assert_eq!(values("volume / 1000", &[5.0, 6.0]), vec![Some(1.0), Some(2.0)]);
+        assert_eq!(values("OPEN", &[5.0]), vec![Some(5.0)]);
+    }
+
+    #[test]
+    fn rolling_functions_wait_for_a_full_window() {
+        let closes = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(values("sma(close, 3)", &closes), vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+        assert_eq!(values("std(close, 2)", &closes), vec![None, Some(0.5), Some(0.5), Some(0.5), Some(0.5)]);
+        assert_eq!(values("highest(close, 2)", &[3.0, 1.0, 2.0]), vec![None, Some(3.0), Some(2.0)]);
+        assert_eq!(values("lowest(close, 2)", &[3.0, 1.0, 2.0]), vec![None, Some(1.0), Some(1.0)]);
+        assert_eq!(values("lag(close, 2)", &closes), vec![None, None, Some(1.0), Some(2.0), Some(3.0)]);
+        assert_eq!(values("abs(close - 3)", &closes), vec![Some(2.0), Some(1.0), Some(0.0), Some(1.0), Some(2.0)]);
+    }
+
+    #[test]
+    fn ema_is_seeded_with_the_first_average() {
+        // alpha = 2 / (3 + 1) = 0.5 after a seed of (1 + 2 + 3) / 3
+        assert_eq!(values("ema(close, 3)", &[1.0, 2.0, 3.0, 4.0, 8.0]), vec![None, None, Some(2.0), Some(3.0), Some(5.5)]);
+    }
+
+    #[test]
+    fn rsi_uses_wilders_smoothing() {
+        assert_eq!(values("rsi(close, 2)", &[1.0, 2.0, 3.0, 4.0]), vec![None, None, Some(100.0), Some(100.0)]);
+        assert_eq!(values("rsi(close, 2)", &[5.0, 5.0, 5.0]), vec![None, None, Some(50.0)]);
+        // Seed averages of 0.5 gain and 0.5 loss give 50; a gain of 2 then smooths them to
+        // 1.25 and 0.25, so 100 - 100 / (1 + 5)
+        let smoothed = values("rsi(close, 2)", &[1.0, 2.0, 1.0, 3.0]);
+        assert_eq!(smoothed[..3], [None, None, Some(50.0)]);
+        assert!((smoothed[3].unwrap() - 250.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn division_by_zero_is_missing() {
+        assert_eq!(values("close / (close - 2)", &[1.0, 2.0, 4.0]), vec![Some(-1.0), None, Some(2.0)]);
+    }
+
+    #[test]
+    fn lookback_covers_nested_windows() {
+        assert_eq!(Expression::parse("close").unwrap().lookback(), 1);
+        assert_eq!(Expression::parse("sma(close, 20)").unwrap().lookback(), 20);
+        assert_eq!(Expression::parse("lag(sma(close, 5), 3)").unwrap().lookback(), 8);
+        assert_eq!(Expression::parse("ema(close, 10) - rsi(close, 4)").unwrap().lookback(), 31);
+    }
+
+    #[test]
+    fn crossed_needs_the_previous_value_on_the_other_side() {
+        let expression = Expression::parse("close - 10").unwrap();
+        assert_eq!(expression.crossed(&candles(&[9.0, 12.0]), 0.0, true), Some(2.0));
+        assert_eq!(expression.crossed(&candles(&[11.0, 12.0]), 0.0, true), None);
+        assert_eq!(expression.crossed(&candles(&[11.0, 8.0]), 0.0, false), Some(-2.0));
+        assert_eq!(expression.crossed(&candles(&[12.0]), 0.0, true), None);
+    }
+}
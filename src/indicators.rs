@@ -0,0 +1,507 @@
+//! Technical indicators and the signals alerts can wait for.
+//!
+//! Indicator functions take closes oldest first and return one value per close from the
+//! first full period on, so the last value always belongs to the latest close. They never
+//! panic: invalid or insufficient input gives an empty series.
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Simple moving average; periods with too few valid prices are 0
+pub fn calculate_sma_safe(prices: &[f64], period: usize) -> Vec<f64> {
+    if prices.is_empty() || period == 0 || period > prices.len() {
+        return vec![];
+    }
+    
+    let mut sma = Vec::new();
+    for i in (period - 1)..prices.len() {
+        // Saturating arithmetic to completely prevent underflow
+        let start_idx = i.saturating_sub(period.saturating_sub(1));
+        let end_idx = i + 1;
+        
+        if start_idx >= prices.len() || end_idx > prices.len() || start_idx >= end_idx {
+            continue;
+        }
+        
+        let slice = &prices[start_idx..end_idx];
+        let valid_prices: Vec<f64> = slice.iter()
+            .filter(|&&x| x.is_finite() && x > 0.0)
+            .cloned()
+            .collect();
+        
+        if valid_prices.len() >= (period * 2 / 3) { // At least 2/3 of period must be valid
+            let avg = valid_prices.iter().sum::<f64>() / valid_prices.len() as f64;
+            if avg.is_finite() && avg > 0.0 {
+                sma.push(avg);
+            } else {
+                sma.push(0.0);
+            }
+        } else {
+            sma.push(0.0);
+        }
+    }
+    sma
+}
+
+/// Exponential moving average over the valid prices
+pub fn calculate_ema_safe(prices: &[f64], period: usize) -> Vec<f64> {
+    if prices.is_empty() || period == 0 {
+        return vec![];
+    }
+    
+    // Validate input data
+    let valid_prices: Vec<f64> = prices.iter()
+        .filter(|&&x| x.is_finite() && x > 0.0)
+        .cloned()
+        .collect();
+    
+    if valid_prices.is_empty() {
+        return vec![];
+    }
+    
+    let multiplier = 2.0 / (period as f64 + 1.0);
+    if !multiplier.is_finite() || multiplier <= 0.0 || multiplier >= 1.0 {
+        return vec![];
+    }
+    
+    let mut ema = Vec::new();
+    ema.push(valid_prices[0]);
+    
+    for i in 1..valid_prices.len() {
+        let current_price = valid_prices[i];
+        let new_ema = (current_price * multiplier) + (ema[i - 1] * (1.0 - multiplier));
+        
+        if new_ema.is_finite() && new_ema > 0.0 {
+            ema.push(new_ema);
+        } else {
+            ema.push(ema[i - 1]); // Use previous value if calculation fails
+        }
+    }
+    
+    ema
+}
+
+/// Wilder's relative strength index, one value per close after the first `period`
+pub fn calculate_rsi_safe(prices: &[f64], period: usize) -> Vec<f64> {
+    if prices.len() <= period || period == 0 || period > 100 {
+        return vec![];
+    }
+
+    // Validate and sanitize input data
+    let valid_prices: Vec<f64> = prices.iter()
+        .filter(|&&x| x.is_finite() && x > 0.0)
+        .cloned()
+        .collect();
+
+    if valid_prices.len() <= period {
+        return vec![];
+    }
+
+    let mut rsi = Vec::new();
+    let mut gains = Vec::new();
+    let mut losses = Vec::new();
+
+    // Calculate price changes with validation
+    for i in 1..valid_prices.len() {
+        let change = valid_prices[i] - valid_prices[i - 1];
+        if change.is_finite() {
+            gains.push(if change > 0.0 { change } else { 0.0 });
+            losses.push(if change < 0.0 { -change } else { 0.0 });
+        } else {
+            gains.push(0.0);
+            losses.push(0.0);
+        }
+    }
+
+    if gains.len() < period {
+        return vec![];
+    }
+
+    // Calculate initial averages with validation
+    let initial_gain_sum: f64 = gains[..period].iter().sum();
+    let initial_loss_sum: f64 = losses[..period].iter().sum();
+    
+    if !initial_gain_sum.is_finite() || !initial_loss_sum.is_finite() {
+        return vec![];
+    }
+
+    let mut avg_gain = initial_gain_sum / period as f64;
+    let mut avg_loss = initial_loss_sum / period as f64;
+
+    // Calculate first RSI with comprehensive safety checks
+    let first_rsi = calculate_rsi_value_safe(avg_gain, avg_loss);
+    rsi.push(first_rsi);
+
+    // Calculate subsequent RSI values with validation
+    for i in period..gains.len() {
+        if !gains[i].is_finite() || !losses[i].is_finite() {
+            continue;
+        }
+
+        let new_avg_gain = ((avg_gain * (period as f64 - 1.0)) + gains[i]) / period as f64;
+        let new_avg_loss = ((avg_loss * (period as f64 - 1.0)) + losses[i]) / period as f64;
+        
+        if new_avg_gain.is_finite() && new_avg_loss.is_finite() && new_avg_gain >= 0.0 && new_avg_loss >= 0.0 {
+            avg_gain = new_avg_gain;
+            avg_loss = new_avg_loss;
+            
+            let rsi_value = calculate_rsi_value_safe(avg_gain, avg_loss);
+            rsi.push(rsi_value);
+        } else {
+            // Use previous RSI if calculation fails
+            rsi.push(*rsi.last().unwrap_or(&50.0));
+        }
+    }
+
+    rsi
+}
+
+// Helper function for safe RSI value calculation
+fn calculate_rsi_value_safe(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss > 0.0 {
+        let rs = avg_gain / avg_loss;
+        if rs.is_finite() && rs >= 0.0 {
+            let rsi = 100.0 - (100.0 / (1.0 + rs));
+            if rsi.is_finite() {
+                return rsi.clamp(0.0, 100.0);
+            }
+        }
+    } else if avg_gain > 0.0 {
+        return 100.0; // Pure gains, maximum RSI
+    }
+    
+    50.0 // Default neutral RSI
+}
+
+/// MACD line from a fast and a slow EMA
+pub fn calculate_macd_safe(ema_fast: &[f64], ema_slow: &[f64]) -> Vec<f64> {
+    if ema_fast.is_empty() || ema_slow.is_empty() {
+        return vec![];
+    }
+
+    let min_len = std::cmp::min(ema_fast.len(), ema_slow.len());
+    let mut macd = Vec::new();
+
+    for i in 0..min_len {
+        let fast = ema_fast[i];
+        let slow = ema_slow[i];
+        
+        if fast.is_finite() && slow.is_finite() {
+            let macd_value = fast - slow;
+            if macd_value.is_finite() {
+                macd.push(macd_value);
+            } else {
+                macd.push(0.0);
+            }
+        } else {
+            macd.push(0.0);
+        }
+    }
+
+    macd
+}
+
+/// Upper, middle and lower Bollinger bands, aligned with the SMA
+pub fn calculate_bollinger_bands_safe(prices: &[f64], period: usize, std_dev: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    if period == 0 || prices.len() < period || !std_dev.is_finite() || std_dev <= 0.0 {
+        return (vec![], vec![], vec![]);
+    }
+    
+    let sma = calculate_sma_safe(prices, period);
+    if sma.is_empty() {
+        return (vec![], vec![], vec![]);
+    }
+    
+    let mut upper = Vec::new();
+    let mut lower = Vec::new();
+    
+    // For each SMA value, calculate the corresponding Bollinger Bands
+    // In calculate_sma_safe: for price index i (where i >= period-1),
+    // SMA is calculated from prices[(i-period+1)..=i] which has 'period' elements
+    // This SMA value is stored at index (i - (period-1)) in the SMA array
+    // So SMA[sma_idx] corresponds to prices[sma_idx..sma_idx+period]
+    for (sma_idx, &middle) in sma.iter().enumerate() {
+        // Get the same price slice that was used to calculate this SMA value
+        let slice_start = sma_idx;
+        let slice_end = std::cmp::min(sma_idx + period, prices.len());
+        
+        if slice_start >= prices.len() || slice_end > prices.len() || slice_start >= slice_end {
+            // Fallback: use middle value if we can't calculate properly
+            upper.push(middle);
+            lower.push(middle);
+            continue;
+        }
+        
+        let slice = &prices[slice_start..slice_end];
+        
+        // Need at least half the period for meaningful calculation
+        if slice.len() >= period / 2 {
+            let valid_slice: Vec<f64> = slice.iter()
+                .filter(|&&x| x.is_finite() && x > 0.0)
+                .cloned()
+                .collect();
+            
+            if valid_slice.len() >= period / 2 && middle.is_finite() && middle > 0.0 {
+                // Calculate standard deviation using the same period as the SMA
+                let variance = valid_slice.iter()
+                    .map(|&x| {
+                        let diff = x - middle;
+                        diff * diff  // More efficient than powi(2)
+                    })
+                    .sum::<f64>() / valid_slice.len() as f64;
+                    
+                if variance.is_finite() && variance >= 0.0 {
+                    let std = variance.sqrt();
+                    if std.is_finite() && std >= 0.0 {
+                        let upper_band = middle + (std_dev * std);
+                        let lower_band = middle - (std_dev * std);
+                        
+                        if upper_band.is_finite() && lower_band.is_finite() && upper_band > lower_band {
+                            upper.push(upper_band);
+                            lower.push(lower_band);
+                        } else {
+                            upper.push(middle);
+                            lower.push(middle);
+                        }
+                    } else {
+                        upper.push(middle);
+                        lower.push(middle);
+                    }
+                } else {
+                    upper.push(middle);
+                    lower.push(middle);
+                }
+            } else {
+                upper.push(middle);
+                lower.push(middle);
+            }
+        } else {
+            // Not enough data in slice
+            upper.push(middle);
+            lower.push(middle);
+        }
+    }
+    
+    (upper, sma, lower)
+}
+
+/// Technical condition a signal alert waits for, checked on daily closes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalCondition {
+    /// 50-day SMA crosses above the 200-day SMA
+    GoldenCross,
+    /// 50-day SMA crosses below the 200-day SMA
+    DeathCross,
+    /// 14-day RSI falls below 30
+    RsiOversold,
+    /// 14-day RSI rises above 70
+    RsiOverbought,
+    /// Close rises above the upper 20-day, 2σ Bollinger band
+    BollingerUpperBreak,
+    /// Close falls below the lower 20-day, 2σ Bollinger band
+    BollingerLowerBreak,
+    /// A custom indicator rises above the alert's threshold
+    IndicatorCrossAbove,
+    /// A custom indicator falls below the alert's threshold
+    IndicatorCrossBelow,
+}
+
+impl SignalCondition {
+    pub const ALL: [SignalCondition; 8] = [
+        SignalCondition::GoldenCross,
+        SignalCondition::DeathCross,
+        SignalCondition::RsiOversold,
+        SignalCondition::RsiOverbought,
+        SignalCondition::BollingerUpperBreak,
+        SignalCondition::BollingerLowerBreak,
+        SignalCondition::IndicatorCrossAbove,
+        SignalCondition::IndicatorCrossBelow,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalCondition::GoldenCross => "golden_cross",
+            SignalCondition::DeathCross => "death_cross",
+            SignalCondition::RsiOversold => "rsi_oversold",
+            SignalCondition::RsiOverbought => "rsi_overbought",
+            SignalCondition::BollingerUpperBreak => "bollinger_upper_break",
+            SignalCondition::BollingerLowerBreak => "bollinger_lower_break",
+            SignalCondition::IndicatorCrossAbove => "indicator_cross_above",
+            SignalCondition::IndicatorCrossBelow => "indicator_cross_below",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SignalCondition::GoldenCross => "50-day SMA crossed above the 200-day SMA",
+            SignalCondition::DeathCross => "50-day SMA crossed below the 200-day SMA",
+            SignalCondition::RsiOversold => "14-day RSI fell below 30",
+            SignalCondition::RsiOverbought => "14-day RSI rose above 70",
+            SignalCondition::BollingerUpperBreak => "close rose above the upper Bollinger band",
+            SignalCondition::BollingerLowerBreak => "close fell below the lower Bollinger band",
+            SignalCondition::IndicatorCrossAbove => "custom indicator rose above its threshold",
+            SignalCondition::IndicatorCrossBelow => "custom indicator fell below its threshold",
+        }
+    }
+
+    /// Whether the condition watches a custom indicator rather than a built-in signal
+    pub fn is_custom(&self) -> bool {
+        matches!(self, SignalCondition::IndicatorCrossAbove | SignalCondition::IndicatorCrossBelow)
+    }
+
+    /// Daily closes needed to see a crossing on the latest one
+    pub fn min_closes(&self) -> usize {
+        match self {
+            SignalCondition::GoldenCross | SignalCondition::DeathCross => 201,
+            SignalCondition::RsiOversold | SignalCondition::RsiOverbought => 16,
+            SignalCondition::BollingerUpperBreak | SignalCondition::BollingerLowerBreak => 21,
+            // Depends on the expression; see `Expression::lookback`
+            SignalCondition::IndicatorCrossAbove | SignalCondition::IndicatorCrossBelow => 0,
+        }
+    }
+
+    /// Indicator value at the latest close when the condition became true on it, having been
+    /// false on the close before. `closes` are oldest first. Custom indicator conditions are
+    /// checked with `Expression::crossed` instead and never match here.
+    pub fn crossed(&self, closes: &[f64]) -> Option<f64> {
+        let last_two = |series: &[f64]| -> Option<(f64, f64)> {
+            match series {
+                [.., previous, latest] => Some((*previous, *latest)),
+                _ => None,
+            }
+        };
+        match self {
+            SignalCondition::GoldenCross | SignalCondition::DeathCross => {
+                let (fast_prev, fast) = last_two(&calculate_sma_safe(closes, 50))?;
+                let (slow_prev, slow) = last_two(&calculate_sma_safe(closes, 200))?;
+                if [fast_prev, fast, slow_prev, slow].iter().any(|v| *v <= 0.0) {
+                    return None;
+                }
+                let crossed = if *self == SignalCondition::GoldenCross {
+                    fast_prev <= slow_prev && fast > slow
+                } else {
+                    fast_prev >= slow_prev && fast < slow
+                };
+                crossed.then_some(fast)
+            }
+            SignalCondition::RsiOversold => {
+                let (previous, latest) = last_two(&calculate_rsi_safe(closes, 14))?;
+                (previous >= 30.0 && latest < 30.0).then_some(latest)
+            }
+            SignalCondition::RsiOverbought => {
+                let (previous, latest) = last_two(&calculate_rsi_safe(closes, 14))?;
+                (previous <= 70.0 && latest > 70.0).then_some(latest)
+            }
+            SignalCondition::BollingerUpperBreak | SignalCondition::BollingerLowerBreak => {
+                let (upper, _, lower) = calculate_bollinger_bands_safe(closes, 20, 2.0);
+                let (close_prev, close) = last_two(closes)?;
+                if *self == SignalCondition::BollingerUpperBreak {
+                    let (band_prev, band) = last_two(&upper)?;
+                    (close_prev <= band_prev && close > band).then_some(band)
+                } else {
+                    let (band_prev, band) = last_two(&lower)?;
+                    (close_prev >= band_prev && close < band).then_some(band)
+                }
+            }
+            SignalCondition::IndicatorCrossAbove | SignalCondition::IndicatorCrossBelow => None,
+        }
+    }
+}
+
+impl FromStr for SignalCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|condition| condition.as_str() == s)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|c| c.as_str()).collect();
+                format!("Unknown signal condition '{}'. Use one of: {}", s, known.join(", "))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prefixes of `closes` on whose last close `condition` fires
+    fn firings(condition: SignalCondition, closes: &[f64]) -> Vec<usize> {
+        (1..=closes.len())
+            .filter(|&end| condition.crossed(&closes[..end]).is_some())
+            .map(|end| end - 1)
+            .collect()
+    }
+
+    /// Closes zigzagging around `level`, so averages and RSI sit in the middle
+    fn zigzag(level: f64, len: usize) -> Vec<f64> {
+        (0..len).map(|i| if i % 2 == 0 { level + 0.5 } else { level - 0.5 }).collect()
+    }
+
+    #[test]
+    fn golden_cross_fires_once_when_the_trend_turns_up() {
+        let mut closes: Vec<f64> = (0..250).map(|i| 300.0 - i as f64 * 0.5).collect();
+        closes.extend((1..=150).map(|i| 175.0 + i as f64 * 2.0));
+        assert_eq!(firings(SignalCondition::GoldenCross, &closes).len(), 1);
+        assert!(firings(SignalCondition::DeathCross, &closes).is_empty());
+    }
+
+    #[test]
+    fn death_cross_fires_once_when_the_trend_turns_down() {
+        let mut closes: Vec<f64> = (0..250).map(|i| 100.0 + i as f64 * 0.5).collect();
+        closes.extend((1..=150).map(|i| 225.0 - i as f64));
+        assert_eq!(firings(SignalCondition::DeathCross, &closes).len(), 1);
+        assert!(firings(SignalCondition::GoldenCross, &closes).is_empty());
+    }
+
+    #[test]
+    fn rsi_signals_fire_on_the_close_that_crosses() {
+        let mut falling = zigzag(100.0, 30);
+        falling.extend((1..=10).map(|i| 100.0 - i as f64 * 3.0));
+        let fired = firings(SignalCondition::RsiOversold, &falling);
+        assert_eq!(fired.len(), 1);
+        let rsi = SignalCondition::RsiOversold.crossed(&falling[..=fired[0]]).unwrap();
+        assert!(rsi < 30.0);
+        assert!(firings(SignalCondition::RsiOverbought, &falling).is_empty());
+
+        let mut rising = zigzag(100.0, 30);
+        rising.extend((1..=10).map(|i| 100.0 + i as f64 * 3.0));
+        assert_eq!(firings(SignalCondition::RsiOverbought, &rising).len(), 1);
+        assert!(firings(SignalCondition::RsiOversold, &rising).is_empty());
+    }
+
+    #[test]
+    fn bollinger_breaks_fire_on_a_jump_out_of_the_band() {
+        let mut closes = zigzag(50.0, 30);
+        closes.push(60.0);
+        let band = SignalCondition::BollingerUpperBreak.crossed(&closes).unwrap();
+        assert!(band < 60.0);
+        assert!(SignalCondition::BollingerLowerBreak.crossed(&closes).is_none());
+
+        let mut closes = zigzag(50.0, 30);
+        closes.push(40.0);
+        assert!(SignalCondition::BollingerLowerBreak.crossed(&closes).is_some());
+        assert!(SignalCondition::BollingerUpperBreak.crossed(&closes).is_none());
+    }
+
+    #[test]
+    fn short_histories_and_custom_conditions_never_fire() {
+        for condition in SignalCondition::ALL {
+            let closes = zigzag(10.0, condition.min_closes().saturating_sub(1));
+            assert!(condition.crossed(&closes).is_none(), "{}", condition.as_str());
+        }
+        let mut closes = zigzag(50.0, 30);
+        closes.push(60.0);
+        assert!(SignalCondition::IndicatorCrossAbove.crossed(&closes).is_none());
+    }
+
+    #[test]
+    fn conditions_round_trip_through_their_names() {
+        for condition in SignalCondition::ALL {
+            assert_eq!(condition.as_str().parse::<SignalCondition>(), Ok(condition));
+        }
+        assert!("moon_phase".parse::<SignalCondition>().is_err());
+    }
+}
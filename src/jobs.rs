@@ -0,0 +1,247 @@
+//! Persistent background job queue for long-running fetches.
+//!
+//! `POST /api/jobs` stores a [`JobRequest`] in the `jobs` table and returns at once; a small
+//! pool of worker tasks claims queued jobs oldest first, runs them and records the result
+//! or error for `GET /api/jobs/:id`. Workers are woken when a job is submitted and also poll
+//! the table periodically, so jobs queued by another process are picked up too. Jobs that
+//! were running when the service stopped are queued again on the next start, and failed jobs
+//! can be queued again by hand. Bulk jobs record how many symbols they have fetched so far.
+
+use chrono::Utc;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::MAX_BULK_SYMBOLS;
+use crate::database::Database;
+use crate::models::{Job, JobProgress, JobRequest, JobStatus};
+use crate::validation::validate_symbol;
+use crate::webhooks::{self, WebhookEvent};
+use crate::yahoo_service::YahooFinanceService;
+
+/// Idle workers look for new jobs at least this often
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How often a running bulk job stores its progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+fn wake_signal() -> &'static Notify {
+    static WAKE: OnceLock<Notify> = OnceLock::new();
+    WAKE.get_or_init(Notify::new)
+}
+
+/// Check a request and normalize its symbols before it is queued
+pub fn validate(request: &mut JobRequest) -> Result<(), String> {
+    match request {
+        JobRequest::FetchHistorical { symbol, .. } => {
+            *symbol = symbol.trim().to_uppercase();
+            validate_symbol(symbol).map_err(|_| format!("Invalid symbol '{}'", symbol))?;
+        }
+        JobRequest::BulkHistorical { symbols, universe, offset, .. } => {
+            match (symbols.is_empty(), universe.is_some()) {
+                (false, false) => {}
+                (true, true) => {
+                    if let Some(universe) = universe {
+                        *universe = universe.trim().to_lowercase();
+                    }
+                }
+                _ => return Err("Provide either 'symbols' or 'universe'".to_string()),
+            }
+            if offset.is_some() && universe.is_none() {
+                return Err("'offset' only applies to 'universe'".to_string());
+            }
+
+            for symbol in symbols.iter_mut() {
+                *symbol = symbol.trim().to_uppercase();
+                validate_symbol(symbol).map_err(|_| format!("Invalid symbol '{}'", symbol))?;
+            }
+            let mut seen = HashSet::new();
+            symbols.retain(|symbol| seen.insert(symbol.clone()));
+            if symbols.len() > MAX_BULK_SYMBOLS {
+                return Err(format!(
+                    "Too many symbols requested: {}. Maximum allowed: {}",
+                    symbols.len(),
+                    MAX_BULK_SYMBOLS
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Queue a validated request and wake a worker
+pub async fn submit(db: &Database, request: JobRequest) -> anyhow::Result<Job> {
+    let job = Job {
+        id: Uuid::new_v4(),
+        kind: request.kind().to_string(),
+        request,
+        status: JobStatus::Queued,
+        result: None,
+        error: None,
+        progress: None,
+        created_at: Utc::now(),
+        started_at: None,
+        finished_at: None,
+    };
+    db.insert_job(&job).await?;
+    wake_signal().notify_one();
+    Ok(job)
+}
+
+/// Queue a failed job again and wake a worker; `None` unless the job exists and failed
+pub async fn retry(db: &Database, job_id: Uuid) -> anyhow::Result<Option<Job>> {
+    let job = db.retry_job(job_id).await?;
+    if job.is_some() {
+        wake_signal().notify_one();
+    }
+    Ok(job)
+}
+
+/// Requeue interrupted jobs, then start `workers` worker tasks on the current runtime
+pub fn spawn_workers(service: Arc<YahooFinanceService>, workers: usize) {
+    tokio::spawn(async move {
+        match service.db.requeue_interrupted_jobs().await {
+            Ok(0) => {}
+            Ok(count) => info!("Requeued {} interrupted jobs", count),
+            Err(e) => error!("Failed to requeue interrupted jobs: {}", e),
+        }
+
+        for worker in 0..workers {
+            tokio::spawn(worker_loop(service.clone(), worker));
+        }
+    });
+}
+
+async fn worker_loop(service: Arc<YahooFinanceService>, worker: usize) {
+    loop {
+        match service.db.claim_next_job().await {
+            Ok(Some(job)) => {
+                info!("Worker {} running job {} ({})", worker, job.id, job.kind);
+                let outcome = run(&service, &job).await;
+                if let Err(e) = &outcome {
+                    warn!("Job {} failed: {}", job.id, e);
+                }
+                if let Err(e) = service.db.finish_job(job.id, outcome).await {
+                    error!("Failed to record outcome of job {}: {}", job.id, e);
+                }
+            }
+            Ok(None) => {
+                let _ = tokio::time::timeout(POLL_INTERVAL, wake_signal().notified()).await;
+            }
+            Err(e) => {
+                error!("Failed to claim next job: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn run(service: &Arc<YahooFinanceService>, job: &Job) -> Result<serde_json::Value, String> {
+    match &job.request {
+        JobRequest::FetchHistorical { symbol, interval } => {
+            let interval = interval.as_deref().unwrap_or("1d");
+            let data = service
+                .fetch_historical_data(symbol, interval, true)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            webhooks::emit(
+                service.db.clone(),
+                WebhookEvent::BackfillCompleted,
+                serde_json::json!({
+                    "symbol": symbol,
+                    "interval": interval,
+                    "records": data.len(),
+                }),
+            );
+            Ok(serde_json::json!({
+                "symbol": symbol,
+                "interval": interval,
+                "records": data.len(),
+            }))
+        }
+        JobRequest::BulkHistorical {
+            symbols,
+            universe,
+            offset,
+            interval,
+            max_concurrent,
+        } => {
+            let symbols = match universe {
+                Some(universe) => {
+                    let members = service
+                        .db
+                        .get_universe_symbols(universe)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    if members.is_empty() {
+                        return Err(format!("Universe '{}' not found or empty", universe));
+                    }
+                    members
+                        .into_iter()
+                        .skip(offset.unwrap_or(0))
+                        .take(MAX_BULK_SYMBOLS)
+                        .collect()
+                }
+                None => symbols.clone(),
+            };
+            let interval = interval.as_deref().unwrap_or("1d");
+            let max_concurrent = max_concurrent.unwrap_or(5).clamp(1, 10) as usize;
+
+            let completed = Arc::new(AtomicUsize::new(0));
+            let fetch = service.bulk_fetch_historical_tracked(
+                symbols.iter().map(|s| s.as_str()).collect(),
+                interval,
+                max_concurrent,
+                completed.clone(),
+            );
+            tokio::pin!(fetch);
+            // The first tick is immediate, so the total shows as soon as the job starts
+            let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+            let results = loop {
+                tokio::select! {
+                    results = &mut fetch => break results,
+                    _ = ticker.tick() => {
+                        record_progress(service, job.id, completed.load(Ordering::Relaxed), symbols.len()).await;
+                    }
+                }
+            };
+            record_progress(service, job.id, completed.load(Ordering::Relaxed), symbols.len()).await;
+            let results = results.map_err(|e| e.to_string())?;
+
+            // Candles are stored; the result only says what was fetched
+            let summary: Vec<serde_json::Value> = results
+                .into_iter()
+                .map(|(symbol, result)| match result {
+                    Ok(data) => serde_json::json!({
+                        "symbol": symbol,
+                        "success": true,
+                        "count": data.len(),
+                    }),
+                    Err(e) => serde_json::json!({
+                        "symbol": symbol,
+                        "success": false,
+                        "error": e.to_string(),
+                    }),
+                })
+                .collect();
+            Ok(serde_json::json!({
+                "interval": interval,
+                "symbols": summary,
+            }))
+        }
+    }
+}
+
+async fn record_progress(service: &YahooFinanceService, job_id: Uuid, done: usize, total: usize) {
+    let progress = JobProgress {
+        done: done as i64,
+        total: total as i64,
+    };
+    if let Err(e) = service.db.update_job_progress(job_id, progress).await {
+        warn!("Failed to record progress of job {}: {}", job_id, e);
+    }
+}
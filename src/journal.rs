@@ -0,0 +1,77 @@
+//! Trade journal: real trades logged with tags, rationale and screenshot links.
+//!
+//! Entries are kept apart from the portfolio ledger, so logging a trade never moves holdings,
+//! lots or cash. Tags are compared lower-cased; screenshots are links to images hosted
+//! elsewhere, never uploads.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::config::{MAX_JOURNAL_SCREENSHOTS, MAX_JOURNAL_TAGS, MAX_JOURNAL_TAG_LENGTH};
+use crate::models::JournalEntry;
+
+/// Trimmed, lower-cased tags without duplicates, in the order given
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.chars().count() > MAX_JOURNAL_TAG_LENGTH {
+            return Err(format!("Tags must be 1 to {} characters", MAX_JOURNAL_TAG_LENGTH));
+        }
+        if !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Tag '{}' may only contain letters, digits, '-' and '_'", tag));
+        }
+        if seen.insert(tag.clone()) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_JOURNAL_TAGS {
+        return Err(format!("Too many tags (max {})", MAX_JOURNAL_TAGS));
+    }
+    Ok(normalized)
+}
+
+/// Check that every screenshot link is an absolute http(s) URL
+pub fn check_screenshot_urls(urls: &[String]) -> Result<(), String> {
+    if urls.len() > MAX_JOURNAL_SCREENSHOTS {
+        return Err(format!("Too many screenshots (max {})", MAX_JOURNAL_SCREENSHOTS));
+    }
+    for url in urls {
+        let scheme = reqwest::Url::parse(url).map(|url| url.scheme().to_string()).unwrap_or_default();
+        if scheme != "http" && scheme != "https" {
+            return Err(format!("Screenshot URL '{}' must be an http or https URL", url));
+        }
+    }
+    Ok(())
+}
+
+/// One row per entry; tags and screenshot links are joined with spaces
+pub fn entries_csv(entries: &[JournalEntry]) -> String {
+    let mut csv = String::from("trade_date,symbol,side,quantity,price,fees,tags,rationale,screenshot_urls,id\n");
+    for entry in entries {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{}",
+            entry.trade_date,
+            csv_field(&entry.symbol),
+            entry.side,
+            entry.quantity,
+            entry.price,
+            entry.fees,
+            csv_field(&entry.tags.join(" ")),
+            csv_field(entry.rationale.as_deref().unwrap_or_default()),
+            csv_field(&entry.screenshot_urls.join(" ")),
+            entry.id
+        );
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,355 @@
+//! Minimal JWT support (HS256 / RS256) for bearer-token admin auth.
+//!
+//! Only the compact JWS form with the claims this service issues is handled; tokens
+//! are rejected unless the header algorithm matches the configured one.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Clock skew tolerated when checking `exp`
+const LEEWAY_SECONDS: i64 = 30;
+
+/// `role` claim that grants admin access; tokens without it are not accepted
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    Hs256,
+    Rs256,
+}
+
+impl JwtAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::Rs256 => "RS256",
+        }
+    }
+}
+
+impl std::str::FromStr for JwtAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "HS256" => Ok(JwtAlgorithm::Hs256),
+            "RS256" => Ok(JwtAlgorithm::Rs256),
+            other => Err(format!("Unsupported JWT algorithm '{}'. Use HS256 or RS256", other)),
+        }
+    }
+}
+
+/// Key material for the configured algorithm. RS256 may be verify-only when just the
+/// public key is configured (tokens issued by another service).
+#[derive(Clone)]
+pub enum JwtKeys {
+    Hmac(Vec<u8>),
+    Rsa {
+        private: Option<Box<RsaPrivateKey>>,
+        public: Box<RsaPublicKey>,
+    },
+}
+
+impl fmt::Debug for JwtKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtKeys::Hmac(_) => f.write_str("JwtKeys::Hmac(<redacted>)"),
+            JwtKeys::Rsa { private, .. } => {
+                write!(f, "JwtKeys::Rsa {{ can_sign: {} }}", private.is_some())
+            }
+        }
+    }
+}
+
+impl JwtKeys {
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        match self {
+            JwtKeys::Hmac(_) => JwtAlgorithm::Hs256,
+            JwtKeys::Rsa { .. } => JwtAlgorithm::Rs256,
+        }
+    }
+
+    pub fn can_sign(&self) -> bool {
+        match self {
+            JwtKeys::Hmac(_) => true,
+            JwtKeys::Rsa { private, .. } => private.is_some(),
+        }
+    }
+
+    /// Load RSA keys from PEM text (PKCS#8 or PKCS#1). The public key is derived from the
+    /// private key when not given separately.
+    pub fn rsa_from_pem(private_pem: Option<&str>, public_pem: Option<&str>) -> Result<Self, String> {
+        let private = private_pem
+            .map(|pem| {
+                RsaPrivateKey::from_pkcs8_pem(pem)
+                    .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+                    .map_err(|e| format!("Invalid RSA private key: {}", e))
+            })
+            .transpose()?;
+
+        let public = match public_pem {
+            Some(pem) => RsaPublicKey::from_public_key_pem(pem)
+                .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+                .map_err(|e| format!("Invalid RSA public key: {}", e))?,
+            None => private
+                .as_ref()
+                .map(RsaPublicKey::from)
+                .ok_or("RS256 needs a private or public key")?,
+        };
+
+        Ok(JwtKeys::Rsa {
+            private: private.map(Box::new),
+            public: Box::new(public),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+impl Claims {
+    pub fn is_admin(&self) -> bool {
+        self.role.as_deref() == Some(ADMIN_ROLE)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    typ: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JwtError {
+    Malformed,
+    UnsupportedAlgorithm,
+    InvalidSignature,
+    Expired,
+    InvalidIssuer,
+    CannotSign,
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            JwtError::Malformed => "malformed token",
+            JwtError::UnsupportedAlgorithm => "unexpected signing algorithm",
+            JwtError::InvalidSignature => "invalid signature",
+            JwtError::Expired => "token expired",
+            JwtError::InvalidIssuer => "unexpected issuer",
+            JwtError::CannotSign => "no signing key configured",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+pub fn encode(claims: &Claims, keys: &JwtKeys) -> Result<String, JwtError> {
+    let header = Header {
+        alg: keys.algorithm().as_str().to_string(),
+        typ: Some("JWT".to_string()),
+    };
+    let header_json = serde_json::to_vec(&header).map_err(|_| JwtError::Malformed)?;
+    let claims_json = serde_json::to_vec(claims).map_err(|_| JwtError::Malformed)?;
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header_json),
+        URL_SAFE_NO_PAD.encode(claims_json)
+    );
+
+    let signature = match keys {
+        JwtKeys::Hmac(secret) => {
+            let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
+            mac.update(signing_input.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+        JwtKeys::Rsa { private, .. } => {
+            let private = private.as_ref().ok_or(JwtError::CannotSign)?;
+            let signing_key = SigningKey::<Sha256>::new(private.as_ref().clone());
+            signing_key.sign(signing_input.as_bytes()).to_vec()
+        }
+    };
+
+    Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Verify signature, algorithm, issuer and expiry, returning the claims
+pub fn decode(token: &str, keys: &JwtKeys, issuer: &str, now: i64) -> Result<Claims, JwtError> {
+    let mut parts = token.split('.');
+    let (Some(header_b64), Some(claims_b64), Some(signature_b64), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(JwtError::Malformed);
+    };
+
+    let header: Header = URL_SAFE_NO_PAD
+        .decode(header_b64)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or(JwtError::Malformed)?;
+    if header.alg != keys.algorithm().as_str() {
+        return Err(JwtError::UnsupportedAlgorithm);
+    }
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature_b64)
+        .map_err(|_| JwtError::Malformed)?;
+    let signing_input = &token[..header_b64.len() + 1 + claims_b64.len()];
+
+    match keys {
+        JwtKeys::Hmac(secret) => {
+            let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
+            mac.update(signing_input.as_bytes());
+            mac.verify_slice(&signature)
+                .map_err(|_| JwtError::InvalidSignature)?;
+        }
+        JwtKeys::Rsa { public, .. } => {
+            let verifying_key = VerifyingKey::<Sha256>::new(public.as_ref().clone());
+            let signature =
+                Signature::try_from(signature.as_slice()).map_err(|_| JwtError::InvalidSignature)?;
+            verifying_key
+                .verify(signing_input.as_bytes(), &signature)
+                .map_err(|_| JwtError::InvalidSignature)?;
+        }
+    }
+
+    let claims: Claims = URL_SAFE_NO_PAD
+        .decode(claims_b64)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or(JwtError::Malformed)?;
+
+    if claims.iss != issuer {
+        return Err(JwtError::InvalidIssuer);
+    }
+    if claims.exp + LEEWAY_SECONDS < now {
+        return Err(JwtError::Expired);
+    }
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISSUER: &str = "mango-data-service";
+    const NOW: i64 = 1_700_000_000;
+
+    fn claims(role: Option<&str>) -> Claims {
+        Claims {
+            sub: "admin".to_string(),
+            iss: ISSUER.to_string(),
+            iat: NOW,
+            exp: NOW + 3600,
+            role: role.map(str::to_string),
+        }
+    }
+
+    fn hmac_keys() -> JwtKeys {
+        JwtKeys::Hmac(b"0123456789abcdef0123456789abcdef".to_vec())
+    }
+
+    fn rsa_keys() -> JwtKeys {
+        let private = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        let public = RsaPublicKey::from(&private);
+        JwtKeys::Rsa {
+            private: Some(Box::new(private)),
+            public: Box::new(public),
+        }
+    }
+
+    #[test]
+    fn hs256_round_trip() {
+        let keys = hmac_keys();
+        let token = encode(&claims(Some(ADMIN_ROLE)), &keys).unwrap();
+        let decoded = decode(&token, &keys, ISSUER, NOW).unwrap();
+        assert_eq!(decoded.sub, "admin");
+        assert!(decoded.is_admin());
+    }
+
+    #[test]
+    fn rs256_round_trip_and_verify_only_keys() {
+        let keys = rsa_keys();
+        let token = encode(&claims(Some(ADMIN_ROLE)), &keys).unwrap();
+        assert!(decode(&token, &keys, ISSUER, NOW).unwrap().is_admin());
+
+        let JwtKeys::Rsa { public, .. } = keys else { unreachable!() };
+        let verify_only = JwtKeys::Rsa { private: None, public };
+        assert!(!verify_only.can_sign());
+        assert_eq!(encode(&claims(None), &verify_only), Err(JwtError::CannotSign));
+        assert!(decode(&token, &verify_only, ISSUER, NOW).is_ok());
+    }
+
+    #[test]
+    fn rejects_tokens_signed_with_another_key() {
+        let token = encode(&claims(Some(ADMIN_ROLE)), &hmac_keys()).unwrap();
+        let other = JwtKeys::Hmac(b"another secret of at least 32 chars".to_vec());
+        assert_eq!(decode(&token, &other, ISSUER, NOW).unwrap_err(), JwtError::InvalidSignature);
+
+        let token = encode(&claims(Some(ADMIN_ROLE)), &rsa_keys()).unwrap();
+        assert_eq!(decode(&token, &rsa_keys(), ISSUER, NOW).unwrap_err(), JwtError::InvalidSignature);
+    }
+
+    #[test]
+    fn rejects_tampered_claims() {
+        let keys = hmac_keys();
+        let token = encode(&claims(None), &keys).unwrap();
+        let forged = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims(Some(ADMIN_ROLE))).unwrap());
+        let mut parts: Vec<&str> = token.split('.').collect();
+        parts[1] = &forged;
+        assert_eq!(decode(&parts.join("."), &keys, ISSUER, NOW).unwrap_err(), JwtError::InvalidSignature);
+    }
+
+    #[test]
+    fn rejects_algorithm_mismatch() {
+        // An HS256 token must not verify against an RS256 configuration, whatever its MAC key
+        let token = encode(&claims(Some(ADMIN_ROLE)), &hmac_keys()).unwrap();
+        assert_eq!(decode(&token, &rsa_keys(), ISSUER, NOW).unwrap_err(), JwtError::UnsupportedAlgorithm);
+    }
+
+    #[test]
+    fn rejects_wrong_issuer_and_expired_tokens() {
+        let keys = hmac_keys();
+        let token = encode(&claims(Some(ADMIN_ROLE)), &keys).unwrap();
+        assert_eq!(decode(&token, &keys, "someone-else", NOW).unwrap_err(), JwtError::InvalidIssuer);
+
+        let expires = NOW + 3600;
+        assert!(decode(&token, &keys, ISSUER, expires + LEEWAY_SECONDS).is_ok());
+        assert_eq!(decode(&token, &keys, ISSUER, expires + LEEWAY_SECONDS + 1).unwrap_err(), JwtError::Expired);
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        let keys = hmac_keys();
+        for token in ["", "abc", "a.b", "a.b.c.d", "!!.??.**"] {
+            assert_eq!(decode(token, &keys, ISSUER, NOW).unwrap_err(), JwtError::Malformed, "{}", token);
+        }
+    }
+
+    #[test]
+    fn only_the_admin_role_is_admin() {
+        assert!(claims(Some(ADMIN_ROLE)).is_admin());
+        assert!(!claims(Some("reader")).is_admin());
+        assert!(!claims(None).is_admin());
+    }
+}
@@ -0,0 +1,241 @@
+//! Latency histograms for API routes and market-data provider calls.
+//!
+//! Every matched API route and every provider call is timed into a histogram with
+//! exponentially growing buckets, so memory stays constant however much traffic arrives.
+//! `GET /api/admin/latency` reports p50/p95/p99 per route template and per provider
+//! operation since startup. Provider calls slower than `SLOW_UPSTREAM_CALL_MS` are also
+//! logged as warnings.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::handlers::AppState;
+use crate::models::{LatencyReport, LatencySummary};
+use crate::provider::{FundComposition, LatestQuote, MarketDataProvider, Ownership, PriceHistory, SymbolMatch};
+
+/// Upper bound of the first bucket in microseconds; each further bucket is 25% wider, so 64
+/// buckets reach past two minutes and anything slower lands in the last one
+const FIRST_BUCKET_MICROS: f64 = 250.0;
+const BUCKET_GROWTH: f64 = 1.25;
+const BUCKETS: usize = 64;
+
+fn bucket_upper_micros(index: usize) -> f64 {
+    FIRST_BUCKET_MICROS * BUCKET_GROWTH.powi(index as i32)
+}
+
+fn bucket_for(micros: u64) -> usize {
+    if (micros as f64) <= FIRST_BUCKET_MICROS {
+        return 0;
+    }
+    let index = ((micros as f64) / FIRST_BUCKET_MICROS).log(BUCKET_GROWTH).ceil() as usize;
+    index.min(BUCKETS - 1)
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    total_micros: u64,
+    max_micros: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            count: 0,
+            total_micros: 0,
+            max_micros: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn record(&mut self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.buckets[bucket_for(micros)] += 1;
+        self.count += 1;
+        self.total_micros = self.total_micros.saturating_add(micros);
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    /// Upper bound of the bucket holding the `quantile` observation, capped at the slowest seen
+    fn percentile_micros(&self, quantile: f64) -> f64 {
+        let rank = ((self.count as f64) * quantile).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper_micros(index).min(self.max_micros as f64);
+            }
+        }
+        self.max_micros as f64
+    }
+
+    fn summary(&self, name: &str) -> LatencySummary {
+        let ms = |micros: f64| (micros / 10.0).round() / 100.0;
+        LatencySummary {
+            name: name.to_string(),
+            count: self.count,
+            mean_ms: ms(self.total_micros as f64 / self.count.max(1) as f64),
+            p50_ms: ms(self.percentile_micros(0.50)),
+            p95_ms: ms(self.percentile_micros(0.95)),
+            p99_ms: ms(self.percentile_micros(0.99)),
+            max_ms: ms(self.max_micros as f64),
+        }
+    }
+}
+
+/// Histograms of route and provider call latency since startup
+pub struct LatencyTracker {
+    since: DateTime<Utc>,
+    slow_upstream_call: Duration,
+    slow_upstream_calls: AtomicU64,
+    routes: Mutex<HashMap<String, Histogram>>,
+    upstream: Mutex<HashMap<String, Histogram>>,
+}
+
+impl LatencyTracker {
+    pub fn new(slow_upstream_call: Duration) -> Self {
+        Self {
+            since: Utc::now(),
+            slow_upstream_call,
+            slow_upstream_calls: AtomicU64::new(0),
+            routes: Mutex::new(HashMap::new()),
+            upstream: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_route(&self, route: &str, elapsed: Duration) {
+        record(&self.routes, route, elapsed);
+    }
+
+    pub fn record_upstream(&self, call: &str, elapsed: Duration) {
+        record(&self.upstream, call, elapsed);
+        if !self.slow_upstream_call.is_zero() && elapsed >= self.slow_upstream_call {
+            self.slow_upstream_calls.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "🐢 Slow upstream call {} took {}ms (threshold {}ms)",
+                call,
+                elapsed.as_millis(),
+                self.slow_upstream_call.as_millis()
+            );
+        }
+    }
+
+    pub fn report(&self) -> LatencyReport {
+        LatencyReport {
+            since: self.since,
+            slow_upstream_call_ms: self.slow_upstream_call.as_millis() as u64,
+            slow_upstream_calls: self.slow_upstream_calls.load(Ordering::Relaxed),
+            routes: summaries(&self.routes),
+            upstream: summaries(&self.upstream),
+        }
+    }
+}
+
+fn record(histograms: &Mutex<HashMap<String, Histogram>>, name: &str, elapsed: Duration) {
+    let mut histograms = histograms.lock().unwrap_or_else(|e| e.into_inner());
+    match histograms.get_mut(name) {
+        Some(histogram) => histogram.record(elapsed),
+        None => histograms.entry(name.to_string()).or_default().record(elapsed),
+    }
+}
+
+fn summaries(histograms: &Mutex<HashMap<String, Histogram>>) -> Vec<LatencySummary> {
+    let histograms = histograms.lock().unwrap_or_else(|e| e.into_inner());
+    let mut summaries: Vec<LatencySummary> = histograms
+        .iter()
+        .map(|(name, histogram)| histogram.summary(name))
+        .collect();
+    summaries.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms).then_with(|| a.name.cmp(&b.name)));
+    summaries
+}
+
+/// Time each matched API route, keyed by method and route template so path parameters do
+/// not multiply the histograms
+pub async fn track_route_latency_middleware(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => format!("{} {}", request.method(), path.as_str()),
+        None => return next.run(request).await,
+    };
+    let started = Instant::now();
+    let response = next.run(request).await;
+    app_state.service.latency.record_route(&route, started.elapsed());
+    response
+}
+
+/// Provider wrapper that times every call into a [`LatencyTracker`] as
+/// `<provider>.<operation>`
+pub struct TimedProvider {
+    inner: Arc<dyn MarketDataProvider>,
+    latency: Arc<LatencyTracker>,
+}
+
+impl TimedProvider {
+    pub fn new(inner: Arc<dyn MarketDataProvider>, latency: Arc<LatencyTracker>) -> Self {
+        Self { inner, latency }
+    }
+
+    async fn timed<T>(&self, operation: &str, call: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        let result = call.await;
+        let name = format!("{}.{}", self.inner.name(), operation);
+        self.latency.record_upstream(&name, started.elapsed());
+        result
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for TimedProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn price_history(&self, symbol: &str, interval: &str, range: &str) -> Result<PriceHistory> {
+        self.timed("price_history", self.inner.price_history(symbol, interval, range))
+            .await
+    }
+
+    async fn price_history_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+    ) -> Result<PriceHistory> {
+        self.timed("price_history", self.inner.price_history_since(symbol, interval, start))
+            .await
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<Option<LatestQuote>> {
+        self.timed("latest_quote", self.inner.latest_quote(symbol)).await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SymbolMatch>> {
+        self.timed("search", self.inner.search(query)).await
+    }
+
+    async fn ownership(&self, symbol: &str) -> Result<Option<Ownership>> {
+        self.timed("ownership", self.inner.ownership(symbol)).await
+    }
+
+    async fn fund_composition(&self, symbol: &str) -> Result<Option<FundComposition>> {
+        self.timed("fund_composition", self.inner.fund_composition(symbol)).await
+    }
+}
@@ -0,0 +1,332 @@
+//! Tax lot matching over the portfolio transaction ledger.
+//!
+//! Every buy opens a lot; every sell closes quantity from open lots of the same symbol in
+//! FIFO or LIFO order. Buy fees are added to the lot's cost basis and sell fees are
+//! deducted from proceeds, both allocated pro rata by quantity.
+
+use crate::models::{PortfolioTransaction, RealizedLot, TaxReport};
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Holding period after which a gain counts as long-term
+const LONG_TERM_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+}
+
+impl LotMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LotMethod::Fifo => "fifo",
+            LotMethod::Lifo => "lifo",
+        }
+    }
+}
+
+impl FromStr for LotMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            other => Err(format!("Unsupported lot method '{}'. Use 'fifo' or 'lifo'", other)),
+        }
+    }
+}
+
+struct OpenLot {
+    transaction_id: uuid::Uuid,
+    acquired: NaiveDate,
+    quantity: Decimal,
+    cost_per_share: Decimal,
+}
+
+/// Match every sell against open lots. `transactions` must be ordered by trade date, then
+/// creation time. Fails if a sell exceeds the quantity held at that point, or if a
+/// transaction has no positive quantity to spread its fees over.
+pub fn realize_lots(
+    transactions: &[PortfolioTransaction],
+    method: LotMethod,
+) -> Result<Vec<RealizedLot>, String> {
+    let mut open: HashMap<&str, VecDeque<OpenLot>> = HashMap::new();
+    let mut realized = Vec::new();
+
+    for tx in transactions {
+        // Rows from archive imports skip request validation
+        if tx.quantity <= Decimal::ZERO {
+            return Err(format!(
+                "{} of {} {} on {} must have a positive quantity",
+                tx.side, tx.quantity, tx.symbol, tx.trade_date
+            ));
+        }
+        let lots = open.entry(tx.symbol.as_str()).or_default();
+
+        if tx.side == "buy" {
+            lots.push_back(OpenLot {
+                transaction_id: tx.id,
+                acquired: tx.trade_date,
+                quantity: tx.quantity,
+                cost_per_share: tx.price + tx.fees / tx.quantity,
+            });
+            continue;
+        }
+
+        let held: Decimal = lots.iter().map(|l| l.quantity).sum();
+        if tx.quantity > held {
+            return Err(format!(
+                "Sell of {} {} on {} exceeds the {} shares held",
+                tx.quantity, tx.symbol, tx.trade_date, held
+            ));
+        }
+
+        let proceeds_per_share = tx.price - tx.fees / tx.quantity;
+        let mut remaining = tx.quantity;
+        while remaining > Decimal::ZERO {
+            let lot = match method {
+                LotMethod::Fifo => lots.front_mut(),
+                LotMethod::Lifo => lots.back_mut(),
+            }
+            .expect("held quantity checked above");
+
+            let quantity = remaining.min(lot.quantity);
+            let proceeds = quantity * proceeds_per_share;
+            let cost_basis = quantity * lot.cost_per_share;
+            let holding_days = (tx.trade_date - lot.acquired).num_days();
+
+            realized.push(RealizedLot {
+                symbol: tx.symbol.clone(),
+                buy_transaction_id: lot.transaction_id,
+                sell_transaction_id: tx.id,
+                acquired: lot.acquired,
+                sold: tx.trade_date,
+                quantity,
+                proceeds: proceeds.round_dp(2),
+                cost_basis: cost_basis.round_dp(2),
+                gain: (proceeds - cost_basis).round_dp(2),
+                holding_days,
+                term: if holding_days > LONG_TERM_DAYS { "long" } else { "short" }.to_string(),
+            });
+
+            lot.quantity -= quantity;
+            remaining -= quantity;
+            if lot.quantity.is_zero() {
+                match method {
+                    LotMethod::Fifo => lots.pop_front(),
+                    LotMethod::Lifo => lots.pop_back(),
+                };
+            }
+        }
+    }
+
+    Ok(realized)
+}
+
+/// Realized lots for sales in `year`
+pub fn tax_report(
+    transactions: &[PortfolioTransaction],
+    method: LotMethod,
+    year: i32,
+) -> Result<TaxReport, String> {
+    let lots: Vec<RealizedLot> = realize_lots(transactions, method)?
+        .into_iter()
+        .filter(|lot| lot.sold.year() == year)
+        .collect();
+
+    let total = |term: Option<&str>| -> Decimal {
+        lots.iter()
+            .filter(|lot| term.is_none_or(|t| lot.term == t))
+            .map(|lot| lot.gain)
+            .sum()
+    };
+
+    Ok(TaxReport {
+        year,
+        method: method.as_str().to_string(),
+        short_term_gain: total(Some("short")),
+        long_term_gain: total(Some("long")),
+        total_gain: total(None),
+        total_proceeds: lots.iter().map(|lot| lot.proceeds).sum(),
+        total_cost_basis: lots.iter().map(|lot| lot.cost_basis).sum(),
+        lots,
+    })
+}
+
+/// One row per realized lot, in the column order of common tax worksheets
+pub fn tax_report_csv(report: &TaxReport) -> String {
+    let mut csv = String::from(
+        "symbol,quantity,date_acquired,date_sold,proceeds,cost_basis,gain,term,holding_days\n",
+    );
+    for lot in &report.lots {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&lot.symbol),
+            lot.quantity,
+            lot.acquired,
+            lot.sold,
+            lot.proceeds,
+            lot.cost_basis,
+            lot.gain,
+            lot.term,
+            lot.holding_days
+        );
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn tx(symbol: &str, side: &str, quantity: Decimal, price: Decimal, fees: Decimal, trade_date: &str) -> PortfolioTransaction {
+        PortfolioTransaction {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity,
+            price,
+            fees,
+            trade_date: date(trade_date),
+            notes: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn ledger() -> Vec<PortfolioTransaction> {
+        vec![
+            tx("AAPL", "buy", d("10"), d("100"), d("10"), "2024-01-02"),
+            tx("AAPL", "buy", d("10"), d("150"), d("0"), "2024-06-03"),
+            tx("AAPL", "sell", d("15"), d("200"), d("15"), "2025-01-02"),
+        ]
+    }
+
+    #[test]
+    fn fifo_closes_the_oldest_lots_first() {
+        let transactions = ledger();
+        let lots = realize_lots(&transactions, LotMethod::Fifo).unwrap();
+        assert_eq!(lots.len(), 2);
+
+        // All of the first lot: cost 100 + 1 fee per share, proceeds 200 - 1 fee per share
+        assert_eq!(lots[0].buy_transaction_id, transactions[0].id);
+        assert_eq!(lots[0].quantity, d("10"));
+        assert_eq!(lots[0].cost_basis, d("1010"));
+        assert_eq!(lots[0].proceeds, d("1990"));
+        assert_eq!(lots[0].gain, d("980"));
+        // Half of the second lot
+        assert_eq!(lots[1].buy_transaction_id, transactions[1].id);
+        assert_eq!(lots[1].quantity, d("5"));
+        assert_eq!(lots[1].cost_basis, d("750"));
+        assert_eq!(lots[1].gain, d("245"));
+    }
+
+    #[test]
+    fn lifo_closes_the_newest_lots_first() {
+        let transactions = ledger();
+        let lots = realize_lots(&transactions, LotMethod::Lifo).unwrap();
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[0].buy_transaction_id, transactions[1].id);
+        assert_eq!(lots[0].quantity, d("10"));
+        assert_eq!(lots[1].buy_transaction_id, transactions[0].id);
+        assert_eq!(lots[1].quantity, d("5"));
+        assert_eq!(lots[1].cost_basis, d("505"));
+    }
+
+    #[test]
+    fn partially_closed_lots_stay_open_for_later_sells() {
+        let mut transactions = ledger();
+        transactions.push(tx("AAPL", "sell", d("5"), d("120"), d("0"), "2025-02-03"));
+        let lots = realize_lots(&transactions, LotMethod::Fifo).unwrap();
+        assert_eq!(lots.len(), 3);
+        assert_eq!(lots[2].buy_transaction_id, transactions[1].id);
+        assert_eq!(lots[2].quantity, d("5"));
+        assert_eq!(lots[2].gain, d("-150"));
+    }
+
+    #[test]
+    fn overselling_is_an_error() {
+        let mut transactions = ledger();
+        transactions.push(tx("AAPL", "sell", d("6"), d("120"), d("0"), "2025-02-03"));
+        let err = realize_lots(&transactions, LotMethod::Fifo).unwrap_err();
+        assert!(err.contains("exceeds the 5 shares held"), "{}", err);
+
+        // Shares of another symbol do not count
+        let transactions = vec![
+            tx("MSFT", "buy", d("10"), d("100"), d("0"), "2024-01-02"),
+            tx("AAPL", "sell", d("1"), d("100"), d("0"), "2024-01-03"),
+        ];
+        assert!(realize_lots(&transactions, LotMethod::Fifo).is_err());
+    }
+
+    #[test]
+    fn zero_quantity_rows_are_rejected_instead_of_dividing_by_zero() {
+        for side in ["buy", "sell"] {
+            let transactions = vec![
+                tx("AAPL", "buy", d("10"), d("100"), d("0"), "2024-01-02"),
+                tx("AAPL", side, d("0"), d("100"), d("5"), "2024-01-03"),
+            ];
+            let err = realize_lots(&transactions, LotMethod::Fifo).unwrap_err();
+            assert!(err.contains("positive quantity"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn gains_turn_long_term_after_a_year() {
+        let transactions = vec![
+            tx("AAPL", "buy", d("2"), d("100"), d("0"), "2023-01-02"),
+            // 365 days later: still short-term
+            tx("AAPL", "sell", d("1"), d("110"), d("0"), "2024-01-02"),
+            // 366 days
+            tx("AAPL", "sell", d("1"), d("120"), d("0"), "2024-01-03"),
+        ];
+        let report = tax_report(&transactions, LotMethod::Fifo, 2024).unwrap();
+        assert_eq!(report.lots[0].holding_days, 365);
+        assert_eq!(report.lots[0].term, "short");
+        assert_eq!(report.lots[1].holding_days, 366);
+        assert_eq!(report.lots[1].term, "long");
+        assert_eq!(report.short_term_gain, d("10"));
+        assert_eq!(report.long_term_gain, d("20"));
+        assert_eq!(report.total_gain, d("30"));
+
+        assert!(tax_report(&transactions, LotMethod::Fifo, 2023).unwrap().lots.is_empty());
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        let transactions = vec![
+            tx("BRK,B \"A\"", "buy", d("1"), d("100"), d("0"), "2024-01-02"),
+            tx("BRK,B \"A\"", "sell", d("1"), d("110"), d("0"), "2024-03-01"),
+            tx("MSFT", "buy", d("1"), d("100"), d("0"), "2024-01-02"),
+            tx("MSFT", "sell", d("1"), d("90"), d("0"), "2024-03-01"),
+        ];
+        let csv = tax_report_csv(&tax_report(&transactions, LotMethod::Fifo, 2024).unwrap());
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "symbol,quantity,date_acquired,date_sold,proceeds,cost_basis,gain,term,holding_days");
+        assert_eq!(rows[1], "\"BRK,B \"\"A\"\"\",1,2024-01-02,2024-03-01,110,100,10,short,59");
+        assert_eq!(rows[2], "MSFT,1,2024-01-02,2024-03-01,90,100,-10,short,59");
+    }
+}
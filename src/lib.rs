@@ -0,0 +1,87 @@
+//! Mango Data Service as a library.
+//!
+//! The `mango-data-service` binary is a thin wrapper around this crate; other binaries
+//! and integration tests can build the same [`YahooFinanceService`], [`Database`] and
+//! router without going through `main`.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use mango_data_service::{build_router, AppState, Config, Database, YahooFinanceService};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = Config::from_env()?;
+//! let db = Database::new(&config.database).await?;
+//! let service = Arc::new(YahooFinanceService::new(Arc::new(db), config.clone())?);
+//! let app = build_router(AppState::new(service, config));
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+//! axum::serve(listener, app).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "analytics")]
+pub mod analytics_sql;
+pub mod api_keys;
+pub mod anomaly;
+pub mod app;
+pub mod archive;
+pub mod audit;
+pub mod auth;
+pub mod auth_handler;
+pub mod auth_middleware;
+pub mod auth_routes;
+pub mod backtest;
+pub mod cache;
+pub mod cache_bus;
+pub mod config;
+pub mod database;
+pub mod db_writer;
+pub mod demo;
+pub mod errors;
+pub mod event_stream;
+pub mod features;
+pub mod fields;
+pub mod forecast;
+pub mod fx;
+pub mod http_cache;
+pub mod handlers;
+pub mod identifiers;
+pub mod indicator_expr;
+pub mod indicators;
+pub mod jobs;
+pub mod journal;
+pub mod jwt;
+pub mod latency;
+pub mod limits;
+pub mod ledger;
+pub mod macro_series;
+pub mod market_calendar;
+pub mod models;
+pub mod nl_query;
+pub mod notify;
+pub mod numeric;
+pub mod object_export;
+pub mod oidc;
+pub mod parquet;
+pub mod peers;
+pub mod portfolio_updater;
+pub mod projection;
+pub mod ranking;
+pub mod provider;
+pub mod quota;
+pub mod read_only;
+pub mod risk;
+pub mod stats_history;
+pub mod tools;
+pub mod udf;
+pub mod universe;
+pub mod validation;
+pub mod web_ui;
+pub mod webhooks;
+pub mod yahoo_service;
+
+pub use app::{build_app, build_router, build_test_app, spawn_background_tasks};
+pub use config::Config;
+pub use database::Database;
+pub use handlers::AppState;
+pub use yahoo_service::YahooFinanceService;
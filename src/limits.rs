@@ -0,0 +1,45 @@
+//! Rate limit headers on API responses.
+//!
+//! Every `/api` response tells the caller where it stands in the per-minute API rate limit,
+//! so SDKs and scripts can slow down before they are refused instead of finding the limit
+//! through `429`s. `X-RateLimit-Reset` is the Unix time at which the oldest request counted
+//! leaves the sliding window and frees a slot. Refusals by the limit also carry `Retry-After`.
+//! `GET /api/limits` reports the same figures with the provider limits and the daily budget.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+
+use crate::handlers::{get_client_id, AppState};
+
+pub const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Add the caller's rate limit standing after the handler has counted its request
+pub async fn rate_limit_headers_middleware(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client_id = get_client_id(request.headers());
+    let mut response = next.run(request).await;
+
+    let status = app_state.service.api_rate_limit_status(&client_id).await;
+    let headers = response.headers_mut();
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(status.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(status.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(status.resets_at.timestamp()));
+    if response.status() == StatusCode::TOO_MANY_REQUESTS
+        && status.remaining == 0
+        && !response.headers().contains_key(header::RETRY_AFTER)
+    {
+        let seconds = (status.resets_at - Utc::now()).num_seconds().max(0) + 1;
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+    }
+    response
+}
@@ -0,0 +1,175 @@
+//! Macro-economic series: the 13-week T-bill and 10-year Treasury yields and US CPI.
+//!
+//! Observations come from FRED when a FRED API key is configured. Without one, the two
+//! yields fall back to the provider's daily closes of the CBOE yield indices (`^IRX`,
+//! `^TNX`), which quote the same rates in percent; CPI is only published through FRED.
+//! The mock provider serves flat synthetic series so demos and tests never reach out.
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::provider::UpstreamError;
+
+const FRED_OBSERVATIONS_URL: &str = "https://api.stlouisfed.org/fred/series/observations";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroSeries {
+    TBill13Week,
+    Treasury10Year,
+    Cpi,
+}
+
+impl MacroSeries {
+    pub const ALL: [MacroSeries; 3] = [MacroSeries::TBill13Week, MacroSeries::Treasury10Year, MacroSeries::Cpi];
+
+    /// Name used in routes and storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MacroSeries::TBill13Week => "tbill-13w",
+            MacroSeries::Treasury10Year => "treasury-10y",
+            MacroSeries::Cpi => "cpi",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            MacroSeries::TBill13Week => "13-week Treasury bill secondary market rate",
+            MacroSeries::Treasury10Year => "10-year Treasury constant maturity yield",
+            MacroSeries::Cpi => "Consumer price index for all urban consumers",
+        }
+    }
+
+    pub fn units(&self) -> &'static str {
+        match self {
+            MacroSeries::TBill13Week | MacroSeries::Treasury10Year => "percent",
+            MacroSeries::Cpi => "index 1982-1984=100",
+        }
+    }
+
+    pub fn frequency(&self) -> &'static str {
+        match self {
+            MacroSeries::TBill13Week | MacroSeries::Treasury10Year => "daily",
+            MacroSeries::Cpi => "monthly",
+        }
+    }
+
+    /// FRED series id
+    pub fn fred_id(&self) -> &'static str {
+        match self {
+            MacroSeries::TBill13Week => "DTB3",
+            MacroSeries::Treasury10Year => "DGS10",
+            MacroSeries::Cpi => "CPIAUCSL",
+        }
+    }
+
+    /// Provider ticker quoting the same series, for instances without a FRED key
+    pub fn provider_symbol(&self) -> Option<&'static str> {
+        match self {
+            MacroSeries::TBill13Week => Some("^IRX"),
+            MacroSeries::Treasury10Year => Some("^TNX"),
+            MacroSeries::Cpi => None,
+        }
+    }
+
+    /// Synthetic value for the mock provider on `date`: flat yields, and prices rising about
+    /// 3% a year
+    pub fn mock_value(&self, date: NaiveDate) -> Decimal {
+        match self {
+            MacroSeries::TBill13Week => Decimal::new(400, 2),
+            MacroSeries::Treasury10Year => Decimal::new(425, 2),
+            MacroSeries::Cpi => {
+                let months = (date.year() - 2000) * 12 + date.month0() as i32;
+                Decimal::from_f64_retain(170.0 * 1.0025_f64.powi(months)).unwrap_or_default().round_dp(3)
+            }
+        }
+    }
+}
+
+impl FromStr for MacroSeries {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        MacroSeries::ALL
+            .into_iter()
+            .find(|series| series.as_str() == value || series.fred_id().eq_ignore_ascii_case(&value))
+            .ok_or_else(|| {
+                let names: Vec<&str> = MacroSeries::ALL.iter().map(MacroSeries::as_str).collect();
+                format!("Unknown macro series '{}'; available: {}", value, names.join(", "))
+            })
+    }
+}
+
+/// Mock observations of `series` from `from` to `to`: every weekday for daily series, the
+/// first of each month for CPI
+pub fn mock_observations(series: MacroSeries, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, Decimal)> {
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| match series {
+            MacroSeries::Cpi => date.day() == 1,
+            _ => date.weekday().num_days_from_monday() < 5,
+        })
+        .map(|date| (date, series.mock_value(date)))
+        .collect()
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("mango-data-service/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("reqwest client with static configuration")
+    })
+}
+
+#[derive(Deserialize)]
+struct FredObservations {
+    observations: Vec<FredObservation>,
+}
+
+#[derive(Deserialize)]
+struct FredObservation {
+    date: String,
+    value: String,
+}
+
+/// Observations of `series` from FRED since `start`, oldest first. Days FRED marks as
+/// missing (`.`, e.g. market holidays) are left out.
+pub async fn fred_observations(api_key: &str, series: MacroSeries, start: NaiveDate) -> Result<Vec<(NaiveDate, Decimal)>> {
+    let response = http_client()
+        .get(FRED_OBSERVATIONS_URL)
+        .query(&[
+            ("series_id", series.fred_id()),
+            ("api_key", api_key),
+            ("file_type", "json"),
+            ("observation_start", &start.to_string()),
+        ])
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(match UpstreamError::from_status(status, None) {
+            Some(upstream) => upstream.into(),
+            None => anyhow::anyhow!("FRED rejected {} with HTTP {}", series.fred_id(), status),
+        });
+    }
+
+    let body: FredObservations = response.json().await?;
+    Ok(body
+        .observations
+        .into_iter()
+        .filter_map(|observation| {
+            let date = NaiveDate::from_str(&observation.date).ok()?;
+            let value = Decimal::from_str(&observation.value).ok()?;
+            Some((date, value))
+        })
+        .collect())
+}
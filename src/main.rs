@@ -1,48 +1,11 @@
 use anyhow::Result;
-use axum::{
-    http::{
-        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-        HeaderValue, Method,
-    },
-    routing::{delete, get, post, put},
-    Router,
-};
 use std::sync::Arc;
-use tower::ServiceBuilder;
-use tower_http::{
-    cors::CorsLayer,
-    trace::TraceLayer,
-    services::ServeDir,
-};
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod config;
-mod database;
-mod errors;
-mod handlers;
-mod models;
-mod validation;
-mod yahoo_service;
-mod web_ui;
-mod auth;
-mod auth_handler;
-mod auth_routes;
-mod auth_middleware;
-
-use config::Config;
-use database::Database;
-use handlers::{
-    health_check, get_symbols, search_symbols, validate_symbol,
-    get_historical_data, fetch_historical_data, bulk_fetch_historical,
-    get_real_time_quote, get_company_profile, get_symbol_overview,
-    get_price_analysis, get_database_stats, get_comprehensive_quote,
-    get_extended_quote_data, handler_404, cleanup_cache,
-    get_technical_indicators, compare_symbols,
-    get_portfolio, add_portfolio_holding, update_portfolio_holding,
-    delete_portfolio_holding, update_portfolio_prices, download_backup, AppState,
+use mango_data_service::{
+    build_router, demo, spawn_background_tasks, AppState, Config, Database, YahooFinanceService,
 };
-use yahoo_service::YahooFinanceService;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -69,198 +32,24 @@ async fn main() -> Result<()> {
     info!("Connecting to database: {}", config.database.url);
 
     // Initialize database
-    let db = Database::new(&config.database.url, config.database.max_connections).await?;
+    let db = Arc::new(Database::new(&config.database).await?);
     info!("✅ Database initialized successfully");
 
+    if config.market_data.demo_mode && !config.database.read_only {
+        demo::seed_if_needed(db.clone(), &config).await?;
+    }
+
     // Create Yahoo Finance service with optimizations
-    let yahoo_service = Arc::new(YahooFinanceService::new(Arc::new(db), config.clone())?);
+    let yahoo_service = Arc::new(YahooFinanceService::new(db, config.clone())?);
     info!("✅ Yahoo Finance service initialized with rate limiting and caching");
     
     // Create AppState with service and config
     let app_state = AppState::new(yahoo_service.clone(), config.clone());
     
-    // Start background cache cleanup task
-    let cleanup_service = yahoo_service.clone();
-    let cleanup_interval = config.cache.cleanup_interval;
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(cleanup_interval);
-        loop {
-            interval.tick().await;
-            cleanup_service.cleanup_cache();
-            info!("🧹 Cache cleanup completed");
-        }
-    });
-
-    // Start background portfolio price update task (every 5 minutes)
-    let portfolio_service = yahoo_service.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5 minutes
-        loop {
-            interval.tick().await;
-            info!("📊 Updating portfolio prices...");
-            match portfolio_service.db.get_all_portfolio_holdings().await {
-                Ok(holdings) => {
-                    let total = holdings.len();
-                    let mut updated = 0;
-                    for holding in holdings {
-                        // Get current quote
-                        if let Ok(Some(quote)) = portfolio_service.get_latest_quote(&holding.symbol).await {
-                            let current_price = quote.price;
-                            let current_value = current_price * holding.quantity;
-                            let total_cost = holding.purchase_price * holding.quantity;
-                            let gain_loss = current_value - total_cost;
-                            let gain_loss_percent = if total_cost > rust_decimal::Decimal::ZERO {
-                                (gain_loss / total_cost) * rust_decimal::Decimal::from(100)
-                            } else {
-                                rust_decimal::Decimal::ZERO
-                            };
+    // Start background cache cleanup and portfolio price update tasks
+    spawn_background_tasks(yahoo_service.clone(), &config);
 
-                            if let Ok(_) = portfolio_service.db.update_portfolio_holding_prices(
-                                holding.id,
-                                current_price,
-                                current_value,
-                                gain_loss,
-                                gain_loss_percent,
-                            ).await {
-                                updated += 1;
-                            }
-                        }
-                    }
-                    info!("✅ Portfolio prices updated: {}/{} holdings", updated, total);
-                }
-                Err(e) => {
-                    warn!("Failed to update portfolio prices: {:?}", e);
-                }
-            }
-        }
-    });
-
-    // Build CORS layer
-    let cors = if config.cors.allow_all_origins {
-        CorsLayer::new()
-            .allow_origin("*".parse::<HeaderValue>().unwrap())
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE])
-    } else {
-        let mut cors_builder = CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
-        
-        for origin in &config.cors.allowed_origins {
-            if let Ok(header_value) = origin.parse::<HeaderValue>() {
-                cors_builder = cors_builder.allow_origin(header_value);
-            } else {
-                warn!("Invalid CORS origin: {}", origin);
-            }
-        }
-        
-        cors_builder
-    };
-
-    // Build the application with optimized routes
-    let mut app = Router::<AppState>::new()
-        // Health check
-        .route("/health", get(health_check))
-        
-        // Auth routes (if Tezos auth is enabled)
-        .merge(auth_routes::create_auth_router())
-        
-        // Symbol management
-        .route("/api/symbols", get(get_symbols))
-        .route("/api/symbols/search", get(search_symbols))
-        .route("/api/symbols/:symbol/validate", get(validate_symbol))
-        
-        // Historical data
-        .route("/api/symbols/:symbol/historical", get(get_historical_data))
-        .route("/api/symbols/:symbol/fetch", post(fetch_historical_data))
-        .route("/api/bulk/historical", get(bulk_fetch_historical))
-        
-        // Real-time quotes
-        .route("/api/symbols/:symbol/quote", get(get_real_time_quote))
-        
-        // Company profiles
-        .route("/api/symbols/:symbol/profile", get(get_company_profile))
-        
-        // Comprehensive data
-        .route("/api/symbols/:symbol/overview", get(get_symbol_overview))
-        .route("/api/symbols/:symbol/analysis", get(get_price_analysis))
-        .route("/api/symbols/:symbol/comprehensive", get(get_comprehensive_quote))
-        .route("/api/symbols/:symbol/extended", get(get_extended_quote_data))
-        .route("/api/symbols/:symbol/indicators", get(get_technical_indicators))
-        
-        // Comparison and advanced analytics
-        .route("/api/compare", get(compare_symbols))
-        
-        // Statistics and monitoring
-        .route("/api/stats", get(get_database_stats))
-        
-        // Portfolio endpoints
-        .route("/api/portfolio", get(get_portfolio))
-        .route("/api/portfolio/holdings", post(add_portfolio_holding))
-        .route("/api/portfolio/holdings/:holding_id", put(update_portfolio_holding))
-        .route("/api/portfolio/holdings/:holding_id", delete(delete_portfolio_holding))
-        .route("/api/portfolio/update-prices", post(update_portfolio_prices))
-        
-        // Admin endpoints
-        .route("/api/admin/cache/cleanup", post(cleanup_cache));
-        
-    // Add web UI routes if feature is enabled
-    #[cfg(feature = "web-ui")]
-    {
-        use axum::middleware;
-        
-        // Serve static files (favicon, etc.) with proper cache headers
-        let static_files = Router::new()
-            .route("/favicon.svg", get(web_ui::favicon))
-            .route("/favicon.ico", get(web_ui::favicon))
-            .nest_service("/static", ServeDir::new("static"))
-            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
-        
-        // Create protected routes with auth middleware and cache headers
-        let protected_routes = Router::new()
-            .route("/ui", get(web_ui::dashboard))
-            .route("/ui/search", get(web_ui::search))
-            .route("/ui/analytics", get(web_ui::analytics))
-            .route("/ui/backup", get(web_ui::backup))
-            .route("/api/backup/download", get(download_backup))
-            .route("/", get(web_ui::dashboard)) // Root redirects to dashboard
-            .route_layer(middleware::from_fn_with_state(
-                app_state.clone(),
-                auth_middleware::require_auth_middleware,
-            ))
-            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
-        
-        // Public login route with cache headers
-        let login_route = Router::new()
-            .route("/login", get(web_ui::login))
-            .layer(middleware::from_fn(web_ui::cache_headers_middleware));
-        
-        app = app
-            .merge(static_files)
-            .merge(protected_routes)
-            .merge(login_route);
-    }
-        
-    // Add basic API info route when web-ui is disabled
-    #[cfg(not(feature = "web-ui"))]
-    {
-        app = app
-            .route("/", get(|| async { "Mango Data Service API - Use /health for status or /api/* for endpoints" }));
-    }
-        
-    let app = app
-        // Fallback for 404
-        .fallback(handler_404)
-        
-        // Add middleware
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(cors)
-        )
-        
-        // Add shared state
-        .with_state(app_state);
+    let app = build_router(app_state);
 
     // Start the server
     let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -299,6 +88,7 @@ fn print_api_info() {
         info!("    GET  /ui                            - Interactive Dashboard");
         info!("    GET  /ui/search                     - Symbol Search Interface");
         info!("    GET  /ui/analytics                  - Financial Analytics Interface");
+        info!("    GET  /ui/compare?symbols=AAPL,MSFT  - Symbol Comparison Interface");
         info!("");
     }
     
@@ -308,32 +98,77 @@ fn print_api_info() {
     info!("  Symbol Management:");
     info!("    GET  /api/symbols                    - List all symbols");
     info!("    GET  /api/symbols/search?q=QUERY    - Search symbols (optimized)");
+    info!("    GET  /api/symbols/suggest?q=AP      - Type-ahead suggestions (cached)");
     info!("    GET  /api/symbols/{{symbol}}/validate  - Validate symbol (cached)");
     info!("");
     info!("  Historical Data:");
     info!("    GET  /api/symbols/{{symbol}}/historical?interval=1d&limit=100&force_refresh=false");
+    info!("    GET  /api/symbols/{{symbol}}/klines?interval=1d&limit=500 - [time, o, h, l, c, v] arrays for charts");
+    info!("    GET  /udf/config, /udf/symbols, /udf/history - TradingView UDF datafeed");
     info!("    POST /api/symbols/{{symbol}}/fetch?interval=1d");
+    info!("    GET  /api/symbols/{{symbol}}/verify?range=1mo - Diff stored candles against upstream");
     info!("    GET  /api/bulk/historical?symbols=AAPL,MSFT&interval=1d&max_concurrent=5");
     info!("");
     info!("  Real-time Data:");
     info!("    GET  /api/symbols/{{symbol}}/quote     - Latest quote (cached)");
     info!("");
     info!("  Company Information:");
-    info!("    GET  /api/symbols/{{symbol}}/profile   - Company profile (cached)");
+    info!("    GET  /api/symbols/{{symbol}}/profile   - Company profile (cached, ?as_of=YYYY-MM-DD)");
+    info!("    GET  /api/symbols/{{symbol}}/holders   - Top institutional and fund holders (cached daily)");
+    info!("    GET  /api/symbols/{{symbol}}/etf       - ETF top holdings and sector weights (cached daily)");
+    info!("    GET  /api/symbols/{{symbol}}/listings  - Quotes of all exchange listings in one currency (?currency=USD)");
     info!("");
     info!("  Analytics:");
     info!("    GET  /api/symbols/{{symbol}}/overview  - Comprehensive overview");
-    info!("    GET  /api/symbols/{{symbol}}/analysis?limit=30 - Price analysis (optimized)");
+    info!("    GET  /api/symbols/{{symbol}}/analysis?limit=30&benchmark=SPY - Price analysis with beta");
     info!("    GET  /api/symbols/{{symbol}}/comprehensive - Comprehensive quote");
     info!("    GET  /api/symbols/{{symbol}}/extended - Extended quote data");
     info!("    GET  /api/symbols/{{symbol}}/indicators - Technical indicators");
     info!("");
     info!("  Comparison:");
-    info!("    GET  /api/compare?symbol1=AAPL&symbol2=MSFT - Compare two symbols");
+    info!("    GET  /api/compare?symbols=AAPL,MSFT&period=100 - Compare symbols");
+    info!("    GET  /api/sectors                   - Sectors in stored symbols");
+    info!("    GET  /api/sectors/{{sector}}/performance - Sector average change and breadth");
+    info!("    GET  /api/market/movers?universe=stored&limit=20 - Top gainers, losers, volume surges (stored|portfolio|<universe>)");
+    info!("    GET  /api/market/status?exchanges=US,LSE - Market sessions and next open/close");
+    info!("");
+    info!("  Tools (LLM agents):");
+    info!("    GET  /api/tools                     - Tool discovery with JSON Schemas");
+    info!("    POST /api/tools/{{name}}              - Invoke get_quote, get_historical, get_indicators");
+    info!("    POST /api/query                     - Natural-language query, e.g. \"AAPL vs MSFT last 6 months\"");
+    #[cfg(feature = "analytics")]
+    info!("    POST /api/analytics/sql             - Read-only SQL (ANALYTICS_SQL_ENABLED)");
+    info!("");
+    info!("  Dashboards:");
+    info!("    GET  /api/dashboards                - Saved /ui layouts (CRUD at /api/dashboards/{{id}})");
+    info!("");
+    info!("  Jobs:");
+    info!("    POST /api/jobs                      - Queue fetch_historical or bulk_historical");
+    info!("    GET  /api/jobs/{{id}}                  - Job status and result");
+    info!("");
+    info!("  Aliases:");
+    info!("    GET  /api/aliases                   - Symbol aliases (PUT/DELETE /api/aliases/{{alias}})");
+    info!("    GET  /api/symbols/{{symbol}}/resolve   - Show which ticker a symbol resolves to");
+    info!("    GET  /api/lookup?isin=|cusip=        - Ticker for an ISIN or CUSIP (stored once found)");
+    info!("");
+    info!("  Alerts:");
+    info!("    GET  /api/alerts/signals            - Technical signal alerts (POST, DELETE /api/alerts/signals/{{id}})");
+    info!("");
+    info!("  Universes:");
+    info!("    GET  /api/universes                 - Index constituent lists (details at /api/universes/{{name}})");
+    info!("    POST /api/universes/{{name}}/import   - Upload a constituent CSV (?mode=replace|append)");
     info!("");
     info!("  System:");
     info!("    GET  /api/stats                      - Database & cache statistics");
+    info!("    GET  /api/stats/history              - Cache & provider activity over time");
     info!("    POST /api/admin/cache/cleanup        - Manual cache cleanup");
+    info!("    GET  /api/admin/audit                - Audit log of logins and changes");
+    info!("    GET  /api/admin/latency              - Route & provider latency percentiles");
+    info!("    DELETE /api/admin/demo               - Remove DEMO_MODE sample data");
+    info!("    GET  /api/admin/export               - Stream a JSON archive of the database");
+    info!("    POST /api/admin/import               - Load a JSON archive (?mode=merge|replace)");
+    info!("    GET  /api/admin/webhooks             - Webhook subscriptions (CRUD, deliveries, test)");
+    info!("    GET  /api/admin/notifications        - Email/Telegram notification history");
     info!("");
     info!("  🛡️  Rate Limits:");
     info!("    - API: 100 requests/minute (burst: 10)");
@@ -351,11 +186,11 @@ fn print_api_info() {
     info!("  curl http://localhost:3000/api/symbols/AAPL/overview");
     info!("  curl -X POST http://localhost:3000/api/symbols/AAPL/fetch?interval=1d");
     info!("  curl http://localhost:3000/api/symbols/search?q=apple&limit=5");
-    info!("  curl http://localhost:3000/api/compare?symbol1=AAPL&symbol2=MSFT");
+    info!("  curl http://localhost:3000/api/compare?symbols=AAPL,MSFT");
     info!("");
     info!("🔧 Optimizations Active:");
     info!("  - Cow (Clone on Write) for zero-copy string operations");
-    info!("  - DashMap for concurrent caching");
+    info!("  - Byte-bounded TinyLFU caches (moka) with hit/miss stats");
     info!("  - Governor for rate limiting");
     info!("  - Automatic cache cleanup every hour");
     info!("  - Optimized bulk operations with semaphore control");
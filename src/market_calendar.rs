@@ -0,0 +1,698 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Embedded exchange calendars. The US, London and Xetra calendars carry holidays; Tokyo and
+// Hong Kong are modelled with their regular hours and weekend closures.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    Pre,
+    Regular,
+    Post,
+    Closed,
+}
+
+impl SessionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionKind::Pre => "pre",
+            SessionKind::Regular => "regular",
+            SessionKind::Post => "post",
+            SessionKind::Closed => "closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OffsetRule {
+    Fixed(i32),
+    /// US Eastern: DST from the second Sunday of March to the first Sunday of November
+    UsEastern,
+    /// EU/UK: DST from the last Sunday of March to the last Sunday of October (01:00 UTC)
+    European { standard: i32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Session {
+    kind: SessionKind,
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+#[derive(Debug)]
+pub struct ExchangeCalendar {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub timezone: &'static str,
+    offset: OffsetRule,
+    sessions: &'static [Session],
+    holidays: &'static [((i32, u32, u32), &'static str)],
+    early_closes: &'static [(i32, u32, u32)],
+    /// Local end of the regular session on early-close days; later sessions shift accordingly
+    early_close: Option<(u32, u32)>,
+    /// Set once a date past the last listed holiday year has been warned about
+    stale_warned: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketStatus {
+    pub exchange: &'static str,
+    pub name: &'static str,
+    pub timezone: &'static str,
+    pub local_time: String,
+    pub session: SessionKind,
+    pub is_open: bool,
+    pub holiday: Option<&'static str>,
+    pub early_close: bool,
+    pub next_open: Option<DateTime<Utc>>,
+    pub next_close: Option<DateTime<Utc>>,
+    pub holidays_tracked: bool,
+}
+
+const US_SESSIONS: &[Session] = &[
+    Session { kind: SessionKind::Pre, start: (4, 0), end: (9, 30) },
+    Session { kind: SessionKind::Regular, start: (9, 30), end: (16, 0) },
+    Session { kind: SessionKind::Post, start: (16, 0), end: (20, 0) },
+];
+
+// NYSE/NASDAQ full-day closures
+const US_HOLIDAYS: &[((i32, u32, u32), &str)] = &[
+    ((2024, 1, 1), "New Year's Day"),
+    ((2024, 1, 15), "Martin Luther King Jr. Day"),
+    ((2024, 2, 19), "Washington's Birthday"),
+    ((2024, 3, 29), "Good Friday"),
+    ((2024, 5, 27), "Memorial Day"),
+    ((2024, 6, 19), "Juneteenth"),
+    ((2024, 7, 4), "Independence Day"),
+    ((2024, 9, 2), "Labor Day"),
+    ((2024, 11, 28), "Thanksgiving Day"),
+    ((2024, 12, 25), "Christmas Day"),
+    ((2025, 1, 1), "New Year's Day"),
+    ((2025, 1, 9), "National Day of Mourning"),
+    ((2025, 1, 20), "Martin Luther King Jr. Day"),
+    ((2025, 2, 17), "Washington's Birthday"),
+    ((2025, 4, 18), "Good Friday"),
+    ((2025, 5, 26), "Memorial Day"),
+    ((2025, 6, 19), "Juneteenth"),
+    ((2025, 7, 4), "Independence Day"),
+    ((2025, 9, 1), "Labor Day"),
+    ((2025, 11, 27), "Thanksgiving Day"),
+    ((2025, 12, 25), "Christmas Day"),
+    ((2026, 1, 1), "New Year's Day"),
+    ((2026, 1, 19), "Martin Luther King Jr. Day"),
+    ((2026, 2, 16), "Washington's Birthday"),
+    ((2026, 4, 3), "Good Friday"),
+    ((2026, 5, 25), "Memorial Day"),
+    ((2026, 6, 19), "Juneteenth"),
+    ((2026, 7, 3), "Independence Day (observed)"),
+    ((2026, 9, 7), "Labor Day"),
+    ((2026, 11, 26), "Thanksgiving Day"),
+    ((2026, 12, 25), "Christmas Day"),
+    ((2027, 1, 1), "New Year's Day"),
+    ((2027, 1, 18), "Martin Luther King Jr. Day"),
+    ((2027, 2, 15), "Washington's Birthday"),
+    ((2027, 3, 26), "Good Friday"),
+    ((2027, 5, 31), "Memorial Day"),
+    ((2027, 6, 18), "Juneteenth (observed)"),
+    ((2027, 7, 5), "Independence Day (observed)"),
+    ((2027, 9, 6), "Labor Day"),
+    ((2027, 11, 25), "Thanksgiving Day"),
+    ((2027, 12, 24), "Christmas Day (observed)"),
+];
+
+const US_EARLY_CLOSES: &[(i32, u32, u32)] = &[
+    (2024, 7, 3),
+    (2024, 11, 29),
+    (2024, 12, 24),
+    (2025, 7, 3),
+    (2025, 11, 28),
+    (2025, 12, 24),
+    (2026, 11, 27),
+    (2026, 12, 24),
+    (2027, 11, 26),
+];
+
+// London full-day closures (English bank holidays)
+const LSE_HOLIDAYS: &[((i32, u32, u32), &str)] = &[
+    ((2024, 1, 1), "New Year's Day"),
+    ((2024, 3, 29), "Good Friday"),
+    ((2024, 4, 1), "Easter Monday"),
+    ((2024, 5, 6), "Early May Bank Holiday"),
+    ((2024, 5, 27), "Spring Bank Holiday"),
+    ((2024, 8, 26), "Summer Bank Holiday"),
+    ((2024, 12, 25), "Christmas Day"),
+    ((2024, 12, 26), "Boxing Day"),
+    ((2025, 1, 1), "New Year's Day"),
+    ((2025, 4, 18), "Good Friday"),
+    ((2025, 4, 21), "Easter Monday"),
+    ((2025, 5, 5), "Early May Bank Holiday"),
+    ((2025, 5, 26), "Spring Bank Holiday"),
+    ((2025, 8, 25), "Summer Bank Holiday"),
+    ((2025, 12, 25), "Christmas Day"),
+    ((2025, 12, 26), "Boxing Day"),
+    ((2026, 1, 1), "New Year's Day"),
+    ((2026, 4, 3), "Good Friday"),
+    ((2026, 4, 6), "Easter Monday"),
+    ((2026, 5, 4), "Early May Bank Holiday"),
+    ((2026, 5, 25), "Spring Bank Holiday"),
+    ((2026, 8, 31), "Summer Bank Holiday"),
+    ((2026, 12, 25), "Christmas Day"),
+    ((2026, 12, 28), "Boxing Day (substitute)"),
+    ((2027, 1, 1), "New Year's Day"),
+    ((2027, 3, 26), "Good Friday"),
+    ((2027, 3, 29), "Easter Monday"),
+    ((2027, 5, 3), "Early May Bank Holiday"),
+    ((2027, 5, 31), "Spring Bank Holiday"),
+    ((2027, 8, 30), "Summer Bank Holiday"),
+    ((2027, 12, 27), "Christmas Day (substitute)"),
+    ((2027, 12, 28), "Boxing Day (substitute)"),
+];
+
+// London closes at 12:30 on Christmas Eve and New Year's Eve
+const LSE_EARLY_CLOSES: &[(i32, u32, u32)] = &[
+    (2024, 12, 24),
+    (2024, 12, 31),
+    (2025, 12, 24),
+    (2025, 12, 31),
+    (2026, 12, 24),
+    (2026, 12, 31),
+    (2027, 12, 24),
+    (2027, 12, 31),
+];
+
+// Frankfurt Stock Exchange trading holidays that fall on weekdays
+const XETRA_HOLIDAYS: &[((i32, u32, u32), &str)] = &[
+    ((2024, 1, 1), "New Year's Day"),
+    ((2024, 3, 29), "Good Friday"),
+    ((2024, 4, 1), "Easter Monday"),
+    ((2024, 5, 1), "Labour Day"),
+    ((2024, 12, 24), "Christmas Eve"),
+    ((2024, 12, 25), "Christmas Day"),
+    ((2024, 12, 26), "Boxing Day"),
+    ((2024, 12, 31), "New Year's Eve"),
+    ((2025, 1, 1), "New Year's Day"),
+    ((2025, 4, 18), "Good Friday"),
+    ((2025, 4, 21), "Easter Monday"),
+    ((2025, 5, 1), "Labour Day"),
+    ((2025, 12, 24), "Christmas Eve"),
+    ((2025, 12, 25), "Christmas Day"),
+    ((2025, 12, 26), "Boxing Day"),
+    ((2025, 12, 31), "New Year's Eve"),
+    ((2026, 1, 1), "New Year's Day"),
+    ((2026, 4, 3), "Good Friday"),
+    ((2026, 4, 6), "Easter Monday"),
+    ((2026, 5, 1), "Labour Day"),
+    ((2026, 12, 24), "Christmas Eve"),
+    ((2026, 12, 25), "Christmas Day"),
+    ((2026, 12, 31), "New Year's Eve"),
+    ((2027, 1, 1), "New Year's Day"),
+    ((2027, 3, 26), "Good Friday"),
+    ((2027, 3, 29), "Easter Monday"),
+    ((2027, 12, 24), "Christmas Eve"),
+    ((2027, 12, 31), "New Year's Eve"),
+];
+
+pub static EXCHANGES: [ExchangeCalendar; 5] = [
+    ExchangeCalendar {
+        code: "US",
+        name: "NYSE / NASDAQ",
+        timezone: "America/New_York",
+        offset: OffsetRule::UsEastern,
+        sessions: US_SESSIONS,
+        holidays: US_HOLIDAYS,
+        early_closes: US_EARLY_CLOSES,
+        early_close: Some((13, 0)),
+        stale_warned: AtomicBool::new(false),
+    },
+    ExchangeCalendar {
+        code: "LSE",
+        name: "London Stock Exchange",
+        timezone: "Europe/London",
+        offset: OffsetRule::European { standard: 0 },
+        sessions: &[Session { kind: SessionKind::Regular, start: (8, 0), end: (16, 30) }],
+        holidays: LSE_HOLIDAYS,
+        early_closes: LSE_EARLY_CLOSES,
+        early_close: Some((12, 30)),
+        stale_warned: AtomicBool::new(false),
+    },
+    ExchangeCalendar {
+        code: "XETRA",
+        name: "Deutsche Börse Xetra",
+        timezone: "Europe/Berlin",
+        offset: OffsetRule::European { standard: 3600 },
+        sessions: &[Session { kind: SessionKind::Regular, start: (9, 0), end: (17, 30) }],
+        holidays: XETRA_HOLIDAYS,
+        early_closes: &[],
+        early_close: None,
+        stale_warned: AtomicBool::new(false),
+    },
+    ExchangeCalendar {
+        code: "TSE",
+        name: "Tokyo Stock Exchange",
+        timezone: "Asia/Tokyo",
+        offset: OffsetRule::Fixed(9 * 3600),
+        sessions: &[
+            Session { kind: SessionKind::Regular, start: (9, 0), end: (11, 30) },
+            Session { kind: SessionKind::Regular, start: (12, 30), end: (15, 30) },
+        ],
+        holidays: &[],
+        early_closes: &[],
+        early_close: None,
+        stale_warned: AtomicBool::new(false),
+    },
+    ExchangeCalendar {
+        code: "HKEX",
+        name: "Hong Kong Stock Exchange",
+        timezone: "Asia/Hong_Kong",
+        offset: OffsetRule::Fixed(8 * 3600),
+        sessions: &[
+            Session { kind: SessionKind::Regular, start: (9, 30), end: (12, 0) },
+            Session { kind: SessionKind::Regular, start: (13, 0), end: (16, 0) },
+        ],
+        holidays: &[],
+        early_closes: &[],
+        early_close: None,
+        stale_warned: AtomicBool::new(false),
+    },
+];
+
+/// Look up a calendar by its code (case-insensitive)
+pub fn calendar(code: &str) -> Option<&'static ExchangeCalendar> {
+    EXCHANGES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+/// Exchange calendar of a Yahoo ticker by suffix; `None` for exchanges without an embedded
+/// calendar. Unsuffixed tickers and single-letter share classes (`BRK.B`) are US listings.
+pub fn known_calendar_for_symbol(symbol: &str) -> Option<&'static ExchangeCalendar> {
+    let code = match symbol.rsplit_once('.').map(|(_, suffix)| suffix) {
+        Some("L") => "LSE",
+        Some("DE") | Some("F") => "XETRA",
+        Some("T") => "TSE",
+        Some("HK") => "HKEX",
+        None => "US",
+        Some(suffix) if suffix.len() == 1 => "US",
+        Some(_) => return None,
+    };
+    calendar(code)
+}
+
+/// Map a Yahoo ticker to its exchange calendar by suffix, defaulting to US
+pub fn calendar_for_symbol(symbol: &str) -> &'static ExchangeCalendar {
+    known_calendar_for_symbol(symbol).unwrap_or(&EXCHANGES[0])
+}
+
+/// Whether a background refresh of `symbol` is worthwhile: crypto trades around the clock,
+/// listings on exchanges without a calendar are always refreshed, and everything else only
+/// while a session is open or shortly after it closed.
+pub fn should_refresh(symbol: &str, asset_type: &str, now: DateTime<Utc>) -> bool {
+    if asset_type.eq_ignore_ascii_case("crypto") {
+        return true;
+    }
+    let Some(calendar) = known_calendar_for_symbol(symbol) else {
+        return true;
+    };
+    let grace = Duration::minutes(15);
+    calendar.session_at(now) != SessionKind::Closed
+        || calendar.session_at(now - grace) != SessionKind::Closed
+}
+
+/// Timezone used to present timestamps: UTC, a fixed offset, or an exchange's DST-aware zone
+#[derive(Debug, Clone, Copy)]
+pub enum DisplayZone {
+    Utc,
+    Fixed(FixedOffset),
+    Exchange(&'static ExchangeCalendar),
+}
+
+impl DisplayZone {
+    /// Parse "UTC", a fixed offset such as "+09:00" / "-0500", or a supported IANA name
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("utc") || name == "Z" || name.eq_ignore_ascii_case("etc/utc") {
+            return Some(DisplayZone::Utc);
+        }
+
+        if let Some(sign) = name.chars().next().filter(|c| *c == '+' || *c == '-') {
+            let digits: String = name[1..].chars().filter(|c| *c != ':').collect();
+            if !(digits.len() == 2 || digits.len() == 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let hours: i32 = digits[..2].parse().ok()?;
+            let minutes: i32 = if digits.len() == 4 { digits[2..].parse().ok()? } else { 0 };
+            let seconds = (hours * 3600 + minutes * 60) * if sign == '-' { -1 } else { 1 };
+            return FixedOffset::east_opt(seconds).map(DisplayZone::Fixed);
+        }
+
+        EXCHANGES
+            .iter()
+            .find(|c| c.timezone.eq_ignore_ascii_case(name))
+            .map(DisplayZone::Exchange)
+    }
+
+    /// Zone of a symbol's exchange: its stored IANA name when supported, else the stored
+    /// offset, else the calendar implied by the ticker suffix
+    pub fn for_symbol(symbol: &str, timezone: Option<&str>, utc_offset_seconds: Option<i32>) -> Self {
+        timezone
+            .and_then(DisplayZone::parse)
+            .or_else(|| utc_offset_seconds.and_then(FixedOffset::east_opt).map(DisplayZone::Fixed))
+            .unwrap_or_else(|| DisplayZone::Exchange(calendar_for_symbol(symbol)))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            DisplayZone::Utc => "UTC".to_string(),
+            DisplayZone::Fixed(offset) => offset.to_string(),
+            DisplayZone::Exchange(calendar) => calendar.timezone.to_string(),
+        }
+    }
+
+    pub fn offset_at(&self, utc: DateTime<Utc>) -> FixedOffset {
+        match self {
+            DisplayZone::Utc => FixedOffset::east_opt(0).expect("valid offset"),
+            DisplayZone::Fixed(offset) => *offset,
+            DisplayZone::Exchange(calendar) => calendar.offset_at(utc),
+        }
+    }
+
+    pub fn localize(&self, utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+        utc.with_timezone(&self.offset_at(utc))
+    }
+
+    /// Midnight of `date` in this zone, as a UTC instant
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let noon = date.and_hms_opt(12, 0, 0).expect("valid time").and_utc();
+        self.offset_at(noon)
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .single()
+            .expect("fixed offsets are unambiguous")
+            .with_timezone(&Utc)
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid nth weekday")
+}
+
+fn last_sunday(year: i32, month: u32) -> NaiveDate {
+    let mut date = NaiveDate::from_ymd_opt(year, month + 1, 1).expect("valid month") - Duration::days(1);
+    while date.weekday() != Weekday::Sun {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+impl OffsetRule {
+    fn offset_at(&self, utc: DateTime<Utc>) -> FixedOffset {
+        let seconds = match *self {
+            OffsetRule::Fixed(seconds) => seconds,
+            OffsetRule::UsEastern => {
+                let year = utc.year();
+                // Transitions happen at 02:00 local, i.e. 07:00 UTC in March and 06:00 UTC in November
+                let start = nth_weekday(year, 3, Weekday::Sun, 2).and_hms_opt(7, 0, 0).expect("valid time").and_utc();
+                let end = nth_weekday(year, 11, Weekday::Sun, 1).and_hms_opt(6, 0, 0).expect("valid time").and_utc();
+                if utc >= start && utc < end {
+                    -4 * 3600
+                } else {
+                    -5 * 3600
+                }
+            }
+            OffsetRule::European { standard } => {
+                let year = utc.year();
+                let start = last_sunday(year, 3).and_hms_opt(1, 0, 0).expect("valid time").and_utc();
+                let end = last_sunday(year, 10).and_hms_opt(1, 0, 0).expect("valid time").and_utc();
+                if utc >= start && utc < end {
+                    standard + 3600
+                } else {
+                    standard
+                }
+            }
+        };
+        FixedOffset::east_opt(seconds).expect("valid offset")
+    }
+}
+
+impl ExchangeCalendar {
+    /// Whether `date` falls within the years the embedded holiday list covers. Past the last
+    /// listed year the calendar still knows weekends and hours but not holidays, which is
+    /// logged once per exchange.
+    pub fn holidays_tracked(&self, date: NaiveDate) -> bool {
+        let Some(last_year) = self.holidays.iter().map(|((year, _, _), _)| *year).max() else {
+            return false;
+        };
+        let tracked = date.year() <= last_year;
+        if !tracked && !self.stale_warned.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                "{} holidays are only listed through {}; sessions on {} ignore holidays",
+                self.code,
+                last_year,
+                date
+            );
+        }
+        tracked
+    }
+
+    /// Regular sessions in exchange-local time as `HHMM-HHMM` ranges joined by commas, e.g.
+    /// `0900-1130,1230-1530` for Tokyo's lunch break
+    pub fn regular_hours(&self) -> String {
+        self.sessions
+            .iter()
+            .filter(|session| session.kind == SessionKind::Regular)
+            .map(|session| {
+                format!(
+                    "{:02}{:02}-{:02}{:02}",
+                    session.start.0, session.start.1, session.end.0, session.end.1
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Current UTC offset of the exchange's local time
+    pub fn offset_at(&self, utc: DateTime<Utc>) -> FixedOffset {
+        self.offset.offset_at(utc)
+    }
+
+    /// The exchange-local calendar date containing `utc`
+    pub fn local_date(&self, utc: DateTime<Utc>) -> NaiveDate {
+        utc.with_timezone(&self.offset_at(utc)).date_naive()
+    }
+
+    pub fn holiday(&self, date: NaiveDate) -> Option<&'static str> {
+        self.holidays
+            .iter()
+            .find(|((y, m, d), _)| date.year() == *y && date.month() == *m && date.day() == *d)
+            .map(|(_, name)| *name)
+    }
+
+    fn is_early_close(&self, date: NaiveDate) -> bool {
+        self.early_closes
+            .iter()
+            .any(|(y, m, d)| date.year() == *y && date.month() == *m && date.day() == *d)
+    }
+
+    fn to_utc(&self, date: NaiveDate, (hour, minute): (u32, u32)) -> DateTime<Utc> {
+        let local = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0).expect("valid session time"));
+        // Sessions never straddle a DST switch, so the offset at local noon is always correct
+        let noon = date.and_hms_opt(12, 0, 0).expect("valid time").and_utc();
+        self.offset_at(noon)
+            .from_local_datetime(&local)
+            .single()
+            .expect("fixed offsets are unambiguous")
+            .with_timezone(&Utc)
+    }
+
+    /// Session windows for a local date; empty on weekends and holidays
+    fn sessions_on(&self, date: NaiveDate) -> Vec<(SessionKind, DateTime<Utc>, DateTime<Utc>)> {
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || self.holiday(date).is_some() {
+            return Vec::new();
+        }
+
+        let early = self.early_close.filter(|_| self.is_early_close(date));
+        self.sessions
+            .iter()
+            .map(|session| {
+                let (start, end) = match (early, session.kind) {
+                    (Some(close), SessionKind::Regular) => (session.start, close),
+                    // Post-market keeps its length but starts at the early close
+                    (Some(close), SessionKind::Post) => {
+                        let length = (session.end.0 * 60 + session.end.1) - (session.start.0 * 60 + session.start.1);
+                        let end = close.0 * 60 + close.1 + length;
+                        (close, (end / 60, end % 60))
+                    }
+                    _ => (session.start, session.end),
+                };
+                (session.kind, self.to_utc(date, start), self.to_utc(date, end))
+            })
+            .collect()
+    }
+
+    /// The last trading day before `date` as the UTC span from its local midnight to the end
+    /// of its regular session, skipping weekends and known holidays
+    pub fn previous_session(&self, date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        (1..14).find_map(|days| {
+            let day = date - Duration::days(days);
+            let close = self
+                .sessions_on(day)
+                .into_iter()
+                .filter(|(kind, _, _)| *kind == SessionKind::Regular)
+                .map(|(_, _, end)| end)
+                .max()?;
+            Some((self.to_utc(day, (0, 0)), close))
+        })
+    }
+
+    pub fn session_at(&self, now: DateTime<Utc>) -> SessionKind {
+        self.sessions_on(self.local_date(now))
+            .into_iter()
+            .find(|(_, start, end)| now >= *start && now < *end)
+            .map(|(kind, _, _)| kind)
+            .unwrap_or(SessionKind::Closed)
+    }
+
+    /// Next regular session boundary of the given kind strictly after `now`
+    fn next_regular_boundary(&self, now: DateTime<Utc>, want_open: bool) -> Option<DateTime<Utc>> {
+        let today = self.local_date(now);
+        // Two weeks comfortably covers any run of weekends and holidays
+        (0..14).find_map(|days| {
+            self.sessions_on(today + Duration::days(days))
+                .into_iter()
+                .filter(|(kind, _, _)| *kind == SessionKind::Regular)
+                .map(|(_, start, end)| if want_open { start } else { end })
+                .find(|boundary| *boundary > now)
+        })
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> MarketStatus {
+        let offset = self.offset_at(now);
+        let local_date = self.local_date(now);
+        let session = self.session_at(now);
+
+        MarketStatus {
+            exchange: self.code,
+            name: self.name,
+            timezone: self.timezone,
+            local_time: now.with_timezone(&offset).to_rfc3339(),
+            session,
+            is_open: session == SessionKind::Regular,
+            holiday: self.holiday(local_date),
+            early_close: self.early_close.is_some() && self.is_early_close(local_date),
+            next_open: self.next_regular_boundary(now, true),
+            next_close: self.next_regular_boundary(now, false),
+            holidays_tracked: self.holidays_tracked(local_date),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn previous_session_skips_weekends_and_holidays() {
+        let us = calendar("US").unwrap();
+        // Tuesday -> Monday, ending at the 16:00 EDT close
+        assert_eq!(
+            us.previous_session(date(2026, 6, 16)),
+            Some((utc(2026, 6, 15, 4, 0), utc(2026, 6, 15, 20, 0)))
+        );
+        // Monday -> Friday across the weekend
+        assert_eq!(us.previous_session(date(2026, 6, 15)).unwrap().0, utc(2026, 6, 12, 4, 0));
+        // Monday after Good Friday -> Thursday
+        assert_eq!(us.previous_session(date(2026, 4, 6)).unwrap().0, utc(2026, 4, 2, 4, 0));
+        // Day after Thanksgiving closes early at 13:00 EST
+        assert_eq!(us.previous_session(date(2026, 11, 30)).unwrap().1, utc(2026, 11, 27, 18, 0));
+    }
+
+    #[test]
+    fn us_sessions_follow_eastern_time_across_dst() {
+        let us = calendar("US").unwrap();
+        // Wednesday 2026-01-14, EST (UTC-5)
+        assert_eq!(us.session_at(utc(2026, 1, 14, 8, 59)), SessionKind::Closed);
+        assert_eq!(us.session_at(utc(2026, 1, 14, 9, 0)), SessionKind::Pre);
+        assert_eq!(us.session_at(utc(2026, 1, 14, 14, 30)), SessionKind::Regular);
+        assert_eq!(us.session_at(utc(2026, 1, 14, 21, 0)), SessionKind::Post);
+        assert_eq!(us.session_at(utc(2026, 1, 15, 1, 0)), SessionKind::Closed);
+        // Wednesday 2026-07-15, EDT (UTC-4)
+        assert_eq!(us.session_at(utc(2026, 7, 15, 13, 29)), SessionKind::Pre);
+        assert_eq!(us.session_at(utc(2026, 7, 15, 13, 30)), SessionKind::Regular);
+        assert_eq!(us.session_at(utc(2026, 7, 15, 20, 0)), SessionKind::Post);
+    }
+
+    #[test]
+    fn split_sessions_close_for_lunch() {
+        let tse = calendar("TSE").unwrap();
+        // 11:45 JST on a Wednesday
+        assert_eq!(tse.session_at(utc(2026, 1, 14, 2, 45)), SessionKind::Closed);
+        assert_eq!(tse.session_at(utc(2026, 1, 14, 3, 30)), SessionKind::Regular);
+        assert_eq!(tse.regular_hours(), "0900-1130,1230-1530");
+    }
+
+    #[test]
+    fn weekends_are_closed_until_monday() {
+        let lse = calendar("LSE").unwrap();
+        let saturday = utc(2026, 6, 13, 10, 0);
+        let status = lse.status(saturday);
+        assert_eq!(status.session, SessionKind::Closed);
+        assert!(!status.is_open);
+        assert_eq!(status.holiday, None);
+        // 08:00 BST on Monday
+        assert_eq!(status.next_open, Some(utc(2026, 6, 15, 7, 0)));
+    }
+
+    #[test]
+    fn holidays_and_early_closes_change_the_day() {
+        let us = calendar("US").unwrap();
+        let thanksgiving = us.status(utc(2026, 11, 26, 15, 0));
+        assert_eq!(thanksgiving.session, SessionKind::Closed);
+        assert_eq!(thanksgiving.holiday, Some("Thanksgiving Day"));
+        assert_eq!(thanksgiving.next_open, Some(utc(2026, 11, 27, 14, 30)));
+
+        // The day after closes at 13:00 EST and post-market runs four hours from then
+        let friday = us.status(utc(2026, 11, 27, 15, 0));
+        assert!(friday.early_close);
+        assert_eq!(friday.next_close, Some(utc(2026, 11, 27, 18, 0)));
+        assert_eq!(us.session_at(utc(2026, 11, 27, 18, 0)), SessionKind::Post);
+        assert_eq!(us.session_at(utc(2026, 11, 27, 22, 0)), SessionKind::Closed);
+
+        // Xetra closes on Christmas Eve, which is an ordinary day in New York
+        assert_eq!(calendar("XETRA").unwrap().holiday(date(2026, 12, 24)), Some("Christmas Eve"));
+        assert_eq!(us.holiday(date(2026, 12, 24)), None);
+    }
+
+    #[test]
+    fn holidays_are_tracked_only_through_the_last_listed_year() {
+        let us = calendar("US").unwrap();
+        assert!(us.holidays_tracked(date(2027, 12, 31)));
+        assert!(!us.holidays_tracked(date(2028, 1, 3)));
+        assert!(!us.status(utc(2028, 1, 3, 15, 0)).holidays_tracked);
+        assert!(us.status(utc(2026, 1, 14, 15, 0)).holidays_tracked);
+        assert!(!calendar("HKEX").unwrap().holidays_tracked(date(2026, 1, 14)));
+    }
+
+    #[test]
+    fn should_refresh_only_around_open_markets() {
+        let saturday = utc(2026, 6, 13, 15, 0);
+        assert!(should_refresh("BTC-USD", "crypto", saturday));
+        // No calendar for Toronto listings, so they are always refreshed
+        assert!(should_refresh("SHOP.TO", "stock", saturday));
+        assert!(!should_refresh("AAPL", "stock", saturday));
+        assert!(!should_refresh("VOD.L", "stock", saturday));
+
+        // Regular session, within the grace period after the 20:00 EDT post-market close,
+        // and after it
+        assert!(should_refresh("AAPL", "stock", utc(2026, 6, 15, 15, 0)));
+        assert!(should_refresh("AAPL", "stock", utc(2026, 6, 16, 0, 10)));
+        assert!(!should_refresh("AAPL", "stock", utc(2026, 6, 16, 0, 20)));
+        assert!(!should_refresh("AAPL", "stock", utc(2026, 11, 26, 15, 0)));
+    }
+}
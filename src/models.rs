@@ -1,11 +1,14 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+use crate::errors::ErrorCode;
+use crate::indicators::SignalCondition;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
     pub id: Uuid,
     pub symbol: String,
@@ -13,41 +16,103 @@ pub struct Symbol {
     pub exchange: Option<String>,
     pub sector: Option<String>,
     pub industry: Option<String>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub market_cap: Option<Decimal>,
+    pub timezone: Option<String>, // IANA name of the exchange timezone, e.g. "America/New_York"
+    pub utc_offset_seconds: Option<i32>, // Exchange UTC offset at the last fetch
+    pub currency: Option<String>, // Quoted currency, possibly a minor unit such as "GBp"
+    #[serde(default)]
+    pub status: SymbolStatus,
+    pub status_changed_at: Option<DateTime<Utc>>, // When the symbol was last delisted or reactivated
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+/// Whether the provider still has data for a stored symbol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolStatus {
+    #[default]
+    Active,
+    /// The provider returned no data for `DELISTING_EMPTY_FETCHES` fetches in a row; background
+    /// refreshes skip the symbol until a fetch returns data again
+    Delisted,
+}
+
+impl SymbolStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolStatus::Active => "active",
+            SymbolStatus::Delisted => "delisted",
+        }
+    }
+}
+
+impl std::str::FromStr for SymbolStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(SymbolStatus::Active),
+            "delisted" => Ok(SymbolStatus::Delisted),
+            other => Err(format!("Unknown symbol status '{}'. Use active or delisted", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoricalPrice {
     pub id: Uuid,
     pub symbol_id: Uuid,
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub open: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub high: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub low: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub close: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub adjusted_close: Option<Decimal>,
     pub volume: i64,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeQuote {
     pub id: Uuid,
     pub symbol_id: Uuid,
     pub symbol: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub change: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub change_percent: Option<Decimal>,
     pub volume: Option<i64>,
     pub market_time: DateTime<Utc>,
-    pub trading_session: String, // "regular", "pre", "post"
+    pub trading_session: String, // "regular", "pre", "post" or "closed"
+    /// Last regular-session price, kept for pre- and post-market quotes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub regular_price: Option<Decimal>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dividend {
+    pub id: Uuid,
+    pub symbol_id: Uuid,
+    pub symbol: String,
+    pub ex_date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub amount: Decimal, // Per share
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompanyProfile {
     pub id: Uuid,
     pub symbol_id: Uuid,
@@ -68,31 +133,122 @@ pub struct CompanyProfile {
     pub updated_at: DateTime<Utc>,
 }
 
-// Optimized request structures using Cow for zero-copy when possible
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PriceRequest<'a> {
-    pub symbol: Cow<'a, str>,
-    pub interval: Option<Cow<'a, str>>, // "1d", "5d", "1mo", "3mo", "6mo", "1y", "2y", "5y", "10y", "ytd", "max"
-    pub start_date: Option<DateTime<Utc>>,
-    pub end_date: Option<DateTime<Utc>>,
-}
-
 // Optimized response structures using Cow
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteResponse<'a> {
     pub symbol: Cow<'a, str>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub change: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub change_percent: Option<Decimal>,
     pub volume: Option<i64>,
     pub market_time: DateTime<Utc>,
     pub trading_session: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub regular_price: Option<Decimal>,
+}
+
+/// One captured quote in a quote history; repeated captures of the same `market_time`
+/// are collapsed into the latest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteTick {
+    pub market_time: DateTime<Utc>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_percent: Option<Decimal>,
+    pub volume: Option<i64>,
+    pub trading_session: String,
+}
+
+impl From<RealTimeQuote> for QuoteTick {
+    fn from(quote: RealTimeQuote) -> Self {
+        Self {
+            market_time: quote.market_time,
+            price: quote.price,
+            change: quote.change,
+            change_percent: quote.change_percent,
+            volume: quote.volume,
+            trading_session: quote.trading_session,
+        }
+    }
+}
+
+/// A page of the quotes this service captured for a symbol, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteHistory {
+    pub symbol: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub ticks: Vec<QuoteTick>,
+    /// Distinct ticks in the window, across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoricalResponse<'a> {
     pub symbol: Cow<'a, str>,
-    pub data: Vec<HistoricalPrice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Cow<'a, str>>,
+    /// Currency prices were converted to with `base_currency`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Cow<'a, str>>,
+    /// `captured` when the candles were built from quotes stored by this service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Cow<'a, str>>,
+    pub data: Vec<HistoricalCandle>,
+    pub count: usize,
+}
+
+/// A historical price, optionally annotated with its time in a requested timezone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalCandle {
+    #[serde(flatten)]
+    pub price: HistoricalPrice,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_timestamp: Option<DateTime<FixedOffset>>,
+    /// Calendar date of the candle in the requested timezone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trading_date: Option<NaiveDate>,
+}
+
+/// One candle as `[time, open, high, low, close, volume]`, the array shape of Binance klines
+/// and TradingView Lightweight Charts data
+#[derive(Debug, Clone, Serialize)]
+pub struct Kline(pub i64, pub f64, pub f64, pub f64, pub f64, pub i64);
+
+impl Kline {
+    /// `time` is the candle's Unix timestamp in seconds, or milliseconds with `millis`
+    pub fn from_price(price: &HistoricalPrice, millis: bool) -> Self {
+        use rust_decimal::prelude::ToPrimitive;
+
+        let time = if millis { price.timestamp.timestamp_millis() } else { price.timestamp.timestamp() };
+        Kline(
+            time,
+            price.open.to_f64().unwrap_or_default(),
+            price.high.to_f64().unwrap_or_default(),
+            price.low.to_f64().unwrap_or_default(),
+            price.close.to_f64().unwrap_or_default(),
+            price.volume,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KlinesResponse {
+    pub symbol: String,
+    pub interval: String,
+    /// "s" or "ms", the unit of each kline's time
+    pub time_unit: &'static str,
+    /// Oldest first
+    pub data: Vec<Kline>,
     pub count: usize,
 }
 
@@ -100,6 +256,9 @@ pub struct HistoricalResponse<'a> {
 pub struct ProfileResponse<'a> {
     pub symbol: Cow<'a, str>,
     pub profile: Option<CompanyProfile>,
+    /// Date of the point-in-time snapshot returned for an `as_of` request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<NaiveDate>,
 }
 
 // Data transfer objects for API responses
@@ -108,6 +267,9 @@ pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<Cow<'static, str>>,
+    /// Machine-readable reason, set whenever `success` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -117,15 +279,17 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
             timestamp: Utc::now(),
         }
     }
 
-    pub fn error(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn failure(code: ErrorCode, message: impl Into<Cow<'static, str>>) -> Self {
         Self {
             success: false,
             data: None,
             error: Some(message.into()),
+            code: Some(code),
             timestamp: Utc::now(),
         }
     }
@@ -254,6 +418,14 @@ impl RealTimeQuote {
         symbol_id: Uuid,
         quote: &yahoo_finance_api::Quote,
     ) -> Self {
+        let market_time = DateTime::from_timestamp(quote.timestamp as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(&Utc);
+        // Labelled by the exchange session the trade fell in
+        let trading_session = crate::market_calendar::calendar_for_symbol(&symbol)
+            .session_at(market_time)
+            .as_str()
+            .to_string();
         Self {
             id: Uuid::new_v4(),
             symbol_id,
@@ -262,13 +434,468 @@ impl RealTimeQuote {
             change: None,
             change_percent: None,
             volume: Some(quote.volume as i64),
-            market_time: DateTime::from_timestamp(quote.timestamp as i64, 0)
-                .unwrap_or_default()
-                .with_timezone(&Utc),
-            trading_session: "regular".to_string(),
+            market_time,
+            trading_session,
+            regular_price: None,
             created_at: Utc::now(),
         }
     }
+
+    /// Fill in the change against the previous session's close
+    pub fn with_previous_close(mut self, previous_close: Option<Decimal>) -> Self {
+        if let Some(previous_close) = previous_close.filter(|c| !c.is_zero()) {
+            let change = (self.price - previous_close).round_dp(6).normalize();
+            self.change = Some(change);
+            self.change_percent = Some((change / previous_close * Decimal::from(100)).round_dp(4));
+        }
+        self
+    }
+}
+
+/// Server-side aggregation period for stored daily candles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResamplePeriod {
+    Weekly,
+    Monthly,
+}
+
+impl std::str::FromStr for ResamplePeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1wk" => Ok(ResamplePeriod::Weekly),
+            "1mo" => Ok(ResamplePeriod::Monthly),
+            other => Err(format!("Unsupported resample period '{}'. Use '1wk' or '1mo'", other)),
+        }
+    }
+}
+
+impl ResamplePeriod {
+    /// Bucket key for a trading date: ISO week start (Monday) or first of the month
+    fn bucket(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ResamplePeriod::Weekly => {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            ResamplePeriod::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Aggregate daily candles into periods: open of the first bar, high max, low min,
+    /// close of the last bar and summed volume. Each period keeps the id and timestamp of its
+    /// first daily bar. `trading_date` maps a bar to the calendar date used for bucketing.
+    /// Input may be in any order; output is newest first, matching the historical endpoint.
+    pub fn resample(
+        &self,
+        prices: &[HistoricalPrice],
+        trading_date: impl Fn(&HistoricalPrice) -> NaiveDate,
+    ) -> Vec<HistoricalPrice> {
+        let mut sorted: Vec<&HistoricalPrice> = prices.iter().collect();
+        sorted.sort_by_key(|p| p.timestamp);
+
+        let mut periods: Vec<(NaiveDate, HistoricalPrice)> = Vec::new();
+        for price in sorted {
+            let bucket = self.bucket(trading_date(price));
+            match periods.last_mut() {
+                Some((key, period)) if *key == bucket => {
+                    period.high = period.high.max(price.high);
+                    period.low = period.low.min(price.low);
+                    period.close = price.close;
+                    period.adjusted_close = price.adjusted_close;
+                    period.volume += price.volume;
+                }
+                _ => periods.push((bucket, price.clone())),
+            }
+        }
+
+        periods.into_iter().rev().map(|(_, period)| period).collect()
+    }
+}
+
+/// Calendar period `/api/symbols/{symbol}/stats` groups stored daily candles by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsBucket {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl std::str::FromStr for StatsBucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "weekly" => Ok(StatsBucket::Weekly),
+            "monthly" => Ok(StatsBucket::Monthly),
+            "quarterly" => Ok(StatsBucket::Quarterly),
+            "yearly" => Ok(StatsBucket::Yearly),
+            other => Err(format!(
+                "Unsupported bucket '{}'. Use weekly, monthly, quarterly or yearly",
+                other
+            )),
+        }
+    }
+}
+
+impl StatsBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatsBucket::Weekly => "weekly",
+            StatsBucket::Monthly => "monthly",
+            StatsBucket::Quarterly => "quarterly",
+            StatsBucket::Yearly => "yearly",
+        }
+    }
+}
+
+/// Aggregates of the stored daily candles in one bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodStats {
+    /// First day of the bucket (Monday for weeks)
+    pub period_start: NaiveDate,
+    pub first_timestamp: DateTime<Utc>,
+    pub last_timestamp: DateTime<Utc>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub open: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub high: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub low: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub close: Decimal,
+    /// Close over the previous bucket's close, or over this bucket's open for the first one
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub return_ratio: Option<Decimal>,
+    /// Annualized standard deviation of the daily returns in the bucket; needs two of them
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub volatility: Option<Decimal>,
+    pub volume: i64,
+    pub average_volume: i64,
+    pub candles: i64,
+}
+
+/// Per-bucket summary of a symbol's stored daily candles, newest bucket first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolStats {
+    pub symbol: String,
+    pub bucket: String,
+    pub periods: Vec<PeriodStats>,
+}
+
+/// Width of the intraday candles synthesized from captured quotes with `source=captured`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureInterval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+}
+
+impl std::str::FromStr for CaptureInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(CaptureInterval::OneMinute),
+            "5m" => Ok(CaptureInterval::FiveMinutes),
+            "15m" => Ok(CaptureInterval::FifteenMinutes),
+            "30m" => Ok(CaptureInterval::ThirtyMinutes),
+            "1h" => Ok(CaptureInterval::OneHour),
+            other => Err(format!(
+                "Unsupported interval '{}' for captured candles. Use 1m, 5m, 15m, 30m or 1h",
+                other
+            )),
+        }
+    }
+}
+
+impl CaptureInterval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CaptureInterval::OneMinute => 60,
+            CaptureInterval::FiveMinutes => 300,
+            CaptureInterval::FifteenMinutes => 900,
+            CaptureInterval::ThirtyMinutes => 1800,
+            CaptureInterval::OneHour => 3600,
+        }
+    }
+
+    /// Bucket quotes by `market_time` into candles: open of the first tick, high max, low
+    /// min and close of the last. Quote volume is the day's running total, so a candle's
+    /// volume is how much that total grew from the tick before its first one to its last;
+    /// the first tick of the window adds nothing. Each candle keeps the id of its first
+    /// tick and is stamped with its bucket start. Input may be in any order; output is
+    /// newest first.
+    pub fn synthesize(&self, quotes: &[RealTimeQuote]) -> Vec<HistoricalPrice> {
+        let mut sorted: Vec<&RealTimeQuote> = quotes.iter().collect();
+        sorted.sort_by_key(|q| q.market_time);
+
+        let width = self.seconds();
+        let mut candles: Vec<(i64, HistoricalPrice)> = Vec::new();
+        let mut running: Option<i64> = None;
+        for quote in sorted {
+            // Volume traded since the previous tick; a drop in the running total is a new day
+            let traded = match (running, quote.volume) {
+                (Some(previous), Some(volume)) if volume >= previous => volume - previous,
+                (Some(_), Some(volume)) => volume,
+                _ => 0,
+            };
+            running = quote.volume.or(running);
+
+            let bucket = quote.market_time.timestamp().div_euclid(width) * width;
+            match candles.last_mut() {
+                Some((key, candle)) if *key == bucket => {
+                    candle.high = candle.high.max(quote.price);
+                    candle.low = candle.low.min(quote.price);
+                    candle.close = quote.price;
+                    candle.adjusted_close = Some(quote.price);
+                    candle.volume += traded;
+                    candle.created_at = quote.created_at;
+                }
+                _ => candles.push((
+                    bucket,
+                    HistoricalPrice {
+                        id: quote.id,
+                        symbol_id: quote.symbol_id,
+                        symbol: quote.symbol.clone(),
+                        timestamp: DateTime::from_timestamp(bucket, 0).unwrap_or(quote.market_time),
+                        open: quote.price,
+                        high: quote.price,
+                        low: quote.price,
+                        close: quote.price,
+                        adjusted_close: Some(quote.price),
+                        volume: traded,
+                        created_at: quote.created_at,
+                    },
+                )),
+            }
+        }
+
+        candles.into_iter().rev().map(|(_, candle)| candle).collect()
+    }
+}
+
+/// Descriptive symbol fields gathered from provider lookups. `None` leaves the stored value untouched.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolMetadata {
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    pub sector: Option<String>,
+    pub industry: Option<String>,
+    pub market_cap: Option<Decimal>,
+    pub timezone: Option<String>,
+    pub utc_offset_seconds: Option<i32>,
+    pub currency: Option<String>,
+}
+
+/// Lightweight symbol entry for type-ahead suggestions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSuggestion {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+}
+
+/// Optional filters for symbol listing and search (case-insensitive exact match)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SymbolFilter {
+    pub sector: Option<String>,
+    pub exchange: Option<String>,
+    /// Only members of this universe
+    pub universe: Option<String>,
+    /// Only active or only delisted symbols
+    pub status: Option<SymbolStatus>,
+}
+
+impl SymbolFilter {
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        fn field_matches(wanted: &Option<String>, actual: &Option<String>) -> bool {
+            match wanted {
+                Some(wanted) => actual
+                    .as_deref()
+                    .is_some_and(|actual| actual.eq_ignore_ascii_case(wanted)),
+                None => true,
+            }
+        }
+
+        field_matches(&self.sector, &symbol.sector)
+            && field_matches(&self.exchange, &symbol.exchange)
+            && self.status.is_none_or(|status| status == symbol.status)
+    }
+}
+
+/// A symbol with its most recent stored price, used by cross-symbol aggregations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSnapshot {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    pub sector: Option<String>,
+    pub industry: Option<String>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub price: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_percent: Option<Decimal>,
+    pub volume: Option<i64>,
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorSummary {
+    pub sector: String,
+    pub symbol_count: i64,
+    pub industry_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupPerformance {
+    pub name: String,
+    pub symbol_count: usize,
+    pub priced_count: usize,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub average_change_percent: Option<Decimal>,
+    pub advancers: usize,
+    pub decliners: usize,
+    pub unchanged: usize,
+    /// (advancers - decliners) / priced symbols, in the range -1..=1
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub breadth: Option<Decimal>,
+}
+
+impl GroupPerformance {
+    pub fn from_snapshots<'a>(name: impl Into<String>, snapshots: impl IntoIterator<Item = &'a SymbolSnapshot>) -> Self {
+        let mut symbol_count = 0;
+        let mut changes = Vec::new();
+        for snapshot in snapshots {
+            symbol_count += 1;
+            if let Some(change) = snapshot.change_percent {
+                changes.push(change);
+            }
+        }
+
+        let priced_count = changes.len();
+        let advancers = changes.iter().filter(|c| c.is_sign_positive() && !c.is_zero()).count();
+        let decliners = changes.iter().filter(|c| c.is_sign_negative() && !c.is_zero()).count();
+        let unchanged = priced_count - advancers - decliners;
+
+        let (average_change_percent, breadth) = if priced_count > 0 {
+            let count = Decimal::from(priced_count);
+            let total: Decimal = changes.iter().sum();
+            let net = Decimal::from(advancers as i64 - decliners as i64);
+            (Some((total / count).round_dp(4)), Some((net / count).round_dp(4)))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            name: name.into(),
+            symbol_count,
+            priced_count,
+            average_change_percent,
+            advancers,
+            decliners,
+            unchanged,
+            breadth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorPerformance {
+    pub sector: GroupPerformance,
+    pub industries: Vec<GroupPerformance>,
+    pub members: Vec<SymbolSnapshot>,
+}
+
+/// Valuation and performance of one symbol, from its stored daily candles and dividends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerMetrics {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    pub industry: Option<String>,
+    pub currency: Option<String>,
+    /// Whether the symbol shares the compared symbol's industry, not just its sector
+    pub same_industry: bool,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub market_cap: Option<Decimal>,
+    /// Latest stored close and its date
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub price: Option<Decimal>,
+    pub as_of: Option<NaiveDate>,
+    /// Close-to-close changes over 1, 21, 63 and 252 trading days
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1d_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1m_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_3m_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1y_percent: Option<Decimal>,
+    /// Annualized standard deviation of the last year's daily returns
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub volatility_percent: Option<Decimal>,
+    /// Dividends with an ex-date in the last year over the latest close
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub dividend_yield_percent: Option<Decimal>,
+}
+
+/// Medians of the peers' metrics, each over the peers that have it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerMedians {
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub market_cap: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1d_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1m_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_3m_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_1y_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub volatility_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub dividend_yield_percent: Option<Decimal>,
+}
+
+/// A symbol next to stored symbols of its sector, same-industry peers first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerComparison {
+    pub symbol: String,
+    pub sector: String,
+    pub industry: Option<String>,
+    pub subject: PeerMetrics,
+    pub peers: Vec<PeerMetrics>,
+    pub medians: PeerMedians,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketMover {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub price: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_percent: Option<Decimal>,
+    pub volume: Option<i64>,
+    pub average_volume: Option<i64>,
+    /// Latest volume relative to the trailing average (1.0 = normal)
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub volume_ratio: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketMovers {
+    pub universe: String,
+    pub symbol_count: usize,
+    pub gainers: Vec<MarketMover>,
+    pub losers: Vec<MarketMover>,
+    pub volume_surges: Vec<MarketMover>,
 }
 
 // Portfolio models
@@ -278,32 +905,146 @@ pub struct PortfolioHolding {
     pub symbol: String,
     pub symbol_id: Option<Uuid>,
     pub asset_type: String, // "stock", "etf", "crypto"
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub purchase_price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub current_price: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub current_value: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub gain_loss: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub gain_loss_percent: Option<Decimal>,
+    /// Target allocation as a percentage of total portfolio value
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub target_weight: Option<Decimal>,
+    /// Alert when the price falls to or below this level
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub stop_loss: Option<Decimal>,
+    /// Alert when the price falls this many percent below its high since the stop was set
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub trailing_stop_percent: Option<Decimal>,
+    /// Alert when the price rises to or above this level
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub target_price: Option<Decimal>,
+    /// Highest price seen while the trailing stop is set
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub high_water_mark: Option<Decimal>,
+    /// Set while the stop or target has fired and the price has not yet moved back past it
+    pub stop_triggered_at: Option<DateTime<Utc>>,
+    pub target_triggered_at: Option<DateTime<Utc>>,
+    /// Overrides of ALERT_COOLDOWN_MINUTES and ALERT_REARM_PERCENT for this holding
+    pub alert_cooldown_minutes: Option<i64>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub alert_rearm_percent: Option<Decimal>,
+    /// Stop and target alerts stay silent until then
+    pub alerts_muted_until: Option<DateTime<Utc>>,
+    pub last_alert_at: Option<DateTime<Utc>>,
+    /// Notification channels for stop and target alerts; every configured one when unset
+    pub alert_channels: Option<Vec<String>>,
     pub last_updated: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl PortfolioHolding {
+    /// Trailing stop level implied by the high-water mark
+    pub fn trailing_stop(&self) -> Option<Decimal> {
+        let (percent, high) = self.trailing_stop_percent.zip(self.high_water_mark)?;
+        Some((high * (Decimal::ONE_HUNDRED - percent) / Decimal::ONE_HUNDRED).round_dp(4))
+    }
+}
+
+/// Refreshed market value of a holding, written back by the price updater
+#[derive(Debug, Clone)]
+pub struct HoldingPriceUpdate {
+    pub holding_id: Uuid,
+    pub current_price: Decimal,
+    pub current_value: Decimal,
+    pub gain_loss: Decimal,
+    pub gain_loss_percent: Decimal,
+}
+
+impl HoldingPriceUpdate {
+    /// Value and gain of `holding` at `price` against its purchase cost
+    pub fn at_price(holding: &PortfolioHolding, price: Decimal) -> Self {
+        let current_value = price * holding.quantity;
+        let cost = holding.purchase_price * holding.quantity;
+        let gain_loss = current_value - cost;
+        let gain_loss_percent = if cost > Decimal::ZERO {
+            (gain_loss / cost) * Decimal::from(100)
+        } else {
+            Decimal::ZERO
+        };
+        Self {
+            holding_id: holding.id,
+            current_price: price,
+            current_value,
+            gain_loss,
+            gain_loss_percent,
+        }
+    }
+}
+
+/// One purchase behind a holding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioLot {
+    pub id: Uuid,
+    pub holding_id: Uuid,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub purchase_price: Decimal,
+    pub acquired_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortfolioHoldingWithQuote {
     pub holding: PortfolioHolding,
     pub quote: Option<RealTimeQuote>,
     pub name: Option<String>,
+    pub lots: Vec<PortfolioLot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSettings {
+    /// "merge" averages repeat purchases into one lot; "separate" keeps each purchase as a lot
+    pub lot_mode: String,
+}
+
+impl Default for PortfolioSettings {
+    fn default() -> Self {
+        Self {
+            lot_mode: "merge".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortfolioSummary {
     pub total_holdings: usize,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub total_cost: Decimal,
+    /// Market value of holdings plus the cash balance
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub total_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub cash_balance: Decimal,
+    /// Deposits minus withdrawals to date
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub net_contributions: Decimal,
+    /// Annualized money-weighted return in percent, when deposits have been recorded
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub money_weighted_return: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub total_gain_loss: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub total_gain_loss_percent: Decimal,
     pub holdings: Vec<PortfolioHoldingWithQuote>,
+    /// When the background updater last refreshed holding prices, all in one transaction
     pub last_updated: Option<DateTime<Utc>>,
 }
 
@@ -312,29 +1053,2048 @@ pub struct AddHoldingRequest {
     pub symbol: String,
     #[serde(default)]
     pub asset_type: Option<String>, // Optional: "stock", "etf", "crypto" - auto-detected if not provided
+    #[serde(serialize_with = "crate::numeric::serialize")]
     pub quantity: Decimal,
     #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub purchase_price: Option<Decimal>, // Optional: will use current price if not provided
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateHoldingRequest {
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub quantity: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub purchase_price: Option<Decimal>,
+    /// Target allocation in percent (0-100)
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub target_weight: Option<Decimal>,
+    /// Exit levels; 0 clears a level
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub stop_loss: Option<Decimal>,
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub trailing_stop_percent: Option<Decimal>,
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub target_price: Option<Decimal>,
+    /// Alert throttling for this holding's stop and target
+    #[serde(default)]
+    pub alert_cooldown_minutes: Option<i64>,
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub alert_rearm_percent: Option<Decimal>,
+    /// "email" and/or "telegram"; an empty list keeps the alerts to webhooks
+    #[serde(default)]
+    pub alert_channels: Option<Vec<String>>,
 }
 
-// Rate limiting configuration
-#[derive(Debug, Clone)]
-pub struct RateLimitConfig {
-    pub requests_per_minute: u32,
-    pub yahoo_api_requests_per_minute: u32,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldingIncome {
+    pub symbol: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    /// Dividends per share paid over the trailing twelve months
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub trailing_dividend_per_share: Decimal,
+    /// Payments per year inferred from the trailing ex-dates
+    pub payments_per_year: usize,
+    pub last_ex_date: Option<NaiveDate>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub projected_annual_income: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub yield_on_cost: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub current_yield: Option<Decimal>,
 }
 
-impl Default for RateLimitConfig {
-    fn default() -> Self {
-        Self {
-            requests_per_minute: 100,
-            yahoo_api_requests_per_minute: 30, // Conservative limit for Yahoo Finance API
-        }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExDividendEntry {
+    pub symbol: String,
+    pub ex_date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub amount_per_share: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub expected_payment: Decimal,
+    /// False when the ex-date was reported by the data source rather than projected
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioIncome {
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub projected_annual_income: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub portfolio_yield: Option<Decimal>,
+    pub holdings: Vec<HoldingIncome>,
+    pub calendar: Vec<ExDividendEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioTransaction {
+    pub id: Uuid,
+    pub symbol: String,
+    pub side: String, // "buy" or "sell"
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub fees: Decimal,
+    pub trade_date: NaiveDate,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddTransactionRequest {
+    pub symbol: String,
+    pub side: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub fees: Option<Decimal>,
+    pub trade_date: NaiveDate,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// A real trade logged with the reasoning behind it, kept apart from the portfolio ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    /// Same owners as dashboards
+    pub owner: String,
+    pub symbol: String,
+    pub side: String, // "buy" or "sell"
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub fees: Decimal,
+    pub trade_date: NaiveDate,
+    pub rationale: Option<String>,
+    /// Lower-case labels such as "breakout" or "earnings"
+    pub tags: Vec<String>,
+    /// Links to chart screenshots hosted elsewhere
+    pub screenshot_urls: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntryRequest {
+    pub symbol: String,
+    pub side: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(default)]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub fees: Option<Decimal>,
+    pub trade_date: NaiveDate,
+    #[serde(default)]
+    pub rationale: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub screenshot_urls: Vec<String>,
+}
+
+/// Which journal entries to list or export
+#[derive(Debug, Clone, Default)]
+pub struct JournalFilter {
+    pub symbol: Option<String>,
+    pub tag: Option<String>,
+    pub side: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// A page of journal entries, newest trade first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalPage {
+    pub entries: Vec<JournalEntry>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Quantity of one buy lot closed by one sell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealizedLot {
+    pub symbol: String,
+    pub buy_transaction_id: Uuid,
+    pub sell_transaction_id: Uuid,
+    pub acquired: NaiveDate,
+    pub sold: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub proceeds: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub cost_basis: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub gain: Decimal,
+    pub holding_days: i64,
+    pub term: String, // "short" or "long"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxReport {
+    pub year: i32,
+    pub method: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_proceeds: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_cost_basis: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub short_term_gain: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub long_term_gain: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_gain: Decimal,
+    pub lots: Vec<RealizedLot>,
+}
+
+/// A deposit into or withdrawal from the portfolio's cash balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashFlow {
+    pub id: Uuid,
+    pub kind: String, // "deposit" or "withdrawal"
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub amount: Decimal,
+    pub flow_date: NaiveDate,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashFlowRequest {
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub amount: Decimal,
+    /// Defaults to today
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CashSummary {
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub balance: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_deposits: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_withdrawals: Decimal,
+    /// Spent on ledger buys with their fees, and on holdings whose symbol has no ledger entries
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_purchases: Decimal,
+    /// Proceeds of ledger sells net of fees
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_sales: Decimal,
+    /// Dividends paid on the ledger shares held at each ex-date
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_dividends: Decimal,
+    pub flows: Vec<CashFlow>,
+}
+
+impl CashSummary {
+    /// Cash left from the deposits and withdrawals after what the portfolio spent and
+    /// received up to `as_of`. Without any deposit or withdrawal, cash is not tracked and
+    /// the balance stays zero, so portfolios funded outside the app are valued as before.
+    pub fn from_ledger(
+        flows: Vec<CashFlow>,
+        transactions: &[PortfolioTransaction],
+        holdings: &[PortfolioHolding],
+        dividends: &[Dividend],
+        as_of: NaiveDate,
+    ) -> Self {
+        if flows.is_empty() {
+            return Self::default();
+        }
+        let total = |kind: &str| -> Decimal {
+            flows.iter().filter(|f| f.kind == kind).map(|f| f.amount).sum()
+        };
+        let total_deposits = total("deposit");
+        let total_withdrawals = total("withdrawal");
+
+        let mut total_purchases = Decimal::ZERO;
+        let mut total_sales = Decimal::ZERO;
+        for tx in transactions.iter().filter(|tx| tx.trade_date <= as_of) {
+            match tx.side.as_str() {
+                "buy" => total_purchases += tx.quantity * tx.price + tx.fees,
+                "sell" => total_sales += tx.quantity * tx.price - tx.fees,
+                _ => {}
+            }
+        }
+        // Holdings entered directly were paid for too, unless the ledger already has them
+        total_purchases += holdings
+            .iter()
+            .filter(|h| !transactions.iter().any(|tx| tx.symbol == h.symbol))
+            .map(|h| h.purchase_price * h.quantity)
+            .sum::<Decimal>();
+
+        // Shares bought before the ex-date and not yet sold receive the dividend
+        let total_dividends = dividends
+            .iter()
+            .filter(|d| d.ex_date <= as_of)
+            .map(|d| {
+                let held: Decimal = transactions
+                    .iter()
+                    .filter(|tx| tx.symbol == d.symbol && tx.trade_date < d.ex_date)
+                    .map(|tx| if tx.side == "sell" { -tx.quantity } else { tx.quantity })
+                    .sum();
+                held.max(Decimal::ZERO) * d.amount
+            })
+            .sum();
+
+        Self {
+            balance: total_deposits - total_withdrawals - total_purchases + total_sales + total_dividends,
+            total_deposits,
+            total_withdrawals,
+            total_purchases,
+            total_sales,
+            total_dividends,
+            flows,
+        }
+    }
+
+    /// Annualized money-weighted return (IRR of the external flows against `ending_value`
+    /// on `as_of`), in percent. None without deposits or when no rate balances the flows.
+    pub fn money_weighted_return(&self, ending_value: Decimal, as_of: NaiveDate) -> Option<Decimal> {
+        use rust_decimal::prelude::ToPrimitive;
+
+        let start = self.flows.iter().find(|f| f.kind == "deposit")?.flow_date;
+        if (as_of - start).num_days() < 1 {
+            return None;
+        }
+
+        // Investor's view: deposits are outflows, withdrawals and the ending value inflows
+        let mut cash_flows: Vec<(f64, f64)> = self
+            .flows
+            .iter()
+            .map(|f| {
+                let amount = f.amount.to_f64().unwrap_or_default();
+                let years = (f.flow_date - start).num_days() as f64 / 365.0;
+                (if f.kind == "deposit" { -amount } else { amount }, years)
+            })
+            .collect();
+        cash_flows.push((
+            ending_value.to_f64()?,
+            (as_of - start).num_days() as f64 / 365.0,
+        ));
+
+        let npv = |rate: f64| -> f64 {
+            cash_flows
+                .iter()
+                .map(|(amount, years)| amount / (1.0 + rate).powf(*years))
+                .sum()
+        };
+
+        // NPV falls as the rate rises, so bisect between a near-total loss and +10000%
+        let (mut low, mut high) = (-0.9999, 100.0);
+        if npv(low) < 0.0 || npv(high) > 0.0 {
+            return None;
+        }
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+            if npv(mid) > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        Decimal::from_f64_retain((low + high) / 2.0 * 100.0).map(|r| r.round_dp(2))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTargetsRequest {
+    /// Symbol -> target allocation in percent; holdings not listed lose their target
+    #[serde(serialize_with = "crate::numeric::serialize_map")]
+    pub targets: HashMap<String, Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceTrade {
+    pub holding_id: Uuid,
+    pub symbol: String,
+    pub asset_type: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub current_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub current_weight: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub target_weight: Decimal,
+    /// Current minus target weight, in percentage points
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub drift: Decimal,
+    /// "buy", "sell" or "hold"
+    pub action: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub trade_quantity: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub trade_value: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_value: Decimal,
+    /// Portfolio cash balance included in `total_value`
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub cash_balance: Decimal,
+    /// Extra cash requested for this plan
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub cash: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub min_trade_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub target_weight_total: Decimal,
+    pub trades: Vec<RebalanceTrade>,
+    /// Holdings without a target, left untouched
+    pub untargeted: Vec<String>,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub buy_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub sell_value: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectionRequest {
+    /// "gbm" (default) or "bootstrap"
+    pub method: Option<String>,
+    /// Trading days to project forward
+    pub horizon_days: Option<u32>,
+    pub simulations: Option<u32>,
+    /// Daily bars of history used to estimate returns
+    pub lookback_days: Option<u32>,
+    /// Fixed RNG seed for reproducible results
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectionBand {
+    pub day: u32,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub p5: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub p25: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub p50: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub p75: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub p95: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioProjection {
+    pub method: String,
+    pub horizon_days: u32,
+    pub simulations: u32,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub initial_value: Decimal,
+    /// Number of daily portfolio returns the simulation was fitted on
+    pub observations: usize,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub annualized_return: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub annualized_volatility: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub expected_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub probability_of_loss: Decimal,
+    pub bands: Vec<ProjectionBand>,
+}
+
+/// Forecast close of one future trading day with its 80% and 95% intervals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub date: NaiveDate,
+    /// Median forecast close
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub forecast: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub lower_80: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub upper_80: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub lower_95: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub upper_95: Decimal,
+}
+
+/// Statistical baseline forecast of a symbol's daily closes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forecast {
+    pub symbol: String,
+    /// "ets" or "arima"
+    pub method: String,
+    pub horizon: u32,
+    /// Stored daily closes the model was fitted on
+    pub observations: usize,
+    /// Date of the last close fitted
+    pub last_date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub last_close: Decimal,
+    /// Fitted model parameters by name
+    pub parameters: BTreeMap<String, f64>,
+    /// Standard deviation of one-day-ahead errors in fitting, in percent
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub residual_sd_percent: Decimal,
+    pub points: Vec<ForecastPoint>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// One daily candle's features, in the order of [`FeatureMatrix::columns`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureRow {
+    pub date: NaiveDate,
+    pub volume: i64,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Engineered features of a symbol's daily candles, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureMatrix {
+    pub symbol: String,
+    /// Candles spanned by the rolling statistics
+    pub window: usize,
+    /// Feature names; every row also carries its date and volume
+    pub columns: Vec<String>,
+    pub rows: Vec<FeatureRow>,
+}
+
+/// Sensitivity of a symbol's daily returns to a benchmark's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BetaEstimate {
+    pub benchmark: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub beta: Decimal,
+    /// Share of the symbol's return variance explained by the benchmark
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub r_squared: Decimal,
+    /// Daily returns both series share
+    pub observations: usize,
+}
+
+/// A name users may type in place of a canonical provider ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolAlias {
+    pub alias: String,
+    pub symbol: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetAliasRequest {
+    pub symbol: String,
+}
+
+/// Alert on a technical signal of a symbol, fired once per crossing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalAlert {
+    pub id: Uuid,
+    pub symbol: String,
+    pub condition: SignalCondition,
+    /// Custom indicator watched by the `indicator_cross_above` and `indicator_cross_below`
+    /// conditions, and the value it has to cross
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indicator_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indicator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub threshold: Option<Decimal>,
+    /// Override of ALERT_COOLDOWN_MINUTES for this alert
+    pub cooldown_minutes: Option<i64>,
+    /// Notification channels the alert fires on; every configured one when unset
+    pub channels: Option<Vec<String>>,
+    /// The alert stays silent until then
+    pub muted_until: Option<DateTime<Utc>>,
+    /// Date of the daily close the alert last fired on
+    pub last_fired_on: Option<NaiveDate>,
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSignalAlertRequest {
+    pub symbol: String,
+    /// "golden_cross", "death_cross", "rsi_oversold", "rsi_overbought",
+    /// "bollinger_upper_break", "bollinger_lower_break", "indicator_cross_above" or
+    /// "indicator_cross_below"
+    pub condition: String,
+    /// Name of one of the caller's custom indicators, for the indicator conditions
+    #[serde(default)]
+    pub indicator: Option<String>,
+    /// Value the indicator has to cross; defaults to 0
+    #[serde(default)]
+    pub threshold: Option<Decimal>,
+    #[serde(default)]
+    pub cooldown_minutes: Option<i64>,
+    /// "email" and/or "telegram"; an empty list keeps the alert to webhooks
+    #[serde(default)]
+    pub channels: Option<Vec<String>>,
+}
+
+/// Change the notification channels of a signal alert
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSignalAlertRequest {
+    pub channels: Vec<String>,
+}
+
+/// Which measure of a daily bar was unusual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Close-to-close return
+    Return,
+    /// Traded volume
+    Volume,
+}
+
+impl AnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyKind::Return => "return",
+            AnomalyKind::Volume => "volume",
+        }
+    }
+}
+
+impl std::str::FromStr for AnomalyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "return" => Ok(AnomalyKind::Return),
+            "volume" => Ok(AnomalyKind::Volume),
+            other => Err(format!("Unsupported anomaly kind '{}'. Use return or volume", other)),
+        }
+    }
+}
+
+/// A daily move or volume far outside a symbol's recent range, found by the background
+/// analyzer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub id: Uuid,
+    pub symbol: String,
+    /// Date of the daily bar
+    pub date: NaiveDate,
+    pub kind: AnomalyKind,
+    /// Return in percent, or the day's volume
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+    /// Standard deviations from the lookback mean; log volume for volume anomalies
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub z_score: Decimal,
+    /// Mean return in percent or average volume over the lookback
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub baseline: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub close: Decimal,
+    pub volume: i64,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Which anomalies to list
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyFilter {
+    /// Bars on or after this date
+    pub since: Option<NaiveDate>,
+    pub symbol: Option<String>,
+    pub kind: Option<AnomalyKind>,
+}
+
+/// An indicator expression saved by one owner, computed on the indicators endpoint and usable
+/// in signal alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomIndicator {
+    pub id: Uuid,
+    /// Same owners as dashboards
+    pub owner: String,
+    pub name: String,
+    pub expression: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveCustomIndicatorRequest {
+    pub expression: String,
+}
+
+/// Snooze an alert for `minutes` or until a time; exactly one must be given
+#[derive(Debug, Clone, Deserialize)]
+pub struct MuteAlertRequest {
+    #[serde(default)]
+    pub minutes: Option<i64>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Ticker an ISIN or CUSIP was mapped to, stored after the first lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityIdentifier {
+    pub identifier: String,
+    /// "isin" or "cusip"
+    pub kind: String,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    /// "search" or "openfigi"
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How user input maps onto a provider ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolResolution {
+    pub input: String,
+    pub symbol: String,
+    /// "alias", "isin", "exchange" or "input"
+    pub source: String,
+}
+
+/// One institution or fund holding a symbol, as of its latest filing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holder {
+    pub name: String,
+    pub shares: Option<i64>,
+    /// Market value of the position on the report date
+    pub value: Option<i64>,
+    /// Percentage of shares outstanding
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub percent_held: Option<Decimal>,
+    /// Percentage change in shares since the previous filing
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub percent_change: Option<Decimal>,
+    pub report_date: Option<NaiveDate>,
+}
+
+/// Ownership split across insiders and institutions, in percent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OwnershipBreakdown {
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub insiders_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub institutions_percent: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub institutions_float_percent: Option<Decimal>,
+    pub institutions_count: Option<i64>,
+}
+
+/// Top institutional and fund holders of a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHolders {
+    pub symbol: String,
+    pub breakdown: OwnershipBreakdown,
+    pub institutions: Vec<Holder>,
+    pub funds: Vec<Holder>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A position inside a fund. `symbol` is missing for holdings without a listed ticker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundHolding {
+    pub symbol: Option<String>,
+    pub name: String,
+    /// Percentage of the fund's assets
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorWeight {
+    pub sector: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+}
+
+/// Top holdings and sector weightings of an ETF or other fund
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EtfComposition {
+    pub symbol: String,
+    pub holdings: Vec<FundHolding>,
+    pub sector_weights: Vec<SectorWeight>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Quote of one exchange listing, with its price converted to the comparison currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingQuote {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    /// Currency of `price`, in major units (GBP rather than pence)
+    pub currency: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub price: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub change_percent: Option<Decimal>,
+    pub market_time: DateTime<Utc>,
+    /// Units of the comparison currency per unit of `currency`
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub fx_rate: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub converted_price: Option<Decimal>,
+    /// Converted price relative to the requested listing
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub premium_percent: Option<Decimal>,
+}
+
+/// Every exchange listing of the company behind a symbol, priced in one currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolListings {
+    pub symbol: String,
+    pub currency: String,
+    pub listings: Vec<ListingQuote>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Portfolio value attributed to one underlying symbol, held directly or through funds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolExposure {
+    pub symbol: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+    /// Portfolio holdings the exposure comes from, the symbol itself when held directly
+    pub via: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorExposure {
+    pub sector: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+}
+
+/// Portfolio exposure with funds replaced by their underlying holdings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioExposure {
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_value: Decimal,
+    pub symbols: Vec<SymbolExposure>,
+    pub sectors: Vec<SectorExposure>,
+    /// Fund assets outside the top holdings the provider reports
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub unattributed_value: Decimal,
+    /// Holdings that were looked through
+    pub funds: Vec<String>,
+}
+
+/// Share of portfolio value in one asset type or sector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationSlice {
+    pub name: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+}
+
+/// A holding's weight in the portfolio and its performance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldingAllocation {
+    pub holding_id: Uuid,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub asset_type: String,
+    pub sector: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub weight_percent: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub gain_loss: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub gain_loss_percent: Decimal,
+    /// Change in the holding's value since the previous close, when a quote is available
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub day_change: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub day_change_percent: Option<Decimal>,
+}
+
+/// Portfolio totals with weights per holding and allocation by asset type and sector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioAllocationSummary {
+    pub total_holdings: usize,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_cost: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub holdings_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub cash_balance: Decimal,
+    /// Market value of holdings plus the cash balance; weights are shares of this
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_value: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_gain_loss: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_gain_loss_percent: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub day_change: Decimal,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub day_change_percent: Option<Decimal>,
+    /// Largest holding first
+    pub holdings: Vec<HoldingAllocation>,
+    pub by_asset_type: Vec<AllocationSlice>,
+    pub by_sector: Vec<AllocationSlice>,
+    /// Holding with the largest percentage rise today, if any rose
+    pub top_gainer: Option<HoldingAllocation>,
+    /// Holding with the largest percentage fall today, if any fell
+    pub top_loser: Option<HoldingAllocation>,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+/// One audit trail record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    /// "tezos", "oidc", "jwt", "dev" or "anonymous"
+    pub actor_type: String,
+    /// Tezos address, email, token subject or client IP
+    pub actor: String,
+    /// "api", "login", "logout", "token" or "session.revoke"
+    pub action: String,
+    pub method: String,
+    pub path: String,
+    pub status_code: u16,
+    /// Truncated request payload or a short description
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    pub actor_type: Option<String>,
+    pub action: Option<String>,
+    /// Matches paths starting with this prefix
+    pub path: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// One cache's size at the end of a stats sampling interval and its activity during it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSample {
+    pub cache: String,
+    pub entries: u64,
+    pub weighted_bytes: u64,
+    pub max_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// Cache and provider activity over one sampling interval ending at `sampled_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub sampled_at: DateTime<Utc>,
+    pub interval_seconds: u64,
+    /// Provider requests made, and refused by the Yahoo rate limit, during the interval
+    pub yahoo_calls: u64,
+    pub yahoo_rate_limited: u64,
+    pub caches: Vec<CacheSample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsHistory {
+    pub hours: i64,
+    /// "memory" for samples since startup, "database" when STATS_HISTORY_PERSIST is set
+    pub source: &'static str,
+    /// Oldest first
+    pub samples: Vec<StatsSample>,
+}
+
+/// Latency distribution of one route or upstream call since startup, in milliseconds.
+/// Percentiles are accurate to the histogram bucket, within about 20%.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    /// "GET /api/symbols/:symbol/quote" for routes, "yahoo.latest_quote" for upstream calls
+    pub name: String,
+    pub count: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Today's provider calls against one feature's share of the daily budget
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureQuota {
+    pub allowance: u32,
+    pub used: u32,
+    pub remaining: u32,
+    pub exhausted: bool,
+}
+
+/// Usage of the daily provider call budget, keyed by feature
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaUsage {
+    pub daily_budget: u32,
+    pub resets_at: DateTime<Utc>,
+    pub features: BTreeMap<String, FeatureQuota>,
+}
+
+/// A client's standing in the per-minute API rate limit
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiRateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    pub window_seconds: u64,
+    /// When the oldest request in the window leaves it and frees a slot; now when none are counted
+    pub resets_at: DateTime<Utc>,
+}
+
+/// Rate limits and budgets that apply to a client
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiLimits {
+    pub api: ApiRateLimit,
+    /// Provider calls per minute across all clients
+    pub yahoo_api_requests_per_minute: u32,
+    pub bulk_yahoo_requests_per_minute: u32,
+    /// How long a provider call may queue for a slot before the request fails
+    pub yahoo_api_max_wait_ms: u64,
+    pub quota: Option<QuotaUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub since: DateTime<Utc>,
+    pub slow_upstream_call_ms: u64,
+    /// Provider calls slower than `slow_upstream_call_ms`
+    pub slow_upstream_calls: u64,
+    /// Slowest p95 first
+    pub routes: Vec<LatencySummary>,
+    pub upstream: Vec<LatencySummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditEntry>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Registered webhook endpoint. The signing secret is only returned when it is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    /// Event types delivered to this endpoint, e.g. "backfill.completed"
+    pub events: Vec<String>,
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateWebhookRequest {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub active: Option<bool>,
+}
+
+/// One delivered (or finally failed) webhook event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    /// JSON body as sent
+    pub payload: String,
+    pub attempts: u32,
+    /// Status of the last attempt; `None` when the request never got a response
+    pub status_code: Option<u16>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What requests made with an API key may do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyRole {
+    /// Read the API, even when reads need a login
+    #[default]
+    Read,
+    /// Everything an admin may do through the API
+    Admin,
+}
+
+impl ApiKeyRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyRole::Read => "read",
+            ApiKeyRole::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for ApiKeyRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(ApiKeyRole::Read),
+            "admin" => Ok(ApiKeyRole::Admin),
+            other => Err(format!("Unknown API key role '{}'. Use read or admin", other)),
+        }
+    }
+}
+
+/// Key of a programmatic consumer, sent as `X-API-Key`. Only its hash is stored, so the key
+/// itself is returned once, when it is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the key, to tell keys apart
+    pub prefix: String,
+    #[serde(skip_serializing, default)]
+    pub key_hash: String,
+    pub role: ApiKeyRole,
+    /// Requests allowed per UTC day; `None` for unlimited
+    pub daily_quota: Option<u32>,
+    /// Requests allowed per UTC calendar month; `None` for unlimited
+    pub monthly_quota: Option<u32>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    #[serde(default)]
+    pub role: ApiKeyRole,
+    #[serde(default)]
+    pub daily_quota: Option<u32>,
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+}
+
+/// Changes to a key; a quota of 0 removes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateApiKeyRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub role: Option<ApiKeyRole>,
+    #[serde(default)]
+    pub daily_quota: Option<u32>,
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+    #[serde(default)]
+    pub active: Option<bool>,
+}
+
+/// Requests made with a key in one quota period
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaPeriodUsage {
+    pub quota: Option<u32>,
+    pub used: u32,
+    /// `None` without a quota
+    pub remaining: Option<u32>,
+    pub resets_at: DateTime<Utc>,
+}
+
+/// Requests made with a key on one UTC day
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsageDay {
+    pub date: NaiveDate,
+    pub requests: u32,
+    /// Requests refused for exceeding a quota
+    pub rejected: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyUsage {
+    pub key_id: Uuid,
+    pub name: String,
+    pub daily: QuotaPeriodUsage,
+    pub monthly: QuotaPeriodUsage,
+    /// Days with requests, newest first
+    pub days: Vec<KeyUsageDay>,
+}
+
+/// One email or Telegram notification attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    /// "email" or "telegram"
+    pub channel: String,
+    /// What triggered it, e.g. "test" or "alert:<id>"
+    pub source: String,
+    pub subject: String,
+    pub body: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Notification history plus the channels that can currently send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationHistory {
+    pub configured_channels: Vec<String>,
+    pub notifications: Vec<Notification>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NaturalLanguageQuery {
+    /// e.g. "AAPL vs MSFT last 6 months"
+    pub query: String,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalyticsSqlRequest {
+    /// A single SELECT or WITH query
+    pub sql: String,
+    /// Rows to return, capped at ANALYTICS_SQL_MAX_ROWS
+    pub limit: Option<usize>,
+}
+
+#[cfg(feature = "analytics")]
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsSqlResult {
+    pub columns: Vec<String>,
+    /// One array per row, values in column order
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    /// More rows matched than were returned
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestNotificationRequest {
+    /// "email" or "telegram"
+    pub channel: String,
+    pub message: Option<String>,
+}
+
+/// Result of removing the DEMO_MODE sample data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoWipeSummary {
+    /// Demo symbols whose data was removed
+    pub symbols: Vec<String>,
+    /// Symbol rows deleted; symbols held in the portfolio are kept
+    pub deleted_symbols: u64,
+    pub price_rows: u64,
+}
+
+/// Stored data removed with a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDeletion {
+    pub symbol: String,
+    pub price_rows: u64,
+    pub quote_rows: u64,
+    pub dividend_rows: u64,
+    /// The current profile and its dated snapshots
+    pub profile_rows: u64,
+}
+
+/// Stored data moved from an old ticker to a new one, e.g. after FB became META
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolRename {
+    pub from: String,
+    pub to: String,
+    /// Whether `to` was already stored; its own rows win where both have the same candle,
+    /// dividend, profile or alert
+    pub merged: bool,
+    pub price_rows: u64,
+    pub quote_rows: u64,
+    pub dividend_rows: u64,
+    pub profile_rows: u64,
+    pub holding_rows: u64,
+    pub transaction_rows: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameSymbolRequest {
+    pub to: String,
+}
+
+/// Rows loaded from one table of an imported archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveTableImport {
+    pub table: String,
+    /// Rows in the archive
+    pub rows: usize,
+    /// Rows inserted; in merge mode rows whose keys already existed are not counted
+    pub imported: u64,
+    /// Archived columns the current schema does not have
+    pub skipped_columns: Vec<String>,
+}
+
+/// Result of `POST /api/admin/import`
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveImportSummary {
+    pub mode: crate::archive::ImportMode,
+    pub schema_version: Option<i64>,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub tables: Vec<ArchiveTableImport>,
+}
+
+/// A stored value that differs from the upstream one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMismatch {
+    /// "open", "high", "low", "close", "adjusted_close" or "volume"
+    pub field: String,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub stored: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub upstream: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleMismatch {
+    pub timestamp: DateTime<Utc>,
+    pub fields: Vec<FieldMismatch>,
+}
+
+/// Stored candles compared with a fresh upstream fetch that was not stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalVerification {
+    pub symbol: String,
+    pub interval: String,
+    pub range: String,
+    pub provider: String,
+    /// Relative difference below which values count as equal
+    pub tolerance: f64,
+    pub upstream_count: usize,
+    /// Stored candles between the first and last upstream timestamp
+    pub stored_count: usize,
+    pub matched: usize,
+    /// Number of mismatched candles per field, e.g. {"adjusted_close": 40}
+    pub mismatched_fields: HashMap<String, usize>,
+    pub mismatches: Vec<CandleMismatch>,
+    /// Upstream candles with no stored row at the same timestamp
+    pub missing_from_store: Vec<DateTime<Utc>>,
+    /// Stored candles the upstream response no longer has
+    pub missing_upstream: Vec<DateTime<Utc>>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// One panel of a saved dashboard layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DashboardWidget {
+    QuoteCard {
+        symbol: String,
+    },
+    Chart {
+        symbol: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+        /// Candles to show
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<i32>,
+    },
+    IndicatorPanel {
+        symbol: String,
+    },
+    Watchlist {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        symbols: Vec<String>,
+    },
+}
+
+/// Named, saved layout for the /ui dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub id: Uuid,
+    /// "tezos:tz1...", "oidc:user@example.com", "jwt:subject", "dev:dev" or "public"
+    pub owner: String,
+    pub name: String,
+    pub widgets: Vec<DashboardWidget>,
+    /// Shown at /ui when no dashboard is picked
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateDashboardRequest {
+    pub name: String,
+    pub widgets: Vec<DashboardWidget>,
+    #[serde(default)]
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateDashboardRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub widgets: Option<Vec<DashboardWidget>>,
+    #[serde(default)]
+    pub is_default: Option<bool>,
+}
+
+/// Which way a strategy rule's expression has to cross its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossDirection {
+    Above,
+    Below,
+}
+
+/// Signal that fires on the candle where an indicator expression crosses a threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyRule {
+    /// Custom indicator expression, e.g. "ema(close, 12) - ema(close, 26)"
+    pub expression: String,
+    pub cross: CrossDirection,
+    #[serde(default, serialize_with = "crate::numeric::serialize")]
+    pub threshold: Decimal,
+}
+
+/// Long-only trading rules run by the backtester
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyDefinition {
+    /// Buy at the close of the candle where this fires
+    pub entry: StrategyRule,
+    /// Sell at the close of the candle where this fires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit: Option<StrategyRule>,
+    /// Sell once the price falls this fraction below the entry price, e.g. 0.08
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::numeric::serialize_option"
+    )]
+    pub stop_loss: Option<Decimal>,
+    /// Sell once the price rises this fraction above the entry price, e.g. 0.25
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::numeric::serialize_option"
+    )]
+    pub take_profit: Option<Decimal>,
+}
+
+/// Named trading strategy; every change to its definition is kept as a new version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Strategy {
+    pub id: Uuid,
+    /// Same owners as dashboards; only the owner can change or delete it
+    pub owner: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Listed for and runnable by every caller, not only the owner
+    pub shared: bool,
+    /// Latest version number, starting at 1
+    pub version: i64,
+    /// Definition of `version`
+    pub definition: StrategyDefinition,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyVersion {
+    pub version: i64,
+    pub definition: StrategyDefinition,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateStrategyRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub shared: Option<bool>,
+    pub definition: StrategyDefinition,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateStrategyRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub shared: Option<bool>,
+    /// Stored as a new version when it differs from the latest one
+    #[serde(default)]
+    pub definition: Option<StrategyDefinition>,
+}
+
+/// One round trip of a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub entry_date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub entry_price: Decimal,
+    pub exit_date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub exit_price: Decimal,
+    /// "exit_signal", "stop_loss", "take_profit" or "end_of_data" for a position still open
+    pub exit_reason: String,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub return_ratio: Decimal,
+}
+
+/// A strategy run over one symbol's daily candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub symbol: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub candles: usize,
+    pub trades: Vec<BacktestTrade>,
+    /// Compounded return of all trades, fully invested while in a position
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub total_return: Decimal,
+    /// Return of holding from the first candle to the last
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub buy_and_hold_return: Option<Decimal>,
+    /// Largest fall of the strategy's equity from a previous high, as a fraction
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub max_drawdown: Decimal,
+    /// Share of trades with a positive return; `null` without trades
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub win_rate: Option<Decimal>,
+    /// Share of candles spent in a position
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub exposure: Decimal,
+    /// Annualized Sharpe and Sortino ratios of the daily equity returns over the report's
+    /// risk-free rate; `null` when the equity never moved
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub sharpe_ratio: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub sortino_ratio: Option<Decimal>,
+}
+
+/// A stored strategy run over several symbols
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub strategy_id: Uuid,
+    pub name: String,
+    pub version: i64,
+    /// Annual risk-free rate as a fraction that the Sharpe and Sortino ratios are measured over
+    pub risk_free_rate: f64,
+    pub results: Vec<BacktestResult>,
+    /// Symbols that could not be run, with the reason
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Observations of a macro series, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroSeriesData {
+    /// Service name of the series, e.g. "tbill-13w"
+    pub series: String,
+    pub fred_id: String,
+    pub title: String,
+    pub units: String,
+    pub frequency: String,
+    /// Where observations come from: "fred", "provider" or "mock"
+    pub source: String,
+    pub observations: Vec<MacroObservation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroObservation {
+    pub date: NaiveDate,
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub value: Decimal,
+}
+
+/// Metric a ranking model can weigh, each measured from stored daily candles, dividends or
+/// symbol metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RankingFactor {
+    /// Change over 63 trading days (momentum)
+    #[serde(rename = "momentum_3m")]
+    Momentum3m,
+    /// Change over 252 trading days (momentum)
+    #[serde(rename = "momentum_12m")]
+    Momentum12m,
+    /// Annualized volatility of a year of daily returns; lower scores higher
+    #[serde(rename = "volatility")]
+    Volatility,
+    /// Deepest fall from a high over a year; shallower scores higher (quality)
+    #[serde(rename = "max_drawdown")]
+    MaxDrawdown,
+    /// Annualized Sharpe ratio over a year (quality)
+    #[serde(rename = "sharpe_ratio")]
+    SharpeRatio,
+    /// Trailing twelve-month dividends over the latest close (value)
+    #[serde(rename = "dividend_yield")]
+    DividendYield,
+    /// Market cap from symbol metadata (size)
+    #[serde(rename = "market_cap")]
+    MarketCap,
+}
+
+impl RankingFactor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankingFactor::Momentum3m => "momentum_3m",
+            RankingFactor::Momentum12m => "momentum_12m",
+            RankingFactor::Volatility => "volatility",
+            RankingFactor::MaxDrawdown => "max_drawdown",
+            RankingFactor::SharpeRatio => "sharpe_ratio",
+            RankingFactor::DividendYield => "dividend_yield",
+            RankingFactor::MarketCap => "market_cap",
+        }
+    }
+
+    /// Whether a lower value is the better one
+    pub fn lower_is_better(&self) -> bool {
+        matches!(self, RankingFactor::Volatility | RankingFactor::MaxDrawdown)
+    }
+}
+
+/// Named weighting of ranking factors. A symbol scores the weighted average of its
+/// percentiles on each factor; a negative weight favours the other end of a factor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingModel {
+    /// Lower-case identifier used in `model=` query parameters, e.g. "default"
+    pub name: String,
+    pub description: Option<String>,
+    pub weights: BTreeMap<RankingFactor, f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SaveRankingModelRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub weights: BTreeMap<RankingFactor, f64>,
+}
+
+/// A factor's value for one symbol and where it falls among the ranked symbols
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingFactorScore {
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub value: Option<Decimal>,
+    /// 0 for the worst value and 100 for the best, after the model's weight sign
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub percentile: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingEntry {
+    pub rank: usize,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub sector: Option<String>,
+    /// Weighted average of the factor percentiles, 0 to 100
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub score: Decimal,
+    /// Share of the model's total weight the symbol had values for
+    #[serde(serialize_with = "crate::numeric::serialize")]
+    pub coverage: Decimal,
+    pub factors: BTreeMap<RankingFactor, RankingFactorScore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rankings {
+    pub model: RankingModel,
+    pub universe: String,
+    /// Symbols that had a value for at least one weighted factor
+    pub symbol_count: usize,
+    /// Annual risk-free rate as a fraction that Sharpe ratios are measured over
+    pub risk_free_rate: f64,
+    pub entries: Vec<RankingEntry>,
+}
+
+/// Named set of symbols, e.g. the constituents of an index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Universe {
+    /// Lower-case identifier used in `universe=` query parameters, e.g. "sp500"
+    pub name: String,
+    pub description: Option<String>,
+    pub member_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseMember {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub sector: Option<String>,
+    /// Index weight in percent, when the import carried one
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
+    pub weight: Option<Decimal>,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseDetail {
+    #[serde(flatten)]
+    pub universe: Universe,
+    pub members: Vec<UniverseMember>,
+}
+
+/// One constituent row read from an import
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniverseConstituent {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub sector: Option<String>,
+    pub weight: Option<Decimal>,
+}
+
+/// Outcome of a constituent CSV import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseImportSummary {
+    pub universe: String,
+    /// "replace" or "append"
+    pub mode: String,
+    /// Rows that were read as constituents
+    pub imported: usize,
+    /// Symbols that were not members before
+    pub added: usize,
+    /// Members dropped by a replace import
+    pub removed: usize,
+    pub member_count: i64,
+    /// Rows that could not be read, as "line N: reason"
+    pub skipped: Vec<String>,
+}
+
+/// Work that can be queued with `POST /api/jobs` instead of blocking the request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum JobRequest {
+    /// Force-refresh one symbol's history, like `POST /api/symbols/:symbol/fetch`
+    FetchHistorical {
+        symbol: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+    },
+    /// Fetch several symbols, like `GET /api/bulk/historical`
+    BulkHistorical {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        symbols: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        universe: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        offset: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_concurrent: Option<i32>,
+    },
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobRequest::FetchHistorical { .. } => "fetch_historical",
+            JobRequest::BulkHistorical { .. } => "bulk_historical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            other => Err(format!(
+                "Unknown job status '{}'. Use queued, running, succeeded or failed",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub request: JobRequest,
+    pub status: JobStatus,
+    /// Output of a succeeded job
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// How far a bulk job has got; `None` for single-symbol jobs and jobs not started
+    pub progress: Option<JobProgress>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// Symbols fetched so far, successfully or not
+    pub done: i64,
+    pub total: i64,
+}
+
+/// What started an export to object storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportTrigger {
+    Scheduled,
+    Manual,
+}
+
+impl ExportTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportTrigger::Scheduled => "scheduled",
+            ExportTrigger::Manual => "manual",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scheduled" => Ok(ExportTrigger::Scheduled),
+            "manual" => Ok(ExportTrigger::Manual),
+            other => Err(format!("Unknown export trigger '{}'. Use scheduled or manual", other)),
+        }
+    }
+}
+
+/// One run of the object storage exporter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRun {
+    pub id: Uuid,
+    pub triggered_by: ExportTrigger,
+    /// Running, succeeded or failed; export runs are never queued
+    pub status: JobStatus,
+    /// Rows stored after this instant were exported; `None` for a first, full export
+    pub data_since: Option<DateTime<Utc>>,
+    /// Rows stored up to this instant were exported
+    pub data_until: DateTime<Utc>,
+    pub objects_written: i64,
+    pub rows_written: i64,
+    pub bytes_written: i64,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// Rate limiting configuration
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: u32,
+    pub yahoo_api_requests_per_minute: u32,
+    /// Longest a Yahoo call queues for a free slot; zero disables queueing
+    pub yahoo_api_max_wait: std::time::Duration,
+    /// Share of the Yahoo window available to bulk operations
+    pub bulk_yahoo_requests_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 100,
+            yahoo_api_requests_per_minute: 30, // Conservative limit for Yahoo Finance API
+            yahoo_api_max_wait: std::time::Duration::ZERO,
+            bulk_yahoo_requests_per_minute: 15,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn flow(kind: &str, amount: Decimal, flow_date: &str) -> CashFlow {
+        CashFlow {
+            id: Uuid::new_v4(),
+            kind: kind.to_string(),
+            amount,
+            flow_date: date(flow_date),
+            notes: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn trade(symbol: &str, side: &str, quantity: Decimal, price: Decimal, fees: Decimal, trade_date: &str) -> PortfolioTransaction {
+        PortfolioTransaction {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity,
+            price,
+            fees,
+            trade_date: date(trade_date),
+            notes: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn holding(symbol: &str, quantity: &str, purchase_price: &str) -> PortfolioHolding {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "symbol": symbol,
+            "symbol_id": null,
+            "asset_type": "stock",
+            "quantity": quantity,
+            "purchase_price": purchase_price,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    fn dividend(symbol: &str, ex_date: &str, amount: Decimal) -> Dividend {
+        Dividend {
+            id: Uuid::new_v4(),
+            symbol_id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            ex_date: date(ex_date),
+            amount,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn cash_without_flows_is_not_tracked() {
+        let holdings = [holding("AAPL", "10", "150")];
+        let cash = CashSummary::from_ledger(Vec::new(), &[], &holdings, &[], date("2024-06-01"));
+        assert_eq!(cash.balance, Decimal::ZERO);
+        assert_eq!(cash.total_purchases, Decimal::ZERO);
+    }
+
+    #[test]
+    fn cash_debits_buys_and_credits_sells_and_dividends() {
+        let flows = vec![flow("deposit", d("10000"), "2024-01-02"), flow("withdrawal", d("500"), "2024-05-01")];
+        let transactions = [
+            trade("MSFT", "buy", d("10"), d("300"), d("5"), "2024-01-03"),
+            trade("MSFT", "sell", d("4"), d("350"), d("2"), "2024-04-01"),
+        ];
+        // 10 shares held on the first ex-date, 6 on the second
+        let dividends = [dividend("MSFT", "2024-02-14", d("0.75")), dividend("MSFT", "2024-05-15", d("0.75"))];
+        let cash = CashSummary::from_ledger(flows, &transactions, &[], &dividends, date("2024-06-01"));
+
+        assert_eq!(cash.total_purchases, d("3005"));
+        assert_eq!(cash.total_sales, d("1398"));
+        assert_eq!(cash.total_dividends, d("12.00"));
+        assert_eq!(cash.balance, d("10000") - d("500") - d("3005") + d("1398") + d("12"));
+    }
+
+    #[test]
+    fn cash_uses_holding_cost_only_for_symbols_missing_from_the_ledger() {
+        let flows = vec![flow("deposit", d("5000"), "2024-01-02")];
+        let transactions = [trade("MSFT", "buy", d("2"), d("300"), Decimal::ZERO, "2024-01-03")];
+        let holdings = [holding("MSFT", "2", "300"), holding("AAPL", "10", "150")];
+        let cash = CashSummary::from_ledger(flows, &transactions, &holdings, &[], date("2024-06-01"));
+
+        assert_eq!(cash.total_purchases, d("2100"));
+        assert_eq!(cash.balance, d("2900"));
+    }
+
+    #[test]
+    fn cash_ignores_trades_and_dividends_after_as_of() {
+        let flows = vec![flow("deposit", d("1000"), "2024-01-02")];
+        let transactions = [trade("MSFT", "buy", d("1"), d("300"), Decimal::ZERO, "2024-07-01")];
+        let dividends = [dividend("MSFT", "2024-08-15", d("0.75"))];
+        let cash = CashSummary::from_ledger(flows, &transactions, &[], &dividends, date("2024-06-01"));
+        assert_eq!(cash.balance, d("1000"));
+    }
+
+    #[test]
+    fn money_weighted_return_counts_invested_cash_once() {
+        // 1000 deposited and fully invested, now worth 1100 a year later: 10%
+        let flows = vec![flow("deposit", d("1000"), "2023-01-01")];
+        let transactions = [trade("MSFT", "buy", d("4"), d("250"), Decimal::ZERO, "2023-01-01")];
+        let cash = CashSummary::from_ledger(flows, &transactions, &[], &[], date("2024-01-01"));
+        assert_eq!(cash.balance, Decimal::ZERO);
+
+        let holdings_value = d("1100");
+        let mwr = cash.money_weighted_return(holdings_value + cash.balance, date("2024-01-01")).unwrap();
+        assert_eq!(mwr, d("10.00"));
+    }
+
+    #[test]
+    fn money_weighted_return_needs_a_deposit() {
+        let cash = CashSummary::default();
+        assert!(cash.money_weighted_return(d("100"), date("2024-01-01")).is_none());
     }
 }
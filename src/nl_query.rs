@@ -0,0 +1,353 @@
+//! Rule-based parser for short natural-language market questions.
+//!
+//! `POST /api/query` turns text such as "AAPL vs MSFT last 6 months", "RSI for TSLA" or
+//! "weekly NVDA since 2024-01-01" into one of the existing compare, historical, indicators
+//! or quote calls. There is no model involved: symbols, a lookback period, an interval and
+//! a few keywords are picked out of the text, and the parsed intent is returned with the
+//! data so callers can see how the question was understood.
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::config::MAX_COMPARE_SYMBOLS;
+use crate::validation::validate_symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Intent {
+    Compare,
+    Historical,
+    Indicators,
+    Quote,
+}
+
+/// Lookback window, as a start date and the calendar days it covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Period {
+    /// The phrase it was read from, e.g. "last 6 months"
+    pub text: String,
+    pub start_date: NaiveDate,
+    pub days: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParsedQuery {
+    pub intent: Intent,
+    pub symbols: Vec<String>,
+    pub period: Option<Period>,
+    pub interval: &'static str,
+}
+
+impl ParsedQuery {
+    /// Approximate number of candles of `interval` in the period
+    pub fn candles(&self) -> Option<i32> {
+        self.period.as_ref().map(|period| {
+            let trading_days = (period.days * 5 + 6) / 7;
+            let candles = match self.interval {
+                "1h" => trading_days * 7,
+                "1wk" => period.days / 7,
+                "1mo" => period.days / 30,
+                _ => trading_days,
+            };
+            candles.clamp(1, i32::MAX as i64) as i32
+        })
+    }
+}
+
+/// Body of a successful `POST /api/query`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryResponse {
+    pub query: String,
+    pub parsed: ParsedQuery,
+    /// The response data of the endpoint the query was routed to
+    pub data: serde_json::Value,
+}
+
+/// Words that are never read as ticker symbols
+const STOPWORDS: &[&str] = &[
+    "a", "about", "against", "ago", "all", "an", "and", "are", "as", "at", "bands", "between", "bollinger",
+    "by", "candles", "chart", "close", "compare", "comparison", "current", "daily", "data", "date",
+    "day", "days", "did", "do", "does", "ema", "for", "from", "get", "give", "has", "have", "history",
+    "historical", "hourly", "how", "i", "in", "indicator", "indicators", "is", "it", "last", "latest",
+    "list", "macd", "me", "month", "monthly", "months", "now", "of", "on", "over", "past", "perform",
+    "performance", "performed", "price", "prices", "quarter", "quote", "rsi", "show", "since", "sma",
+    "stock", "stocks", "technical", "technicals", "than", "the", "this", "to", "today", "trend", "versus",
+    "vs", "was", "week", "weekly", "weeks", "what", "whats", "with", "year", "years", "ytd",
+];
+
+const INDICATOR_WORDS: &[&str] = &[
+    "rsi", "macd", "sma", "ema", "bollinger", "indicator", "indicators", "technical", "technicals",
+];
+const COMPARE_WORDS: &[&str] = &["vs", "versus", "compare", "comparison", "against"];
+const HISTORY_WORDS: &[&str] = &["history", "historical", "chart", "candles", "prices", "trend"];
+
+/// Parse `text`, with relative periods counted back from `today`
+pub fn parse(text: &str, today: NaiveDate) -> Result<ParsedQuery, String> {
+    let tokens: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '?' | '!' | ';' | '(' | ')' | '/'))
+        .map(|t| t.trim_end_matches(['.', ':']))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let words: Vec<String> = tokens.iter().map(|t| t.to_ascii_lowercase()).collect();
+    let has = |list: &[&str]| words.iter().any(|w| list.contains(&w.as_str()));
+
+    let (period, period_tokens) = parse_period(&words, today)?;
+    let symbols = parse_symbols(&tokens, &words, &period_tokens)?;
+    if symbols.is_empty() {
+        return Err(format!(
+            "No ticker symbol found in '{}'. Try e.g. \"AAPL vs MSFT last 6 months\"",
+            text.trim()
+        ));
+    }
+
+    let intent = if symbols.len() > 1 || has(COMPARE_WORDS) {
+        Intent::Compare
+    } else if has(INDICATOR_WORDS) {
+        Intent::Indicators
+    } else if period.is_some() || has(HISTORY_WORDS) {
+        Intent::Historical
+    } else {
+        Intent::Quote
+    };
+
+    if intent == Intent::Compare && symbols.len() < 2 {
+        return Err("Name at least two symbols to compare, e.g. \"AAPL vs MSFT\"".to_string());
+    }
+    if symbols.len() > MAX_COMPARE_SYMBOLS {
+        return Err(format!("Too many symbols: at most {} can be compared", MAX_COMPARE_SYMBOLS));
+    }
+
+    let interval = if words.iter().any(|w| w == "hourly") {
+        "1h"
+    } else if words.iter().any(|w| w == "weekly") {
+        "1wk"
+    } else if words.iter().any(|w| w == "monthly") {
+        "1mo"
+    } else {
+        "1d"
+    };
+
+    Ok(ParsedQuery {
+        intent,
+        symbols,
+        period,
+        interval,
+    })
+}
+
+/// Find the lookback period; also returns the token positions it consumed
+fn parse_period(words: &[String], today: NaiveDate) -> Result<(Option<Period>, Vec<usize>), String> {
+    for (i, word) in words.iter().enumerate() {
+        match word.as_str() {
+            "ytd" => {
+                let start = NaiveDate::from_ymd_opt(today.year(), 1, 1).expect("January 1st exists");
+                return Ok((Some(period("year to date", start, today)), vec![i]));
+            }
+            "since" => {
+                if let Some(date) = words.get(i + 1).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+                    if date > today {
+                        return Err(format!("Start date {} is in the future", date));
+                    }
+                    return Ok((Some(period(&format!("since {}", date), date, today)), vec![i, i + 1]));
+                }
+            }
+            "last" | "past" => {
+                // "last 6 months", "past 2 weeks", "last year"
+                let (count, unit_at) = match words.get(i + 1).and_then(|n| n.parse::<i64>().ok()) {
+                    Some(count) => (count, i + 2),
+                    None => (1, i + 1),
+                };
+                if let Some(days) = words.get(unit_at).and_then(|unit| unit_days(unit)) {
+                    let text = words[i..=unit_at].join(" ");
+                    let consumed = (i..=unit_at).collect();
+                    return Ok((Some(lookback(&text, count, days, today)?), consumed));
+                }
+            }
+            _ => {
+                // Compact forms: 30d, 6w, 6m, 6mo, 1y
+                let split = word.find(|c: char| !c.is_ascii_digit()).unwrap_or(word.len());
+                let (count, unit) = word.split_at(split);
+                if let (Ok(count), Some(days)) = (count.parse::<i64>(), unit_days(unit)) {
+                    if unit.len() <= 2 {
+                        return Ok((Some(lookback(word, count, days, today)?), vec![i]));
+                    }
+                }
+            }
+        }
+    }
+    Ok((None, Vec::new()))
+}
+
+fn unit_days(unit: &str) -> Option<i64> {
+    match unit {
+        "d" | "day" | "days" => Some(1),
+        "w" | "wk" | "week" | "weeks" => Some(7),
+        "m" | "mo" | "month" | "months" => Some(30),
+        "quarter" | "quarters" => Some(91),
+        "y" | "yr" | "year" | "years" => Some(365),
+        _ => None,
+    }
+}
+
+fn lookback(text: &str, count: i64, unit_days: i64, today: NaiveDate) -> Result<Period, String> {
+    let days = count.saturating_mul(unit_days);
+    if !(1..=3650).contains(&days) {
+        return Err(format!("Period '{}' must be between 1 day and 10 years", text));
+    }
+    Ok(period(text, today - Duration::days(days), today))
+}
+
+fn period(text: &str, start_date: NaiveDate, today: NaiveDate) -> Period {
+    Period {
+        text: text.to_string(),
+        start_date,
+        days: (today - start_date).num_days().max(1),
+    }
+}
+
+/// Ticker symbols in order of appearance. `$AAPL` cashtags and upper-case words count;
+/// when the text has neither, any word that is not a known keyword is tried.
+fn parse_symbols(tokens: &[&str], words: &[String], skip: &[usize]) -> Result<Vec<String>, String> {
+    let candidates: Vec<(usize, &str)> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !skip.contains(i))
+        .filter(|(i, _)| !STOPWORDS.contains(&words[*i].trim_start_matches('$')))
+        .map(|(i, t)| (i, *t))
+        .collect();
+
+    let explicit: Vec<&str> = candidates
+        .iter()
+        .filter(|(_, t)| t.starts_with('$') || (t.chars().any(|c| c.is_ascii_uppercase()) && !t.chars().any(|c| c.is_ascii_lowercase())))
+        .map(|(_, t)| *t)
+        .collect();
+    let chosen: Vec<&str> = if explicit.is_empty() {
+        candidates
+            .iter()
+            .map(|(_, t)| *t)
+            .filter(|t| t.chars().any(|c| c.is_ascii_alphabetic()) && t.len() <= 6)
+            .collect()
+    } else {
+        explicit
+    };
+
+    let mut symbols: Vec<String> = Vec::new();
+    for token in chosen {
+        let symbol = token.trim_start_matches('$').to_ascii_uppercase();
+        if symbol.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        validate_symbol(&symbol).map_err(|_| format!("'{}' is not a valid ticker symbol", token))?;
+        if !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    Ok(symbols)
+}
+
+/// Parse relative to the current UTC date
+pub fn parse_now(text: &str) -> Result<ParsedQuery, String> {
+    parse(text, Utc::now().date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 15).unwrap()
+    }
+
+    fn parsed(text: &str) -> ParsedQuery {
+        parse(text, today()).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn symbols_joined_by_vs_are_compared() {
+        let query = parsed("AAPL vs MSFT last 6 months");
+        assert_eq!(query.intent, Intent::Compare);
+        assert_eq!(query.symbols, ["AAPL", "MSFT"]);
+        let period = query.period.unwrap();
+        assert_eq!(period.text, "last 6 months");
+        assert_eq!(period.start_date, today() - Duration::days(180));
+        assert_eq!(query.interval, "1d");
+    }
+
+    #[test]
+    fn lower_case_text_falls_back_to_non_keywords() {
+        let query = parsed("compare aapl and msft");
+        assert_eq!(query.intent, Intent::Compare);
+        assert_eq!(query.symbols, ["AAPL", "MSFT"]);
+        assert_eq!(parsed("compare $aapl against $msft").symbols, ["AAPL", "MSFT"]);
+    }
+
+    #[test]
+    fn indicator_keywords_ask_for_indicators() {
+        let query = parsed("RSI for TSLA");
+        assert_eq!(query.intent, Intent::Indicators);
+        assert_eq!(query.symbols, ["TSLA"]);
+        assert!(query.period.is_none());
+        assert_eq!(parsed("show me bollinger bands on NVDA").intent, Intent::Indicators);
+    }
+
+    #[test]
+    fn since_a_date_asks_for_history() {
+        let query = parsed("weekly NVDA since 2024-01-01");
+        assert_eq!(query.intent, Intent::Historical);
+        assert_eq!(query.interval, "1wk");
+        let period = query.period.clone().unwrap();
+        assert_eq!(period.text, "since 2024-01-01");
+        assert_eq!(period.start_date, date(2024, 1, 1));
+        assert_eq!(query.candles(), Some(period.days as i32 / 7));
+    }
+
+    #[test]
+    fn relative_periods() {
+        assert_eq!(parsed("$SPY ytd").period.unwrap().start_date, date(2026, 1, 1));
+        assert_eq!(parsed("AAPL past year").period.unwrap().days, 365);
+        assert_eq!(parsed("AAPL last quarter").period.unwrap().days, 91);
+        assert_eq!(parsed("AAPL last 2 weeks").period.unwrap().days, 14);
+        for (compact, days) in [("30d", 30), ("6w", 42), ("6m", 180), ("6mo", 180), ("1y", 365)] {
+            let query = parsed(&format!("AAPL {compact}"));
+            assert_eq!(query.intent, Intent::Historical, "{compact}");
+            assert_eq!(query.period.unwrap().days, days, "{compact}");
+        }
+    }
+
+    #[test]
+    fn history_keywords_and_intervals() {
+        let query = parsed("hourly AAPL chart");
+        assert_eq!(query.intent, Intent::Historical);
+        assert_eq!(query.interval, "1h");
+        assert!(query.period.is_none());
+        assert_eq!(parsed("monthly MSFT last 2 years").interval, "1mo");
+        // 180 calendar days hold about 129 trading days
+        assert_eq!(parsed("AAPL last 6 months").candles(), Some(129));
+    }
+
+    #[test]
+    fn a_bare_symbol_asks_for_a_quote() {
+        assert_eq!(parsed("AAPL").intent, Intent::Quote);
+        let query = parsed("what is the price of aapl?");
+        assert_eq!(query.intent, Intent::Quote);
+        assert_eq!(query.symbols, ["AAPL"]);
+        assert_eq!(parsed("BRK.B").symbols, ["BRK.B"]);
+    }
+
+    #[test]
+    fn unrecognised_queries_are_rejected() {
+        assert!(parse("how did it perform today?", today()).unwrap_err().starts_with("No ticker symbol found"));
+        assert!(parse("", today()).unwrap_err().starts_with("No ticker symbol found"));
+        assert!(parse("compare AAPL", today()).unwrap_err().starts_with("Name at least two symbols"));
+        assert_eq!(parse("AAPL since 2027-01-01", today()).unwrap_err(), "Start date 2027-01-01 is in the future");
+        assert!(parse("AAPL last 20 years", today()).unwrap_err().contains("between 1 day and 10 years"));
+        assert_eq!(parse("$AA_PL", today()).unwrap_err(), "'$AA_PL' is not a valid ticker symbol");
+
+        let many: Vec<String> = (0..=MAX_COMPARE_SYMBOLS).map(|i| format!("S{i}A")).collect();
+        assert!(parse(&many.join(" vs "), today()).unwrap_err().starts_with("Too many symbols"));
+    }
+}
@@ -0,0 +1,425 @@
+//! Email and Telegram notification channels.
+//!
+//! Channels are configured through the environment (see [`NotificationConfig`]) and picked
+//! per alert by name. Every attempt, including ones to a channel that is not configured,
+//! is written to the notification history so missed alerts can be traced afterwards.
+//! Email goes out through a minimal SMTP client with implicit TLS or STARTTLS; Telegram
+//! messages through the Bot API `sendMessage` method.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::Utc;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::config::{NotificationConfig, SmtpConfig, TelegramConfig};
+use crate::database::Database;
+use crate::models::Notification;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationChannel {
+    Email,
+    Telegram,
+}
+
+impl NotificationChannel {
+    pub const ALL: [NotificationChannel; 2] = [NotificationChannel::Email, NotificationChannel::Telegram];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Email => "email",
+            NotificationChannel::Telegram => "telegram",
+        }
+    }
+}
+
+impl FromStr for NotificationChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|channel| channel.as_str() == s.to_ascii_lowercase())
+            .ok_or_else(|| format!("Unknown notification channel '{}'. Use email or telegram", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// TLS from the first byte, usually port 465
+    Implicit,
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    StartTls,
+    /// No encryption, only for local relays
+    None,
+}
+
+impl SmtpTls {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpTls::Implicit => "tls",
+            SmtpTls::StartTls => "starttls",
+            SmtpTls::None => "none",
+        }
+    }
+}
+
+impl FromStr for SmtpTls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tls" | "implicit" => Ok(SmtpTls::Implicit),
+            "starttls" => Ok(SmtpTls::StartTls),
+            "none" => Ok(SmtpTls::None),
+            _ => Err(format!("Invalid SMTP_TLS '{}'. Use tls, starttls or none", s)),
+        }
+    }
+}
+
+/// A way to get a short text message to a person
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn channel(&self) -> NotificationChannel;
+
+    async fn send(&self, subject: &str, body: &str) -> Result<()>;
+}
+
+/// Channels with complete settings in `config`
+pub fn configured_channels(config: &NotificationConfig) -> Vec<NotificationChannel> {
+    NotificationChannel::ALL
+        .into_iter()
+        .filter(|channel| notifier(config, *channel).is_some())
+        .collect()
+}
+
+/// The configured channels an alert picked: all of them when it picked none (`None`), and
+/// only the listed ones otherwise
+pub fn selected_channels(configured: &[NotificationChannel], selection: Option<&[String]>) -> Vec<NotificationChannel> {
+    match selection {
+        None => configured.to_vec(),
+        Some(names) => configured
+            .iter()
+            .copied()
+            .filter(|channel| names.iter().any(|name| name.parse() == Ok(*channel)))
+            .collect(),
+    }
+}
+
+/// The notifier for `channel`, or `None` when it is not configured
+pub fn notifier(config: &NotificationConfig, channel: NotificationChannel) -> Option<Box<dyn Notifier>> {
+    match channel {
+        NotificationChannel::Email => config
+            .email
+            .clone()
+            .map(|config| Box::new(EmailNotifier::new(config)) as Box<dyn Notifier>),
+        NotificationChannel::Telegram => config
+            .telegram
+            .clone()
+            .map(|config| Box::new(TelegramNotifier::new(config)) as Box<dyn Notifier>),
+    }
+}
+
+/// Send to each of `channels` in the background. `source` names the trigger in the history.
+pub fn dispatch(
+    db: Arc<Database>,
+    config: NotificationConfig,
+    channels: Vec<NotificationChannel>,
+    source: String,
+    subject: String,
+    body: String,
+) {
+    tokio::spawn(async move {
+        let sends = channels
+            .into_iter()
+            .map(|channel| send(&db, &config, channel, &source, &subject, &body));
+        futures::future::join_all(sends).await;
+    });
+}
+
+/// Send one notification and record the outcome in the history
+pub async fn send(
+    db: &Database,
+    config: &NotificationConfig,
+    channel: NotificationChannel,
+    source: &str,
+    subject: &str,
+    body: &str,
+) -> Notification {
+    let result = match notifier(config, channel) {
+        Some(notifier) => notifier.send(subject, body).await,
+        None => Err(anyhow::anyhow!("{} channel is not configured", channel.as_str())),
+    };
+
+    let error = result.err().map(|e| format!("{:#}", e));
+    if let Some(e) = &error {
+        warn!("{} notification from {} failed: {}", channel.as_str(), source, e);
+    }
+
+    let notification = Notification {
+        id: Uuid::new_v4(),
+        channel: channel.as_str().to_string(),
+        source: source.to_string(),
+        subject: subject.to_string(),
+        body: body.to_string(),
+        success: error.is_none(),
+        error,
+        created_at: Utc::now(),
+    };
+    if let Err(e) = db.insert_notification(&notification).await {
+        error!("Failed to record notification {}: {}", notification.id, e);
+    }
+    notification
+}
+
+pub struct TelegramNotifier {
+    config: TelegramConfig,
+}
+
+impl TelegramNotifier {
+    pub fn new(config: TelegramConfig) -> Self {
+        Self { config }
+    }
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("mango-data-service/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("reqwest client with static configuration")
+    })
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Telegram
+    }
+
+    async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let url = format!("{}/bot{}/sendMessage", self.config.api_url, self.config.bot_token);
+        // The URL carries the bot token, so keep it out of error messages
+        let response = http_client()
+            .post(url)
+            .json(&serde_json::json!({
+                "chat_id": self.config.chat_id,
+                "text": format!("{}\n\n{}", subject, body),
+                "disable_web_page_preview": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+
+        let status = response.status();
+        let reply: serde_json::Value = response.json().await.map_err(|e| e.without_url())?;
+        if !status.is_success() || reply["ok"] != serde_json::Value::Bool(true) {
+            let description = reply["description"].as_str().unwrap_or("no description");
+            bail!("Telegram API returned HTTP {}: {}", status, description);
+        }
+        Ok(())
+    }
+}
+
+pub struct EmailNotifier {
+    config: SmtpConfig,
+}
+
+impl EmailNotifier {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Email
+    }
+
+    async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let message = build_message(&self.config, subject, body);
+        tokio::time::timeout(SMTP_TIMEOUT, send_mail(&self.config, &message))
+            .await
+            .context("SMTP session timed out")?
+    }
+}
+
+trait SmtpStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> SmtpStream for T {}
+
+struct SmtpConnection {
+    stream: BufReader<Box<dyn SmtpStream>>,
+}
+
+impl SmtpConnection {
+    fn new(stream: Box<dyn SmtpStream>) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// Read one possibly multi-line reply, failing unless its code is in `expected`
+    async fn expect(&mut self, expected: &[u16]) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                bail!("SMTP server closed the connection");
+            }
+            let line = line.trim_end();
+            let code: u16 = line
+                .get(..3)
+                .and_then(|code| code.parse().ok())
+                .with_context(|| format!("Malformed SMTP reply '{}'", line))?;
+            text.push_str(line.get(4..).unwrap_or(""));
+            text.push('\n');
+
+            if line.as_bytes().get(3) != Some(&b'-') {
+                if !expected.contains(&code) {
+                    bail!("SMTP server replied {} {}", code, text.trim_end());
+                }
+                return Ok(text);
+            }
+        }
+    }
+
+    async fn command(&mut self, command: &str, expected: &[u16]) -> Result<String> {
+        let stream = self.stream.get_mut();
+        stream.write_all(command.as_bytes()).await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+        self.expect(expected).await
+    }
+
+    /// Upgrade the connection after a successful STARTTLS
+    async fn start_tls(self, host: &str) -> Result<Self> {
+        let stream = tls_connect(host, self.stream.into_inner()).await?;
+        Ok(Self::new(stream))
+    }
+}
+
+fn tls_config() -> Arc<ClientConfig> {
+    static TLS_CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    TLS_CONFIG
+        .get_or_init(|| {
+            let roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("ring supports the default protocol versions")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Arc::new(config)
+        })
+        .clone()
+}
+
+async fn tls_connect(host: &str, stream: Box<dyn SmtpStream>) -> Result<Box<dyn SmtpStream>> {
+    let server_name = ServerName::try_from(host.to_string()).context("Invalid SMTP_HOST for TLS")?;
+    let stream = TlsConnector::from(tls_config())
+        .connect(server_name, stream)
+        .await
+        .context("TLS handshake with SMTP server failed")?;
+    Ok(Box::new(stream))
+}
+
+async fn send_mail(config: &SmtpConfig, message: &str) -> Result<()> {
+    let tcp = TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .with_context(|| format!("Cannot connect to SMTP server {}:{}", config.host, config.port))?;
+    let stream: Box<dyn SmtpStream> = match config.tls {
+        SmtpTls::Implicit => tls_connect(&config.host, Box::new(tcp)).await?,
+        _ => Box::new(tcp),
+    };
+
+    let mut conn = SmtpConnection::new(stream);
+    conn.expect(&[220]).await?;
+    let mut capabilities = conn.command("EHLO localhost", &[250]).await?;
+    if config.tls == SmtpTls::StartTls {
+        conn.command("STARTTLS", &[220]).await?;
+        conn = conn.start_tls(&config.host).await?;
+        capabilities = conn.command("EHLO localhost", &[250]).await?;
+    }
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        let auth_line = capabilities
+            .lines()
+            .find(|line| line.to_ascii_uppercase().starts_with("AUTH"))
+            .map(|line| line.to_ascii_uppercase())
+            .unwrap_or_default();
+        if auth_line.contains("PLAIN") || !auth_line.contains("LOGIN") {
+            let credentials = BASE64.encode(format!("\0{}\0{}", username, password));
+            conn.command(&format!("AUTH PLAIN {}", credentials), &[235]).await?;
+        } else {
+            conn.command("AUTH LOGIN", &[334]).await?;
+            conn.command(&BASE64.encode(username), &[334]).await?;
+            conn.command(&BASE64.encode(password), &[235]).await?;
+        }
+    }
+
+    conn.command(&format!("MAIL FROM:<{}>", mailbox(&config.from)), &[250]).await?;
+    for to in &config.to {
+        conn.command(&format!("RCPT TO:<{}>", mailbox(to)), &[250, 251]).await?;
+    }
+    conn.command("DATA", &[354]).await?;
+    conn.command(&format!("{}\r\n.", message), &[250]).await?;
+    // The message is accepted at this point; a failed QUIT does not matter
+    let _ = conn.command("QUIT", &[221]).await;
+    Ok(())
+}
+
+/// Bare address from `Name <user@example.com>` or `user@example.com`
+fn mailbox(address: &str) -> &str {
+    match (address.find('<'), address.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &address[start + 1..end],
+        _ => address.trim(),
+    }
+}
+
+/// RFC 5322 message with CRLF line endings and dot-stuffed body
+fn build_message(config: &SmtpConfig, subject: &str, body: &str) -> String {
+    let subject: String = subject.chars().filter(|c| !c.is_control()).collect();
+    let subject = if subject.is_ascii() {
+        subject
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64.encode(subject))
+    };
+    let domain = mailbox(&config.from).rsplit('@').next().unwrap_or("localhost");
+
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\n\
+         MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        config.from,
+        config.to.join(", "),
+        subject,
+        Utc::now().to_rfc2822(),
+        Uuid::new_v4(),
+        domain,
+    );
+    for line in body.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    // The DATA terminator supplies the final line break
+    message.truncate(message.len() - 2);
+    message
+}
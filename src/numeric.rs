@@ -0,0 +1,112 @@
+//! `?numeric=` choice of how decimal values are written in responses.
+//!
+//! Prices, quantities and amounts are `Decimal`s, serialized as strings so no precision is
+//! lost. Some charting libraries only accept JSON numbers, so `?numeric=float` writes them
+//! as floats for the one request instead. The mode lives in a task-local set by
+//! [`numeric_mode_middleware`]; everything serialized outside a request, such as webhook
+//! payloads, stored job results and the database itself, keeps strings.
+
+use axum::{
+    extract::{Query, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+
+use crate::errors::AppError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumericMode {
+    /// Exact decimal strings, e.g. `"185.92"`
+    #[default]
+    String,
+    /// JSON numbers, e.g. `185.92`
+    Float,
+}
+
+impl NumericMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "string" => Some(Self::String),
+            "float" => Some(Self::Float),
+            _ => None,
+        }
+    }
+}
+
+tokio::task_local! {
+    static MODE: NumericMode;
+}
+
+/// Mode of the request being served, strings outside of one
+pub fn current() -> NumericMode {
+    MODE.try_with(|mode| *mode).unwrap_or_default()
+}
+
+/// `serialize_with` for `Decimal` fields of response models
+pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    match current() {
+        NumericMode::Float => serializer.serialize_f64(to_float(value)),
+        NumericMode::String => Serialize::serialize(value, serializer),
+    }
+}
+
+/// Nearest float to a decimal. Going through the string rounds correctly, where
+/// `Decimal::to_f64` can land a step off for values with many decimal places.
+fn to_float(value: &Decimal) -> f64 {
+    value.to_string().parse().unwrap_or_else(|_| value.to_f64().unwrap_or_default())
+}
+
+/// `serialize_with` for `Option<Decimal>` fields of response models
+pub fn serialize_option<S: Serializer>(
+    value: &Option<Decimal>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serialize(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// `serialize_with` for maps of `Decimal`s, e.g. target weights by symbol
+pub fn serialize_map<S: Serializer>(
+    values: &HashMap<String, Decimal>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(values.iter().map(|(key, value)| (key, Number(*value))))
+}
+
+/// A `Decimal` that follows the request's mode, for values put straight into `json!`
+#[derive(Debug, Clone, Copy)]
+pub struct Number(pub Decimal);
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NumericParams {
+    numeric: Option<String>,
+}
+
+/// Serve the request in the mode named by `?numeric=string|float`
+pub async fn numeric_mode_middleware(request: Request, next: Next) -> Response {
+    let mode = match Query::<NumericParams>::try_from_uri(request.uri()) {
+        Ok(Query(NumericParams { numeric: Some(mode) })) => match NumericMode::parse(&mode) {
+            Some(mode) => mode,
+            None => {
+                return AppError::ValidationError(
+                    "Invalid numeric mode. Must be one of: string, float".to_string(),
+                )
+                .into_response()
+            }
+        },
+        _ => NumericMode::default(),
+    };
+    MODE.scope(mode, next.run(request)).await
+}
@@ -0,0 +1,707 @@
+//! OAuth2 / OpenID Connect login for the web UI.
+//!
+//! Implements the authorization code flow with PKCE. Google and generic issuers are
+//! resolved through OIDC discovery; GitHub is plain OAuth2 with fixed endpoints. For OIDC
+//! providers the ID token is required and checked here: its RS256 signature against the
+//! issuer's JWKS, the issuer, the audience, the expiry and the nonce sent with the login.
+//! The admin's identity is the verified email from the ID token, or from the provider's
+//! user endpoint when the token carries none (and always for GitHub).
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{BigUint, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::OidcConfig;
+
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+const GITHUB_AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const GITHUB_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const GITHUB_USER_EMAILS_URL: &str = "https://api.github.com/user/emails";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Clock difference tolerated when checking an ID token's expiry
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OidcProvider {
+    Google,
+    GitHub,
+    Generic,
+}
+
+impl OidcProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OidcProvider::Google => "google",
+            OidcProvider::GitHub => "github",
+            OidcProvider::Generic => "generic",
+        }
+    }
+
+    /// Label for the login button
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            OidcProvider::Google => "Google",
+            OidcProvider::GitHub => "GitHub",
+            OidcProvider::Generic => "Single Sign-On",
+        }
+    }
+
+    fn default_scopes(&self) -> &'static str {
+        match self {
+            OidcProvider::GitHub => "read:user user:email",
+            _ => "openid email",
+        }
+    }
+}
+
+impl FromStr for OidcProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "google" => Ok(OidcProvider::Google),
+            "github" => Ok(OidcProvider::GitHub),
+            "generic" | "oidc" => Ok(OidcProvider::Generic),
+            other => Err(format!(
+                "Unsupported OIDC provider '{}'. Use google, github or generic",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Endpoints {
+    /// Expected `iss` of ID tokens; absent for GitHub, which issues none
+    #[serde(default)]
+    issuer: Option<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    jwks_uri: Option<String>,
+}
+
+/// State carried between the redirect to the provider and the callback, kept in a
+/// short-lived signed cookie
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub state: String,
+    pub code_verifier: String,
+    /// Must come back in the ID token, tying it to this login
+    #[serde(default)]
+    pub nonce: String,
+}
+
+impl PendingLogin {
+    /// Fresh random state, PKCE verifier and nonce
+    pub fn generate() -> Self {
+        Self {
+            state: random_token(),
+            code_verifier: random_token(),
+            nonce: random_token(),
+        }
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("mango-data-service/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("reqwest client with static configuration")
+    })
+}
+
+async fn endpoints(config: &OidcConfig) -> Result<Endpoints, String> {
+    let issuer = match config.provider {
+        OidcProvider::GitHub => {
+            return Ok(Endpoints {
+                issuer: None,
+                authorization_endpoint: GITHUB_AUTHORIZE_URL.to_string(),
+                token_endpoint: GITHUB_TOKEN_URL.to_string(),
+                userinfo_endpoint: Some(GITHUB_USER_EMAILS_URL.to_string()),
+                jwks_uri: None,
+            })
+        }
+        OidcProvider::Google => GOOGLE_ISSUER,
+        OidcProvider::Generic => config
+            .issuer_url
+            .as_deref()
+            .ok_or("OIDC_ISSUER_URL is required for the generic provider")?,
+    };
+
+    let discovery_url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
+    http_client()
+        .get(&discovery_url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("OIDC discovery failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid OIDC discovery document: {}", e))
+}
+
+/// URL to send the browser to for the provider's consent screen
+pub async fn authorization_url(
+    config: &OidcConfig,
+    pending: &PendingLogin,
+) -> Result<String, String> {
+    let endpoints = endpoints(config).await?;
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(pending.code_verifier.as_bytes()));
+
+    let mut url = reqwest::Url::parse_with_params(
+        &endpoints.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("scope", config.provider.default_scopes()),
+            ("state", pending.state.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| format!("Invalid authorization endpoint: {}", e))?;
+    if endpoints.issuer.is_some() {
+        url.query_pairs_mut().append_pair("nonce", &pending.nonce);
+    }
+
+    Ok(url.into())
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// The provider's public signing keys, served at the discovery document's `jwks_uri`
+#[derive(Debug, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(default, rename = "use")]
+    pub key_use: Option<String>,
+    #[serde(default)]
+    pub n: Option<String>,
+    #[serde(default)]
+    pub e: Option<String>,
+}
+
+impl Jwk {
+    fn rsa_public_key(&self) -> Option<RsaPublicKey> {
+        if self.kty != "RSA" || self.key_use.as_deref().is_some_and(|u| u != "sig") {
+            return None;
+        }
+        let n = URL_SAFE_NO_PAD.decode(self.n.as_deref()?).ok()?;
+        let e = URL_SAFE_NO_PAD.decode(self.e.as_deref()?).ok()?;
+        RsaPublicKey::new(BigUint::from_bytes_be(&n), BigUint::from_bytes_be(&e)).ok()
+    }
+}
+
+#[derive(Deserialize)]
+struct IdTokenHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+/// `aud` is a single client id or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+
+    fn is_single(&self) -> bool {
+        matches!(self, Audience::One(_)) || matches!(self, Audience::Many(auds) if auds.len() == 1)
+    }
+}
+
+/// Claims of a verified ID token that the login uses
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    aud: Audience,
+    #[serde(default)]
+    azp: Option<String>,
+    pub exp: i64,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+}
+
+/// Check an ID token's RS256 signature against `jwks`, then its issuer, audience, expiry
+/// and nonce. Google also issues tokens with the scheme-less `accounts.google.com`.
+pub fn verify_id_token(
+    token: &str,
+    jwks: &Jwks,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+    now: i64,
+) -> Result<IdTokenClaims, String> {
+    let mut parts = token.split('.');
+    let (Some(header_b64), Some(claims_b64), Some(signature_b64), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err("Malformed ID token".to_string());
+    };
+    let decode_json = |part: &str| {
+        URL_SAFE_NO_PAD
+            .decode(part)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .ok_or_else(|| "Malformed ID token".to_string())
+    };
+
+    let header: IdTokenHeader = serde_json::from_value(decode_json(header_b64)?)
+        .map_err(|_| "Malformed ID token header")?;
+    if header.alg != "RS256" {
+        return Err(format!("Unsupported ID token algorithm '{}'", header.alg));
+    }
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature_b64)
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+        .ok_or("Malformed ID token signature")?;
+    let signing_input = &token[..header_b64.len() + 1 + claims_b64.len()];
+    let signed_by_issuer = jwks
+        .keys
+        .iter()
+        .filter(|key| header.kid.is_none() || key.kid == header.kid)
+        .filter_map(Jwk::rsa_public_key)
+        .any(|key| {
+            VerifyingKey::<Sha256>::new(key)
+                .verify(signing_input.as_bytes(), &signature)
+                .is_ok()
+        });
+    if !signed_by_issuer {
+        return Err("ID token signature does not match the provider's keys".to_string());
+    }
+
+    let claims: IdTokenClaims = serde_json::from_value(decode_json(claims_b64)?)
+        .map_err(|e| format!("Invalid ID token claims: {}", e))?;
+    let issuer = issuer.trim_end_matches('/');
+    let google_alias = issuer == GOOGLE_ISSUER && claims.iss == "accounts.google.com";
+    if claims.iss.trim_end_matches('/') != issuer && !google_alias {
+        return Err(format!(
+            "ID token issued by '{}', expected '{}'",
+            claims.iss, issuer
+        ));
+    }
+    if !claims.aud.contains(client_id) {
+        return Err("ID token was issued for another client".to_string());
+    }
+    if !claims.aud.is_single() && claims.azp.as_deref() != Some(client_id) {
+        return Err("ID token was authorized for another client".to_string());
+    }
+    if claims.exp + CLOCK_SKEW_SECONDS < now {
+        return Err("ID token has expired".to_string());
+    }
+    if nonce.is_empty() || claims.nonce.as_deref() != Some(nonce) {
+        return Err("ID token nonce does not match this login".to_string());
+    }
+
+    Ok(claims)
+}
+
+async fn fetch_jwks(jwks_uri: &str) -> Result<Jwks, String> {
+    http_client()
+        .get(jwks_uri)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Fetching provider keys failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid provider keys: {}", e))
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+/// Exchange the authorization code and return the signed-in user's verified email,
+/// lowercased
+pub async fn verified_email(
+    config: &OidcConfig,
+    pending: &PendingLogin,
+    code: &str,
+) -> Result<String, String> {
+    let endpoints = endpoints(config).await?;
+
+    let token: TokenResponse = http_client()
+        .post(&endpoints.token_endpoint)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid token response: {}", e))?;
+
+    // OIDC providers must prove the login with an ID token bound to our nonce
+    let mut id_token_email = None;
+    if let Some(issuer) = endpoints.issuer.as_deref() {
+        let id_token = token
+            .id_token
+            .as_deref()
+            .ok_or("Provider did not return an ID token")?;
+        let jwks_uri = endpoints
+            .jwks_uri
+            .as_deref()
+            .ok_or("Provider does not publish its signing keys")?;
+        let jwks = fetch_jwks(jwks_uri).await?;
+        let claims = verify_id_token(
+            id_token,
+            &jwks,
+            issuer,
+            &config.client_id,
+            &pending.nonce,
+            chrono::Utc::now().timestamp(),
+        )?;
+        id_token_email = claims
+            .email
+            .filter(|_| claims.email_verified.unwrap_or(true));
+    }
+    if let Some(email) = id_token_email {
+        return Ok(email.to_ascii_lowercase());
+    }
+
+    let access_token = match (token.access_token, token.error) {
+        (Some(access_token), None) => access_token,
+        (_, error) => {
+            return Err(format!(
+                "Token exchange rejected: {}",
+                token
+                    .error_description
+                    .or(error)
+                    .unwrap_or_else(|| "no access token".to_string())
+            ))
+        }
+    };
+
+    let userinfo_url = endpoints
+        .userinfo_endpoint
+        .ok_or("Provider does not expose a userinfo endpoint")?;
+    let response = http_client()
+        .get(&userinfo_url)
+        .bearer_auth(&access_token)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("User info request failed: {}", e))?;
+
+    let email = if config.provider == OidcProvider::GitHub {
+        let emails: Vec<GitHubEmail> = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid GitHub email list: {}", e))?;
+        emails
+            .into_iter()
+            .find(|e| e.primary && e.verified)
+            .map(|e| e.email)
+    } else {
+        let info: UserInfo = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid user info: {}", e))?;
+        // Providers that omit email_verified are trusted to only return verified addresses
+        info.email.filter(|_| info.email_verified.unwrap_or(true))
+    };
+
+    email
+        .map(|e| e.to_ascii_lowercase())
+        .ok_or_else(|| "Provider did not return a verified email address".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs1v15::SigningKey;
+    use rsa::signature::{SignatureEncoding, Signer};
+    use rsa::traits::PublicKeyParts;
+    use rsa::RsaPrivateKey;
+    use serde_json::json;
+
+    const ISSUER: &str = "https://issuer.example.com";
+    const CLIENT_ID: &str = "pantera-client";
+    const NONCE: &str = "nonce-123";
+    const NOW: i64 = 1_700_000_000;
+
+    fn test_key() -> RsaPrivateKey {
+        RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap()
+    }
+
+    fn jwks_for(key: &RsaPrivateKey, kid: &str) -> Jwks {
+        let public = key.to_public_key();
+        Jwks {
+            keys: vec![Jwk {
+                kty: "RSA".to_string(),
+                kid: Some(kid.to_string()),
+                key_use: Some("sig".to_string()),
+                n: Some(URL_SAFE_NO_PAD.encode(public.n().to_bytes_be())),
+                e: Some(URL_SAFE_NO_PAD.encode(public.e().to_bytes_be())),
+            }],
+        }
+    }
+
+    fn claims() -> serde_json::Value {
+        json!({
+            "iss": ISSUER,
+            "aud": CLIENT_ID,
+            "exp": NOW + 300,
+            "nonce": NONCE,
+            "email": "Admin@Example.com",
+            "email_verified": true,
+        })
+    }
+
+    fn sign(key: &RsaPrivateKey, header: serde_json::Value, claims: serde_json::Value) -> String {
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = SigningKey::<Sha256>::new(key.clone()).sign(signing_input.as_bytes());
+        format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    fn token(key: &RsaPrivateKey, claims: serde_json::Value) -> String {
+        sign(key, json!({"alg": "RS256", "kid": "k1"}), claims)
+    }
+
+    #[test]
+    fn accepts_valid_id_token() {
+        let key = test_key();
+        let verified = verify_id_token(
+            &token(&key, claims()),
+            &jwks_for(&key, "k1"),
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(verified.email.as_deref(), Some("Admin@Example.com"));
+        assert_eq!(verified.email_verified, Some(true));
+    }
+
+    #[test]
+    fn rejects_wrong_or_missing_nonce() {
+        let key = test_key();
+        let jwks = jwks_for(&key, "k1");
+        let id_token = token(&key, claims());
+        assert!(verify_id_token(&id_token, &jwks, ISSUER, CLIENT_ID, "other-nonce", NOW).is_err());
+        assert!(verify_id_token(&id_token, &jwks, ISSUER, CLIENT_ID, "", NOW).is_err());
+
+        let mut without_nonce = claims();
+        without_nonce.as_object_mut().unwrap().remove("nonce");
+        let err = verify_id_token(
+            &token(&key, without_nonce),
+            &jwks,
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW,
+        )
+        .unwrap_err();
+        assert!(err.contains("nonce"));
+    }
+
+    #[test]
+    fn rejects_wrong_audience() {
+        let key = test_key();
+        let jwks = jwks_for(&key, "k1");
+
+        let mut other_client = claims();
+        other_client["aud"] = json!("someone-else");
+        assert!(verify_id_token(
+            &token(&key, other_client),
+            &jwks,
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_err());
+
+        // Multiple audiences need azp to name this client
+        let mut shared = claims();
+        shared["aud"] = json!([CLIENT_ID, "someone-else"]);
+        assert!(verify_id_token(
+            &token(&key, shared.clone()),
+            &jwks,
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_err());
+        shared["azp"] = json!(CLIENT_ID);
+        assert!(
+            verify_id_token(&token(&key, shared), &jwks, ISSUER, CLIENT_ID, NONCE, NOW).is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_signature() {
+        let key = test_key();
+        let jwks = jwks_for(&key, "k1");
+
+        let forged = token(&test_key(), claims());
+        assert!(verify_id_token(&forged, &jwks, ISSUER, CLIENT_ID, NONCE, NOW).is_err());
+
+        let mut tampered_claims = claims();
+        tampered_claims["email"] = json!("attacker@example.com");
+        let genuine = token(&key, claims());
+        let tampered = genuine.replacen(
+            genuine.split('.').nth(1).unwrap(),
+            &URL_SAFE_NO_PAD.encode(tampered_claims.to_string()),
+            1,
+        );
+        let err = verify_id_token(&tampered, &jwks, ISSUER, CLIENT_ID, NONCE, NOW).unwrap_err();
+        assert!(err.contains("signature"));
+    }
+
+    #[test]
+    fn rejects_unknown_key_id() {
+        let key = test_key();
+        let id_token = token(&key, claims());
+        assert!(verify_id_token(
+            &id_token,
+            &jwks_for(&key, "k2"),
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_unsigned_or_symmetric_algorithms() {
+        let key = test_key();
+        let jwks = jwks_for(&key, "k1");
+        for alg in ["none", "HS256"] {
+            let id_token = sign(&key, json!({"alg": alg, "kid": "k1"}), claims());
+            assert!(verify_id_token(&id_token, &jwks, ISSUER, CLIENT_ID, NONCE, NOW).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_expired_token_and_wrong_issuer() {
+        let key = test_key();
+        let jwks = jwks_for(&key, "k1");
+
+        let mut expired = claims();
+        expired["exp"] = json!(NOW - CLOCK_SKEW_SECONDS - 1);
+        assert!(
+            verify_id_token(&token(&key, expired), &jwks, ISSUER, CLIENT_ID, NONCE, NOW).is_err()
+        );
+
+        let mut other_issuer = claims();
+        other_issuer["iss"] = json!("https://evil.example.com");
+        assert!(verify_id_token(
+            &token(&key, other_issuer),
+            &jwks,
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn accepts_google_schemeless_issuer() {
+        let key = test_key();
+        let mut google = claims();
+        google["iss"] = json!("accounts.google.com");
+        let id_token = token(&key, google);
+        assert!(verify_id_token(
+            &id_token,
+            &jwks_for(&key, "k1"),
+            GOOGLE_ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_ok());
+        assert!(verify_id_token(
+            &id_token,
+            &jwks_for(&key, "k1"),
+            ISSUER,
+            CLIENT_ID,
+            NONCE,
+            NOW
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! Just enough of Apache Parquet to write a small table for pandas, polars, DuckDB or Spark.
+//!
+//! Files hold a single row group of flat columns, each stored as one uncompressed data page
+//! with PLAIN values. Nullable columns carry their definition levels as a bit-packed run, and
+//! dates are INT32 days since the Unix epoch annotated as `DATE`. File and page metadata use
+//! Thrift's compact protocol, written by hand here rather than generated.
+
+use chrono::NaiveDate;
+
+const MAGIC: &[u8] = b"PAR1";
+
+// Physical types
+const INT32: i32 = 1;
+const INT64: i32 = 2;
+const DOUBLE: i32 = 5;
+
+// Repetition types
+const REQUIRED: i32 = 0;
+const OPTIONAL: i32 = 1;
+
+// Encodings
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+
+/// Converted type of day-count dates
+const CONVERTED_DATE: i32 = 6;
+const DATA_PAGE: i32 = 0;
+const UNCOMPRESSED: i32 = 0;
+
+/// Values of one column, one per row
+#[derive(Debug, Clone)]
+pub enum ColumnData {
+    Date(Vec<NaiveDate>),
+    Int64(Vec<i64>),
+    /// Nullable doubles; non-finite values are written as nulls
+    Double(Vec<Option<f64>>),
+}
+
+impl ColumnData {
+    fn len(&self) -> usize {
+        match self {
+            ColumnData::Date(values) => values.len(),
+            ColumnData::Int64(values) => values.len(),
+            ColumnData::Double(values) => values.len(),
+        }
+    }
+
+    fn physical_type(&self) -> i32 {
+        match self {
+            ColumnData::Date(_) => INT32,
+            ColumnData::Int64(_) => INT64,
+            ColumnData::Double(_) => DOUBLE,
+        }
+    }
+
+    fn is_optional(&self) -> bool {
+        matches!(self, ColumnData::Double(_))
+    }
+
+    /// Definition levels, when the column is nullable, followed by the PLAIN values
+    fn page_body(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        match self {
+            ColumnData::Date(values) => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("epoch is a valid date");
+                for date in values {
+                    body.extend_from_slice(&((*date - epoch).num_days() as i32).to_le_bytes());
+                }
+            }
+            ColumnData::Int64(values) => {
+                for value in values {
+                    body.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            ColumnData::Double(values) => {
+                let present: Vec<Option<f64>> = values.iter().map(|v| v.filter(|v| v.is_finite())).collect();
+                // Bit-packed run of 1-bit levels, padded to whole groups of eight
+                let mut levels = Vec::new();
+                let groups = present.len().div_ceil(8);
+                write_varint(&mut levels, ((groups as u64) << 1) | 1);
+                for group in present.chunks(8) {
+                    let byte = group
+                        .iter()
+                        .enumerate()
+                        .fold(0u8, |byte, (i, value)| byte | (u8::from(value.is_some()) << i));
+                    levels.push(byte);
+                }
+                body.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+                body.extend_from_slice(&levels);
+                for value in present.into_iter().flatten() {
+                    body.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        body
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub data: ColumnData,
+}
+
+/// A Parquet file of `columns`, which must all have the same number of rows
+pub fn write(columns: &[Column]) -> Vec<u8> {
+    let rows = columns.first().map_or(0, |column| column.data.len());
+    let mut file = MAGIC.to_vec();
+
+    // (offset, size including page header) of every column's single page
+    let mut chunks = Vec::with_capacity(columns.len());
+    for column in columns {
+        let body = column.data.page_body();
+        let mut header = Compact::new();
+        header.i32(1, DATA_PAGE);
+        header.i32(2, body.len() as i32);
+        header.i32(3, body.len() as i32);
+        header.begin_struct(5);
+        header.i32(1, rows as i32);
+        header.i32(2, PLAIN);
+        header.i32(3, RLE);
+        header.i32(4, RLE);
+        header.end_struct();
+        let header = header.finish();
+
+        chunks.push((file.len() as i64, (header.len() + body.len()) as i64));
+        file.extend_from_slice(&header);
+        file.extend_from_slice(&body);
+    }
+
+    let mut meta = Compact::new();
+    meta.i32(1, 1);
+    meta.list_header(2, Compact::STRUCT, columns.len() + 1);
+    meta.begin_element();
+    meta.binary(4, b"schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end_struct();
+    for column in columns {
+        meta.begin_element();
+        meta.i32(1, column.data.physical_type());
+        meta.i32(3, if column.data.is_optional() { OPTIONAL } else { REQUIRED });
+        meta.binary(4, column.name.as_bytes());
+        if let ColumnData::Date(_) = column.data {
+            meta.i32(6, CONVERTED_DATE);
+            // LogicalType union holding an empty DateType
+            meta.begin_struct(10);
+            meta.begin_struct(6);
+            meta.end_struct();
+            meta.end_struct();
+        }
+        meta.end_struct();
+    }
+    meta.i64(3, rows as i64);
+    meta.list_header(4, Compact::STRUCT, 1);
+    meta.begin_element();
+    meta.list_header(1, Compact::STRUCT, columns.len());
+    for (column, (offset, size)) in columns.iter().zip(&chunks) {
+        meta.begin_element();
+        meta.i64(2, *offset);
+        meta.begin_struct(3);
+        meta.i32(1, column.data.physical_type());
+        let encodings: &[i32] = if column.data.is_optional() { &[PLAIN, RLE] } else { &[PLAIN] };
+        meta.list_header(2, Compact::I32, encodings.len());
+        for encoding in encodings {
+            meta.list_i32(*encoding);
+        }
+        meta.list_header(3, Compact::BINARY, 1);
+        meta.list_binary(column.name.as_bytes());
+        meta.i32(4, UNCOMPRESSED);
+        meta.i64(5, rows as i64);
+        meta.i64(6, *size);
+        meta.i64(7, *size);
+        meta.i64(9, *offset);
+        meta.end_struct();
+        meta.end_struct();
+    }
+    meta.i64(2, chunks.iter().map(|(_, size)| size).sum());
+    meta.i64(3, rows as i64);
+    meta.end_struct();
+    meta.binary(6, concat!("mango-data-service version ", env!("CARGO_PKG_VERSION")).as_bytes());
+    let meta = meta.finish();
+
+    file.extend_from_slice(&meta);
+    file.extend_from_slice(&(meta.len() as u32).to_le_bytes());
+    file.extend_from_slice(MAGIC);
+    file
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Writer of one top-level Thrift struct in the compact protocol
+struct Compact {
+    buf: Vec<u8>,
+    /// Last field id written in each open struct, innermost last
+    last_field: Vec<i16>,
+}
+
+impl Compact {
+    const I32: u8 = 5;
+    const I64: u8 = 6;
+    const BINARY: u8 = 8;
+    const LIST: u8 = 9;
+    const STRUCT: u8 = 12;
+
+    fn new() -> Self {
+        Self { buf: Vec::new(), last_field: vec![0] }
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last_field.last_mut().expect("a struct is open");
+        let delta = id - *last;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | kind);
+        } else {
+            self.buf.push(kind);
+            write_varint(&mut self.buf, zigzag(id as i64));
+        }
+        *last = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, Self::I32);
+        write_varint(&mut self.buf, zigzag(value as i64));
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, Self::I64);
+        write_varint(&mut self.buf, zigzag(value));
+    }
+
+    fn binary(&mut self, id: i16, value: &[u8]) {
+        self.field(id, Self::BINARY);
+        self.list_binary(value);
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field(id, Self::STRUCT);
+        self.last_field.push(0);
+    }
+
+    /// Start a struct that is an element of a list
+    fn begin_element(&mut self) {
+        self.last_field.push(0);
+    }
+
+    fn end_struct(&mut self) {
+        self.buf.push(0);
+        self.last_field.pop();
+    }
+
+    fn list_header(&mut self, id: i16, element: u8, size: usize) {
+        self.field(id, Self::LIST);
+        if size < 15 {
+            self.buf.push(((size as u8) << 4) | element);
+        } else {
+            self.buf.push(0xF0 | element);
+            write_varint(&mut self.buf, size as u64);
+        }
+    }
+
+    fn list_i32(&mut self, value: i32) {
+        write_varint(&mut self.buf, zigzag(value as i64));
+    }
+
+    fn list_binary(&mut self, value: &[u8]) {
+        write_varint(&mut self.buf, value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    /// The struct's bytes, closing it
+    fn finish(mut self) -> Vec<u8> {
+        self.buf.push(0);
+        self.buf
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader of just enough of the compact protocol to walk what `write` produces
+    struct Reader<'a> {
+        buf: &'a [u8],
+        pos: usize,
+    }
+
+    impl Reader<'_> {
+        fn byte(&mut self) -> u8 {
+            self.pos += 1;
+            self.buf[self.pos - 1]
+        }
+
+        fn varint(&mut self) -> u64 {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let byte = self.byte();
+                value |= u64::from(byte & 0x7F) << shift;
+                if byte & 0x80 == 0 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+
+        fn signed(&mut self) -> i64 {
+            let value = self.varint();
+            ((value >> 1) as i64) ^ -((value & 1) as i64)
+        }
+
+        fn skip(&mut self, kind: u8) {
+            match kind {
+                Compact::I32 | Compact::I64 => {
+                    self.varint();
+                }
+                Compact::BINARY => {
+                    let len = self.varint() as usize;
+                    self.pos += len;
+                }
+                Compact::LIST => {
+                    let header = self.byte();
+                    let size = match header >> 4 {
+                        15 => self.varint() as usize,
+                        size => size as usize,
+                    };
+                    for _ in 0..size {
+                        self.skip(header & 0x0F);
+                    }
+                }
+                Compact::STRUCT => {
+                    self.fields();
+                }
+                other => panic!("unexpected compact type {other}"),
+            }
+        }
+
+        /// Integer fields of the struct at the cursor by id; other fields are skipped
+        fn fields(&mut self) -> Vec<(i16, i64)> {
+            let mut fields = Vec::new();
+            let mut last = 0i16;
+            loop {
+                let header = self.byte();
+                if header == 0 {
+                    return fields;
+                }
+                let kind = header & 0x0F;
+                let id = match header >> 4 {
+                    0 => self.signed() as i16,
+                    delta => last + i16::from(delta),
+                };
+                last = id;
+                if kind == Compact::I32 || kind == Compact::I64 {
+                    fields.push((id, self.signed()));
+                } else {
+                    self.skip(kind);
+                }
+            }
+        }
+    }
+
+    fn field(fields: &[(i16, i64)], id: i16) -> i64 {
+        fields.iter().find(|(field, _)| *field == id).map(|(_, value)| *value).expect("field is present")
+    }
+
+    fn sample() -> Vec<Column> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closes = (0..10)
+            .map(|i| match i {
+                2 | 9 => None,
+                5 => Some(f64::NAN),
+                _ => Some(100.0 + i as f64),
+            })
+            .collect();
+        vec![
+            Column { name: "close".to_string(), data: ColumnData::Double(closes) },
+            Column { name: "date".to_string(), data: ColumnData::Date((0..10).map(|i| start + chrono::Duration::days(i)).collect()) },
+            Column { name: "volume".to_string(), data: ColumnData::Int64((0..10).map(|i| i * 1_000).collect()) },
+        ]
+    }
+
+    #[test]
+    fn file_is_framed_by_magic_and_footer_length() {
+        let file = write(&sample());
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(&file[file.len() - 4..], MAGIC);
+
+        let footer_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        let footer_start = file.len() - 8 - footer_len;
+        let mut reader = Reader { buf: &file[..file.len() - 8], pos: footer_start };
+        let meta = reader.fields();
+        assert_eq!(reader.pos, file.len() - 8, "footer length covers exactly the metadata");
+        assert_eq!(field(&meta, 1), 1);
+        assert_eq!(field(&meta, 3), 10);
+    }
+
+    #[test]
+    fn nullable_page_starts_with_bit_packed_definition_levels() {
+        let file = write(&sample());
+        let mut reader = Reader { buf: &file, pos: MAGIC.len() };
+        let header = reader.fields();
+        assert_eq!(field(&header, 1), i64::from(DATA_PAGE));
+
+        let body = &file[reader.pos..reader.pos + field(&header, 2) as usize];
+        // Length 3, then two bit-packed groups: rows 2 and 5 (NaN) are null in the first
+        // eight, row 9 in the last two
+        assert_eq!(&body[..7], &[3, 0, 0, 0, 0x05, 0b1101_1011, 0b0000_0001]);
+        let values: Vec<f64> = body[7..].chunks(8).map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap())).collect();
+        assert_eq!(values, vec![100.0, 101.0, 103.0, 104.0, 106.0, 107.0, 108.0]);
+    }
+
+    #[test]
+    fn required_columns_hold_plain_values_without_levels() {
+        let file = write(&sample());
+        let mut reader = Reader { buf: &file, pos: MAGIC.len() };
+        let header = reader.fields();
+        reader.pos += field(&header, 2) as usize;
+
+        let header = reader.fields();
+        let body = &file[reader.pos..reader.pos + field(&header, 2) as usize];
+        assert_eq!(body.len(), 10 * 4);
+        // 2024-01-01 is day 19723 since the epoch
+        assert_eq!(i32::from_le_bytes(body[..4].try_into().unwrap()), 19_723);
+        assert_eq!(i32::from_le_bytes(body[36..].try_into().unwrap()), 19_732);
+    }
+}
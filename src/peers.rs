@@ -0,0 +1,123 @@
+//! Peer comparison: a symbol's valuation and performance next to stored symbols of its
+//! sector.
+//!
+//! Every figure comes from what is stored (daily candles, the dividends that arrive with
+//! them, and the market cap on the symbols table), so comparing against dozens of peers never
+//! calls the provider. Peers in the same industry rank first, then those whose market cap is
+//! closest to the compared symbol's.
+
+use chrono::Duration;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+use crate::config::TRADING_DAYS_PER_YEAR;
+use crate::models::{Dividend, HistoricalPrice, PeerMedians, PeerMetrics, Symbol};
+use crate::risk;
+
+/// Trading days behind each reported change
+const MONTH_BARS: usize = 21;
+const QUARTER_BARS: usize = 63;
+const YEAR_BARS: usize = 252;
+
+/// Metrics of `symbol` from its daily `candles` (newest first) and the dividends with an
+/// ex-date in the year before the latest candle
+pub fn metrics(symbol: &Symbol, same_industry: bool, candles: &[HistoricalPrice], dividends: &[Dividend]) -> PeerMetrics {
+    let latest = candles.first();
+    let price = latest.map(|candle| candle.close).filter(|close| *close > Decimal::ZERO);
+    let as_of = latest.map(|candle| candle.timestamp.date_naive());
+
+    let year = &candles[..candles.len().min(YEAR_BARS + 1)];
+    let closes: Vec<f64> = year.iter().rev().filter_map(|candle| candle.close.to_f64()).collect();
+    let returns = risk::daily_returns(&closes);
+    let volatility_percent = (returns.len() >= 2)
+        .then(|| {
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+            variance.sqrt() * TRADING_DAYS_PER_YEAR.sqrt() * 100.0
+        })
+        .and_then(Decimal::from_f64)
+        .map(|volatility| volatility.round_dp(2));
+
+    // Dividends arrive with candles, so a year of candles without any means none were paid
+    let dividend_yield_percent = match (price, as_of) {
+        (Some(price), Some(as_of)) => {
+            let since = as_of - Duration::days(365);
+            let paid: Decimal = dividends.iter().filter(|d| d.ex_date > since).map(|d| d.amount).sum();
+            (paid > Decimal::ZERO || candles.len() > YEAR_BARS)
+                .then(|| (paid / price * Decimal::ONE_HUNDRED).round_dp(2))
+        }
+        _ => None,
+    };
+
+    PeerMetrics {
+        symbol: symbol.symbol.clone(),
+        name: symbol.name.clone(),
+        exchange: symbol.exchange.clone(),
+        industry: symbol.industry.clone(),
+        currency: symbol.currency.clone(),
+        same_industry,
+        market_cap: symbol.market_cap,
+        price,
+        as_of,
+        change_1d_percent: change_percent(candles, 1),
+        change_1m_percent: change_percent(candles, MONTH_BARS),
+        change_3m_percent: change_percent(candles, QUARTER_BARS),
+        change_1y_percent: change_percent(candles, YEAR_BARS),
+        volatility_percent,
+        dividend_yield_percent,
+    }
+}
+
+/// Change from the close `bars` candles back to the latest one
+fn change_percent(candles: &[HistoricalPrice], bars: usize) -> Option<Decimal> {
+    let latest = candles.first()?.close;
+    let then = candles.get(bars)?.close;
+    (then > Decimal::ZERO).then(|| ((latest / then - Decimal::ONE) * Decimal::ONE_HUNDRED).round_dp(2))
+}
+
+/// Order candidates for display: same industry first, then by closeness of market cap to
+/// `market_cap` (largest first when it is unknown), symbols without one last
+pub fn rank(candidates: &mut [(Symbol, bool)], market_cap: Option<Decimal>) {
+    let distance = |symbol: &Symbol| -> Option<f64> {
+        let cap = symbol.market_cap.and_then(|cap| cap.to_f64()).filter(|cap| *cap > 0.0)?;
+        Some(match market_cap.and_then(|cap| cap.to_f64()).filter(|cap| *cap > 0.0) {
+            Some(subject) => (cap / subject).ln().abs(),
+            None => -cap,
+        })
+    };
+    candidates.sort_by(|(a, a_same), (b, b_same)| {
+        b_same
+            .cmp(a_same)
+            .then_with(|| match (distance(a), distance(b)) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+}
+
+/// Median of each metric over the peers that have it
+pub fn medians(peers: &[PeerMetrics]) -> PeerMedians {
+    let median = |value: fn(&PeerMetrics) -> Option<Decimal>| {
+        let mut values: Vec<Decimal> = peers.iter().filter_map(value).collect();
+        values.sort();
+        let n = values.len();
+        match n {
+            0 => None,
+            _ if n % 2 == 1 => Some(values[n / 2]),
+            _ => Some(((values[n / 2 - 1] + values[n / 2]) / Decimal::TWO).round_dp(2)),
+        }
+    };
+    PeerMedians {
+        market_cap: median(|p| p.market_cap),
+        change_1d_percent: median(|p| p.change_1d_percent),
+        change_1m_percent: median(|p| p.change_1m_percent),
+        change_3m_percent: median(|p| p.change_3m_percent),
+        change_1y_percent: median(|p| p.change_1y_percent),
+        volatility_percent: median(|p| p.volatility_percent),
+        dividend_yield_percent: median(|p| p.dividend_yield_percent),
+    }
+}
@@ -0,0 +1,140 @@
+//! Background refresh of portfolio holding prices.
+//!
+//! Every `PORTFOLIO_UPDATE_INTERVAL` the updater loads all holdings, fetches one latest quote
+//! per distinct symbol whose market is open (see [`market_calendar::should_refresh`]), and
+//! writes the new value and gain/loss of every holding of those symbols in a single
+//! transaction. Quotes are fetched
+//! `PORTFOLIO_UPDATE_CONCURRENCY` at a time. Each pass also checks stop and target levels,
+//! emits a portfolio snapshot webhook and evaluates signal alerts on daily closes.
+
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::{NotificationConfig, PortfolioUpdateConfig};
+use crate::event_stream::StreamEvent;
+use crate::market_calendar;
+use crate::models::HoldingPriceUpdate;
+use crate::webhooks::{self, WebhookEvent};
+use crate::yahoo_service::YahooFinanceService;
+
+/// Start the periodic holding price refresh on the current runtime
+pub fn spawn(
+    service: Arc<YahooFinanceService>,
+    config: PortfolioUpdateConfig,
+    notifications: NotificationConfig,
+) {
+    info!(
+        "📊 Portfolio prices refresh every {}s, {} quotes at a time",
+        config.interval.as_secs(),
+        config.max_concurrent
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+        loop {
+            interval.tick().await;
+            update_holdings(&service, config.max_concurrent, &notifications).await;
+            match service.evaluate_signal_alerts(&notifications).await {
+                Ok(0) => {}
+                Ok(fired) => info!("🔔 {} signal alerts fired", fired),
+                Err(e) => warn!("Failed to evaluate signal alerts: {:?}", e),
+            }
+        }
+    });
+}
+
+async fn update_holdings(
+    service: &YahooFinanceService,
+    max_concurrent: usize,
+    notifications: &NotificationConfig,
+) {
+    info!("📊 Updating portfolio prices...");
+    let holdings = match service.db.get_all_portfolio_holdings().await {
+        Ok(holdings) => holdings,
+        Err(e) => {
+            warn!("Failed to update portfolio prices: {:?}", e);
+            return;
+        }
+    };
+
+    // Quotes do not move while a holding's market is closed, and delisted symbols have none.
+    // Holdings sharing a symbol share one quote.
+    let delisted = service.delisted_symbols().await.unwrap_or_else(|e| {
+        warn!("Failed to load delisted symbols: {:?}", e);
+        HashSet::new()
+    });
+    let now = chrono::Utc::now();
+    let mut seen = HashSet::new();
+    let symbols: Vec<String> = holdings
+        .iter()
+        .filter(|holding| !delisted.contains(&holding.symbol))
+        .filter(|holding| market_calendar::should_refresh(&holding.symbol, &holding.asset_type, now))
+        .filter(|holding| seen.insert(holding.symbol.as_str()))
+        .map(|holding| holding.symbol.clone())
+        .collect();
+    let quotes = service.get_latest_quotes(&symbols, max_concurrent).await;
+
+    let total = holdings.len();
+    let mut skipped = 0;
+    let mut total_value = Decimal::ZERO;
+    let mut total_cost = Decimal::ZERO;
+    let mut updates = Vec::new();
+    for holding in &holdings {
+        total_cost += holding.purchase_price * holding.quantity;
+
+        let Some(quote) = quotes.get(&holding.symbol) else {
+            if !seen.contains(holding.symbol.as_str()) {
+                skipped += 1;
+            }
+            total_value += holding.current_value.unwrap_or_default();
+            continue;
+        };
+        let update = HoldingPriceUpdate::at_price(holding, quote.price);
+        total_value += update.current_value;
+        updates.push(update);
+    }
+
+    // All holdings are written in one transaction before any exit levels are checked
+    let updated = match service.db.record_portfolio_refresh(updates).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            warn!("Failed to write portfolio prices: {:?}", e);
+            0
+        }
+    };
+    for holding in &holdings {
+        let Some(quote) = quotes.get(&holding.symbol) else {
+            continue;
+        };
+        if let Err(e) = service
+            .check_exit_levels(holding, quote.price, notifications)
+            .await
+        {
+            warn!("Failed to check exit levels for {}: {:?}", holding.symbol, e);
+        }
+    }
+
+    info!(
+        "✅ Portfolio prices updated: {}/{} holdings from {} quotes ({} skipped while market closed)",
+        updated,
+        total,
+        quotes.len(),
+        skipped
+    );
+
+    if total > 0 {
+        let snapshot = serde_json::json!({
+            "holdings": total,
+            "updated": updated,
+            "total_value": total_value,
+            "total_cost": total_cost,
+            "gain_loss": total_value - total_cost,
+            "as_of": now,
+        });
+        if updated > 0 {
+            service.events.publish(StreamEvent::PortfolioUpdated, snapshot.clone());
+            webhooks::emit(service.db.clone(), WebhookEvent::PortfolioSnapshot, snapshot);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Monte Carlo projection of portfolio value.
+//!
+//! Simulations run on daily log returns of the whole portfolio, so correlation between
+//! holdings is carried through implicitly: the bootstrap resamples entire historical days,
+//! and GBM uses the drift and volatility of the combined series.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::str::FromStr;
+
+/// Percentiles reported for every band
+pub const PERCENTILES: [f64; 5] = [5.0, 25.0, 50.0, 75.0, 95.0];
+
+/// Maximum number of points on the projected path; longer horizons are sampled evenly
+const MAX_BAND_POINTS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionMethod {
+    /// Geometric Brownian motion with drift and volatility estimated from history
+    Gbm,
+    /// Resample observed daily returns with replacement
+    Bootstrap,
+}
+
+impl ProjectionMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectionMethod::Gbm => "gbm",
+            ProjectionMethod::Bootstrap => "bootstrap",
+        }
+    }
+}
+
+impl FromStr for ProjectionMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gbm" => Ok(ProjectionMethod::Gbm),
+            "bootstrap" | "historical" => Ok(ProjectionMethod::Bootstrap),
+            other => Err(format!(
+                "Unsupported projection method '{}'. Use 'gbm' or 'bootstrap'",
+                other
+            )),
+        }
+    }
+}
+
+/// Percentile values of simulated portfolio value at one point of the horizon
+#[derive(Debug, Clone)]
+pub struct Band {
+    pub day: u32,
+    pub values: [f64; PERCENTILES.len()],
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub bands: Vec<Band>,
+    pub mean_final: f64,
+    pub probability_of_loss: f64,
+}
+
+/// Mean and sample standard deviation of daily log returns
+pub fn drift_and_volatility(returns: &[f64]) -> (f64, f64) {
+    if returns.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    if returns.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+/// Run `simulations` paths of `horizon_days` steps starting from `initial_value`.
+pub fn simulate(
+    initial_value: f64,
+    returns: &[f64],
+    method: ProjectionMethod,
+    horizon_days: u32,
+    simulations: u32,
+    seed: Option<u64>,
+) -> Simulation {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let checkpoints = checkpoint_days(horizon_days);
+    let (mean, volatility) = drift_and_volatility(returns);
+    // Log-return drift of GBM already includes the -sigma^2/2 correction when estimated
+    // from observed log returns, so the sample mean is used directly.
+    let mut samples: Vec<Vec<f64>> = vec![Vec::with_capacity(simulations as usize); checkpoints.len()];
+
+    for _ in 0..simulations {
+        let mut log_value = 0.0;
+        let mut next_checkpoint = 0;
+        for day in 1..=horizon_days {
+            log_value += match method {
+                ProjectionMethod::Gbm => mean + volatility * standard_normal(&mut rng),
+                ProjectionMethod::Bootstrap => returns[rng.gen_range(0..returns.len())],
+            };
+            if checkpoints[next_checkpoint] == day {
+                samples[next_checkpoint].push(initial_value * log_value.exp());
+                next_checkpoint += 1;
+            }
+        }
+    }
+
+    let bands = checkpoints
+        .iter()
+        .zip(samples.iter_mut())
+        .map(|(&day, values)| {
+            values.sort_by(|a, b| a.total_cmp(b));
+            Band {
+                day,
+                values: PERCENTILES.map(|p| percentile(values, p)),
+            }
+        })
+        .collect();
+
+    let finals = samples.last().map(Vec::as_slice).unwrap_or_default();
+    let mean_final = if finals.is_empty() {
+        initial_value
+    } else {
+        finals.iter().sum::<f64>() / finals.len() as f64
+    };
+    let probability_of_loss = if finals.is_empty() {
+        0.0
+    } else {
+        finals.iter().filter(|v| **v < initial_value).count() as f64 / finals.len() as f64
+    };
+
+    Simulation {
+        bands,
+        mean_final,
+        probability_of_loss,
+    }
+}
+
+/// Days at which percentiles are recorded, always ending on the horizon
+fn checkpoint_days(horizon_days: u32) -> Vec<u32> {
+    let step = (horizon_days as usize).div_ceil(MAX_BAND_POINTS).max(1) as u32;
+    let mut days: Vec<u32> = (1..=horizon_days).filter(|d| d % step == 0).collect();
+    if days.last() != Some(&horizon_days) {
+        days.push(horizon_days);
+    }
+    days
+}
+
+/// Linear-interpolated percentile of an ascending slice
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - lower as f64;
+    sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+}
+
+/// Box-Muller transform
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let returns = [0.01, -0.02, 0.005, 0.015, -0.01];
+        let first = simulate(1000.0, &returns, ProjectionMethod::Gbm, 30, 200, Some(7));
+        let second = simulate(1000.0, &returns, ProjectionMethod::Gbm, 30, 200, Some(7));
+        assert_eq!(first.mean_final, second.mean_final);
+        assert_eq!(first.bands.last().unwrap().values, second.bands.last().unwrap().values);
+    }
+
+    #[test]
+    fn constant_returns_compound_without_spread() {
+        let simulation = simulate(1000.0, &[0.001; 10], ProjectionMethod::Gbm, 100, 50, Some(1));
+        let expected = 1000.0 * (0.1f64).exp();
+        let last = simulation.bands.last().unwrap();
+        assert_eq!(last.day, 100);
+        for value in last.values {
+            assert!((value - expected).abs() < 1e-6);
+        }
+        assert!((simulation.mean_final - expected).abs() < 1e-6);
+        assert_eq!(simulation.probability_of_loss, 0.0);
+    }
+
+    #[test]
+    fn bootstrap_only_draws_observed_returns() {
+        let simulation = simulate(500.0, &[-0.01, -0.02], ProjectionMethod::Bootstrap, 20, 100, Some(3));
+        assert_eq!(simulation.probability_of_loss, 1.0);
+        let last = simulation.bands.last().unwrap();
+        assert!(last.values[4] <= 500.0 * (-0.2f64).exp() + 1e-9);
+        assert!(last.values[0] >= 500.0 * (-0.4f64).exp() - 1e-9);
+    }
+
+    #[test]
+    fn bands_are_capped_and_ordered() {
+        let returns = [0.02, -0.015, 0.01, -0.005, 0.0];
+        let simulation = simulate(100.0, &returns, ProjectionMethod::Bootstrap, 252, 300, Some(11));
+        assert!(simulation.bands.len() <= MAX_BAND_POINTS + 1);
+        assert_eq!(simulation.bands.last().unwrap().day, 252);
+        for band in &simulation.bands {
+            assert!(band.values.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn drift_and_volatility_use_the_sample_deviation() {
+        let (mean, volatility) = drift_and_volatility(&[0.01, 0.03]);
+        assert!((mean - 0.02).abs() < 1e-12);
+        assert!((volatility - 0.02f64.sqrt() * 0.1).abs() < 1e-12);
+        assert_eq!(drift_and_volatility(&[]), (0.0, 0.0));
+        assert_eq!(drift_and_volatility(&[0.05]), (0.05, 0.0));
+    }
+
+    #[test]
+    fn methods_parse_with_their_alias() {
+        assert_eq!("GBM".parse::<ProjectionMethod>(), Ok(ProjectionMethod::Gbm));
+        assert_eq!("historical".parse::<ProjectionMethod>(), Ok(ProjectionMethod::Bootstrap));
+        assert!("random".parse::<ProjectionMethod>().is_err());
+    }
+}
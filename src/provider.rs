@@ -0,0 +1,1284 @@
+//! Market-data providers behind [`YahooFinanceService`](crate::yahoo_service::YahooFinanceService).
+//!
+//! The service only needs candles, a latest quote, a symbol search and ownership data; each
+//! provider maps its upstream API onto those. `DATA_PROVIDER=yahoo` (the default) talks to
+//! Yahoo Finance, `DATA_PROVIDER=mock` serves deterministic synthetic data so the web UI and
+//! tests run without network access or Yahoo quota.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use reqwest::header::{COOKIE, RETRY_AFTER, SET_COOKIE};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use yahoo_finance_api::{Dividend, Quote, YResponse, YSearchResultOpt, YahooError};
+
+use crate::config::MarketDataConfig;
+use crate::fx;
+use crate::market_calendar::{self, SessionKind};
+use crate::models::{FundHolding, Holder, OwnershipBreakdown, SectorWeight};
+
+/// Candles for one symbol plus whatever exchange metadata and dividend events the
+/// provider returned alongside them
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory {
+    pub quotes: Vec<Quote>,
+    pub exchange: Option<String>,
+    pub timezone: Option<String>,
+    pub utc_offset_seconds: Option<i32>,
+    /// Currency prices are quoted in, possibly a minor unit such as `GBp`
+    pub currency: Option<String>,
+    pub dividends: Vec<Dividend>,
+}
+
+/// The most recent quote, which may be a pre- or post-market trade, plus the previous
+/// session's close and the latest regular-session price when the provider reports them
+#[derive(Debug, Clone)]
+pub struct LatestQuote {
+    pub quote: Quote,
+    pub previous_close: Option<f64>,
+    pub regular_market_price: Option<f64>,
+    pub currency: Option<String>,
+}
+
+/// Who owns a symbol: the insider/institution split plus the largest holders by filing
+#[derive(Debug, Clone, Default)]
+pub struct Ownership {
+    pub breakdown: OwnershipBreakdown,
+    pub institutions: Vec<Holder>,
+    pub funds: Vec<Holder>,
+}
+
+/// Top holdings and sector weightings of a fund
+#[derive(Debug, Clone, Default)]
+pub struct FundComposition {
+    pub holdings: Vec<FundHolding>,
+    pub sector_weights: Vec<SectorWeight>,
+}
+
+/// A symbol search hit
+#[derive(Debug, Clone)]
+pub struct SymbolMatch {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: String,
+}
+
+#[async_trait]
+pub trait MarketDataProvider: Send + Sync {
+    /// Short identifier used in logs
+    fn name(&self) -> &'static str;
+
+    /// Candles at `interval` covering `range` (Yahoo notation, e.g. "1d" and "1y")
+    async fn price_history(&self, symbol: &str, interval: &str, range: &str) -> Result<PriceHistory>;
+
+    /// Candles at `interval` from `start` up to now, for topping up stored history
+    async fn price_history_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+    ) -> Result<PriceHistory>;
+
+    /// Most recent quote, or `None` when the provider has nothing for the symbol
+    async fn latest_quote(&self, symbol: &str) -> Result<Option<LatestQuote>>;
+
+    /// Symbols matching a ticker or company name
+    async fn search(&self, query: &str) -> Result<Vec<SymbolMatch>>;
+
+    /// Institutional and fund ownership, or `None` when the provider has none for the symbol
+    async fn ownership(&self, symbol: &str) -> Result<Option<Ownership>>;
+
+    /// Holdings and sector weights of a fund, or `None` when the symbol is not a fund
+    async fn fund_composition(&self, symbol: &str) -> Result<Option<FundComposition>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataProvider {
+    Yahoo,
+    Mock,
+}
+
+impl DataProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataProvider::Yahoo => "yahoo",
+            DataProvider::Mock => "mock",
+        }
+    }
+}
+
+impl FromStr for DataProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "yahoo" => Ok(DataProvider::Yahoo),
+            "mock" => Ok(DataProvider::Mock),
+            other => Err(format!(
+                "Unsupported data provider '{}'. Use yahoo or mock",
+                other
+            )),
+        }
+    }
+}
+
+/// Build the provider selected by the configuration
+pub fn from_config(config: &MarketDataConfig) -> Result<Arc<dyn MarketDataProvider>> {
+    Ok(match config.provider {
+        DataProvider::Yahoo => Arc::new(YahooProvider::new(config)?),
+        DataProvider::Mock => match &config.mock_fixtures {
+            Some(path) => Arc::new(MockProvider::from_fixture_file(path)?),
+            None => Arc::new(MockProvider::new()),
+        },
+    })
+}
+
+const YAHOO_CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const YAHOO_SEARCH_URL: &str = "https://query2.finance.yahoo.com/v1/finance/search";
+const YAHOO_COOKIE_URL: &str = "https://fc.yahoo.com";
+const YAHOO_CRUMB_URL: &str = "https://query2.finance.yahoo.com/v1/test/getcrumb";
+const YAHOO_QUOTE_SUMMARY_URL: &str = "https://query2.finance.yahoo.com/v10/finance/quoteSummary";
+/// Yahoo refuses cookies and crumbs to clients that do not look like a browser
+pub const BROWSER_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+/// Cookie and matching crumb that quoteSummary requests must carry
+#[derive(Debug, Clone)]
+struct YahooSession {
+    cookie: String,
+    crumb: String,
+}
+
+/// Failures of the upstream provider itself, as opposed to unknown symbols or bad input;
+/// the API reports them as `UPSTREAM_RATE_LIMITED` (429) or `UPSTREAM_UNAVAILABLE` (503)
+#[derive(Debug, Error)]
+pub enum UpstreamError {
+    #[error("Upstream provider is rate limiting requests")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("Upstream provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl UpstreamError {
+    /// Classify an HTTP status returned by the provider, `None` when it is not the provider's fault
+    pub fn from_status(status: StatusCode, retry_after: Option<Duration>) -> Option<Self> {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            Some(UpstreamError::RateLimited { retry_after })
+        } else if status.is_server_error() {
+            Some(UpstreamError::Unavailable(status.to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Classify a failed request that never got a response
+    pub fn from_request(err: &reqwest::Error) -> Option<Self> {
+        (err.is_timeout() || err.is_connect()).then(|| UpstreamError::Unavailable(err.to_string()))
+    }
+
+    fn from_yahoo(err: &YahooError) -> Option<Self> {
+        match err {
+            // The crate only keeps the status line of failed responses
+            YahooError::FetchFailed(status) => status
+                .split_whitespace()
+                .next()
+                .and_then(|code| code.parse::<u16>().ok())
+                .and_then(|code| StatusCode::from_u16(code).ok())
+                .and_then(|status| Self::from_status(status, None)),
+            YahooError::ConnectionFailed(e) => Self::from_request(e),
+            _ => None,
+        }
+    }
+}
+
+/// The provider has no data at all for a symbol, as for unknown or delisted tickers
+#[derive(Debug, Error)]
+#[error("No data for the symbol")]
+pub struct NoData;
+
+/// Whether a failed provider call means the symbol has no data, rather than a transient failure
+pub fn is_no_data(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<NoData>().is_some()
+}
+
+/// `Retry-After` of a response, when given in seconds
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// A `yahoo_finance_api` failure with `context`, keeping its upstream kind where it has one
+fn yahoo_error(err: YahooError, context: String) -> anyhow::Error {
+    let message = format!("{}: {}", context, err);
+    if let Some(upstream) = UpstreamError::from_yahoo(&err) {
+        return anyhow::Error::new(upstream).context(message);
+    }
+    match err {
+        // The chart endpoint answers 404 for tickers it has nothing for
+        YahooError::EmptyDataSet => anyhow::Error::new(NoData).context(message),
+        YahooError::FetchFailed(status) if status.starts_with("404") => anyhow::Error::new(NoData).context(message),
+        _ => anyhow!(message),
+    }
+}
+
+/// Yahoo Finance chart, search and quoteSummary endpoints, parsed with `yahoo_finance_api`.
+/// Requests go through one client built from the configured timeouts, proxy and
+/// User-Agent; the crate's own connector cannot be given a custom client.
+pub struct YahooProvider {
+    // Chart and search calls are serialized; the service's own rate limiter keeps the volume down
+    chart_calls: Mutex<()>,
+    http: reqwest::Client,
+    session: Mutex<Option<YahooSession>>,
+}
+
+impl YahooProvider {
+    pub fn new(config: &MarketDataConfig) -> Result<Self> {
+        let mut http = reqwest::Client::builder()
+            .connect_timeout(config.yahoo_connect_timeout)
+            .read_timeout(config.yahoo_read_timeout)
+            .user_agent(&config.yahoo_user_agent);
+        if let Some(proxy) = &config.yahoo_proxy {
+            http = http.proxy(
+                reqwest::Proxy::all(proxy).with_context(|| format!("Invalid YAHOO_PROXY_URL '{}'", proxy))?,
+            );
+        }
+        Ok(Self {
+            chart_calls: Mutex::new(()),
+            http: http.build()?,
+            session: Mutex::new(None),
+        })
+    }
+
+    /// JSON body of a chart or search request, failing like `yahoo_finance_api` does
+    async fn get_json(&self, url: &str, query: &[(&str, &str)]) -> Result<serde_json::Value, YahooError> {
+        let _turn = self.chart_calls.lock().await;
+        let response = self.http.get(url).query(query).send().await?;
+        match response.status() {
+            StatusCode::OK => Ok(response.json().await?),
+            status => Err(YahooError::FetchFailed(status.to_string())),
+        }
+    }
+
+    /// Chart of `symbol` with the given query parameters
+    async fn chart(&self, symbol: &str, query: &[(&str, &str)]) -> Result<YResponse, YahooError> {
+        let json = self.get_json(&format!("{}/{}", YAHOO_CHART_URL, symbol), query).await?;
+        YResponse::from_json(json)
+    }
+
+    /// Current session, fetching a new cookie and crumb when there is none or `renew` is set
+    async fn session(&self, renew: bool) -> Result<YahooSession> {
+        let mut session = self.session.lock().await;
+        if let Some(current) = session.as_ref().filter(|_| !renew) {
+            return Ok(current.clone());
+        }
+
+        // fc.yahoo.com answers 404 but still sets the cookie the crumb is bound to
+        let response = self
+            .http
+            .get(YAHOO_COOKIE_URL)
+            .send()
+            .await
+            .context("Failed to obtain a Yahoo session cookie")?;
+        let cookie = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok()?.split(';').next())
+            .collect::<Vec<_>>()
+            .join("; ");
+        if cookie.is_empty() {
+            return Err(anyhow!("Yahoo did not set a session cookie"));
+        }
+
+        let crumb = self
+            .http
+            .get(YAHOO_CRUMB_URL)
+            .header(COOKIE, &cookie)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to obtain a Yahoo crumb")?
+            .text()
+            .await?
+            .trim()
+            .to_string();
+        if crumb.is_empty() || crumb.contains('<') {
+            return Err(anyhow!("Yahoo returned an invalid crumb"));
+        }
+
+        let fresh = YahooSession { cookie, crumb };
+        *session = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    /// The quoteSummary result for `modules` (comma-separated), or `None` for unknown symbols
+    async fn quote_summary<T: DeserializeOwned>(&self, symbol: &str, modules: &str) -> Result<Option<T>> {
+        // A rejected crumb usually means the session expired; retry once with a new one
+        for renew in [false, true] {
+            let session = self.session(renew).await?;
+            let response = self
+                .http
+                .get(format!("{}/{}", YAHOO_QUOTE_SUMMARY_URL, symbol))
+                .query(&[("modules", modules), ("crumb", session.crumb.as_str())])
+                .header(COOKIE, &session.cookie)
+                .send()
+                .await
+                .with_context(|| format!("quoteSummary request failed for {}", symbol))?;
+
+            match response.status() {
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN if !renew => continue,
+                StatusCode::NOT_FOUND => return Ok(None),
+                status if !status.is_success() => {
+                    let err = anyhow!("quoteSummary for {} returned {}", symbol, status);
+                    return Err(match UpstreamError::from_status(status, retry_after(&response)) {
+                        Some(upstream) => anyhow::Error::new(upstream).context(err),
+                        None => err,
+                    });
+                }
+                _ => {}
+            }
+
+            let body: QuoteSummaryResponse<T> = response
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse quoteSummary for {}", symbol))?;
+            return Ok(body.quote_summary.result.and_then(|results| results.into_iter().next()));
+        }
+        Err(anyhow!("Yahoo rejected the quoteSummary crumb for {}", symbol))
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for YahooProvider {
+    fn name(&self) -> &'static str {
+        "yahoo"
+    }
+
+    async fn price_history(&self, symbol: &str, interval: &str, range: &str) -> Result<PriceHistory> {
+        let response = self
+            .chart(
+                symbol,
+                &[("interval", interval), ("range", range), ("events", "div|split|capitalGains")],
+            )
+            .await
+            .map_err(|e| yahoo_error(e, format!("Failed to fetch data from Yahoo Finance for {}", symbol)))?;
+        history_from_response(symbol, response)
+    }
+
+    async fn price_history_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+    ) -> Result<PriceHistory> {
+        let (start, end) = (start.timestamp().to_string(), Utc::now().timestamp().to_string());
+        let response = self
+            .chart(
+                symbol,
+                &[
+                    ("period1", start.as_str()),
+                    ("period2", end.as_str()),
+                    ("interval", interval),
+                    ("events", "div|split|capitalGains"),
+                ],
+            )
+            .await
+            .map_err(|e| yahoo_error(e, format!("Failed to fetch data from Yahoo Finance for {}", symbol)))?;
+        history_from_response(symbol, response)
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<Option<LatestQuote>> {
+        // Minute candles of the latest trading day, extended hours included
+        let response = self
+            .chart(symbol, &[("period", "1d"), ("interval", "1m"), ("includePrePost", "true")])
+            .await
+            .map_err(|e| yahoo_error(e, format!("Failed to fetch latest quote for {}", symbol)))?;
+
+        let Ok(mut quote) = response.last_quote() else {
+            return Ok(None);
+        };
+        // Day volume counts the regular session only, like a daily candle
+        let calendar = market_calendar::calendar_for_symbol(symbol);
+        quote.volume = response
+            .quotes()
+            .unwrap_or_default()
+            .iter()
+            .filter(|candle| {
+                DateTime::from_timestamp(candle.timestamp as i64, 0)
+                    .is_some_and(|t| calendar.session_at(t) == SessionKind::Regular)
+            })
+            .map(|candle| candle.volume)
+            .sum();
+
+        // For a one-day chart the chart's previous close is the prior session's close
+        let meta = response.metadata().ok();
+        let previous_close = meta
+            .as_ref()
+            .and_then(|meta| meta.previous_close.or(Some(meta.chart_previous_close)))
+            .filter(|close| *close > 0.0);
+        let regular_market_price = meta
+            .as_ref()
+            .map(|meta| meta.regular_market_price)
+            .filter(|price| *price > 0.0);
+        let currency = meta.and_then(|meta| meta.currency).filter(|c| !c.is_empty());
+        Ok(Some(LatestQuote {
+            quote,
+            previous_close,
+            regular_market_price,
+            currency,
+        }))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SymbolMatch>> {
+        let response = self
+            .get_json(YAHOO_SEARCH_URL, &[("q", query)])
+            .await
+            .and_then(YSearchResultOpt::from_json)
+            .map_err(|e| yahoo_error(e, format!("Yahoo symbol search failed for '{}'", query)))?;
+
+        Ok(response
+            .quotes
+            .into_iter()
+            .map(|item| SymbolMatch {
+                symbol: item.symbol,
+                name: item.long_name.or(item.short_name).filter(|n| !n.is_empty()),
+                exchange: item.exchange,
+            })
+            .collect())
+    }
+
+    async fn ownership(&self, symbol: &str) -> Result<Option<Ownership>> {
+        let Some(modules) = self
+            .quote_summary::<OwnershipModules>(
+                symbol,
+                "institutionOwnership,fundOwnership,majorHoldersBreakdown",
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let breakdown = modules
+            .major_holders_breakdown
+            .map(|b| OwnershipBreakdown {
+                insiders_percent: fraction_to_percent(raw(&b.insiders_percent_held)),
+                institutions_percent: fraction_to_percent(raw(&b.institutions_percent_held)),
+                institutions_float_percent: fraction_to_percent(raw(&b.institutions_float_percent_held)),
+                institutions_count: raw(&b.institutions_count).map(|v| v as i64),
+            })
+            .unwrap_or_default();
+        let ownership = Ownership {
+            breakdown,
+            institutions: holders(modules.institution_ownership),
+            funds: holders(modules.fund_ownership),
+        };
+        if ownership.institutions.is_empty()
+            && ownership.funds.is_empty()
+            && ownership.breakdown.institutions_percent.is_none()
+        {
+            return Ok(None);
+        }
+        Ok(Some(ownership))
+    }
+
+    async fn fund_composition(&self, symbol: &str) -> Result<Option<FundComposition>> {
+        let Some(top) = self
+            .quote_summary::<TopHoldingsModule>(symbol, "topHoldings")
+            .await?
+            .and_then(|m| m.top_holdings)
+        else {
+            return Ok(None);
+        };
+
+        let holdings: Vec<FundHolding> = top
+            .holdings
+            .into_iter()
+            .filter_map(|h| {
+                Some(FundHolding {
+                    name: h.holding_name.or_else(|| h.symbol.clone()).filter(|n| !n.is_empty())?,
+                    symbol: h.symbol.filter(|s| !s.is_empty()),
+                    weight_percent: fraction_to_percent(raw(&h.holding_percent))?,
+                })
+            })
+            .collect();
+        let mut sector_weights: Vec<SectorWeight> = top
+            .sector_weightings
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, weight)| {
+                Some(SectorWeight {
+                    sector: sector_name(&key),
+                    weight_percent: fraction_to_percent(raw(&Some(weight)))?,
+                })
+            })
+            .filter(|w| !w.weight_percent.is_zero())
+            .collect();
+        sector_weights.sort_by_key(|w| std::cmp::Reverse(w.weight_percent));
+
+        // Single stocks answer with an empty module
+        if holdings.is_empty() && sector_weights.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(FundComposition {
+            holdings,
+            sector_weights,
+        }))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteSummaryResponse<T> {
+    #[serde(rename = "quoteSummary")]
+    quote_summary: QuoteSummaryBody<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteSummaryBody<T> {
+    result: Option<Vec<T>>,
+}
+
+/// quoteSummary numbers come as `{"raw": 0.0712, "fmt": "7.12%"}`, or `{}` when unknown
+#[derive(Debug, Deserialize)]
+struct RawValue {
+    raw: Option<f64>,
+}
+
+fn raw(value: &Option<RawValue>) -> Option<f64> {
+    value.as_ref().and_then(|v| v.raw).filter(|v| v.is_finite())
+}
+
+/// A fraction such as 0.0712 as a percentage, 7.12
+fn fraction_to_percent(fraction: Option<f64>) -> Option<Decimal> {
+    Decimal::from_f64_retain(fraction? * 100.0).map(|p| p.round_dp(4).normalize())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnershipModules {
+    institution_ownership: Option<OwnershipList>,
+    fund_ownership: Option<OwnershipList>,
+    major_holders_breakdown: Option<MajorHoldersBreakdown>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnershipList {
+    #[serde(default)]
+    ownership_list: Vec<OwnershipEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnershipEntry {
+    organization: Option<String>,
+    report_date: Option<RawValue>,
+    pct_held: Option<RawValue>,
+    position: Option<RawValue>,
+    value: Option<RawValue>,
+    pct_change: Option<RawValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MajorHoldersBreakdown {
+    insiders_percent_held: Option<RawValue>,
+    institutions_percent_held: Option<RawValue>,
+    institutions_float_percent_held: Option<RawValue>,
+    institutions_count: Option<RawValue>,
+}
+
+impl OwnershipEntry {
+    fn into_holder(self) -> Option<Holder> {
+        Some(Holder {
+            name: self.organization.filter(|n| !n.is_empty())?,
+            shares: raw(&self.position).map(|v| v as i64),
+            value: raw(&self.value).map(|v| v as i64),
+            percent_held: fraction_to_percent(raw(&self.pct_held)),
+            percent_change: fraction_to_percent(raw(&self.pct_change)),
+            report_date: raw(&self.report_date)
+                .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+                .map(|t| t.date_naive()),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TopHoldingsModule {
+    top_holdings: Option<TopHoldings>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TopHoldings {
+    #[serde(default)]
+    holdings: Vec<TopHolding>,
+    /// One single-key object per sector, e.g. `[{"technology": {"raw": 0.31}}]`
+    #[serde(default)]
+    sector_weightings: Vec<HashMap<String, RawValue>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TopHolding {
+    symbol: Option<String>,
+    holding_name: Option<String>,
+    holding_percent: Option<RawValue>,
+}
+
+/// Sector names as company profiles report them, so fund and direct exposure line up
+fn sector_name(key: &str) -> String {
+    match key {
+        "realestate" => "Real Estate".to_string(),
+        "consumer_cyclical" => "Consumer Cyclical".to_string(),
+        "basic_materials" => "Basic Materials".to_string(),
+        "consumer_defensive" => "Consumer Defensive".to_string(),
+        "technology" => "Technology".to_string(),
+        "communication_services" => "Communication Services".to_string(),
+        "financial_services" => "Financial Services".to_string(),
+        "utilities" => "Utilities".to_string(),
+        "industrials" => "Industrials".to_string(),
+        "energy" => "Energy".to_string(),
+        "healthcare" => "Healthcare".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn holders(list: Option<OwnershipList>) -> Vec<Holder> {
+    list.map(|l| l.ownership_list)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(OwnershipEntry::into_holder)
+        .collect()
+}
+
+fn history_from_response(symbol: &str, response: YResponse) -> Result<PriceHistory> {
+    let quotes = response
+        .quotes()
+        .map_err(|e| yahoo_error(e, format!("Failed to parse quotes for {}", symbol)))?;
+
+    let mut history = PriceHistory {
+        quotes,
+        dividends: response.dividends().unwrap_or_default(),
+        ..Default::default()
+    };
+    if let Ok(meta) = response.metadata() {
+        history.exchange = Some(meta.exchange_name).filter(|e| !e.is_empty());
+        history.timezone = Some(meta.exchange_timezone_name).filter(|tz| !tz.is_empty());
+        history.utc_offset_seconds = Some(meta.gmtoffset);
+        history.currency = meta.currency.filter(|c| !c.is_empty());
+    }
+
+    Ok(history)
+}
+
+const MOCK_EXCHANGE: &str = "MOCK";
+const MOCK_TIMEZONE: &str = "America/New_York";
+/// Daily candles are stamped at the regular-session open, 09:30 New York time in UTC
+const MOCK_SESSION_OPEN_SECS: u64 = 13 * 3600 + 1800;
+const MOCK_MAX_CANDLES: usize = 2000;
+const SECS_PER_DAY: u64 = 86_400;
+const MOCK_INSTITUTIONS: [&str; 5] = [
+    "Mock Index Advisors",
+    "Mock Global Investors",
+    "Mock Street Capital",
+    "Mock Asset Management",
+    "Mock Pension Trust",
+];
+/// Tickers the mock market treats as ETFs
+const MOCK_ETFS: [&str; 7] = ["SPY", "VOO", "IVV", "VTI", "QQQ", "DIA", "IWM"];
+/// Constituents mock ETFs draw their top holdings from: ticker, name, sector
+const MOCK_CONSTITUENTS: [(&str, &str, &str); 12] = [
+    ("AAPL", "Apple Inc.", "Technology"),
+    ("MSFT", "Microsoft Corporation", "Technology"),
+    ("NVDA", "NVIDIA Corporation", "Technology"),
+    ("AMZN", "Amazon.com, Inc.", "Consumer Cyclical"),
+    ("GOOGL", "Alphabet Inc.", "Communication Services"),
+    ("META", "Meta Platforms, Inc.", "Communication Services"),
+    ("JPM", "JPMorgan Chase & Co.", "Financial Services"),
+    ("XOM", "Exxon Mobil Corporation", "Energy"),
+    ("JNJ", "Johnson & Johnson", "Healthcare"),
+    ("UNH", "UnitedHealth Group Incorporated", "Healthcare"),
+    ("PG", "The Procter & Gamble Company", "Consumer Defensive"),
+    ("CAT", "Caterpillar Inc.", "Industrials"),
+];
+const MOCK_FUNDS: [&str; 3] = [
+    "Mock Total Market Index Fund",
+    "Mock 500 Index Fund",
+    "Mock Growth Fund",
+];
+
+/// Per-symbol overrides loaded from MOCK_PROVIDER_FIXTURES
+#[derive(Debug, Default, Deserialize)]
+struct MockFixture {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    exchange: Option<String>,
+    /// Trading currency; otherwise implied by the ticker's exchange suffix
+    #[serde(default)]
+    currency: Option<String>,
+    /// Level the synthetic series oscillates around
+    #[serde(default)]
+    price: Option<f64>,
+    /// Served verbatim instead of synthetic candles when present
+    #[serde(default)]
+    quotes: Vec<Quote>,
+    #[serde(default)]
+    dividends: Vec<Dividend>,
+}
+
+/// Deterministic synthetic market data. Every symbol that passes validation gets a price
+/// series derived from a hash of its ticker, so the same symbol and timestamp always
+/// produce the same candle. Fixtures can pin names, price levels, candles and dividends.
+#[derive(Debug, Default)]
+pub struct MockProvider {
+    fixtures: HashMap<String, MockFixture>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load fixtures from a JSON object keyed by symbol, e.g.
+    /// `{"AAPL": {"name": "Apple Inc.", "price": 190.0}}`
+    pub fn from_fixture_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock provider fixtures {}", path.display()))?;
+        Self::from_fixture_json(&raw)
+            .with_context(|| format!("Invalid mock provider fixtures {}", path.display()))
+    }
+
+    /// Fixtures from a JSON string in the same format as [`MockProvider::from_fixture_file`].
+    /// Unknown fields are ignored.
+    pub fn from_fixture_json(json: &str) -> Result<Self> {
+        let fixtures: HashMap<String, MockFixture> = serde_json::from_str(json)?;
+
+        Ok(Self {
+            fixtures: fixtures
+                .into_iter()
+                .map(|(symbol, fixture)| (symbol.to_uppercase(), fixture))
+                .collect(),
+        })
+    }
+
+    fn fixture(&self, symbol: &str) -> Option<&MockFixture> {
+        self.fixtures.get(&symbol.to_uppercase())
+    }
+
+    fn series(&self, symbol: &str) -> Series {
+        let seed = fnv1a(symbol.to_uppercase().as_bytes());
+        let pair_rate = fx::mock_pair_rate(&symbol.to_uppercase());
+        let base = self
+            .fixture(symbol)
+            .and_then(|f| f.price)
+            .filter(|p| *p > 0.0)
+            .or(pair_rate)
+            .unwrap_or_else(|| 20.0 + (seed % 48_000) as f64 / 100.0);
+        Series {
+            seed,
+            base,
+            currency_pair: pair_rate.is_some(),
+        }
+    }
+
+    fn exchange(&self, symbol: &str) -> String {
+        self.fixture(symbol)
+            .and_then(|f| f.exchange.clone())
+            .unwrap_or_else(|| MOCK_EXCHANGE.to_string())
+    }
+
+    fn currency(&self, symbol: &str) -> String {
+        self.fixture(symbol)
+            .and_then(|f| f.currency.clone())
+            .unwrap_or_else(|| fx::currency_for_symbol(&symbol.to_uppercase()).to_string())
+    }
+
+    fn company_name(&self, symbol: &str) -> String {
+        self.fixture(symbol)
+            .and_then(|f| f.name.clone())
+            .unwrap_or_else(|| format!("{} Mock Corp.", symbol.to_uppercase()))
+    }
+
+    fn history_between(&self, symbol: &str, interval: &str, start: u64, end: u64) -> Result<PriceHistory> {
+        let step = interval_seconds(interval)
+            .ok_or_else(|| anyhow!("Unsupported interval '{}'", interval))?;
+        let fixture = self.fixture(symbol);
+
+        let quotes = match fixture.filter(|f| !f.quotes.is_empty()) {
+            Some(f) => f.quotes.clone(),
+            None => self.series(symbol).candles(start, end, step),
+        };
+        let dividends = match fixture.filter(|f| !f.dividends.is_empty()) {
+            Some(f) => f.dividends.clone(),
+            None => self.series(symbol).dividends(start, end),
+        };
+
+        Ok(PriceHistory {
+            quotes,
+            exchange: Some(self.exchange(symbol)),
+            timezone: Some(MOCK_TIMEZONE.to_string()),
+            utc_offset_seconds: Some(-5 * 3600),
+            currency: Some(self.currency(symbol)),
+            dividends,
+        })
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn price_history(&self, symbol: &str, interval: &str, range: &str) -> Result<PriceHistory> {
+        let end = Utc::now().timestamp().max(0) as u64;
+        let start = end.saturating_sub(range_days(range) * SECS_PER_DAY);
+        self.history_between(symbol, interval, start, end)
+    }
+
+    async fn price_history_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+    ) -> Result<PriceHistory> {
+        let end = Utc::now().timestamp().max(0) as u64;
+        let start = (start.timestamp().max(0) as u64).min(end);
+        let mut history = self.history_between(symbol, interval, start, end)?;
+        // Fixture candles are served whole by price_history; trim them to the requested range
+        history.quotes.retain(|q| q.timestamp >= start);
+        history.dividends.retain(|d| d.date >= start);
+        Ok(history)
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<Option<LatestQuote>> {
+        if let Some(quotes) = self.fixture(symbol).map(|f| &f.quotes).filter(|q| !q.is_empty()) {
+            let calendar = market_calendar::calendar_for_symbol(symbol);
+            return Ok(Some(LatestQuote {
+                quote: quotes[quotes.len() - 1].clone(),
+                previous_close: quotes.len().checked_sub(2).map(|i| quotes[i].close),
+                regular_market_price: quotes
+                    .iter()
+                    .rev()
+                    .find(|q| {
+                        DateTime::from_timestamp(q.timestamp as i64, 0)
+                            .is_some_and(|t| calendar.session_at(t) == SessionKind::Regular)
+                    })
+                    .map(|q| q.close),
+                currency: Some(self.currency(symbol)),
+            }));
+        }
+
+        // Quotes move once a minute so repeated polling sees stable values
+        let now = Utc::now().timestamp().max(0) as u64;
+        let series = self.series(symbol);
+        let before_today = (now - now % SECS_PER_DAY).saturating_sub(1);
+        Ok(Some(LatestQuote {
+            quote: series.candle(now - now % 60, SECS_PER_DAY),
+            previous_close: series
+                .candles(before_today.saturating_sub(7 * SECS_PER_DAY), before_today, SECS_PER_DAY)
+                .last()
+                .map(|q| q.close),
+            regular_market_price: None,
+            currency: Some(self.currency(symbol)),
+        }))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SymbolMatch>> {
+        let needle = query.trim().to_uppercase();
+        if needle.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matches: Vec<SymbolMatch> = self
+            .fixtures
+            .iter()
+            .filter(|(symbol, fixture)| {
+                symbol.contains(&needle)
+                    || fixture
+                        .name
+                        .as_ref()
+                        .is_some_and(|n| n.to_uppercase().contains(&needle))
+            })
+            .map(|(symbol, _)| SymbolMatch {
+                symbol: symbol.clone(),
+                name: Some(self.company_name(symbol)),
+                exchange: self.exchange(symbol),
+            })
+            .collect();
+        matches.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        // Any well-formed ticker exists in the mock market
+        if matches.is_empty() && crate::validation::validate_symbol(&needle).is_ok() {
+            matches.push(SymbolMatch {
+                name: Some(self.company_name(&needle)),
+                exchange: self.exchange(&needle),
+                symbol: needle,
+            });
+        }
+
+        Ok(matches)
+    }
+
+    async fn ownership(&self, symbol: &str) -> Result<Option<Ownership>> {
+        let series = self.series(symbol);
+        let seed = series.seed;
+        // Filings are dated at the last quarter end
+        let today = Utc::now().date_naive();
+        let quarter_start = NaiveDate::from_ymd_opt(today.year(), (today.month() - 1) / 3 * 3 + 1, 1)
+            .ok_or_else(|| anyhow!("Invalid quarter start for {}", today))?;
+        let report_date = quarter_start.pred_opt();
+        let report_secs = report_date
+            .and_then(|d| d.and_hms_opt(20, 0, 0))
+            .map_or(0, |t| t.and_utc().timestamp().max(0) as u64);
+        let price = series.price_at(report_secs);
+        let shares_outstanding = 100_000_000.0 + unit(seed, 1) * 4_900_000_000.0;
+
+        let holder = |name: &str, n: u64, fraction: f64| Holder {
+            name: name.to_string(),
+            shares: Some((shares_outstanding * fraction) as i64),
+            value: Some((shares_outstanding * fraction * price) as i64),
+            percent_held: fraction_to_percent(Some(fraction)),
+            percent_change: fraction_to_percent(Some((unit(seed, n) - 0.5) * 0.1)),
+            report_date,
+        };
+        let institutions: Vec<Holder> = MOCK_INSTITUTIONS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let n = 10 + i as u64;
+                holder(name, n, 0.09 * 0.7f64.powi(i as i32) * (0.8 + 0.4 * unit(seed, n + 100)))
+            })
+            .collect();
+        let funds: Vec<Holder> = MOCK_FUNDS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let n = 30 + i as u64;
+                holder(name, n, 0.03 * 0.7f64.powi(i as i32) * (0.8 + 0.4 * unit(seed, n + 100)))
+            })
+            .collect();
+
+        let institutions_fraction = 0.4 + unit(seed, 2) * 0.45;
+        let insiders_fraction = unit(seed, 3) * 0.1;
+        Ok(Some(Ownership {
+            breakdown: OwnershipBreakdown {
+                insiders_percent: fraction_to_percent(Some(insiders_fraction)),
+                institutions_percent: fraction_to_percent(Some(institutions_fraction)),
+                institutions_float_percent: fraction_to_percent(Some(
+                    institutions_fraction / (1.0 - insiders_fraction),
+                )),
+                institutions_count: Some(500 + (unit(seed, 4) * 3500.0) as i64),
+            },
+            institutions,
+            funds,
+        }))
+    }
+
+    async fn fund_composition(&self, symbol: &str) -> Result<Option<FundComposition>> {
+        let symbol = symbol.to_uppercase();
+        if !MOCK_ETFS.contains(&symbol.as_str()) {
+            return Ok(None);
+        }
+
+        // Ten constituents with decaying weights, starting at a per-fund offset
+        let seed = self.series(&symbol).seed;
+        let offset = (seed % MOCK_CONSTITUENTS.len() as u64) as usize;
+        let picks: Vec<(&(&str, &str, &str), f64)> = (0..10)
+            .map(|i| {
+                let constituent = &MOCK_CONSTITUENTS[(offset + i) % MOCK_CONSTITUENTS.len()];
+                let fraction = 0.07 * 0.8f64.powi(i as i32) * (0.8 + 0.4 * unit(seed, 50 + i as u64));
+                (constituent, fraction)
+            })
+            .collect();
+
+        // The rest of the fund is spread like its top holdings
+        let mut by_sector: HashMap<&str, f64> = HashMap::new();
+        for ((_, _, sector), fraction) in &picks {
+            *by_sector.entry(*sector).or_default() += fraction;
+        }
+        let top_total: f64 = by_sector.values().sum();
+        let mut sector_weights: Vec<SectorWeight> = by_sector
+            .into_iter()
+            .filter_map(|(sector, fraction)| {
+                Some(SectorWeight {
+                    sector: sector.to_string(),
+                    weight_percent: fraction_to_percent(Some(fraction / top_total))?,
+                })
+            })
+            .collect();
+        sector_weights.sort_by(|a, b| {
+            b.weight_percent.cmp(&a.weight_percent).then_with(|| a.sector.cmp(&b.sector))
+        });
+
+        Ok(Some(FundComposition {
+            holdings: picks
+                .into_iter()
+                .filter_map(|((ticker, name, _), fraction)| {
+                    Some(FundHolding {
+                        symbol: Some(ticker.to_string()),
+                        name: name.to_string(),
+                        weight_percent: fraction_to_percent(Some(fraction))?,
+                    })
+                })
+                .collect(),
+            sector_weights,
+        }))
+    }
+}
+
+/// Price path for one symbol: a slow yearly cycle plus a faster swing and hourly noise
+/// around `base`, evaluated purely from the timestamp
+struct Series {
+    seed: u64,
+    base: f64,
+    /// Currency pairs move a tenth as much as stocks, are priced to six decimals and pay
+    /// no dividends
+    currency_pair: bool,
+}
+
+impl Series {
+    fn price_at(&self, t: u64) -> f64 {
+        let days = t as f64 / SECS_PER_DAY as f64;
+        let phase = (self.seed % 360) as f64 * TAU / 360.0;
+        let cycle = 0.2 * (days / 365.0 * TAU + phase).sin();
+        let swing = 0.05 * (days / 23.0 * TAU + phase * 3.0).sin();
+        let noise = (unit(self.seed, t / 3600) - 0.5) * 0.02;
+        let scale = if self.currency_pair { 0.1 } else { 1.0 };
+        self.round(self.base * (1.0 + scale * (cycle + swing + noise)))
+    }
+
+    fn round(&self, value: f64) -> f64 {
+        if self.currency_pair {
+            (value.max(0.000001) * 1_000_000.0).round() / 1_000_000.0
+        } else {
+            round_cents(value)
+        }
+    }
+
+    fn candle(&self, t: u64, step: u64) -> Quote {
+        let open = self.price_at(t.saturating_sub(step));
+        let close = self.price_at(t);
+        let high = self.round(open.max(close) * (1.0 + 0.01 * unit(self.seed, t ^ 0x01)));
+        let low = self.round(open.min(close) * (1.0 - 0.01 * unit(self.seed, t ^ 0x02)));
+        let volume_scale = 0.5 + unit(self.seed, t ^ 0x03);
+        let volume = (1_000_000.0 * volume_scale * (step as f64 / SECS_PER_DAY as f64).min(1.0)) as u64;
+
+        Quote {
+            timestamp: t,
+            open,
+            high,
+            low,
+            volume: volume.max(100),
+            close,
+            adjclose: close,
+        }
+    }
+
+    /// Candles between `start` and `end`, newest `MOCK_MAX_CANDLES` kept. Daily and
+    /// intraday bars skip weekends.
+    fn candles(&self, start: u64, end: u64, step: u64) -> Vec<Quote> {
+        let first = if step >= SECS_PER_DAY {
+            start - start % SECS_PER_DAY + MOCK_SESSION_OPEN_SECS
+        } else {
+            start - start % step
+        };
+
+        let mut candles: Vec<Quote> = (0..)
+            .map(|i| first + i * step)
+            .take_while(|t| *t <= end)
+            .filter(|t| step > SECS_PER_DAY || !is_weekend(*t))
+            .map(|t| self.candle(t, step))
+            .collect();
+        if candles.len() > MOCK_MAX_CANDLES {
+            candles.drain(..candles.len() - MOCK_MAX_CANDLES);
+        }
+        candles
+    }
+
+    /// Roughly half the symbols pay quarterly, going ex on the 15th of Feb/May/Aug/Nov
+    fn dividends(&self, start: u64, end: u64) -> Vec<Dividend> {
+        if self.currency_pair || self.seed % 2 != 0 {
+            return Vec::new();
+        }
+        let annual_yield = 0.01 + (self.seed % 30) as f64 / 1000.0;
+        let amount = (self.base * annual_yield / 4.0 * 10_000.0).round() / 10_000.0;
+
+        let (Some(from), Some(to)) = (date_of(start), date_of(end)) else {
+            return Vec::new();
+        };
+        (from.year()..=to.year())
+            .flat_map(|year| [2, 5, 8, 11].map(|month| NaiveDate::from_ymd_opt(year, month, 15)))
+            .flatten()
+            .filter(|date| *date >= from && *date <= to)
+            .filter_map(|date| date.and_hms_opt(0, 0, 0))
+            .map(|at| Dividend {
+                amount,
+                date: at.and_utc().timestamp() as u64 + MOCK_SESSION_OPEN_SECS,
+            })
+            .collect()
+    }
+}
+
+fn interval_seconds(interval: &str) -> Option<u64> {
+    Some(match interval {
+        "1m" => 60,
+        "2m" => 120,
+        "5m" => 300,
+        "15m" => 900,
+        "30m" => 1800,
+        "60m" | "1h" => 3600,
+        "90m" => 5400,
+        "1d" => SECS_PER_DAY,
+        "5d" => 5 * SECS_PER_DAY,
+        "1wk" => 7 * SECS_PER_DAY,
+        "1mo" => 30 * SECS_PER_DAY,
+        "3mo" => 91 * SECS_PER_DAY,
+        _ => return None,
+    })
+}
+
+fn range_days(range: &str) -> u64 {
+    match range {
+        "1d" => 1,
+        "5d" => 5,
+        "1mo" => 30,
+        "3mo" => 91,
+        "6mo" => 182,
+        "2y" => 730,
+        "5y" => 1826,
+        "10y" | "max" => 3652,
+        "ytd" => Utc::now().ordinal() as u64,
+        _ => 365,
+    }
+}
+
+fn date_of(t: u64) -> Option<NaiveDate> {
+    chrono::DateTime::from_timestamp(t as i64, 0).map(|dt| dt.date_naive())
+}
+
+fn is_weekend(t: u64) -> bool {
+    // 1970-01-01 was a Thursday
+    matches!((t / SECS_PER_DAY + 4) % 7, 0 | 6)
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value.max(0.01) * 100.0).round() / 100.0
+}
+
+/// FNV-1a, stable across platforms and Rust releases unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Uniform value in [0, 1) for a seed and counter (splitmix64 finalizer)
+fn unit(seed: u64, n: u64) -> f64 {
+    let mut z = seed ^ n.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday 2024-01-08 00:00 UTC
+    const MONDAY: u64 = 1_704_672_000;
+
+    #[test]
+    fn synthetic_series_are_deterministic() {
+        let end = MONDAY + 14 * SECS_PER_DAY;
+        let first = MockProvider::new().history_between("aapl", "1d", MONDAY, end).unwrap();
+        let second = MockProvider::new().history_between("AAPL", "1d", MONDAY, end).unwrap();
+        let closes = |history: &PriceHistory| history.quotes.iter().map(|q| (q.timestamp, q.close)).collect::<Vec<_>>();
+        assert_eq!(closes(&first), closes(&second));
+
+        let other = MockProvider::new().history_between("MSFT", "1d", MONDAY, end).unwrap();
+        assert_ne!(closes(&first), closes(&other));
+    }
+
+    #[test]
+    fn daily_candles_skip_weekends_and_stay_consistent() {
+        let history = MockProvider::new()
+            .history_between("AAPL", "1d", MONDAY, MONDAY + 14 * SECS_PER_DAY)
+            .unwrap();
+        // Two trading weeks; the range ends before the final Monday's session opens
+        assert_eq!(history.quotes.len(), 10);
+        for quote in &history.quotes {
+            assert!(!is_weekend(quote.timestamp));
+            assert!(quote.high >= quote.open.max(quote.close));
+            assert!(quote.low <= quote.open.min(quote.close));
+            assert!(quote.low > 0.0);
+        }
+        assert_eq!(history.exchange.as_deref(), Some(MOCK_EXCHANGE));
+    }
+
+    #[test]
+    fn unsupported_intervals_are_refused() {
+        assert!(MockProvider::new().history_between("AAPL", "7h", MONDAY, MONDAY + SECS_PER_DAY).is_err());
+    }
+
+    #[tokio::test]
+    async fn fixtures_replace_synthetic_data() {
+        let provider = MockProvider::from_fixture_json(
+            r#"{"test": {
+                "name": "Test Holdings",
+                "currency": "EUR",
+                "quotes": [
+                    {"timestamp": 1704720600, "open": 10.0, "high": 11.0, "low": 9.5, "volume": 1000, "close": 10.5, "adjclose": 10.5},
+                    {"timestamp": 1704807000, "open": 10.5, "high": 12.0, "low": 10.0, "volume": 1200, "close": 11.5, "adjclose": 11.5}
+                ]
+            }}"#,
+        )
+        .unwrap();
+
+        let history = provider.history_between("TEST", "1d", 0, MONDAY).unwrap();
+        assert_eq!(history.quotes.len(), 2);
+        assert_eq!(history.currency.as_deref(), Some("EUR"));
+
+        let latest = provider.latest_quote("TEST").await.unwrap().unwrap();
+        assert_eq!(latest.quote.close, 11.5);
+        assert_eq!(latest.previous_close, Some(10.5));
+
+        let matches = provider.search("holdings").await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].symbol, "TEST");
+        assert_eq!(matches[0].name.as_deref(), Some("Test Holdings"));
+    }
+
+    #[tokio::test]
+    async fn any_valid_ticker_exists_in_the_mock_market() {
+        let provider = MockProvider::new();
+        let matches = provider.search("zzzz").await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].symbol, "ZZZZ");
+        assert!(provider.latest_quote("ZZZZ").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn fixture_json_must_be_an_object_of_symbols() {
+        assert!(MockProvider::from_fixture_json("[1, 2, 3]").is_err());
+    }
+}
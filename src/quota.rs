@@ -0,0 +1,299 @@
+//! Daily budget of market-data provider calls.
+//!
+//! The per-minute Yahoo window keeps bursts polite; this budget caps what a deployment
+//! spends over a whole UTC day, so a busy instance stops short of the volume that gets its
+//! IP banned. `YAHOO_DAILY_BUDGET` is split across features by percent. A feature that has
+//! used 80% of its share is logged once a day. One that has used all of it makes no further
+//! calls until midnight UTC: quotes, candles and profiles are answered from stored data
+//! however old, and anything without stored data is refused as rate limited until the reset.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::QuotaConfig;
+use crate::models::{FeatureQuota, QuotaUsage};
+
+/// Share of a feature's budget after which it is logged as running low
+const WARN_PERCENT: u64 = 80;
+
+/// What a provider call is for, each with its own share of the daily budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamFeature {
+    Quotes,
+    Historical,
+    /// Company profiles, holders and ETF compositions
+    Profiles,
+    /// Bulk operations and background jobs
+    Backfills,
+    /// Searches, symbol resolution and listings
+    Other,
+}
+
+impl UpstreamFeature {
+    pub const ALL: [UpstreamFeature; 5] = [
+        UpstreamFeature::Quotes,
+        UpstreamFeature::Historical,
+        UpstreamFeature::Profiles,
+        UpstreamFeature::Backfills,
+        UpstreamFeature::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpstreamFeature::Quotes => "quotes",
+            UpstreamFeature::Historical => "historical",
+            UpstreamFeature::Profiles => "profiles",
+            UpstreamFeature::Backfills => "backfills",
+            UpstreamFeature::Other => "other",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug)]
+struct QuotaState {
+    day: NaiveDate,
+    used: [u32; UpstreamFeature::ALL.len()],
+    warned: [bool; UpstreamFeature::ALL.len()],
+}
+
+impl QuotaState {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            used: [0; UpstreamFeature::ALL.len()],
+            warned: [false; UpstreamFeature::ALL.len()],
+        }
+    }
+
+    /// Start over when the UTC day has changed
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.day != today {
+            *self = Self::new(today);
+        }
+    }
+}
+
+/// Provider calls made today per feature against the configured daily budget
+#[derive(Debug)]
+pub struct QuotaBudget {
+    config: QuotaConfig,
+    state: Mutex<QuotaState>,
+}
+
+impl QuotaBudget {
+    pub fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(QuotaState::new(Utc::now().date_naive())),
+        }
+    }
+
+    /// Calls `feature` may make per day, or `None` without a budget
+    pub fn allowance(&self, feature: UpstreamFeature) -> Option<u32> {
+        if self.config.daily_budget == 0 {
+            return None;
+        }
+        let percent = match feature {
+            UpstreamFeature::Quotes => self.config.quotes_percent,
+            UpstreamFeature::Historical => self.config.historical_percent,
+            UpstreamFeature::Profiles => self.config.profiles_percent,
+            UpstreamFeature::Backfills => self.config.backfills_percent,
+            UpstreamFeature::Other => 100u32.saturating_sub(self.config.allocated_percent()),
+        };
+        Some((u64::from(self.config.daily_budget) * u64::from(percent) / 100) as u32)
+    }
+
+    /// Count a call for `feature`, or return how long until its share refills at midnight UTC
+    pub fn try_consume(&self, feature: UpstreamFeature) -> Result<(), Duration> {
+        self.try_consume_at(feature, Utc::now())
+    }
+
+    fn try_consume_at(&self, feature: UpstreamFeature, now: DateTime<Utc>) -> Result<(), Duration> {
+        let Some(allowance) = self.allowance(feature) else {
+            return Ok(());
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll_over(now.date_naive());
+
+        let index = feature.index();
+        if state.used[index] >= allowance {
+            return Err(until_reset(now));
+        }
+        state.used[index] += 1;
+
+        let used = u64::from(state.used[index]);
+        if !state.warned[index] && used * 100 >= u64::from(allowance) * WARN_PERCENT {
+            state.warned[index] = true;
+            warn!(
+                "{} of today's {} provider calls for {} used; once spent they are served from stored data until midnight UTC",
+                used,
+                allowance,
+                feature.as_str()
+            );
+        }
+        Ok(())
+    }
+
+    /// Give back a call counted by `try_consume` that was never made
+    pub fn refund(&self, feature: UpstreamFeature) {
+        if self.allowance(feature).is_none() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let used = &mut state.used[feature.index()];
+        *used = used.saturating_sub(1);
+    }
+
+    /// Today's usage per feature, or `None` without a budget
+    pub fn usage(&self) -> Option<QuotaUsage> {
+        self.usage_at(Utc::now())
+    }
+
+    fn usage_at(&self, now: DateTime<Utc>) -> Option<QuotaUsage> {
+        if self.config.daily_budget == 0 {
+            return None;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll_over(now.date_naive());
+
+        let features: BTreeMap<String, FeatureQuota> = UpstreamFeature::ALL
+            .iter()
+            .map(|&feature| {
+                let allowance = self.allowance(feature).unwrap_or(0);
+                let used = state.used[feature.index()];
+                let quota = FeatureQuota {
+                    allowance,
+                    used,
+                    remaining: allowance.saturating_sub(used),
+                    exhausted: used >= allowance,
+                };
+                (feature.as_str().to_string(), quota)
+            })
+            .collect();
+        Some(QuotaUsage {
+            daily_budget: self.config.daily_budget,
+            resets_at: next_reset(now),
+            features,
+        })
+    }
+}
+
+/// Midnight UTC after `now`, when daily budgets and quotas start over
+pub fn next_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    let tomorrow = now.date_naive().checked_add_days(Days::new(1)).unwrap_or(now.date_naive());
+    tomorrow.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+fn until_reset(now: DateTime<Utc>) -> Duration {
+    (next_reset(now) - now).to_std().unwrap_or(Duration::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A budget of 100 calls a day: 50 for quotes, 20 for history, 10 each for profiles and
+    /// backfills, leaving 10 for everything else, counted from 2026-03-10
+    fn budget() -> QuotaBudget {
+        let budget = QuotaBudget::new(QuotaConfig {
+            daily_budget: 100,
+            quotes_percent: 50,
+            historical_percent: 20,
+            profiles_percent: 10,
+            backfills_percent: 10,
+        });
+        *budget.state.lock().unwrap() = QuotaState::new(at(10, 0, 0).date_naive());
+        budget
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn budget_is_split_by_percent() {
+        let budget = budget();
+        let allowances: Vec<_> = UpstreamFeature::ALL.iter().map(|&f| budget.allowance(f)).collect();
+        assert_eq!(allowances, [Some(50), Some(20), Some(10), Some(10), Some(10)]);
+    }
+
+    #[test]
+    fn a_spent_share_waits_for_midnight_utc() {
+        let budget = budget();
+        for _ in 0..10 {
+            budget.try_consume_at(UpstreamFeature::Profiles, at(10, 9, 0)).unwrap();
+        }
+        let wait = budget.try_consume_at(UpstreamFeature::Profiles, at(10, 22, 30)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(90 * 60));
+        // Other features keep their own shares
+        budget.try_consume_at(UpstreamFeature::Quotes, at(10, 22, 30)).unwrap();
+
+        let usage = budget.usage_at(at(10, 23, 0)).unwrap();
+        assert_eq!(usage.resets_at, at(11, 0, 0));
+        let profiles = &usage.features["profiles"];
+        assert_eq!((profiles.used, profiles.remaining, profiles.exhausted), (10, 0, true));
+        assert_eq!(usage.features["quotes"].remaining, 49);
+    }
+
+    #[test]
+    fn the_next_utc_day_starts_over() {
+        let budget = budget();
+        for _ in 0..10 {
+            budget.try_consume_at(UpstreamFeature::Backfills, at(10, 23, 59)).unwrap();
+        }
+        assert!(budget.try_consume_at(UpstreamFeature::Backfills, at(10, 23, 59)).is_err());
+
+        budget.try_consume_at(UpstreamFeature::Backfills, at(11, 0, 0)).unwrap();
+        let usage = budget.usage_at(at(11, 0, 1)).unwrap();
+        assert_eq!(usage.features["backfills"].used, 1);
+        assert_eq!(usage.resets_at, at(12, 0, 0));
+        assert!(!budget.state.lock().unwrap().warned[UpstreamFeature::Backfills.index()]);
+    }
+
+    #[test]
+    fn warns_once_at_eighty_percent() {
+        let budget = budget();
+        let warned = || budget.state.lock().unwrap().warned[UpstreamFeature::Historical.index()];
+        for _ in 0..15 {
+            budget.try_consume_at(UpstreamFeature::Historical, at(10, 12, 0)).unwrap();
+        }
+        assert!(!warned());
+        budget.try_consume_at(UpstreamFeature::Historical, at(10, 12, 0)).unwrap();
+        assert!(warned());
+    }
+
+    #[test]
+    fn refunds_give_back_unmade_calls() {
+        let budget = budget();
+        for _ in 0..10 {
+            budget.try_consume_at(UpstreamFeature::Other, at(10, 8, 0)).unwrap();
+        }
+        budget.refund(UpstreamFeature::Other);
+        budget.try_consume_at(UpstreamFeature::Other, at(10, 8, 0)).unwrap();
+        assert!(budget.try_consume_at(UpstreamFeature::Other, at(10, 8, 0)).is_err());
+
+        // Never below zero
+        budget.refund(UpstreamFeature::Quotes);
+        assert_eq!(budget.usage_at(at(10, 8, 0)).unwrap().features["quotes"].used, 0);
+    }
+
+    #[test]
+    fn a_zero_budget_is_unlimited() {
+        let budget = QuotaBudget::new(QuotaConfig::default());
+        assert_eq!(budget.allowance(UpstreamFeature::Quotes), None);
+        for _ in 0..1_000 {
+            budget.try_consume(UpstreamFeature::Quotes).unwrap();
+        }
+        assert!(budget.usage().is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! Composite rankings of stored symbols under a weighted factor model.
+//!
+//! Each factor is measured from stored data only, like peer comparisons: a year of daily
+//! candles, the dividends stored with them and the market cap on the symbols table. A symbol's
+//! value on a factor becomes its percentile among the ranked symbols that have one (100 is
+//! the best, after the factor's direction and the sign of its weight), and its score is the
+//! weighted average of those percentiles over the factors it has. Symbols missing factors are
+//! still ranked on the rest, with `coverage` telling how much of the model's weight that was.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::config::{MAX_RANKING_FACTOR_WEIGHT, MAX_RANKING_MODEL_NAME_LENGTH};
+use crate::models::{
+    Dividend, HistoricalPrice, RankingEntry, RankingFactor, RankingFactorScore, RankingModel, Symbol,
+};
+use crate::peers;
+use crate::risk;
+
+/// Lower-cased model name of letters, digits, '-' and '_'
+pub fn normalize_model_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() || name.len() > MAX_RANKING_MODEL_NAME_LENGTH {
+        return Err(format!("Model name must be 1-{} characters", MAX_RANKING_MODEL_NAME_LENGTH));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid model name '{}'. Use letters, digits, '-' and '_'", name));
+    }
+    Ok(name)
+}
+
+/// Check a model's weights: finite, within bounds and not all zero
+pub fn validate(weights: &BTreeMap<RankingFactor, f64>) -> Result<(), String> {
+    for (factor, weight) in weights {
+        if !weight.is_finite() || weight.abs() > MAX_RANKING_FACTOR_WEIGHT {
+            return Err(format!(
+                "weight of {} must be between -{} and {}",
+                factor.as_str(),
+                MAX_RANKING_FACTOR_WEIGHT,
+                MAX_RANKING_FACTOR_WEIGHT
+            ));
+        }
+    }
+    if weights.values().all(|weight| *weight == 0.0) {
+        return Err("need at least one non-zero factor weight".to_string());
+    }
+    Ok(())
+}
+
+/// Factor values of `symbol` from its daily `candles` (newest first, a year is enough) and the
+/// past year's dividends. Returns and ratios are in percent except the Sharpe ratio.
+pub fn factor_values(
+    symbol: &Symbol,
+    candles: &[HistoricalPrice],
+    dividends: &[Dividend],
+    daily_risk_free: f64,
+) -> BTreeMap<RankingFactor, Decimal> {
+    let metrics = peers::metrics(symbol, false, candles, dividends);
+    let closes: Vec<f64> = candles.iter().rev().filter_map(|candle| candle.close.to_f64()).collect();
+    let returns = risk::daily_returns(&closes);
+    let sharpe = risk::sharpe_ratio(&returns, daily_risk_free).and_then(Decimal::from_f64);
+    let drawdown = risk::max_drawdown(&closes).and_then(|drawdown| Decimal::from_f64(drawdown * 100.0));
+
+    [
+        (RankingFactor::Momentum3m, metrics.change_3m_percent),
+        (RankingFactor::Momentum12m, metrics.change_1y_percent),
+        (RankingFactor::Volatility, metrics.volatility_percent),
+        (RankingFactor::MaxDrawdown, drawdown.map(|d| d.round_dp(2))),
+        (RankingFactor::SharpeRatio, sharpe.map(|s| s.round_dp(4))),
+        (RankingFactor::DividendYield, metrics.dividend_yield_percent),
+        (RankingFactor::MarketCap, metrics.market_cap),
+    ]
+    .into_iter()
+    .filter_map(|(factor, value)| value.map(|value| (factor, value)))
+    .collect()
+}
+
+/// Percentile (0 to 100, higher is better) of every symbol that has a value for `factor`
+fn percentiles(factor: RankingFactor, weight: f64, values: &[&BTreeMap<RankingFactor, Decimal>]) -> Vec<Option<f64>> {
+    let mut present: Vec<Decimal> = values.iter().filter_map(|v| v.get(&factor).copied()).collect();
+    present.sort();
+    let n = present.len();
+    let invert = factor.lower_is_better() != (weight < 0.0);
+
+    values
+        .iter()
+        .map(|v| {
+            let value = v.get(&factor)?;
+            if n < 2 {
+                return Some(50.0);
+            }
+            // Ties share the average of their positions
+            let below = present.partition_point(|other| other < value);
+            let up_to = present.partition_point(|other| other <= value);
+            let percentile = (below + up_to - 1) as f64 / 2.0 / (n - 1) as f64 * 100.0;
+            Some(if invert { 100.0 - percentile } else { percentile })
+        })
+        .collect()
+}
+
+fn round(value: f64, dp: u32) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(dp)
+}
+
+/// Score and rank `symbols` with their factor values under `model`, best first. Symbols
+/// without a value for any weighted factor are left out.
+pub fn rank(model: &RankingModel, symbols: Vec<(Symbol, BTreeMap<RankingFactor, Decimal>)>) -> Vec<RankingEntry> {
+    let weighted: Vec<(RankingFactor, f64)> = model
+        .weights
+        .iter()
+        .filter(|(_, weight)| **weight != 0.0)
+        .map(|(factor, weight)| (*factor, *weight))
+        .collect();
+    let total_weight: f64 = weighted.iter().map(|(_, weight)| weight.abs()).sum();
+    let values: Vec<&BTreeMap<RankingFactor, Decimal>> = symbols.iter().map(|(_, values)| values).collect();
+    let factor_percentiles: Vec<Vec<Option<f64>>> = weighted
+        .iter()
+        .map(|(factor, weight)| percentiles(*factor, *weight, &values))
+        .collect();
+
+    let mut scored: Vec<(f64, f64, RankingEntry)> = Vec::new();
+    for (i, (symbol, values)) in symbols.into_iter().enumerate() {
+        let mut factors = BTreeMap::new();
+        let (mut weighted_sum, mut covered) = (0.0, 0.0);
+        for ((factor, weight), percentiles) in weighted.iter().zip(&factor_percentiles) {
+            let percentile = percentiles[i];
+            if let Some(percentile) = percentile {
+                weighted_sum += weight.abs() * percentile;
+                covered += weight.abs();
+            }
+            factors.insert(
+                *factor,
+                RankingFactorScore {
+                    value: values.get(factor).copied(),
+                    percentile: percentile.map(|p| round(p, 2)),
+                },
+            );
+        }
+        if covered == 0.0 {
+            continue;
+        }
+
+        let score = weighted_sum / covered;
+        let coverage = covered / total_weight;
+        scored.push((
+            score,
+            coverage,
+            RankingEntry {
+                rank: 0,
+                symbol: symbol.symbol,
+                name: symbol.name,
+                sector: symbol.sector,
+                score: round(score, 2),
+                coverage: round(coverage, 4),
+                factors,
+            },
+        ));
+    }
+
+    scored.sort_by(|(a_score, a_coverage, a), (b_score, b_coverage, b)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b_coverage.partial_cmp(a_coverage).unwrap_or(Ordering::Equal))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    scored
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, _, entry))| RankingEntry { rank: i + 1, ..entry })
+        .collect()
+}
@@ -0,0 +1,60 @@
+//! Read-only instances for scaling reads.
+//!
+//! With `READ_ONLY=true` the service opens an existing database read-only, so a second
+//! instance can serve the SQLite file another instance writes (or a replica of it). Writes
+//! are refused up front: every `POST`, `PUT`, `PATCH` and `DELETE` except the few that only
+//! compute, and the OIDC callback, which would create a session. Quotes, candles and profiles
+//! are served as stored, however old, since fetching them would mean storing them; lookups
+//! that store nothing, such as suggestions and holders, still reach the provider. Background
+//! writers (job workers, the portfolio updater, persisted stats samples and object storage
+//! exports) are not started. Refused requests, and any write that still slips through and
+//! fails in SQLite, are answered `503 READ_ONLY` so clients and load balancers can retry
+//! against the writable instance.
+
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::{ApiError, ErrorCode};
+
+pub const READ_ONLY_MESSAGE: &str = "This instance is read-only; send writes to the writable instance";
+
+/// POST routes that only compute from stored data and stay available
+const ALLOWED_POST_PREFIXES: &[&str] = &[
+    "/api/tools/",
+    "/api/query",
+    "/api/analytics/sql",
+    "/api/portfolio/projection",
+    "/auth/jwt/token",
+];
+
+/// GET routes that write
+const REFUSED_GET_PATHS: &[&str] = &["/auth/oidc/callback"];
+
+/// Refuse requests that would write; only installed on read-only instances
+pub async fn reject_writes_middleware(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let writes = match *request.method() {
+        Method::GET | Method::HEAD => REFUSED_GET_PATHS.contains(&path),
+        Method::OPTIONS => false,
+        Method::POST => !ALLOWED_POST_PREFIXES.iter().any(|prefix| path.starts_with(prefix)),
+        _ => true,
+    };
+    if writes {
+        tracing::debug!("Refused {} {} on a read-only instance", request.method(), path);
+        return ApiError::from(ErrorCode::ReadOnly).into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether `err` is SQLite refusing a write to a database opened read-only
+pub fn is_read_only_write(err: &sqlx::Error) -> bool {
+    // SQLITE_READONLY (8) and its extended codes, which keep it in the low byte
+    err.as_database_error()
+        .and_then(|e| e.code())
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| code & 0xff == 8)
+}
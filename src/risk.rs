@@ -0,0 +1,94 @@
+//! Risk statistics on daily close-to-close returns.
+
+use crate::config::TRADING_DAYS_PER_YEAR;
+
+/// Simple returns between consecutive closes, oldest first
+pub fn daily_returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .filter(|w| w[0] != 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect()
+}
+
+/// Beta of `asset` against `benchmark` and the R² of that fit. Both slices hold returns for
+/// the same days; `None` when they differ in length or the benchmark never moved.
+pub fn beta(asset: &[f64], benchmark: &[f64]) -> Option<(f64, f64)> {
+    let n = asset.len();
+    if n < 2 || n != benchmark.len() {
+        return None;
+    }
+
+    let mean_asset = asset.iter().sum::<f64>() / n as f64;
+    let mean_benchmark = benchmark.iter().sum::<f64>() / n as f64;
+    let (mut covariance, mut var_asset, mut var_benchmark) = (0.0, 0.0, 0.0);
+    for (a, b) in asset.iter().zip(benchmark) {
+        let (da, db) = (a - mean_asset, b - mean_benchmark);
+        covariance += da * db;
+        var_asset += da * da;
+        var_benchmark += db * db;
+    }
+    if var_benchmark == 0.0 {
+        return None;
+    }
+
+    let beta = covariance / var_benchmark;
+    let r_squared = if var_asset == 0.0 {
+        0.0
+    } else {
+        covariance * covariance / (var_asset * var_benchmark)
+    };
+    Some((beta, r_squared))
+}
+
+/// Daily rate that compounds to `annual_rate` over a trading year
+pub fn daily_rate(annual_rate: f64) -> f64 {
+    (1.0 + annual_rate).powf(1.0 / TRADING_DAYS_PER_YEAR) - 1.0
+}
+
+/// Annualized Sharpe ratio: mean excess return over its standard deviation
+pub fn sharpe_ratio(returns: &[f64], daily_risk_free: f64) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean_excess = returns.iter().map(|r| r - daily_risk_free).sum::<f64>() / n;
+    let variance = returns
+        .iter()
+        .map(|r| (r - daily_risk_free - mean_excess).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    (variance > 0.0).then(|| mean_excess / variance.sqrt() * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+/// Annualized Sortino ratio: mean excess return over the downside deviation, which only
+/// counts returns below the risk-free rate. `None` when there were none.
+pub fn sortino_ratio(returns: &[f64], daily_risk_free: f64) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean_excess = returns.iter().map(|r| r - daily_risk_free).sum::<f64>() / n;
+    let downside = returns
+        .iter()
+        .map(|r| (r - daily_risk_free).min(0.0).powi(2))
+        .sum::<f64>()
+        / n;
+    (downside > 0.0).then(|| mean_excess / downside.sqrt() * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+/// Deepest fall from a running high as a fraction, over closes oldest first
+pub fn max_drawdown(closes: &[f64]) -> Option<f64> {
+    if closes.len() < 2 {
+        return None;
+    }
+    let mut peak = f64::MIN;
+    let mut deepest: f64 = 0.0;
+    for &close in closes {
+        peak = peak.max(close);
+        if peak > 0.0 {
+            deepest = deepest.max(1.0 - close / peak);
+        }
+    }
+    Some(deepest)
+}
@@ -0,0 +1,133 @@
+//! History of cache and provider activity for capacity planning.
+//!
+//! Every `STATS_SAMPLE_INTERVAL` the sampler records each cache's size and the hits, misses
+//! and evictions since the previous sample, along with the provider calls made and refused
+//! by the Yahoo rate limit. Samples covering the last `MAX_STATS_HISTORY_HOURS` are kept in
+//! memory; with `STATS_HISTORY_PERSIST` they are also stored so `GET /api/stats/history`
+//! survives restarts.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::cache::CacheStats;
+use crate::config::{StatsHistoryConfig, MAX_STATS_HISTORY_HOURS};
+use crate::models::{CacheSample, StatsSample};
+use crate::yahoo_service::YahooFinanceService;
+
+/// Fixed-capacity buffer of the most recent samples
+pub struct StatsRing {
+    samples: Mutex<VecDeque<StatsSample>>,
+    capacity: usize,
+}
+
+impl StatsRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, sample: StatsSample) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// Samples taken at or after `since`, oldest first
+    pub fn since(&self, since: DateTime<Utc>) -> Vec<StatsSample> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples.iter().filter(|s| s.sampled_at >= since).cloned().collect()
+    }
+}
+
+/// Start sampling on the current runtime
+pub fn spawn(service: Arc<YahooFinanceService>, config: StatsHistoryConfig) {
+    info!(
+        "📈 Sampling cache statistics every {}s{}",
+        config.sample_interval.as_secs(),
+        if config.persist { ", stored in the database" } else { "" }
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.sample_interval);
+        // The first tick completes at once; counters start from zero at startup
+        interval.tick().await;
+        let mut previous = Totals::default();
+        let mut last_sampled = Utc::now();
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            let totals = Totals::read(&service);
+            let sample = totals.since(&previous, last_sampled, now);
+            previous = totals;
+            last_sampled = now;
+
+            if config.persist {
+                if let Err(e) = service.db.insert_stats_sample(&sample).await {
+                    warn!("Failed to store stats sample: {:?}", e);
+                }
+                let cutoff = now - chrono::Duration::hours(MAX_STATS_HISTORY_HOURS);
+                if let Err(e) = service.db.delete_stats_samples_before(cutoff).await {
+                    warn!("Failed to prune stats samples: {:?}", e);
+                }
+            }
+            service.stats_history.push(sample);
+        }
+    });
+}
+
+/// Cumulative counters at one point in time
+#[derive(Default)]
+struct Totals {
+    caches: Vec<(&'static str, CacheStats)>,
+    yahoo_calls: u64,
+    yahoo_rate_limited: u64,
+}
+
+impl Totals {
+    fn read(service: &YahooFinanceService) -> Self {
+        let (yahoo_calls, yahoo_rate_limited) = service.yahoo_call_counts();
+        Self {
+            caches: service.cache_stats(),
+            yahoo_calls,
+            yahoo_rate_limited,
+        }
+    }
+
+    /// Activity between `previous` and these totals
+    fn since(&self, previous: &Totals, from: DateTime<Utc>, to: DateTime<Utc>) -> StatsSample {
+        let before: HashMap<&str, &CacheStats> =
+            previous.caches.iter().map(|(name, stats)| (*name, stats)).collect();
+        let caches = self
+            .caches
+            .iter()
+            .map(|(name, stats)| {
+                let prior = before.get(name);
+                let delta = |now: u64, then: fn(&CacheStats) -> u64| {
+                    now.saturating_sub(prior.map_or(0, |p| then(p)))
+                };
+                CacheSample {
+                    cache: name.to_string(),
+                    entries: stats.entries,
+                    weighted_bytes: stats.weighted_bytes,
+                    max_bytes: stats.max_bytes,
+                    hits: delta(stats.hits, |s| s.hits),
+                    misses: delta(stats.misses, |s| s.misses),
+                    evictions: delta(stats.evictions, |s| s.evictions),
+                }
+            })
+            .collect();
+
+        StatsSample {
+            sampled_at: to,
+            interval_seconds: (to - from).num_seconds().max(0) as u64,
+            yahoo_calls: self.yahoo_calls.saturating_sub(previous.yahoo_calls),
+            yahoo_rate_limited: self.yahoo_rate_limited.saturating_sub(previous.yahoo_rate_limited),
+            caches,
+        }
+    }
+}
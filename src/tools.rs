@@ -0,0 +1,217 @@
+//! Tool endpoints for LLM agents.
+//!
+//! `GET /api/tools` describes each tool with a JSON Schema for its input, in the shape
+//! tool-calling models and MCP clients expect. `POST /api/tools/{name}` takes the input as
+//! the JSON body, validates it strictly (unknown fields, wrong types and out-of-range values
+//! are rejected rather than coerced) and answers with the same response as the matching
+//! REST endpoint.
+
+use chrono::{DateTime, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{MAX_HISTORICAL_LIMIT, MAX_SYMBOL_LENGTH, MIN_TECHNICAL_INDICATOR_PERIODS};
+use crate::validation::validate_symbol;
+
+/// Intervals accepted by the historical tool
+pub const INTERVALS: [&str; 10] = ["1m", "2m", "5m", "15m", "30m", "90m", "1h", "1d", "1wk", "1mo"];
+
+/// Most daily periods the indicators tool looks back over
+pub const MAX_INDICATOR_DAYS: i32 = 500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: Value,
+}
+
+/// Discovery document served at `GET /api/tools`
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCatalog {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Path template for invoking a tool with its input as the JSON body
+    pub invoke: &'static str,
+    pub tools: Vec<ToolDefinition>,
+}
+
+pub fn catalog() -> ToolCatalog {
+    let symbol = json!({
+        "type": "string",
+        "description": "Ticker symbol, e.g. AAPL, BRK-B or BTC-USD",
+        "pattern": "^[A-Za-z0-9.-]+$",
+        "minLength": 1,
+        "maxLength": MAX_SYMBOL_LENGTH,
+    });
+
+    ToolCatalog {
+        name: "mango-data-service",
+        version: env!("CARGO_PKG_VERSION"),
+        invoke: "POST /api/tools/{name}",
+        tools: vec![
+            ToolDefinition {
+                name: "get_quote",
+                description: "Latest price, change, change percent and volume for one symbol.",
+                input_schema: json!({
+                    "type": "object",
+                    "properties": { "symbol": symbol },
+                    "required": ["symbol"],
+                    "additionalProperties": false,
+                }),
+            },
+            ToolDefinition {
+                name: "get_historical",
+                description: "OHLCV candles for one symbol, newest first.",
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": symbol,
+                        "interval": {
+                            "type": "string",
+                            "enum": INTERVALS,
+                            "default": "1d",
+                        },
+                        "start_date": {
+                            "type": "string",
+                            "description": "YYYY-MM-DD or RFC 3339 timestamp, inclusive",
+                        },
+                        "end_date": {
+                            "type": "string",
+                            "description": "YYYY-MM-DD or RFC 3339 timestamp, inclusive",
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_HISTORICAL_LIMIT,
+                        },
+                    },
+                    "required": ["symbol"],
+                    "additionalProperties": false,
+                }),
+            },
+            ToolDefinition {
+                name: "get_indicators",
+                description: "Technical indicators (SMA, EMA, RSI, MACD, Bollinger Bands, volume) \
+                              computed from daily closes.",
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": symbol,
+                        "days": {
+                            "type": "integer",
+                            "description": "Daily periods to compute over",
+                            "minimum": MIN_TECHNICAL_INDICATOR_PERIODS,
+                            "maximum": MAX_INDICATOR_DAYS,
+                            "default": 100,
+                        },
+                    },
+                    "required": ["symbol"],
+                    "additionalProperties": false,
+                }),
+            },
+        ],
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetQuoteInput {
+    pub symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetHistoricalInput {
+    pub symbol: String,
+    pub interval: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetIndicatorsInput {
+    pub symbol: String,
+    pub days: Option<i32>,
+}
+
+/// A validated tool invocation. Symbols are uppercased.
+#[derive(Debug)]
+pub enum ToolCall {
+    GetQuote(GetQuoteInput),
+    GetHistorical(GetHistoricalInput),
+    GetIndicators(GetIndicatorsInput),
+}
+
+impl ToolCall {
+    pub fn parse(name: &str, input: Value) -> Result<Self, String> {
+        match name {
+            "get_quote" => {
+                let mut input: GetQuoteInput = decode(name, input)?;
+                input.symbol = check_symbol(&input.symbol)?;
+                Ok(ToolCall::GetQuote(input))
+            }
+            "get_historical" => {
+                let mut input: GetHistoricalInput = decode(name, input)?;
+                input.symbol = check_symbol(&input.symbol)?;
+                if let Some(interval) = &input.interval {
+                    if !INTERVALS.contains(&interval.as_str()) {
+                        return Err(format!("interval must be one of: {}", INTERVALS.join(", ")));
+                    }
+                }
+                for (field, value) in [("start_date", &input.start_date), ("end_date", &input.end_date)] {
+                    if let Some(value) = value {
+                        check_date(field, value)?;
+                    }
+                }
+                if let Some(limit) = input.limit {
+                    check_range("limit", limit, 1, MAX_HISTORICAL_LIMIT)?;
+                }
+                Ok(ToolCall::GetHistorical(input))
+            }
+            "get_indicators" => {
+                let mut input: GetIndicatorsInput = decode(name, input)?;
+                input.symbol = check_symbol(&input.symbol)?;
+                if let Some(days) = input.days {
+                    check_range("days", days, MIN_TECHNICAL_INDICATOR_PERIODS as i32, MAX_INDICATOR_DAYS)?;
+                }
+                Ok(ToolCall::GetIndicators(input))
+            }
+            _ => Err(format!(
+                "Unknown tool '{}'. See GET /api/tools for the available tools",
+                name
+            )),
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(tool: &str, input: Value) -> Result<T, String> {
+    if !input.is_object() {
+        return Err(format!("Input for {} must be a JSON object", tool));
+    }
+    serde_json::from_value(input).map_err(|e| format!("Invalid input for {}: {}", tool, e))
+}
+
+fn check_symbol(symbol: &str) -> Result<String, String> {
+    validate_symbol(symbol).map_err(|e| format!("symbol: {}", e))?;
+    Ok(symbol.to_uppercase())
+}
+
+fn check_date(field: &str, value: &str) -> Result<(), String> {
+    if DateTime::parse_from_rfc3339(value).is_ok() || NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        Ok(())
+    } else {
+        Err(format!("{} must be YYYY-MM-DD or an RFC 3339 timestamp", field))
+    }
+}
+
+fn check_range(field: &str, value: i32, min: i32, max: i32) -> Result<(), String> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} must be between {} and {}", field, min, max))
+    }
+}
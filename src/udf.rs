@@ -0,0 +1,244 @@
+//! TradingView Universal Data Feed (UDF) adapter.
+//!
+//! `/udf/config`, `/udf/symbols`, `/udf/search`, `/udf/history` and `/udf/time` implement the
+//! UDF protocol over stored candles, so the TradingView charting library can use this service
+//! as its datafeed directly. Responses use the protocol's own shapes rather than the
+//! `ApiResponse` envelope: history is columnar (`t`, `o`, `h`, `l`, `c`, `v`) with a status in
+//! `s`, and errors are `{"s": "error", "errmsg": "..."}`.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::market_calendar::{self, EXCHANGES};
+use crate::models::{HistoricalPrice, Symbol};
+
+/// Resolutions offered to the chart, from one minute to one month
+pub const SUPPORTED_RESOLUTIONS: [&str; 10] = ["1", "2", "5", "15", "30", "60", "90", "1D", "1W", "1M"];
+
+/// Candle interval for a UDF resolution; `D`, `W` and `M` are accepted without the leading 1
+pub fn interval_for_resolution(resolution: &str) -> Option<&'static str> {
+    match resolution.trim().to_uppercase().as_str() {
+        "1" => Some("1m"),
+        "2" => Some("2m"),
+        "5" => Some("5m"),
+        "15" => Some("15m"),
+        "30" => Some("30m"),
+        "60" => Some("1h"),
+        "90" => Some("90m"),
+        "D" | "1D" => Some("1d"),
+        "W" | "1W" => Some("1wk"),
+        "M" | "1M" => Some("1mo"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UdfExchange {
+    pub value: &'static str,
+    pub name: &'static str,
+    pub desc: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UdfSymbolType {
+    pub name: &'static str,
+    pub value: &'static str,
+}
+
+/// Datafeed capabilities served at `/udf/config`
+#[derive(Debug, Clone, Serialize)]
+pub struct UdfConfig {
+    pub supported_resolutions: Vec<&'static str>,
+    pub supports_group_request: bool,
+    pub supports_marks: bool,
+    pub supports_search: bool,
+    pub supports_timescale_marks: bool,
+    pub supports_time: bool,
+    pub exchanges: Vec<UdfExchange>,
+    pub symbols_types: Vec<UdfSymbolType>,
+}
+
+pub fn config() -> UdfConfig {
+    let mut exchanges = vec![UdfExchange { value: "", name: "All Exchanges", desc: "" }];
+    exchanges.extend(EXCHANGES.iter().map(|calendar| UdfExchange {
+        value: calendar.code,
+        name: calendar.code,
+        desc: calendar.name,
+    }));
+
+    UdfConfig {
+        supported_resolutions: SUPPORTED_RESOLUTIONS.to_vec(),
+        supports_group_request: false,
+        supports_marks: false,
+        supports_search: true,
+        supports_timescale_marks: false,
+        supports_time: true,
+        exchanges,
+        symbols_types: vec![
+            UdfSymbolType { name: "All types", value: "" },
+            UdfSymbolType { name: "Stock", value: "stock" },
+            UdfSymbolType { name: "Index", value: "index" },
+            UdfSymbolType { name: "Crypto", value: "crypto" },
+            UdfSymbolType { name: "Forex", value: "forex" },
+            UdfSymbolType { name: "Futures", value: "futures" },
+        ],
+    }
+}
+
+/// TradingView instrument type of a Yahoo ticker
+pub fn symbol_type(symbol: &str) -> &'static str {
+    if symbol.starts_with('^') {
+        "index"
+    } else if symbol.ends_with("=X") {
+        "forex"
+    } else if symbol.ends_with("=F") {
+        "futures"
+    } else if symbol.contains('-') {
+        "crypto"
+    } else {
+        "stock"
+    }
+}
+
+/// Symbol description served at `/udf/symbols`
+#[derive(Debug, Clone, Serialize)]
+pub struct UdfSymbolInfo {
+    pub name: String,
+    pub ticker: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub session: String,
+    pub exchange: String,
+    pub listed_exchange: String,
+    pub timezone: String,
+    pub minmov: u32,
+    pub pricescale: u32,
+    pub has_intraday: bool,
+    pub has_daily: bool,
+    pub has_weekly_and_monthly: bool,
+    pub supported_resolutions: Vec<&'static str>,
+    pub intraday_multipliers: Vec<&'static str>,
+    pub volume_precision: u32,
+    pub data_status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_code: Option<String>,
+}
+
+impl UdfSymbolInfo {
+    /// Describe a stored symbol. Sessions come from the exchange calendar of its suffix; crypto
+    /// and listings without a calendar trade `24x7` so no candles are hidden. Prices below 1
+    /// get four decimals instead of two.
+    pub fn new(symbol: &Symbol, latest_close: Option<Decimal>) -> Self {
+        let kind = symbol_type(&symbol.symbol);
+        let calendar = (kind != "crypto")
+            .then(|| market_calendar::known_calendar_for_symbol(&symbol.symbol))
+            .flatten();
+        let session = calendar.map_or_else(|| "24x7".to_string(), |c| c.regular_hours());
+        // Session hours are local to the calendar's timezone, so it wins over the stored one
+        let timezone = calendar
+            .map(|c| c.timezone.to_string())
+            .or_else(|| symbol.timezone.clone())
+            .unwrap_or_else(|| "Etc/UTC".to_string());
+        let exchange = symbol
+            .exchange
+            .clone()
+            .or_else(|| calendar.map(|c| c.code.to_string()))
+            .unwrap_or_default();
+        let pricescale = match latest_close {
+            Some(close) if close < Decimal::ONE => 10_000,
+            _ => 100,
+        };
+
+        UdfSymbolInfo {
+            name: symbol.symbol.clone(),
+            ticker: symbol.symbol.clone(),
+            description: symbol.name.clone().unwrap_or_else(|| symbol.symbol.clone()),
+            kind,
+            session,
+            listed_exchange: exchange.clone(),
+            exchange,
+            timezone,
+            minmov: 1,
+            pricescale,
+            has_intraday: true,
+            has_daily: true,
+            has_weekly_and_monthly: true,
+            supported_resolutions: SUPPORTED_RESOLUTIONS.to_vec(),
+            intraday_multipliers: SUPPORTED_RESOLUTIONS[..7].to_vec(),
+            volume_precision: 0,
+            data_status: "delayed_streaming",
+            currency_code: symbol.currency.clone(),
+        }
+    }
+}
+
+/// One match served at `/udf/search`
+#[derive(Debug, Clone, Serialize)]
+pub struct UdfSearchResult {
+    pub symbol: String,
+    pub full_name: String,
+    pub description: String,
+    pub exchange: String,
+    pub ticker: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+}
+
+impl From<&Symbol> for UdfSearchResult {
+    fn from(symbol: &Symbol) -> Self {
+        UdfSearchResult {
+            symbol: symbol.symbol.clone(),
+            full_name: symbol.symbol.clone(),
+            description: symbol.name.clone().unwrap_or_default(),
+            exchange: symbol.exchange.clone().unwrap_or_default(),
+            ticker: symbol.symbol.clone(),
+            kind: symbol_type(&symbol.symbol),
+        }
+    }
+}
+
+/// Bars served at `/udf/history`, tagged by status in `s`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "s", rename_all = "snake_case")]
+pub enum UdfHistory {
+    Ok {
+        t: Vec<i64>,
+        o: Vec<f64>,
+        h: Vec<f64>,
+        l: Vec<f64>,
+        c: Vec<f64>,
+        v: Vec<i64>,
+    },
+    /// No bars in the range; `nextTime` is the latest bar before it, if any
+    NoData {
+        #[serde(rename = "nextTime", skip_serializing_if = "Option::is_none")]
+        next_time: Option<i64>,
+    },
+    Error { errmsg: String },
+}
+
+impl UdfHistory {
+    pub fn error(message: impl Into<String>) -> Self {
+        UdfHistory::Error { errmsg: message.into() }
+    }
+
+    /// Columnar bars, oldest first, with `time` in Unix seconds
+    pub fn from_prices(prices: &[HistoricalPrice]) -> Self {
+        let mut sorted: Vec<&HistoricalPrice> = prices.iter().collect();
+        sorted.sort_by_key(|p| p.timestamp);
+        let column = |value: fn(&HistoricalPrice) -> Decimal| -> Vec<f64> {
+            sorted.iter().map(|p| value(p).to_f64().unwrap_or_default()).collect()
+        };
+
+        UdfHistory::Ok {
+            t: sorted.iter().map(|p| p.timestamp.timestamp()).collect(),
+            o: column(|p| p.open),
+            h: column(|p| p.high),
+            l: column(|p| p.low),
+            c: column(|p| p.close),
+            v: sorted.iter().map(|p| p.volume).collect(),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Constituent list imports for named symbol universes.
+//!
+//! `POST /api/universes/:name/import` takes a CSV file with a header row, such as an index
+//! constituent list. A `symbol` (or `ticker`) column is required; `name`, `sector` and
+//! `weight` columns are picked up when present and any other columns are ignored. Rows that
+//! cannot be read are reported back rather than failing the whole import.
+
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::config::{MAX_UNIVERSE_MEMBERS, MAX_UNIVERSE_NAME_LENGTH};
+use crate::models::UniverseConstituent;
+use crate::validation::validate_symbol;
+
+/// Movers universes that are not stored in the universes table
+pub const RESERVED_UNIVERSE_NAMES: [&str; 2] = ["stored", "portfolio"];
+
+/// Lower-cased universe name of letters, digits, '-' and '_'
+pub fn normalize_universe_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() || name.len() > MAX_UNIVERSE_NAME_LENGTH {
+        return Err(format!(
+            "Universe name must be 1-{} characters",
+            MAX_UNIVERSE_NAME_LENGTH
+        ));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "Invalid universe name '{}'. Use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    if RESERVED_UNIVERSE_NAMES.contains(&name.as_str()) {
+        return Err(format!("'{}' is a reserved universe name", name));
+    }
+    Ok(name)
+}
+
+/// Constituents read from a CSV document
+#[derive(Debug, Default)]
+pub struct ParsedConstituents {
+    pub constituents: Vec<UniverseConstituent>,
+    /// "line N: reason" for each row that was left out
+    pub skipped: Vec<String>,
+}
+
+pub fn parse_constituents_csv(csv: &str) -> Result<ParsedConstituents, String> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_start_matches('\u{feff}').trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Err("CSV is empty".to_string());
+    };
+    let columns: Vec<String> = split_csv_line(header)
+        .into_iter()
+        .map(|c| c.trim().to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+
+    let symbol_at = column(&["symbol", "ticker"])
+        .ok_or_else(|| "CSV header must have a 'symbol' or 'ticker' column".to_string())?;
+    let name_at = column(&["name", "company", "security"]);
+    let sector_at = column(&["sector", "gics sector"]);
+    let weight_at = column(&["weight", "weight (%)"]);
+
+    let mut parsed = ParsedConstituents::default();
+    let mut seen = HashSet::new();
+    for (line_no, line) in lines {
+        let fields = split_csv_line(line);
+        let field = |at: Option<usize>| {
+            at.and_then(|i| fields.get(i))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+        };
+
+        let Some(symbol) = field(Some(symbol_at)).map(|s| s.to_ascii_uppercase()) else {
+            parsed.skipped.push(format!("line {}: missing symbol", line_no));
+            continue;
+        };
+        if validate_symbol(&symbol).is_err() {
+            parsed.skipped.push(format!("line {}: invalid symbol '{}'", line_no, symbol));
+            continue;
+        }
+        if !seen.insert(symbol.clone()) {
+            parsed.skipped.push(format!("line {}: duplicate symbol '{}'", line_no, symbol));
+            continue;
+        }
+
+        let weight = match field(weight_at) {
+            Some(raw) => match Decimal::from_str(raw.trim_end_matches('%').trim()) {
+                Ok(weight) if weight >= Decimal::ZERO => Some(weight),
+                _ => {
+                    parsed.skipped.push(format!("line {}: invalid weight '{}'", line_no, raw));
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        parsed.constituents.push(UniverseConstituent {
+            symbol,
+            name: field(name_at),
+            sector: field(sector_at),
+            weight,
+        });
+    }
+
+    if parsed.constituents.len() > MAX_UNIVERSE_MEMBERS {
+        return Err(format!(
+            "Too many constituents: {}. Maximum allowed: {}",
+            parsed.constituents.len(),
+            MAX_UNIVERSE_MEMBERS
+        ));
+    }
+    Ok(parsed)
+}
+
+/// Split one CSV line, honouring double-quoted fields with "" escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
@@ -1,5 +1,40 @@
-use crate::errors::InternalError;
-use crate::config::{MAX_SYMBOL_LENGTH, MAX_SEARCH_QUERY_LENGTH};
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Path, Request},
+    http::request::Parts,
+    Json,
+};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+#[cfg(feature = "analytics")]
+use crate::analytics_sql;
+#[cfg(feature = "analytics")]
+use crate::config::MAX_ANALYTICS_SQL_LENGTH;
+#[cfg(feature = "analytics")]
+use crate::models::AnalyticsSqlRequest;
+use crate::backtest;
+use crate::indicator_expr::Expression;
+use crate::journal;
+use crate::notify::NotificationChannel;
+use crate::ranking;
+use crate::errors::{AppError, InternalError};
+use crate::config::{
+    MAX_ALERT_COOLDOWN_MINUTES, MAX_API_KEY_NAME_LENGTH, MAX_NL_QUERY_LENGTH, MAX_PROJECTION_HORIZON_DAYS,
+    MAX_JOURNAL_RATIONALE_LENGTH, MAX_PROJECTION_SIMULATIONS, MAX_RANKING_DESCRIPTION_LENGTH, MAX_SEARCH_QUERY_LENGTH, MAX_STRATEGY_DESCRIPTION_LENGTH, MAX_STRATEGY_NAME_LENGTH,
+    MAX_SYMBOL_LENGTH,
+};
+use crate::models::{
+    AddHoldingRequest, AddTransactionRequest, CashFlowRequest, CreateApiKeyRequest, CreateDashboardRequest,
+    CreateSignalAlertRequest, CreateStrategyRequest, CreateWebhookRequest, JobRequest, JournalEntryRequest, MuteAlertRequest,
+    NaturalLanguageQuery, PortfolioSettings, ProjectionRequest, RenameSymbolRequest, SaveCustomIndicatorRequest, SaveRankingModelRequest, SetAliasRequest,
+    SetTargetsRequest, StrategyDefinition, TestNotificationRequest, UpdateDashboardRequest, UpdateHoldingRequest,
+    UpdateApiKeyRequest, UpdateSignalAlertRequest, UpdateStrategyRequest,
+    UpdateWebhookRequest,
+};
 
 /// Validate a stock symbol
 pub fn validate_symbol(symbol: &str) -> Result<(), InternalError> {
@@ -25,6 +60,34 @@ pub fn validate_symbol(symbol: &str) -> Result<(), InternalError> {
     Ok(())
 }
 
+/// Symbol taken from the request path, trimmed, uppercased and checked with
+/// [`validate_symbol`]; anything else is rejected with a 400 before the handler runs
+#[derive(Debug, Clone)]
+pub struct ValidSymbol(pub String);
+
+impl ValidSymbol {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let symbol = raw.trim().to_uppercase();
+        match validate_symbol(&symbol) {
+            Ok(()) => Ok(ValidSymbol(symbol)),
+            Err(InternalError::InvalidInput { message }) => Err(message),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ValidSymbol {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(raw) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| AppError::ValidationError(rejection.body_text()))?;
+        ValidSymbol::parse(&raw).map_err(AppError::ValidationError)
+    }
+}
+
 /// Validate and sanitize search query
 pub fn validate_search_query(query: &str) -> Result<String, InternalError> {
     let trimmed = query.trim();
@@ -86,3 +149,419 @@ pub fn validate_limit(limit: Option<i32>, max: i32, default: i32) -> i32 {
     limit.unwrap_or(default).clamp(1, max)
 }
 
+
+/// Problems found in a request body, one message per field
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<(String, String)>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push((field.into(), message.into()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn positive(&mut self, field: &str, value: Decimal) {
+        if value <= Decimal::ZERO {
+            self.add(field, "must be greater than 0");
+        }
+    }
+
+    pub fn non_negative(&mut self, field: &str, value: Decimal) {
+        if value < Decimal::ZERO {
+            self.add(field, "must not be negative");
+        }
+    }
+
+    /// A percentage from 0 up to 100, or below 100 when `inclusive` is false
+    pub fn percent(&mut self, field: &str, value: Decimal, inclusive: bool) {
+        let too_high = if inclusive { value > Decimal::ONE_HUNDRED } else { value >= Decimal::ONE_HUNDRED };
+        if value < Decimal::ZERO || too_high {
+            let upper = if inclusive { "100" } else { "below 100" };
+            self.add(field, format!("must be between 0 and {}", upper));
+        }
+    }
+
+    pub fn symbol(&mut self, field: &str, value: &str) {
+        if let Err(InternalError::InvalidInput { message }) = validate_symbol(&value.trim().to_uppercase()) {
+            self.add(field, message);
+        }
+    }
+
+    pub fn one_of(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value.to_lowercase().as_str()) {
+            self.add(field, format!("must be one of: {}", allowed.join(", ")));
+        }
+    }
+
+    pub fn not_in_future(&mut self, field: &str, date: chrono::NaiveDate) {
+        if date > Utc::now().date_naive() {
+            self.add(field, "cannot be in the future");
+        }
+    }
+
+    pub fn alert_cooldown(&mut self, field: &str, minutes: Option<i64>) {
+        if minutes.is_some_and(|minutes| !(0..=MAX_ALERT_COOLDOWN_MINUTES).contains(&minutes)) {
+            self.add(field, format!("must be between 0 and {} minutes", MAX_ALERT_COOLDOWN_MINUTES));
+        }
+    }
+
+    /// Known notification channel names, each at most once
+    pub fn notification_channels(&mut self, field: &str, channels: &[String]) {
+        let mut seen = Vec::new();
+        for name in channels {
+            match name.trim().parse::<NotificationChannel>() {
+                Ok(channel) if seen.contains(&channel) => self.add(field, format!("lists {} twice", channel.as_str())),
+                Ok(channel) => seen.push(channel),
+                Err(message) => self.add(field, message),
+            }
+        }
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|(field, message)| format!("{} {}", field, message)).collect();
+        write!(f, "Invalid request body: {}", messages.join("; "))
+    }
+}
+
+/// Field checks run on a request body before its handler sees it. Checks that need
+/// normalization or stored state stay in the handler; types without field checks of their
+/// own keep the default.
+pub trait Validate {
+    fn validate(&self, _errors: &mut FieldErrors) {}
+}
+
+fn checked<T: Validate>(value: T) -> Result<T, AppError> {
+    let mut errors = FieldErrors::default();
+    value.validate(&mut errors);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(AppError::ValidationError(errors.to_string()))
+    }
+}
+
+/// JSON body extractor that rejects malformed payloads and failed [`Validate`] checks with a
+/// 400 naming the offending fields, instead of axum's plain-text 415/422 rejections
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|rejection| AppError::ValidationError(rejection.body_text()))?;
+        checked(value).map(ValidJson)
+    }
+}
+
+/// Like [`ValidJson`] for endpoints whose body may be left out; an empty body gives `None`
+pub struct OptionalValidJson<T>(pub Option<T>);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for OptionalValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| AppError::ValidationError(rejection.body_text()))?;
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(OptionalValidJson(None));
+        }
+        let Json(value) = Json::<T>::from_bytes(&bytes)
+            .map_err(|rejection| AppError::ValidationError(rejection.body_text()))?;
+        checked(value).map(|value| OptionalValidJson(Some(value)))
+    }
+}
+
+impl Validate for AddHoldingRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("symbol", &self.symbol);
+        if self.asset_type.as_deref().is_some_and(|kind| kind.trim().is_empty()) {
+            errors.add("asset_type", "must not be empty");
+        }
+        errors.positive("quantity", self.quantity);
+        if let Some(price) = self.purchase_price {
+            errors.positive("purchase_price", price);
+        }
+    }
+}
+
+impl Validate for UpdateHoldingRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(quantity) = self.quantity {
+            errors.positive("quantity", quantity);
+        }
+        if let Some(price) = self.purchase_price {
+            errors.positive("purchase_price", price);
+        }
+        if let Some(weight) = self.target_weight {
+            errors.percent("target_weight", weight, true);
+        }
+        // Zero clears an exit level
+        if let Some(level) = self.stop_loss {
+            errors.non_negative("stop_loss", level);
+        }
+        if let Some(level) = self.target_price {
+            errors.non_negative("target_price", level);
+        }
+        if let Some(percent) = self.trailing_stop_percent {
+            errors.percent("trailing_stop_percent", percent, false);
+        }
+        errors.alert_cooldown("alert_cooldown_minutes", self.alert_cooldown_minutes);
+        if let Some(percent) = self.alert_rearm_percent {
+            errors.percent("alert_rearm_percent", percent, false);
+        }
+        if let Some(channels) = &self.alert_channels {
+            errors.notification_channels("alert_channels", channels);
+        }
+    }
+}
+
+impl Validate for SetTargetsRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        for (symbol, weight) in &self.targets {
+            errors.percent(&format!("targets.{}", symbol), *weight, true);
+        }
+        let total: Decimal = self.targets.values().sum();
+        if total > Decimal::ONE_HUNDRED {
+            errors.add("targets", format!("sum to {}%, which exceeds 100%", total));
+        }
+    }
+}
+
+impl Validate for AddTransactionRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("symbol", &self.symbol);
+        errors.one_of("side", &self.side, &["buy", "sell"]);
+        errors.positive("quantity", self.quantity);
+        errors.non_negative("price", self.price);
+        if let Some(fees) = self.fees {
+            errors.non_negative("fees", fees);
+        }
+        errors.not_in_future("trade_date", self.trade_date);
+    }
+}
+
+impl Validate for JournalEntryRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("symbol", &self.symbol);
+        errors.one_of("side", &self.side, &["buy", "sell"]);
+        errors.positive("quantity", self.quantity);
+        errors.non_negative("price", self.price);
+        if let Some(fees) = self.fees {
+            errors.non_negative("fees", fees);
+        }
+        errors.not_in_future("trade_date", self.trade_date);
+        if self.rationale.as_ref().is_some_and(|r| r.chars().count() > MAX_JOURNAL_RATIONALE_LENGTH) {
+            errors.add("rationale", format!("must be at most {} characters", MAX_JOURNAL_RATIONALE_LENGTH));
+        }
+        if let Err(message) = journal::normalize_tags(&self.tags) {
+            errors.add("tags", message);
+        }
+        if let Err(message) = journal::check_screenshot_urls(&self.screenshot_urls) {
+            errors.add("screenshot_urls", message);
+        }
+    }
+}
+
+impl Validate for CashFlowRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.positive("amount", self.amount);
+        if let Some(date) = self.date {
+            errors.not_in_future("date", date);
+        }
+    }
+}
+
+impl Validate for PortfolioSettings {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.one_of("lot_mode", &self.lot_mode, &["merge", "separate"]);
+    }
+}
+
+impl Validate for ProjectionRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.horizon_days.is_some_and(|days| days == 0 || days > MAX_PROJECTION_HORIZON_DAYS) {
+            errors.add("horizon_days", format!("must be between 1 and {}", MAX_PROJECTION_HORIZON_DAYS));
+        }
+        if self.simulations.is_some_and(|n| n == 0 || n > MAX_PROJECTION_SIMULATIONS) {
+            errors.add("simulations", format!("must be between 1 and {}", MAX_PROJECTION_SIMULATIONS));
+        }
+        if self.lookback_days == Some(0) {
+            errors.add("lookback_days", "must be greater than 0");
+        }
+    }
+}
+
+impl Validate for MuteAlertRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        let now = Utc::now();
+        let max = now + chrono::Duration::minutes(MAX_ALERT_COOLDOWN_MINUTES);
+        match (self.minutes, self.until) {
+            (Some(minutes), None) => {
+                if !(1..=MAX_ALERT_COOLDOWN_MINUTES).contains(&minutes) {
+                    errors.add("minutes", format!("must be between 1 and {}", MAX_ALERT_COOLDOWN_MINUTES));
+                }
+            }
+            (None, Some(until)) if until <= now => errors.add("until", "must be in the future"),
+            (None, Some(until)) if until > max => errors.add("until", "must be within a year"),
+            (None, Some(_)) => {}
+            _ => errors.add("minutes", "or until must be given, but not both"),
+        }
+    }
+}
+
+impl Validate for CreateSignalAlertRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("symbol", &self.symbol);
+        errors.alert_cooldown("cooldown_minutes", self.cooldown_minutes);
+        if let Some(channels) = &self.channels {
+            errors.notification_channels("channels", channels);
+        }
+    }
+}
+
+impl Validate for UpdateSignalAlertRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.notification_channels("channels", &self.channels);
+    }
+}
+
+impl Validate for SetAliasRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("symbol", &self.symbol);
+    }
+}
+
+impl Validate for SaveCustomIndicatorRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Err(message) = Expression::parse(&self.expression) {
+            errors.add("expression", message);
+        }
+    }
+}
+
+fn validate_strategy_fields(
+    errors: &mut FieldErrors,
+    name: Option<&str>,
+    description: Option<&str>,
+    definition: Option<&StrategyDefinition>,
+) {
+    if let Some(name) = name {
+        let length = name.trim().chars().count();
+        if length == 0 || length > MAX_STRATEGY_NAME_LENGTH {
+            errors.add("name", format!("must be between 1 and {} characters", MAX_STRATEGY_NAME_LENGTH));
+        }
+    }
+    if description.is_some_and(|description| description.chars().count() > MAX_STRATEGY_DESCRIPTION_LENGTH) {
+        errors.add("description", format!("must be at most {} characters", MAX_STRATEGY_DESCRIPTION_LENGTH));
+    }
+    if let Some(Err(message)) = definition.map(backtest::validate) {
+        errors.add("definition", message);
+    }
+}
+
+impl Validate for CreateStrategyRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_strategy_fields(errors, Some(&self.name), self.description.as_deref(), Some(&self.definition));
+    }
+}
+
+impl Validate for UpdateStrategyRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_strategy_fields(errors, self.name.as_deref(), self.description.as_deref(), self.definition.as_ref());
+    }
+}
+
+impl Validate for SaveRankingModelRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Err(message) = ranking::validate(&self.weights) {
+            errors.add("weights", message);
+        }
+        if self.description.as_deref().is_some_and(|d| d.chars().count() > MAX_RANKING_DESCRIPTION_LENGTH) {
+            errors.add("description", format!("must be at most {} characters", MAX_RANKING_DESCRIPTION_LENGTH));
+        }
+    }
+}
+
+fn validate_api_key_name(errors: &mut FieldErrors, name: &str) {
+    let length = name.trim().chars().count();
+    if length == 0 || length > MAX_API_KEY_NAME_LENGTH {
+        errors.add("name", format!("must be between 1 and {} characters", MAX_API_KEY_NAME_LENGTH));
+    }
+}
+
+impl Validate for CreateApiKeyRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_api_key_name(errors, &self.name);
+        if self.daily_quota == Some(0) {
+            errors.add("daily_quota", "must be positive; leave it out for no quota");
+        }
+        if self.monthly_quota == Some(0) {
+            errors.add("monthly_quota", "must be positive; leave it out for no quota");
+        }
+    }
+}
+
+impl Validate for UpdateApiKeyRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(name) = &self.name {
+            validate_api_key_name(errors, name);
+        }
+    }
+}
+
+impl Validate for RenameSymbolRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.symbol("to", &self.to);
+    }
+}
+
+impl Validate for NaturalLanguageQuery {
+    fn validate(&self, errors: &mut FieldErrors) {
+        let length = self.query.trim().len();
+        if length == 0 || length > MAX_NL_QUERY_LENGTH {
+            errors.add("query", format!("must be between 1 and {} characters", MAX_NL_QUERY_LENGTH));
+        }
+    }
+}
+
+#[cfg(feature = "analytics")]
+impl Validate for AnalyticsSqlRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.sql.len() > MAX_ANALYTICS_SQL_LENGTH {
+            errors.add("sql", format!("must be at most {} characters", MAX_ANALYTICS_SQL_LENGTH));
+        } else if let Err(message) = analytics_sql::check_statement(&self.sql) {
+            errors.add("sql", message);
+        }
+        if self.limit == Some(0) {
+            errors.add("limit", "must be greater than 0");
+        }
+    }
+}
+
+// Checked while their symbols, events, channels or widgets are normalized in the handler
+impl Validate for JobRequest {}
+impl Validate for CreateWebhookRequest {}
+impl Validate for UpdateWebhookRequest {}
+impl Validate for TestNotificationRequest {}
+impl Validate for CreateDashboardRequest {}
+impl Validate for UpdateDashboardRequest {}
+// Tool arguments are checked against each tool's own schema
+impl Validate for serde_json::Value {}
@@ -3,7 +3,11 @@ use askama::Template;
 #[cfg(feature = "web-ui")]
 use askama_axum::IntoResponse;
 #[cfg(feature = "web-ui")]
-use axum::extract::Query;
+use axum::extract::{Path, Query, State};
+#[cfg(feature = "web-ui")]
+use axum::http::StatusCode;
+#[cfg(feature = "web-ui")]
+use rust_decimal::Decimal;
 #[cfg(feature = "web-ui")]
 use axum::{
     body::Body,
@@ -12,6 +16,14 @@ use axum::{
 };
 #[cfg(feature = "web-ui")]
 use serde::Deserialize;
+#[cfg(feature = "web-ui")]
+use crate::config::WebUiConfig;
+#[cfg(feature = "web-ui")]
+use crate::errors::ApiError;
+#[cfg(feature = "web-ui")]
+use crate::handlers::AppState;
+#[cfg(feature = "web-ui")]
+use crate::models::CompanyProfile;
 
 // Asset version for cache busting
 // Uses Cargo package version by default, but can be overridden via ASSET_VERSION env var at build time
@@ -27,27 +39,56 @@ fn get_asset_version() -> &'static str {
     option_env!("ASSET_VERSION").unwrap_or(ASSET_VERSION)
 }
 
-// Base template context with asset versioning
+/// Pinned third-party libraries: CDN URL and path under `/static/vendor`.
+/// `scripts/fetch-web-assets.sh` downloads the same versions for local serving.
 #[cfg(feature = "web-ui")]
-pub struct BaseTemplateContext {
-    pub asset_version: &'static str,
+const VENDOR_ASSETS: [(&str, &str); 4] = [
+    ("https://cdnjs.cloudflare.com/ajax/libs/tailwindcss/2.2.19/tailwind.min.css", "/static/vendor/tailwind.min.css"),
+    ("https://cdnjs.cloudflare.com/ajax/libs/font-awesome/6.0.0/css/all.min.css", "/static/vendor/fontawesome/css/all.min.css"),
+    ("https://cdnjs.cloudflare.com/ajax/libs/Chart.js/3.9.1/chart.min.js", "/static/vendor/chart.min.js"),
+    ("https://unpkg.com/@airgap/beacon-sdk@3.1.2/dist/walletbeacon.min.js", "/static/vendor/walletbeacon.min.js"),
+];
+
+/// Where templates load CSS/JS libraries from
+#[cfg(feature = "web-ui")]
+#[derive(Debug, Clone, Copy)]
+pub struct AssetUrls {
+    pub tailwind_css: &'static str,
+    pub font_awesome_css: &'static str,
+    pub chart_js: &'static str,
+    pub beacon_js: &'static str,
 }
 
 #[cfg(feature = "web-ui")]
-impl Default for BaseTemplateContext {
-    fn default() -> Self {
+impl AssetUrls {
+    pub fn for_config(config: &WebUiConfig) -> Self {
+        let pick = |(cdn, local): (&'static str, &'static str)| if config.local_assets { local } else { cdn };
         Self {
-            asset_version: get_asset_version(),
+            tailwind_css: pick(VENDOR_ASSETS[0]),
+            font_awesome_css: pick(VENDOR_ASSETS[1]),
+            chart_js: pick(VENDOR_ASSETS[2]),
+            beacon_js: pick(VENDOR_ASSETS[3]),
         }
     }
 }
 
+/// Local vendor files that are missing from `config.static_dir`
+#[cfg(feature = "web-ui")]
+pub fn missing_vendor_assets(config: &WebUiConfig) -> Vec<std::path::PathBuf> {
+    VENDOR_ASSETS
+        .iter()
+        .map(|(_, local)| config.static_dir.join(local.trim_start_matches("/static/")))
+        .filter(|path| !path.is_file())
+        .collect()
+}
+
 #[cfg(feature = "web-ui")]
 #[derive(Template)]
 #[template(path = "dashboard.html")]
 pub struct DashboardTemplate {
     #[template(escape = "none")]
     pub asset_version: &'static str,
+    pub assets: AssetUrls,
 }
 
 #[cfg(feature = "web-ui")]
@@ -56,6 +97,7 @@ pub struct DashboardTemplate {
 pub struct SearchTemplate {
     #[template(escape = "none")]
     pub asset_version: &'static str,
+    pub assets: AssetUrls,
 }
 
 #[cfg(feature = "web-ui")]
@@ -65,6 +107,16 @@ pub struct AnalyticsTemplate {
     pub symbol: Option<String>,
     #[template(escape = "none")]
     pub asset_version: &'static str,
+    pub assets: AssetUrls,
+}
+
+#[cfg(feature = "web-ui")]
+#[derive(Template)]
+#[template(path = "compare.html")]
+pub struct CompareTemplate {
+    #[template(escape = "none")]
+    pub asset_version: &'static str,
+    pub assets: AssetUrls,
 }
 
 #[cfg(feature = "web-ui")]
@@ -73,6 +125,9 @@ pub struct AnalyticsTemplate {
 pub struct LoginTemplate {
     #[template(escape = "none")]
     pub asset_version: &'static str,
+    pub assets: AssetUrls,
+    /// Provider label for the single sign-on button, when OIDC login is enabled
+    pub oidc_provider: Option<&'static str>,
 }
 
 #[cfg(feature = "web-ui")]
@@ -81,6 +136,74 @@ pub struct LoginTemplate {
 pub struct BackupTemplate {
     #[template(escape = "none")]
     pub asset_version: &'static str,
+    pub assets: AssetUrls,
+}
+
+/// Everything the symbol page renders server-side; the chart is drawn client-side
+#[cfg(feature = "web-ui")]
+#[derive(Template)]
+#[template(path = "symbol.html")]
+pub struct SymbolTemplate {
+    #[template(escape = "none")]
+    pub asset_version: &'static str,
+    pub assets: AssetUrls,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    pub currency: Option<String>,
+    pub delisted: bool,
+    pub quote: Option<QuoteView>,
+    /// Label and formatted value pairs
+    pub fundamentals: Vec<(&'static str, String)>,
+    pub profile: Option<CompanyProfile>,
+    pub ownership: Vec<(&'static str, String)>,
+    pub top_holders: Vec<HolderView>,
+    /// Why the page could not be filled in, if it could not
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "web-ui")]
+pub struct QuoteView {
+    pub price: String,
+    pub change: String,
+    pub change_percent: String,
+    pub rising: bool,
+    pub market_time: String,
+    pub trading_session: String,
+}
+
+#[cfg(feature = "web-ui")]
+pub struct HolderView {
+    pub name: String,
+    pub percent_held: String,
+    pub shares: String,
+}
+
+#[cfg(feature = "web-ui")]
+#[derive(Template)]
+#[template(path = "admin.html")]
+pub struct AdminTemplate {
+    #[template(escape = "none")]
+    pub asset_version: &'static str,
+    pub assets: AssetUrls,
+}
+
+#[cfg(feature = "web-ui")]
+#[derive(Template)]
+#[template(path = "jobs.html")]
+pub struct JobsTemplate {
+    #[template(escape = "none")]
+    pub asset_version: &'static str,
+    pub assets: AssetUrls,
+}
+
+#[cfg(feature = "web-ui")]
+#[derive(Template)]
+#[template(path = "journal.html")]
+pub struct JournalTemplate {
+    #[template(escape = "none")]
+    pub asset_version: &'static str,
+    pub assets: AssetUrls,
 }
 
 #[cfg(feature = "web-ui")]
@@ -90,56 +213,241 @@ pub struct AnalyticsQuery {
 }
 
 #[cfg(feature = "web-ui")]
-pub async fn dashboard() -> impl IntoResponse {
+pub async fn dashboard(State(app_state): State<AppState>) -> impl IntoResponse {
     DashboardTemplate {
         asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
     }
 }
 
 #[cfg(feature = "web-ui")]
-pub async fn search() -> impl IntoResponse {
+pub async fn search(State(app_state): State<AppState>) -> impl IntoResponse {
     SearchTemplate {
         asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
     }
 }
 
 #[cfg(feature = "web-ui")]
-pub async fn analytics(Query(params): Query<AnalyticsQuery>) -> impl IntoResponse {
+pub async fn analytics(
+    State(app_state): State<AppState>,
+    Query(params): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
     AnalyticsTemplate {
         symbol: params.symbol,
         asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
     }
 }
 
+/// Multi-symbol comparison; symbols and period are read from the query string client-side
 #[cfg(feature = "web-ui")]
-pub async fn login() -> impl IntoResponse {
+pub async fn compare(State(app_state): State<AppState>) -> impl IntoResponse {
+    CompareTemplate {
+        asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+    }
+}
+
+#[cfg(feature = "web-ui")]
+pub async fn login(State(app_state): State<AppState>) -> impl IntoResponse {
     LoginTemplate {
         asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+        oidc_provider: app_state.config.auth.oidc.as_ref().map(|o| o.provider.display_name()),
     }
 }
 
 #[cfg(feature = "web-ui")]
-pub async fn backup() -> impl IntoResponse {
+pub async fn backup(State(app_state): State<AppState>) -> impl IntoResponse {
     BackupTemplate {
         asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+    }
+}
+
+/// Symbols shown in the top holders table
+#[cfg(feature = "web-ui")]
+const TOP_HOLDERS: usize = 5;
+
+/// One page per symbol: quote, chart, fundamentals, profile and ownership
+#[cfg(feature = "web-ui")]
+pub async fn symbol(State(app_state): State<AppState>, Path(symbol): Path<String>) -> impl IntoResponse {
+    let service = &app_state.service;
+    let mut page = SymbolTemplate {
+        asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+        symbol: symbol.trim().to_uppercase(),
+        name: None,
+        exchange: None,
+        currency: None,
+        delisted: false,
+        quote: None,
+        fundamentals: Vec::new(),
+        profile: None,
+        ownership: Vec::new(),
+        top_holders: Vec::new(),
+        error: None,
+    };
+    if crate::validation::validate_symbol(&page.symbol).is_err() {
+        page.error = Some(format!("'{}' is not a valid symbol", page.symbol));
+        return (StatusCode::NOT_FOUND, page);
+    }
+    page.symbol = service.resolve_symbol(&page.symbol).await;
+    let symbol = page.symbol.clone();
+
+    // The overview fetches what is missing, so the other lookups can rely on stored data
+    let overview = match service.get_symbol_overview(&symbol).await {
+        Ok(overview) => overview,
+        Err(e) => {
+            tracing::warn!("Failed to load symbol page for {}: {}", symbol, e);
+            page.error = Some(ApiError::from_service(&e).message.into_owned());
+            return (StatusCode::OK, page);
+        }
+    };
+    let year_ago = (chrono::Utc::now() - chrono::Duration::days(365)).date_naive();
+    let (stored, year, dividends, holders) = tokio::join!(
+        service.db.get_symbol(&symbol),
+        service.get_historical_data(&symbol, None, None, Some("1d"), Some(252)),
+        service.db.get_dividends(&symbol, year_ago),
+        service.get_holders(&symbol),
+    );
+
+    if let Some(stored) = stored.ok().flatten() {
+        page.name = stored.name;
+        page.exchange = stored.exchange;
+        page.currency = stored.currency;
+        page.delisted = stored.status == crate::models::SymbolStatus::Delisted;
+        if let Some(market_cap) = stored.market_cap {
+            page.fundamentals.push(("Market cap", format_large(market_cap)));
+        }
+    }
+    if page.name.is_none() {
+        page.name = overview.profile.as_ref().and_then(|p| p.company_name.clone());
+    }
+
+    page.quote = overview.latest_quote.map(|quote| {
+        let change = quote.change.unwrap_or_default();
+        QuoteView {
+            price: format_price(quote.price),
+            change: format!("{:+.2}", change),
+            change_percent: format!("{:+.2}%", quote.change_percent.unwrap_or_default()),
+            rising: change >= Decimal::ZERO,
+            market_time: quote.market_time.format("%Y-%m-%d %H:%M UTC").to_string(),
+            trading_session: quote.trading_session,
+        }
+    });
+
+    if let Some(year) = year.ok().filter(|candles| !candles.is_empty()) {
+        let high = year.iter().map(|c| c.high).max().unwrap_or_default();
+        let low = year.iter().map(|c| c.low).min().unwrap_or_default();
+        page.fundamentals.push(("52-week range", format!("{} – {}", format_price(low), format_price(high))));
+    }
+    if let Some(change) = overview.price_change_30d_percent {
+        page.fundamentals.push(("30-day change", format!("{:+.2}%", change)));
+    }
+    if let Some(volume) = overview.avg_volume_30d {
+        page.fundamentals.push(("30-day average volume", format_large(Decimal::from(volume))));
+    }
+    if let Some(dividends) = dividends.ok().filter(|dividends| !dividends.is_empty()) {
+        let total: Decimal = dividends.iter().map(|d| d.amount).sum();
+        page.fundamentals.push(("Dividends, last 12 months", format_price(total)));
+    }
+
+    // Ownership is optional; missing filings or an exhausted quota just hide the section
+    if let Ok(Some(holders)) = holders {
+        let breakdown = holders.breakdown;
+        let percentages = [
+            ("Held by insiders", breakdown.insiders_percent),
+            ("Held by institutions", breakdown.institutions_percent),
+            ("Float held by institutions", breakdown.institutions_float_percent),
+        ];
+        for (label, percent) in percentages {
+            if let Some(percent) = percent {
+                page.ownership.push((label, format!("{:.2}%", percent)));
+            }
+        }
+        if let Some(count) = breakdown.institutions_count {
+            page.ownership.push(("Institutions", count.to_string()));
+        }
+        page.top_holders = holders
+            .institutions
+            .into_iter()
+            .take(TOP_HOLDERS)
+            .map(|holder| HolderView {
+                name: holder.name,
+                percent_held: holder.percent_held.map(|p| format!("{:.2}%", p)).unwrap_or_default(),
+                shares: holder.shares.map(|s| format_large(Decimal::from(s))).unwrap_or_default(),
+            })
+            .collect();
+    }
+    page.profile = overview.profile;
+
+    (StatusCode::OK, page)
+}
+
+#[cfg(feature = "web-ui")]
+fn format_price(value: Decimal) -> String {
+    format!("{:.2}", value.round_dp(2))
+}
+
+/// Market caps and volumes, e.g. `2.95T` or `48.21M`
+#[cfg(feature = "web-ui")]
+fn format_large(value: Decimal) -> String {
+    use rust_decimal::prelude::ToPrimitive;
+
+    let value = value.to_f64().unwrap_or(0.0);
+    match value.abs() {
+        v if v >= 1e12 => format!("{:.2}T", value / 1e12),
+        v if v >= 1e9 => format!("{:.2}B", value / 1e9),
+        v if v >= 1e6 => format!("{:.2}M", value / 1e6),
+        v if v >= 1e3 => format!("{:.2}K", value / 1e3),
+        _ => format!("{:.0}", value),
+    }
+}
+
+/// API keys, their usage and admin sessions; managed client-side through the key and session APIs
+#[cfg(feature = "web-ui")]
+pub async fn admin(State(app_state): State<AppState>) -> impl IntoResponse {
+    AdminTemplate {
+        asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+    }
+}
+
+/// Background jobs and object storage exports; the page polls the API client-side
+#[cfg(feature = "web-ui")]
+pub async fn jobs(State(app_state): State<AppState>) -> impl IntoResponse {
+    JobsTemplate {
+        asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
+    }
+}
+
+/// Trade journal; entries are loaded and saved client-side through the journal API
+#[cfg(feature = "web-ui")]
+pub async fn journal(State(app_state): State<AppState>) -> impl IntoResponse {
+    JournalTemplate {
+        asset_version: get_asset_version(),
+        assets: AssetUrls::for_config(&app_state.config.web_ui),
     }
 }
 
 /// Serve favicon directly for better browser compatibility
 /// Browsers often request /favicon.ico or /favicon.svg directly
 #[cfg(feature = "web-ui")]
-pub async fn favicon() -> impl IntoResponse {
+pub async fn favicon(State(app_state): State<AppState>) -> impl IntoResponse {
     use axum::{
         http::{header, StatusCode},
         response::Response,
     };
     
-    match tokio::fs::read_to_string("static/favicon.svg").await {
+    match tokio::fs::read_to_string(app_state.config.web_ui.static_dir.join("favicon.svg")).await {
         Ok(content) => {
             Response::builder()
                 .status(StatusCode::OK)
                 .header(header::CONTENT_TYPE, "image/svg+xml")
-                .header(header::CACHE_CONTROL, format!("public, max-age=31536000, immutable"))
+                .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
                 .header(header::ETAG, format!("\"{}\"", get_asset_version()))
                 .body(axum::body::Body::from(content))
                 .unwrap()
@@ -180,7 +488,7 @@ pub async fn cache_headers_middleware(
                     format!("\"{}\"", get_asset_version()).parse().unwrap(),
                 );
             } else if content_type_str.contains("text/css")
-                || content_type_str.contains("application/javascript")
+                || content_type_str.contains("javascript")
                 || content_type_str.contains("image/")
                 || content_type_str.contains("font/")
                 || content_type_str.contains("image/svg+xml")
@@ -197,22 +505,3 @@ pub async fn cache_headers_middleware(
     
     response
 }
-
-// Placeholder functions for when web-ui feature is disabled
-#[cfg(not(feature = "web-ui"))]
-#[allow(dead_code)]
-pub async fn dashboard() -> Result<&'static str, axum::http::StatusCode> {
-    Err(axum::http::StatusCode::NOT_FOUND)
-}
-
-#[cfg(not(feature = "web-ui"))]
-#[allow(dead_code)]
-pub async fn search() -> Result<&'static str, axum::http::StatusCode> {
-    Err(axum::http::StatusCode::NOT_FOUND)
-}
-
-#[cfg(not(feature = "web-ui"))]
-#[allow(dead_code)]
-pub async fn analytics() -> Result<&'static str, axum::http::StatusCode> {
-    Err(axum::http::StatusCode::NOT_FOUND)
-} 
\ No newline at end of file
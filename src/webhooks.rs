@@ -0,0 +1,204 @@
+//! Outbound webhook notifications.
+//!
+//! Events are POSTed as JSON to every active webhook subscribed to them. The raw body is
+//! signed with HMAC-SHA256 using the webhook's secret and sent as
+//! `X-Mango-Signature: sha256=<hex>`. Failed attempts are retried with exponential backoff
+//! and the final outcome of every delivery is kept in the delivery log. Emitting never
+//! blocks or fails the caller.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::models::{Webhook, WebhookDelivery};
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    PriceAlertFired,
+    SignalAlertFired,
+    BackfillCompleted,
+    DataQualityIssue,
+    PortfolioSnapshot,
+    AnomalyDetected,
+    /// Sent on demand to a single webhook to check the endpoint
+    Test,
+}
+
+impl WebhookEvent {
+    /// Events a webhook can subscribe to
+    pub const SUBSCRIBABLE: [WebhookEvent; 6] = [
+        WebhookEvent::PriceAlertFired,
+        WebhookEvent::SignalAlertFired,
+        WebhookEvent::BackfillCompleted,
+        WebhookEvent::DataQualityIssue,
+        WebhookEvent::PortfolioSnapshot,
+        WebhookEvent::AnomalyDetected,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PriceAlertFired => "price_alert.fired",
+            WebhookEvent::SignalAlertFired => "signal_alert.fired",
+            WebhookEvent::BackfillCompleted => "backfill.completed",
+            WebhookEvent::DataQualityIssue => "data_quality.issue",
+            WebhookEvent::PortfolioSnapshot => "portfolio.snapshot",
+            WebhookEvent::AnomalyDetected => "anomaly.detected",
+            WebhookEvent::Test => "webhook.test",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::SUBSCRIBABLE
+            .into_iter()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::SUBSCRIBABLE.iter().map(|e| e.as_str()).collect();
+                format!("Unknown webhook event '{}'. Use one of: {}", s, known.join(", "))
+            })
+    }
+}
+
+/// Random signing secret, 32 bytes hex-encoded
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Value of the `X-Mango-Signature` header for `body`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("mango-data-service/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("reqwest client with static configuration")
+    })
+}
+
+/// Deliver `data` to every active webhook subscribed to `event` in the background
+pub fn emit(db: Arc<Database>, event: WebhookEvent, data: serde_json::Value) {
+    tokio::spawn(async move {
+        let webhooks = match db.get_webhooks_for_event(event.as_str()).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                error!("Failed to load webhooks for {}: {}", event.as_str(), e);
+                return;
+            }
+        };
+
+        let deliveries = webhooks
+            .iter()
+            .map(|webhook| deliver(&db, webhook, event, data.clone()));
+        futures::future::join_all(deliveries).await;
+    });
+}
+
+/// Send one event to one webhook, retrying transient failures, and log the outcome
+pub async fn deliver(
+    db: &Database,
+    webhook: &Webhook,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) -> WebhookDelivery {
+    let delivery_id = Uuid::new_v4();
+    let created_at = Utc::now();
+    let body = serde_json::json!({
+        "id": delivery_id,
+        "event": event.as_str(),
+        "created_at": created_at,
+        "data": data,
+    })
+    .to_string();
+    let signature = sign(&webhook.secret, body.as_bytes());
+
+    let mut attempts = 0;
+    let mut status_code = None;
+    let mut last_error = None;
+    let mut success = false;
+    while attempts < MAX_ATTEMPTS {
+        if attempts > 0 {
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempts - 1)).await;
+        }
+        attempts += 1;
+
+        let result = http_client()
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Mango-Event", event.as_str())
+            .header("X-Mango-Delivery", delivery_id.to_string())
+            .header("X-Mango-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                status_code = Some(status.as_u16());
+                if status.is_success() {
+                    success = true;
+                    last_error = None;
+                    break;
+                }
+                last_error = Some(format!("HTTP {}", status));
+                // Other client errors will not go away on retry
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    break;
+                }
+            }
+            Err(e) => {
+                status_code = None;
+                last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    if let Some(e) = &last_error {
+        warn!(
+            "Webhook {} delivery of {} failed after {} attempts: {}",
+            webhook.id,
+            event.as_str(),
+            attempts,
+            e
+        );
+    }
+
+    let delivery = WebhookDelivery {
+        id: delivery_id,
+        webhook_id: webhook.id,
+        event: event.as_str().to_string(),
+        payload: body,
+        attempts,
+        status_code,
+        success,
+        error: last_error,
+        created_at,
+    };
+    if let Err(e) = db.insert_webhook_delivery(&delivery).await {
+        error!("Failed to record webhook delivery {}: {}", delivery.id, e);
+    }
+    delivery
+}
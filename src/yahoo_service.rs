@@ -1,43 +1,43 @@
-use crate::config::Config;
+use crate::cache::{CacheStats, WeightedCache};
+use crate::cache_bus::{CacheBus, CacheEvent, CacheScope, DataKind};
+use crate::config::{
+    AlertConfig, AnomalyConfig, CacheConfig, Config, NotificationConfig, ANOMALY_RECENT_BARS, DELISTING_EMPTY_FETCHES, DELISTING_MIN_DAYS, FORECAST_LOOKBACK_BARS, FX_RATE_MAX_AGE_DAYS, LOGIN_FAILURE_WINDOW, MACRO_HISTORY_DAYS, MACRO_REFRESH_INTERVAL, MAX_BULK_CONCURRENCY, MAX_HISTORICAL_LIMIT, MAX_LISTINGS, MAX_LOGIN_FAILURES, MIN_BETA_OBSERVATIONS, MIN_FORECAST_OBSERVATIONS, MIN_PROJECTION_OBSERVATIONS, TRADING_DAYS_PER_YEAR, UNCLASSIFIED_SECTOR,
+};
 use crate::database::Database;
+use crate::event_stream::{EventPublisher, StreamEvent};
+use crate::forecast::{self, ForecastMethod};
+use crate::fx;
+use crate::identifiers::{self, IdentifierKind};
+use crate::indicator_expr::Expression;
+use crate::indicators::SignalCondition;
+use crate::market_calendar;
 use crate::models::*;
+use crate::notify;
+use crate::object_export::ObjectExporter;
+use crate::projection::{self, ProjectionMethod};
+use crate::quota::{QuotaBudget, UpstreamFeature};
+use crate::provider::{self as market_data, MarketDataProvider, SymbolMatch, UpstreamError};
+use crate::latency::{LatencyTracker, TimedProvider};
+use crate::macro_series::{self, MacroSeries};
+use crate::risk;
+use crate::stats_history::StatsRing;
+use crate::webhooks::{self, WebhookEvent};
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
-use dashmap::DashMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::{self, StreamExt};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tokio::sync::Mutex;
 
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
-use yahoo_finance_api::YahooConnector;
-
-#[derive(Debug, Clone)]
-pub struct CachedData<T> {
-    pub data: T,
-    pub timestamp: Instant,
-    pub ttl: Duration,
-}
-
-impl<T> CachedData<T> {
-    pub fn new(data: T, ttl: Duration) -> Self {
-        Self {
-            data,
-            timestamp: Instant::now(),
-            ttl,
-        }
-    }
-
-    pub fn is_expired(&self) -> bool {
-        self.timestamp.elapsed() > self.ttl
-    }
-}
 
 #[derive(Debug, thiserror::Error)]
 pub enum YahooServiceError {
@@ -45,97 +45,159 @@ pub enum YahooServiceError {
     DatabaseError(#[from] anyhow::Error),
     #[error("Rate limit exceeded")]
     RateLimitExceeded,
+    #[error(transparent)]
+    Upstream(#[from] UpstreamError),
+    #[error("Daily budget of provider calls for {} used up", .feature.as_str())]
+    QuotaExhausted {
+        feature: UpstreamFeature,
+        retry_after: Duration,
+    },
+    #[error("Nothing stored, and this read-only instance does not fetch data it would have to store")]
+    ReadOnly,
+}
+
+tokio::task_local! {
+    /// Set while a bulk operation fetches, so its provider calls count against the bulk share
+    /// of the Yahoo window and never hold up interactive calls waiting for a slot
+    static BULK_OPERATION: ();
+}
+
+/// Whether the current task is part of a bulk operation
+fn in_bulk_operation() -> bool {
+    BULK_OPERATION.try_with(|_| ()).is_ok()
+}
+
+/// Log that `what` is answered from storage because `err` ruled out a provider call; expected
+/// on every such request of a read-only instance, so only logged at debug level there
+fn log_stored_fallback(err: &YahooServiceError, what: std::fmt::Arguments) {
+    if matches!(err, YahooServiceError::ReadOnly) {
+        debug!("Serving stored {} on a read-only instance", what);
+    } else {
+        warn!("{}; serving stored {}", err, what);
+    }
 }
 
 pub struct YahooFinanceService {
     pub db: Arc<Database>,
-    provider: Arc<Mutex<YahooConnector>>, // Wrap in Arc<Mutex> for sharing across tasks
-    // Concurrent cache using DashMap for better performance with size limits
-    historical_cache: Arc<DashMap<String, CachedData<Vec<HistoricalPrice>>>>,
-    quote_cache: Arc<DashMap<String, CachedData<RealTimeQuote>>>,
-    profile_cache: Arc<DashMap<String, CachedData<Option<CompanyProfile>>>>,
+    provider: Arc<dyn MarketDataProvider>,
+    // In-memory caches bounded by approximate bytes
+    historical_cache: WeightedCache<Vec<HistoricalPrice>>,
+    quote_cache: WeightedCache<RealTimeQuote>,
+    profile_cache: WeightedCache<Option<CompanyProfile>>,
+    suggest_cache: WeightedCache<Vec<SymbolSuggestion>>,
+    holders_cache: WeightedCache<Option<SymbolHolders>>,
+    etf_cache: WeightedCache<Option<EtfComposition>>,
+    resolve_cache: WeightedCache<SymbolResolution>,
+    fx_cache: WeightedCache<Decimal>,
+    listings_cache: WeightedCache<Option<SymbolListings>>,
+    forecast_cache: WeightedCache<Option<Forecast>>,
+    /// Lifetimes of cached quotes, candles and profiles
+    cache_ttls: CacheConfig,
+    /// Exchange suffixes tried for bare tickers, `US` meaning none
+    exchange_preference: Vec<String>,
+    /// OpenFIGI fallback for identifier lookups, with its optional API key
+    openfigi: Option<Option<String>>,
+    /// FRED API key, the source of macro series when set
+    fred_api_key: Option<String>,
+    /// Serve synthetic macro series, as the mock provider does for market data
+    mock_macro: bool,
+    /// When each macro series was last topped up from its source; held while fetching so
+    /// concurrent callers wait for one refresh
+    macro_refreshed: Mutex<HashMap<&'static str, Instant>>,
+    /// Default cooldown and re-arm distance of price and signal alerts
+    alerts: AlertConfig,
     // Simple rate limiting using timestamps
     api_rate_limits: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
+    // Failed password logins per client or username, in the same sliding-window form
+    login_failures: Mutex<HashMap<String, Vec<Instant>>>,
     yahoo_api_calls: Arc<Mutex<Vec<Instant>>>,
+    // The subset of those calls made by bulk operations
+    yahoo_bulk_calls: Mutex<Vec<Instant>>,
+    // Calls waiting for a Yahoo slot take turns here, in arrival order
+    yahoo_queue: Mutex<()>,
+    // Provider calls made today per feature against the daily budget
+    quota: QuotaBudget,
+    // Provider requests made and refused by the rate limit since startup
+    yahoo_calls_total: AtomicU64,
+    yahoo_rate_limited_total: AtomicU64,
+    /// Recent samples of cache and provider activity
+    pub stats_history: StatsRing,
+    /// Route and provider call latency since startup
+    pub latency: Arc<LatencyTracker>,
+    /// Cache events shared with other instances
+    pub cache_bus: CacheBus,
+    /// Ingested-data events for downstream consumers
+    pub events: EventPublisher,
+    /// Archival of candles and quotes to object storage
+    pub exports: ObjectExporter,
+    // Serve stored data only, never fetching what would have to be stored (READ_ONLY)
+    read_only: bool,
     // Configuration
     config: RateLimitConfig,
-    // Cache configuration
-    cache_config: CacheConfig,
-    // Semaphore for controlling bulk operation concurrency
+    // Bulkhead shared by all bulk operations, so together they cannot tie up more provider
+    // slots and database connections than `BULK_MAX_CONCURRENCY`
     bulk_semaphore: Arc<Semaphore>,
 }
 
-#[derive(Debug, Clone)]
-struct CacheConfig {
-    max_size_historical: usize,
-    max_size_quotes: usize,
-    max_size_profiles: usize,
-}
-
 impl YahooFinanceService {
     pub fn new(db: Arc<Database>, config: Config) -> Result<Self> {
-        let provider = YahooConnector::new()?;
+        let provider = market_data::from_config(&config.market_data)?;
+        Ok(Self::with_provider(db, config, provider))
+    }
+
+    /// Service backed by an explicit market-data provider instead of the configured one
+    pub fn with_provider(
+        db: Arc<Database>,
+        config: Config,
+        provider: Arc<dyn MarketDataProvider>,
+    ) -> Self {
         let rate_limit_config = RateLimitConfig {
             requests_per_minute: config.rate_limiting.api_requests_per_minute,
             yahoo_api_requests_per_minute: config.rate_limiting.yahoo_api_requests_per_minute,
+            yahoo_api_max_wait: config.rate_limiting.yahoo_api_max_wait,
+            bulk_yahoo_requests_per_minute: config.rate_limiting.bulk_yahoo_requests_per_minute,
         };
-        
-        let cache_config = CacheConfig {
-            max_size_historical: config.cache.max_size_historical,
-            max_size_quotes: config.cache.max_size_quotes,
-            max_size_profiles: config.cache.max_size_profiles,
-        };
+        let latency = Arc::new(LatencyTracker::new(config.latency.slow_upstream_call));
+        let provider = Arc::new(TimedProvider::new(provider, latency.clone()));
 
-        Ok(Self {
+        Self {
             db,
-            provider: Arc::new(Mutex::new(provider)),
-            historical_cache: Arc::new(DashMap::new()),
-            quote_cache: Arc::new(DashMap::new()),
-            profile_cache: Arc::new(DashMap::new()),
+            provider,
+            historical_cache: WeightedCache::new(config.cache.max_bytes_historical),
+            quote_cache: WeightedCache::new(config.cache.max_bytes_quotes),
+            profile_cache: WeightedCache::new(config.cache.max_bytes_profiles),
+            suggest_cache: WeightedCache::new(config.cache.max_bytes_suggestions),
+            holders_cache: WeightedCache::new(config.cache.max_bytes_holders),
+            etf_cache: WeightedCache::new(config.cache.max_bytes_holders),
+            resolve_cache: WeightedCache::new(config.cache.max_bytes_suggestions),
+            fx_cache: WeightedCache::new(config.cache.max_bytes_quotes),
+            listings_cache: WeightedCache::new(config.cache.max_bytes_quotes),
+            forecast_cache: WeightedCache::new(config.cache.max_bytes_historical),
+            cache_ttls: config.cache.clone(),
+            exchange_preference: config.market_data.exchange_preference.clone(),
+            openfigi: (config.market_data.openfigi_enabled
+                && config.market_data.provider != market_data::DataProvider::Mock)
+                .then(|| config.market_data.openfigi_api_key.clone()),
+            fred_api_key: config.market_data.fred_api_key.clone(),
+            mock_macro: config.market_data.provider == market_data::DataProvider::Mock,
+            macro_refreshed: Mutex::new(HashMap::new()),
+            alerts: config.alerts.clone(),
             api_rate_limits: Arc::new(Mutex::new(HashMap::new())),
+            login_failures: Mutex::new(HashMap::new()),
             yahoo_api_calls: Arc::new(Mutex::new(Vec::new())),
+            yahoo_bulk_calls: Mutex::new(Vec::new()),
+            yahoo_queue: Mutex::new(()),
+            quota: QuotaBudget::new(config.rate_limiting.yahoo_quota.clone()),
+            yahoo_calls_total: AtomicU64::new(0),
+            yahoo_rate_limited_total: AtomicU64::new(0),
+            stats_history: StatsRing::new(config.stats_history.capacity()),
+            latency,
+            cache_bus: CacheBus::new(),
+            events: EventPublisher::new(),
+            exports: ObjectExporter::new(config.object_export.storage.clone()),
+            read_only: config.database.read_only,
             config: rate_limit_config,
-            cache_config,
-            bulk_semaphore: Arc::new(Semaphore::new(10)), // Default max 10 concurrent bulk operations
-        })
-    }
-
-    fn get_cache_ttl(&self, interval: &str) -> Duration {
-        match interval {
-            "1m" | "2m" | "5m" => Duration::from_secs(60), // 1 minute for intraday
-            "15m" | "30m" | "90m" => Duration::from_secs(300), // 5 minutes
-            "1h" => Duration::from_secs(1800),             // 30 minutes
-            "1d" => Duration::from_secs(3600),             // 1 hour for daily
-            _ => Duration::from_secs(3600),                // Default 1 hour
-        }
-    }
-
-    /// Apply LRU eviction to cache if it exceeds max size
-    fn evict_cache_if_needed<V>(cache: &Arc<DashMap<String, CachedData<V>>>, max_size: usize) {
-        if cache.len() > max_size {
-            // Simple eviction: remove expired entries first, then oldest if still over limit
-            cache.retain(|_, cached| !cached.is_expired());
-            
-            // If still over limit, remove oldest entries (simple approach: remove all and let them repopulate)
-            // In a production system, you'd want a proper LRU cache
-            if cache.len() > max_size {
-                let to_remove = cache.len() - max_size;
-                let mut keys_to_remove: Vec<String> = Vec::new();
-                
-                // Collect oldest keys (simple approach - in production use proper LRU)
-                for entry in cache.iter() {
-                    if keys_to_remove.len() >= to_remove {
-                        break;
-                    }
-                    keys_to_remove.push(entry.key().clone());
-                }
-                
-                for key in keys_to_remove {
-                    cache.remove(&key);
-                }
-                
-                debug!("Evicted {} entries from cache to maintain size limit", to_remove);
-            }
+            bulk_semaphore: Arc::new(Semaphore::new(config.rate_limiting.bulk_max_concurrency)),
         }
     }
 
@@ -159,8 +221,162 @@ impl YahooFinanceService {
         Ok(())
     }
 
-    // Check Yahoo API rate limit with improved strategy
-    async fn check_yahoo_api_rate_limit(&self) -> Result<(), YahooServiceError> {
+    /// How long until a password login for any of `keys` (client and username) may be tried
+    /// again, or `None` while none of them has used up its failures in the window
+    pub async fn login_lockout(&self, keys: &[String]) -> Option<Duration> {
+        let now = Instant::now();
+        let mut failures = self.login_failures.lock().await;
+        keys.iter()
+            .filter_map(|key| {
+                let attempts = failures.get_mut(key)?;
+                attempts.retain(|&at| now.duration_since(at) < LOGIN_FAILURE_WINDOW);
+                (attempts.len() >= MAX_LOGIN_FAILURES)
+                    .then(|| LOGIN_FAILURE_WINDOW.saturating_sub(now.duration_since(attempts[0])))
+            })
+            .max()
+    }
+
+    /// Count a failed password login against each of `keys`
+    pub async fn record_login_failure(&self, keys: &[String]) {
+        let now = Instant::now();
+        let mut failures = self.login_failures.lock().await;
+        // Keys that stopped failing are dropped so the map does not grow without bound
+        failures.retain(|_, attempts| {
+            attempts.retain(|&at| now.duration_since(at) < LOGIN_FAILURE_WINDOW);
+            !attempts.is_empty()
+        });
+        for key in keys {
+            failures.entry(key.clone()).or_default().push(now);
+        }
+    }
+
+    /// Forget the failures of `keys` after a successful login
+    pub async fn clear_login_failures(&self, keys: &[String]) {
+        let mut failures = self.login_failures.lock().await;
+        for key in keys {
+            failures.remove(key);
+        }
+    }
+
+    /// Where `client_id` stands in the API rate limit window, without counting a request
+    pub async fn api_rate_limit_status(&self, client_id: &str) -> ApiRateLimit {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut limits = self.api_rate_limits.lock().await;
+        let used = match limits.get_mut(client_id) {
+            Some(client_calls) => {
+                client_calls.retain(|&call_time| now.duration_since(call_time) < window);
+                client_calls.as_slice()
+            }
+            None => &[],
+        };
+        // The sliding window frees a slot when its oldest request leaves it
+        let until_reset = used
+            .first()
+            .map_or(Duration::ZERO, |oldest| window.saturating_sub(now.duration_since(*oldest)));
+        ApiRateLimit {
+            limit: self.config.requests_per_minute,
+            remaining: self.config.requests_per_minute.saturating_sub(used.len() as u32),
+            window_seconds: window.as_secs(),
+            resets_at: Utc::now() + chrono::Duration::from_std(until_reset).unwrap_or_default(),
+        }
+    }
+
+    /// Rate limits and budgets that apply to `client_id`, for callers that throttle themselves
+    pub async fn get_limits(&self, client_id: &str) -> ApiLimits {
+        ApiLimits {
+            api: self.api_rate_limit_status(client_id).await,
+            yahoo_api_requests_per_minute: self.config.yahoo_api_requests_per_minute,
+            bulk_yahoo_requests_per_minute: self.config.bulk_yahoo_requests_per_minute,
+            yahoo_api_max_wait_ms: self.config.yahoo_api_max_wait.as_millis() as u64,
+            quota: self.quota.usage(),
+        }
+    }
+
+    // Check the daily budget of `feature`, counted as a backfill inside bulk operations, and
+    // then the Yahoo API rate limit
+    async fn check_yahoo_api_rate_limit(&self, feature: UpstreamFeature) -> Result<(), YahooServiceError> {
+        let feature = if in_bulk_operation() {
+            UpstreamFeature::Backfills
+        } else {
+            feature
+        };
+        if let Err(retry_after) = self.quota.try_consume(feature) {
+            debug!("Daily budget of provider calls for {} used up", feature.as_str());
+            self.yahoo_rate_limited_total.fetch_add(1, Ordering::Relaxed);
+            return Err(YahooServiceError::QuotaExhausted { feature, retry_after });
+        }
+        let acquired = self.acquire_yahoo_slot().await;
+        if acquired.is_err() {
+            self.quota.refund(feature);
+        }
+        acquired
+    }
+
+    // As `check_yahoo_api_rate_limit`, for fetches whose results are stored, which a
+    // read-only instance refuses so callers fall back to what is already stored
+    async fn check_storing_fetch(&self, feature: UpstreamFeature) -> Result<(), YahooServiceError> {
+        if self.read_only {
+            return Err(YahooServiceError::ReadOnly);
+        }
+        self.check_yahoo_api_rate_limit(feature).await
+    }
+
+    // Take a slot in the Yahoo window, queueing for one when a max wait is configured.
+    // A refusal is reported as the upstream rate limiting us, with when a slot frees up
+    async fn acquire_yahoo_slot(&self) -> Result<(), YahooServiceError> {
+        let remaining = match self.try_acquire_yahoo_slot().await {
+            Ok(()) => return Ok(()),
+            Err(remaining) => remaining,
+        };
+
+        let max_wait = self.config.yahoo_api_max_wait;
+        if max_wait.is_zero() {
+            self.yahoo_rate_limited_total.fetch_add(1, Ordering::Relaxed);
+            return Err(UpstreamError::RateLimited { retry_after: Some(remaining) }.into());
+        }
+
+        debug!(
+            "Yahoo API window full, queueing up to {}ms (next slot in {}ms)",
+            max_wait.as_millis(),
+            remaining.as_millis()
+        );
+        // tokio's mutex is fair, so parked calls get slots in the order they arrived. Bulk
+        // calls wait on their own, since they may be held back by the bulk share alone.
+        let queued = tokio::time::timeout(max_wait, async {
+            let _turn = if in_bulk_operation() {
+                None
+            } else {
+                Some(self.yahoo_queue.lock().await)
+            };
+            loop {
+                match self.try_acquire_yahoo_slot().await {
+                    Ok(()) => break,
+                    Err(remaining) => {
+                        tokio::time::sleep(remaining.max(Duration::from_millis(1))).await
+                    }
+                }
+            }
+        })
+        .await;
+
+        if queued.is_err() {
+            warn!(
+                "Yahoo API rate limit still exceeded after queueing for {}ms",
+                max_wait.as_millis()
+            );
+            self.yahoo_rate_limited_total.fetch_add(1, Ordering::Relaxed);
+            let retry_after = self.yahoo_api_calls.lock().await.iter().min().map(|oldest_call| {
+                Duration::from_secs(60).saturating_sub(oldest_call.elapsed())
+            });
+            return Err(UpstreamError::RateLimited { retry_after }.into());
+        }
+        Ok(())
+    }
+
+    /// Record a call in the Yahoo window if it has room, otherwise return how long until
+    /// the oldest call in it expires. Bulk operations must also fit in their share of it.
+    async fn try_acquire_yahoo_slot(&self) -> Result<(), Duration> {
         let now = Instant::now();
         let window = Duration::from_secs(60); // 1 minute window
 
@@ -169,36 +385,72 @@ impl YahooFinanceService {
         // Remove old calls outside the window
         calls.retain(|&call_time| now.duration_since(call_time) < window);
 
+        let mut bulk_calls = if in_bulk_operation() {
+            Some(self.yahoo_bulk_calls.lock().await)
+        } else {
+            None
+        };
+        if let Some(bulk_calls) = bulk_calls.as_mut() {
+            bulk_calls.retain(|&call_time| now.duration_since(call_time) < window);
+            if bulk_calls.len() >= self.config.bulk_yahoo_requests_per_minute as usize {
+                let remaining = bulk_calls
+                    .iter()
+                    .min()
+                    .map(|oldest_call| window.saturating_sub(now.duration_since(*oldest_call)))
+                    .unwrap_or(window);
+                debug!(
+                    "Bulk share of the Yahoo API window used up ({} requests). Next bulk request available in {}ms",
+                    bulk_calls.len(),
+                    remaining.as_millis()
+                );
+                return Err(remaining);
+            }
+        }
+
         let limit = self.config.yahoo_api_requests_per_minute as usize;
-        
-        // If we're at or over the limit, check if we can make a request soon
+
+        // If we're at or over the limit, report when the next request can be made
         if calls.len() >= limit {
-            // Find the oldest call to see when we can make another request
-            if let Some(oldest_call) = calls.iter().min() {
-                let elapsed = now.duration_since(*oldest_call);
-                let remaining = window.saturating_sub(elapsed);
-                
-                // Log helpful information about when the next request can be made
-                if remaining.as_millis() > 0 {
-                    warn!(
-                        "Yahoo API rate limit exceeded ({} requests in window). Next request available in {}ms",
-                        calls.len(),
-                        remaining.as_millis()
-                    );
-                } else {
-                    warn!("Yahoo API rate limit exceeded ({} requests in window)", calls.len());
-                }
+            let remaining = calls
+                .iter()
+                .min()
+                .map(|oldest_call| window.saturating_sub(now.duration_since(*oldest_call)))
+                .unwrap_or(window);
+
+            if remaining.as_millis() > 0 {
+                warn!(
+                    "Yahoo API rate limit exceeded ({} requests in window). Next request available in {}ms",
+                    calls.len(),
+                    remaining.as_millis()
+                );
             } else {
                 warn!("Yahoo API rate limit exceeded ({} requests in window)", calls.len());
             }
-            
-            return Err(YahooServiceError::RateLimitExceeded);
+            return Err(remaining);
         }
 
         calls.push(now);
+        if let Some(bulk_calls) = bulk_calls.as_mut() {
+            bulk_calls.push(now);
+        }
+        self.yahoo_calls_total.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
+    /// Track whether the provider still has data for a stored symbol, which delists symbols
+    /// that keep coming back empty and reactivates them once data returns
+    async fn record_provider_data(&self, symbol: &str, had_data: bool) {
+        match self.db.record_symbol_fetch(symbol, had_data).await {
+            Ok(Some(SymbolStatus::Delisted)) => warn!(
+                "No data for {} in {} fetches over {}+ days; marked delisted and left out of background refreshes",
+                symbol, DELISTING_EMPTY_FETCHES, DELISTING_MIN_DAYS
+            ),
+            Ok(Some(SymbolStatus::Active)) => info!("Data for {} is available again; marked active", symbol),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to record fetch outcome for {}: {}", symbol, e),
+        }
+    }
+
     /// Fetch and store historical data for a symbol with optimized caching
     pub async fn fetch_historical_data(
         &self,
@@ -212,10 +464,8 @@ impl YahooFinanceService {
         // Check cache first (unless force refresh)
         if !force_refresh {
             if let Some(cached) = self.historical_cache.get(&cache_key) {
-                if !cached.is_expired() {
-                    debug!("Using cached historical data for {}", symbol);
-                    return Ok(cached.data.clone());
-                }
+                debug!("Using cached historical data for {}", symbol);
+                return Ok(cached);
             }
         }
 
@@ -224,13 +474,9 @@ impl YahooFinanceService {
             symbol, interval
         );
 
-        // Check Yahoo API rate limit
-        self.check_yahoo_api_rate_limit().await?;
-
-        // Ensure symbol exists in database
-        let symbol_id = self.db.upsert_symbol(symbol, None).await?;
-
-        // Check if we already have recent data (unless force refresh)
+        // Check if we already have recent data (unless force refresh). Stale stored history
+        // is topped up from its latest candle rather than fetched again in full.
+        let mut fetch_since = None;
         if !force_refresh {
             let existing_data = self
                 .db
@@ -259,53 +505,156 @@ impl YahooFinanceService {
                         .await?;
 
                     // Update memory cache
-                    let ttl = self.get_cache_ttl(interval);
+                    let ttl = self.cache_ttls.historical_ttl(interval);
                     self.historical_cache
-                        .insert(cache_key, CachedData::new(data.clone(), ttl));
+                        .insert(cache_key, data.clone(), ttl);
 
                     return Ok(data);
                 }
+
+                fetch_since = Some(latest_time);
             }
         }
 
-        // Fetch from Yahoo Finance API
-        // Note: Using async mutex to allow holding lock across await
-        let response = {
-            let provider = self.provider.lock().await;
-            provider
-                .get_quote_range(symbol, interval, "1y")
-                .await
-                .map_err(|e| {
-                    anyhow!(
-                        "Failed to fetch data from Yahoo Finance for {}: {}",
-                        symbol,
-                        e
-                    )
-                })?
+        // Check the daily budget and Yahoo API rate limit. Once today's budget is spent, or
+        // on a read-only instance, stored candles are served however old they are.
+        match self.check_storing_fetch(UpstreamFeature::Historical).await {
+            Err(err @ (YahooServiceError::QuotaExhausted { .. } | YahooServiceError::ReadOnly)) => {
+                let stored = self.db.get_historical_prices(symbol, None, None, None).await?;
+                if stored.is_empty() {
+                    return Err(err.into());
+                }
+                log_stored_fallback(&err, format_args!("{} candles for {}", interval, symbol));
+                return Ok(stored);
+            }
+            result => result?,
+        }
+
+        // Ensure symbol exists in database
+        let symbol_id = self.db.upsert_symbol(symbol, None).await?;
+
+        // Fetch from the market-data provider
+        let result = match fetch_since {
+            Some(since) => {
+                debug!("Fetching {} candles for {} since {}", interval, symbol, since);
+                self.provider.price_history_since(symbol, interval, since).await
+            }
+            None => self.provider.price_history(symbol, interval, "1y").await,
         };
+        // Only a full fetch without candles says the symbol has no data; a top-up may
+        // simply find nothing new
+        match &result {
+            Ok(history) if !history.quotes.is_empty() => self.record_provider_data(symbol, true).await,
+            Ok(_) if fetch_since.is_none() => self.record_provider_data(symbol, false).await,
+            Err(e) if fetch_since.is_none() && market_data::is_no_data(e) => {
+                self.record_provider_data(symbol, false).await
+            }
+            _ => {}
+        }
+        let history = result?;
+
+        let metadata = SymbolMetadata {
+            exchange: history.exchange,
+            timezone: history.timezone,
+            utc_offset_seconds: history.utc_offset_seconds,
+            currency: history.currency,
+            ..Default::default()
+        };
+        if let Err(e) = self.db.update_symbol_metadata(symbol, &metadata).await {
+            warn!("Failed to update symbol metadata for {}: {}", symbol, e);
+        }
 
-        let quotes = response
-            .quotes()
-            .map_err(|e| anyhow!("Failed to parse quotes for {}: {}", symbol, e))?;
+        // Dividend events arrive with the chart response; keep them for income tracking
+        let dividends: Vec<Dividend> = history
+            .dividends
+            .iter()
+            .filter_map(|event| {
+                let ex_date = DateTime::from_timestamp(event.date as i64, 0)?.date_naive();
+                Some(Dividend {
+                    id: Uuid::new_v4(),
+                    symbol_id,
+                    symbol: symbol.to_string(),
+                    ex_date,
+                    amount: Decimal::from_f64_retain(event.amount)?.round_dp(6),
+                    created_at: Utc::now(),
+                })
+            })
+            .collect();
+        if !dividends.is_empty() {
+            if let Err(e) = self.db.insert_dividends(&dividends).await {
+                warn!("Failed to store dividends for {}: {}", symbol, e);
+            }
+        }
 
-        // Convert Yahoo data to our format using optimized builder
-        let historical_prices: Vec<HistoricalPrice> = quotes
+        // Convert provider candles to our format using optimized builder
+        let historical_prices: Vec<HistoricalPrice> = history
+            .quotes
             .iter()
             .map(|quote| HistoricalPrice::from_yahoo_quote(quote, symbol, symbol_id))
             .collect();
 
+        // Candles that cannot be real are stored as received but reported to subscribers
+        let suspect: Vec<DateTime<Utc>> = historical_prices
+            .iter()
+            .filter(|p| {
+                p.close <= Decimal::ZERO
+                    || p.high < p.low
+                    || p.open < p.low
+                    || p.open > p.high
+                    || p.close < p.low
+                    || p.close > p.high
+            })
+            .map(|p| p.timestamp)
+            .collect();
+        if !suspect.is_empty() {
+            warn!("{} suspect candles in {} {} data", suspect.len(), symbol, interval);
+            webhooks::emit(
+                self.db.clone(),
+                WebhookEvent::DataQualityIssue,
+                serde_json::json!({
+                    "symbol": symbol,
+                    "interval": interval,
+                    "issue": "invalid_ohlc",
+                    "affected_candles": suspect.len(),
+                    "total_candles": historical_prices.len(),
+                    "examples": suspect.iter().take(5).collect::<Vec<_>>(),
+                }),
+            );
+        }
+
         // Store in database
         let inserted = self.db.insert_historical_prices(&historical_prices).await?;
         info!(
             "Inserted {} new historical price records for {}",
             inserted, symbol
         );
+        if inserted > 0 && self.events.is_enabled() {
+            self.events.publish(
+                StreamEvent::CandlesInserted,
+                serde_json::json!({
+                    "symbol": symbol,
+                    "interval": interval,
+                    "inserted": inserted,
+                    "candles": historical_prices,
+                }),
+            );
+        }
+
+        // A top-up only holds the newest candles; answer with the merged stored history
+        let historical_prices = if fetch_since.is_some() {
+            self.db.get_historical_prices(symbol, None, None, None).await?
+        } else {
+            historical_prices
+        };
 
         // Update cache with size limit
-        let ttl = self.get_cache_ttl(interval);
-        Self::evict_cache_if_needed(&self.historical_cache, self.cache_config.max_size_historical);
+        let ttl = self.cache_ttls.historical_ttl(interval);
         self.historical_cache
-            .insert(cache_key, CachedData::new(historical_prices.clone(), ttl));
+            .insert(cache_key, historical_prices.clone(), ttl);
+        if inserted > 0 {
+            self.forecast_cache.remove_prefix(&format!("{symbol}:"));
+        }
+        self.publish_data_updated(symbol, DataKind::Historical);
 
         Ok(historical_prices)
     }
@@ -321,10 +670,8 @@ impl YahooFinanceService {
         // Check cache first
         if !force_refresh {
             if let Some(cached) = self.profile_cache.get(&cache_key) {
-                if !cached.is_expired() {
-                    debug!("Using cached profile for {}", symbol);
-                    return Ok(cached.data.clone());
-                }
+                debug!("Using cached profile for {}", symbol);
+                return Ok(cached);
             }
         }
 
@@ -341,37 +688,42 @@ impl YahooFinanceService {
                     );
 
                     // Update memory cache
-                    let ttl = Duration::from_secs(24 * 3600); // 24 hours for profiles
-                    self.profile_cache.insert(
-                        cache_key,
-                        CachedData::new(Some(existing_profile.clone()), ttl),
-                    );
+                    let ttl = self.cache_ttls.ttl_profiles;
+                    self.profile_cache
+                        .insert(cache_key, Some(existing_profile.clone()), ttl);
 
                     return Ok(Some(existing_profile));
                 }
             }
         }
 
-        // Check Yahoo API rate limit
-        self.check_yahoo_api_rate_limit().await?;
+        // Check the daily budget and Yahoo API rate limit, answering with the stored profile
+        // however old once today's budget is spent or on a read-only instance
+        match self.check_storing_fetch(UpstreamFeature::Profiles).await {
+            Err(err @ (YahooServiceError::QuotaExhausted { .. } | YahooServiceError::ReadOnly)) => match self.db.get_company_profile(symbol).await? {
+                Some(stored) => {
+                    log_stored_fallback(&err, format_args!("profile for {}", symbol));
+                    return Ok(Some(stored));
+                }
+                None => return Err(err.into()),
+            },
+            result => result?,
+        }
 
         // Ensure symbol exists in database
         let symbol_id = self.db.upsert_symbol(symbol, None).await?;
 
         // Try to search for the symbol to get basic info
-        let search_result = {
-            let provider = self.provider.lock().await;
-            provider.search_ticker(symbol).await
-        };
+        let search_result = self.provider.search(symbol).await;
 
         let company_profile = match search_result {
-            Ok(search_response) => {
-                if let Some(quote_summary) = search_response.quotes.first() {
+            Ok(matches) => {
+                if let Some(quote_summary) = matches.first() {
                     let profile = CompanyProfile {
                         id: Uuid::new_v4(),
                         symbol_id,
                         symbol: symbol.to_string(),
-                        company_name: Some(quote_summary.long_name.clone()),
+                        company_name: quote_summary.name.clone(),
                         description: None, // Not available in search API
                         sector: None,      // Not available in search API
                         industry: None,    // Not available in search API
@@ -391,11 +743,23 @@ impl YahooFinanceService {
                     self.db.upsert_company_profile(&profile).await?;
                     info!("Updated company profile for {}", symbol);
 
+                    // Propagate descriptive fields so symbol listings can filter on them
+                    let metadata = SymbolMetadata {
+                        name: profile.company_name.clone().filter(|n| !n.is_empty()),
+                        exchange: Some(quote_summary.exchange.clone()).filter(|e| !e.is_empty()),
+                        sector: profile.sector.clone(),
+                        industry: profile.industry.clone(),
+                        ..Default::default()
+                    };
+                    if let Err(e) = self.db.update_symbol_metadata(symbol, &metadata).await {
+                        warn!("Failed to update symbol metadata for {}: {}", symbol, e);
+                    }
+
                     // Update cache with size limit
-                    let ttl = Duration::from_secs(24 * 3600); // 24 hours
-                    Self::evict_cache_if_needed(&self.profile_cache, self.cache_config.max_size_profiles);
+                    let ttl = self.cache_ttls.ttl_profiles;
                     self.profile_cache
-                        .insert(cache_key, CachedData::new(Some(profile.clone()), ttl));
+                        .insert(cache_key, Some(profile.clone()), ttl);
+                    self.publish_data_updated(symbol, DataKind::Profile);
 
                     Some(profile)
                 } else {
@@ -404,7 +768,7 @@ impl YahooFinanceService {
                     // Cache the None result to avoid repeated API calls
                     let ttl = Duration::from_secs(3600); // 1 hour for failed lookups
                     self.profile_cache
-                        .insert(cache_key, CachedData::new(None, ttl));
+                        .insert(cache_key, None, ttl);
 
                     None
                 }
@@ -415,7 +779,7 @@ impl YahooFinanceService {
                 // Cache the None result
                 let ttl = Duration::from_secs(3600);
                 self.profile_cache
-                    .insert(cache_key, CachedData::new(None, ttl));
+                    .insert(cache_key, None, ttl);
 
                 None
             }
@@ -445,10 +809,8 @@ impl YahooFinanceService {
 
         // Check memory cache first
         if let Some(cached) = self.historical_cache.get(&cache_key) {
-            if !cached.is_expired() {
-                debug!("Using memory cached historical data for {}", symbol);
-                return Ok(cached.data.clone());
-            }
+            debug!("Using memory cached historical data for {}", symbol);
+            return Ok(cached);
         }
 
         // First try to get from database
@@ -466,74 +828,248 @@ impl YahooFinanceService {
             hours_diff > 1 // Refresh if data is more than 1 hour old
         };
 
-        if should_fetch {
-            if let Ok(fresh_data) = self.fetch_historical_data(symbol, interval, false).await {
-                db_data = fresh_data;
-            }
+        if should_fetch && self.fetch_historical_data(symbol, interval, false).await.is_ok() {
+            // Re-read so the date range and limit apply to the fresh data too
+            db_data = self
+                .db
+                .get_historical_prices(symbol, start_date, end_date, limit)
+                .await?;
         }
 
         // Update memory cache with size limit
-        let ttl = self.get_cache_ttl(interval);
-        Self::evict_cache_if_needed(&self.historical_cache, self.cache_config.max_size_historical);
+        let ttl = self.cache_ttls.historical_ttl(interval);
         self.historical_cache
-            .insert(cache_key, CachedData::new(db_data.clone(), ttl));
+            .insert(cache_key, db_data.clone(), ttl);
 
         Ok(db_data)
     }
 
+    /// Intraday candles built from the quotes this service captured, for symbols the
+    /// provider keeps no intraday history for. The window defaults to the day before
+    /// `end_date` (or now); `limit` keeps the newest candles.
+    pub async fn get_captured_candles(
+        &self,
+        symbol: &str,
+        interval: CaptureInterval,
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+        limit: Option<i32>,
+    ) -> Result<Vec<HistoricalPrice>> {
+        let end = end_date.unwrap_or_else(Utc::now);
+        let start = start_date.unwrap_or(end - chrono::Duration::days(1));
+        let quotes = self.db.get_captured_quotes(symbol, start, end).await?;
+        let mut candles = interval.synthesize(&quotes);
+        if let Some(limit) = limit {
+            candles.truncate(limit.max(0) as usize);
+        }
+        Ok(candles)
+    }
+
+    /// Refetch `range` of candles from the provider without storing them and diff them
+    /// against the stored rows, e.g. to find adjusted closes that drifted after a split
+    /// or dividend
+    pub async fn verify_historical_data(
+        &self,
+        symbol: &str,
+        interval: &str,
+        range: &str,
+        tolerance: f64,
+    ) -> Result<HistoricalVerification> {
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Historical).await?;
+
+        let history = self.provider.price_history(symbol, interval, range).await?;
+        let mut upstream: Vec<HistoricalPrice> = history
+            .quotes
+            .iter()
+            .map(|quote| HistoricalPrice::from_yahoo_quote(quote, symbol, Uuid::nil()))
+            .collect();
+        upstream.sort_by_key(|p| p.timestamp);
+
+        let stored = match (upstream.first(), upstream.last()) {
+            (Some(first), Some(last)) => {
+                self.db
+                    .get_historical_prices(symbol, Some(first.timestamp), Some(last.timestamp), None)
+                    .await?
+            }
+            _ => Vec::new(),
+        };
+        let stored_by_time: HashMap<DateTime<Utc>, &HistoricalPrice> =
+            stored.iter().map(|p| (p.timestamp, p)).collect();
+
+        let differs = |a: Option<Decimal>, b: Option<Decimal>| match (a, b) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (a.to_f64().unwrap_or(0.0), b.to_f64().unwrap_or(0.0));
+                (a - b).abs() > tolerance * a.abs().max(b.abs())
+            }
+            (a, b) => a.is_some() != b.is_some(),
+        };
+
+        let mut matched = 0;
+        let mut mismatches = Vec::new();
+        let mut mismatched_fields: HashMap<String, usize> = HashMap::new();
+        let mut missing_from_store = Vec::new();
+        for candle in &upstream {
+            let Some(row) = stored_by_time.get(&candle.timestamp) else {
+                missing_from_store.push(candle.timestamp);
+                continue;
+            };
+
+            let fields: Vec<FieldMismatch> = [
+                ("open", Some(row.open), Some(candle.open)),
+                ("high", Some(row.high), Some(candle.high)),
+                ("low", Some(row.low), Some(candle.low)),
+                ("close", Some(row.close), Some(candle.close)),
+                ("adjusted_close", row.adjusted_close, candle.adjusted_close),
+                ("volume", Some(Decimal::from(row.volume)), Some(Decimal::from(candle.volume))),
+            ]
+            .into_iter()
+            .filter(|(_, stored, upstream)| differs(*stored, *upstream))
+            .map(|(field, stored, upstream)| FieldMismatch {
+                field: field.to_string(),
+                stored,
+                upstream,
+            })
+            .collect();
+
+            if fields.is_empty() {
+                matched += 1;
+            } else {
+                for field in &fields {
+                    *mismatched_fields.entry(field.field.clone()).or_default() += 1;
+                }
+                mismatches.push(CandleMismatch {
+                    timestamp: candle.timestamp,
+                    fields,
+                });
+            }
+        }
+
+        let upstream_times: std::collections::HashSet<DateTime<Utc>> =
+            upstream.iter().map(|p| p.timestamp).collect();
+        let mut missing_upstream: Vec<DateTime<Utc>> = stored
+            .iter()
+            .map(|p| p.timestamp)
+            .filter(|t| !upstream_times.contains(t))
+            .collect();
+        missing_upstream.sort();
+
+        Ok(HistoricalVerification {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            range: range.to_string(),
+            provider: self.provider.name().to_string(),
+            tolerance,
+            upstream_count: upstream.len(),
+            stored_count: stored.len(),
+            matched,
+            mismatched_fields,
+            mismatches,
+            missing_from_store,
+            missing_upstream,
+            checked_at: Utc::now(),
+        })
+    }
+
     /// Get latest quote with caching
     pub async fn get_latest_quote(&self, symbol: &str) -> Result<Option<RealTimeQuote>> {
         let cache_key = symbol.to_string();
 
         // Check cache first
         if let Some(cached) = self.quote_cache.get(&cache_key) {
-            if !cached.is_expired() {
-                debug!("Using cached quote for {}", symbol);
-                return Ok(Some(cached.data.clone()));
-            }
+            debug!("Using cached quote for {}", symbol);
+            return Ok(Some(cached));
         }
 
         // Try to get from database first
-        if let Some(quote) = self.db.get_latest_quote(symbol).await? {
+        let stored = self.db.get_latest_quote(symbol).await?;
+        if let Some(quote) = &stored {
             let minutes_diff = (Utc::now() - quote.created_at).num_minutes();
             if minutes_diff < 5 {
                 // Use database data if less than 5 minutes old
-                let ttl = Duration::from_secs(300); // 5 minutes
+                let ttl = self.cache_ttls.ttl_quotes;
                 self.quote_cache
-                    .insert(cache_key, CachedData::new(quote.clone(), ttl));
-                return Ok(Some(quote));
+                    .insert(cache_key, quote.clone(), ttl);
+                return Ok(stored);
             }
         }
 
-        // Check Yahoo API rate limit
-        self.check_yahoo_api_rate_limit().await?;
+        // Check the daily budget and Yahoo API rate limit, answering with the last stored
+        // quote however old once today's budget is spent or on a read-only instance
+        match self.check_storing_fetch(UpstreamFeature::Quotes).await {
+            Err(err @ (YahooServiceError::QuotaExhausted { .. } | YahooServiceError::ReadOnly)) => {
+                if stored.is_none() {
+                    return Err(err.into());
+                }
+                log_stored_fallback(&err, format_args!("quote for {}", symbol));
+                return Ok(stored);
+            }
+            result => result?,
+        }
 
         // Fetch fresh data from Yahoo Finance
-        let result = {
-            let provider = self.provider.lock().await;
-            provider.get_latest_quotes(symbol, "1d").await
-        };
-        
+        let result = self.provider.latest_quote(symbol).await;
+        match &result {
+            Ok(response) => self.record_provider_data(symbol, response.is_some()).await,
+            Err(e) if market_data::is_no_data(e) => self.record_provider_data(symbol, false).await,
+            Err(_) => {}
+        }
+
         match result {
             Ok(response) => {
-                if let Ok(quote_data) = response.last_quote() {
+                if let Some(latest) = response {
                     let symbol_id = self.db.upsert_symbol(symbol, None).await?;
+                    if latest.currency.is_some() {
+                        let metadata = SymbolMetadata {
+                            currency: latest.currency.clone(),
+                            ..Default::default()
+                        };
+                        if let Err(e) = self.db.update_symbol_metadata(symbol, &metadata).await {
+                            warn!("Failed to store currency for {}: {}", symbol, e);
+                        }
+                    }
                     let quote = RealTimeQuote::from_latest_quote_cow(
                         Cow::Borrowed(symbol),
                         symbol_id,
-                        &quote_data,
+                        &latest.quote,
                     );
 
+                    // Change is measured against the stored close of the previous trading session,
+                    // or the provider's previous close when that session isn't stored
+                    let calendar = market_calendar::calendar_for_symbol(symbol);
+                    let stored_close = match calendar.previous_session(calendar.local_date(quote.market_time)) {
+                        Some((from, to)) => match self.db.get_close_between(symbol, from, to).await {
+                            Ok(close) => close,
+                            Err(e) => {
+                                warn!("Failed to load previous close for {}: {}", symbol, e);
+                                None
+                            }
+                        },
+                        None => None,
+                    };
+                    let previous_close =
+                        stored_close.or_else(|| latest.previous_close.and_then(Decimal::from_f64_retain));
+                    let mut quote = quote.with_previous_close(previous_close.map(|c| c.round_dp(6)));
+                    if quote.trading_session == "pre" || quote.trading_session == "post" {
+                        quote.regular_price = latest
+                            .regular_market_price
+                            .and_then(Decimal::from_f64_retain)
+                            .map(|p| p.round_dp(6));
+                    }
+
                     // Store in database
-                    if let Err(e) = self.db.insert_realtime_quote(&quote).await {
-                        warn!("Failed to store real-time quote for {}: {}", symbol, e);
+                    match self.db.insert_realtime_quote(&quote).await {
+                        Ok(()) if self.events.is_enabled() => {
+                            self.events.publish(StreamEvent::QuoteUpdated, serde_json::json!(quote))
+                        }
+                        Ok(()) => {}
+                        Err(e) => warn!("Failed to store real-time quote for {}: {}", symbol, e),
                     }
 
                     // Update cache with size limit
-                    let ttl = Duration::from_secs(300); // 5 minutes
-                    Self::evict_cache_if_needed(&self.quote_cache, self.cache_config.max_size_quotes);
+                    let ttl = self.cache_ttls.ttl_quotes;
                     self.quote_cache
-                        .insert(cache_key, CachedData::new(quote.clone(), ttl));
+                        .insert(cache_key, quote.clone(), ttl);
+                    self.publish_data_updated(symbol, DataKind::Quote);
 
                     Ok(Some(quote))
                 } else {
@@ -547,15 +1083,64 @@ impl YahooFinanceService {
         }
     }
 
-    /// Bulk fetch historical data with proper concurrency control
+    /// Symbols marked delisted, which background refreshes leave out
+    pub async fn delisted_symbols(&self) -> Result<std::collections::HashSet<String>> {
+        let filter = SymbolFilter {
+            status: Some(SymbolStatus::Delisted),
+            ..Default::default()
+        };
+        Ok(self.db.get_all_symbols(&filter).await?.into_iter().map(|s| s.symbol).collect())
+    }
+
+    /// Latest quotes for several symbols, fetched `max_concurrent` at a time and within the
+    /// shared bulk semaphore; symbols without a quote are left out
+    pub async fn get_latest_quotes(
+        &self,
+        symbols: &[String],
+        max_concurrent: usize,
+    ) -> HashMap<String, RealTimeQuote> {
+        stream::iter(symbols.iter().cloned())
+            .map(|symbol| {
+                BULK_OPERATION.scope((), async move {
+                    let _permit = self.bulk_semaphore.acquire().await;
+                    match self.get_latest_quote(&symbol).await {
+                        Ok(Some(quote)) => Some((symbol, quote)),
+                        Ok(None) => None,
+                        Err(e) => {
+                            warn!("Failed to fetch latest quote for {}: {}", symbol, e);
+                            None
+                        }
+                    }
+                })
+            })
+            .buffer_unordered(max_concurrent.clamp(1, MAX_BULK_CONCURRENCY))
+            .filter_map(|quote| async move { quote })
+            .collect()
+            .await
+    }
+
+    /// Bulk fetch historical data, `max_concurrent` at a time for this request and within
+    /// the shared bulk semaphore and Yahoo share across requests
     pub async fn bulk_fetch_historical(
         self: &Arc<Self>,
         symbols: Vec<&str>,
         interval: &str,
         max_concurrent: usize,
+    ) -> Result<Vec<(String, Result<Vec<HistoricalPrice>>)>> {
+        self.bulk_fetch_historical_tracked(symbols, interval, max_concurrent, Arc::default())
+            .await
+    }
+
+    /// [`Self::bulk_fetch_historical`], counting finished symbols in `completed` as it goes
+    pub async fn bulk_fetch_historical_tracked(
+        self: &Arc<Self>,
+        symbols: Vec<&str>,
+        interval: &str,
+        max_concurrent: usize,
+        completed: Arc<AtomicUsize>,
     ) -> Result<Vec<(String, Result<Vec<HistoricalPrice>>)>> {
         // Create semaphore for this bulk operation
-        let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1).min(10)));
+        let semaphore = Arc::new(Semaphore::new(max_concurrent.clamp(1, MAX_BULK_CONCURRENCY)));
         let mut handles = Vec::new();
 
         // Convert symbols to owned strings for async tasks
@@ -566,12 +1151,15 @@ impl YahooFinanceService {
             let service = Arc::clone(self);
             let interval = interval_owned.clone();
             let semaphore = semaphore.clone();
+            let completed = completed.clone();
             
-            let handle = tokio::spawn(async move {
+            let handle = tokio::spawn(BULK_OPERATION.scope((), async move {
                 let _permit = semaphore.acquire().await;
+                let _bulkhead = service.bulk_semaphore.acquire().await;
                 let result = service.fetch_historical_data(&symbol, &interval, false).await;
+                completed.fetch_add(1, Ordering::Relaxed);
                 (symbol, result)
-            });
+            }));
             
             handles.push(handle);
         }
@@ -648,48 +1236,2007 @@ impl YahooFinanceService {
         }
 
         // Check Yahoo API rate limit
-        self.check_yahoo_api_rate_limit().await?;
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Other).await?;
 
         // Try Yahoo Finance API
-        let result = {
-            let provider = self.provider.lock().await;
-            provider.search_ticker(symbol).await
-        };
-        
+        let result = self.provider.search(symbol).await;
+
         match result {
-            Ok(response) => Ok(!response.quotes.is_empty()),
+            Ok(matches) => Ok(!matches.is_empty()),
             Err(_) => Ok(false),
         }
     }
 
-    /// Get database statistics
-    pub async fn get_stats(&self) -> Result<serde_json::Value> {
-        let stats = self.db.get_database_stats().await?;
-        Ok(serde_json::json!({
-            "database": stats,
-            "cache": {
-                "historical_cache_size": self.historical_cache.len(),
-                "quote_cache_size": self.quote_cache.len(),
-                "profile_cache_size": self.profile_cache.len(),
+    /// Canonical provider ticker for an upper-cased, validated symbol typed by a user
+    pub async fn resolve_symbol(&self, input: &str) -> String {
+        self.resolve(input).await.symbol
+    }
+
+    /// Resolve `input` through the alias table, then as an ISIN, then, for bare tickers, the
+    /// first listing on an exchange from SYMBOL_EXCHANGE_PREFERENCE. Anything else passes
+    /// through unchanged.
+    pub async fn resolve(&self, input: &str) -> SymbolResolution {
+        if let Some(cached) = self.resolve_cache.get(input) {
+            return cached;
+        }
+
+        let resolution = |symbol: String, source: &str| SymbolResolution {
+            input: input.to_string(),
+            symbol,
+            source: source.to_string(),
+        };
+        let resolved = match self.db.get_alias_target(input).await {
+            Ok(Some(symbol)) => resolution(symbol, "alias"),
+            Ok(None) if identifiers::is_valid_isin(input) => {
+                match self.lookup_identifier(IdentifierKind::Isin, input).await {
+                    Ok(Some(mapping)) => resolution(mapping.symbol, "isin"),
+                    Ok(None) => resolution(input.to_string(), "input"),
+                    Err(e) => {
+                        warn!("Failed to look up ISIN {}: {}", input, e);
+                        return resolution(input.to_string(), "input");
+                    }
+                }
+            }
+            Ok(None) => match self.preferred_listing(input).await {
+                Some(symbol) if symbol != input => resolution(symbol, "exchange"),
+                _ => resolution(input.to_string(), "input"),
             },
-            "rate_limits": {
-                "api_requests_per_minute": self.config.requests_per_minute,
-                "yahoo_api_requests_per_minute": self.config.yahoo_api_requests_per_minute,
+            Err(e) => {
+                // Fall back to the input without caching so the next request retries
+                warn!("Failed to look up alias {}: {}", input, e);
+                return resolution(input.to_string(), "input");
             }
-        }))
+        };
+
+        self.resolve_cache
+            .insert(input.to_string(), resolved.clone(), Duration::from_secs(24 * 3600));
+        resolved
     }
 
-    /// Clear expired cache entries
-    pub fn cleanup_cache(&self) {
-        self.historical_cache
-            .retain(|_, cached| !cached.is_expired());
-        self.quote_cache.retain(|_, cached| !cached.is_expired());
-        self.profile_cache.retain(|_, cached| !cached.is_expired());
+    /// First listing of a bare ticker on a preferred exchange, known locally or found by
+    /// the provider's search
+    async fn preferred_listing(&self, input: &str) -> Option<String> {
+        // Suffixed tickers and pairs such as BTC-USD already name their market
+        if self.exchange_preference.is_empty() || input.contains(['.', '-']) {
+            return None;
+        }
+        let candidates: Vec<String> = self
+            .exchange_preference
+            .iter()
+            .map(|code| match code.as_str() {
+                "US" => input.to_string(),
+                suffix => format!("{}.{}", input, suffix),
+            })
+            .collect();
+
+        for candidate in &candidates {
+            if matches!(self.db.get_symbol(candidate).await, Ok(Some(_))) {
+                return Some(candidate.clone());
+            }
+        }
+
+        if self.check_yahoo_api_rate_limit(UpstreamFeature::Other).await.is_err() {
+            return None;
+        }
+        let matches = match self.provider.search(input).await {
+            Ok(matches) => matches,
+            Err(e) => {
+                warn!("Symbol search failed while resolving {}: {}", input, e);
+                return None;
+            }
+        };
+        candidates
+            .into_iter()
+            .find(|candidate| matches.iter().any(|m| m.symbol.eq_ignore_ascii_case(candidate)))
     }
 
-    // Additional optimized methods...
-    pub async fn get_comprehensive_quote(&self, symbol: &str) -> Result<serde_json::Value> {
-        let overview = self.get_symbol_overview(symbol).await?;
+    /// Ticker for a validated ISIN or CUSIP. Stored mappings are used first; otherwise the
+    /// provider's search is asked (CUSIPs as the equivalent US ISIN), then OpenFIGI, and the
+    /// answer is stored. `None` when no source knows the identifier.
+    pub async fn lookup_identifier(
+        &self,
+        kind: IdentifierKind,
+        identifier: &str,
+    ) -> Result<Option<SecurityIdentifier>> {
+        if let Some(mapping) = self.db.get_security_identifier(identifier).await? {
+            return Ok(Some(mapping));
+        }
+
+        let isin = match kind {
+            IdentifierKind::Isin => Some(identifier.to_string()),
+            IdentifierKind::Cusip => identifiers::cusip_to_isin(identifier),
+        };
+        let mut found = None;
+        if let Some(isin) = isin {
+            self.check_storing_fetch(UpstreamFeature::Other).await?;
+            match self.provider.search(&isin).await {
+                // Providers that echo any well-formed query back do not know the identifier
+                Ok(matches) => {
+                    found = matches
+                        .into_iter()
+                        .find(|m| m.symbol != isin && crate::validation::validate_symbol(&m.symbol).is_ok())
+                        .map(|m| (m, "search"));
+                }
+                Err(e) => warn!("Symbol search failed for {}: {}", isin, e),
+            }
+        }
+        if found.is_none() {
+            if let Some(api_key) = &self.openfigi {
+                found = identifiers::openfigi_lookup(api_key.as_deref(), kind, identifier)
+                    .await?
+                    .map(|m| (m, "openfigi"));
+            }
+        }
+
+        let Some((listing, source)) = found else {
+            return Ok(None);
+        };
+        let mapping = SecurityIdentifier {
+            identifier: identifier.to_string(),
+            kind: kind.as_str().to_string(),
+            symbol: listing.symbol.to_uppercase(),
+            name: listing.name,
+            exchange: Some(listing.exchange).filter(|e| !e.is_empty()),
+            source: source.to_string(),
+            created_at: Utc::now(),
+        };
+        info!("Mapped {} {} to {}", kind.as_str().to_uppercase(), identifier, mapping.symbol);
+        self.db.upsert_security_identifier(&mapping).await?;
+        Ok(Some(mapping))
+    }
+
+    /// [`resolve_symbol`](Self::resolve_symbol) for each of `inputs`, dropping duplicates
+    pub async fn resolve_symbols(&self, inputs: Vec<String>) -> Vec<String> {
+        let mut resolved: Vec<String> = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let symbol = self.resolve_symbol(&input).await;
+            if !resolved.contains(&symbol) {
+                resolved.push(symbol);
+            }
+        }
+        resolved
+    }
+
+    /// Forget cached resolutions after the alias table changed, here and on other instances
+    pub fn clear_resolutions(&self) {
+        self.invalidate_caches(CacheScope::Resolutions);
+    }
+
+    /// Type-ahead suggestions from local symbols, cached per query for fast repeated keystrokes
+    pub async fn suggest_symbols(&self, query: &str, limit: i32) -> Result<Vec<SymbolSuggestion>> {
+        let cache_key = format!("{}:{}", query.to_uppercase(), limit);
+
+        if let Some(cached) = self.suggest_cache.get(&cache_key) {
+            return Ok(cached);
+        }
+
+        let suggestions = self.db.suggest_symbols(query, limit).await?;
+
+        let ttl = Duration::from_secs(600); // 10 minutes; cleared when new symbols are discovered
+        self.suggest_cache
+            .insert(cache_key, suggestions.clone(), ttl);
+
+        Ok(suggestions)
+    }
+
+    /// Search local symbols, falling back to a Yahoo Finance lookup when nothing matches.
+    /// Symbols discovered remotely are persisted so later searches are served locally.
+    pub async fn search_symbols(
+        &self,
+        query: &str,
+        filter: &SymbolFilter,
+        limit: i32,
+    ) -> Result<Vec<Symbol>> {
+        let local = self.db.search_symbols(query, filter, limit).await?;
+        // A universe has a fixed membership, so nothing found remotely could belong to it
+        if !local.is_empty() || filter.universe.is_some() {
+            return Ok(local);
+        }
+
+        // Searching remotely is a nice-to-have; an exhausted budget or a read-only instance
+        // just yields no results
+        if self.check_storing_fetch(UpstreamFeature::Other).await.is_err() {
+            warn!("Skipping Yahoo symbol search for '{}': rate limit reached", query);
+            return Ok(local);
+        }
+
+        let matches = match self.provider.search(query).await {
+            Ok(matches) => matches,
+            Err(e) => {
+                warn!("Symbol search via {} failed for '{}': {}", self.provider.name(), query, e);
+                return Ok(local);
+            }
+        };
+
+        let mut symbols = Vec::new();
+        for item in matches {
+            let ticker = item.symbol.to_uppercase();
+            if crate::validation::validate_symbol(&ticker).is_err() {
+                continue;
+            }
+
+            self.db.upsert_symbol(&ticker, item.name.as_deref()).await?;
+            let metadata = SymbolMetadata {
+                exchange: Some(item.exchange).filter(|e| !e.is_empty()),
+                ..Default::default()
+            };
+            self.db.update_symbol_metadata(&ticker, &metadata).await?;
+
+            if let Some(symbol) = self.db.get_symbol(&ticker).await? {
+                if filter.matches(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+
+        if !symbols.is_empty() {
+            self.suggest_cache.clear();
+        }
+
+        info!(
+            "Discovered {} symbols from {} for '{}'",
+            symbols.len(),
+            self.provider.name(),
+            query
+        );
+        symbols.truncate(limit.max(0) as usize);
+        Ok(symbols)
+    }
+
+    /// Store a universe's constituents; symbols it introduces become searchable right away
+    pub async fn import_universe(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        constituents: &[UniverseConstituent],
+        replace: bool,
+    ) -> Result<(usize, usize)> {
+        let counts = self.db.import_universe(name, description, constituents, replace).await?;
+        self.suggest_cache.clear();
+        info!(
+            "Imported {} constituents into universe '{}' ({} added, {} removed)",
+            constituents.len(),
+            name,
+            counts.0,
+            counts.1
+        );
+        Ok(counts)
+    }
+
+    /// Stored symbols with their latest price and daily change. Quotes rarely carry a change,
+    /// so it is derived from the previous stored daily close when missing.
+    pub async fn get_symbol_snapshots(&self, filter: &SymbolFilter) -> Result<Vec<SymbolSnapshot>> {
+        let mut snapshots = self.db.get_symbol_snapshots(filter).await?;
+
+        for snapshot in snapshots.iter_mut() {
+            if snapshot.change_percent.is_some() {
+                continue;
+            }
+
+            let history = self
+                .db
+                .get_historical_prices(&snapshot.symbol, None, None, Some(2))
+                .await?;
+            let Some(latest) = history.first() else {
+                continue;
+            };
+
+            // A quote newer than the last daily bar is compared against that bar's close
+            let (price, previous_close) = match (snapshot.price, snapshot.as_of) {
+                (Some(price), Some(as_of)) if as_of.date_naive() > latest.timestamp.date_naive() => {
+                    (price, Some(latest.close))
+                }
+                _ => (latest.close, history.get(1).map(|p| p.close)),
+            };
+
+            if snapshot.price.is_none() {
+                snapshot.price = Some(price);
+                snapshot.volume = Some(latest.volume);
+                snapshot.as_of = Some(latest.timestamp);
+            }
+
+            if let Some(previous_close) = previous_close.filter(|c| !c.is_zero()) {
+                snapshot.change_percent =
+                    Some(((price - previous_close) / previous_close * Decimal::from(100)).round_dp(4));
+            }
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Average change and breadth for a sector, broken down by industry
+    pub async fn get_sector_performance(&self, sector: &str) -> Result<Option<SectorPerformance>> {
+        let filter = SymbolFilter {
+            sector: Some(sector.to_string()),
+            ..Default::default()
+        };
+        let members = self.get_symbol_snapshots(&filter).await?;
+        if members.is_empty() {
+            return Ok(None);
+        }
+
+        let sector_name = members[0].sector.clone().unwrap_or_else(|| sector.to_string());
+
+        let mut by_industry: std::collections::BTreeMap<String, Vec<&SymbolSnapshot>> =
+            std::collections::BTreeMap::new();
+        for member in &members {
+            let industry = member.industry.clone().unwrap_or_else(|| "Unknown".to_string());
+            by_industry.entry(industry).or_default().push(member);
+        }
+
+        let industries = by_industry
+            .into_iter()
+            .map(|(industry, snapshots)| GroupPerformance::from_snapshots(industry, snapshots))
+            .collect();
+
+        Ok(Some(SectorPerformance {
+            sector: GroupPerformance::from_snapshots(sector_name, &members),
+            industries,
+            members,
+        }))
+    }
+
+    /// Compare `symbol` with up to `limit` active stored symbols of its sector, or only of its
+    /// industry. Peers are measured from stored candles alone; `None` when no sector is known
+    /// for the symbol even after fetching its profile.
+    pub async fn get_peers(&self, symbol: &str, limit: usize, industry_only: bool) -> Result<Option<PeerComparison>> {
+        let mut subject = self.db.get_symbol(symbol).await?;
+        if subject.as_ref().is_none_or(|s| s.sector.is_none()) {
+            if let Err(e) = self.fetch_company_profile(symbol, false).await {
+                warn!("Could not fetch profile of {} for peers: {}", symbol, e);
+            }
+            subject = self.db.get_symbol(symbol).await?;
+        }
+        let Some(subject) = subject else { return Ok(None) };
+        let Some(sector) = subject.sector.clone() else { return Ok(None) };
+
+        let same_industry = |candidate: &Symbol| match (&subject.industry, &candidate.industry) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => false,
+        };
+        let filter = SymbolFilter {
+            sector: Some(sector.clone()),
+            status: Some(SymbolStatus::Active),
+            ..Default::default()
+        };
+        let mut candidates: Vec<(Symbol, bool)> = self
+            .db
+            .get_all_symbols(&filter)
+            .await?
+            .into_iter()
+            .filter(|candidate| candidate.symbol != subject.symbol)
+            .map(|candidate| {
+                let same = same_industry(&candidate);
+                (candidate, same)
+            })
+            .filter(|(_, same)| *same || !industry_only)
+            .collect();
+        crate::peers::rank(&mut candidates, subject.market_cap);
+        candidates.truncate(limit);
+
+        let bars = Some(TRADING_DAYS_PER_YEAR as i32 + 1);
+        let dividends_since = Utc::now().date_naive() - chrono::Duration::days(366);
+        let candles = self.get_historical_data(symbol, None, None, Some("1d"), bars).await?;
+        let dividends = self.db.get_dividends(symbol, dividends_since).await?;
+        let subject_metrics = crate::peers::metrics(&subject, true, &candles, &dividends);
+
+        let mut peers = Vec::with_capacity(candidates.len());
+        for (candidate, same) in &candidates {
+            let candles = self.db.get_historical_prices(&candidate.symbol, None, None, bars).await?;
+            let dividends = self.db.get_dividends(&candidate.symbol, dividends_since).await?;
+            peers.push(crate::peers::metrics(candidate, *same, &candles, &dividends));
+        }
+
+        Ok(Some(PeerComparison {
+            symbol: subject.symbol.clone(),
+            sector,
+            industry: subject.industry.clone(),
+            subject: subject_metrics,
+            medians: crate::peers::medians(&peers),
+            peers,
+        }))
+    }
+
+    /// Score active stored symbols under `model`, from stored candles and dividends only.
+    /// `universe` is "stored", "portfolio" or the name of a stored universe, as for movers;
+    /// percentiles are taken over the whole universe before keeping the top `limit`.
+    pub async fn get_rankings(
+        &self,
+        model: RankingModel,
+        universe: &str,
+        sector: Option<&str>,
+        limit: usize,
+        risk_free_rate: f64,
+    ) -> Result<Rankings> {
+        let filter = SymbolFilter {
+            sector: sector.map(str::to_string),
+            universe: Some(universe.to_string()).filter(|u| u != "stored" && u != "portfolio"),
+            status: Some(SymbolStatus::Active),
+            ..Default::default()
+        };
+        let mut symbols = self.db.get_all_symbols(&filter).await?;
+
+        if universe == "portfolio" {
+            let held: std::collections::HashSet<String> = self
+                .db
+                .get_all_portfolio_holdings()
+                .await?
+                .into_iter()
+                .map(|h| h.symbol)
+                .collect();
+            symbols.retain(|s| held.contains(&s.symbol));
+        }
+
+        let bars = Some(TRADING_DAYS_PER_YEAR as i32 + 1);
+        let dividends_since = Utc::now().date_naive() - chrono::Duration::days(366);
+        let daily_risk_free = risk::daily_rate(risk_free_rate);
+        let mut measured = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let candles = self.db.get_historical_prices(&symbol.symbol, None, None, bars).await?;
+            let dividends = self.db.get_dividends(&symbol.symbol, dividends_since).await?;
+            let values = crate::ranking::factor_values(&symbol, &candles, &dividends, daily_risk_free);
+            measured.push((symbol, values));
+        }
+
+        let mut entries = crate::ranking::rank(&model, measured);
+        let symbol_count = entries.len();
+        entries.truncate(limit);
+
+        Ok(Rankings {
+            model,
+            universe: universe.to_string(),
+            symbol_count,
+            risk_free_rate,
+            entries,
+        })
+    }
+
+    /// Rank stored symbols by daily change and volume surge. `universe` is "stored" for every
+    /// known symbol, "portfolio" for current holdings only, or the name of a stored universe.
+    pub async fn get_market_movers(&self, universe: &str, limit: usize) -> Result<MarketMovers> {
+        let filter = SymbolFilter {
+            universe: Some(universe.to_string()).filter(|u| u != "stored" && u != "portfolio"),
+            ..Default::default()
+        };
+        let mut snapshots = self.get_symbol_snapshots(&filter).await?;
+
+        if universe == "portfolio" {
+            let held: std::collections::HashSet<String> = self
+                .db
+                .get_all_portfolio_holdings()
+                .await?
+                .into_iter()
+                .map(|h| h.symbol)
+                .collect();
+            snapshots.retain(|s| held.contains(&s.symbol));
+        }
+
+        let mut movers = Vec::new();
+        for snapshot in snapshots {
+            if snapshot.change_percent.is_none() {
+                continue;
+            }
+
+            // Trailing average over the bars before the latest one
+            let history = self
+                .db
+                .get_historical_prices(&snapshot.symbol, None, None, Some(21))
+                .await?;
+            let trailing: Vec<i64> = history.iter().skip(1).map(|p| p.volume).collect();
+            let average_volume = if trailing.is_empty() {
+                None
+            } else {
+                Some(trailing.iter().sum::<i64>() / trailing.len() as i64)
+            };
+            let volume_ratio = match (snapshot.volume, average_volume) {
+                (Some(volume), Some(average)) if average > 0 => {
+                    Some((Decimal::from(volume) / Decimal::from(average)).round_dp(2))
+                }
+                _ => None,
+            };
+
+            movers.push(MarketMover {
+                symbol: snapshot.symbol,
+                name: snapshot.name,
+                exchange: snapshot.exchange,
+                price: snapshot.price,
+                change_percent: snapshot.change_percent,
+                volume: snapshot.volume,
+                average_volume,
+                volume_ratio,
+            });
+        }
+
+        let symbol_count = movers.len();
+
+        let mut gainers: Vec<MarketMover> = movers
+            .iter()
+            .filter(|m| m.change_percent.is_some_and(|c| c > Decimal::ZERO))
+            .cloned()
+            .collect();
+        gainers.sort_by_key(|m| std::cmp::Reverse(m.change_percent));
+        gainers.truncate(limit);
+
+        let mut losers: Vec<MarketMover> = movers
+            .iter()
+            .filter(|m| m.change_percent.is_some_and(|c| c < Decimal::ZERO))
+            .cloned()
+            .collect();
+        losers.sort_by_key(|m| m.change_percent);
+        losers.truncate(limit);
+
+        let mut volume_surges: Vec<MarketMover> =
+            movers.into_iter().filter(|m| m.volume_ratio.is_some()).collect();
+        volume_surges.sort_by_key(|m| std::cmp::Reverse(m.volume_ratio));
+        volume_surges.truncate(limit);
+
+        Ok(MarketMovers {
+            universe: universe.to_string(),
+            symbol_count,
+            gainers,
+            losers,
+            volume_surges,
+        })
+    }
+
+    /// Forward twelve-month dividend income per holding, projected from trailing payouts, and
+    /// a calendar of reported or projected ex-dates within `calendar_days`.
+    pub async fn get_portfolio_income(&self, calendar_days: i64) -> Result<PortfolioIncome> {
+        let holdings = self.db.get_all_portfolio_holdings().await?;
+        let today = Utc::now().date_naive();
+        let year_ago = today - chrono::Duration::days(365);
+        let horizon = today + chrono::Duration::days(calendar_days);
+
+        let mut incomes = Vec::with_capacity(holdings.len());
+        let mut calendar = Vec::new();
+        let mut total_income = Decimal::ZERO;
+        let mut total_value = Decimal::ZERO;
+
+        for holding in holdings {
+            let mut dividends = self.db.get_dividends(&holding.symbol, year_ago).await?;
+            if dividends.is_empty() {
+                // Dividends are recorded with daily history; fetch it once if we have none
+                if let Err(e) = self.fetch_historical_data(&holding.symbol, "1d", false).await {
+                    debug!("Could not refresh history for {}: {}", holding.symbol, e);
+                }
+                dividends = self.db.get_dividends(&holding.symbol, year_ago).await?;
+            }
+
+            let (trailing, announced): (Vec<Dividend>, Vec<Dividend>) =
+                dividends.into_iter().partition(|d| d.ex_date <= today);
+
+            let trailing_per_share: Decimal = trailing.iter().map(|d| d.amount).sum();
+            let payments_per_year = trailing.len();
+            let last = trailing.last();
+            let price = holding.current_price.unwrap_or(holding.purchase_price);
+            let projected = trailing_per_share * holding.quantity;
+            let percent_of = |base: Decimal| {
+                (base > Decimal::ZERO)
+                    .then(|| (trailing_per_share / base * Decimal::ONE_HUNDRED).round_dp(2))
+            };
+
+            for dividend in announced.iter().filter(|d| d.ex_date <= horizon) {
+                calendar.push(ExDividendEntry {
+                    symbol: holding.symbol.clone(),
+                    ex_date: dividend.ex_date,
+                    amount_per_share: dividend.amount,
+                    expected_payment: (dividend.amount * holding.quantity).round_dp(2),
+                    estimated: false,
+                });
+            }
+
+            // Project the regular schedule forward from the latest known ex-date
+            if let Some(anchor) = announced.last().or(last).filter(|_| payments_per_year > 0) {
+                let spacing = chrono::Duration::days(365 / payments_per_year as i64);
+                let mut next = anchor.ex_date + spacing;
+                while next <= horizon {
+                    if next > today {
+                        calendar.push(ExDividendEntry {
+                            symbol: holding.symbol.clone(),
+                            ex_date: next,
+                            amount_per_share: anchor.amount,
+                            expected_payment: (anchor.amount * holding.quantity).round_dp(2),
+                            estimated: true,
+                        });
+                    }
+                    next += spacing;
+                }
+            }
+
+            total_income += projected;
+            total_value += price * holding.quantity;
+
+            incomes.push(HoldingIncome {
+                symbol: holding.symbol,
+                quantity: holding.quantity,
+                trailing_dividend_per_share: trailing_per_share,
+                payments_per_year,
+                last_ex_date: last.map(|d| d.ex_date),
+                projected_annual_income: projected.round_dp(2),
+                yield_on_cost: percent_of(holding.purchase_price),
+                current_yield: percent_of(price),
+            });
+        }
+
+        incomes.sort_by_key(|h| std::cmp::Reverse(h.projected_annual_income));
+        calendar.sort_by(|a, b| a.ex_date.cmp(&b.ex_date).then_with(|| a.symbol.cmp(&b.symbol)));
+
+        Ok(PortfolioIncome {
+            projected_annual_income: total_income.round_dp(2),
+            portfolio_yield: (total_value > Decimal::ZERO)
+                .then(|| (total_income / total_value * Decimal::ONE_HUNDRED).round_dp(2)),
+            holdings: incomes,
+            calendar,
+        })
+    }
+
+    /// Least time between two firings of an alert with this cooldown override
+    fn alert_cooldown(&self, minutes: Option<i64>) -> chrono::Duration {
+        minutes
+            .map(chrono::Duration::minutes)
+            .or_else(|| chrono::Duration::from_std(self.alerts.cooldown).ok())
+            .unwrap_or_default()
+    }
+
+    /// Check a holding's stop and target against a new `price`. A breach fires the
+    /// `price_alert.fired` webhook plus the holding's notification channels, unless the
+    /// holding's alerts are muted or one fired within the cooldown. A level that fired re-arms
+    /// once the price moves back past it by the re-arm percentage, so a price hovering at the
+    /// level does not fire every refresh. Also advances the trailing stop's high-water mark.
+    pub async fn check_exit_levels(
+        &self,
+        holding: &PortfolioHolding,
+        price: Decimal,
+        notifications: &NotificationConfig,
+    ) -> Result<()> {
+        let mut state = holding.clone();
+        if state.trailing_stop_percent.is_some() {
+            state.high_water_mark = Some(state.high_water_mark.map_or(price, |high| high.max(price)));
+        }
+
+        let now = Utc::now();
+        let rearm = holding.alert_rearm_percent.unwrap_or(self.alerts.rearm_percent) / Decimal::ONE_HUNDRED;
+        let silenced = holding.alerts_muted_until.is_some_and(|until| until > now)
+            || holding
+                .last_alert_at
+                .is_some_and(|at| now - at < self.alert_cooldown(holding.alert_cooldown_minutes));
+
+        let mut breaches = Vec::new();
+        let stops = [("stop_loss", state.stop_loss), ("trailing_stop", state.trailing_stop())];
+        // The higher stop is the one in force
+        if let Some((kind, stop)) = stops
+            .into_iter()
+            .filter_map(|(kind, stop)| Some((kind, stop?)))
+            .max_by_key(|(_, stop)| *stop)
+        {
+            if state.stop_triggered_at.is_some() {
+                if price >= stop * (Decimal::ONE + rearm) {
+                    state.stop_triggered_at = None;
+                }
+            } else if price <= stop && !silenced {
+                state.stop_triggered_at = Some(now);
+                breaches.push((kind, stop));
+            }
+        }
+        if let Some(target) = state.target_price {
+            if state.target_triggered_at.is_some() {
+                if price <= target * (Decimal::ONE - rearm) {
+                    state.target_triggered_at = None;
+                }
+            } else if price >= target && !silenced {
+                state.target_triggered_at = Some(now);
+                breaches.push(("target_price", target));
+            }
+        }
+        if !breaches.is_empty() {
+            state.last_alert_at = Some(now);
+        }
+
+        if state.high_water_mark != holding.high_water_mark
+            || state.stop_triggered_at != holding.stop_triggered_at
+            || state.target_triggered_at != holding.target_triggered_at
+            || state.last_alert_at != holding.last_alert_at
+        {
+            self.db.update_holding_alert_state(&state).await?;
+        }
+
+        let channels = notify::selected_channels(
+            &notify::configured_channels(notifications),
+            holding.alert_channels.as_deref(),
+        );
+        for (kind, level) in breaches {
+            info!("🔔 {} {} hit at {} (level {})", holding.symbol, kind, price, level);
+            webhooks::emit(
+                self.db.clone(),
+                WebhookEvent::PriceAlertFired,
+                serde_json::json!({
+                    "holding_id": holding.id,
+                    "symbol": holding.symbol,
+                    "kind": kind,
+                    "level": level,
+                    "price": price,
+                    "as_of": now,
+                }),
+            );
+            if !channels.is_empty() {
+                let direction = if kind == "target_price" { "rose to" } else { "fell to" };
+                notify::dispatch(
+                    self.db.clone(),
+                    notifications.clone(),
+                    channels.clone(),
+                    format!("holding:{}", holding.symbol),
+                    format!("{} {} hit", holding.symbol, kind.replace('_', " ")),
+                    format!(
+                        "{} {} {}, crossing its {} of {}.",
+                        holding.symbol,
+                        direction,
+                        price,
+                        kind.replace('_', " "),
+                        level
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check every signal alert against the latest daily closes of its symbol, skipping
+    /// symbols whose market is closed. An alert fires once per crossing close: the
+    /// `signal_alert.fired` webhook plus the alert's notification channels. Muted alerts and
+    /// alerts that fired within their cooldown are not checked. Returns the number of alerts
+    /// fired.
+    pub async fn evaluate_signal_alerts(&self, notifications: &NotificationConfig) -> Result<usize> {
+        let alerts = self.db.get_signal_alerts().await?;
+        let now = Utc::now();
+        let mut by_symbol: BTreeMap<String, Vec<SignalAlert>> = BTreeMap::new();
+        for alert in alerts {
+            let silenced = alert.muted_until.is_some_and(|until| until > now)
+                || alert
+                    .last_fired_at
+                    .is_some_and(|at| now - at < self.alert_cooldown(alert.cooldown_minutes));
+            if !silenced {
+                by_symbol.entry(alert.symbol.clone()).or_default().push(alert);
+            }
+        }
+
+        // Custom indicators watched by alerts, by id
+        let mut expressions: HashMap<Uuid, Expression> = HashMap::new();
+        for indicator in self.db.get_alerted_custom_indicators().await? {
+            match Expression::parse(&indicator.expression) {
+                Ok(expression) => {
+                    expressions.insert(indicator.id, expression);
+                }
+                Err(e) => warn!("Skipping alerts on custom indicator {}: {}", indicator.name, e),
+            }
+        }
+
+        let configured = notify::configured_channels(notifications);
+        let delisted = self.delisted_symbols().await?;
+        let mut fired = 0;
+        for (symbol, alerts) in by_symbol {
+            if delisted.contains(&symbol) {
+                continue;
+            }
+            // Same heuristic as new holdings: BTC-USD style tickers trade around the clock
+            let asset_type = if symbol.contains('-') { "crypto" } else { "stock" };
+            if !market_calendar::should_refresh(&symbol, asset_type, now) {
+                continue;
+            }
+            // A crossing compares the latest settled value with the one before it
+            let needed = alerts
+                .iter()
+                .map(|a| match a.indicator_id.and_then(|id| expressions.get(&id)) {
+                    Some(expression) => expression.lookback() + 1,
+                    None => a.condition.min_closes(),
+                })
+                .max()
+                .unwrap_or(0)
+                .min(MAX_HISTORICAL_LIMIT as usize);
+            let mut history = match self
+                .get_historical_data(&symbol, None, None, Some("1d"), Some(needed as i32))
+                .await
+            {
+                Ok(history) => history,
+                Err(e) => {
+                    warn!("Could not evaluate signal alerts for {}: {}", symbol, e);
+                    continue;
+                }
+            };
+            history.sort_by_key(|p| p.timestamp);
+            let Some(latest) = history.last() else { continue };
+            let candle_date = latest.timestamp.date_naive();
+            let close = latest.close;
+            let closes: Vec<f64> = history.iter().filter_map(|p| p.close.to_f64()).collect();
+
+            for alert in alerts {
+                if alert.last_fired_on == Some(candle_date) {
+                    continue;
+                }
+                let crossed = match alert.condition {
+                    SignalCondition::IndicatorCrossAbove | SignalCondition::IndicatorCrossBelow => {
+                        let Some(expression) = alert.indicator_id.and_then(|id| expressions.get(&id)) else {
+                            continue;
+                        };
+                        let threshold = alert.threshold.and_then(|t| t.to_f64()).unwrap_or_default();
+                        let above = alert.condition == SignalCondition::IndicatorCrossAbove;
+                        expression.crossed(&history, threshold, above)
+                    }
+                    _ => alert.condition.crossed(&closes),
+                };
+                let Some(value) = crossed else { continue };
+                self.db.mark_signal_alert_fired(alert.id, candle_date, now).await?;
+                fired += 1;
+
+                let value = Decimal::from_f64_retain(value).map(|v| v.round_dp(4));
+                info!("🔔 {} {} at {}", symbol, alert.condition.as_str(), close);
+                webhooks::emit(
+                    self.db.clone(),
+                    WebhookEvent::SignalAlertFired,
+                    serde_json::json!({
+                        "alert_id": alert.id,
+                        "symbol": symbol,
+                        "condition": alert.condition,
+                        "indicator": alert.indicator,
+                        "threshold": alert.threshold,
+                        "value": value,
+                        "close": close,
+                        "candle_date": candle_date,
+                        "as_of": now,
+                    }),
+                );
+                let channels = notify::selected_channels(&configured, alert.channels.as_deref());
+                if !channels.is_empty() {
+                    let (title, description) = match &alert.indicator {
+                        Some(indicator) => {
+                            let threshold = alert.threshold.unwrap_or_default();
+                            let direction = if alert.condition == SignalCondition::IndicatorCrossAbove {
+                                "above"
+                            } else {
+                                "below"
+                            };
+                            (
+                                format!("{} {} crossed {} {}", symbol, indicator, direction, threshold),
+                                format!(
+                                    "{} crossed {} {} at {}",
+                                    indicator,
+                                    direction,
+                                    threshold,
+                                    value.unwrap_or_default()
+                                ),
+                            )
+                        }
+                        None => (
+                            format!("{} {}", symbol, alert.condition.as_str().replace('_', " ")),
+                            alert.condition.describe().to_string(),
+                        ),
+                    };
+                    notify::dispatch(
+                        self.db.clone(),
+                        notifications.clone(),
+                        channels,
+                        format!("signal:{}", alert.id),
+                        title,
+                        format!("{}: {} (close {} on {}).", symbol, description, close, candle_date),
+                    );
+                }
+            }
+        }
+        Ok(fired)
+    }
+
+    /// Look for unusual recent moves in every active, listed symbol's stored daily bars and
+    /// record the new ones, announcing each as a webhook and, if configured, a notification.
+    /// Returns the number of new anomalies.
+    pub async fn scan_anomalies(&self, config: &AnomalyConfig, notifications: &NotificationConfig) -> Result<usize> {
+        let filter = SymbolFilter {
+            status: Some(SymbolStatus::Active),
+            ..Default::default()
+        };
+        let symbols = self.db.get_all_symbols(&filter).await?;
+        let delisted = self.delisted_symbols().await?;
+        let channels = if config.notify {
+            notify::configured_channels(notifications)
+        } else {
+            Vec::new()
+        };
+
+        let bars = Some((config.lookback_days + ANOMALY_RECENT_BARS + 1) as i32);
+        let mut found = 0;
+        for symbol in symbols.iter().filter(|s| !delisted.contains(&s.symbol)) {
+            let mut candles = self.db.get_historical_prices(&symbol.symbol, None, None, bars).await?;
+            candles.reverse();
+            let detected = crate::anomaly::detect(&symbol.symbol, &candles, config);
+            if detected.is_empty() {
+                continue;
+            }
+
+            for anomaly in self.db.insert_anomalies(&detected).await? {
+                found += 1;
+                info!(
+                    "🔎 {} {} anomaly on {} (z {})",
+                    anomaly.symbol,
+                    anomaly.kind.as_str(),
+                    anomaly.date,
+                    anomaly.z_score
+                );
+                webhooks::emit(
+                    self.db.clone(),
+                    WebhookEvent::AnomalyDetected,
+                    serde_json::to_value(&anomaly)?,
+                );
+                if !channels.is_empty() {
+                    let description = match anomaly.kind {
+                        AnomalyKind::Return => format!("moved {}%", anomaly.value),
+                        AnomalyKind::Volume => format!("traded {} shares vs {} average", anomaly.value, anomaly.baseline),
+                    };
+                    notify::dispatch(
+                        self.db.clone(),
+                        notifications.clone(),
+                        channels.clone(),
+                        format!("anomaly:{}", anomaly.symbol),
+                        format!("{} unusual {}", anomaly.symbol, anomaly.kind.as_str()),
+                        format!(
+                            "{} {} on {} (z-score {}, close {}).",
+                            anomaly.symbol, description, anomaly.date, anomaly.z_score, anomaly.close
+                        ),
+                    );
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// Monte Carlo projection of current portfolio value. Returns `None` when the portfolio is
+    /// empty or its holdings share too few days of stored history to fit returns on.
+    pub async fn project_portfolio(
+        &self,
+        method: ProjectionMethod,
+        horizon_days: u32,
+        simulations: u32,
+        lookback_days: u32,
+        seed: Option<u64>,
+    ) -> Result<Option<PortfolioProjection>> {
+        let holdings = self.db.get_all_portfolio_holdings().await?;
+        if holdings.is_empty() {
+            return Ok(None);
+        }
+
+        // Current value and close-by-date series for every holding
+        let mut weights = Vec::with_capacity(holdings.len());
+        let mut series = Vec::with_capacity(holdings.len());
+        for holding in &holdings {
+            let history = self
+                .db
+                .get_historical_prices(&holding.symbol, None, None, Some(lookback_days as i32 + 1))
+                .await?;
+            let closes: HashMap<NaiveDate, f64> = history
+                .iter()
+                .filter_map(|p| Some((p.timestamp.date_naive(), p.close.to_f64()?)))
+                .collect();
+            let value = holding
+                .current_value
+                .or_else(|| history.first().map(|p| p.close * holding.quantity))
+                .unwrap_or(holding.purchase_price * holding.quantity);
+            weights.push(value.to_f64().unwrap_or_default());
+            series.push(closes);
+        }
+
+        let initial_value: f64 = weights.iter().sum();
+        if initial_value <= 0.0 {
+            return Ok(None);
+        }
+
+        // Days every holding traded, oldest first
+        let mut dates: Vec<NaiveDate> = series[0]
+            .keys()
+            .filter(|d| series.iter().all(|s| s.contains_key(d)))
+            .copied()
+            .collect();
+        dates.sort();
+
+        let returns: Vec<f64> = dates
+            .windows(2)
+            .filter_map(|pair| {
+                let simple: f64 = series
+                    .iter()
+                    .zip(&weights)
+                    .map(|(closes, weight)| {
+                        let (prev, curr) = (closes[&pair[0]], closes[&pair[1]]);
+                        if prev > 0.0 {
+                            weight / initial_value * (curr / prev - 1.0)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum();
+                (simple > -1.0).then(|| (1.0 + simple).ln())
+            })
+            .collect();
+
+        if returns.len() < MIN_PROJECTION_OBSERVATIONS {
+            return Ok(None);
+        }
+
+        let (drift, volatility) = projection::drift_and_volatility(&returns);
+        let observations = returns.len();
+        let result = tokio::task::spawn_blocking(move || {
+            projection::simulate(initial_value, &returns, method, horizon_days, simulations, seed)
+        })
+        .await?;
+
+        let money = |v: f64| Decimal::from_f64_retain(v).unwrap_or_default().round_dp(2);
+        let ratio = |v: f64| Decimal::from_f64_retain(v).unwrap_or_default().round_dp(4);
+        let bands = result
+            .bands
+            .into_iter()
+            .map(|band| {
+                let [p5, p25, p50, p75, p95] = band.values.map(money);
+                ProjectionBand { day: band.day, p5, p25, p50, p75, p95 }
+            })
+            .collect();
+
+        Ok(Some(PortfolioProjection {
+            method: method.as_str().to_string(),
+            horizon_days,
+            simulations,
+            initial_value: money(initial_value),
+            observations,
+            annualized_return: ratio((drift * TRADING_DAYS_PER_YEAR).exp_m1()),
+            annualized_volatility: ratio(volatility * TRADING_DAYS_PER_YEAR.sqrt()),
+            expected_value: money(result.mean_final),
+            probability_of_loss: ratio(result.probability_of_loss),
+            bands,
+        }))
+    }
+
+    /// Forecast `horizon` trading days of `symbol` from its stored daily closes, fitted off the
+    /// async runtime and cached until new candles are stored. `None` when fewer than
+    /// [`MIN_FORECAST_OBSERVATIONS`] closes are stored.
+    pub async fn get_forecast(&self, symbol: &str, method: ForecastMethod, horizon: u32) -> Result<Option<Forecast>> {
+        let cache_key = format!("{}:{}:{}", symbol, method.as_str(), horizon);
+        if let Some(cached) = self.forecast_cache.get(&cache_key) {
+            debug!("Using cached forecast for {}", symbol);
+            return Ok(cached);
+        }
+
+        let mut candles = self
+            .db
+            .get_historical_prices(symbol, None, None, Some(FORECAST_LOOKBACK_BARS))
+            .await?;
+        candles.retain(|candle| candle.close > Decimal::ZERO);
+        candles.reverse();
+        let result = match candles.last() {
+            Some(last) if candles.len() >= MIN_FORECAST_OBSERVATIONS => {
+                let (last_date, last_close) = (last.timestamp.date_naive(), last.close);
+                let closes: Vec<f64> = candles.iter().filter_map(|c| c.close.to_f64()).collect();
+                let fitted = tokio::task::spawn_blocking(move || forecast::fit(method, &closes, horizon as usize))
+                    .await?
+                    .ok_or_else(|| anyhow!("Could not fit a forecast for {}", symbol))?;
+
+                let price = |v: f64| Decimal::from_f64_retain(v).unwrap_or_default().round_dp(4);
+                let dates = forecast::future_dates(symbol, last_date, horizon as usize);
+                let points = dates
+                    .into_iter()
+                    .zip(fitted.steps())
+                    .map(|(date, step)| ForecastPoint {
+                        date,
+                        forecast: price(step.median),
+                        lower_80: price(step.lower_80),
+                        upper_80: price(step.upper_80),
+                        lower_95: price(step.lower_95),
+                        upper_95: price(step.upper_95),
+                    })
+                    .collect();
+                Some(Forecast {
+                    symbol: symbol.to_string(),
+                    method: method.as_str().to_string(),
+                    horizon,
+                    observations: candles.len(),
+                    last_date,
+                    last_close,
+                    parameters: fitted
+                        .parameters
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), (value * 1e6).round() / 1e6))
+                        .collect(),
+                    residual_sd_percent: price(fitted.sigma.exp_m1() * 100.0),
+                    points,
+                    generated_at: Utc::now(),
+                })
+            }
+            _ => None,
+        };
+
+        self.forecast_cache
+            .insert(cache_key, result.clone(), self.cache_ttls.historical_ttl("1d"));
+        Ok(result)
+    }
+
+    /// Beta and R² of `symbol` against `benchmark` over the past year of daily closes. Both
+    /// series go through [`Self::get_historical_data`], so a benchmark shared by many symbols
+    /// is fetched and cached once. With `base_currency` both are converted first, so a
+    /// foreign listing is measured as a holder in that currency sees it. Returns `None`
+    /// when they share too few trading days.
+    pub async fn get_beta(
+        &self,
+        symbol: &str,
+        benchmark: &str,
+        base_currency: Option<&str>,
+    ) -> Result<Option<BetaEstimate>> {
+        // Start at midnight so the cache key holds for the whole day
+        let today = Utc::now().date_naive();
+        let start = (today - chrono::Duration::days(365)).and_time(chrono::NaiveTime::MIN).and_utc();
+
+        let mut closes = Vec::with_capacity(2);
+        for ticker in [symbol, benchmark] {
+            let mut history = self
+                .get_historical_data(ticker, Some(start), None, Some("1d"), None)
+                .await?;
+            if let Some(base_currency) = base_currency {
+                history = self.convert_candles(ticker, history, base_currency).await?;
+            }
+            let by_date: HashMap<NaiveDate, f64> = history
+                .iter()
+                .filter_map(|p| Some((p.timestamp.date_naive(), p.close.to_f64()?)))
+                .collect();
+            closes.push(by_date);
+        }
+
+        // Days both traded, oldest first
+        let mut dates: Vec<NaiveDate> = closes[0]
+            .keys()
+            .filter(|d| closes[1].contains_key(d))
+            .copied()
+            .collect();
+        dates.sort();
+
+        let series = |i: usize| {
+            let aligned: Vec<f64> = dates.iter().map(|d| closes[i][d]).collect();
+            risk::daily_returns(&aligned)
+        };
+        let (asset_returns, benchmark_returns) = (series(0), series(1));
+        if asset_returns.len() < MIN_BETA_OBSERVATIONS || asset_returns.len() != benchmark_returns.len() {
+            return Ok(None);
+        }
+
+        let ratio = |v: f64| Decimal::from_f64_retain(v).unwrap_or_default().round_dp(4);
+        Ok(risk::beta(&asset_returns, &benchmark_returns).map(|(beta, r_squared)| BetaEstimate {
+            benchmark: benchmark.to_string(),
+            beta: ratio(beta),
+            r_squared: ratio(r_squared),
+            observations: asset_returns.len(),
+        }))
+    }
+
+    /// Top institutional and fund holders, fetched at most once a day per symbol
+    pub async fn get_holders(&self, symbol: &str) -> Result<Option<SymbolHolders>> {
+        if let Some(cached) = self.holders_cache.get(symbol) {
+            debug!("Using cached holders for {}", symbol);
+            return Ok(cached);
+        }
+
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Profiles).await?;
+        info!("Fetching holders for {}", symbol);
+        let holders = self.provider.ownership(symbol).await?.map(|ownership| SymbolHolders {
+            symbol: symbol.to_string(),
+            breakdown: ownership.breakdown,
+            institutions: ownership.institutions,
+            funds: ownership.funds,
+            fetched_at: Utc::now(),
+        });
+
+        // Filings change quarterly, so a day is fresh enough; misses are retried sooner
+        let ttl = if holders.is_some() {
+            Duration::from_secs(24 * 3600)
+        } else {
+            Duration::from_secs(3600)
+        };
+        self.holders_cache.insert(symbol.to_string(), holders.clone(), ttl);
+        Ok(holders)
+    }
+
+    /// Top holdings and sector weights of an ETF, fetched at most once a day per symbol.
+    /// `None` for symbols that are not funds.
+    pub async fn get_etf_composition(&self, symbol: &str) -> Result<Option<EtfComposition>> {
+        if let Some(cached) = self.etf_cache.get(symbol) {
+            debug!("Using cached ETF composition for {}", symbol);
+            return Ok(cached);
+        }
+
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Profiles).await?;
+        info!("Fetching ETF composition for {}", symbol);
+        let composition = self
+            .provider
+            .fund_composition(symbol)
+            .await?
+            .map(|fund| EtfComposition {
+                symbol: symbol.to_string(),
+                holdings: fund.holdings,
+                sector_weights: fund.sector_weights,
+                fetched_at: Utc::now(),
+            });
+
+        // A symbol does not turn into a fund, so negative answers keep for a day as well
+        self.etf_cache
+            .insert(symbol.to_string(), composition.clone(), Duration::from_secs(24 * 3600));
+        Ok(composition)
+    }
+
+    /// Units of `to` per unit of `from` (major ISO codes) from the provider's currency pair
+    /// quote, cached for ten minutes. `None` when the provider has no quote for the pair.
+    pub async fn fx_rate(&self, from: &str, to: &str) -> Result<Option<Decimal>> {
+        if from == to {
+            return Ok(Some(Decimal::ONE));
+        }
+        let pair = fx::pair_symbol(from, to);
+        if let Some(rate) = self.fx_cache.get(&pair) {
+            return Ok(Some(rate));
+        }
+
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Quotes).await?;
+        let rate = self
+            .provider
+            .latest_quote(&pair)
+            .await?
+            .and_then(|latest| Decimal::from_f64_retain(latest.quote.close))
+            .filter(|rate| *rate > Decimal::ZERO)
+            .map(|rate| rate.round_dp(8));
+        if let Some(rate) = rate {
+            self.fx_cache.insert(pair, rate, Duration::from_secs(600));
+        }
+        Ok(rate)
+    }
+
+    /// Daily `to` per `from` rates from `start` (less a week, so early candles can use the
+    /// close before a weekend or holiday) up to today. Stored rates are used when they
+    /// cover the range and are recent; otherwise the pair's daily candles are fetched from
+    /// the provider and stored.
+    async fn daily_fx_rates(&self, from: &str, to: &str, start: NaiveDate) -> Result<BTreeMap<NaiveDate, Decimal>> {
+        let pair = format!("{}{}", from, to);
+        let lookback = start - chrono::Duration::days(7);
+        let today = Utc::now().date_naive();
+        let mut rates: BTreeMap<NaiveDate, Decimal> =
+            self.db.get_fx_rates(&pair, lookback, today).await?.into_iter().collect();
+
+        let covers_start = rates.keys().next().is_some_and(|first| *first <= start);
+        let last = rates.keys().next_back().copied();
+        if covers_start && last.is_some_and(|last| (today - last).num_days() <= FX_RATE_MAX_AGE_DAYS) {
+            return Ok(rates);
+        }
+
+        let fetch_from = match last {
+            Some(last) if covers_start => last,
+            _ => lookback,
+        };
+        // A read-only instance converts at the stored rates, however old
+        match self.check_storing_fetch(UpstreamFeature::Historical).await {
+            Err(YahooServiceError::ReadOnly) if !rates.is_empty() => return Ok(rates),
+            result => result?,
+        }
+        debug!("Fetching {} daily rates since {}", pair, fetch_from);
+        let history = self
+            .provider
+            .price_history_since(
+                &fx::pair_symbol(from, to),
+                "1d",
+                fetch_from.and_time(chrono::NaiveTime::MIN).and_utc(),
+            )
+            .await?;
+        let fetched: Vec<(NaiveDate, Decimal)> = history
+            .quotes
+            .iter()
+            .filter_map(|q| {
+                let date = DateTime::from_timestamp(q.timestamp as i64, 0)?.date_naive();
+                let rate = Decimal::from_f64_retain(q.close)?.round_dp(8);
+                (rate > Decimal::ZERO).then_some((date, rate))
+            })
+            .collect();
+        if !fetched.is_empty() {
+            self.db.upsert_fx_rates(&pair, fetched.clone()).await?;
+            rates.extend(fetched);
+        }
+        Ok(rates)
+    }
+
+    /// Where observations of `series` come from on this instance: `mock`, `fred` or
+    /// `provider`. `None` when nothing serves it, as for CPI without a FRED key.
+    pub fn macro_source(&self, series: MacroSeries) -> Option<&'static str> {
+        if self.mock_macro {
+            Some("mock")
+        } else if self.fred_api_key.is_some() {
+            Some("fred")
+        } else {
+            series.provider_symbol().map(|_| "provider")
+        }
+    }
+
+    /// Observations of `series` between `from` and `to` inclusive, oldest first. Stored
+    /// observations are topped up from the series' source first; when that fails, what is
+    /// stored is served, if anything.
+    pub async fn macro_observations(&self, series: MacroSeries, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, Decimal)>> {
+        let refreshed = self.refresh_macro_series(series).await;
+        let stored = self.db.get_macro_observations(series.as_str(), from, to).await?;
+        if let Err(e) = refreshed {
+            if stored.is_empty() {
+                return Err(e);
+            }
+            warn!("Could not refresh macro series {}: {}; serving stored observations", series.as_str(), e);
+        }
+        Ok(stored)
+    }
+
+    /// Fetch and store observations of `series` since the latest stored one (or
+    /// `MACRO_HISTORY_DAYS` back), at most once per `MACRO_REFRESH_INTERVAL`. Returns the
+    /// latest observation.
+    async fn refresh_macro_series(&self, series: MacroSeries) -> Result<Option<(NaiveDate, Decimal)>> {
+        let mut refreshed = self.macro_refreshed.lock().await;
+        let latest = self.db.get_latest_macro_observation(series.as_str()).await?;
+        if refreshed
+            .get(series.as_str())
+            .is_some_and(|at| at.elapsed() < MACRO_REFRESH_INTERVAL)
+        {
+            return Ok(latest);
+        }
+        if self.read_only {
+            return match latest {
+                Some(latest) => Ok(Some(latest)),
+                None => Err(YahooServiceError::ReadOnly.into()),
+            };
+        }
+
+        let today = Utc::now().date_naive();
+        let start = latest.map_or(today - chrono::Duration::days(MACRO_HISTORY_DAYS), |(date, _)| date);
+        debug!("Fetching macro series {} since {}", series.as_str(), start);
+        let fetched = if self.mock_macro {
+            macro_series::mock_observations(series, start, today)
+        } else if let Some(api_key) = &self.fred_api_key {
+            macro_series::fred_observations(api_key, series, start).await?
+        } else if let Some(symbol) = series.provider_symbol() {
+            self.check_yahoo_api_rate_limit(UpstreamFeature::Historical).await?;
+            let history = self
+                .provider
+                .price_history_since(symbol, "1d", start.and_time(chrono::NaiveTime::MIN).and_utc())
+                .await?;
+            history
+                .quotes
+                .iter()
+                .filter_map(|q| {
+                    let date = DateTime::from_timestamp(q.timestamp as i64, 0)?.date_naive();
+                    Some((date, Decimal::from_f64_retain(q.close)?.round_dp(4)))
+                })
+                .collect()
+        } else {
+            return Err(anyhow!("Macro series {} needs FRED_API_KEY", series.as_str()));
+        };
+
+        let fetched_latest = fetched.iter().max_by_key(|(date, _)| *date).copied();
+        if !fetched.is_empty() {
+            self.db.upsert_macro_observations(series.as_str(), fetched).await?;
+        }
+        refreshed.insert(series.as_str(), Instant::now());
+        Ok(fetched_latest.or(latest))
+    }
+
+    /// Annual risk-free rate as a fraction from the latest 13-week T-bill yield, `None` when
+    /// no yield is stored or can be fetched
+    pub async fn tbill_rate(&self) -> Option<f64> {
+        let latest = match self.refresh_macro_series(MacroSeries::TBill13Week).await {
+            Ok(latest) => latest,
+            Err(e) => {
+                warn!("Could not refresh the T-bill yield: {}", e);
+                self.db
+                    .get_latest_macro_observation(MacroSeries::TBill13Week.as_str())
+                    .await
+                    .ok()
+                    .flatten()
+            }
+        };
+        latest.and_then(|(_, percent)| percent.to_f64()).map(|percent| percent / 100.0)
+    }
+
+    /// Candles of `symbol` converted into `base_currency` at the rate of each candle's date,
+    /// or the latest earlier one. Prices quoted in a minor unit such as pence are brought to
+    /// the major unit first; candles already in `base_currency` are returned unchanged.
+    pub async fn convert_candles(
+        &self,
+        symbol: &str,
+        prices: Vec<HistoricalPrice>,
+        base_currency: &str,
+    ) -> Result<Vec<HistoricalPrice>> {
+        let (currency, units) = fx::major_unit(&self.symbol_currency(symbol).await);
+        let Some(start) = prices.iter().map(|p| p.timestamp.date_naive()).min() else {
+            return Ok(prices);
+        };
+        if currency == base_currency && units == Decimal::ONE {
+            return Ok(prices);
+        }
+
+        let rates = if currency == base_currency {
+            BTreeMap::from([(start, Decimal::ONE)])
+        } else {
+            self.daily_fx_rates(&currency, base_currency, start).await?
+        };
+        let Some(first_rate) = rates.values().next().copied() else {
+            return Err(anyhow!("No {}/{} exchange rates available", currency, base_currency));
+        };
+
+        Ok(prices
+            .into_iter()
+            .map(|mut price| {
+                let rate = rates
+                    .range(..=price.timestamp.date_naive())
+                    .next_back()
+                    .map_or(first_rate, |(_, rate)| *rate);
+                let factor = rate / units;
+                price.open = (price.open * factor).round_dp(6);
+                price.high = (price.high * factor).round_dp(6);
+                price.low = (price.low * factor).round_dp(6);
+                price.close = (price.close * factor).round_dp(6);
+                price.adjusted_close = price.adjusted_close.map(|c| (c * factor).round_dp(6));
+                price
+            })
+            .collect())
+    }
+
+    /// Currency a symbol is quoted in as last reported by the provider, or implied by its
+    /// exchange suffix before the first fetch
+    pub async fn symbol_currency(&self, symbol: &str) -> String {
+        match self.db.get_symbol(symbol).await {
+            Ok(stored) => stored.and_then(|s| s.currency),
+            Err(e) => {
+                warn!("Failed to load currency of {}: {}", symbol, e);
+                None
+            }
+        }
+        .unwrap_or_else(|| fx::currency_for_symbol(symbol).to_string())
+    }
+
+    /// Quotes for every exchange listing of the company behind `symbol`, converted to
+    /// `currency` (the requested listing's own currency by default). Cached for a minute;
+    /// `None` when the symbol itself has no quote.
+    pub async fn get_listings(&self, symbol: &str, currency: Option<&str>) -> Result<Option<SymbolListings>> {
+        let cache_key = format!("{}:{}", symbol, currency.unwrap_or_default());
+        if let Some(cached) = self.listings_cache.get(&cache_key) {
+            debug!("Using cached listings for {}", symbol);
+            return Ok(cached);
+        }
+
+        let Some(requested) = self.get_latest_quote(symbol).await? else {
+            return Ok(None);
+        };
+        let mut listings: Vec<ListingQuote> = Vec::new();
+        for listing in self.find_listings(symbol).await? {
+            let quote = if listing.symbol == symbol {
+                requested.clone()
+            } else {
+                match self.get_latest_quote(&listing.symbol).await {
+                    Ok(Some(quote)) => quote,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!("Failed to quote listing {} of {}: {}", listing.symbol, symbol, e);
+                        continue;
+                    }
+                }
+            };
+            let (listing_currency, units) = fx::major_unit(&self.symbol_currency(&listing.symbol).await);
+            listings.push(ListingQuote {
+                symbol: listing.symbol,
+                name: listing.name,
+                exchange: Some(listing.exchange).filter(|e| !e.is_empty()),
+                currency: listing_currency,
+                price: (quote.price / units).round_dp(6),
+                change_percent: quote.change_percent,
+                market_time: quote.market_time,
+                fx_rate: None,
+                converted_price: None,
+                premium_percent: None,
+            });
+        }
+
+        let target = currency
+            .map(str::to_string)
+            .unwrap_or_else(|| listings[0].currency.clone());
+        for listing in &mut listings {
+            match self.fx_rate(&listing.currency, &target).await {
+                Ok(Some(rate)) => {
+                    listing.fx_rate = Some(rate);
+                    listing.converted_price = Some((listing.price * rate).round_dp(6));
+                }
+                Ok(None) => warn!("No {}/{} rate for {}", listing.currency, target, listing.symbol),
+                Err(e) => warn!("Failed to convert {} to {}: {}", listing.symbol, target, e),
+            }
+        }
+        if let Some(base) = listings[0].converted_price.filter(|p| !p.is_zero()) {
+            for listing in &mut listings {
+                listing.premium_percent = listing
+                    .converted_price
+                    .map(|p| ((p / base - Decimal::ONE) * Decimal::ONE_HUNDRED).round_dp(2));
+            }
+        }
+
+        let result = Some(SymbolListings {
+            symbol: symbol.to_string(),
+            currency: target,
+            listings,
+            fetched_at: Utc::now(),
+        });
+        self.listings_cache.insert(cache_key, result.clone(), Duration::from_secs(60));
+        Ok(result)
+    }
+
+    /// The requested listing followed by other listings of the same company: search hits for
+    /// the base ticker and the company name whose normalized names match, or which share the
+    /// base ticker when a name is missing
+    async fn find_listings(&self, symbol: &str) -> Result<Vec<SymbolMatch>> {
+        let base = symbol.split('.').next().unwrap_or(symbol);
+        self.check_yahoo_api_rate_limit(UpstreamFeature::Other).await?;
+        let mut matches = self.provider.search(base).await?;
+
+        let stored = self.db.get_symbol(symbol).await?;
+        let own_match = matches.iter().find(|m| m.symbol == symbol);
+        let name = own_match
+            .and_then(|m| m.name.clone())
+            .or_else(|| stored.as_ref().and_then(|s| s.name.clone()));
+        let exchange = own_match
+            .map(|m| m.exchange.clone())
+            .filter(|e| !e.is_empty())
+            .or_else(|| stored.and_then(|s| s.exchange))
+            .unwrap_or_default();
+        if let Some(name) = &name {
+            self.check_yahoo_api_rate_limit(UpstreamFeature::Other).await?;
+            match self.provider.search(name).await {
+                Ok(by_name) => matches.extend(by_name),
+                Err(e) => warn!("Listing search by name failed for {}: {}", symbol, e),
+            }
+        }
+
+        let company = name.as_deref().map(company_key);
+        let mut listings = vec![SymbolMatch {
+            symbol: symbol.to_string(),
+            name,
+            exchange,
+        }];
+        for candidate in matches {
+            if listings.len() >= MAX_LISTINGS {
+                break;
+            }
+            let candidate_symbol = candidate.symbol.to_uppercase();
+            if listings.iter().any(|l| l.symbol == candidate_symbol)
+                || crate::validation::validate_symbol(&candidate_symbol).is_err()
+            {
+                continue;
+            }
+            let same_company = match (&company, candidate.name.as_deref()) {
+                (Some(company), Some(name)) => company_key(name) == *company,
+                _ => candidate_symbol.split('.').next() == Some(base),
+            };
+            if same_company {
+                listings.push(SymbolMatch {
+                    symbol: candidate_symbol,
+                    ..candidate
+                });
+            }
+        }
+        Ok(listings)
+    }
+
+    /// Portfolio exposure by underlying symbol and sector, looking through ETF holdings
+    pub async fn get_portfolio_exposure(&self) -> Result<PortfolioExposure> {
+        let holdings = self.db.get_all_portfolio_holdings().await?;
+
+        let mut symbols: HashMap<String, (Decimal, Vec<String>)> = HashMap::new();
+        let mut sectors: HashMap<String, Decimal> = HashMap::new();
+        let mut total_value = Decimal::ZERO;
+        let mut unattributed_value = Decimal::ZERO;
+        let mut funds = Vec::new();
+
+        for holding in holdings {
+            let value = holding.current_price.unwrap_or(holding.purchase_price) * holding.quantity;
+            total_value += value;
+
+            let composition = match self.get_etf_composition(&holding.symbol).await {
+                Ok(composition) => composition,
+                Err(e) => {
+                    warn!("Could not look through {}: {}", holding.symbol, e);
+                    None
+                }
+            };
+            let Some(composition) = composition else {
+                let sector = self
+                    .db
+                    .get_symbol(&holding.symbol)
+                    .await?
+                    .and_then(|s| s.sector)
+                    .unwrap_or_else(|| UNCLASSIFIED_SECTOR.to_string());
+                *sectors.entry(sector).or_default() += value;
+                let entry = symbols.entry(holding.symbol.clone()).or_default();
+                entry.0 += value;
+                entry.1.push(holding.symbol);
+                continue;
+            };
+
+            let share = |percent: Decimal| value * percent / Decimal::ONE_HUNDRED;
+            let mut attributed = Decimal::ZERO;
+            for underlying in &composition.holdings {
+                let Some(symbol) = &underlying.symbol else { continue };
+                let part = share(underlying.weight_percent);
+                attributed += part;
+                let entry = symbols.entry(symbol.clone()).or_default();
+                entry.0 += part;
+                entry.1.push(holding.symbol.clone());
+            }
+            unattributed_value += value - attributed;
+
+            let mut classified = Decimal::ZERO;
+            for weight in &composition.sector_weights {
+                let part = share(weight.weight_percent);
+                classified += part;
+                *sectors.entry(weight.sector.clone()).or_default() += part;
+            }
+            if value > classified {
+                *sectors.entry(UNCLASSIFIED_SECTOR.to_string()).or_default() += value - classified;
+            }
+            funds.push(holding.symbol);
+        }
+
+        let weight = |value: Decimal| {
+            if total_value > Decimal::ZERO {
+                (value / total_value * Decimal::ONE_HUNDRED).round_dp(2)
+            } else {
+                Decimal::ZERO
+            }
+        };
+        let mut symbols: Vec<SymbolExposure> = symbols
+            .into_iter()
+            .map(|(symbol, (value, mut via))| {
+                via.sort();
+                via.dedup();
+                SymbolExposure {
+                    symbol,
+                    value: value.round_dp(2),
+                    weight_percent: weight(value),
+                    via,
+                }
+            })
+            .collect();
+        symbols.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.symbol.cmp(&b.symbol)));
+        let mut sectors: Vec<SectorExposure> = sectors
+            .into_iter()
+            .map(|(sector, value)| SectorExposure {
+                sector,
+                value: value.round_dp(2),
+                weight_percent: weight(value),
+            })
+            .collect();
+        sectors.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.sector.cmp(&b.sector)));
+        funds.sort();
+        funds.dedup();
+
+        Ok(PortfolioExposure {
+            total_value: total_value.round_dp(2),
+            symbols,
+            sectors,
+            unattributed_value: unattributed_value.round_dp(2),
+            funds,
+        })
+    }
+
+    /// Cash balance from the deposits and withdrawals, less what buys and directly entered
+    /// holdings cost, plus sale proceeds and dividends on the ledger's shares
+    pub async fn get_cash_summary(&self) -> Result<CashSummary> {
+        let flows = self.db.get_cash_flows().await?;
+        if flows.is_empty() {
+            return Ok(CashSummary::default());
+        }
+        let transactions = self.db.get_portfolio_transactions(None).await?;
+        let holdings = self.db.get_all_portfolio_holdings().await?;
+
+        // Ledger entries are in trade order, so the first one per symbol is its first buy
+        let mut first_trades: HashMap<&str, NaiveDate> = HashMap::new();
+        for tx in &transactions {
+            first_trades.entry(tx.symbol.as_str()).or_insert(tx.trade_date);
+        }
+        let mut dividends = Vec::new();
+        for (symbol, since) in first_trades {
+            dividends.extend(self.db.get_dividends(symbol, since).await?);
+        }
+
+        Ok(CashSummary::from_ledger(
+            flows,
+            &transactions,
+            &holdings,
+            &dividends,
+            Utc::now().date_naive(),
+        ))
+    }
+
+    /// Totals, holding weights and allocation by asset type and sector, valued at the latest
+    /// quotes (fetched `max_concurrent` at a time). Cash counts as its own asset type and
+    /// sector so each breakdown adds up to the whole portfolio.
+    pub async fn get_portfolio_allocation(&self, max_concurrent: usize) -> Result<PortfolioAllocationSummary> {
+        let holdings = self.db.get_all_portfolio_holdings().await?;
+        let cash = self.get_cash_summary().await?;
+
+        let mut symbols: Vec<String> = holdings.iter().map(|h| h.symbol.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+        let quotes = self.get_latest_quotes(&symbols, max_concurrent).await;
+
+        let mut allocations = Vec::with_capacity(holdings.len());
+        let mut total_cost = Decimal::ZERO;
+        let mut day_change = Decimal::ZERO;
+        for holding in &holdings {
+            let quote = quotes.get(&holding.symbol);
+            let cost = holding.purchase_price * holding.quantity;
+            let value = match quote {
+                Some(quote) => quote.price * holding.quantity,
+                None => holding.current_value.unwrap_or(cost),
+            };
+            let gain_loss = value - cost;
+            let holding_day_change = quote.and_then(|q| q.change).map(|change| change * holding.quantity);
+            total_cost += cost;
+            day_change += holding_day_change.unwrap_or_default();
+
+            let symbol = self.db.get_symbol(&holding.symbol).await?;
+            allocations.push(HoldingAllocation {
+                holding_id: holding.id,
+                symbol: holding.symbol.clone(),
+                name: symbol.as_ref().and_then(|s| s.name.clone()),
+                asset_type: holding.asset_type.clone(),
+                sector: symbol
+                    .and_then(|s| s.sector)
+                    .unwrap_or_else(|| UNCLASSIFIED_SECTOR.to_string()),
+                value,
+                weight_percent: Decimal::ZERO,
+                gain_loss,
+                gain_loss_percent: if cost > Decimal::ZERO {
+                    (gain_loss / cost * Decimal::ONE_HUNDRED).round_dp(2)
+                } else {
+                    Decimal::ZERO
+                },
+                day_change: holding_day_change.map(|change| change.round_dp(2)),
+                day_change_percent: quote.and_then(|q| q.change_percent).map(|p| p.round_dp(2)),
+            });
+        }
+
+        let holdings_value: Decimal = allocations.iter().map(|a| a.value).sum();
+        let total_value = holdings_value + cash.balance;
+        let weight = |value: Decimal| {
+            if total_value > Decimal::ZERO {
+                (value / total_value * Decimal::ONE_HUNDRED).round_dp(2)
+            } else {
+                Decimal::ZERO
+            }
+        };
+
+        let mut by_asset_type: HashMap<String, Decimal> = HashMap::new();
+        let mut by_sector: HashMap<String, Decimal> = HashMap::new();
+        for allocation in &mut allocations {
+            *by_asset_type.entry(allocation.asset_type.clone()).or_default() += allocation.value;
+            *by_sector.entry(allocation.sector.clone()).or_default() += allocation.value;
+            allocation.weight_percent = weight(allocation.value);
+            allocation.value = allocation.value.round_dp(2);
+            allocation.gain_loss = allocation.gain_loss.round_dp(2);
+        }
+        if cash.balance > Decimal::ZERO {
+            *by_asset_type.entry("cash".to_string()).or_default() += cash.balance;
+            *by_sector.entry("Cash".to_string()).or_default() += cash.balance;
+        }
+        let slices = |totals: HashMap<String, Decimal>| {
+            let mut slices: Vec<AllocationSlice> = totals
+                .into_iter()
+                .map(|(name, value)| AllocationSlice {
+                    name,
+                    value: value.round_dp(2),
+                    weight_percent: weight(value),
+                })
+                .collect();
+            slices.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+            slices
+        };
+        allocations.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.symbol.cmp(&b.symbol)));
+
+        let top_gainer = allocations
+            .iter()
+            .filter(|a| a.day_change_percent.is_some_and(|p| p > Decimal::ZERO))
+            .max_by_key(|a| a.day_change_percent)
+            .cloned();
+        let top_loser = allocations
+            .iter()
+            .filter(|a| a.day_change_percent.is_some_and(|p| p < Decimal::ZERO))
+            .min_by_key(|a| a.day_change_percent)
+            .cloned();
+
+        let total_gain_loss = holdings_value - total_cost;
+        let previous_value = holdings_value - day_change;
+        Ok(PortfolioAllocationSummary {
+            total_holdings: allocations.len(),
+            total_cost: total_cost.round_dp(2),
+            holdings_value: holdings_value.round_dp(2),
+            cash_balance: cash.balance,
+            total_value: total_value.round_dp(2),
+            total_gain_loss: total_gain_loss.round_dp(2),
+            total_gain_loss_percent: if total_cost > Decimal::ZERO {
+                (total_gain_loss / total_cost * Decimal::ONE_HUNDRED).round_dp(2)
+            } else {
+                Decimal::ZERO
+            },
+            day_change: day_change.round_dp(2),
+            day_change_percent: (previous_value > Decimal::ZERO)
+                .then(|| (day_change / previous_value * Decimal::ONE_HUNDRED).round_dp(2)),
+            holdings: allocations,
+            by_asset_type: slices(by_asset_type),
+            by_sector: slices(by_sector),
+            top_gainer,
+            top_loser,
+            last_updated: self.db.get_portfolio_last_refresh().await?,
+        })
+    }
+
+    /// Get database statistics
+    pub async fn get_stats(&self) -> Result<serde_json::Value> {
+        let stats = self.db.get_database_stats().await?;
+        let caches: serde_json::Map<String, serde_json::Value> = self
+            .cache_stats()
+            .into_iter()
+            .map(|(name, stats)| Ok((name.to_string(), serde_json::to_value(stats)?)))
+            .collect::<Result<_>>()?;
+        let (yahoo_calls, yahoo_rate_limited) = self.yahoo_call_counts();
+        Ok(serde_json::json!({
+            "database": stats,
+            "cache": caches,
+            "rate_limits": {
+                "api_requests_per_minute": self.config.requests_per_minute,
+                "yahoo_api_requests_per_minute": self.config.yahoo_api_requests_per_minute,
+                "yahoo_api_max_wait_ms": self.config.yahoo_api_max_wait.as_millis() as u64,
+                "bulk_yahoo_requests_per_minute": self.config.bulk_yahoo_requests_per_minute,
+                "yahoo_calls": yahoo_calls,
+                "yahoo_rate_limited": yahoo_rate_limited,
+            },
+            "quota": self.quota.usage(),
+        }))
+    }
+
+    /// Current figures and cumulative counters of every in-memory cache, by name
+    pub fn cache_stats(&self) -> Vec<(&'static str, CacheStats)> {
+        vec![
+            ("historical", self.historical_cache.stats()),
+            ("quotes", self.quote_cache.stats()),
+            ("profiles", self.profile_cache.stats()),
+            ("suggestions", self.suggest_cache.stats()),
+            ("holders", self.holders_cache.stats()),
+            ("etf", self.etf_cache.stats()),
+            ("resolutions", self.resolve_cache.stats()),
+            ("fx", self.fx_cache.stats()),
+            ("listings", self.listings_cache.stats()),
+            ("forecasts", self.forecast_cache.stats()),
+        ]
+    }
+
+    /// Provider requests made and refused by the Yahoo rate limit since startup
+    pub fn yahoo_call_counts(&self) -> (u64, u64) {
+        (
+            self.yahoo_calls_total.load(Ordering::Relaxed),
+            self.yahoo_rate_limited_total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Clear expired cache entries
+    pub fn cleanup_cache(&self) {
+        self.historical_cache.run_pending_tasks();
+        self.quote_cache.run_pending_tasks();
+        self.profile_cache.run_pending_tasks();
+        self.suggest_cache.run_pending_tasks();
+        self.holders_cache.run_pending_tasks();
+        self.etf_cache.run_pending_tasks();
+        self.resolve_cache.run_pending_tasks();
+        self.fx_cache.run_pending_tasks();
+        self.listings_cache.run_pending_tasks();
+        self.forecast_cache.run_pending_tasks();
+    }
+
+    /// Drop every cached entry here and on other instances, e.g. after stored data was deleted
+    pub fn clear_caches(&self) {
+        self.invalidate_caches(CacheScope::All);
+    }
+
+    /// Drop cached entries in `scope` here and on other instances
+    pub fn invalidate_caches(&self, scope: CacheScope) {
+        let event = CacheEvent::CacheInvalidate { scope };
+        self.apply_cache_event(&event);
+        self.cache_bus.publish(event);
+    }
+
+    /// Tell other instances that fresh `kind` data of `symbol` was stored
+    fn publish_data_updated(&self, symbol: &str, kind: DataKind) {
+        self.cache_bus.publish(CacheEvent::DataUpdated {
+            symbol: symbol.to_string(),
+            kind,
+        });
+    }
+
+    /// Apply a cache event to this instance's caches only
+    pub fn apply_cache_event(&self, event: &CacheEvent) {
+        match event {
+            CacheEvent::CacheInvalidate { scope: CacheScope::Expired } => self.cleanup_cache(),
+            CacheEvent::CacheInvalidate { scope: CacheScope::Resolutions } => self.resolve_cache.clear(),
+            CacheEvent::CacheInvalidate { scope: CacheScope::All } => {
+                self.historical_cache.clear();
+                self.quote_cache.clear();
+                self.profile_cache.clear();
+                self.suggest_cache.clear();
+                self.holders_cache.clear();
+                self.etf_cache.clear();
+                self.resolve_cache.clear();
+                self.fx_cache.clear();
+                self.listings_cache.clear();
+                self.forecast_cache.clear();
+            }
+            CacheEvent::DataUpdated { symbol, kind } => match kind {
+                DataKind::Quote => {
+                    self.quote_cache.remove(symbol);
+                    self.listings_cache.remove_prefix(&format!("{symbol}:"));
+                }
+                DataKind::Historical => {
+                    self.historical_cache.remove_prefix(&format!("{symbol}:"));
+                    self.forecast_cache.remove_prefix(&format!("{symbol}:"));
+                }
+                DataKind::Profile => self.profile_cache.remove(symbol),
+            },
+            CacheEvent::SymbolDeleted { symbol } => self.forget_symbol(symbol),
+            CacheEvent::SymbolRenamed { from, to } => {
+                self.forget_symbol(from);
+                self.forget_symbol(to);
+            }
+        }
+    }
+
+    /// Drop every cached entry of a symbol whose stored data went away or changed hands
+    fn forget_symbol(&self, symbol: &str) {
+        self.quote_cache.remove(symbol);
+        self.listings_cache.remove_prefix(&format!("{symbol}:"));
+        self.historical_cache.remove_prefix(&format!("{symbol}:"));
+        self.forecast_cache.remove_prefix(&format!("{symbol}:"));
+        self.profile_cache.remove(symbol);
+        self.holders_cache.remove(symbol);
+        self.etf_cache.remove(symbol);
+        // Suggestions and resolutions may point at the symbol under other keys
+        self.suggest_cache.clear();
+        self.resolve_cache.clear();
+    }
+
+    /// Delete a stored symbol with its data and drop it from every instance's caches;
+    /// `None` if it is not stored
+    pub async fn delete_symbol(&self, symbol: &str) -> Result<Option<SymbolDeletion>> {
+        let deletion = self.db.delete_symbol(symbol).await?;
+        if deletion.is_some() {
+            let event = CacheEvent::SymbolDeleted {
+                symbol: symbol.to_string(),
+            };
+            self.apply_cache_event(&event);
+            self.cache_bus.publish(event);
+        }
+        Ok(deletion)
+    }
+
+    /// Move a stored symbol's data to a new ticker and refresh every instance's caches of
+    /// both; `None` if `from` is not stored
+    pub async fn rename_symbol(&self, from: &str, to: &str) -> Result<Option<SymbolRename>> {
+        let rename = self.db.rename_symbol(from, to).await?;
+        if rename.is_some() {
+            let event = CacheEvent::SymbolRenamed {
+                from: from.to_string(),
+                to: to.to_string(),
+            };
+            self.apply_cache_event(&event);
+            self.cache_bus.publish(event);
+        }
+        Ok(rename)
+    }
+
+    // Additional optimized methods...
+    pub async fn get_comprehensive_quote(&self, symbol: &str) -> Result<serde_json::Value> {
+        let overview = self.get_symbol_overview(symbol).await?;
 
         // Create comprehensive quote with OHLC data from latest historical record
         let latest_historical = overview.historical_data.first();
@@ -713,7 +3260,8 @@ impl YahooFinanceService {
                 "price": quote.price,
                 "volume": quote.volume,
                 "market_time": quote.market_time,
-                "trading_session": quote.trading_session
+                "trading_session": quote.trading_session,
+                "regular_price": quote.regular_price
             })
         } else {
             serde_json::json!({
@@ -818,6 +3366,20 @@ impl YahooFinanceService {
     }
 }
 
+/// Company name without legal-form words or punctuation, so "Rio Tinto plc" and
+/// "Rio Tinto Limited" compare equal
+fn company_key(name: &str) -> String {
+    const LEGAL_FORMS: [&str; 16] = [
+        "plc", "inc", "incorporated", "ltd", "limited", "corp", "corporation", "co", "company", "group",
+        "sa", "ag", "nv", "se", "spa", "the",
+    ];
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !LEGAL_FORMS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolOverview {
     pub symbol: String,
@@ -825,6 +3387,8 @@ pub struct SymbolOverview {
     pub historical_data: Vec<HistoricalPrice>,
     pub profile: Option<CompanyProfile>,
     pub avg_volume_30d: Option<i64>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub price_change_30d: Option<Decimal>,
+    #[serde(serialize_with = "crate::numeric::serialize_option")]
     pub price_change_30d_percent: Option<Decimal>,
 }